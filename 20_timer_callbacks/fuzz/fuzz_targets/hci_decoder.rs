@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Feed arbitrary bytes from the Bluetooth controller to the H4 decoder and parse whatever comes
+//! out, as the host side does. Also parse the input as a firmware patch file.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mingo_fuzz::bluetooth::hci;
+
+fuzz_target!(|data: &[u8]| {
+    let mut decoder = hci::Decoder::new();
+
+    for b in data {
+        if let Some(hci::Packet::Event { params, .. }) = decoder.push(*b) {
+            let _ = hci::BdAddr::parse(&params);
+            let _ = hci::LocalVersion::parse(&params);
+        }
+    }
+
+    let _ = hci::firmware_records(data);
+});
@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Parse arbitrary strings as the addresses that shell commands like `ping` and `arp` accept, and
+//! check that what is printed parses back to the same address.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mingo_fuzz::net::{Ipv4Address, MacAddress};
+
+extern crate alloc;
+use alloc::string::ToString;
+
+fuzz_target!(|data: &[u8]| {
+    let s = match core::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    if let Ok(addr) = s.parse::<Ipv4Address>() {
+        assert_eq!(addr.to_string().parse::<Ipv4Address>(), Ok(addr));
+    }
+    if let Ok(addr) = s.parse::<MacAddress>() {
+        assert_eq!(addr.to_string().parse::<MacAddress>(), Ok(addr));
+    }
+});
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Parse arbitrary bytes as a received Ethernet frame, down through the protocol layers the way
+//! the network stack does.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mingo_fuzz::net::{arp, ethernet, icmp, ipv4, udp};
+
+fuzz_target!(|data: &[u8]| {
+    let frame = match ethernet::Frame::parse(data) {
+        Some(frame) => frame,
+        None => return,
+    };
+
+    match frame.ethertype {
+        ethernet::ETHERTYPE_ARP => {
+            let _ = arp::Packet::parse(frame.payload);
+        }
+        ethernet::ETHERTYPE_IPV4 => {
+            let packet = match ipv4::Packet::parse(frame.payload) {
+                Some(packet) => packet,
+                None => return,
+            };
+
+            match packet.protocol {
+                ipv4::PROTOCOL_ICMP => {
+                    let _ = icmp::Message::parse(packet.payload);
+                }
+                ipv4::PROTOCOL_UDP => {
+                    let _ = udp::Datagram::parse(packet.src, packet.dst, packet.payload);
+                }
+                _ => (),
+            }
+        }
+        _ => (),
+    }
+});
@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Parse arbitrary strings as shell command lines and as scripts, and check that joined arguments
+//! split back into the same arguments.
+
+#![no_main]
+
+use core::time::Duration;
+use libfuzzer_sys::fuzz_target;
+use mingo_fuzz::shell::syntax;
+
+/// Stands in for the kernel's interval parser, which needs the job scheduler.
+fn interval(s: &str) -> Result<Duration, &'static str> {
+    s.parse()
+        .map(Duration::from_millis)
+        .map_err(|_| "Invalid interval")
+}
+
+fuzz_target!(|data: &[u8]| {
+    let s = match core::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    if let Ok(args) = syntax::tokenize(s) {
+        let line = syntax::join(&args);
+        assert_eq!(syntax::tokenize(&line), Ok(args));
+    }
+    let _ = syntax::script(s, interval);
+});
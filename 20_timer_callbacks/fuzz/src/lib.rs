@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Host-side fuzzing of the kernel's parsers.
+//!
+//! The parsers are compiled from the kernel's own source files, so a crash found here is a crash
+//! on the board. Only modules that depend on nothing but `core` and `alloc` can be included. Run a
+//! target with `make fuzz FUZZ_TARGET=<name>`.
+
+#![no_std]
+
+extern crate alloc;
+
+/// Bluetooth HCI packets.
+#[path = "../../kernel/src/bluetooth"]
+pub mod bluetooth {
+    pub mod hci;
+}
+
+/// Network protocols.
+#[path = "../../kernel/src/net"]
+pub mod net {
+    mod types;
+
+    pub mod arp;
+    pub mod ethernet;
+    pub mod icmp;
+    pub mod ipv4;
+    pub mod udp;
+
+    pub use types::*;
+}
+
+/// Shell command lines and scripts.
+#[path = "../../kernel/src/shell"]
+pub mod shell {
+    pub mod syntax;
+}
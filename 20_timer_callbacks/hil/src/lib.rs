@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Host side of the hardware-in-the-loop test protocol.
+//!
+//! Sends requests to the kernel over its console UART and waits for the responses, skipping the
+//! console output around them. The framing is compiled from the kernel's own source, so both
+//! sides always agree on it.
+
+#![feature(int_roundings)]
+
+#[allow(dead_code)]
+#[path = "../../kernel/src/common.rs"]
+mod common;
+
+/// The protocol.
+#[path = "../../kernel/src/hil"]
+pub mod hil {
+    pub mod frame;
+}
+
+pub use hil::frame::{self, kind, Frame};
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Baud rate of the kernel's console.
+pub const BAUD: u32 = 921_600;
+
+/// How long to wait for a response.
+pub const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Errors of a request.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+
+    /// The payload doesn't fit into a frame.
+    TooLong,
+
+    /// No response arrived in time.
+    Timeout,
+
+    /// The kernel rejected the request, with this message.
+    Remote(String),
+
+    /// The response doesn't match the request.
+    Unexpected(u8),
+}
+
+/// A connection to the kernel.
+pub struct Link {
+    port: File,
+    seq: u8,
+    decoder: frame::Decoder,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::TooLong => write!(f, "Payload too long"),
+            Self::Timeout => write!(f, "No response"),
+            Self::Remote(x) => write!(f, "Kernel: {}", x),
+            Self::Unexpected(kind) => write!(f, "Unexpected response type {:#04x}", kind),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Link {
+    /// Open the serial device, switching it to raw mode at `baud`.
+    pub fn open(device: &str, baud: u32) -> Result<Self, Error> {
+        // Reads return after 100 ms without data, so that the timeout can be checked.
+        let status = Command::new("stty")
+            .args(["-F", device, "raw", "-echo", &baud.to_string()])
+            .args(["min", "0", "time", "1"])
+            .status()?;
+        if !status.success() {
+            return Err(io::Error::new(io::ErrorKind::Other, "stty failed").into());
+        }
+
+        Ok(Self {
+            port: File::options().read(true).write(true).open(device)?,
+            seq: 0,
+            decoder: frame::Decoder::new(),
+        })
+    }
+
+    /// Send a request and return the payload of its response.
+    pub fn request(&mut self, request_kind: u8, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        self.seq = self.seq.wrapping_add(1);
+        let request = Frame::new(request_kind, self.seq, payload).ok_or(Error::TooLong)?;
+        self.port.write_all(request.encode().as_bytes())?;
+
+        let deadline = Instant::now() + TIMEOUT;
+        let mut buf = [0; 64];
+        while Instant::now() < deadline {
+            let len = self.port.read(&mut buf)?;
+
+            for response in buf[..len].iter().filter_map(|b| self.decoder.push(*b)) {
+                // Left over from an earlier request that timed out.
+                if response.seq != self.seq {
+                    continue;
+                }
+
+                return match response.kind {
+                    k if k == request_kind | kind::RESPONSE => Ok(response.payload().to_vec()),
+                    kind::ERROR => Err(Error::Remote(
+                        String::from_utf8_lossy(response.payload()).into_owned(),
+                    )),
+                    k => Err(Error::Unexpected(k)),
+                };
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    /// Check that the kernel answers.
+    pub fn ping(&mut self) -> Result<(), Error> {
+        let payload = b"KHROS";
+
+        match self.request(kind::PING, payload)? {
+            echo if echo == payload => Ok(()),
+            _ => Err(Error::Unexpected(kind::PING | kind::RESPONSE)),
+        }
+    }
+
+    /// Return the kernel's uptime.
+    pub fn uptime(&mut self) -> Result<Duration, Error> {
+        let payload = self.request(kind::UPTIME, &[])?;
+        let micros = payload
+            .try_into()
+            .map(u64::from_le_bytes)
+            .map_err(|_| Error::Unexpected(kind::UPTIME | kind::RESPONSE))?;
+
+        Ok(Duration::from_micros(micros))
+    }
+
+    /// Drive `pin` as an output.
+    pub fn gpio_write(&mut self, pin: u8, level: bool) -> Result<(), Error> {
+        self.request(kind::GPIO_WRITE, &[pin, level as u8])
+            .map(|_| ())
+    }
+
+    /// Make `pin` an input.
+    pub fn gpio_input(&mut self, pin: u8) -> Result<(), Error> {
+        self.request(kind::GPIO_INPUT, &[pin]).map(|_| ())
+    }
+
+    /// Return the level of `pin`.
+    pub fn gpio_read(&mut self, pin: u8) -> Result<bool, Error> {
+        match self.request(kind::GPIO_READ, &[pin])?.as_slice() {
+            [level] => Ok(*level != 0),
+            _ => Err(Error::Unexpected(kind::GPIO_READ | kind::RESPONSE)),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Drive a board running the kernel from the command line or a CI script. Exits with a non-zero
+//! status if a request fails or a check doesn't hold.
+
+use mingo_hil::{Error, Link, BAUD};
+use std::{env, process};
+
+const USAGE: &str = "Usage: hil <device> <command>
+
+Commands:
+    ping                 Check that the kernel answers
+    uptime               Print the kernel's uptime
+    write <pin> <0|1>    Drive a pin
+    input <pin>          Make a pin an input
+    read <pin>           Print the level of a pin
+    loopback <out> <in>  Toggle <out> and check that <in>, jumpered to it, follows";
+
+fn pin(arg: Option<&String>) -> Result<u8, String> {
+    arg.and_then(|x| x.parse().ok())
+        .ok_or_else(|| String::from("Expected a pin number"))
+}
+
+fn loopback(link: &mut Link, out_pin: u8, in_pin: u8) -> Result<(), String> {
+    link.gpio_input(in_pin).map_err(|e| e.to_string())?;
+
+    for level in [true, false, true, false] {
+        link.gpio_write(out_pin, level).map_err(|e| e.to_string())?;
+        if link.gpio_read(in_pin).map_err(|e| e.to_string())? != level {
+            return Err(format!(
+                "Pin {} didn't follow pin {} to {}",
+                in_pin, out_pin, level
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (device, command) = match args {
+        [device, command, ..] => (device, command.as_str()),
+        _ => return Err(String::from(USAGE)),
+    };
+    let mut link = Link::open(device, BAUD).map_err(|e| e.to_string())?;
+
+    let result: Result<(), Error> = match command {
+        "ping" => link.ping(),
+        "uptime" => link.uptime().map(|t| println!("{:?}", t)),
+        "write" => {
+            let level = match args.get(3).map(String::as_str) {
+                Some("0") => false,
+                Some("1") => true,
+                _ => return Err(String::from("Expected 0 or 1")),
+            };
+            link.gpio_write(pin(args.get(2))?, level)
+        }
+        "input" => link.gpio_input(pin(args.get(2))?),
+        "read" => link
+            .gpio_read(pin(args.get(2))?)
+            .map(|level| println!("{}", level as u8)),
+        "loopback" => return loopback(&mut link, pin(args.get(2))?, pin(args.get(3))?),
+        _ => return Err(String::from(USAGE)),
+    };
+
+    result.map_err(|e| e.to_string())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(x) = run(&args) {
+        eprintln!("{}", x);
+        process::exit(1);
+    }
+}
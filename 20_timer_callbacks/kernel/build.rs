@@ -1,6 +1,108 @@
-use std::{env, fs, process};
+use std::{env, fs, path::Path, process};
+
+/// Find the size reserved for the heap in the linker script, which is written as a product like
+/// `16 * 1024 * 1024`.
+fn heap_size(ld_script: &str) -> Option<usize> {
+    let section = &ld_script[ld_script.find(".heap")?..];
+    let expr = section[section.find(". +=")? + 4..].split(';').next()?;
+
+    expr.split('*')
+        .map(|factor| factor.trim().parse::<usize>().ok())
+        .product()
+}
+
+/// Return the features declared in the `[features]` table of the manifest, leaving out `default`
+/// and the implicit features of optional dependencies.
+fn declared_features(manifest: &str) -> Vec<String> {
+    manifest
+        .lines()
+        .skip_while(|l| l.trim() != "[features]")
+        .skip(1)
+        .take_while(|l| !l.starts_with('['))
+        .filter_map(|l| l.split_once('=').map(|(name, _)| name.trim().to_string()))
+        .filter(|name| name != "default")
+        .collect()
+}
+
+/// Write the build configuration to `$OUT_DIR/build_config.rs`.
+fn generate_build_config() {
+    let ld_script_path =
+        Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src/bsp/raspberrypi/kernel.ld");
+    println!("cargo:rerun-if-changed={}", ld_script_path.display());
+
+    let ld_script = fs::read_to_string(&ld_script_path).unwrap();
+    let heap_size = heap_size(&ld_script).expect("Heap size not found in linker script");
+
+    let manifest_path = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("Cargo.toml");
+    println!("cargo:rerun-if-changed={}", manifest_path.display());
+
+    let manifest = fs::read_to_string(manifest_path).unwrap();
+
+    // Cargo passes enabled features as `CARGO_FEATURE_<NAME>`, upper case with `-` replaced by `_`.
+    let features: Vec<String> = declared_features(&manifest)
+        .into_iter()
+        .filter(|f| {
+            let var = format!("CARGO_FEATURE_{}", f.to_uppercase().replace('-', "_"));
+            env::var_os(var).is_some()
+        })
+        .collect();
+
+    let config = format!(
+        "pub const FEATURES: &[&str] = &{:?};\n\
+         pub const PROFILE: &str = {:?};\n\
+         pub const TARGET: &str = {:?};\n\
+         pub const HEAP_SIZE: usize = {};\n",
+        features,
+        env::var("PROFILE").unwrap(),
+        env::var("TARGET").unwrap(),
+        heap_size
+    );
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("build_config.rs");
+    fs::write(out_path, config).unwrap();
+}
+
+/// Link the C archive named by `KERNEL_C_LIB`, if any, and write the C entry points named by
+/// `KERNEL_C_ENTRIES` to `$OUT_DIR/c_entries.rs`. Entries are space separated `name=symbol` pairs.
+fn generate_c_entries() {
+    println!("cargo:rerun-if-env-changed=KERNEL_C_LIB");
+    println!("cargo:rerun-if-env-changed=KERNEL_C_ENTRIES");
+
+    if let Ok(lib) = env::var("KERNEL_C_LIB") {
+        println!("cargo:rerun-if-changed={}", lib);
+        println!("cargo:rustc-link-arg={}", lib);
+    }
+
+    let entries: Vec<(String, String)> = env::var("KERNEL_C_ENTRIES")
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|e| match e.split_once('=') {
+            Some((name, symbol)) => (name.to_string(), symbol.to_string()),
+            None => (e.to_string(), e.to_string()),
+        })
+        .collect();
+
+    let mut code = String::from("extern \"C\" {\n");
+    for (_, symbol) in entries.iter() {
+        code += &format!(
+            "    fn {}(argc: core::ffi::c_int, argv: *const *const core::ffi::c_char) -> core::ffi::c_int;\n",
+            symbol
+        );
+    }
+    code += "}\n\nconst ENTRIES: &[(&str, EntryFn)] = &[\n";
+    for (name, symbol) in entries.iter() {
+        code += &format!("    ({:?}, {}),\n", name, symbol);
+    }
+    code += "];\n";
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("c_entries.rs");
+    fs::write(out_path, code).unwrap();
+}
 
 fn main() {
+    generate_build_config();
+    generate_c_entries();
+
     let ld_script_path = match env::var("LD_SCRIPT_PATH") {
         Ok(var) => var,
         _ => process::exit(0),
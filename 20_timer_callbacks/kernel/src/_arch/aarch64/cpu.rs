@@ -19,6 +19,88 @@ use aarch64_cpu::asm;
 
 pub use asm::nop;
 
+/// Enable the cycle counter `PMCCNTR_EL0` on the executing core and reset it.
+pub fn enable_cycle_counter() {
+    unsafe {
+        // PMCR_EL0.E enables the counters, PMCR_EL0.C resets the cycle counter.
+        core::arch::asm!(
+            "mrs {tmp}, pmcr_el0",
+            "orr {tmp}, {tmp}, #0b101",
+            "msr pmcr_el0, {tmp}",
+            "msr pmcntenset_el0, {c}",
+            "isb",
+            tmp = out(reg) _,
+            c = in(reg) 1u64 << 31,
+            options(nostack)
+        );
+    }
+}
+
+/// Return the number of CPU cycles counted since [`enable_cycle_counter()`].
+#[inline(always)]
+pub fn cycle_count() -> u64 {
+    let value: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, pmccntr_el0", out(reg) value, options(nomem, nostack));
+    }
+    value
+}
+
+/// Write back and invalidate the data cache lines covering `len` bytes at `addr`.
+///
+/// Used to share memory with bus masters that don't snoop the caches, e.g. the VideoCore.
+pub fn clean_invalidate_dcache(addr: usize, len: usize) {
+    // The smallest data cache line of the cores, from CTR_EL0.DminLine.
+    let ctr: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, ctr_el0", out(reg) ctr, options(nomem, nostack));
+    }
+    let line = 4 << ((ctr >> 16) & 0xf);
+
+    let mut a = addr & !(line - 1);
+    while a < addr + len {
+        unsafe {
+            core::arch::asm!("dc civac, {}", in(reg) a, options(nostack));
+        }
+        a += line;
+    }
+    asm::barrier::dsb(asm::barrier::SY);
+}
+
+/// Invalidate the instruction cache, so that code written through the data cache is fetched from
+/// memory. The data cache must have been written back first.
+pub fn invalidate_icache() {
+    unsafe {
+        core::arch::asm!("ic iallu", "dsb nsh", "isb", options(nostack));
+    }
+}
+
+/// Pause execution on the core until an interrupt is pending, even if it is masked.
+#[inline(always)]
+pub fn wait_for_interrupt() {
+    asm::wfi()
+}
+
+/// Hand the image of `len` bytes at the physical address `src` to EL2, which copies it to the
+/// physical address `dst` and jumps there with the MMU off. EL2 first moves its copy loop to the
+/// physical address `trampoline`.
+///
+/// # Safety
+///
+/// - The image must be written back to memory, and `len` must be a multiple of 16.
+/// - `dst` must be below `src`, and `trampoline` must not overlap the image or its destination.
+/// - Everything the running kernel set up is abandoned, so devices must be quiesced.
+pub unsafe fn chainload(src: usize, len: usize, dst: usize, trampoline: usize) -> ! {
+    core::arch::asm!(
+        "hvc #0",
+        in("x0") src,
+        in("x1") len,
+        in("x2") dst,
+        in("x3") trampoline,
+        options(noreturn, nostack)
+    )
+}
+
 /// Pause execution on the core.
 #[inline(always)]
 pub fn wait_forever() -> ! {
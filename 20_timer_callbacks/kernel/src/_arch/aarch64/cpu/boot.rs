@@ -23,7 +23,8 @@ use tock_registers::interfaces::Writeable;
 global_asm!(
     include_str!("boot.s"),
     CONST_CURRENTEL_EL2 = const 0x8,
-    CONST_CORE_ID_MASK = const 0b11
+    CONST_CORE_ID_MASK = const 0b11,
+    CONST_ESR_EC_HVC64 = const 0x16
 );
 
 //--------------------------------------------------------------------------------------------------
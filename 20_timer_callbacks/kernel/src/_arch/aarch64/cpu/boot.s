@@ -92,6 +92,10 @@ _start:
 	b.eq	.L_parking_loop
 	str	w5, [x4]
 
+	// Install the EL2 vector table, through which EL1 chainloads another image.
+	ADR_REL	x4, __el2_vector_start
+	msr	VBAR_EL2, x4
+
 	// Jump to Rust code. x0, x1 and x2 hold the function arguments provided to _start_rust().
 	b	_start_rust
 
@@ -103,3 +107,124 @@ _start:
 .size	_start, . - _start
 .type	_start, function
 .global	_start
+
+//------------------------------------------------------------------------------
+// EL2 vector table
+//------------------------------------------------------------------------------
+// The kernel never returns to EL2 except through `hvc #0` from EL1 to chainload another image.
+// Every other exception taken to EL2 parks the core.
+//
+// EL2 runs with the MMU off, so all addresses here are physical.
+.section .text._el2_vectors
+
+// Park the core. Fits into a vector table entry.
+.macro EL2_PARK
+	wfe
+	b	. - 4
+.endm
+
+// The vector table must be aligned to 2 KiB.
+.align 11
+
+__el2_vector_start:
+.org 0x000
+	EL2_PARK
+.org 0x080
+	EL2_PARK
+.org 0x100
+	EL2_PARK
+.org 0x180
+	EL2_PARK
+
+.org 0x200
+	EL2_PARK
+.org 0x280
+	EL2_PARK
+.org 0x300
+	EL2_PARK
+.org 0x380
+	EL2_PARK
+
+// Synchronous exception from EL1 in AArch64, which is where `hvc` ends up.
+.org 0x400
+	b	__el2_chainload
+.org 0x480
+	EL2_PARK
+.org 0x500
+	EL2_PARK
+.org 0x580
+	EL2_PARK
+
+.org 0x600
+	EL2_PARK
+.org 0x680
+	EL2_PARK
+.org 0x700
+	EL2_PARK
+.org 0x780
+	EL2_PARK
+.org 0x800
+
+//------------------------------------------------------------------------------
+// fn __el2_chainload(src: u64, len: u64, dst: u64, trampoline: u64) -> !
+//------------------------------------------------------------------------------
+// Copy `len` bytes from `src` to `dst` and jump to `dst`, the way the firmware starts a kernel.
+// `len` must be a multiple of 16. The image may overlap the running kernel, so the copy loop is
+// first moved to `trampoline`, which must lie outside of both. `dst` must be below `src`, so the
+// forward copy never overwrites source bytes it still needs.
+__el2_chainload:
+	// Only `hvc #0` chainloads.
+	mrs	x9, ESR_EL2
+	lsr	x10, x9, #26
+	cmp	x10, {CONST_ESR_EC_HVC64}
+	b.ne	.L_el2_parking_loop
+	and	x10, x9, #0xffff
+	cbnz	x10, .L_el2_parking_loop
+
+	// Move the copy loop to the trampoline.
+	ADR_REL	x9, .L_chainload_copy
+	ADR_REL	x10, .L_chainload_copy_end
+	mov	x11, x3
+.L_trampoline_copy_loop:
+	ldr	w12, [x9], #4
+	str	w12, [x11], #4
+	cmp	x9, x10
+	b.ne	.L_trampoline_copy_loop
+
+	// Fetch the trampoline's instructions from memory.
+	dsb	sy
+	ic	iallu
+	dsb	sy
+	isb
+	br	x3
+
+	// Position independent, it runs from the trampoline.
+.L_chainload_copy:
+	mov	x9, x2
+1:
+	cbz	x1, 2f
+	ldp	x10, x11, [x0], #16
+	stp	x10, x11, [x9], #16
+	sub	x1, x1, #16
+	b	1b
+2:
+	dsb	sy
+	ic	iallu
+	dsb	sy
+	isb
+
+	// Enter the image with cleared arguments.
+	mov	x9, x2
+	mov	x0, xzr
+	mov	x1, xzr
+	mov	x2, xzr
+	mov	x3, xzr
+	br	x9
+.L_chainload_copy_end:
+
+.L_el2_parking_loop:
+	wfe
+	b	.L_el2_parking_loop
+
+.size	__el2_chainload, . - __el2_chainload
+.type	__el2_chainload, function
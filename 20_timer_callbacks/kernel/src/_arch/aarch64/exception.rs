@@ -11,9 +11,14 @@
 //!
 //! crate::exception::arch_exception
 
-use crate::{exception, memory, symbols};
+use crate::{exception, latency, memory, process, rand, sched, symbols, syscall, warn};
 use aarch64_cpu::{asm::barrier, registers::*};
-use core::{arch::global_asm, cell::UnsafeCell, fmt};
+use core::{
+    arch::global_asm,
+    cell::UnsafeCell,
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 use tock_registers::{
     interfaces::{Readable, Writeable},
     registers::InMemoryRegister,
@@ -30,6 +35,17 @@ global_asm!(
 // Private Definitions
 //--------------------------------------------------------------------------------------------------
 
+// ISS fields of instruction and data aborts.
+const ISS_FSC_MASK: u64 = 0x3F;
+const ISS_WNR: u64 = 1 << 6;
+const ISS_S1PTW: u64 = 1 << 7;
+const ISS_CM: u64 = 1 << 8;
+const ISS_EA: u64 = 1 << 9;
+const ISS_FNV: u64 = 1 << 10;
+const ISS_SRT_SHIFT: u64 = 16;
+const ISS_SAS_SHIFT: u64 = 22;
+const ISS_ISV: u64 = 1 << 24;
+
 /// Wrapper structs for memory copies of registers.
 #[repr(transparent)]
 struct SpsrEL1(InMemoryRegister<u64, SPSR_EL1::Register>);
@@ -54,6 +70,26 @@ struct ExceptionContext {
     esr_el1: EsrEL1,
 }
 
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Prints the register state for a panic.
+///
+/// A panic raised while handling an IRQ, e.g. in a timer callback, gets the context of the code
+/// the IRQ interrupted. Otherwise, the general purpose registers only hold the panic handler's own
+/// values, so the special registers are printed instead, along with the syndrome of the last
+/// exception.
+pub struct RegisterDump;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+/// Address of the exception context of the IRQ being handled, or 0. IRQs are only handled by the
+/// boot core.
+static IRQ_CONTEXT: AtomicUsize = AtomicUsize::new(0);
+
 //--------------------------------------------------------------------------------------------------
 // Private Code
 //--------------------------------------------------------------------------------------------------
@@ -107,9 +143,21 @@ extern "C" fn current_elx_synchronous(e: &mut ExceptionContext) {
 }
 
 #[no_mangle]
-extern "C" fn current_elx_irq(_e: &mut ExceptionContext) {
+extern "C" fn current_elx_irq(e: &mut ExceptionContext) {
+    latency::handler_entered();
+
+    // The arrival time of IRQs relative to the instruction stream is hard to predict.
+    rand::add_timing_sample();
+
+    let interrupted = IRQ_CONTEXT.swap(e as *const _ as usize, Ordering::Relaxed);
     let token = unsafe { &exception::asynchronous::IRQContext::new() };
     exception::asynchronous::irq_manager().handle_pending_irqs(token);
+    IRQ_CONTEXT.store(interrupted, Ordering::Relaxed);
+    latency::handler_leaving();
+
+    // The IRQs are acknowledged, so the interrupted task may be switched out here. Its exception
+    // context stays on its stack and is restored when it is switched to again.
+    sched::preempt();
 }
 
 #[no_mangle]
@@ -123,12 +171,27 @@ extern "C" fn current_elx_serror(e: &mut ExceptionContext) {
 
 #[no_mangle]
 extern "C" fn lower_aarch64_synchronous(e: &mut ExceptionContext) {
-    default_exception_handler(e);
+    if let Some(ESR_EL1::EC::Value::SVC64) = e.esr_el1.exception_class() {
+        let args = [e.gpr[0], e.gpr[1], e.gpr[2], e.gpr[3], e.gpr[4], e.gpr[5]];
+
+        // Syscalls run like thread context, so that they can block. The program's ELR and SPSR are
+        // already saved in the context.
+        exception::asynchronous::local_irq_unmask();
+        e.gpr[0] = syscall::dispatch(e.gpr[8], &args);
+        exception::asynchronous::local_irq_mask();
+        return;
+    }
+
+    // Any other exception is a fault of the program, which ends the program instead of the
+    // kernel.
+    warn!("Program fault:\n{}", e);
+    process::exit(process::STATUS_FAULT);
 }
 
 #[no_mangle]
 extern "C" fn lower_aarch64_irq(e: &mut ExceptionContext) {
-    default_exception_handler(e);
+    // The program's context stays on its task's stack if the task is switched out.
+    current_elx_irq(e);
 }
 
 #[no_mangle]
@@ -203,6 +266,78 @@ impl EsrEL1 {
     fn iss(&self) -> u64 {
         self.0.read(ESR_EL1::ISS)
     }
+
+    fn is_data_abort(&self) -> bool {
+        use ESR_EL1::EC::Value::*;
+
+        matches!(
+            self.exception_class(),
+            Some(DataAbortLowerEL | DataAbortCurrentEL)
+        )
+    }
+
+    fn is_abort(&self) -> bool {
+        use ESR_EL1::EC::Value::*;
+
+        self.is_data_abort()
+            || matches!(
+                self.exception_class(),
+                Some(InstrAbortLowerEL | InstrAbortCurrentEL)
+            )
+    }
+
+    /// Whether FAR_EL1 holds the faulting address. Aborts can leave it unknown.
+    fn far_valid(&self) -> bool {
+        !self.is_abort() || self.0.read(ESR_EL1::ISS) & ISS_FNV == 0
+    }
+
+    fn class_str(&self) -> &'static str {
+        use ESR_EL1::EC::Value::*;
+
+        match self.exception_class() {
+            Some(Unknown) => "Unknown reason, e.g. an undefined instruction",
+            Some(TrappedWFIorWFE) => "Trapped WFI or WFE",
+            Some(TrappedFP) => "Trapped SIMD or floating point access",
+            Some(IllegalExecutionState) => "Illegal execution state",
+            Some(SVC64) => "SVC",
+            Some(HVC64) => "HVC",
+            Some(SMC64) => "SMC",
+            Some(TrappedMsrMrs) => "Trapped MSR, MRS or system instruction",
+            Some(InstrAbortLowerEL) => "Instruction Abort, lower EL",
+            Some(InstrAbortCurrentEL) => "Instruction Abort, current EL",
+            Some(PCAlignmentFault) => "PC alignment fault",
+            Some(DataAbortLowerEL) => "Data Abort, lower EL",
+            Some(DataAbortCurrentEL) => "Data Abort, current EL",
+            Some(SPAlignmentFault) => "SP alignment fault",
+            Some(SError) => "SError",
+            Some(BreakpointLowerEL | BreakpointCurrentEL) => "Breakpoint",
+            Some(SoftwareStepLowerEL | SoftwareStepCurrentEL) => "Software step",
+            Some(WatchpointLowerEL | WatchpointCurrentEL) => "Watchpoint",
+            Some(Brk64) => "BRK instruction",
+            _ => "N/A",
+        }
+    }
+}
+
+/// Describe the fault status code of an abort, and the translation table level it applies to.
+fn fault_status(fsc: u64) -> (&'static str, Option<u64>) {
+    let level = Some(fsc & 0b11);
+
+    match fsc {
+        0b00_0000..=0b00_0011 => ("Address size fault", level),
+        0b00_0100..=0b00_0111 => ("Translation fault", level),
+        0b00_1000..=0b00_1011 => ("Access flag fault", level),
+        0b00_1100..=0b00_1111 => ("Permission fault", level),
+        0b01_0000 => ("Synchronous external abort", None),
+        0b01_0001 => ("Synchronous tag check fault", None),
+        0b01_0100..=0b01_0111 => ("Synchronous external abort on table walk", level),
+        0b01_1000 => ("Synchronous parity or ECC error", None),
+        0b01_1100..=0b01_1111 => ("Parity or ECC error on table walk", level),
+        0b10_0001 => ("Alignment fault", None),
+        0b11_0000 => ("TLB conflict abort", None),
+        0b11_0001 => ("Unsupported atomic hardware update", None),
+        _ => ("Reserved or implementation defined", None),
+    }
 }
 
 /// Human readable ESR_EL1.
@@ -216,14 +351,50 @@ impl fmt::Display for EsrEL1 {
         write!(f, "      Exception Class         (EC) : {:#x}", self.0.read(ESR_EL1::EC))?;
 
         // Exception class.
-        let ec_translation = match self.exception_class() {
-            Some(ESR_EL1::EC::Value::DataAbortCurrentEL) => "Data Abort, current EL",
-            _ => "N/A",
-        };
-        writeln!(f, " - {}", ec_translation)?;
+        writeln!(f, " - {}", self.class_str())?;
 
         // Raw print of instruction specific syndrome.
-        write!(f, "      Instr Specific Syndrome (ISS): {:#x}", self.0.read(ESR_EL1::ISS))
+        let iss = self.0.read(ESR_EL1::ISS);
+        write!(f, "      Instr Specific Syndrome (ISS): {:#x}", iss)?;
+
+        if !self.is_abort() {
+            return Ok(());
+        }
+
+        // Fault status, e.g. which translation table level had no entry.
+        let fsc = iss & ISS_FSC_MASK;
+        let (status, level) = fault_status(fsc);
+        let name = if self.is_data_abort() { "DFSC" } else { "IFSC" };
+        write!(f, "\n      Fault Status Code     ({}): {:#04x} - {}", name, fsc, status)?;
+        if let Some(level) = level {
+            write!(f, ", level {}", level)?;
+        }
+
+        if self.is_data_abort() {
+            let access = match (iss & ISS_CM != 0, iss & ISS_WNR != 0) {
+                (true, _) => "Cache maintenance",
+                (false, true) => "Write",
+                (false, false) => "Read",
+            };
+            write!(f, "\n      Access                     : {}", access)?;
+            if iss & ISS_S1PTW != 0 {
+                write!(f, ", during the stage 1 table walk")?;
+            }
+
+            // Only loads and stores of a single general purpose register are described.
+            if iss & ISS_ISV != 0 {
+                write!(f, "\n      Size, Register             : {} bytes, x{}",
+                    1 << ((iss >> ISS_SAS_SHIFT) & 0b11),
+                    (iss >> ISS_SRT_SHIFT) & 0x1F
+                )?;
+            }
+        }
+
+        if iss & ISS_EA != 0 {
+            write!(f, "\n      External Abort         (EA) : Set")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -258,8 +429,18 @@ impl fmt::Display for ExceptionContext {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "{}", self.esr_el1)?;
 
-        if self.fault_address_valid() {
-            writeln!(f, "FAR_EL1: {:#018x}", FAR_EL1.get() as usize)?;
+        if self.fault_address_valid() && !self.esr_el1.far_valid() {
+            writeln!(f, "FAR_EL1: Not valid")?;
+        } else if self.fault_address_valid() {
+            let far = FAR_EL1.get() as usize;
+            writeln!(f, "FAR_EL1: {:#018x}", far)?;
+
+            // Names e.g. the driver whose MMIO region was accessed, or shows the address is
+            // unmapped.
+            match memory::mmu::kernel_mapping_of(memory::Address::new(far)) {
+                Some((entity, offset)) => writeln!(f, "      Mapping: {} + {:#x}", entity, offset)?,
+                None => writeln!(f, "      Mapping: None")?,
+            }
         }
 
         writeln!(f, "{}", self.spsr_el1)?;
@@ -267,11 +448,13 @@ impl fmt::Display for ExceptionContext {
         writeln!(
             f,
             "      Symbol: {}",
-            match symbols::lookup_symbol(memory::Address::new(self.elr_el1 as usize)) {
-                Some(sym) => sym.name(),
-                _ => "Symbol not found",
-            }
+            symbols::SymbolOffset(memory::Address::new(self.elr_el1 as usize))
         )?;
+        if self.exception_class() == Some(ESR_EL1::EC::Value::Unknown) {
+            // ELR points at the offending instruction, which was fetched, so it can be read.
+            let insn = unsafe { core::ptr::read_volatile(self.elr_el1 as *const u32) };
+            writeln!(f, "      Instruction: {:#010x}", insn)?;
+        }
         writeln!(f)?;
         writeln!(f, "General purpose register:")?;
 
@@ -288,6 +471,60 @@ impl fmt::Display for ExceptionContext {
     }
 }
 
+impl fmt::Display for RegisterDump {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let context = IRQ_CONTEXT.load(Ordering::Relaxed);
+        if context != 0 {
+            // The context lives on the stack below the IRQ handler, which is still running.
+            let context = unsafe { &*(context as *const ExceptionContext) };
+
+            return writeln!(f, "Interrupted context:\n{}", context);
+        }
+
+        let (_, level) = current_privilege_level();
+        let (sp, fp, lr): (u64, u64, u64);
+        unsafe {
+            core::arch::asm!(
+                "mov {}, sp",
+                "mov {}, x29",
+                "mov {}, x30",
+                out(reg) sp,
+                out(reg) fp,
+                out(reg) lr,
+                options(nomem, nostack)
+            );
+        }
+
+        writeln!(f, "CPU state:")?;
+        writeln!(f, "      Level: {}", level)?;
+        writeln!(
+            f,
+            "      DAIF:  {:#06x} (IRQs {})",
+            DAIF.get(),
+            if DAIF.is_set(DAIF::I) {
+                "masked"
+            } else {
+                "unmasked"
+            }
+        )?;
+        writeln!(f, "      sp:    {:#018x}", sp)?;
+        writeln!(f, "      fp:    {:#018x}", fp)?;
+        writeln!(f, "      lr:    {:#018x}", lr)?;
+        writeln!(f)?;
+
+        let elr = ELR_EL1.get();
+        writeln!(f, "Last exception:")?;
+        writeln!(f, "{}", EsrEL1(InMemoryRegister::new(ESR_EL1.get())))?;
+        writeln!(f, "{}", SpsrEL1(InMemoryRegister::new(SPSR_EL1.get())))?;
+        writeln!(f, "ELR_EL1: {:#018x}", elr)?;
+        writeln!(
+            f,
+            "      Symbol: {}",
+            symbols::SymbolOffset(memory::Address::new(elr as usize))
+        )
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
@@ -323,3 +560,21 @@ pub unsafe fn handling_init() {
     // Force VBAR update to complete before next instruction.
     barrier::isb(barrier::SY);
 }
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Fault status codes must decode with their translation table level.
+    #[kernel_test]
+    fn fault_status_levels() {
+        assert_eq!(fault_status(0b00_0111), ("Translation fault", Some(3)));
+        assert_eq!(fault_status(0b00_1101), ("Permission fault", Some(1)));
+        assert_eq!(fault_status(0b10_0001), ("Alignment fault", None));
+    }
+}
@@ -80,9 +80,13 @@ impl MemoryManagementUnit {
     }
 
     /// Configure various settings of stage 1 of the EL1 translation regime.
+    ///
+    /// The kernel is translated through TTBR1. Walks through TTBR0 stay disabled until a program's
+    /// tables are set.
     #[inline(always)]
     fn configure_translation_control(&self) {
         let t1sz = (64 - bsp::memory::mmu::KernelVirtAddrSpace::SIZE_SHIFT) as u64;
+        let t0sz = (64 - bsp::memory::mmu::UserVirtAddrSpace::SIZE_SHIFT) as u64;
 
         TCR_EL1.write(
             TCR_EL1::TBI1::Used
@@ -94,6 +98,11 @@ impl MemoryManagementUnit {
                 + TCR_EL1::EPD1::EnableTTBR1Walks
                 + TCR_EL1::A1::TTBR1
                 + TCR_EL1::T1SZ.val(t1sz)
+                + TCR_EL1::TG0::KiB_64
+                + TCR_EL1::SH0::Inner
+                + TCR_EL1::ORGN0::WriteBack_ReadAlloc_WriteAlloc_Cacheable
+                + TCR_EL1::IRGN0::WriteBack_ReadAlloc_WriteAlloc_Cacheable
+                + TCR_EL1::T0SZ.val(t0sz)
                 + TCR_EL1::EPD0::DisableTTBR0Walks,
         );
     }
@@ -155,4 +164,30 @@ impl memory::mmu::interface::MMU for MemoryManagementUnit {
     fn is_enabled(&self) -> bool {
         SCTLR_EL1.matches_all(SCTLR_EL1::M::Enable)
     }
+
+    fn invalidate_kernel_tlb(&self) {
+        // The descriptor writes must be visible to the table walks of all cores first.
+        unsafe {
+            core::arch::asm!(
+                "dsb ishst",
+                "tlbi vmalle1is",
+                "dsb ish",
+                "isb",
+                options(nostack)
+            )
+        };
+    }
+
+    unsafe fn set_user_tables(&self, phys_tables_base_addr: Option<Address<Physical>>) {
+        match phys_tables_base_addr {
+            Some(addr) => {
+                TTBR0_EL1.set_baddr(addr.as_usize() as u64);
+                TCR_EL1.modify(TCR_EL1::EPD0::EnableTTBR0Walks);
+            }
+            None => TCR_EL1.modify(TCR_EL1::EPD0::DisableTTBR0Walks),
+        }
+
+        // There are no ASIDs, so the previous program's translations must go.
+        core::arch::asm!("isb", "tlbi vmalle1", "dsb nsh", "isb", options(nostack));
+    }
 }
@@ -180,6 +180,14 @@ impl TableDescriptor {
     }
 }
 
+/// Return if memory with `acc_perms` belongs to a program.
+fn is_user(acc_perms: AccessPermissions) -> bool {
+    matches!(
+        acc_perms,
+        AccessPermissions::ReadOnlyUser | AccessPermissions::ReadWriteUser
+    )
+}
+
 /// Convert the kernel's generic memory attributes to HW-specific attributes of the MMU.
 impl convert::From<AttributeFields>
     for tock_registers::fields::FieldValue<u64, STAGE1_PAGE_DESCRIPTOR::Register>
@@ -201,18 +209,29 @@ impl convert::From<AttributeFields>
         desc += match attribute_fields.acc_perms {
             AccessPermissions::ReadOnly => STAGE1_PAGE_DESCRIPTOR::AP::RO_EL1,
             AccessPermissions::ReadWrite => STAGE1_PAGE_DESCRIPTOR::AP::RW_EL1,
+            AccessPermissions::ReadOnlyUser => STAGE1_PAGE_DESCRIPTOR::AP::RO_EL1_EL0,
+            AccessPermissions::ReadWriteUser => STAGE1_PAGE_DESCRIPTOR::AP::RW_EL1_EL0,
         };
 
-        // The execute-never attribute is mapped to PXN in AArch64.
-        desc += if attribute_fields.execute_never {
+        // The execute-never attribute is mapped to UXN for program memory and to PXN otherwise.
+        // The kernel never executes program memory, and programs never execute kernel memory.
+        let xn = attribute_fields.execute_never;
+        desc += if is_user(attribute_fields.acc_perms) {
             STAGE1_PAGE_DESCRIPTOR::PXN::True
+                + if xn {
+                    STAGE1_PAGE_DESCRIPTOR::UXN::True
+                } else {
+                    STAGE1_PAGE_DESCRIPTOR::UXN::False
+                }
         } else {
-            STAGE1_PAGE_DESCRIPTOR::PXN::False
+            STAGE1_PAGE_DESCRIPTOR::UXN::True
+                + if xn {
+                    STAGE1_PAGE_DESCRIPTOR::PXN::True
+                } else {
+                    STAGE1_PAGE_DESCRIPTOR::PXN::False
+                }
         };
 
-        // Always set unprivileged exectue-never as long as userspace is not implemented yet.
-        desc += STAGE1_PAGE_DESCRIPTOR::UXN::True;
-
         desc
     }
 }
@@ -233,10 +252,16 @@ impl convert::TryFrom<InMemoryRegister<u64, STAGE1_PAGE_DESCRIPTOR::Register>> f
         let acc_perms = match desc.read_as_enum(STAGE1_PAGE_DESCRIPTOR::AP) {
             Some(STAGE1_PAGE_DESCRIPTOR::AP::Value::RO_EL1) => AccessPermissions::ReadOnly,
             Some(STAGE1_PAGE_DESCRIPTOR::AP::Value::RW_EL1) => AccessPermissions::ReadWrite,
-            _ => return Err("Unexpected access permission"),
+            Some(STAGE1_PAGE_DESCRIPTOR::AP::Value::RO_EL1_EL0) => AccessPermissions::ReadOnlyUser,
+            Some(STAGE1_PAGE_DESCRIPTOR::AP::Value::RW_EL1_EL0) => AccessPermissions::ReadWriteUser,
+            None => return Err("Unexpected access permission"),
         };
 
-        let execute_never = desc.read(STAGE1_PAGE_DESCRIPTOR::PXN) > 0;
+        let execute_never = if is_user(acc_perms) {
+            desc.read(STAGE1_PAGE_DESCRIPTOR::UXN) > 0
+        } else {
+            desc.read(STAGE1_PAGE_DESCRIPTOR::PXN) > 0
+        };
 
         Ok(AttributeFields {
             mem_attributes,
@@ -339,6 +364,12 @@ impl<const NUM_TABLES: usize, const START_FROM_TOP: bool>
         Self::_new(false)
     }
 
+    /// The physical address of the lvl2 table, where the walk starts, for the translation table
+    /// base register.
+    pub fn phys_base_address(&self) -> Result<Address<Physical>, &'static str> {
+        memory::mmu::try_kernel_virt_addr_to_phys_addr(self.lvl2.virt_start_addr())
+    }
+
     /// Helper to calculate the lvl2 and lvl3 indices from an address.
     #[inline(always)]
     fn lvl2_lvl3_index_from_page_addr(
@@ -449,6 +480,27 @@ impl<const NUM_TABLES: usize, const START_FROM_TOP: bool>
         Ok(())
     }
 
+    unsafe fn unmap_at(&mut self, virt_region: &MemoryRegion<Virtual>) -> Result<(), &'static str> {
+        assert!(self.initialized, "Translation tables not initialized");
+
+        // Checked first, so that a failure leaves the region untouched.
+        for virt_page_addr in virt_region.into_iter() {
+            if !self
+                .page_descriptor_from_page_addr(virt_page_addr)?
+                .is_valid()
+            {
+                return Err("Tried to unmap a page that is not mapped");
+            }
+        }
+
+        for virt_page_addr in virt_region.into_iter() {
+            let (lvl2_index, lvl3_index) = self.lvl2_lvl3_index_from_page_addr(virt_page_addr)?;
+            self.lvl3[lvl2_index][lvl3_index] = PageDescriptor::new_zeroed();
+        }
+
+        Ok(())
+    }
+
     fn try_virt_page_addr_to_phys_page_addr(
         &self,
         virt_page_addr: PageAddress<Virtual>,
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Architectural program support.
+//!
+//! # Orientation
+//!
+//! Since arch modules are imported into generic modules using the path attribute, the path of this
+//! file is:
+//!
+//! crate::process::arch_process
+
+use crate::syscall;
+use core::{arch::global_asm, cell::UnsafeCell};
+
+// The programs linked into the kernel.
+global_asm!(
+    include_str!("process.s"),
+    SYS_EXIT = const syscall::SYS_EXIT,
+    SYS_WRITE = const syscall::SYS_WRITE,
+    SYS_SLEEP = const syscall::SYS_SLEEP,
+    SYS_GPIO_SET = const syscall::SYS_GPIO_SET
+);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+// Provided by process.s.
+extern "Rust" {
+    static __program_blink_start: UnsafeCell<()>;
+    static __program_blink_end: UnsafeCell<()>;
+    static __program_fault_start: UnsafeCell<()>;
+    static __program_fault_end: UnsafeCell<()>;
+}
+
+/// The code between the symbols `start` and `end`.
+fn image(start: &UnsafeCell<()>, end: &UnsafeCell<()>) -> &'static [u8] {
+    let start = start.get() as usize;
+    let end = end.get() as usize;
+
+    unsafe { core::slice::from_raw_parts(start as *const u8, end - start) }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// The programs, by name.
+pub fn programs() -> [(&'static str, &'static [u8]); 2] {
+    unsafe {
+        [
+            ("blink", image(&__program_blink_start, &__program_blink_end)),
+            ("fault", image(&__program_fault_start, &__program_fault_end)),
+        ]
+    }
+}
+
+/// Drop to EL0 and run the program at `entry`, with its stack ending at `stack_top`, `arg` in x0
+/// and all exceptions unmasked. The program's tables must be active.
+///
+/// The kernel's stack pointer stays where it is, so exceptions of the program are handled on the
+/// calling task's stack.
+///
+/// # Safety
+///
+/// - `entry` and the stack must be mapped for EL0.
+pub unsafe fn enter(entry: usize, stack_top: usize, arg: u64) -> ! {
+    core::arch::asm!(
+        // An IRQ would overwrite ELR_EL1 and SPSR_EL1.
+        "msr daifset, #2",
+        "msr sp_el0, x10",
+        "msr elr_el1, x9",
+        // EL0t with nothing masked.
+        "msr spsr_el1, xzr",
+        // Leave no kernel values behind.
+        "mov x1, xzr",
+        "mov x2, xzr",
+        "mov x3, xzr",
+        "mov x4, xzr",
+        "mov x5, xzr",
+        "mov x6, xzr",
+        "mov x7, xzr",
+        "mov x8, xzr",
+        "mov x9, xzr",
+        "mov x10, xzr",
+        "mov x11, xzr",
+        "mov x12, xzr",
+        "mov x13, xzr",
+        "mov x14, xzr",
+        "mov x15, xzr",
+        "mov x16, xzr",
+        "mov x17, xzr",
+        "mov x18, xzr",
+        "mov x19, xzr",
+        "mov x20, xzr",
+        "mov x21, xzr",
+        "mov x22, xzr",
+        "mov x23, xzr",
+        "mov x24, xzr",
+        "mov x25, xzr",
+        "mov x26, xzr",
+        "mov x27, xzr",
+        "mov x28, xzr",
+        "mov x29, xzr",
+        "mov x30, xzr",
+        "eret",
+        in("x0") arg,
+        in("x9") entry,
+        in("x10") stack_top,
+        options(noreturn, nostack)
+    )
+}
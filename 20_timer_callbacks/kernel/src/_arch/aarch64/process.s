@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//--------------------------------------------------------------------------------------------------
+// Programs
+//--------------------------------------------------------------------------------------------------
+// The programs are copied to the start of a program's address space, so they must be position
+// independent. They are never run in place.
+.section .rodata
+
+//------------------------------------------------------------------------------
+// blink: Greet, then toggle the pin given in x0 three times, 250 ms high and 250 ms low. Ends with
+// the error if the pin can't be driven.
+//------------------------------------------------------------------------------
+.balign 4
+.global __program_blink_start
+__program_blink_start:
+	mov	x19, x0
+	adr	x0, .L_blink_text
+	adr	x1, .L_blink_text_end
+	sub	x1, x1, x0
+	mov	x8, #{SYS_WRITE}
+	svc	#0
+
+	mov	x20, #6				// Level changes left.
+	mov	x21, #1				// Next level.
+.L_blink_loop:
+	mov	x0, x19
+	mov	x1, x21
+	mov	x8, #{SYS_GPIO_SET}
+	svc	#0
+	tbnz	x0, #63, .L_blink_exit		// Negative, an error.
+
+	movz	x0, #0xb280			// 250 ms in ns.
+	movk	x0, #0x0ee6, lsl #16
+	mov	x8, #{SYS_SLEEP}
+	svc	#0
+
+	eor	x21, x21, #1
+	subs	x20, x20, #1
+	b.ne	.L_blink_loop
+
+.L_blink_exit:
+	mov	x8, #{SYS_EXIT}
+	svc	#0
+
+.L_blink_text:
+	.ascii	"Hello from EL0\n"
+.L_blink_text_end:
+
+.global __program_blink_end
+__program_blink_end:
+
+//------------------------------------------------------------------------------
+// fault: Try to read kernel memory, which must end the program.
+//------------------------------------------------------------------------------
+.balign 4
+.global __program_fault_start
+__program_fault_start:
+	adr	x0, .L_fault_text
+	adr	x1, .L_fault_text_end
+	sub	x1, x1, x0
+	mov	x8, #{SYS_WRITE}
+	svc	#0
+
+	movn	x1, #0xffff			// 0xffff_ffff_ffff_0000, in the kernel's half.
+	ldr	x0, [x1]
+
+	// Only reached if the read went through.
+	mov	x8, #{SYS_EXIT}
+	svc	#0
+
+.L_fault_text:
+	.ascii	"Reading kernel memory\n"
+.L_fault_text_end:
+
+.global __program_fault_end
+__program_fault_end:
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Architectural context switch.
+//!
+//! # Orientation
+//!
+//! Since arch modules are imported into generic modules using the path attribute, the path of this
+//! file is:
+//!
+//! crate::sched::arch_sched
+
+use core::arch::global_asm;
+
+// Assembly counterpart to this file.
+global_asm!(include_str!("sched.s"));
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The registers preserved across a switch: the callee-saved x19 to x28, the frame pointer, the
+/// link register and the stack pointer.
+#[repr(C)]
+pub struct Context {
+    x19_x28: [u64; 10],
+    fp: u64,
+    lr: u64,
+    sp: u64,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+extern "C" {
+    fn __sched_switch(from: *mut Context, to: *const Context);
+    fn __sched_task_start();
+}
+
+impl Context {
+    /// A context that is only ever saved to before it is loaded.
+    pub const fn empty() -> Self {
+        Self {
+            x19_x28: [0; 10],
+            fp: 0,
+            lr: 0,
+            sp: 0,
+        }
+    }
+
+    /// A context that calls `entry(arg)` on the stack ending at `stack_top` when first switched to.
+    ///
+    /// The frame pointer is zero, which ends backtraces at the task's entry.
+    pub fn new(stack_top: usize, entry: extern "C" fn(usize) -> !, arg: usize) -> Self {
+        let mut x19_x28 = [0; 10];
+        x19_x28[0] = arg as u64;
+        x19_x28[1] = entry as usize as u64;
+
+        Self {
+            x19_x28,
+            fp: 0,
+            lr: __sched_task_start as usize as u64,
+            sp: (stack_top & !0xF) as u64,
+        }
+    }
+}
+
+/// Save the running context to `from` and continue with `to`. Returns when `from` is switched to.
+///
+/// # Safety
+///
+/// - `to` must have been saved by a switch or created by [`Context::new()`], and its stack must
+///   still be alive.
+/// - Both contexts must stay at their address until they are switched to again.
+pub unsafe fn switch(from: *mut Context, to: *const Context) {
+    __sched_switch(from, to)
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+.section .text
+
+//------------------------------------------------------------------------------
+// fn __sched_switch(from: *mut Context, to: *const Context)
+//------------------------------------------------------------------------------
+// Save the callee-saved registers and the stack pointer to `from` and load them from `to`. The
+// return then continues wherever `to` was saved, or in __sched_task_start for a new task.
+//
+// The kernel is built soft-float, so there are no FP/SIMD registers to save.
+.global __sched_switch
+.type __sched_switch, function
+__sched_switch:
+	mov	x9, sp
+	stp	x19, x20, [x0, #16 * 0]
+	stp	x21, x22, [x0, #16 * 1]
+	stp	x23, x24, [x0, #16 * 2]
+	stp	x25, x26, [x0, #16 * 3]
+	stp	x27, x28, [x0, #16 * 4]
+	stp	x29, x30, [x0, #16 * 5]
+	str	x9,       [x0, #16 * 6]
+
+	ldp	x19, x20, [x1, #16 * 0]
+	ldp	x21, x22, [x1, #16 * 1]
+	ldp	x23, x24, [x1, #16 * 2]
+	ldp	x25, x26, [x1, #16 * 3]
+	ldp	x27, x28, [x1, #16 * 4]
+	ldp	x29, x30, [x1, #16 * 5]
+	ldr	x9,       [x1, #16 * 6]
+	mov	sp, x9
+
+	ret
+
+.size __sched_switch, . - __sched_switch
+
+//------------------------------------------------------------------------------
+// First code of a new task
+//------------------------------------------------------------------------------
+// A new task's context holds the argument in x19 and the entry function in x20. The entry function
+// never returns.
+.global __sched_task_start
+.type __sched_task_start, function
+__sched_task_start:
+	mov	x0, x19
+	blr	x20
+
+	// Should never be reached.
+.L_parking_loop:
+	wfe
+	b	.L_parking_loop
+
+.size __sched_task_start, . - __sched_task_start
@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Architectural system register table.
+//!
+//! # Orientation
+//!
+//! Since arch modules are imported into generic modules using the path attribute, the path of this
+//! file is:
+//!
+//! crate::sysreg::arch_sysreg
+
+use crate::sysreg::{Field, SysReg};
+use aarch64_cpu::registers::*;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The known system registers. Field layouts follow the Arm Architecture Reference Manual for
+/// Armv8-A.
+pub static REGISTERS: &[SysReg] = &[
+    SysReg {
+        name: "CurrentEL",
+        read: || CurrentEL.get(),
+        fields: &[Field::new("EL", 2, 2)],
+    },
+    SysReg {
+        name: "DAIF",
+        read: || DAIF.get(),
+        fields: &[
+            Field::new("F", 6, 1),
+            Field::new("I", 7, 1),
+            Field::new("A", 8, 1),
+            Field::new("D", 9, 1),
+        ],
+    },
+    SysReg {
+        name: "MIDR_EL1",
+        read: || MIDR_EL1.get(),
+        fields: &[
+            Field::new("Revision", 0, 4),
+            Field::new("PartNum", 4, 12),
+            Field::new("Arch", 16, 4),
+            Field::new("Variant", 20, 4),
+            Field::new("Implementer", 24, 8),
+        ],
+    },
+    SysReg {
+        name: "MPIDR_EL1",
+        read: || MPIDR_EL1.get(),
+        fields: &[
+            Field::new("Aff0", 0, 8),
+            Field::new("Aff1", 8, 8),
+            Field::new("Aff2", 16, 8),
+            Field::new("MT", 24, 1),
+            Field::new("U", 30, 1),
+            Field::new("Aff3", 32, 8),
+        ],
+    },
+    SysReg {
+        name: "SCTLR_EL1",
+        read: || SCTLR_EL1.get(),
+        fields: &[
+            Field::new("M", 0, 1),
+            Field::new("A", 1, 1),
+            Field::new("C", 2, 1),
+            Field::new("SA", 3, 1),
+            Field::new("SA0", 4, 1),
+            Field::new("I", 12, 1),
+            Field::new("WXN", 19, 1),
+            Field::new("E0E", 24, 1),
+            Field::new("EE", 25, 1),
+        ],
+    },
+    SysReg {
+        name: "TCR_EL1",
+        read: || TCR_EL1.get(),
+        fields: &[
+            Field::new("T0SZ", 0, 6),
+            Field::new("EPD0", 7, 1),
+            Field::new("IRGN0", 8, 2),
+            Field::new("ORGN0", 10, 2),
+            Field::new("SH0", 12, 2),
+            Field::new("TG0", 14, 2),
+            Field::new("T1SZ", 16, 6),
+            Field::new("A1", 22, 1),
+            Field::new("EPD1", 23, 1),
+            Field::new("IRGN1", 24, 2),
+            Field::new("ORGN1", 26, 2),
+            Field::new("SH1", 28, 2),
+            Field::new("TG1", 30, 2),
+            Field::new("IPS", 32, 3),
+            Field::new("AS", 36, 1),
+        ],
+    },
+    SysReg {
+        name: "MAIR_EL1",
+        read: || MAIR_EL1.get(),
+        fields: &[
+            Field::new("Attr0", 0, 8),
+            Field::new("Attr1", 8, 8),
+            Field::new("Attr2", 16, 8),
+            Field::new("Attr3", 24, 8),
+            Field::new("Attr4", 32, 8),
+            Field::new("Attr5", 40, 8),
+            Field::new("Attr6", 48, 8),
+            Field::new("Attr7", 56, 8),
+        ],
+    },
+    SysReg {
+        name: "TTBR0_EL1",
+        read: || TTBR0_EL1.get(),
+        fields: &[
+            Field::new("CnP", 0, 1),
+            Field::new("BADDR", 1, 47),
+            Field::new("ASID", 48, 16),
+        ],
+    },
+    SysReg {
+        name: "TTBR1_EL1",
+        read: || TTBR1_EL1.get(),
+        fields: &[
+            Field::new("CnP", 0, 1),
+            Field::new("BADDR", 1, 47),
+            Field::new("ASID", 48, 16),
+        ],
+    },
+    SysReg {
+        name: "ID_AA64MMFR0_EL1",
+        read: || ID_AA64MMFR0_EL1.get(),
+        fields: &[
+            Field::new("PARange", 0, 4),
+            Field::new("ASIDBits", 4, 4),
+            Field::new("BigEnd", 8, 4),
+            Field::new("TGran16", 20, 4),
+            Field::new("TGran64", 24, 4),
+            Field::new("TGran4", 28, 4),
+        ],
+    },
+    SysReg {
+        name: "VBAR_EL1",
+        read: || VBAR_EL1.get(),
+        fields: &[],
+    },
+    SysReg {
+        name: "ESR_EL1",
+        read: || ESR_EL1.get(),
+        fields: &[
+            Field::new("ISS", 0, 25),
+            Field::new("IL", 25, 1),
+            Field::new("EC", 26, 6),
+        ],
+    },
+    SysReg {
+        name: "FAR_EL1",
+        read: || FAR_EL1.get(),
+        fields: &[],
+    },
+    SysReg {
+        name: "CNTFRQ_EL0",
+        read: || CNTFRQ_EL0.get(),
+        fields: &[Field::new("Freq", 0, 32)],
+    },
+    SysReg {
+        name: "CNTPCT_EL0",
+        read: || CNTPCT_EL0.get(),
+        fields: &[],
+    },
+    SysReg {
+        name: "CNTP_CTL_EL0",
+        read: || CNTP_CTL_EL0.get(),
+        fields: &[
+            Field::new("ENABLE", 0, 1),
+            Field::new("IMASK", 1, 1),
+            Field::new("ISTATUS", 2, 1),
+        ],
+    },
+    SysReg {
+        name: "CNTP_CVAL_EL0",
+        read: || CNTP_CVAL_EL0.get(),
+        fields: &[],
+    },
+];
@@ -19,6 +19,7 @@ use aarch64_cpu::{asm::barrier, registers::*};
 use core::{
     num::{NonZeroU128, NonZeroU32, NonZeroU64},
     ops::{Add, Div},
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
     time::Duration,
 };
 use tock_registers::interfaces::{ReadWriteable, Readable, Writeable};
@@ -41,11 +42,27 @@ struct GenericTimerCounterValue(u64);
 #[no_mangle]
 static ARCH_TIMER_COUNTER_FREQUENCY: NonZeroU32 = NonZeroU32::MIN;
 
+/// The calibrated frequency, if it replaced the one the firmware put in CNTFRQ_EL0. Zero if not.
+static CORRECTED_FREQUENCY: AtomicU32 = AtomicU32::new(0);
+
+/// Counter value and uptime in nanoseconds at the last frequency change. The uptime counts from
+/// there at the new frequency, so that it doesn't jump.
+static EPOCH_COUNTER: AtomicU64 = AtomicU64::new(0);
+static EPOCH_NANOS: AtomicU64 = AtomicU64::new(0);
+
 //--------------------------------------------------------------------------------------------------
 // Private Code
 //--------------------------------------------------------------------------------------------------
 
 fn arch_timer_counter_frequency() -> NonZeroU32 {
+    if let Some(corrected) = NonZeroU32::new(CORRECTED_FREQUENCY.load(Ordering::Relaxed)) {
+        return corrected;
+    }
+
+    reported_frequency()
+}
+
+fn reported_frequency() -> NonZeroU32 {
     // Read volatile is needed here to prevent the compiler from optimizing
     // ARCH_TIMER_COUNTER_FREQUENCY away.
     //
@@ -129,6 +146,13 @@ fn read_cntpct() -> GenericTimerCounterValue {
     GenericTimerCounterValue(cnt)
 }
 
+fn epoch() -> (GenericTimerCounterValue, Duration) {
+    (
+        GenericTimerCounterValue(EPOCH_COUNTER.load(Ordering::Relaxed)),
+        Duration::from_nanos(EPOCH_NANOS.load(Ordering::Relaxed)),
+    )
+}
+
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
@@ -142,7 +166,42 @@ pub fn resolution() -> Duration {
 ///
 /// This includes time consumed by firmware and bootloaders.
 pub fn uptime() -> Duration {
-    read_cntpct().into()
+    let (epoch_counter, epoch_time) = epoch();
+    let since_epoch = GenericTimerCounterValue(read_cntpct().0.wrapping_sub(epoch_counter.0));
+
+    epoch_time + Duration::from(since_epoch)
+}
+
+/// The raw counter value.
+pub fn counter() -> u64 {
+    read_cntpct().0
+}
+
+/// The counter frequency in use.
+pub fn frequency() -> NonZeroU32 {
+    arch_timer_counter_frequency()
+}
+
+/// The counter frequency as reported by the firmware in CNTFRQ_EL0.
+pub fn firmware_frequency() -> NonZeroU32 {
+    reported_frequency()
+}
+
+/// Convert counter values at `frequency` from now on. The uptime continues from its current value.
+///
+/// Timer IRQs must be masked, and the caller must program the next timeout IRQ again afterwards.
+pub fn set_frequency(frequency: NonZeroU32) {
+    let now = read_cntpct();
+    let now_time = uptime();
+
+    EPOCH_COUNTER.store(now.0, Ordering::Relaxed);
+    EPOCH_NANOS.store(now_time.as_nanos() as u64, Ordering::Relaxed);
+    let corrected = if frequency == reported_frequency() {
+        0
+    } else {
+        frequency.get()
+    };
+    CORRECTED_FREQUENCY.store(corrected, Ordering::Relaxed);
 }
 
 /// Spin for a given duration.
@@ -169,9 +228,12 @@ pub const fn timeout_irq() -> exception::asynchronous::IRQNumber {
     bsp::exception::asynchronous::irq_map::ARM_NS_PHYSICAL_TIMER
 }
 
-/// Program a timer IRQ to be fired after `delay` has passed.
+/// Program a timer IRQ to be fired once the uptime reached `due_time`.
 pub fn set_timeout_irq(due_time: Duration) {
-    let counter_value_target: GenericTimerCounterValue = match due_time.try_into() {
+    let (epoch_counter, epoch_time) = epoch();
+    let due_since_epoch = due_time.saturating_sub(epoch_time);
+
+    let mut counter_value_target: GenericTimerCounterValue = match due_since_epoch.try_into() {
         Err(msg) => {
             warn!("set_timeout: {}. Skipping", msg);
             return;
@@ -179,6 +241,13 @@ pub fn set_timeout_irq(due_time: Duration) {
         Ok(val) => val,
     };
 
+    // The conversion rounds down. Round up instead, so that the IRQ doesn't fire before the
+    // timeout is due.
+    if Duration::from(counter_value_target) < due_since_epoch {
+        counter_value_target = counter_value_target + GenericTimerCounterValue(1);
+    }
+    counter_value_target = counter_value_target + epoch_counter;
+
     // Set the compare value register.
     CNTP_CVAL_EL0.set(counter_value_target.0);
 
@@ -85,13 +85,10 @@ impl fmt::Display for Backtrace {
                             BacktraceItem::Link(addr) => {
                                 fmt_res = writeln!(
                                     f,
-                                    "      {:>2}. {:016x} | {:<50}",
+                                    "      {:>2}. {:016x} | {}",
                                     i + 1,
                                     addr.as_usize(),
-                                    match symbols::lookup_symbol(addr) {
-                                        Some(sym) => sym.name(),
-                                        _ => "Symbol not found",
-                                    }
+                                    symbols::SymbolOffset(addr)
                                 )
                             }
                         };
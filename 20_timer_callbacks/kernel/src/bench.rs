@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Benchmarks.
+//!
+//! Benchmarks take seconds, so each runs as a kernel thread and prints its report when done. Time
+//! other threads or IRQ handlers take meanwhile is counted too, so a quiet system gives the most
+//! telling numbers.
+//!
+//! CoreMark reports in the format of the reference implementation, so that the results can be put
+//! next to published ones. Ticks are microseconds of uptime.
+
+mod coremark;
+
+use crate::{build_config, info, task, time, warn};
+use core::time::Duration;
+use coremark::CoreMark;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Shortest run CoreMark accepts as a valid result.
+const COREMARK_MIN_RUN: Duration = Duration::from_secs(10);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Time `iterations` iterations.
+fn time_iterations(coremark: &mut CoreMark, iterations: u32) -> Duration {
+    let start = time::time_manager().uptime();
+    coremark.iterate(iterations);
+
+    time::time_manager().uptime() - start
+}
+
+/// Pick a number of iterations that runs for a bit over [`COREMARK_MIN_RUN`], the way the
+/// reference implementation does.
+fn coremark_calibrate(coremark: &mut CoreMark) -> u32 {
+    let mut iterations = 1u32;
+    let secs = loop {
+        iterations = iterations.saturating_mul(10);
+        let elapsed = time_iterations(coremark, iterations);
+        if elapsed >= Duration::from_secs(1) {
+            break elapsed.as_secs() as u32;
+        }
+    };
+
+    iterations.saturating_mul(1 + COREMARK_MIN_RUN.as_secs() as u32 / secs)
+}
+
+fn run_coremark(iterations: Option<u32>) {
+    let mut coremark = CoreMark::new(coremark::PERFORMANCE_SEEDS);
+
+    info!("Running CoreMark...");
+    let iterations = iterations.unwrap_or_else(|| coremark_calibrate(&mut coremark));
+    let elapsed = time_iterations(&mut coremark, iterations);
+    let secs = elapsed.as_secs_f64();
+
+    let mut errors = coremark.errors().unwrap_or(0);
+    if let Some(name) = coremark.run_name() {
+        info!("{} parameters for coremark.", name);
+    }
+    info!("CoreMark Size    : {}", coremark.size());
+    info!("Total ticks      : {}", elapsed.as_micros());
+    info!("Total time (secs): {:.6}", secs);
+    if secs > 0.0 {
+        info!("Iterations/Sec   : {:.6}", iterations as f64 / secs);
+    }
+    if elapsed < COREMARK_MIN_RUN {
+        warn!("ERROR! Must execute for at least 10 secs for a valid result!");
+        errors += 1;
+    }
+    info!("Iterations       : {}", iterations);
+    info!(
+        "Compiler version : rustc, {} profile",
+        build_config::PROFILE
+    );
+    info!("Compiler flags   : {}", build_config::TARGET);
+    info!("Memory location  : Heap");
+
+    let [list, matrix, state, fin] = coremark.crcs();
+    info!("seedcrc          : {:#06x}", coremark.seed_crc());
+    info!("[0]crclist       : {:#06x}", list);
+    info!("[0]crcmatrix     : {:#06x}", matrix);
+    info!("[0]crcstate      : {:#06x}", state);
+    info!("[0]crcfinal      : {:#06x}", fin);
+
+    if errors == 0 {
+        info!("Correct operation validated.");
+        info!(
+            "CoreMark 1.0 : {:.6} / rustc {}",
+            iterations as f64 / secs,
+            build_config::PROFILE
+        );
+    } else {
+        warn!("Errors detected");
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Run CoreMark for `iterations`, or for at least 10 seconds if not given. Returns the thread's id.
+pub fn coremark(iterations: Option<u32>) -> Result<usize, &'static str> {
+    if iterations == Some(0) {
+        return Err("Iterations must not be zero");
+    }
+
+    task::spawn("coremark", move || run_coremark(iterations))
+}
@@ -0,0 +1,811 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! CoreMark 1.0, ported to Rust.
+//!
+//! A port of EEMBC's CoreMark that keeps the original's algorithms and their integer arithmetic,
+//! so that the CRCs come out the same as those of the C version and the run can be validated
+//! against them. The list works on indices into arrays instead of pointers, which changes nothing
+//! the benchmark measures, as the C version never looks at addresses either.
+//!
+//! The data set is the standard one of 2000 bytes, split evenly between the linked list, matrix
+//! and state machine work.
+
+use alloc::{vec, vec::Vec};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of algorithms sharing the data set.
+const NUM_ALGORITHMS: u32 = 3;
+
+/// Bytes a list item takes in the C version, which sizes the list.
+const LIST_ITEM_SIZE: u32 = 16 + 4;
+
+/// Seed CRCs of the known parameter sets, and the CRCs a correct run of each produces.
+const KNOWN: [(u16, &str, u16, u16, u16); 5] = [
+    (0x8a02, "6k performance run", 0xd4b0, 0xbe52, 0x5e47),
+    (0x7b05, "6k validation run", 0x3340, 0x1199, 0x39bf),
+    (0x4eaf, "Profile generation run", 0x6a79, 0x5608, 0xe5a4),
+    (0xe9f5, "2K performance run", 0xe714, 0x1fd7, 0x8e3a),
+    (0x18f2, "2K validation run", 0xe3c1, 0x0747, 0x8d84),
+];
+
+const INT_PATTERNS: [&[u8]; 4] = [b"5012", b"1234", b"-874", b"+122"];
+const FLOAT_PATTERNS: [&[u8]; 4] = [b"35.54400", b".1234500", b"-110.700", b"+0.64400"];
+const SCI_PATTERNS: [&[u8]; 4] = [b"5.500e+3", b"-.123e-2", b"-87e+832", b"+0.6e-12"];
+const ERR_PATTERNS: [&[u8]; 4] = [b"T0.3e-1F", b"-T.T++Tq", b"1T3.4e4z", b"34.0e-T^"];
+
+/// States of the number parser.
+const START: usize = 0;
+const INVALID: usize = 1;
+const S1: usize = 2;
+const S2: usize = 3;
+const INT: usize = 4;
+const FLOAT: usize = 5;
+const EXPONENT: usize = 6;
+const SCIENTIFIC: usize = 7;
+const NUM_STATES: usize = 8;
+
+#[derive(Copy, Clone, Default)]
+struct ListData {
+    data16: i16,
+    idx: i16,
+}
+
+#[derive(Copy, Clone, Default)]
+struct Node {
+    next: Option<usize>,
+
+    /// Index of the node's data, which the list swaps between nodes.
+    info: usize,
+}
+
+struct List {
+    nodes: Vec<Node>,
+    data: Vec<ListData>,
+}
+
+#[derive(Copy, Clone)]
+enum Compare {
+    /// By the result of the matrix or state work encoded in the data.
+    Complex,
+
+    /// By index, restoring the data's upper byte on the way.
+    Index,
+}
+
+struct Matrix {
+    n: usize,
+    a: Vec<i16>,
+    b: Vec<i16>,
+    c: Vec<i32>,
+}
+
+/// Everything the list's comparison may run.
+struct Work {
+    seed1: i16,
+    seed2: i16,
+    size: u32,
+    matrix: Matrix,
+    state: Vec<u8>,
+    crc: u16,
+    crc_matrix: u16,
+    crc_state: u16,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Size of the whole data set, in bytes.
+pub const TOTAL_DATA_SIZE: u32 = 2000;
+
+/// Seeds of the standard performance run.
+pub const PERFORMANCE_SEEDS: (i16, i16, i16) = (0, 0, 0x66);
+
+/// A CoreMark instance.
+pub struct CoreMark {
+    seed3: i16,
+    list: List,
+    head: usize,
+    work: Work,
+    crc_list: u16,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn crcu8(mut data: u8, mut crc: u16) -> u16 {
+    for _ in 0..8 {
+        let x16 = (data & 1) ^ (crc as u8 & 1);
+        data >>= 1;
+        if x16 == 1 {
+            crc ^= 0x4002;
+            crc = (crc >> 1) | 0x8000;
+        } else {
+            crc = (crc >> 1) & 0x7fff;
+        }
+    }
+
+    crc
+}
+
+fn crcu16(value: u16, crc: u16) -> u16 {
+    crcu8((value >> 8) as u8, crcu8(value as u8, crc))
+}
+
+fn crc16(value: i16, crc: u16) -> u16 {
+    crcu16(value as u16, crc)
+}
+
+fn crcu32(value: u32, crc: u16) -> u16 {
+    crc16((value >> 16) as i16, crc16(value as i16, crc))
+}
+
+impl List {
+    /// Build the list in a block of `blksize` bytes, sorted by index.
+    fn new(blksize: u32, seed: i16) -> (Self, usize) {
+        let size = (blksize / LIST_ITEM_SIZE - 2) as usize;
+        let mut list = Self {
+            nodes: vec![Node::default(); size],
+            data: vec![ListData::default(); size],
+        };
+
+        // Head and tail.
+        list.data[0] = ListData {
+            data16: 0x8080u16 as i16,
+            idx: 0,
+        };
+        let mut free = 1;
+        list.insert_new(
+            0,
+            ListData {
+                data16: -1,
+                idx: 0x7fff,
+            },
+            &mut free,
+        );
+
+        // Items alternating between the algorithms. Like in the C version, the block fills up
+        // before all of them are in.
+        for i in 0..size as u16 {
+            let pattern = (seed as u16 ^ i) & 0xf;
+            let dat = (pattern << 3) | (i & 0x7);
+            list.insert_new(
+                0,
+                ListData {
+                    data16: ((dat << 8) | dat) as i16,
+                    idx: 0,
+                },
+                &mut free,
+            );
+        }
+
+        // Index the first 20% in order and the rest pseudo-randomly after them.
+        let mut finder = list.nodes[0].next.unwrap();
+        let mut i = 1u32;
+        while let Some(next) = list.nodes[finder].next {
+            let info = list.nodes[finder].info;
+            if i < size as u32 / 5 {
+                list.data[info].idx = i as i16;
+                i += 1;
+            } else {
+                let pattern = (i ^ seed as i32 as u32) as u16 as u32;
+                i += 1;
+                list.data[info].idx = (0x3fff & (((i & 0x07) << 8) | pattern)) as i16;
+            }
+            finder = next;
+        }
+
+        let head = list.sort(0, Compare::Index, None);
+        (list, head)
+    }
+
+    /// Insert a copy of `info` after `at`, unless the block is full.
+    fn insert_new(&mut self, at: usize, info: ListData, free: &mut usize) {
+        if *free + 1 >= self.nodes.len() {
+            return;
+        }
+
+        let new = *free;
+        *free += 1;
+        self.nodes[new] = Node {
+            next: self.nodes[at].next,
+            info: new,
+        };
+        self.nodes[at].next = Some(new);
+        self.data[new] = info;
+    }
+
+    fn find(&self, mut list: Option<usize>, info: &ListData) -> Option<usize> {
+        while let Some(node) = list {
+            let data = &self.data[self.nodes[node].info];
+            let hit = if info.idx >= 0 {
+                data.idx == info.idx
+            } else {
+                data.data16 & 0xff == info.data16
+            };
+            if hit {
+                break;
+            }
+            list = self.nodes[node].next;
+        }
+
+        list
+    }
+
+    fn reverse(&mut self, mut list: Option<usize>) -> usize {
+        let mut next = None;
+        while let Some(node) = list {
+            list = self.nodes[node].next;
+            self.nodes[node].next = next;
+            next = Some(node);
+        }
+
+        next.unwrap()
+    }
+
+    /// Unlink the node after `item`, moving its data to `item`.
+    fn remove(&mut self, item: usize) -> usize {
+        let removed = self.nodes[item].next.unwrap();
+        self.swap_info(item, removed);
+        self.nodes[item].next = self.nodes[removed].next;
+        self.nodes[removed].next = None;
+
+        removed
+    }
+
+    fn undo_remove(&mut self, removed: usize, modified: usize) {
+        self.swap_info(removed, modified);
+        self.nodes[removed].next = self.nodes[modified].next;
+        self.nodes[modified].next = Some(removed);
+    }
+
+    fn swap_info(&mut self, a: usize, b: usize) {
+        let info = self.nodes[a].info;
+        self.nodes[a].info = self.nodes[b].info;
+        self.nodes[b].info = info;
+    }
+
+    fn compare(&mut self, a: usize, b: usize, cmp: Compare, work: Option<&mut Work>) -> i32 {
+        let (a, b) = (self.nodes[a].info, self.nodes[b].info);
+
+        match (cmp, work) {
+            (Compare::Complex, Some(work)) => {
+                let x = work.calc(&mut self.data[a].data16);
+                let y = work.calc(&mut self.data[b].data16);
+                x as i32 - y as i32
+            }
+            _ => {
+                for i in [a, b] {
+                    let d = self.data[i].data16 as i32;
+                    self.data[i].data16 = ((d & 0xff00) | (0xff & (d >> 8))) as i16;
+                }
+                self.data[a].idx as i32 - self.data[b].idx as i32
+            }
+        }
+    }
+
+    /// Bottom-up merge sort, comparing in exactly the order of the C version.
+    fn sort(&mut self, list: usize, cmp: Compare, mut work: Option<&mut Work>) -> usize {
+        let mut list = Some(list);
+        let mut insize = 1;
+
+        loop {
+            let mut p = list;
+            list = None;
+            let mut tail: Option<usize> = None;
+            let mut merges = 0;
+
+            while let Some(start) = p {
+                merges += 1;
+                let mut q = Some(start);
+                let mut psize = 0;
+                for _ in 0..insize {
+                    psize += 1;
+                    q = self.nodes[q.unwrap()].next;
+                    if q.is_none() {
+                        break;
+                    }
+                }
+                let mut qsize = insize;
+
+                while psize > 0 || (qsize > 0 && q.is_some()) {
+                    let e;
+                    if psize == 0 {
+                        e = q.unwrap();
+                        q = self.nodes[e].next;
+                        qsize -= 1;
+                    } else if qsize == 0
+                        || q.is_none()
+                        || self.compare(p.unwrap(), q.unwrap(), cmp, work.as_deref_mut()) <= 0
+                    {
+                        e = p.unwrap();
+                        p = self.nodes[e].next;
+                        psize -= 1;
+                    } else {
+                        e = q.unwrap();
+                        q = self.nodes[e].next;
+                        qsize -= 1;
+                    }
+
+                    match tail {
+                        Some(t) => self.nodes[t].next = Some(e),
+                        None => list = Some(e),
+                    }
+                    tail = Some(e);
+                }
+
+                p = q;
+            }
+
+            self.nodes[tail.unwrap()].next = None;
+            if merges <= 1 {
+                return list.unwrap();
+            }
+            insize *= 2;
+        }
+    }
+}
+
+impl Matrix {
+    fn new(blksize: u32, seed: i32) -> Self {
+        let mut seed = if seed == 0 { 1 } else { seed };
+        let (mut i, mut j) = (0u32, 0u32);
+        while j < blksize {
+            i += 1;
+            j = i * i * 2 * 4;
+        }
+        let n = (i - 1) as usize;
+
+        let mut matrix = Self {
+            n,
+            a: vec![0; n * n],
+            b: vec![0; n * n],
+            c: vec![0; n * n],
+        };
+        let mut order = 1i32;
+        for k in 0..n * n {
+            seed = (order * seed) % 65536;
+            let val = (seed + order) as i16;
+            matrix.b[k] = val;
+            matrix.a[k] = ((val as i32 + order) & 0xff) as i16;
+            order += 1;
+        }
+
+        matrix
+    }
+
+    fn add_const(&mut self, val: i16) {
+        self.a.iter_mut().for_each(|x| *x = x.wrapping_add(val));
+    }
+
+    fn mul_const(&mut self, val: i16) {
+        for (c, a) in self.c.iter_mut().zip(self.a.iter()) {
+            *c = *a as i32 * val as i32;
+        }
+    }
+
+    fn mul_vect(&mut self) {
+        let n = self.n;
+        for i in 0..n {
+            self.c[i] = 0;
+            for j in 0..n {
+                self.c[i] = self.c[i].wrapping_add(self.a[i * n + j] as i32 * self.b[j] as i32);
+            }
+        }
+    }
+
+    fn mul_matrix(&mut self, bit_extract: bool) {
+        let n = self.n;
+        for i in 0..n {
+            for j in 0..n {
+                let mut sum = 0i32;
+                for k in 0..n {
+                    let tmp = self.a[i * n + k] as i32 * self.b[k * n + j] as i32;
+                    let term = if bit_extract {
+                        (((tmp >> 2) & 0xf) as u32 * ((tmp >> 5) & 0x7f) as u32) as i32
+                    } else {
+                        tmp
+                    };
+                    sum = sum.wrapping_add(term);
+                }
+                self.c[i * n + j] = sum;
+            }
+        }
+    }
+
+    fn sum(&self, clipval: i16) -> i16 {
+        let (mut tmp, mut prev, mut ret) = (0i32, 0i32, 0i16);
+        for &cur in self.c.iter() {
+            tmp = tmp.wrapping_add(cur);
+            if tmp > clipval as i32 {
+                ret = ret.wrapping_add(10);
+                tmp = 0;
+            } else if cur > prev {
+                ret = ret.wrapping_add(1);
+            }
+            prev = cur;
+        }
+
+        ret
+    }
+
+    fn bench(&mut self, val: i16, crc: u16) -> u16 {
+        let clipval = (0xf000 | val as i32) as i16;
+        let mut test = 0;
+
+        self.add_const(val);
+        self.mul_const(val);
+        test = crc16(self.sum(clipval), test);
+        self.mul_vect();
+        test = crc16(self.sum(clipval), test);
+        self.mul_matrix(false);
+        test = crc16(self.sum(clipval), test);
+        self.mul_matrix(true);
+        test = crc16(self.sum(clipval), test);
+        self.add_const(val.wrapping_neg());
+
+        crc16(test as i16, crc)
+    }
+}
+
+/// Fill `p` with comma separated numbers, valid and not, followed by zeros.
+fn state_init(p: &mut [u8], seed: i16) {
+    let size = p.len() as u32 - 1;
+    let (mut total, mut next) = (0u32, 0u32);
+    let mut buf: &[u8] = &[];
+    let mut seed = seed;
+
+    while total + next + 1 < size {
+        if next > 0 {
+            let at = total as usize;
+            p[at..at + next as usize].copy_from_slice(&buf[..next as usize]);
+            p[at + next as usize] = b',';
+            total += next + 1;
+        }
+        seed = seed.wrapping_add(1);
+        let pattern = ((seed >> 3) & 0x3) as usize;
+        (buf, next) = match seed & 0x7 {
+            0..=2 => (INT_PATTERNS[pattern], 4),
+            3 | 4 => (FLOAT_PATTERNS[pattern], 8),
+            5 | 6 => (SCI_PATTERNS[pattern], 8),
+            _ => (ERR_PATTERNS[pattern], 8),
+        };
+    }
+
+    p[total as usize..].fill(0);
+}
+
+/// Run the parser over the number starting at `*at`, counting transitions, and move `*at` past
+/// it. Returns the final state.
+fn state_transition(mem: &[u8], at: &mut usize, counts: &mut [u32; NUM_STATES]) -> usize {
+    let is_digit = |c: u8| c.is_ascii_digit();
+    let mut s = *at;
+    let mut state = START;
+
+    while mem.get(s).map_or(false, |c| *c != 0) && state != INVALID {
+        let c = mem[s];
+        if c == b',' {
+            s += 1;
+            break;
+        }
+
+        match state {
+            START => {
+                state = if is_digit(c) {
+                    INT
+                } else if c == b'+' || c == b'-' {
+                    S1
+                } else if c == b'.' {
+                    FLOAT
+                } else {
+                    counts[INVALID] += 1;
+                    INVALID
+                };
+                counts[START] += 1;
+            }
+            S1 => {
+                state = if is_digit(c) {
+                    INT
+                } else if c == b'.' {
+                    FLOAT
+                } else {
+                    INVALID
+                };
+                counts[S1] += 1;
+            }
+            INT => {
+                if c == b'.' {
+                    state = FLOAT;
+                    counts[INT] += 1;
+                } else if !is_digit(c) {
+                    state = INVALID;
+                    counts[INT] += 1;
+                }
+            }
+            FLOAT => {
+                if c == b'E' || c == b'e' {
+                    state = S2;
+                    counts[FLOAT] += 1;
+                } else if !is_digit(c) {
+                    state = INVALID;
+                    counts[FLOAT] += 1;
+                }
+            }
+            S2 => {
+                state = if c == b'+' || c == b'-' {
+                    EXPONENT
+                } else {
+                    INVALID
+                };
+                counts[S2] += 1;
+            }
+            EXPONENT => {
+                state = if is_digit(c) { SCIENTIFIC } else { INVALID };
+                counts[EXPONENT] += 1;
+            }
+            SCIENTIFIC => {
+                if !is_digit(c) {
+                    state = INVALID;
+                    counts[INVALID] += 1;
+                }
+            }
+            _ => (),
+        }
+        s += 1;
+    }
+
+    *at = s;
+    state
+}
+
+impl Work {
+    fn bench_state(&mut self, step: i16, crc: u16) -> u16 {
+        let blksize = self.size as usize;
+        let mut final_counts = [0u32; NUM_STATES];
+        let mut track_counts = [0u32; NUM_STATES];
+
+        let mut scan = |mem: &[u8], final_counts: &mut [u32; NUM_STATES]| {
+            let mut at = 0;
+            while mem.get(at).map_or(false, |c| *c != 0) {
+                final_counts[state_transition(mem, &mut at, &mut track_counts)] += 1;
+            }
+        };
+        let corrupt = |mem: &mut [u8], seed: i16| {
+            for c in mem[..blksize].iter_mut().step_by(step as usize) {
+                if *c != b',' {
+                    *c ^= seed as u8;
+                }
+            }
+        };
+
+        scan(&self.state, &mut final_counts);
+        corrupt(&mut self.state, self.seed1);
+        scan(&self.state, &mut final_counts);
+        corrupt(&mut self.state, self.seed2);
+
+        let mut crc = crc;
+        for i in 0..NUM_STATES {
+            crc = crcu32(final_counts[i], crc);
+            crc = crcu32(track_counts[i], crc);
+        }
+
+        crc
+    }
+
+    /// Run the work encoded in a list item's data, or return the result cached in it.
+    fn calc(&mut self, pdata: &mut i16) -> i16 {
+        let data = *pdata;
+        if (data >> 7) & 1 != 0 {
+            return data & 0x007f;
+        }
+
+        let mut dtype = (data >> 3) & 0xf;
+        dtype |= dtype << 4;
+        let result = match data & 0x7 {
+            0 => {
+                // Minimum period of the corruption.
+                let result = self.bench_state(dtype.max(0x22), self.crc) as i16;
+                if self.crc_state == 0 {
+                    self.crc_state = result as u16;
+                }
+                result
+            }
+            1 => {
+                let result = self.matrix.bench(dtype, self.crc) as i16;
+                if self.crc_matrix == 0 {
+                    self.crc_matrix = result as u16;
+                }
+                result
+            }
+            _ => data,
+        };
+
+        self.crc = crcu16(result as u16, self.crc);
+        let result = result & 0x007f;
+        *pdata = (data & 0xff00u16 as i16) | 0x0080 | result;
+
+        result
+    }
+}
+
+impl CoreMark {
+    fn bench_list(&mut self, finder_idx: i16) -> u16 {
+        let list = &mut self.list;
+        let mut head = self.head;
+        let (mut retval, mut found, mut missed) = (0u16, 0u16, 0u16);
+        let mut info = ListData {
+            data16: 0,
+            idx: finder_idx,
+        };
+
+        // Find values, reversing the list each time and moving the item after a hit to the front.
+        for i in 0..self.seed3 {
+            info.data16 = i & 0xff;
+            let this_find = list.find(Some(head), &info);
+            head = list.reverse(Some(head));
+            match this_find {
+                None => {
+                    missed += 1;
+                    let next = list.nodes[head].next.unwrap();
+                    retval += ((list.data[list.nodes[next].info].data16 >> 8) & 1) as u16;
+                }
+                Some(hit) => {
+                    found += 1;
+                    let data = list.data[list.nodes[hit].info].data16;
+                    if data & 0x1 != 0 {
+                        retval += ((data >> 9) & 1) as u16;
+                    }
+                    if let Some(finder) = list.nodes[hit].next {
+                        list.nodes[hit].next = list.nodes[finder].next;
+                        list.nodes[finder].next = list.nodes[head].next;
+                        list.nodes[head].next = Some(finder);
+                    }
+                }
+            }
+            if info.idx >= 0 {
+                info.idx += 1;
+            }
+        }
+        retval = retval.wrapping_add(found.wrapping_mul(4).wrapping_sub(missed));
+
+        if finder_idx > 0 {
+            head = list.sort(head, Compare::Complex, Some(&mut self.work));
+        }
+        let removed = list.remove(list.nodes[head].next.unwrap());
+
+        // The C version CRCs the head's data on every step, which is kept to match its results.
+        let mut finder = list.find(Some(head), &info).or(list.nodes[head].next);
+        while let Some(node) = finder {
+            retval = crc16(list.data[list.nodes[head].info].data16, retval);
+            finder = list.nodes[node].next;
+        }
+        list.undo_remove(removed, list.nodes[head].next.unwrap());
+
+        head = list.sort(head, Compare::Index, None);
+        let mut finder = list.nodes[head].next;
+        while let Some(node) = finder {
+            retval = crc16(list.data[list.nodes[head].info].data16, retval);
+            finder = list.nodes[node].next;
+        }
+
+        retval
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl CoreMark {
+    /// Set up the data for the given seeds.
+    pub fn new((seed1, seed2, seed3): (i16, i16, i16)) -> Self {
+        let size = TOTAL_DATA_SIZE / NUM_ALGORITHMS;
+        let (list, head) = List::new(size, seed1);
+
+        let mut state = vec![0; size as usize];
+        state_init(&mut state, seed1);
+
+        Self {
+            seed3,
+            list,
+            head,
+            work: Work {
+                seed1,
+                seed2,
+                size,
+                matrix: Matrix::new(size, seed1 as i32 | (seed2 as i32) << 16),
+                state,
+                crc: 0,
+                crc_matrix: 0,
+                crc_state: 0,
+            },
+            crc_list: 0,
+        }
+    }
+
+    /// Run `iterations` iterations.
+    pub fn iterate(&mut self, iterations: u32) {
+        self.work.crc = 0;
+        self.crc_list = 0;
+        self.work.crc_matrix = 0;
+        self.work.crc_state = 0;
+
+        for i in 0..iterations {
+            let crc = self.bench_list(1);
+            self.work.crc = crcu16(crc, self.work.crc);
+            let crc = self.bench_list(-1);
+            self.work.crc = crcu16(crc, self.work.crc);
+            if i == 0 {
+                self.crc_list = self.work.crc;
+            }
+        }
+    }
+
+    /// Return the size of each algorithm's data, as CoreMark reports it.
+    pub fn size(&self) -> u32 {
+        self.work.size
+    }
+
+    /// Return the CRC of the seeds and size, which identifies the parameter set.
+    pub fn seed_crc(&self) -> u16 {
+        let mut crc = crc16(self.work.seed1, 0);
+        crc = crc16(self.work.seed2, crc);
+        crc = crc16(self.seed3, crc);
+        crc16(self.work.size as i16, crc)
+    }
+
+    /// Return the list, matrix, state and final CRCs of the last run.
+    pub fn crcs(&self) -> [u16; 4] {
+        [
+            self.crc_list,
+            self.work.crc_matrix,
+            self.work.crc_state,
+            self.work.crc,
+        ]
+    }
+
+    /// Return the name of the parameter set, if it is a known one.
+    pub fn run_name(&self) -> Option<&'static str> {
+        KNOWN.iter().find(|k| k.0 == self.seed_crc()).map(|k| k.1)
+    }
+
+    /// Return the number of CRCs of the last run that differ from the known ones, or `None` if
+    /// the parameter set isn't a known one.
+    pub fn errors(&self) -> Option<usize> {
+        let known = KNOWN.iter().find(|k| k.0 == self.seed_crc())?;
+        let [list, matrix, state, _] = self.crcs();
+
+        Some(
+            [(list, known.2), (matrix, known.3), (state, known.4)]
+                .iter()
+                .filter(|(crc, expected)| crc != expected)
+                .count(),
+        )
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A single iteration of the performance run must produce the published CRCs.
+    #[kernel_test]
+    fn performance_run_validates() {
+        let mut coremark = CoreMark::new(PERFORMANCE_SEEDS);
+
+        coremark.iterate(1);
+        assert_eq!(coremark.seed_crc(), 0xe9f5);
+        assert_eq!(coremark.errors(), Some(0));
+    }
+}
@@ -0,0 +1,381 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Block devices.
+//!
+//! Storage is accessed in blocks of [`BLOCK_SIZE`] bytes. Drivers register their devices with
+//! [`register_device()`], e.g. the SD card as `sd0`, and users such as the USB mass storage gadget
+//! look them up by name. A BSP that can't reach a device a user may expect, e.g. the SD card on the
+//! Raspberry Pi 3, names it with [`register_missing()`], so that looking it up tells why it is
+//! missing.
+//! [`create_ram_disk()`] adds a device backed by kernel heap, for testing without storage.
+//!
+//! [`read_bytes()`] reads data of a known length, e.g. a firmware image written to a device with
+//! `dd`.
+//!
+//! For testing the block layer, [`fill()`] writes a pattern that depends on a seed and each block's
+//! number, and [`verify()`] checks that it reads back. Misplaced blocks fail the check as well as
+//! corrupted ones.
+
+use crate::{
+    info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+};
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+use core::fmt::Write;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of blocks `fill()` and `verify()` transfer at once.
+const PATTERN_CHUNK: usize = 16;
+
+/// Bytes per line of a hex dump.
+const HEXDUMP_WIDTH: usize = 16;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Size of a block in bytes.
+pub const BLOCK_SIZE: usize = 512;
+
+/// Block device interfaces.
+pub mod interface {
+    /// A device that stores blocks.
+    pub trait BlockDevice {
+        /// Name of the device.
+        fn name(&self) -> &str;
+
+        /// Number of blocks.
+        fn block_count(&self) -> u64;
+
+        /// Fill `buf`, a whole number of blocks, from block `lba` on.
+        fn read(&self, lba: u64, buf: &mut [u8]) -> Result<(), &'static str>;
+
+        /// Write `buf`, a whole number of blocks, from block `lba` on.
+        fn write(&self, lba: u64, buf: &[u8]) -> Result<(), &'static str>;
+    }
+}
+
+/// A block device on the kernel heap.
+pub struct RamDisk {
+    name: String,
+    data: IRQSafeNullLock<Vec<u8>>,
+}
+
+/// A registered block device.
+pub type Device = &'static (dyn interface::BlockDevice + Sync);
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static DEVICES: IRQSafeNullLock<Vec<Device>> = IRQSafeNullLock::new(Vec::new());
+
+/// Names of devices the board can't provide, and why.
+static MISSING: IRQSafeNullLock<Vec<(&'static str, &'static str)>> =
+    IRQSafeNullLock::new(Vec::new());
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return the byte range of `len` bytes from block `lba` on, if it is whole blocks within `size`.
+fn byte_range(lba: u64, len: usize, size: usize) -> Result<core::ops::Range<usize>, &'static str> {
+    if len % BLOCK_SIZE != 0 {
+        return Err("Not a whole number of blocks");
+    }
+
+    let start = usize::try_from(lba)
+        .ok()
+        .and_then(|lba| lba.checked_mul(BLOCK_SIZE))
+        .ok_or("Block out of range")?;
+    match start.checked_add(len) {
+        Some(end) if end <= size => Ok(start..end),
+        _ => Err("Block out of range"),
+    }
+}
+
+/// Fill `buf`, which holds blocks from `lba` on, with the test pattern for `seed`.
+fn pattern(seed: u32, lba: u64, buf: &mut [u8]) {
+    for (i, block) in buf.chunks_exact_mut(BLOCK_SIZE).enumerate() {
+        let lba = lba + i as u64;
+        let base = seed ^ (lba as u32).wrapping_mul(0x9E37_79B9) ^ (lba >> 32) as u32;
+
+        for (j, word) in block.chunks_exact_mut(4).enumerate() {
+            let value = base ^ (j as u32).wrapping_mul(0x85EB_CA6B);
+            word.copy_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+/// Call `f` with every chunk of the device: its first block and its length in blocks.
+fn for_each_chunk(
+    device: Device,
+    mut f: impl FnMut(u64, usize) -> Result<(), &'static str>,
+) -> Result<u64, &'static str> {
+    let blocks = device.block_count();
+    if blocks == 0 {
+        return Err("No medium");
+    }
+
+    let mut lba = 0;
+    while lba < blocks {
+        let count = (blocks - lba).min(PATTERN_CHUNK as u64) as usize;
+        f(lba, count)?;
+        lba += count as u64;
+    }
+
+    Ok(blocks)
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl RamDisk {
+    /// Create a zeroed disk of `blocks` blocks.
+    pub fn new(name: String, blocks: usize) -> Self {
+        Self {
+            name,
+            data: IRQSafeNullLock::new(vec![0; blocks * BLOCK_SIZE]),
+        }
+    }
+}
+
+impl interface::BlockDevice for RamDisk {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn block_count(&self) -> u64 {
+        self.data.lock(|data| (data.len() / BLOCK_SIZE) as u64)
+    }
+
+    fn read(&self, lba: u64, buf: &mut [u8]) -> Result<(), &'static str> {
+        self.data.lock(|data| {
+            let range = byte_range(lba, buf.len(), data.len())?;
+            buf.copy_from_slice(&data[range]);
+
+            Ok(())
+        })
+    }
+
+    fn write(&self, lba: u64, buf: &[u8]) -> Result<(), &'static str> {
+        self.data.lock(|data| {
+            let range = byte_range(lba, buf.len(), data.len())?;
+            data[range].copy_from_slice(buf);
+
+            Ok(())
+        })
+    }
+}
+
+/// Register a block device. Names must be unique.
+pub fn register_device(new_device: Device) -> Result<(), &'static str> {
+    DEVICES.lock(|devices| {
+        if devices.iter().any(|d| d.name() == new_device.name()) {
+            return Err("Block device already registered");
+        }
+        devices.push(new_device);
+
+        Ok(())
+    })
+}
+
+/// Return the block device called `name`.
+pub fn device(name: &str) -> Option<Device> {
+    DEVICES.lock(|devices| devices.iter().find(|d| d.name() == name).copied())
+}
+
+/// Name a device the board can't provide, with the reason that [`lookup()`] reports for it.
+pub fn register_missing(name: &'static str, reason: &'static str) {
+    MISSING.lock(|missing| missing.push((name, reason)));
+}
+
+/// Return the block device called `name`, or why there is none.
+pub fn lookup(name: &str) -> Result<Device, &'static str> {
+    if let Some(d) = device(name) {
+        return Ok(d);
+    }
+
+    MISSING.lock(|missing| {
+        missing
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map_or(Err("No such block device"), |(_, reason)| Err(*reason))
+    })
+}
+
+/// Create and register a RAM disk of `blocks` blocks. Returns its name. RAM disks live until the
+/// next boot.
+pub fn create_ram_disk(blocks: usize) -> Result<String, &'static str> {
+    if blocks == 0 {
+        return Err("Empty disk");
+    }
+
+    let name = DEVICES.lock(|devices| {
+        (0..)
+            .map(|i| format!("ram{}", i))
+            .find(|name| devices.iter().all(|d| d.name() != name))
+            .unwrap_or_default()
+    });
+    let disk: &'static RamDisk = Box::leak(Box::new(RamDisk::new(name.clone(), blocks)));
+    register_device(disk)?;
+
+    Ok(name)
+}
+
+/// Read `len` bytes from block `lba` on.
+pub fn read_bytes(device: Device, lba: u64, len: usize) -> Result<Vec<u8>, &'static str> {
+    let mut buf = vec![0; (len + BLOCK_SIZE - 1) / BLOCK_SIZE * BLOCK_SIZE];
+    device.read(lba, &mut buf)?;
+    buf.truncate(len);
+
+    Ok(buf)
+}
+
+/// Overwrite the whole device with the test pattern for `seed`. Returns the number of blocks
+/// written.
+pub fn fill(device: Device, seed: u32) -> Result<u64, &'static str> {
+    let mut buf = vec![0; PATTERN_CHUNK * BLOCK_SIZE];
+
+    for_each_chunk(device, |lba, count| {
+        let buf = &mut buf[..count * BLOCK_SIZE];
+        pattern(seed, lba, buf);
+        device.write(lba, buf)
+    })
+}
+
+/// Check the whole device against the test pattern for `seed`. Returns the number of blocks that
+/// differ.
+pub fn verify(device: Device, seed: u32) -> Result<u64, &'static str> {
+    let mut expected = vec![0; PATTERN_CHUNK * BLOCK_SIZE];
+    let mut actual = vec![0; PATTERN_CHUNK * BLOCK_SIZE];
+    let mut bad = 0;
+
+    for_each_chunk(device, |lba, count| {
+        let len = count * BLOCK_SIZE;
+        pattern(seed, lba, &mut expected[..len]);
+        device.read(lba, &mut actual[..len])?;
+
+        bad += expected[..len]
+            .chunks_exact(BLOCK_SIZE)
+            .zip(actual[..len].chunks_exact(BLOCK_SIZE))
+            .filter(|(e, a)| e != a)
+            .count() as u64;
+
+        Ok(())
+    })?;
+
+    Ok(bad)
+}
+
+/// Print `blocks` blocks from block `lba` on as hex and ASCII, with their offsets on the device.
+pub fn hexdump(device: Device, lba: u64, blocks: usize) -> Result<(), &'static str> {
+    let mut buf = vec![0; blocks * BLOCK_SIZE];
+    device.read(lba, &mut buf)?;
+
+    let start = lba * BLOCK_SIZE as u64;
+    for (i, line) in buf.chunks(HEXDUMP_WIDTH).enumerate() {
+        let mut hex = String::new();
+        for b in line {
+            let _ = write!(hex, "{:02x} ", b);
+        }
+        let ascii: String = line
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        info!(
+            "      {:08x}: {}|{}|",
+            start + (i * HEXDUMP_WIDTH) as u64,
+            hex,
+            ascii
+        );
+    }
+
+    Ok(())
+}
+
+/// Print the registered block devices.
+pub fn print() {
+    DEVICES.lock(|devices| {
+        for d in devices.iter() {
+            let blocks = d.block_count();
+
+            info!(
+                "      {:<8} {:>10} blocks {:>8} KiB",
+                d.name(),
+                blocks,
+                blocks * BLOCK_SIZE as u64 / 1024
+            );
+        }
+    });
+    MISSING.lock(|missing| {
+        for (name, reason) in missing.iter() {
+            info!("      {:<8} unavailable: {}", name, reason);
+        }
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use interface::BlockDevice;
+    use test_macros::kernel_test;
+
+    /// Blocks must read back as written, and accesses past the end or of partial blocks must fail.
+    #[kernel_test]
+    fn ram_disk_bounds() {
+        let disk = RamDisk::new(String::from("test"), 4);
+        let mut buf = [0u8; 2 * BLOCK_SIZE];
+
+        buf[BLOCK_SIZE] = 0xA5;
+        disk.write(2, &buf).unwrap();
+        buf.fill(0);
+        disk.read(2, &mut buf).unwrap();
+        assert_eq!(buf[BLOCK_SIZE], 0xA5);
+
+        assert!(disk.read(3, &mut buf).is_err());
+        assert!(disk.write(0, &buf[..100]).is_err());
+        assert!(disk.read(u64::MAX, &mut buf).is_err());
+    }
+
+    /// The pattern must verify with its own seed only, and a block overwritten must be counted.
+    #[kernel_test]
+    fn pattern_verifies() {
+        let disk: Device = Box::leak(Box::new(RamDisk::new(String::from("test"), 20)));
+
+        assert_eq!(fill(disk, 1).unwrap(), 20);
+        assert_eq!(verify(disk, 1).unwrap(), 0);
+        assert_eq!(verify(disk, 2).unwrap(), 20);
+
+        disk.write(17, &[0; BLOCK_SIZE]).unwrap();
+        assert_eq!(verify(disk, 1).unwrap(), 1);
+    }
+    /// Looking up a missing device must report why it is missing, unless one registered since.
+    #[kernel_test]
+    fn missing_device_reason() {
+        register_missing("gone0", "Gone for testing");
+        assert_eq!(lookup("gone0").err(), Some("Gone for testing"));
+        assert_eq!(lookup("gone1").err(), Some("No such block device"));
+
+        let disk: Device = Box::leak(Box::new(RamDisk::new(String::from("gone0"), 1)));
+        register_device(disk).unwrap();
+        assert!(lookup("gone0").is_ok());
+    }
+}
@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Bluetooth.
+//!
+//! The BSP registers the serial link to the board's Bluetooth controller with
+//! [`register_transport()`]. Commands are sent synchronously: the caller polls the transport until
+//! the controller completes the command. Packets that arrive meanwhile are queued.
+//!
+//! The controller starts with its ROM firmware. A patch from the vendor, which is not part of the
+//! kernel, is downloaded with [`load_firmware()`], e.g. read from a block device by
+//! [`load_firmware_from()`].
+
+pub mod gatt;
+pub mod hci;
+pub mod peripheral;
+
+use crate::{
+    block, cpu, info,
+    synchronization::{
+        interface::{Mutex, ReadWriteEx},
+        IRQSafeNullLock, InitStateLock,
+    },
+    time,
+};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// How long to wait for a command to complete.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Maximum number of received packets nobody collected yet.
+const PENDING_CAPACITY: usize = 16;
+
+struct HciInner {
+    decoder: hci::Decoder,
+    pending: Vec<hci::Packet>,
+    initialized: bool,
+    firmware_loaded: bool,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Bluetooth interfaces.
+pub mod interface {
+    /// A byte stream to the controller, carrying H4 framed packets.
+    pub trait HciTransport {
+        /// Change the baud rate of the host side.
+        fn set_baud_rate(&self, baud: u32);
+
+        /// Send bytes, blocking until they are queued for transmission.
+        fn write(&self, data: &[u8]);
+
+        /// Return a received byte, if any. Does not block.
+        fn read_byte(&self) -> Option<u8>;
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static CUR_TRANSPORT: InitStateLock<Option<&'static (dyn interface::HciTransport + Sync)>> =
+    InitStateLock::new(None);
+
+static HCI: IRQSafeNullLock<HciInner> = IRQSafeNullLock::new(HciInner {
+    decoder: hci::Decoder::new(),
+    pending: Vec::new(),
+    initialized: false,
+    firmware_loaded: false,
+});
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn transport() -> Result<&'static dyn interface::HciTransport, &'static str> {
+    CUR_TRANSPORT
+        .read(|t| *t)
+        .map(|t| t as &'static dyn interface::HciTransport)
+        .ok_or("No Bluetooth controller")
+}
+
+impl HciInner {
+    /// Poll the transport until a packet is complete or the timeout expires.
+    fn receive(
+        &mut self,
+        transport: &dyn interface::HciTransport,
+        timeout: Duration,
+    ) -> Option<hci::Packet> {
+        let deadline = time::time_manager().uptime() + timeout;
+
+        while time::time_manager().uptime() < deadline {
+            match transport.read_byte() {
+                Some(b) => {
+                    if let Some(packet) = self.decoder.push(b) {
+                        return Some(packet);
+                    }
+                }
+                None => cpu::nop(),
+            }
+        }
+
+        None
+    }
+
+    fn queue(&mut self, packet: hci::Packet) {
+        if self.pending.len() == PENDING_CAPACITY {
+            self.pending.remove(0);
+        }
+
+        self.pending.push(packet);
+    }
+
+    /// Send a command and wait for its completion. Returns the return parameters after the status.
+    fn command(
+        &mut self,
+        transport: &dyn interface::HciTransport,
+        opcode: u16,
+        params: &[u8],
+    ) -> Result<Vec<u8>, &'static str> {
+        transport.write(&hci::build_command(opcode, params));
+
+        loop {
+            let packet = self
+                .receive(transport, COMMAND_TIMEOUT)
+                .ok_or("HCI command timed out")?;
+
+            match &packet {
+                hci::Packet::Event { code, params }
+                    if *code == hci::EVENT_COMMAND_COMPLETE
+                        && params.len() >= 4
+                        && u16::from_le_bytes([params[1], params[2]]) == opcode =>
+                {
+                    if params[3] != 0 {
+                        return Err("HCI command failed");
+                    }
+
+                    return Ok(params[4..].to_vec());
+                }
+                hci::Packet::Event { code, params }
+                    if *code == hci::EVENT_COMMAND_STATUS
+                        && params.len() >= 4
+                        && u16::from_le_bytes([params[2], params[3]]) == opcode =>
+                {
+                    if params[0] != 0 {
+                        return Err("HCI command failed");
+                    }
+
+                    return Ok(Vec::new());
+                }
+                _ => self.queue(packet),
+            }
+        }
+    }
+
+    fn reset(&mut self, transport: &dyn interface::HciTransport) -> Result<(), &'static str> {
+        self.decoder = hci::Decoder::new();
+        self.pending.clear();
+        self.command(transport, hci::OP_RESET, &[])?;
+        self.initialized = true;
+
+        Ok(())
+    }
+
+    fn ensure_initialized(
+        &mut self,
+        transport: &dyn interface::HciTransport,
+    ) -> Result<(), &'static str> {
+        if !self.initialized {
+            self.reset(transport)?;
+        }
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Register the serial link to the board's Bluetooth controller.
+pub fn register_transport(new_transport: &'static (dyn interface::HciTransport + Sync)) {
+    CUR_TRANSPORT.write(|t| *t = Some(new_transport));
+}
+
+/// Reset the controller.
+pub fn reset() -> Result<(), &'static str> {
+    let transport = transport()?;
+
+    HCI.lock(|inner| inner.reset(transport))
+}
+
+/// Send a command and wait for its completion. Returns the return parameters after the status.
+///
+/// The controller is reset first if this is the first command.
+pub fn send_command(opcode: u16, params: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let transport = transport()?;
+
+    HCI.lock(|inner| {
+        inner.ensure_initialized(transport)?;
+        inner.command(transport, opcode, params)
+    })
+}
+
+/// Return the next packet received from the controller, if any. Does not block.
+pub fn poll() -> Result<Option<hci::Packet>, &'static str> {
+    let transport = transport()?;
+
+    HCI.lock(|inner| {
+        if !inner.pending.is_empty() {
+            return Ok(Some(inner.pending.remove(0)));
+        }
+
+        while let Some(b) = transport.read_byte() {
+            if let Some(packet) = inner.decoder.push(b) {
+                return Ok(Some(packet));
+            }
+        }
+
+        Ok(None)
+    })
+}
+
+/// Send an L2CAP frame on a connection. The frame must fit into a single ACL data packet.
+pub fn send_acl_data(handle: u16, data: &[u8]) -> Result<(), &'static str> {
+    transport()?.write(&hci::build_acl_data(handle, data));
+
+    Ok(())
+}
+
+/// Download a firmware patch (`.hcd` file) into the controller, then reset it.
+pub fn load_firmware(hcd: &[u8]) -> Result<(), &'static str> {
+    let transport = transport()?;
+    let records = hci::firmware_records(hcd)?;
+
+    HCI.lock(|inner| {
+        inner.reset(transport)?;
+        inner.command(transport, hci::OP_DOWNLOAD_MINIDRIVER, &[])?;
+
+        // The minidriver needs a moment to start.
+        time::time_manager().spin_for(Duration::from_millis(50));
+
+        for (opcode, params) in records {
+            inner.command(transport, opcode, params)?;
+        }
+
+        // The patched firmware restarts at the default baud rate.
+        time::time_manager().spin_for(Duration::from_millis(250));
+        inner.reset(transport)?;
+        inner.firmware_loaded = true;
+
+        Ok(())
+    })
+}
+
+/// Download a firmware patch of `len` bytes from block `lba` of `device`, see [`load_firmware()`].
+pub fn load_firmware_from(device: block::Device, lba: u64, len: usize) -> Result<(), &'static str> {
+    load_firmware(&block::read_bytes(device, lba, len)?)
+}
+
+/// Print identification of the controller.
+pub fn print_info() -> Result<(), &'static str> {
+    let version = hci::LocalVersion::parse(&send_command(hci::OP_READ_LOCAL_VERSION, &[])?)
+        .ok_or("Malformed version information")?;
+    let addr =
+        hci::BdAddr::parse(&send_command(hci::OP_READ_BD_ADDR, &[])?).ok_or("Malformed address")?;
+    let firmware_loaded = HCI.lock(|inner| inner.firmware_loaded);
+
+    info!("      Address:       {}", addr);
+    info!(
+        "      HCI version:   {} (revision {:#06x})",
+        version.hci_version, version.hci_revision
+    );
+    info!(
+        "      LMP version:   {} (subversion {:#06x})",
+        version.lmp_version, version.lmp_subversion
+    );
+    info!("      Manufacturer:  {:#06x}", version.manufacturer);
+    info!(
+        "      Firmware:      {}",
+        if firmware_loaded { "patched" } else { "ROM" }
+    );
+
+    Ok(())
+}
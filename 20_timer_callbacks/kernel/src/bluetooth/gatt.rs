@@ -0,0 +1,501 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! A minimal GATT server.
+//!
+//! The attribute database is a flat list of attributes; the handle of an attribute is its index
+//! plus one. Requests are answered with the default ATT MTU of 23 bytes. Long writes,
+//! notifications and security are not supported.
+
+use alloc::vec::Vec;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The ATT MTU. The server answers MTU exchanges with it, so it never changes.
+const ATT_MTU: usize = 23;
+
+const UUID_PRIMARY_SERVICE: u16 = 0x2800;
+const UUID_CHARACTERISTIC: u16 = 0x2803;
+
+const OP_ERROR_RSP: u8 = 0x01;
+const OP_EXCHANGE_MTU_REQ: u8 = 0x02;
+const OP_EXCHANGE_MTU_RSP: u8 = 0x03;
+const OP_FIND_INFORMATION_REQ: u8 = 0x04;
+const OP_FIND_INFORMATION_RSP: u8 = 0x05;
+const OP_FIND_BY_TYPE_VALUE_REQ: u8 = 0x06;
+const OP_FIND_BY_TYPE_VALUE_RSP: u8 = 0x07;
+const OP_READ_BY_TYPE_REQ: u8 = 0x08;
+const OP_READ_BY_TYPE_RSP: u8 = 0x09;
+const OP_READ_REQ: u8 = 0x0a;
+const OP_READ_RSP: u8 = 0x0b;
+const OP_READ_BLOB_REQ: u8 = 0x0c;
+const OP_READ_BLOB_RSP: u8 = 0x0d;
+const OP_READ_BY_GROUP_TYPE_REQ: u8 = 0x10;
+const OP_READ_BY_GROUP_TYPE_RSP: u8 = 0x11;
+const OP_WRITE_REQ: u8 = 0x12;
+const OP_WRITE_RSP: u8 = 0x13;
+const OP_WRITE_CMD: u8 = 0x52;
+
+/// Opcodes with this bit set are commands, which never get a response.
+const OP_COMMAND_FLAG: u8 = 0x40;
+
+const ERR_INVALID_HANDLE: u8 = 0x01;
+const ERR_READ_NOT_PERMITTED: u8 = 0x02;
+const ERR_WRITE_NOT_PERMITTED: u8 = 0x03;
+const ERR_INVALID_PDU: u8 = 0x04;
+const ERR_REQUEST_NOT_SUPPORTED: u8 = 0x06;
+const ERR_INVALID_OFFSET: u8 = 0x07;
+const ERR_ATTRIBUTE_NOT_FOUND: u8 = 0x0a;
+const ERR_UNSUPPORTED_GROUP_TYPE: u8 = 0x10;
+
+struct Attribute {
+    uuid: Uuid,
+    value: Value,
+}
+
+enum Value {
+    Fixed(Vec<u8>),
+    Dynamic {
+        read: Option<ReadFn>,
+        write: Option<WriteFn>,
+    },
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Characteristic property bits.
+pub const PROP_READ: u8 = 0x02;
+pub const PROP_WRITE_WITHOUT_RESPONSE: u8 = 0x04;
+pub const PROP_WRITE: u8 = 0x08;
+
+/// ATT error for a write with a value of the wrong length.
+pub const ERR_INVALID_ATTRIBUTE_VALUE_LENGTH: u8 = 0x0d;
+
+/// ATT error for a request that can't be served for lack of resources.
+pub const ERR_INSUFFICIENT_RESOURCES: u8 = 0x11;
+
+/// ATT error for a write with a value outside the allowed range.
+pub const ERR_VALUE_NOT_ALLOWED: u8 = 0x13;
+
+/// Produces the current value of a characteristic.
+pub type ReadFn = fn() -> Vec<u8>;
+
+/// Consumes a written value. Returns an ATT error code to reject it.
+pub type WriteFn = fn(&[u8]) -> Result<(), u8>;
+
+/// An attribute type.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Uuid {
+    /// An assigned number.
+    Short(u16),
+    Long(u128),
+}
+
+/// The attribute database and ATT request handler.
+pub struct Server {
+    attributes: Vec<Attribute>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn le16(buf: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([buf[i], buf[i + 1]])
+}
+
+fn error_response(request: u8, handle: u16, code: u8) -> Vec<u8> {
+    let mut rsp = Vec::from([OP_ERROR_RSP, request]);
+    rsp.extend_from_slice(&handle.to_le_bytes());
+    rsp.push(code);
+
+    rsp
+}
+
+impl Uuid {
+    /// Parse from the little-endian wire format, which is either 2 or 16 bytes long.
+    fn parse(buf: &[u8]) -> Option<Self> {
+        match buf.len() {
+            2 => Some(Self::Short(le16(buf, 0))),
+            16 => Some(Self::Long(u128::from_le_bytes(buf.try_into().ok()?))),
+            _ => None,
+        }
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        match self {
+            Self::Short(u) => u.to_le_bytes().to_vec(),
+            Self::Long(u) => u.to_le_bytes().to_vec(),
+        }
+    }
+}
+
+impl Server {
+    fn attribute(&self, handle: u16) -> Option<&Attribute> {
+        self.attributes.get((handle as usize).checked_sub(1)?)
+    }
+
+    /// Iterate over the attributes with handles in `start..=end`.
+    fn range(&self, start: u16, end: u16) -> impl Iterator<Item = (u16, &Attribute)> {
+        self.attributes
+            .iter()
+            .enumerate()
+            .map(|(i, a)| ((i + 1) as u16, a))
+            .filter(move |(h, _)| *h >= start && *h <= end)
+    }
+
+    /// Return the handle of the last attribute of the service declared at `handle`.
+    fn group_end(&self, handle: u16) -> u16 {
+        self.range(handle + 1, u16::MAX)
+            .find(|(_, a)| a.uuid == Uuid::Short(UUID_PRIMARY_SERVICE))
+            .map(|(h, _)| h - 1)
+            .unwrap_or(self.attributes.len() as u16)
+    }
+
+    fn read_value(&self, attribute: &Attribute) -> Option<Vec<u8>> {
+        match &attribute.value {
+            Value::Fixed(v) => Some(v.clone()),
+            Value::Dynamic { read, .. } => read.map(|f| f()),
+        }
+    }
+
+    fn push_attribute(&mut self, uuid: Uuid, value: Value) -> u16 {
+        self.attributes.push(Attribute { uuid, value });
+
+        self.attributes.len() as u16
+    }
+
+    /// Add the declaration of the characteristic whose value is added next.
+    fn add_declaration(&mut self, uuid: Uuid, properties: u8) {
+        let value_handle = self.attributes.len() as u16 + 2;
+
+        let mut declaration = Vec::from([properties]);
+        declaration.extend_from_slice(&value_handle.to_le_bytes());
+        declaration.extend_from_slice(&uuid.to_bytes());
+
+        self.push_attribute(Uuid::Short(UUID_CHARACTERISTIC), Value::Fixed(declaration));
+    }
+
+    /// Validate the handle range of a request.
+    fn parse_range(&self, request: u8, pdu: &[u8]) -> Result<(u16, u16), Vec<u8>> {
+        if pdu.len() < 5 {
+            return Err(error_response(request, 0, ERR_INVALID_PDU));
+        }
+
+        let (start, end) = (le16(pdu, 1), le16(pdu, 3));
+        if start == 0 || start > end {
+            return Err(error_response(request, start, ERR_INVALID_HANDLE));
+        }
+
+        Ok((start, end))
+    }
+
+    fn exchange_mtu(&self) -> Vec<u8> {
+        let mut rsp = Vec::from([OP_EXCHANGE_MTU_RSP]);
+        rsp.extend_from_slice(&(ATT_MTU as u16).to_le_bytes());
+
+        rsp
+    }
+
+    fn find_information(&self, pdu: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+        let (start, end) = self.parse_range(OP_FIND_INFORMATION_REQ, pdu)?;
+
+        // All entries of a response must use the same UUID format.
+        let mut rsp = Vec::from([OP_FIND_INFORMATION_RSP, 0]);
+        for (handle, a) in self.range(start, end) {
+            let format = match a.uuid {
+                Uuid::Short(_) => 1,
+                Uuid::Long(_) => 2,
+            };
+            let uuid = a.uuid.to_bytes();
+
+            if rsp[1] == 0 {
+                rsp[1] = format;
+            } else if rsp[1] != format || rsp.len() + 2 + uuid.len() > ATT_MTU {
+                break;
+            }
+
+            rsp.extend_from_slice(&handle.to_le_bytes());
+            rsp.extend_from_slice(&uuid);
+        }
+
+        if rsp[1] == 0 {
+            return Err(error_response(
+                OP_FIND_INFORMATION_REQ,
+                start,
+                ERR_ATTRIBUTE_NOT_FOUND,
+            ));
+        }
+
+        Ok(rsp)
+    }
+
+    fn find_by_type_value(&self, pdu: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+        let (start, end) = self.parse_range(OP_FIND_BY_TYPE_VALUE_REQ, pdu)?;
+        if pdu.len() < 7 {
+            return Err(error_response(
+                OP_FIND_BY_TYPE_VALUE_REQ,
+                0,
+                ERR_INVALID_PDU,
+            ));
+        }
+        let uuid = Uuid::Short(le16(pdu, 5));
+        let value = &pdu[7..];
+
+        let mut rsp = Vec::from([OP_FIND_BY_TYPE_VALUE_RSP]);
+        for (handle, a) in self.range(start, end) {
+            if a.uuid != uuid || self.read_value(a).as_deref() != Some(value) {
+                continue;
+            }
+            if rsp.len() + 4 > ATT_MTU {
+                break;
+            }
+
+            let group_end = if uuid == Uuid::Short(UUID_PRIMARY_SERVICE) {
+                self.group_end(handle)
+            } else {
+                handle
+            };
+            rsp.extend_from_slice(&handle.to_le_bytes());
+            rsp.extend_from_slice(&group_end.to_le_bytes());
+        }
+
+        if rsp.len() == 1 {
+            return Err(error_response(
+                OP_FIND_BY_TYPE_VALUE_REQ,
+                start,
+                ERR_ATTRIBUTE_NOT_FOUND,
+            ));
+        }
+
+        Ok(rsp)
+    }
+
+    /// Answer Read By Type and Read By Group Type, which only differ in the group end handle.
+    fn read_by_type(&self, pdu: &[u8], grouped: bool) -> Result<Vec<u8>, Vec<u8>> {
+        let (request, response) = if grouped {
+            (OP_READ_BY_GROUP_TYPE_REQ, OP_READ_BY_GROUP_TYPE_RSP)
+        } else {
+            (OP_READ_BY_TYPE_REQ, OP_READ_BY_TYPE_RSP)
+        };
+        let (start, end) = self.parse_range(request, pdu)?;
+        let uuid =
+            Uuid::parse(&pdu[5..]).ok_or_else(|| error_response(request, 0, ERR_INVALID_PDU))?;
+
+        if grouped && uuid != Uuid::Short(UUID_PRIMARY_SERVICE) {
+            return Err(error_response(request, start, ERR_UNSUPPORTED_GROUP_TYPE));
+        }
+
+        // All entries of a response must have the same length.
+        let mut rsp = Vec::from([response, 0]);
+        for (handle, a) in self.range(start, end).filter(|(_, a)| a.uuid == uuid) {
+            let value = match self.read_value(a) {
+                Some(v) => v,
+                None if rsp[1] == 0 => {
+                    return Err(error_response(request, handle, ERR_READ_NOT_PERMITTED))
+                }
+                None => break,
+            };
+
+            let header_len = if grouped { 4 } else { 2 };
+            let value_len = value.len().min(ATT_MTU - 2 - header_len);
+            let entry_len = (header_len + value_len) as u8;
+
+            if rsp[1] == 0 {
+                rsp[1] = entry_len;
+            } else if rsp[1] != entry_len || rsp.len() + entry_len as usize > ATT_MTU {
+                break;
+            }
+
+            rsp.extend_from_slice(&handle.to_le_bytes());
+            if grouped {
+                rsp.extend_from_slice(&self.group_end(handle).to_le_bytes());
+            }
+            rsp.extend_from_slice(&value[..value_len]);
+        }
+
+        if rsp[1] == 0 {
+            return Err(error_response(request, start, ERR_ATTRIBUTE_NOT_FOUND));
+        }
+
+        Ok(rsp)
+    }
+
+    fn read(&self, pdu: &[u8], blob: bool) -> Result<Vec<u8>, Vec<u8>> {
+        let (request, response, pdu_len) = if blob {
+            (OP_READ_BLOB_REQ, OP_READ_BLOB_RSP, 5)
+        } else {
+            (OP_READ_REQ, OP_READ_RSP, 3)
+        };
+        if pdu.len() < pdu_len {
+            return Err(error_response(request, 0, ERR_INVALID_PDU));
+        }
+
+        let handle = le16(pdu, 1);
+        let offset = if blob { le16(pdu, 3) as usize } else { 0 };
+        let a = self
+            .attribute(handle)
+            .ok_or_else(|| error_response(request, handle, ERR_INVALID_HANDLE))?;
+        let value = self
+            .read_value(a)
+            .ok_or_else(|| error_response(request, handle, ERR_READ_NOT_PERMITTED))?;
+        if offset > value.len() {
+            return Err(error_response(request, handle, ERR_INVALID_OFFSET));
+        }
+
+        let mut rsp = Vec::from([response]);
+        let end = value.len().min(offset + ATT_MTU - 1);
+        rsp.extend_from_slice(&value[offset..end]);
+
+        Ok(rsp)
+    }
+
+    fn write(&self, pdu: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+        let request = pdu[0];
+        if pdu.len() < 3 {
+            return Err(error_response(request, 0, ERR_INVALID_PDU));
+        }
+
+        let handle = le16(pdu, 1);
+        let a = self
+            .attribute(handle)
+            .ok_or_else(|| error_response(request, handle, ERR_INVALID_HANDLE))?;
+        let write = match a.value {
+            Value::Dynamic {
+                write: Some(write), ..
+            } => write,
+            _ => return Err(error_response(request, handle, ERR_WRITE_NOT_PERMITTED)),
+        };
+
+        write(&pdu[3..]).map_err(|code| error_response(request, handle, code))?;
+
+        Ok(Vec::from([OP_WRITE_RSP]))
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Server {
+    /// Create an empty database.
+    pub const fn new() -> Self {
+        Self {
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Declare a primary service. Characteristics added afterwards belong to it.
+    pub fn add_service(&mut self, uuid: Uuid) -> u16 {
+        self.push_attribute(
+            Uuid::Short(UUID_PRIMARY_SERVICE),
+            Value::Fixed(uuid.to_bytes()),
+        )
+    }
+
+    /// Add a characteristic with a constant value. Returns the handle of the value.
+    pub fn add_fixed_characteristic(&mut self, uuid: Uuid, value: &[u8]) -> u16 {
+        self.add_declaration(uuid, PROP_READ);
+        self.push_attribute(uuid, Value::Fixed(value.to_vec()))
+    }
+
+    /// Add a characteristic backed by functions. Returns the handle of the value.
+    pub fn add_characteristic(
+        &mut self,
+        uuid: Uuid,
+        read: Option<ReadFn>,
+        write: Option<WriteFn>,
+    ) -> u16 {
+        let mut properties = 0;
+        if read.is_some() {
+            properties |= PROP_READ;
+        }
+        if write.is_some() {
+            properties |= PROP_WRITE | PROP_WRITE_WITHOUT_RESPONSE;
+        }
+
+        self.add_declaration(uuid, properties);
+        self.push_attribute(uuid, Value::Dynamic { read, write })
+    }
+
+    /// Handle an ATT PDU received from the client. Returns the response, if any.
+    pub fn handle(&self, pdu: &[u8]) -> Option<Vec<u8>> {
+        let opcode = *pdu.first()?;
+
+        let result = match opcode {
+            OP_EXCHANGE_MTU_REQ => Ok(self.exchange_mtu()),
+            OP_FIND_INFORMATION_REQ => self.find_information(pdu),
+            OP_FIND_BY_TYPE_VALUE_REQ => self.find_by_type_value(pdu),
+            OP_READ_BY_TYPE_REQ => self.read_by_type(pdu, false),
+            OP_READ_REQ => self.read(pdu, false),
+            OP_READ_BLOB_REQ => self.read(pdu, true),
+            OP_READ_BY_GROUP_TYPE_REQ => self.read_by_type(pdu, true),
+            OP_WRITE_REQ => self.write(pdu),
+            OP_WRITE_CMD => {
+                let _ = self.write(pdu);
+                return None;
+            }
+            _ if opcode & OP_COMMAND_FLAG != 0 => return None,
+            _ => Err(error_response(opcode, 0, ERR_REQUEST_NOT_SUPPORTED)),
+        };
+
+        Some(result.unwrap_or_else(|e| e))
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Primary service discovery must report each service with the handle range of its group.
+    #[kernel_test]
+    fn discover_primary_services() {
+        let mut server = Server::new();
+        server.add_service(Uuid::Short(0x1800));
+        server.add_fixed_characteristic(Uuid::Short(0x2a00), b"KHROS");
+        server.add_service(Uuid::Short(0x180f));
+        server.add_fixed_characteristic(Uuid::Short(0x2a19), &[100]);
+
+        let rsp = server
+            .handle(&[
+                OP_READ_BY_GROUP_TYPE_REQ,
+                0x01,
+                0x00,
+                0xff,
+                0xff,
+                0x00,
+                0x28,
+            ])
+            .unwrap();
+
+        assert_eq!(
+            rsp,
+            [
+                OP_READ_BY_GROUP_TYPE_RSP,
+                6,
+                1,
+                0,
+                3,
+                0,
+                0x00,
+                0x18,
+                4,
+                0,
+                6,
+                0,
+                0x0f,
+                0x18
+            ]
+        );
+    }
+}
@@ -0,0 +1,318 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! HCI packet framing for the UART (H4) transport.
+//!
+//! Every packet on the wire is prefixed with a one byte packet type. Commands flow from the host to
+//! the controller, events from the controller to the host, and ACL data in both directions.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const PACKET_COMMAND: u8 = 0x01;
+const PACKET_ACL_DATA: u8 = 0x02;
+const PACKET_EVENT: u8 = 0x04;
+
+const EVENT_HEADER_LEN: usize = 2;
+const ACL_HEADER_LEN: usize = 4;
+
+/// Receive state of the [`Decoder`].
+enum State {
+    /// Waiting for a packet type byte.
+    Idle,
+
+    /// Collecting the header of a packet of the given type.
+    Header(u8),
+
+    /// Collecting the payload of a packet of the given type.
+    Payload(u8, usize),
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+pub const OGF_CONTROLLER: u16 = 0x03;
+pub const OGF_INFORMATIONAL: u16 = 0x04;
+pub const OGF_LE: u16 = 0x08;
+pub const OGF_VENDOR: u16 = 0x3f;
+
+pub const OP_SET_EVENT_MASK: u16 = opcode(OGF_CONTROLLER, 0x001);
+pub const OP_RESET: u16 = opcode(OGF_CONTROLLER, 0x003);
+pub const OP_READ_LOCAL_VERSION: u16 = opcode(OGF_INFORMATIONAL, 0x001);
+pub const OP_READ_BD_ADDR: u16 = opcode(OGF_INFORMATIONAL, 0x009);
+pub const OP_LE_SET_ADVERTISING_PARAMETERS: u16 = opcode(OGF_LE, 0x006);
+pub const OP_LE_SET_ADVERTISING_DATA: u16 = opcode(OGF_LE, 0x008);
+pub const OP_LE_SET_SCAN_RESPONSE_DATA: u16 = opcode(OGF_LE, 0x009);
+pub const OP_LE_SET_ADVERTISING_ENABLE: u16 = opcode(OGF_LE, 0x00a);
+
+/// Broadcom: prepare the controller for a firmware patch download.
+pub const OP_DOWNLOAD_MINIDRIVER: u16 = opcode(OGF_VENDOR, 0x02e);
+
+pub const EVENT_DISCONNECTION_COMPLETE: u8 = 0x05;
+pub const EVENT_COMMAND_COMPLETE: u8 = 0x0e;
+pub const EVENT_COMMAND_STATUS: u8 = 0x0f;
+pub const EVENT_LE_META: u8 = 0x3e;
+
+/// LE meta event subevent codes.
+pub const LE_CONNECTION_COMPLETE: u8 = 0x01;
+
+/// A packet received from the controller.
+#[derive(Clone)]
+pub enum Packet {
+    Event {
+        code: u8,
+        params: Vec<u8>,
+    },
+    AclData {
+        handle: u16,
+
+        /// Packet boundary and broadcast flags.
+        flags: u8,
+        data: Vec<u8>,
+    },
+}
+
+/// Reassembles packets from the byte stream of the transport.
+pub struct Decoder {
+    state: State,
+    buf: Vec<u8>,
+}
+
+/// A Bluetooth device address.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct BdAddr(pub [u8; 6]);
+
+/// Response to Read Local Version Information.
+#[derive(Copy, Clone)]
+pub struct LocalVersion {
+    pub hci_version: u8,
+    pub hci_revision: u16,
+    pub lmp_version: u8,
+    pub manufacturer: u16,
+    pub lmp_subversion: u16,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn le16(buf: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([buf[i], buf[i + 1]])
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Combine opcode group and command fields.
+pub const fn opcode(ogf: u16, ocf: u16) -> u16 {
+    (ogf << 10) | ocf
+}
+
+/// Build a command packet, including the packet type byte.
+pub fn build_command(opcode: u16, params: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(4 + params.len());
+
+    packet.push(PACKET_COMMAND);
+    packet.extend_from_slice(&opcode.to_le_bytes());
+    packet.push(params.len() as u8);
+    packet.extend_from_slice(params);
+
+    packet
+}
+
+/// Build an ACL data packet carrying a complete L2CAP frame, including the packet type byte.
+pub fn build_acl_data(handle: u16, data: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(1 + ACL_HEADER_LEN + data.len());
+
+    // Packet boundary flag 0b00: first, non-flushable packet of the frame.
+    packet.push(PACKET_ACL_DATA);
+    packet.extend_from_slice(&(handle & 0x0fff).to_le_bytes());
+    packet.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    packet.extend_from_slice(data);
+
+    packet
+}
+
+/// Split a firmware patch (`.hcd` file) into its records. Each record is a vendor command with its
+/// parameters.
+pub fn firmware_records(hcd: &[u8]) -> Result<Vec<(u16, &[u8])>, &'static str> {
+    let mut records = Vec::new();
+    let mut rest = hcd;
+
+    while !rest.is_empty() {
+        if rest.len() < 3 || rest.len() < 3 + rest[2] as usize {
+            return Err("Truncated firmware record");
+        }
+
+        let len = rest[2] as usize;
+        records.push((le16(rest, 0), &rest[3..3 + len]));
+        rest = &rest[3 + len..];
+    }
+
+    Ok(records)
+}
+
+impl Decoder {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            state: State::Idle,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Feed one received byte. Returns a packet once it is complete.
+    ///
+    /// Bytes that do not start a known packet type are dropped, which resynchronizes the decoder
+    /// after garbage on the line.
+    pub fn push(&mut self, b: u8) -> Option<Packet> {
+        match self.state {
+            State::Idle => {
+                if b == PACKET_EVENT || b == PACKET_ACL_DATA {
+                    self.buf.clear();
+                    self.state = State::Header(b);
+                }
+
+                None
+            }
+            State::Header(kind) => {
+                self.buf.push(b);
+
+                let payload_len = match kind {
+                    PACKET_EVENT if self.buf.len() == EVENT_HEADER_LEN => self.buf[1] as usize,
+                    PACKET_ACL_DATA if self.buf.len() == ACL_HEADER_LEN => {
+                        le16(&self.buf, 2) as usize
+                    }
+                    _ => return None,
+                };
+                self.state = State::Payload(kind, payload_len);
+
+                self.complete()
+            }
+            State::Payload(_, _) => {
+                self.buf.push(b);
+                self.complete()
+            }
+        }
+    }
+
+    /// Return the packet if the payload has been fully received.
+    fn complete(&mut self) -> Option<Packet> {
+        let (kind, payload_len) = match self.state {
+            State::Payload(kind, len) => (kind, len),
+            _ => return None,
+        };
+
+        let header_len = if kind == PACKET_EVENT {
+            EVENT_HEADER_LEN
+        } else {
+            ACL_HEADER_LEN
+        };
+        if self.buf.len() < header_len + payload_len {
+            return None;
+        }
+
+        self.state = State::Idle;
+        let payload = self.buf[header_len..].to_vec();
+
+        if kind == PACKET_EVENT {
+            Some(Packet::Event {
+                code: self.buf[0],
+                params: payload,
+            })
+        } else {
+            let word = le16(&self.buf, 0);
+            Some(Packet::AclData {
+                handle: word & 0x0fff,
+                flags: (word >> 12) as u8,
+                data: payload,
+            })
+        }
+    }
+}
+
+impl BdAddr {
+    /// Parse from the little-endian wire format.
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        let bytes: [u8; 6] = buf.get(..6)?.try_into().ok()?;
+        Some(Self(bytes))
+    }
+}
+
+impl fmt::Display for BdAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Addresses are sent least significant byte first, but printed the other way around.
+        let b = &self.0;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            b[5], b[4], b[3], b[2], b[1], b[0]
+        )
+    }
+}
+
+impl LocalVersion {
+    /// Parse the return parameters of Read Local Version Information, without the status.
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        if buf.len() < 8 {
+            return None;
+        }
+
+        Some(Self {
+            hci_version: buf[0],
+            hci_revision: le16(buf, 1),
+            lmp_version: buf[3],
+            manufacturer: le16(buf, 4),
+            lmp_subversion: le16(buf, 6),
+        })
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A command complete event must be reassembled from the byte stream, skipping leading garbage.
+    #[kernel_test]
+    fn decoder_reassembles_event() {
+        let stream = [
+            0xff,
+            PACKET_EVENT,
+            EVENT_COMMAND_COMPLETE,
+            4,
+            1,
+            0x03,
+            0x0c,
+            0x00,
+        ];
+        let mut decoder = Decoder::new();
+
+        let mut packets = Vec::new();
+        for b in stream {
+            if let Some(p) = decoder.push(b) {
+                packets.push(p);
+            }
+        }
+
+        assert_eq!(packets.len(), 1);
+        match &packets[0] {
+            Packet::Event { code, params } => {
+                assert_eq!(*code, EVENT_COMMAND_COMPLETE);
+                assert_eq!(le16(params, 1), OP_RESET);
+            }
+            _ => panic!("not parsed as event"),
+        }
+    }
+}
@@ -0,0 +1,341 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! BLE LED-control peripheral.
+//!
+//! Advertises as "KHROS" and exposes an LED service with two characteristics:
+//!
+//! - LED state: one byte, bit `i` switches the LED on `pattern::RING_PINS[i]`. Writing it stops the
+//!   running pattern.
+//! - Pattern: write 0 to stop and switch all LEDs off, 1 for the hex counter, 2 for the left and 3
+//!   for the right ring counter.
+//!
+//! The controller is polled from a periodic timer callback. Connections and disconnections are
+//! published on the event bus.
+
+use super::{gatt, hci};
+use crate::{
+    event::{self, Event},
+    info, pattern,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const DEVICE_NAME: &str = "KHROS";
+
+const UUID_GAP_SERVICE: u16 = 0x1800;
+const UUID_DEVICE_NAME: u16 = 0x2a00;
+
+const UUID_LED_SERVICE: u128 = 0x8d1f0001_5e4a_4f3b_9c2a_6b1e2d3c4f50;
+const UUID_LED_STATE: u128 = 0x8d1f0002_5e4a_4f3b_9c2a_6b1e2d3c4f50;
+const UUID_LED_PATTERN: u128 = 0x8d1f0003_5e4a_4f3b_9c2a_6b1e2d3c4f50;
+
+/// L2CAP channel identifiers on LE links.
+const CID_ATT: u16 = 0x0004;
+const CID_SMP: u16 = 0x0006;
+
+const SMP_PAIRING_REQUEST: u8 = 0x01;
+const SMP_PAIRING_FAILED: u8 = 0x05;
+const SMP_ERR_PAIRING_NOT_SUPPORTED: u8 = 0x05;
+
+/// How often the controller is polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Advertising interval in units of 0.625 ms, i.e. 100 ms.
+const ADVERTISING_INTERVAL: u16 = 0x00a0;
+
+/// The default event mask, plus LE meta events.
+const EVENT_MASK: u64 = 0x2000_1fff_ffff_ffff;
+
+/// Longest advertising or scan response payload.
+const ADVERTISING_DATA_LEN: usize = 31;
+
+struct PeripheralInner {
+    server: gatt::Server,
+    connection: Option<u16>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static PERIPHERAL: IRQSafeNullLock<PeripheralInner> = IRQSafeNullLock::new(PeripheralInner {
+    server: gatt::Server::new(),
+    connection: None,
+});
+
+/// True while advertising or connected.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The timeout that polls the controller while active.
+static POLLING: IRQSafeNullLock<Option<time::TimeoutHandle>> = IRQSafeNullLock::new(None);
+
+/// The last LED state written by a client.
+static LED_STATE: AtomicU8 = AtomicU8::new(0);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn read_led_state() -> Vec<u8> {
+    Vec::from([LED_STATE.load(Ordering::Relaxed)])
+}
+
+fn write_led_state(value: &[u8]) -> Result<(), u8> {
+    let state = match value {
+        [state] => *state,
+        _ => return Err(gatt::ERR_INVALID_ATTRIBUTE_VALUE_LENGTH),
+    };
+
+    pattern::stop();
+    for (i, pin) in pattern::RING_PINS.iter().enumerate() {
+        pattern::set_pin(*pin, state & (1 << i) != 0);
+    }
+    LED_STATE.store(state, Ordering::Relaxed);
+
+    Ok(())
+}
+
+fn write_led_pattern(value: &[u8]) -> Result<(), u8> {
+    let selected = match value {
+        [0] => None,
+        [1] => Some(pattern::Pattern::Hex),
+        [2] => Some(pattern::Pattern::Left),
+        [3] => Some(pattern::Pattern::Right),
+        [_] => return Err(gatt::ERR_VALUE_NOT_ALLOWED),
+        _ => return Err(gatt::ERR_INVALID_ATTRIBUTE_VALUE_LENGTH),
+    };
+
+    match selected {
+        Some(p) => {
+            pattern::start(p).map_err(|_| gatt::ERR_INSUFFICIENT_RESOURCES)?;
+        }
+        None => {
+            pattern::stop();
+            pattern::reset_pins();
+        }
+    }
+    LED_STATE.store(0, Ordering::Relaxed);
+
+    Ok(())
+}
+
+fn build_server() -> gatt::Server {
+    let mut server = gatt::Server::new();
+
+    server.add_service(gatt::Uuid::Short(UUID_GAP_SERVICE));
+    server.add_fixed_characteristic(gatt::Uuid::Short(UUID_DEVICE_NAME), DEVICE_NAME.as_bytes());
+
+    server.add_service(gatt::Uuid::Long(UUID_LED_SERVICE));
+    server.add_characteristic(
+        gatt::Uuid::Long(UUID_LED_STATE),
+        Some(read_led_state),
+        Some(write_led_state),
+    );
+    server.add_characteristic(
+        gatt::Uuid::Long(UUID_LED_PATTERN),
+        None,
+        Some(write_led_pattern),
+    );
+
+    server
+}
+
+/// Pad advertising data to the fixed length the command expects, prefixed with the used length.
+fn advertising_payload(data: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::from([data.len() as u8]);
+    payload.extend_from_slice(data);
+    payload.resize(1 + ADVERTISING_DATA_LEN, 0);
+
+    payload
+}
+
+fn set_advertising(enable: bool) -> Result<(), &'static str> {
+    super::send_command(hci::OP_LE_SET_ADVERTISING_ENABLE, &[enable as u8]).map(|_| ())
+}
+
+fn configure_advertising() -> Result<(), &'static str> {
+    super::send_command(hci::OP_SET_EVENT_MASK, &EVENT_MASK.to_le_bytes())?;
+
+    // Connectable undirected advertising on all three channels, public address, no filter.
+    let mut params = Vec::new();
+    params.extend_from_slice(&ADVERTISING_INTERVAL.to_le_bytes());
+    params.extend_from_slice(&ADVERTISING_INTERVAL.to_le_bytes());
+    params.extend_from_slice(&[0x00, 0x00, 0x00]);
+    params.extend_from_slice(&[0; 6]);
+    params.extend_from_slice(&[0x07, 0x00]);
+    super::send_command(hci::OP_LE_SET_ADVERTISING_PARAMETERS, &params)?;
+
+    // Flags: LE general discoverable, BR/EDR not supported. Then the complete local name.
+    let mut data = Vec::from([0x02, 0x01, 0x06, DEVICE_NAME.len() as u8 + 1, 0x09]);
+    data.extend_from_slice(DEVICE_NAME.as_bytes());
+    super::send_command(hci::OP_LE_SET_ADVERTISING_DATA, &advertising_payload(&data))?;
+
+    // The LED service UUID goes into the scan response, it does not fit next to the name.
+    let mut scan_response = Vec::from([17, 0x07]);
+    scan_response.extend_from_slice(&UUID_LED_SERVICE.to_le_bytes());
+    super::send_command(
+        hci::OP_LE_SET_SCAN_RESPONSE_DATA,
+        &advertising_payload(&scan_response),
+    )?;
+
+    Ok(())
+}
+
+/// Dispatch an L2CAP frame. Returns the reply frame, if any.
+fn handle_l2cap(server: &gatt::Server, frame: &[u8]) -> Option<Vec<u8>> {
+    if frame.len() < 4 {
+        return None;
+    }
+
+    let cid = u16::from_le_bytes([frame[2], frame[3]]);
+    let payload = &frame[4..];
+
+    let reply = match cid {
+        CID_ATT => server.handle(payload)?,
+        CID_SMP if payload.first() == Some(&SMP_PAIRING_REQUEST) => {
+            Vec::from([SMP_PAIRING_FAILED, SMP_ERR_PAIRING_NOT_SUPPORTED])
+        }
+        _ => return None,
+    };
+
+    let mut frame = Vec::with_capacity(4 + reply.len());
+    frame.extend_from_slice(&(reply.len() as u16).to_le_bytes());
+    frame.extend_from_slice(&cid.to_le_bytes());
+    frame.extend_from_slice(&reply);
+
+    Some(frame)
+}
+
+impl PeripheralInner {
+    /// Handle a packet from the controller. Returns an event to publish, if any.
+    fn handle(&mut self, packet: hci::Packet) -> Option<Event> {
+        match packet {
+            hci::Packet::Event { code, params }
+                if code == hci::EVENT_LE_META
+                    && params.len() >= 4
+                    && params[0] == hci::LE_CONNECTION_COMPLETE
+                    && params[1] == 0 =>
+            {
+                let handle = u16::from_le_bytes([params[2], params[3]]) & 0x0fff;
+                self.connection = Some(handle);
+
+                Some(Event::BleConnected { handle })
+            }
+            hci::Packet::Event { code, params }
+                if code == hci::EVENT_DISCONNECTION_COMPLETE && params.len() >= 3 =>
+            {
+                let handle = u16::from_le_bytes([params[1], params[2]]) & 0x0fff;
+                if self.connection != Some(handle) {
+                    return None;
+                }
+                self.connection = None;
+
+                // Advertising stops when a connection is made, resume it.
+                if let Err(x) = set_advertising(true) {
+                    info!("ble: {}", x);
+                }
+
+                Some(Event::BleDisconnected { handle })
+            }
+            hci::Packet::AclData { handle, data, .. } if self.connection == Some(handle) => {
+                if let Some(reply) = handle_l2cap(&self.server, &data) {
+                    let _ = super::send_acl_data(handle, &reply);
+                }
+
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+fn poll() {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut events = Vec::new();
+    PERIPHERAL.lock(|inner| {
+        while let Ok(Some(packet)) = super::poll() {
+            if let Some(e) = inner.handle(packet) {
+                events.push(e);
+            }
+        }
+    });
+
+    // Publish outside of the lock, so that handlers may query the peripheral.
+    for e in events {
+        event::event_bus().publish(e);
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Start advertising and serving the LED service.
+pub fn start() -> Result<(), &'static str> {
+    if ACTIVE.load(Ordering::Relaxed) {
+        return Err("Already running");
+    }
+
+    PERIPHERAL.lock(|inner| {
+        inner.server = build_server();
+        inner.connection = None;
+    });
+    configure_advertising()?;
+    set_advertising(true)?;
+    ACTIVE.store(true, Ordering::Relaxed);
+
+    let timeout = time::time_manager().set_timeout_periodic(POLL_INTERVAL, Box::new(poll));
+    POLLING.lock(|polling| *polling = Some(timeout));
+
+    Ok(())
+}
+
+/// Stop advertising. A connected client is disconnected by resetting the controller.
+pub fn stop() -> Result<(), &'static str> {
+    if !ACTIVE.swap(false, Ordering::Relaxed) {
+        return Err("Not running");
+    }
+    if let Some(timeout) = POLLING.lock(|polling| polling.take()) {
+        timeout.cancel();
+    }
+
+    let connection = PERIPHERAL.lock(|inner| inner.connection.take());
+    match connection {
+        Some(handle) => {
+            super::reset()?;
+            event::event_bus().publish(Event::BleDisconnected { handle });
+        }
+        None => set_advertising(false)?,
+    }
+
+    Ok(())
+}
+
+/// Print the state of the peripheral.
+pub fn print_status() {
+    let connection = PERIPHERAL.lock(|inner| inner.connection);
+
+    match (ACTIVE.load(Ordering::Relaxed), connection) {
+        (false, _) => info!("      Stopped"),
+        (true, None) => info!("      Advertising as {}", DEVICE_NAME),
+        (true, Some(handle)) => info!("      Connected, handle {:#05x}", handle),
+    }
+    info!(
+        "      LED state: {:#07b}",
+        LED_STATE.load(Ordering::Relaxed)
+    );
+}
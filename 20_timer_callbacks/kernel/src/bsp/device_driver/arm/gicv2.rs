@@ -84,7 +84,7 @@ use crate::{
     cpu, driver, exception,
     memory::{Address, Virtual},
     synchronization,
-    synchronization::InitStateLock,
+    synchronization::IRQSafeNullLock,
 };
 use alloc::vec::Vec;
 
@@ -109,8 +109,10 @@ pub struct GICv2 {
     /// The CPU Interface.
     gicc: gicc::GICC,
 
-    /// Stores registered IRQ handlers. Writable only during kernel init. RO afterwards.
-    handler_table: InitStateLock<HandlerTable>,
+    /// Stores registered IRQ handlers. Filled during kernel init, remapped afterwards.
+    handler_table: IRQSafeNullLock<HandlerTable>,
+
+    storm_detector: exception::asynchronous::StormDetector<{ GICv2::MAX_IRQ_NUMBER + 1 }>,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -120,6 +122,10 @@ pub struct GICv2 {
 impl GICv2 {
     const MAX_IRQ_NUMBER: usize = 1019;
 
+    /// IPI messages are sent as the SGIs with their index.
+    const MAX_SGI_NUMBER: usize = 15;
+    const NUM_CORES: usize = 4;
+
     pub const COMPATIBLE: &'static str = "GICv2 (ARM Generic Interrupt Controller v2)";
 
     /// Create an instance.
@@ -134,7 +140,8 @@ impl GICv2 {
         Self {
             gicd: gicd::GICD::new(gicd_mmio_start_addr),
             gicc: gicc::GICC::new(gicc_mmio_start_addr),
-            handler_table: InitStateLock::new(Vec::new()),
+            handler_table: IRQSafeNullLock::new(Vec::new()),
+            storm_detector: exception::asynchronous::StormDetector::new(),
         }
     }
 }
@@ -142,7 +149,7 @@ impl GICv2 {
 //------------------------------------------------------------------------------
 // OS Interface Code
 //------------------------------------------------------------------------------
-use synchronization::interface::ReadWriteEx;
+use synchronization::interface::Mutex;
 
 impl driver::interface::DeviceDriver for GICv2 {
     type IRQNumberType = IRQNumber;
@@ -153,12 +160,17 @@ impl driver::interface::DeviceDriver for GICv2 {
 
     unsafe fn init(&self) -> Result<(), &'static str> {
         self.handler_table
-            .write(|table| table.resize(IRQNumber::MAX_INCLUSIVE + 1, None));
+            .lock(|table| table.resize(IRQNumber::MAX_INCLUSIVE + 1, None));
 
         if bsp::cpu::BOOT_CORE_ID == cpu::smp::core_id() {
             self.gicd.boot_core_init();
         }
 
+        // SGIs are banked, so each core enables its own.
+        for sgi in 0..exception::asynchronous::IpiMessage::COUNT {
+            self.gicd.enable(&IRQNumber::new(sgi));
+        }
+
         self.gicc.priority_accept_all();
         self.gicc.enable();
 
@@ -173,7 +185,7 @@ impl exception::asynchronous::interface::IRQManager for GICv2 {
         &self,
         irq_handler_descriptor: exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>,
     ) -> Result<(), &'static str> {
-        self.handler_table.write(|table| {
+        self.handler_table.lock(|table| {
             let irq_number = irq_handler_descriptor.number().get();
 
             if table[irq_number].is_some() {
@@ -186,36 +198,104 @@ impl exception::asynchronous::interface::IRQManager for GICv2 {
         })
     }
 
+    fn remap_handler(
+        &self,
+        irq_handler_descriptor: exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>,
+    ) -> Result<exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>, &'static str>
+    {
+        self.handler_table.lock(|table| {
+            let irq_number = irq_handler_descriptor.number().get();
+            let previous = table[irq_number].ok_or("No IRQ handler registered")?;
+
+            table[irq_number] = Some(irq_handler_descriptor);
+
+            Ok(previous)
+        })
+    }
+
+    fn handler(
+        &self,
+        irq: &Self::IRQNumberType,
+    ) -> Option<exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>> {
+        self.handler_table.lock(|table| table[irq.get()])
+    }
+
     fn enable(&self, irq_number: &Self::IRQNumberType) {
         self.gicd.enable(irq_number);
     }
 
+    fn disable(&self, irq_number: &Self::IRQNumberType) {
+        self.gicd.disable(irq_number);
+    }
+
+    fn disable_all_except(&self, keep: &Self::IRQNumberType) -> Vec<Self::IRQNumberType> {
+        let mut disabled = Vec::new();
+
+        self.handler_table.lock(|table| {
+            for (i, _) in table.iter().enumerate().filter(|(_, h)| h.is_some()) {
+                let irq_number = IRQNumber::new(i);
+
+                if i != keep.get() && self.gicd.is_enabled(&irq_number) {
+                    self.gicd.disable(&irq_number);
+                    disabled.push(irq_number);
+                }
+            }
+        });
+
+        disabled
+    }
+
+    fn send_ipi(
+        &self,
+        core: usize,
+        message: exception::asynchronous::IpiMessage,
+    ) -> Result<(), &'static str> {
+        if core >= Self::NUM_CORES {
+            return Err("No such core");
+        }
+
+        self.gicd.send_sgi(core, message.index());
+
+        Ok(())
+    }
+
     fn handle_pending_irqs<'irq_context>(
         &'irq_context self,
         ic: &exception::asynchronous::IRQContext<'irq_context>,
     ) {
         // Extract the highest priority pending IRQ number from the Interrupt Acknowledge Register
         // (IAR).
-        let irq_number = self.gicc.pending_irq_number(ic);
+        let (irq_number, source_core) = self.gicc.pending_irq_number(ic);
 
         // Guard against spurious interrupts.
         if irq_number > GICv2::MAX_IRQ_NUMBER {
             return;
         }
 
-        // Call the IRQ handler. Panic if there is none.
-        self.handler_table.read(|table| {
-            match table[irq_number] {
-                None => panic!("No handler registered for IRQ {}", irq_number),
-                Some(descriptor) => {
-                    // Call the IRQ handler. Panics on failure.
-                    descriptor.handler().handle().expect("Error handling IRQ");
+        // SGIs carry IPI messages and have no entry in the handler table.
+        if irq_number <= GICv2::MAX_SGI_NUMBER {
+            exception::asynchronous::dispatch_ipis(1 << irq_number, ic);
+            self.gicc.mark_comleted(irq_number as u32, source_core, ic);
+            return;
+        }
+
+        // Call the IRQ handler. Panic if there is none. The descriptor is copied out of the table,
+        // so that the handler may remap IRQs.
+        match self.handler_table.lock(|table| table[irq_number]) {
+            None => panic!("No handler registered for IRQ {}", irq_number),
+            Some(descriptor) => {
+                // Call the IRQ handler. Panics on failure.
+                descriptor.handler().handle().expect("Error handling IRQ");
+
+                if self.storm_detector.record(irq_number) {
+                    self.gicd.disable(&IRQNumber::new(irq_number));
+                    exception::asynchronous::report_storm(irq_number, descriptor.name());
                 }
             }
-        });
+        }
 
         // Signal completion of handling.
-        self.gicc.mark_comleted(irq_number as u32, ic);
+        self.gicc.mark_comleted(irq_number as u32, source_core, ic);
     }
 
     fn print_handler(&self) {
@@ -223,7 +303,7 @@ impl exception::asynchronous::interface::IRQManager for GICv2 {
 
         info!("      Peripheral handler:");
 
-        self.handler_table.read(|table| {
+        self.handler_table.lock(|table| {
             for (i, opt) in table.iter().skip(32).enumerate() {
                 if let Some(handler) = opt {
                     info!("            {: >3}. {}", i + 32, handler.name());
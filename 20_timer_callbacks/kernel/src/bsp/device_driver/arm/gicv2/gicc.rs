@@ -34,12 +34,14 @@ register_bitfields! {
 
     /// Interrupt Acknowledge Register
     IAR [
-        InterruptID OFFSET(0) NUMBITS(10) []
+        CPUID       OFFSET(10) NUMBITS(3)  [],
+        InterruptID OFFSET(0)  NUMBITS(10) []
     ],
 
     /// End of Interrupt Register
     EOIR [
-        EOIINTID OFFSET(0) NUMBITS(10) []
+        CPUID    OFFSET(10) NUMBITS(3)  [],
+        EOIINTID OFFSET(0)  NUMBITS(10) []
     ]
 }
 
@@ -108,7 +110,8 @@ impl GICC {
         self.registers.CTLR.write(CTLR::Enable::SET);
     }
 
-    /// Extract the number of the highest-priority pending IRQ.
+    /// Extract the number of the highest-priority pending IRQ, together with the id of the core
+    /// that requested it if it is an SGI.
     ///
     /// Can only be called from IRQ context, which is ensured by taking an `IRQContext` token.
     ///
@@ -120,15 +123,17 @@ impl GICC {
     pub fn pending_irq_number<'irq_context>(
         &self,
         _ic: &exception::asynchronous::IRQContext<'irq_context>,
-    ) -> usize {
-        self.registers.IAR.read(IAR::InterruptID) as usize
+    ) -> (usize, u32) {
+        let iar = self.registers.IAR.extract();
+
+        (iar.read(IAR::InterruptID) as usize, iar.read(IAR::CPUID))
     }
 
     /// Complete handling of the currently active IRQ.
     ///
     /// Can only be called from IRQ context, which is ensured by taking an `IRQContext` token.
     ///
-    /// To be called after `pending_irq_number()`.
+    /// To be called after `pending_irq_number()`, with the values it returned.
     ///
     /// # Safety
     ///
@@ -138,8 +143,11 @@ impl GICC {
     pub fn mark_comleted<'irq_context>(
         &self,
         irq_number: u32,
+        source_core: u32,
         _ic: &exception::asynchronous::IRQContext<'irq_context>,
     ) {
-        self.registers.EOIR.write(EOIR::EOIINTID.val(irq_number));
+        self.registers
+            .EOIR
+            .write(EOIR::EOIINTID.val(irq_number) + EOIR::CPUID.val(source_core));
     }
 }
@@ -6,6 +6,7 @@
 //!
 //! # Glossary
 //!   - SPI - Shared Peripheral Interrupt.
+//!   - SGI - Software Generated Interrupt.
 
 use crate::{
     bsp::device_driver::common::MMIODerefWrapper,
@@ -16,7 +17,7 @@ use crate::{
 use tock_registers::{
     interfaces::{Readable, Writeable},
     register_bitfields, register_structs,
-    registers::{ReadOnly, ReadWrite},
+    registers::{ReadOnly, ReadWrite, WriteOnly},
 };
 
 //--------------------------------------------------------------------------------------------------
@@ -42,6 +43,15 @@ register_bitfields! {
         Offset2 OFFSET(16) NUMBITS(8) [],
         Offset1 OFFSET(8)  NUMBITS(8) [],
         Offset0 OFFSET(0)  NUMBITS(8) []
+    ],
+
+    /// Software Generated Interrupt Register
+    SGIR [
+        TargetListFilter OFFSET(24) NUMBITS(2) [
+            TargetList = 0b00
+        ],
+        CPUTargetList    OFFSET(16) NUMBITS(8) [],
+        SGIINTID         OFFSET(0)  NUMBITS(4) []
     ]
 }
 
@@ -53,8 +63,12 @@ register_structs! {
         (0x008 => _reserved1),
         (0x104 => ISENABLER: [ReadWrite<u32>; 31]),
         (0x180 => _reserved2),
+        (0x184 => ICENABLER: [ReadWrite<u32>; 31]),
+        (0x200 => _reserved3),
         (0x820 => ITARGETSR: [ReadWrite<u32, ITARGETSR::Register>; 248]),
-        (0xC00 => @END),
+        (0xC00 => _reserved4),
+        (0xF00 => SGIR: WriteOnly<u32, SGIR::Register>),
+        (0xF04 => @END),
     }
 }
 
@@ -64,6 +78,8 @@ register_structs! {
         (0x000 => _reserved1),
         (0x100 => ISENABLER: ReadWrite<u32>),
         (0x104 => _reserved2),
+        (0x180 => ICENABLER: ReadWrite<u32>),
+        (0x184 => _reserved3),
         (0x800 => ITARGETSR: [ReadOnly<u32, ITARGETSR::Register>; 8]),
         (0x820 => @END),
     }
@@ -171,6 +187,17 @@ impl GICD {
         });
     }
 
+    /// Raise SGI `sgi` on the core with CPU interface number `core`.
+    pub fn send_sgi(&self, core: usize, sgi: usize) {
+        self.shared_registers.lock(|regs| {
+            regs.SGIR.write(
+                SGIR::TargetListFilter::TargetList
+                    + SGIR::CPUTargetList.val(1 << core)
+                    + SGIR::SGIINTID.val(sgi as u32),
+            )
+        });
+    }
+
     /// Enable an interrupt.
     pub fn enable(&self, irq_num: &super::IRQNumber) {
         let irq_num = irq_num.get();
@@ -198,4 +225,43 @@ impl GICD {
             }
         }
     }
+
+    /// Return if an interrupt is enabled.
+    pub fn is_enabled(&self, irq_num: &super::IRQNumber) -> bool {
+        let irq_num = irq_num.get();
+        let bit: u32 = 1u32 << (irq_num % 32);
+
+        let reg = match irq_num {
+            // Private.
+            0..=31 => self.banked_registers.ISENABLER.get(),
+            // Shared.
+            _ => self
+                .shared_registers
+                .lock(|regs| regs.ISENABLER[(irq_num >> 5) - 1].get()),
+        };
+
+        reg & bit != 0
+    }
+
+    /// Disable an interrupt.
+    pub fn disable(&self, irq_num: &super::IRQNumber) {
+        let irq_num = irq_num.get();
+
+        // Writing a 1 to a bit of ICENABLER disables the IRQ. Zeros have no effect, so no
+        // read-modify-write is needed.
+        let disable_reg_index = irq_num >> 5;
+        let disable_bit: u32 = 1u32 << (irq_num % 32);
+
+        match irq_num {
+            // Private.
+            0..=31 => self.banked_registers.ICENABLER.set(disable_bit),
+            // Shared.
+            _ => {
+                let disable_reg_index_shared = disable_reg_index - 1;
+
+                self.shared_registers
+                    .lock(|regs| regs.ICENABLER[disable_reg_index_shared].set(disable_bit));
+            }
+        }
+    }
 }
@@ -4,12 +4,40 @@
 
 //! BCM driver top level.
 
+#[cfg(feature = "bsp_rpi4")]
+mod bcm2711_genet;
+mod bcm2xxx_dma;
+#[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+mod bcm2xxx_dwc_otg;
+mod bcm2xxx_emmc;
 mod bcm2xxx_gpio;
 #[cfg(feature = "bsp_rpi3")]
 mod bcm2xxx_interrupt_controller;
+mod bcm2xxx_mailbox;
+mod bcm2xxx_mini_uart;
 mod bcm2xxx_pl011_uart;
+mod bcm2xxx_pwm;
+#[cfg(feature = "bsp_rpi3")]
+mod bcm2xxx_rng;
+mod bcm2xxx_system_timer;
+mod bcm2xxx_watchdog;
+mod cyw43438;
 
+#[cfg(feature = "bsp_rpi4")]
+pub use bcm2711_genet::*;
+pub use bcm2xxx_dma::*;
+#[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+pub use bcm2xxx_dwc_otg::*;
+pub use bcm2xxx_emmc::*;
 pub use bcm2xxx_gpio::*;
 #[cfg(feature = "bsp_rpi3")]
 pub use bcm2xxx_interrupt_controller::*;
+pub use bcm2xxx_mailbox::*;
+pub use bcm2xxx_mini_uart::*;
 pub use bcm2xxx_pl011_uart::*;
+pub use bcm2xxx_pwm::*;
+#[cfg(feature = "bsp_rpi3")]
+pub use bcm2xxx_rng::*;
+pub use bcm2xxx_system_timer::*;
+pub use bcm2xxx_watchdog::*;
+pub use cyw43438::*;
@@ -0,0 +1,750 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! GENET v5 Ethernet controller driver, as found in the BCM2711.
+//!
+//! The MAC talks RGMII to an external BCM54213PE PHY at MDIO address 1. A single DMA ring, the
+//! default ring 16, is used in each direction. Its descriptors live in the controller, the frames
+//! in buffers on the heap, which the controller addresses by their ARM physical address. Nothing
+//! is interrupt driven: the network stack polls for received frames, and the PHY's link state is
+//! checked from there.
+//!
+//! The controller doesn't snoop the CPU caches, so the buffers are written back and invalidated
+//! around every transfer.
+//!
+//! The Raspberry Pi 3 has no GENET. Its Ethernet port is a LAN9514 behind the on-board USB hub,
+//! which the USB host mode doesn't support.
+//!
+//! # Resources
+//!
+//! - Linux `drivers/net/ethernet/broadcom/genet`
+//! - U-Boot `drivers/net/bcmgenet.c`
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    cpu, driver,
+    exception::asynchronous::IRQNumber,
+    memory::{self, Address, Virtual},
+    net::{self, MacAddress},
+    spin_until, synchronization,
+    synchronization::IRQSafeNullLock,
+    time,
+};
+use alloc::vec::Vec;
+use core::time::Duration;
+use tock_registers::{
+    interfaces::{ReadWriteable, Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::{ReadOnly, ReadWrite},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// GENET v5 reports itself as major revision 6.
+const MAJOR_REVISION: u32 = 6;
+
+/// Port mode of a MAC with an external gigabit PHY.
+const PORT_MODE_EXT_GPHY: u32 = 3;
+
+/// MDIO address of the PHY.
+const PHY_ADDRESS: u32 = 1;
+
+/// The DMA ring all traffic goes through.
+const DEFAULT_RING: u32 = 16;
+
+/// Descriptors used per direction, of the 256 the controller has for each.
+const RING_DESCS: usize = 32;
+
+/// Words per descriptor, for the ring's start and end addresses.
+const DESC_WORDS: u32 = 3;
+
+/// Length of a DMA buffer, enough for a full frame plus the 2 bytes of alignment padding.
+const BUF_LEN: usize = 2048;
+
+/// Longest frame the MAC accepts: 1500 bytes of payload, header, VLAN tag, Broadcom tag, FCS and
+/// padding.
+const MAX_FRAME_LEN: u32 = 1536;
+
+/// Longest and shortest frame handed to the MAC, without FCS.
+const MAX_TX_LEN: usize = 1514;
+const MIN_TX_LEN: usize = 60;
+
+/// Received frames start after 2 bytes, so that the IP header is word aligned.
+const RX_PADDING: usize = 2;
+
+/// Burst length of the DMA engines, in 64 bit words.
+const DMA_BURST_LENGTH: u32 = 8;
+
+/// Flow control thresholds of the RX ring, in descriptors.
+const XOFF_THRESHOLD: u32 = 5;
+const XON_THRESHOLD: u32 = RING_DESCS as u32 >> 4;
+
+/// How often the PHY is asked for the link state at most.
+const LINK_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long to wait for the controller before giving up.
+const TIMEOUT: Duration = Duration::from_millis(10);
+
+/// The interface's name in the network stack.
+const INTERFACE_NAME: &str = "eth0";
+
+// PHY registers and their bits.
+const MII_BMCR: u32 = 0x00;
+const MII_BMSR: u32 = 0x01;
+const MII_PHYSID1: u32 = 0x02;
+const MII_ADVERTISE: u32 = 0x04;
+const MII_LPA: u32 = 0x05;
+const MII_CTRL1000: u32 = 0x09;
+const MII_STAT1000: u32 = 0x0A;
+
+const BMCR_ANENABLE: u16 = 1 << 12;
+const BMCR_ANRESTART: u16 = 1 << 9;
+const BMSR_LSTATUS: u16 = 1 << 2;
+const ADVERTISE_100: u16 = (1 << 8) | (1 << 7);
+const CTRL1000_ADVERTISE_1000: u16 = (1 << 9) | (1 << 8);
+
+/// STAT1000 reports the link partner's abilities 2 bits above CTRL1000's advertisement.
+const STAT1000_SHIFT: u16 = 2;
+
+register_bitfields! {
+    u32,
+
+    /// RX Buffer Flush Control
+    RBUF_FLUSH_CTRL [
+        RESET OFFSET(1) NUMBITS(1) []
+    ],
+
+    /// RGMII Out-of-Band Control
+    RGMII_OOB_CTRL [
+        ID_MODE_DIS OFFSET(16) NUMBITS(1) [],
+        RGMII_MODE_EN OFFSET(6) NUMBITS(1) [],
+        OOB_DISABLE OFFSET(5) NUMBITS(1) [],
+        RGMII_LINK OFFSET(4) NUMBITS(1) []
+    ],
+
+    /// RX Buffer Control
+    RBUF_CTRL [
+        ALIGN_2B OFFSET(1) NUMBITS(1) [],
+        STATUS_64B OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// UniMAC Command
+    UMAC_CMD [
+        LCL_LOOP_EN OFFSET(15) NUMBITS(1) [],
+        SW_RESET OFFSET(13) NUMBITS(1) [],
+        CRC_FWD OFFSET(6) NUMBITS(1) [],
+        PROMISC OFFSET(4) NUMBITS(1) [],
+        SPEED OFFSET(2) NUMBITS(2) [
+            Speed10 = 0,
+            Speed100 = 1,
+            Speed1000 = 2
+        ],
+        RX_EN OFFSET(1) NUMBITS(1) [],
+        TX_EN OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// MIB Counter Control
+    MIB_CTRL [
+        RESET_TX OFFSET(2) NUMBITS(1) [],
+        RESET_RUNT OFFSET(1) NUMBITS(1) [],
+        RESET_RX OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// MDIO Command
+    MDIO_CMD [
+        START_BUSY OFFSET(29) NUMBITS(1) [],
+        READ_FAIL OFFSET(28) NUMBITS(1) [],
+        OP OFFSET(26) NUMBITS(2) [
+            Write = 1,
+            Read = 2
+        ],
+        PMD OFFSET(21) NUMBITS(5) [],
+        REG OFFSET(16) NUMBITS(5) [],
+        DATA OFFSET(0) NUMBITS(16) []
+    ],
+
+    /// DMA Descriptor Length and Status
+    DESC_STATUS [
+        BUFLENGTH OFFSET(16) NUMBITS(12) [],
+        OWN OFFSET(15) NUMBITS(1) [],
+        EOP OFFSET(14) NUMBITS(1) [],
+        SOP OFFSET(13) NUMBITS(1) [],
+
+        /// TX: tag of the queue the frame came from
+        QTAG OFFSET(7) NUMBITS(6) [],
+
+        /// TX: append the FCS
+        APPEND_CRC OFFSET(6) NUMBITS(1) [],
+
+        /// RX: too long, no buffer, receive error, CRC error and overrun
+        RX_ERRORS OFFSET(0) NUMBITS(5) []
+    ],
+
+    /// DMA Ring Buffer Size
+    RING_BUF_SIZE [
+        DESCS OFFSET(16) NUMBITS(16) [],
+        BUF_LEN OFFSET(0) NUMBITS(16) []
+    ],
+
+    /// DMA Control
+    DMA_CTRL [
+        RING_EN OFFSET(1) NUMBITS(17) [],
+        EN OFFSET(0) NUMBITS(1) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    Descriptor {
+        (0x00 => LENGTH_STATUS: ReadWrite<u32, DESC_STATUS::Register>),
+        (0x04 => ADDRESS_LO: ReadWrite<u32>),
+        (0x08 => ADDRESS_HI: ReadWrite<u32>),
+        (0x0C => @END),
+    },
+
+    #[allow(non_snake_case)]
+    RxRing {
+        (0x00 => WRITE_PTR: ReadWrite<u32>),
+        (0x04 => _reserved1),
+        (0x08 => PROD_INDEX: ReadWrite<u32>),
+        (0x0C => CONS_INDEX: ReadWrite<u32>),
+        (0x10 => BUF_SIZE: ReadWrite<u32, RING_BUF_SIZE::Register>),
+        (0x14 => START_ADDR: ReadWrite<u32>),
+        (0x18 => _reserved2),
+        (0x1C => END_ADDR: ReadWrite<u32>),
+        (0x20 => _reserved3),
+        (0x28 => XON_XOFF_THRESH: ReadWrite<u32>),
+        (0x2C => READ_PTR: ReadWrite<u32>),
+        (0x30 => _reserved4),
+        (0x40 => @END),
+    },
+
+    #[allow(non_snake_case)]
+    TxRing {
+        (0x00 => READ_PTR: ReadWrite<u32>),
+        (0x04 => _reserved1),
+        (0x08 => CONS_INDEX: ReadWrite<u32>),
+        (0x0C => PROD_INDEX: ReadWrite<u32>),
+        (0x10 => BUF_SIZE: ReadWrite<u32, RING_BUF_SIZE::Register>),
+        (0x14 => START_ADDR: ReadWrite<u32>),
+        (0x18 => _reserved2),
+        (0x1C => END_ADDR: ReadWrite<u32>),
+        (0x20 => _reserved3),
+        (0x24 => MBUF_DONE_THRESH: ReadWrite<u32>),
+        (0x28 => FLOW_PERIOD: ReadWrite<u32>),
+        (0x2C => WRITE_PTR: ReadWrite<u32>),
+        (0x30 => _reserved4),
+        (0x40 => @END),
+    },
+
+    #[allow(non_snake_case)]
+    DmaBlock {
+        (0x00 => RING_CFG: ReadWrite<u32>),
+        (0x04 => CTRL: ReadWrite<u32, DMA_CTRL::Register>),
+        (0x08 => _reserved1),
+        (0x0C => SCB_BURST_SIZE: ReadWrite<u32>),
+        (0x10 => @END),
+    },
+
+    #[allow(non_snake_case)]
+    pub RegisterBlock {
+        (0x0000 => SYS_REV_CTRL: ReadOnly<u32>),
+        (0x0004 => SYS_PORT_CTRL: ReadWrite<u32>),
+        (0x0008 => SYS_RBUF_FLUSH_CTRL: ReadWrite<u32, RBUF_FLUSH_CTRL::Register>),
+        (0x000C => _reserved1),
+        (0x008C => EXT_RGMII_OOB_CTRL: ReadWrite<u32, RGMII_OOB_CTRL::Register>),
+        (0x0090 => _reserved2),
+        (0x0300 => RBUF_CTRL: ReadWrite<u32, RBUF_CTRL::Register>),
+        (0x0304 => _reserved3),
+        (0x03B4 => RBUF_TBUF_SIZE_CTRL: ReadWrite<u32>),
+        (0x03B8 => _reserved4),
+        (0x0808 => UMAC_CMD: ReadWrite<u32, UMAC_CMD::Register>),
+        (0x080C => UMAC_MAC0: ReadWrite<u32>),
+        (0x0810 => UMAC_MAC1: ReadWrite<u32>),
+        (0x0814 => UMAC_MAX_FRAME_LEN: ReadWrite<u32>),
+        (0x0818 => _reserved5),
+        (0x0B34 => UMAC_TX_FLUSH: ReadWrite<u32>),
+        (0x0B38 => _reserved6),
+        (0x0D80 => UMAC_MIB_CTRL: ReadWrite<u32, MIB_CTRL::Register>),
+        (0x0D84 => _reserved7),
+        (0x0E14 => MDIO_CMD: ReadWrite<u32, MDIO_CMD::Register>),
+        (0x0E18 => _reserved8),
+        (0x2000 => RX_DESC: [Descriptor; 256]),
+        (0x2C00 => _reserved9),
+        (0x3000 => RDMA_RING: RxRing),
+        (0x3040 => RDMA: DmaBlock),
+        (0x3050 => _reserved10),
+        (0x4000 => TX_DESC: [Descriptor; 256]),
+        (0x4C00 => _reserved11),
+        (0x5000 => TDMA_RING: TxRing),
+        (0x5040 => TDMA: DmaBlock),
+        (0x5050 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+/// A DMA buffer. Cache line aligned, so that maintenance doesn't touch neighbouring data.
+#[repr(C, align(64))]
+struct Buffer([u8; BUF_LEN]);
+
+/// The DMA rings and where the driver is in them.
+struct Rings {
+    rx_buffers: Vec<Buffer>,
+    tx_buffers: Vec<Buffer>,
+
+    /// Next descriptor to receive from and value of the consumer counter.
+    rx_index: usize,
+    rx_cons: u16,
+
+    /// Next descriptor to send from and value of the producer counter.
+    tx_index: usize,
+    tx_prod: u16,
+}
+
+struct GenetInner {
+    registers: Registers,
+    mac: MacAddress,
+    rings: Option<Rings>,
+
+    /// Speed in Mbit/s while the link is up.
+    link: Option<u32>,
+    last_link_check: Option<Duration>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the GENET Ethernet controller.
+pub struct Genet {
+    inner: IRQSafeNullLock<GenetInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return the address the controller sees for kernel memory at `addr`, its physical address.
+fn dma_address(addr: usize) -> Result<u32, &'static str> {
+    let phys_addr = memory::mmu::try_kernel_virt_addr_to_phys_addr(Address::<Virtual>::new(addr))?;
+
+    Ok(phys_addr.as_usize() as u32)
+}
+
+impl GenetInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+            mac: MacAddress::ZERO,
+            rings: None,
+            link: None,
+            last_link_check: None,
+        }
+    }
+
+    fn mdio(&self, cmd: u32) -> Result<u16, &'static str> {
+        self.registers.MDIO_CMD.set(cmd);
+        self.registers.MDIO_CMD.modify(MDIO_CMD::START_BUSY::SET);
+
+        spin_until!(
+            !self.registers.MDIO_CMD.is_set(MDIO_CMD::START_BUSY),
+            TIMEOUT,
+            "MDIO timeout",
+            self.registers.MDIO_CMD.get()
+        )
+        .map_err(|x| x.context)?;
+
+        Ok(self.registers.MDIO_CMD.read(MDIO_CMD::DATA) as u16)
+    }
+
+    fn phy_read(&self, reg: u32) -> Result<u16, &'static str> {
+        let value = self.mdio(
+            (MDIO_CMD::OP::Read + MDIO_CMD::PMD.val(PHY_ADDRESS) + MDIO_CMD::REG.val(reg)).value,
+        )?;
+        if self.registers.MDIO_CMD.is_set(MDIO_CMD::READ_FAIL) {
+            return Err("PHY read failed");
+        }
+
+        Ok(value)
+    }
+
+    fn phy_write(&self, reg: u32, value: u16) -> Result<(), &'static str> {
+        self.mdio(
+            (MDIO_CMD::OP::Write
+                + MDIO_CMD::PMD.val(PHY_ADDRESS)
+                + MDIO_CMD::REG.val(reg)
+                + MDIO_CMD::DATA.val(value as u32))
+            .value,
+        )?;
+
+        Ok(())
+    }
+
+    /// Reset the UniMAC and the RX buffer, clear the counters and set the frame format.
+    fn reset_umac(&self) {
+        let spin = |us| time::time_manager().spin_for(Duration::from_micros(us));
+
+        self.registers
+            .SYS_RBUF_FLUSH_CTRL
+            .modify(RBUF_FLUSH_CTRL::RESET::SET);
+        spin(10);
+        self.registers.SYS_RBUF_FLUSH_CTRL.set(0);
+        spin(10);
+
+        self.registers.UMAC_CMD.set(0);
+        self.registers
+            .UMAC_CMD
+            .write(UMAC_CMD::SW_RESET::SET + UMAC_CMD::LCL_LOOP_EN::SET);
+        spin(2);
+        self.registers.UMAC_CMD.set(0);
+
+        self.registers
+            .UMAC_MIB_CTRL
+            .write(MIB_CTRL::RESET_RX::SET + MIB_CTRL::RESET_RUNT::SET + MIB_CTRL::RESET_TX::SET);
+        self.registers.UMAC_MIB_CTRL.set(0);
+        self.registers.UMAC_MAX_FRAME_LEN.set(MAX_FRAME_LEN);
+
+        self.registers
+            .RBUF_CTRL
+            .modify(RBUF_CTRL::ALIGN_2B::SET + RBUF_CTRL::STATUS_64B::CLEAR);
+        self.registers.RBUF_TBUF_SIZE_CTRL.set(1);
+    }
+
+    fn set_mac_address(&self) {
+        let m = &self.mac.0;
+
+        self.registers
+            .UMAC_MAC0
+            .set(u32::from_be_bytes([m[0], m[1], m[2], m[3]]));
+        self.registers
+            .UMAC_MAC1
+            .set(u32::from_be_bytes([0, 0, m[4], m[5]]));
+    }
+
+    fn disable_dma(&self) {
+        self.registers.TDMA.CTRL.modify(DMA_CTRL::EN::CLEAR);
+        self.registers.RDMA.CTRL.modify(DMA_CTRL::EN::CLEAR);
+
+        self.registers.UMAC_TX_FLUSH.set(1);
+        time::time_manager().spin_for(Duration::from_micros(10));
+        self.registers.UMAC_TX_FLUSH.set(0);
+    }
+
+    /// Hand all RX buffers to the controller and set up both rings on the default ring.
+    fn init_rings(&self, rings: &mut Rings) -> Result<(), &'static str> {
+        let end_addr = RING_DESCS as u32 * DESC_WORDS - 1;
+        let buf_size = RING_BUF_SIZE::DESCS.val(RING_DESCS as u32)
+            + RING_BUF_SIZE::BUF_LEN.val(BUF_LEN as u32);
+
+        // Nothing may be dirty in the cache when the controller writes the buffers.
+        cpu::clean_invalidate_dcache(
+            rings.rx_buffers.as_ptr() as usize,
+            rings.rx_buffers.len() * BUF_LEN,
+        );
+        for (desc, buffer) in self.registers.RX_DESC.iter().zip(&rings.rx_buffers) {
+            desc.ADDRESS_LO
+                .set(dma_address(buffer.0.as_ptr() as usize)?);
+            desc.ADDRESS_HI.set(0);
+            desc.LENGTH_STATUS
+                .write(DESC_STATUS::BUFLENGTH.val(BUF_LEN as u32) + DESC_STATUS::OWN::SET);
+        }
+
+        let rx = &self.registers.RDMA_RING;
+        self.registers.RDMA.SCB_BURST_SIZE.set(DMA_BURST_LENGTH);
+        rx.START_ADDR.set(0);
+        rx.READ_PTR.set(0);
+        rx.WRITE_PTR.set(0);
+        rx.END_ADDR.set(end_addr);
+        // The producer counter can't be written, so the consumer counter catches up with it.
+        rings.rx_cons = rx.PROD_INDEX.get() as u16;
+        rx.CONS_INDEX.set(rings.rx_cons as u32);
+        rings.rx_index = 0;
+        rx.BUF_SIZE.write(buf_size);
+        rx.XON_XOFF_THRESH
+            .set((XOFF_THRESHOLD << 16) | XON_THRESHOLD);
+        self.registers.RDMA.RING_CFG.set(1 << DEFAULT_RING);
+
+        let tx = &self.registers.TDMA_RING;
+        self.registers.TDMA.SCB_BURST_SIZE.set(DMA_BURST_LENGTH);
+        tx.START_ADDR.set(0);
+        tx.READ_PTR.set(0);
+        tx.WRITE_PTR.set(0);
+        tx.END_ADDR.set(end_addr);
+        // Likewise, the consumer counter can't be written.
+        rings.tx_prod = tx.CONS_INDEX.get() as u16;
+        tx.PROD_INDEX.set(rings.tx_prod as u32);
+        rings.tx_index = 0;
+        tx.MBUF_DONE_THRESH.set(1);
+        tx.FLOW_PERIOD.set(0);
+        tx.BUF_SIZE.write(buf_size);
+        self.registers.TDMA.RING_CFG.set(1 << DEFAULT_RING);
+
+        Ok(())
+    }
+
+    fn enable_dma(&self) {
+        let ctrl = DMA_CTRL::EN::SET + DMA_CTRL::RING_EN.val(1 << DEFAULT_RING);
+
+        self.registers.TDMA.CTRL.write(ctrl);
+        self.registers.RDMA.CTRL.modify(ctrl);
+    }
+
+    /// Reset the controller, set up the rings and start autonegotiation on the PHY.
+    fn start(&mut self, mac: MacAddress) -> Result<(), &'static str> {
+        if self.rings.is_some() {
+            return Err("Already running");
+        }
+        if (self.registers.SYS_REV_CTRL.get() >> 24) & 0xf != MAJOR_REVISION {
+            return Err("No GENET v5 core found");
+        }
+
+        self.registers.SYS_PORT_CTRL.set(PORT_MODE_EXT_GPHY);
+        self.reset_umac();
+        if self.phy_read(MII_PHYSID1)? == 0xffff {
+            return Err("No Ethernet PHY");
+        }
+
+        self.mac = mac;
+        self.set_mac_address();
+        self.disable_dma();
+
+        let mut rings = Rings {
+            rx_buffers: (0..RING_DESCS).map(|_| Buffer([0; BUF_LEN])).collect(),
+            tx_buffers: (0..RING_DESCS).map(|_| Buffer([0; BUF_LEN])).collect(),
+            rx_index: 0,
+            rx_cons: 0,
+            tx_index: 0,
+            tx_prod: 0,
+        };
+        self.init_rings(&mut rings)?;
+        self.enable_dma();
+        self.phy_write(MII_BMCR, BMCR_ANENABLE | BMCR_ANRESTART)?;
+
+        self.rings = Some(rings);
+        self.link = None;
+        self.last_link_check = None;
+
+        Ok(())
+    }
+
+    /// The best speed both ends advertised, in Mbit/s.
+    fn negotiated_speed(&self) -> Result<u32, &'static str> {
+        let ctrl1000 = self.phy_read(MII_CTRL1000)?;
+        let stat1000 = self.phy_read(MII_STAT1000)?;
+        if ctrl1000 & (stat1000 >> STAT1000_SHIFT) & CTRL1000_ADVERTISE_1000 != 0 {
+            return Ok(1000);
+        }
+
+        let common = self.phy_read(MII_ADVERTISE)? & self.phy_read(MII_LPA)?;
+        if common & ADVERTISE_100 != 0 {
+            Ok(100)
+        } else {
+            Ok(10)
+        }
+    }
+
+    /// Ask the PHY for the link state, at most every [`LINK_CHECK_INTERVAL`], and set the MAC up
+    /// for the negotiated speed when the link comes up.
+    fn check_link(&mut self) -> Result<(), &'static str> {
+        let now = time::time_manager().uptime();
+        if self.rings.is_none()
+            || matches!(self.last_link_check, Some(t) if now - t < LINK_CHECK_INTERVAL)
+        {
+            return Ok(());
+        }
+        self.last_link_check = Some(now);
+
+        // The link bit latches low, so the first read reports whether the link dropped.
+        self.phy_read(MII_BMSR)?;
+        let up = self.phy_read(MII_BMSR)? & BMSR_LSTATUS != 0;
+
+        match (up, self.link) {
+            (true, None) => {
+                let speed = self.negotiated_speed()?;
+                let cmd_speed = match speed {
+                    1000 => UMAC_CMD::SPEED::Speed1000,
+                    100 => UMAC_CMD::SPEED::Speed100,
+                    _ => UMAC_CMD::SPEED::Speed10,
+                };
+
+                // The board delays RX clock on the PHY, TX clock in the MAC.
+                self.registers.EXT_RGMII_OOB_CTRL.modify(
+                    RGMII_OOB_CTRL::OOB_DISABLE::CLEAR
+                        + RGMII_OOB_CTRL::RGMII_LINK::SET
+                        + RGMII_OOB_CTRL::RGMII_MODE_EN::SET,
+                );
+                self.registers
+                    .UMAC_CMD
+                    .write(cmd_speed + UMAC_CMD::TX_EN::SET + UMAC_CMD::RX_EN::SET);
+                self.link = Some(speed);
+            }
+            (false, Some(_)) => {
+                self.registers
+                    .UMAC_CMD
+                    .modify(UMAC_CMD::TX_EN::CLEAR + UMAC_CMD::RX_EN::CLEAR);
+                self.link = None;
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn transmit(&mut self, frame: &[u8]) -> Result<(), &'static str> {
+        if self.link.is_none() {
+            return Err("Link down");
+        }
+        if frame.len() > MAX_TX_LEN {
+            return Err("Frame too long");
+        }
+        let rings = self.rings.as_mut().ok_or("Not running")?;
+
+        let cons = self.registers.TDMA_RING.CONS_INDEX.get() as u16;
+        if rings.tx_prod.wrapping_sub(cons) as usize >= RING_DESCS {
+            return Err("TX ring full");
+        }
+
+        // Short frames are padded to the minimum length.
+        let len = frame.len().max(MIN_TX_LEN);
+        let buffer = &mut rings.tx_buffers[rings.tx_index].0;
+        buffer[..frame.len()].copy_from_slice(frame);
+        buffer[frame.len()..len].fill(0);
+        cpu::clean_invalidate_dcache(buffer.as_ptr() as usize, len);
+
+        let desc = &self.registers.TX_DESC[rings.tx_index];
+        desc.ADDRESS_LO.set(dma_address(buffer.as_ptr() as usize)?);
+        desc.ADDRESS_HI.set(0);
+        desc.LENGTH_STATUS.write(
+            DESC_STATUS::BUFLENGTH.val(len as u32)
+                + DESC_STATUS::QTAG.val(0x3f)
+                + DESC_STATUS::APPEND_CRC::SET
+                + DESC_STATUS::SOP::SET
+                + DESC_STATUS::EOP::SET,
+        );
+
+        rings.tx_index = (rings.tx_index + 1) % RING_DESCS;
+        rings.tx_prod = rings.tx_prod.wrapping_add(1);
+        self.registers
+            .TDMA_RING
+            .PROD_INDEX
+            .set(rings.tx_prod as u32);
+
+        Ok(())
+    }
+
+    /// Return the next good frame. Frames with errors are skipped.
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        let rings = self.rings.as_mut()?;
+
+        loop {
+            let prod = self.registers.RDMA_RING.PROD_INDEX.get() as u16;
+            if prod == rings.rx_cons {
+                return None;
+            }
+
+            let status = self.registers.RX_DESC[rings.rx_index]
+                .LENGTH_STATUS
+                .extract();
+            let len = status.read(DESC_STATUS::BUFLENGTH) as usize;
+            let buffer = &rings.rx_buffers[rings.rx_index].0;
+            cpu::clean_invalidate_dcache(buffer.as_ptr() as usize, BUF_LEN);
+
+            let good = status.read(DESC_STATUS::RX_ERRORS) == 0
+                && status.is_set(DESC_STATUS::SOP)
+                && status.is_set(DESC_STATUS::EOP)
+                && (RX_PADDING..=BUF_LEN).contains(&len);
+            let frame = good.then(|| buffer[RX_PADDING..len].to_vec());
+
+            rings.rx_index = (rings.rx_index + 1) % RING_DESCS;
+            rings.rx_cons = rings.rx_cons.wrapping_add(1);
+            self.registers
+                .RDMA_RING
+                .CONS_INDEX
+                .set(rings.rx_cons as u32);
+
+            if frame.is_some() {
+                return frame;
+            }
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Genet {
+    pub const COMPATIBLE: &'static str = "BCM GENET v5";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(GenetInner::new(mmio_start_addr)),
+        }
+    }
+
+    /// Bring the controller up with the MAC address `mac`. The link comes up in the background,
+    /// once autonegotiation finished.
+    pub fn start(&self, mac: [u8; 6]) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.start(MacAddress(mac)))
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Genet {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+}
+
+impl net::interface::NetDevice for Genet {
+    fn name(&self) -> &'static str {
+        INTERFACE_NAME
+    }
+
+    fn mac_address(&self) -> MacAddress {
+        self.inner.lock(|inner| inner.mac)
+    }
+
+    fn transmit(&self, frame: &[u8]) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.transmit(frame))
+    }
+
+    fn receive(&self) -> Option<Vec<u8>> {
+        self.inner.lock(|inner| inner.receive())
+    }
+
+    /// A PHY that stops answering counts as link down.
+    fn is_link_up(&self) -> bool {
+        self.inner.lock(|inner| {
+            if inner.check_link().is_err() {
+                inner.link = None;
+            }
+            inner.link.is_some()
+        })
+    }
+
+    fn link_speed(&self) -> Option<u32> {
+        self.inner.lock(|inner| inner.link)
+    }
+}
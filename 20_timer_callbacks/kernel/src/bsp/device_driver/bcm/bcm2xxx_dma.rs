@@ -0,0 +1,470 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! DMA controller driver.
+//!
+//! Drives a single channel of the legacy DMA engine, which the firmware leaves unused. A transfer
+//! is described by a chain of control blocks in memory, the bus address of the first one is written
+//! to the channel.
+//!
+//! Memory fills block until they ended: the source address doesn't increment and points at a word
+//! holding the fill value. [`dma::Transfer`]s run in the background, one control block per step,
+//! and the last one raises the channel's IRQ. A fill while a transfer runs is refused, and done by
+//! the CPU.
+//!
+//! The engine doesn't snoop the CPU caches, so the memory it touches is written back and
+//! invalidated around every transfer.
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    cpu,
+    dma::{self, Peripheral, Step},
+    driver, exception,
+    exception::asynchronous::IRQNumber,
+    memory::{self, Address, Virtual},
+    spin_until, synchronization,
+    synchronization::IRQSafeNullLock,
+};
+use alloc::vec::Vec;
+use core::time::Duration;
+use tock_registers::{
+    interfaces::{ReadWriteable, Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::ReadWrite,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Offset of ARM RAM in the VideoCore's bus address space, bypassing its L2 cache.
+const BUS_ADDRESS_OFFSET: usize = 0xC000_0000;
+
+/// Largest transfer of a single control block.
+const MAX_TRANSFER: usize = 1 << 30;
+
+/// How long a transfer may take, generously above the engine's throughput.
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+register_bitfields! {
+    u32,
+
+    /// Control and Status
+    CS [
+        /// Reset the channel
+        RESET OFFSET(31) NUMBITS(1) [],
+
+        /// Wait for outstanding writes before signalling the end of a transfer
+        WAIT_FOR_OUTSTANDING_WRITES OFFSET(28) NUMBITS(1) [],
+
+        /// An error occurred, details are in DEBUG
+        ERROR OFFSET(8) NUMBITS(1) [],
+
+        /// A control block with INTEN ended. Write 1 to clear
+        INT OFFSET(2) NUMBITS(1) [],
+
+        /// The transfer ended. Write 1 to clear
+        END OFFSET(1) NUMBITS(1) [],
+
+        /// The channel is active
+        ACTIVE OFFSET(0) NUMBITS(1) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    pub RegisterBlock {
+        (0x00 => CS: ReadWrite<u32, CS::Register>),
+        (0x04 => CONBLK_AD: ReadWrite<u32>),
+        (0x08 => _reserved1),
+        (0x20 => DEBUG: ReadWrite<u32>),
+        (0x24 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+/// Transfer information bits of a control block.
+const TI_INTEN: u32 = 1 << 0;
+const TI_WAIT_RESP: u32 = 1 << 3;
+const TI_DEST_INC: u32 = 1 << 4;
+const TI_DEST_WIDTH_128: u32 = 1 << 5;
+const TI_DEST_DREQ: u32 = 1 << 6;
+const TI_SRC_INC: u32 = 1 << 8;
+const TI_SRC_WIDTH_128: u32 = 1 << 9;
+const TI_SRC_DREQ: u32 = 1 << 10;
+const TI_BURST_LENGTH_SHIFT: u32 = 12;
+const TI_PERMAP_SHIFT: u32 = 16;
+
+// Data request lines.
+const DREQ_SPI_TX: u32 = 6;
+const DREQ_SPI_RX: u32 = 7;
+const DREQ_PWM: u32 = 5;
+const DREQ_UART_TX: u32 = 12;
+const DREQ_UART_RX: u32 = 14;
+
+// Bus addresses of the peripheral FIFOs.
+const UART_FIFO: u32 = 0x7E20_1000;
+const SPI_FIFO: u32 = 0x7E20_4004;
+const PWM_FIFO: u32 = 0x7E20_C018;
+
+/// Burst length in 128 bit units. Longer bursts hog the bus.
+const BURST_LENGTH: u32 = 8;
+
+/// A transfer description read by the engine. Must be 32 byte aligned.
+#[derive(Default)]
+#[repr(C, align(32))]
+struct ControlBlock {
+    ti: u32,
+    source_ad: u32,
+    dest_ad: u32,
+    txfr_len: u32,
+    stride: u32,
+    nextconbk: u32,
+    _reserved: [u32; 2],
+}
+
+/// The fill value, repeated to the width of a 128 bit read.
+#[repr(C, align(32))]
+struct FillSource([u32; 4]);
+
+/// A transfer the engine is running.
+struct Active {
+    blocks: Vec<ControlBlock>,
+    buffer: Vec<u8>,
+    done: dma::Completion,
+}
+
+struct DmaInner {
+    registers: Registers,
+    control_block: ControlBlock,
+    source: FillSource,
+    active: Option<Active>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of a DMA channel.
+pub struct Dma {
+    inner: IRQSafeNullLock<DmaInner>,
+
+    /// Registers for [`driver::interface::DeviceDriver::emergency_stop()`], which must not take
+    /// the lock.
+    stop_registers: Registers,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return the bus address of kernel memory at `addr`. Regions of the kernel are mapped
+/// contiguously, so this holds for the whole object.
+fn bus_address(addr: usize) -> Result<u32, &'static str> {
+    let phys_addr = memory::mmu::try_kernel_virt_addr_to_phys_addr(Address::<Virtual>::new(addr))?;
+
+    Ok((phys_addr.as_usize() | BUS_ADDRESS_OFFSET) as u32)
+}
+
+/// Return the FIFO of a peripheral and its data request lines for writing and reading.
+fn fifo(peripheral: Peripheral) -> (u32, u32, u32) {
+    match peripheral {
+        Peripheral::Uart => (UART_FIFO, DREQ_UART_TX, DREQ_UART_RX),
+        Peripheral::Spi => (SPI_FIFO, DREQ_SPI_TX, DREQ_SPI_RX),
+        Peripheral::Pwm => (PWM_FIFO, DREQ_PWM, DREQ_PWM),
+    }
+}
+
+/// Build the control blocks of `steps` on `buffer`, not yet chained. Steps longer than a control
+/// block can move are split.
+fn control_blocks(buffer: &[u8], steps: &[Step]) -> Result<Vec<ControlBlock>, &'static str> {
+    let base = buffer.as_ptr() as usize;
+    let burst = (BURST_LENGTH - 1) << TI_BURST_LENGTH_SHIFT;
+    let mut blocks = Vec::new();
+
+    for step in steps {
+        let (ti, source, dest, len, source_inc, dest_inc) = match *step {
+            Step::Copy { from, to, len } => (
+                TI_SRC_INC | TI_DEST_INC | TI_SRC_WIDTH_128 | TI_DEST_WIDTH_128 | burst,
+                bus_address(base + from)?,
+                bus_address(base + to)?,
+                len,
+                true,
+                true,
+            ),
+            Step::Send { from, len, to } => {
+                let (fifo, dreq, _) = fifo(to);
+                (
+                    TI_SRC_INC | TI_DEST_DREQ | dreq << TI_PERMAP_SHIFT,
+                    bus_address(base + from)?,
+                    fifo,
+                    len,
+                    true,
+                    false,
+                )
+            }
+            Step::Receive { from, to, len } => {
+                let (fifo, _, dreq) = fifo(from);
+                (
+                    TI_DEST_INC | TI_SRC_DREQ | dreq << TI_PERMAP_SHIFT,
+                    fifo,
+                    bus_address(base + to)?,
+                    len,
+                    false,
+                    true,
+                )
+            }
+        };
+
+        for offset in (0..len).step_by(MAX_TRANSFER) {
+            blocks.push(ControlBlock {
+                ti: ti | TI_WAIT_RESP,
+                source_ad: source + if source_inc { offset as u32 } else { 0 },
+                dest_ad: dest + if dest_inc { offset as u32 } else { 0 },
+                txfr_len: (len - offset).min(MAX_TRANSFER) as u32,
+                ..ControlBlock::default()
+            });
+        }
+    }
+
+    Ok(blocks)
+}
+
+impl DmaInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+            control_block: ControlBlock {
+                ti: 0,
+                source_ad: 0,
+                dest_ad: 0,
+                txfr_len: 0,
+                stride: 0,
+                nextconbk: 0,
+                _reserved: [0; 2],
+            },
+            source: FillSource([0; 4]),
+            active: None,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.registers.CS.write(CS::RESET::SET);
+        self.registers.DEBUG.set(self.registers.DEBUG.get());
+    }
+
+    /// Run a single control block and wait for it to end.
+    fn run(&mut self) -> Result<(), &'static str> {
+        let cb = &self.control_block as *const ControlBlock as usize;
+        cpu::clean_invalidate_dcache(cb, core::mem::size_of::<ControlBlock>());
+
+        self.registers.CONBLK_AD.set(bus_address(cb)?);
+        self.registers
+            .CS
+            .write(CS::END::SET + CS::WAIT_FOR_OUTSTANDING_WRITES::SET + CS::ACTIVE::SET);
+
+        let done = spin_until!(
+            !self.registers.CS.is_set(CS::ACTIVE),
+            TIMEOUT,
+            "DMA timeout",
+            self.registers.CS.get()
+        );
+        if let Err(x) = done {
+            self.reset();
+            return Err(x.context);
+        }
+
+        if self.registers.CS.is_set(CS::ERROR) {
+            self.reset();
+            return Err("DMA error");
+        }
+        self.registers.CS.modify(CS::END::SET);
+
+        Ok(())
+    }
+
+    fn fill(&mut self, dst: &mut [u8], value: u8) -> Result<(), &'static str> {
+        if self.active.is_some() {
+            return Err("DMA channel busy");
+        }
+
+        let word = u32::from_ne_bytes([value; 4]);
+        self.source.0 = [word; 4];
+        let source = &self.source as *const FillSource as usize;
+        cpu::clean_invalidate_dcache(source, core::mem::size_of::<FillSource>());
+
+        // Dirty lines must not be evicted over the engine's writes.
+        cpu::clean_invalidate_dcache(dst.as_ptr() as usize, dst.len());
+
+        let mut result = Ok(());
+        for chunk in dst.chunks_mut(MAX_TRANSFER) {
+            self.control_block.ti = TI_WAIT_RESP
+                | TI_DEST_INC
+                | TI_DEST_WIDTH_128
+                | TI_SRC_WIDTH_128
+                | (BURST_LENGTH - 1) << TI_BURST_LENGTH_SHIFT;
+            self.control_block.source_ad = bus_address(source)?;
+            self.control_block.dest_ad = bus_address(chunk.as_ptr() as usize)?;
+            self.control_block.txfr_len = chunk.len() as u32;
+
+            result = self.run();
+            if result.is_err() {
+                break;
+            }
+        }
+
+        // Lines may have been fetched speculatively during the transfer.
+        cpu::clean_invalidate_dcache(dst.as_ptr() as usize, dst.len());
+
+        result
+    }
+
+    fn start(
+        &mut self,
+        transfer: dma::Transfer,
+        done: dma::Completion,
+    ) -> Result<(), &'static str> {
+        if self.active.is_some() {
+            return Err("DMA channel busy");
+        }
+
+        let mut blocks = control_blocks(transfer.buffer(), transfer.steps())?;
+        for i in 1..blocks.len() {
+            blocks[i - 1].nextconbk = bus_address(&blocks[i] as *const ControlBlock as usize)?;
+        }
+        if let Some(last) = blocks.last_mut() {
+            last.ti |= TI_INTEN;
+        }
+        let first = bus_address(blocks.as_ptr() as usize)?;
+
+        let buffer = transfer.into_buffer();
+        cpu::clean_invalidate_dcache(
+            blocks.as_ptr() as usize,
+            blocks.len() * core::mem::size_of::<ControlBlock>(),
+        );
+        cpu::clean_invalidate_dcache(buffer.as_ptr() as usize, buffer.len());
+
+        self.registers.CONBLK_AD.set(first);
+        self.registers.CS.write(
+            CS::INT::SET + CS::END::SET + CS::WAIT_FOR_OUTSTANDING_WRITES::SET + CS::ACTIVE::SET,
+        );
+        self.active = Some(Active {
+            blocks,
+            buffer,
+            done,
+        });
+
+        Ok(())
+    }
+
+    /// Take the running transfer if it ended, with its result.
+    fn finish(&mut self) -> Option<(Active, Result<(), &'static str>)> {
+        let result = if self.registers.CS.is_set(CS::ERROR) {
+            self.reset();
+            Err("DMA error")
+        } else if self.registers.CS.is_set(CS::INT) {
+            self.registers.CS.modify(CS::INT::SET + CS::END::SET);
+            Ok(())
+        } else {
+            return None;
+        };
+
+        let active = self.active.take()?;
+        // Lines may have been fetched speculatively during the transfer.
+        cpu::clean_invalidate_dcache(active.buffer.as_ptr() as usize, active.buffer.len());
+
+        Some((active, result))
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Dma {
+    pub const COMPATIBLE: &'static str = "BCM DMA";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(DmaInner::new(mmio_start_addr)),
+            stop_registers: Registers::new(mmio_start_addr),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Dma {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    unsafe fn init(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.reset());
+
+        Ok(())
+    }
+
+    /// Abort a running transfer, so that the engine stops writing to memory and peripherals.
+    fn emergency_stop(&self) {
+        self.stop_registers.CS.write(CS::RESET::SET);
+    }
+
+    fn register_and_enable_irq_handler(
+        &'static self,
+        irq_number: &Self::IRQNumberType,
+    ) -> Result<(), &'static str> {
+        use exception::asynchronous::{irq_manager, IRQHandlerDescriptor};
+
+        let descriptor = IRQHandlerDescriptor::new(*irq_number, Self::COMPATIBLE, self);
+
+        irq_manager().register_handler(descriptor)?;
+        irq_manager().enable(irq_number);
+
+        Ok(())
+    }
+}
+
+impl exception::asynchronous::interface::IRQHandler for Dma {
+    fn handle(&self) -> Result<(), &'static str> {
+        // The completion may start the next transfer, so it runs outside the lock.
+        if let Some((active, result)) = self.inner.lock(|inner| inner.finish()) {
+            drop(active.blocks);
+            (active.done)(active.buffer, result);
+        }
+
+        Ok(())
+    }
+}
+
+impl dma::interface::Engine for Dma {
+    fn name(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    fn fill(&self, dst: &mut [u8], value: u8) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.fill(dst, value))
+    }
+
+    fn start(&self, transfer: dma::Transfer, done: dma::Completion) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.start(transfer, done))
+    }
+}
@@ -0,0 +1,1345 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! USB OTG (Synopsys DWC2) controller driver, in device and host mode.
+//!
+//! The core is left alone until a gadget is started. It is then reset, forced into device mode
+//! and runs in slave mode: all packets go through the FIFOs by PIO, from the IRQ handler.
+//! Endpoint 0, one pair of bulk endpoints and, for the serial function, an interrupt IN endpoint
+//! are used, for a [`usb::Gadget`]. On the Raspberry Pi 3
+//! Model B, the core is wired to the on-board USB hub, so device mode needs a board that brings it
+//! out, such as the 3 A+ or a Compute Module. On the Raspberry Pi 4, it is the USB-C power
+//! connector.
+//!
+//! In host mode, the core is forced into host mode instead and drives its root port. Transfers
+//! are polled, one packet at a time on host channel 0, without IRQs. The same boards apply: on the
+//! Raspberry Pi 3 Model B, the root port is the on-board hub, which isn't supported.
+//!
+//! # Resources
+//!
+//! - DesignWare Cores USB 2.0 Hi-Speed On-The-Go (OTG) Programming Guide
+//! - Linux `drivers/usb/dwc2`
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    driver, exception,
+    exception::asynchronous::IRQNumber,
+    memory::{Address, Virtual},
+    synchronization,
+    synchronization::IRQSafeNullLock,
+    time,
+    usb::{
+        self,
+        host::{Device, InterruptEndpoint, Speed},
+        Bulk, Control, Gadget,
+    },
+};
+use alloc::vec::Vec;
+use core::time::Duration;
+use tock_registers::{
+    fields::FieldValue,
+    interfaces::{ReadWriteable, Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::{ReadOnly, ReadWrite},
+    LocalRegisterCopy,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// How long to wait for the core before giving up.
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long to wait for a device on the root port in host mode.
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long a device may NAK a control transaction in host mode.
+const TRANSFER_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Upper half of GSNPSID of all DWC2 cores, "OT".
+const SNPSID_OTG: u32 = 0x4F54;
+
+// FIFO layout, in words: the shared RX FIFO, then the TX FIFOs of endpoints 0, 1 and 2.
+const RX_FIFO_WORDS: u32 = 256;
+const EP0_TX_FIFO_WORDS: u32 = 64;
+const EP1_TX_FIFO_WORDS: u32 = 512;
+const EP2_TX_FIFO_WORDS: u32 = 16;
+
+/// Host mode TX FIFOs after the RX FIFO, the non-periodic one and then the periodic one.
+const HOST_TX_FIFO_WORDS: u32 = 64;
+
+/// Largest bulk IN transfer, so that it always fits the empty TX FIFO.
+const MAX_BULK_IN: usize = EP1_TX_FIFO_WORDS as usize * 4;
+
+/// All TX FIFOs, for GRSTCTL.TXFNUM.
+const ALL_TX_FIFOS: u32 = 0x10;
+
+const EP_TYPE_CONTROL: u32 = 0;
+const EP_TYPE_BULK: u32 = 2;
+const EP_TYPE_INTERRUPT: u32 = 3;
+
+// RX FIFO packet status.
+const PKTSTS_OUT_DATA: u32 = 2;
+const PKTSTS_SETUP_DATA: u32 = 6;
+
+/// RX FIFO packet status in host mode.
+const PKTSTS_IN_DATA: u32 = 2;
+
+// Host channel data PIDs.
+const PID_DATA0: u32 = 0;
+const PID_DATA1: u32 = 2;
+const PID_SETUP: u32 = 3;
+
+// USB OTG registers.
+//
+// Names follow the DWC2 databook.
+register_bitfields! {
+    u32,
+
+    /// AHB Configuration.
+    GAHBCFG [
+        GLBLINTRMSK OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// USB Configuration.
+    GUSBCFG [
+        FORCEDEVMODE OFFSET(30) NUMBITS(1) [],
+        FORCEHSTMODE OFFSET(29) NUMBITS(1) [],
+        USBTRDTIM OFFSET(10) NUMBITS(4) []
+    ],
+
+    /// Reset.
+    GRSTCTL [
+        AHBIDLE OFFSET(31) NUMBITS(1) [],
+        TXFNUM OFFSET(6) NUMBITS(5) [],
+        TXFFLSH OFFSET(5) NUMBITS(1) [],
+        RXFFLSH OFFSET(4) NUMBITS(1) [],
+        CSFTRST OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Interrupt status and mask.
+    GINT [
+        OEPINT OFFSET(19) NUMBITS(1) [],
+        IEPINT OFFSET(18) NUMBITS(1) [],
+        ENUMDONE OFFSET(13) NUMBITS(1) [],
+        USBRST OFFSET(12) NUMBITS(1) [],
+        USBSUSP OFFSET(11) NUMBITS(1) [],
+        RXFLVL OFFSET(4) NUMBITS(1) []
+    ],
+
+    /// Receive status, read and pop.
+    GRXSTSP [
+        PKTSTS OFFSET(17) NUMBITS(4) [],
+        BCNT OFFSET(4) NUMBITS(11) [],
+        EPNUM OFFSET(0) NUMBITS(4) []
+    ],
+
+    /// Start and depth of a TX FIFO.
+    TXFSIZ [
+        DEPTH OFFSET(16) NUMBITS(16) [],
+        START OFFSET(0) NUMBITS(16) []
+    ],
+
+    /// Host Configuration.
+    HCFG [
+        FSLSPCLKSEL OFFSET(0) NUMBITS(2) []
+    ],
+
+    /// Host Frame Number.
+    HFNUM [
+        FRNUM OFFSET(0) NUMBITS(16) []
+    ],
+
+    /// Host Port Control and Status. Writing 1 clears the change bits and disables the port.
+    HPRT [
+        PRTSPD OFFSET(17) NUMBITS(2) [
+            High = 0,
+            Full = 1,
+            Low = 2
+        ],
+        PRTPWR OFFSET(12) NUMBITS(1) [],
+        PRTRST OFFSET(8) NUMBITS(1) [],
+        PRTOVRCURRCHNG OFFSET(5) NUMBITS(1) [],
+        PRTENCHNG OFFSET(3) NUMBITS(1) [],
+        PRTENA OFFSET(2) NUMBITS(1) [],
+        PRTCONNDET OFFSET(1) NUMBITS(1) [],
+        PRTCONNSTS OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Host Channel Characteristics.
+    HCCHAR [
+        CHENA OFFSET(31) NUMBITS(1) [],
+        CHDIS OFFSET(30) NUMBITS(1) [],
+        ODDFRM OFFSET(29) NUMBITS(1) [],
+        DEVADDR OFFSET(22) NUMBITS(7) [],
+        MC OFFSET(20) NUMBITS(2) [],
+        EPTYPE OFFSET(18) NUMBITS(2) [],
+        LSPDDEV OFFSET(17) NUMBITS(1) [],
+        EPDIR OFFSET(15) NUMBITS(1) [],
+        EPNUM OFFSET(11) NUMBITS(4) [],
+        MPS OFFSET(0) NUMBITS(11) []
+    ],
+
+    /// Host Channel Interrupt.
+    HCINT [
+        DATATGLERR OFFSET(10) NUMBITS(1) [],
+        FRMOVRUN OFFSET(9) NUMBITS(1) [],
+        BBLERR OFFSET(8) NUMBITS(1) [],
+        XACTERR OFFSET(7) NUMBITS(1) [],
+        NAK OFFSET(4) NUMBITS(1) [],
+        STALL OFFSET(3) NUMBITS(1) [],
+        AHBERR OFFSET(2) NUMBITS(1) [],
+        CHHLTD OFFSET(1) NUMBITS(1) [],
+        XFERCOMPL OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Host Channel Transfer Size.
+    HCTSIZ [
+        PID OFFSET(29) NUMBITS(2) [],
+        PKTCNT OFFSET(19) NUMBITS(10) [],
+        XFERSIZE OFFSET(0) NUMBITS(19) []
+    ],
+
+    /// Device Configuration.
+    DCFG [
+        DEVADDR OFFSET(4) NUMBITS(7) [],
+        DEVSPD OFFSET(0) NUMBITS(2) [
+            HighSpeed = 0
+        ]
+    ],
+
+    /// Device Control.
+    DCTL [
+        SFTDISCON OFFSET(1) NUMBITS(1) []
+    ],
+
+    /// Device Status.
+    DSTS [
+        ENUMSPD OFFSET(1) NUMBITS(2) [
+            HighSpeed = 0
+        ]
+    ],
+
+    /// All endpoints' interrupts, IN in the lower and OUT in the upper half.
+    DAINT [
+        OUT OFFSET(16) NUMBITS(16) [],
+        IN OFFSET(0) NUMBITS(16) []
+    ],
+
+    /// Endpoint Control. The packet size of endpoint 0 is encoded, 0 is 64 bytes.
+    EPCTL [
+        EPENA OFFSET(31) NUMBITS(1) [],
+        EPDIS OFFSET(30) NUMBITS(1) [],
+        SD0PID OFFSET(28) NUMBITS(1) [],
+        SNAK OFFSET(27) NUMBITS(1) [],
+        CNAK OFFSET(26) NUMBITS(1) [],
+        TXFNUM OFFSET(22) NUMBITS(4) [],
+        STALL OFFSET(21) NUMBITS(1) [],
+        EPTYPE OFFSET(18) NUMBITS(2) [],
+        USBACTEP OFFSET(15) NUMBITS(1) [],
+        MPS OFFSET(0) NUMBITS(11) []
+    ],
+
+    /// Endpoint Interrupt.
+    EPINT [
+        SETUP OFFSET(3) NUMBITS(1) [],
+        EPDISBLD OFFSET(1) NUMBITS(1) [],
+        XFERCOMPL OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Endpoint Transfer Size.
+    EPTSIZ [
+        SUPCNT OFFSET(29) NUMBITS(2) [],
+        PKTCNT OFFSET(19) NUMBITS(10) [],
+        XFERSIZE OFFSET(0) NUMBITS(19) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    EndpointBlock {
+        (0x00 => CTL: ReadWrite<u32, EPCTL::Register>),
+        (0x04 => _reserved1),
+        (0x08 => INT: ReadWrite<u32, EPINT::Register>),
+        (0x0C => _reserved2),
+        (0x10 => TSIZ: ReadWrite<u32, EPTSIZ::Register>),
+        (0x14 => _reserved3),
+        (0x20 => @END),
+    },
+
+    #[allow(non_snake_case)]
+    ChannelBlock {
+        (0x00 => CHAR: ReadWrite<u32, HCCHAR::Register>),
+        (0x04 => _reserved1),
+        (0x08 => INT: ReadWrite<u32, HCINT::Register>),
+        (0x0C => _reserved2),
+        (0x10 => TSIZ: ReadWrite<u32, HCTSIZ::Register>),
+        (0x14 => _reserved3),
+        (0x20 => @END),
+    },
+
+    #[allow(non_snake_case)]
+    RegisterBlock {
+        (0x000 => _reserved1),
+        (0x008 => GAHBCFG: ReadWrite<u32, GAHBCFG::Register>),
+        (0x00C => GUSBCFG: ReadWrite<u32, GUSBCFG::Register>),
+        (0x010 => GRSTCTL: ReadWrite<u32, GRSTCTL::Register>),
+        (0x014 => GINTSTS: ReadWrite<u32, GINT::Register>),
+        (0x018 => GINTMSK: ReadWrite<u32, GINT::Register>),
+        (0x01C => _reserved2),
+        (0x020 => GRXSTSP: ReadOnly<u32, GRXSTSP::Register>),
+        (0x024 => GRXFSIZ: ReadWrite<u32>),
+        (0x028 => GNPTXFSIZ: ReadWrite<u32, TXFSIZ::Register>),
+        (0x02C => _reserved3),
+        (0x040 => GSNPSID: ReadOnly<u32>),
+        (0x044 => _reserved4),
+        (0x100 => HPTXFSIZ: ReadWrite<u32, TXFSIZ::Register>),
+        (0x104 => DIEPTXF1: ReadWrite<u32, TXFSIZ::Register>),
+        (0x108 => DIEPTXF2: ReadWrite<u32, TXFSIZ::Register>),
+        (0x10C => _reserved5),
+        (0x400 => HCFG: ReadWrite<u32, HCFG::Register>),
+        (0x404 => _reserved6),
+        (0x408 => HFNUM: ReadOnly<u32, HFNUM::Register>),
+        (0x40C => _reserved7),
+        (0x440 => HPRT: ReadWrite<u32, HPRT::Register>),
+        (0x444 => _reserved8),
+        (0x500 => HC0: ChannelBlock),
+        (0x520 => _reserved9),
+        (0x800 => DCFG: ReadWrite<u32, DCFG::Register>),
+        (0x804 => DCTL: ReadWrite<u32, DCTL::Register>),
+        (0x808 => DSTS: ReadOnly<u32, DSTS::Register>),
+        (0x80C => _reserved10),
+        (0x810 => DIEPMSK: ReadWrite<u32, EPINT::Register>),
+        (0x814 => DOEPMSK: ReadWrite<u32, EPINT::Register>),
+        (0x818 => DAINT: ReadOnly<u32, DAINT::Register>),
+        (0x81C => DAINTMSK: ReadWrite<u32, DAINT::Register>),
+        (0x820 => _reserved11),
+        (0x900 => DIEP: [EndpointBlock; 3]),
+        (0x960 => _reserved12),
+        (0xB00 => DOEP: [EndpointBlock; 2]),
+        (0xB40 => _reserved13),
+        (0xE00 => PCGCCTL: ReadWrite<u32>),
+        (0xE04 => _reserved14),
+        (0x1000 => FIFO0: ReadWrite<u32>),
+        (0x1004 => _reserved15),
+        (0x2000 => FIFO1: ReadWrite<u32>),
+        (0x2004 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+/// An IN transfer in progress, sent in pieces that fit the TX FIFO.
+struct InTransfer {
+    data: Vec<u8>,
+    sent: usize,
+
+    /// End with a zero length packet.
+    zlp: bool,
+}
+
+/// Where host channel 0 sends its transactions.
+struct Channel {
+    address: u8,
+    low_speed: bool,
+    endpoint: u8,
+    ep_type: u32,
+    packet_size: usize,
+}
+
+struct DwcOtgInner {
+    registers: Registers,
+    gadget: Option<Gadget>,
+
+    /// Speed of the device on the root port, while in host mode.
+    host: Option<Speed>,
+    setup: [u8; 8],
+    ep0_in: Option<InTransfer>,
+    bulk_in: Option<InTransfer>,
+    bulk_out: Vec<u8>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the USB OTG controller.
+pub struct DwcOtg {
+    inner: IRQSafeNullLock<DwcOtgInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Spin until `condition` holds or [`TIMEOUT`] expires.
+fn wait_for(condition: impl FnMut() -> bool, error: &'static str) -> Result<(), &'static str> {
+    wait_for_within(TIMEOUT, condition, error)
+}
+
+/// Spin until `condition` holds or `timeout` expires.
+fn wait_for_within(
+    timeout: Duration,
+    mut condition: impl FnMut() -> bool,
+    error: &'static str,
+) -> Result<(), &'static str> {
+    let start = time::time_manager().uptime();
+
+    while !condition() {
+        if time::time_manager().uptime() - start > timeout {
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+impl Channel {
+    fn new(device: &Device, endpoint: u8, ep_type: u32, packet_size: usize) -> Self {
+        Self {
+            address: device.address,
+            low_speed: device.speed == Speed::Low,
+            endpoint,
+            ep_type,
+            packet_size,
+        }
+    }
+}
+
+impl DwcOtgInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+            gadget: None,
+            host: None,
+            setup: [0; 8],
+            ep0_in: None,
+            bulk_in: None,
+            bulk_out: Vec::new(),
+        }
+    }
+
+    fn flush_fifos(&mut self) -> Result<(), &'static str> {
+        self.registers
+            .GRSTCTL
+            .write(GRSTCTL::TXFFLSH::SET + GRSTCTL::TXFNUM.val(ALL_TX_FIFOS));
+        wait_for(
+            || !self.registers.GRSTCTL.is_set(GRSTCTL::TXFFLSH),
+            "USB TX FIFO flush timed out",
+        )?;
+
+        self.registers.GRSTCTL.write(GRSTCTL::RXFFLSH::SET);
+        wait_for(
+            || !self.registers.GRSTCTL.is_set(GRSTCTL::RXFFLSH),
+            "USB RX FIFO flush timed out",
+        )
+    }
+
+    /// Reset the core and force it into host or device mode, with all IRQs masked.
+    fn reset_core(&mut self, host: bool) -> Result<(), &'static str> {
+        self.registers.GAHBCFG.set(0);
+        self.registers.GINTMSK.set(0);
+        self.registers.PCGCCTL.set(0);
+
+        wait_for(
+            || self.registers.GRSTCTL.is_set(GRSTCTL::AHBIDLE),
+            "USB core not idle",
+        )?;
+        self.registers.GRSTCTL.write(GRSTCTL::CSFTRST::SET);
+        wait_for(
+            || !self.registers.GRSTCTL.is_set(GRSTCTL::CSFTRST),
+            "USB core reset timed out",
+        )?;
+
+        // The board's UTMI+ PHY has an 8 bit interface. Forcing the mode takes effect after 25 ms.
+        self.registers.GUSBCFG.modify(
+            GUSBCFG::FORCEHSTMODE.val(host as u32)
+                + GUSBCFG::FORCEDEVMODE.val(!host as u32)
+                + GUSBCFG::USBTRDTIM.val(9),
+        );
+        time::time_manager().spin_for(Duration::from_millis(25));
+
+        Ok(())
+    }
+
+    /// Reset the core into device mode, disconnected, with all IRQs masked.
+    fn reset(&mut self) -> Result<(), &'static str> {
+        self.reset_core(false)?;
+
+        self.registers.DCTL.write(DCTL::SFTDISCON::SET);
+        self.registers
+            .DCFG
+            .write(DCFG::DEVSPD::HighSpeed + DCFG::DEVADDR.val(0));
+
+        self.registers.GRXFSIZ.set(RX_FIFO_WORDS);
+        self.registers
+            .GNPTXFSIZ
+            .write(TXFSIZ::START.val(RX_FIFO_WORDS) + TXFSIZ::DEPTH.val(EP0_TX_FIFO_WORDS));
+        self.registers.DIEPTXF1.write(
+            TXFSIZ::START.val(RX_FIFO_WORDS + EP0_TX_FIFO_WORDS)
+                + TXFSIZ::DEPTH.val(EP1_TX_FIFO_WORDS),
+        );
+        self.registers.DIEPTXF2.write(
+            TXFSIZ::START.val(RX_FIFO_WORDS + EP0_TX_FIFO_WORDS + EP1_TX_FIFO_WORDS)
+                + TXFSIZ::DEPTH.val(EP2_TX_FIFO_WORDS),
+        );
+        self.flush_fifos()?;
+
+        self.registers.GINTSTS.set(u32::MAX);
+
+        Ok(())
+    }
+
+    fn start(&mut self, gadget: Gadget) -> Result<(), &'static str> {
+        if self.gadget.is_some() {
+            return Err("Already running");
+        }
+        if self.host.is_some() {
+            return Err("Running in host mode");
+        }
+        if self.registers.GSNPSID.get() >> 16 != SNPSID_OTG {
+            return Err("No DWC2 core found");
+        }
+
+        self.reset()?;
+        self.gadget = Some(gadget);
+        self.registers.GINTMSK.write(
+            GINT::USBRST::SET
+                + GINT::ENUMDONE::SET
+                + GINT::USBSUSP::SET
+                + GINT::RXFLVL::SET
+                + GINT::IEPINT::SET
+                + GINT::OEPINT::SET,
+        );
+        self.registers.GAHBCFG.write(GAHBCFG::GLBLINTRMSK::SET);
+
+        // Connect.
+        self.registers.DCTL.modify(DCTL::SFTDISCON::CLEAR);
+
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), &'static str> {
+        if self.gadget.is_none() {
+            return Err("Not running");
+        }
+
+        self.registers.DCTL.modify(DCTL::SFTDISCON::SET);
+        self.registers.GAHBCFG.set(0);
+        self.registers.GINTMSK.set(0);
+        self.gadget = None;
+        self.ep0_in = None;
+        self.bulk_in = None;
+
+        Ok(())
+    }
+
+    fn read_fifo(&mut self, len: usize, mut store: impl FnMut(&[u8])) {
+        for i in (0..len).step_by(4) {
+            let word = self.registers.FIFO0.get().to_le_bytes();
+            store(&word[..(len - i).min(4)]);
+        }
+    }
+
+    /// Write the next piece of the IN transfer of `ep`, if any is left.
+    fn continue_in(&mut self, ep: usize) {
+        let (max_len, packet_size) = match ep {
+            0 => (usb::CONTROL_PACKET_SIZE, usb::CONTROL_PACKET_SIZE),
+            _ => (
+                MAX_BULK_IN,
+                self.gadget
+                    .as_ref()
+                    .map_or(usb::BULK_PACKET_SIZE_HS, |g| g.bulk_packet_size()),
+            ),
+        };
+        let transfer = match if ep == 0 {
+            &mut self.ep0_in
+        } else {
+            &mut self.bulk_in
+        } {
+            Some(t) => t,
+            None => return,
+        };
+
+        let len = (transfer.data.len() - transfer.sent).min(max_len);
+        if len == 0 && !transfer.zlp {
+            return;
+        }
+        if len == 0 {
+            transfer.zlp = false;
+        }
+        let packets = len.div_ceil(packet_size).max(1);
+        let start = transfer.sent;
+        transfer.sent += len;
+
+        let regs = &self.registers.DIEP[ep];
+        regs.TSIZ
+            .write(EPTSIZ::PKTCNT.val(packets as u32) + EPTSIZ::XFERSIZE.val(len as u32));
+        regs.CTL.modify(EPCTL::CNAK::SET + EPCTL::EPENA::SET);
+
+        let fifo = if ep == 0 {
+            &self.registers.FIFO0
+        } else {
+            &self.registers.FIFO1
+        };
+        let data = match if ep == 0 { &self.ep0_in } else { &self.bulk_in } {
+            Some(t) => &t.data[start..start + len],
+            None => return,
+        };
+        for chunk in data.chunks(4) {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            fifo.set(u32::from_le_bytes(word));
+        }
+    }
+
+    fn send(&mut self, ep: usize, data: Vec<u8>, zlp: bool) {
+        let transfer = Some(InTransfer { data, sent: 0, zlp });
+        if ep == 0 {
+            self.ep0_in = transfer;
+        } else {
+            self.bulk_in = transfer;
+        }
+
+        self.continue_in(ep);
+    }
+
+    /// Complete a control request without data.
+    fn send_status(&mut self) {
+        self.send(0, Vec::new(), true);
+    }
+
+    /// Take SETUP packets and the status stage on endpoint 0.
+    fn arm_ep0_out(&mut self) {
+        let regs = &self.registers.DOEP[0];
+
+        regs.TSIZ.write(
+            EPTSIZ::SUPCNT.val(3)
+                + EPTSIZ::PKTCNT.val(1)
+                + EPTSIZ::XFERSIZE.val(usb::CONTROL_PACKET_SIZE as u32),
+        );
+        regs.CTL.modify(EPCTL::CNAK::SET + EPCTL::EPENA::SET);
+    }
+
+    fn stall_ep0(&mut self) {
+        self.registers.DIEP[0].CTL.modify(EPCTL::STALL::SET);
+        self.registers.DOEP[0].CTL.modify(EPCTL::STALL::SET);
+    }
+
+    fn bulk(&mut self, action: Bulk) {
+        let ep = usb::BULK_ENDPOINT as usize;
+
+        match action {
+            Bulk::Receive(len) => {
+                let packet_size = self
+                    .gadget
+                    .as_ref()
+                    .map_or(usb::BULK_PACKET_SIZE_HS, |g| g.bulk_packet_size());
+                let packets = len.div_ceil(packet_size).max(1);
+
+                self.bulk_out.clear();
+                self.bulk_out.reserve(len);
+                let regs = &self.registers.DOEP[ep];
+                regs.TSIZ.write(
+                    EPTSIZ::PKTCNT.val(packets as u32)
+                        + EPTSIZ::XFERSIZE.val((packets * packet_size) as u32),
+                );
+                regs.CTL.modify(EPCTL::CNAK::SET + EPCTL::EPENA::SET);
+            }
+            Bulk::Send(data) => self.send(ep, data, false),
+            Bulk::StallIn => self.registers.DIEP[ep].CTL.modify(EPCTL::STALL::SET),
+            Bulk::Idle => (),
+        }
+    }
+
+    fn activate_bulk(&mut self, packet_size: usize) {
+        let ep = usb::BULK_ENDPOINT as usize;
+        let ctl = EPCTL::MPS.val(packet_size as u32)
+            + EPCTL::EPTYPE.val(EP_TYPE_BULK)
+            + EPCTL::USBACTEP::SET
+            + EPCTL::SD0PID::SET
+            + EPCTL::SNAK::SET;
+
+        self.registers.DIEP[ep]
+            .CTL
+            .write(ctl + EPCTL::TXFNUM.val(ep as u32));
+        self.registers.DOEP[ep].CTL.write(ctl);
+        self.registers
+            .DAINTMSK
+            .modify(DAINT::IN.val(0b11) + DAINT::OUT.val(0b11));
+    }
+
+    /// Enable the interrupt IN endpoint. Nothing is ever sent on it, the host just polls.
+    fn activate_notify(&mut self) {
+        let ep = usb::NOTIFY_ENDPOINT as usize;
+
+        self.registers.DIEP[ep].CTL.write(
+            EPCTL::MPS.val(usb::NOTIFY_PACKET_SIZE as u32)
+                + EPCTL::EPTYPE.val(EP_TYPE_INTERRUPT)
+                + EPCTL::USBACTEP::SET
+                + EPCTL::SD0PID::SET
+                + EPCTL::SNAK::SET
+                + EPCTL::TXFNUM.val(ep as u32),
+        );
+    }
+
+    /// Stop the bulk IN endpoint's transfer and drop what is left in its FIFO.
+    fn cancel_bulk_in(&mut self) {
+        let ep = usb::BULK_ENDPOINT as usize;
+        let regs = &self.registers.DIEP[ep];
+
+        self.bulk_in = None;
+        if regs.CTL.is_set(EPCTL::EPENA) {
+            regs.CTL.modify(EPCTL::SNAK::SET + EPCTL::EPDIS::SET);
+            let _ = wait_for(
+                || regs.INT.is_set(EPINT::EPDISBLD),
+                "USB endpoint disable timed out",
+            );
+            regs.INT.write(EPINT::EPDISBLD::SET);
+        }
+
+        self.registers
+            .GRSTCTL
+            .write(GRSTCTL::TXFFLSH::SET + GRSTCTL::TXFNUM.val(ep as u32));
+        let _ = wait_for(
+            || !self.registers.GRSTCTL.is_set(GRSTCTL::TXFFLSH),
+            "USB TX FIFO flush timed out",
+        );
+    }
+
+    fn handle_setup(&mut self) {
+        let setup = usb::Setup::parse(&self.setup);
+        let control = match self.gadget.as_mut() {
+            Some(gadget) => gadget.setup(&setup),
+            None => return,
+        };
+
+        match control {
+            Control::Data(data) => {
+                // A short packet ends the data stage. If the last one is full, an empty one does.
+                let zlp = data.len() < setup.length as usize
+                    && data.len() % usb::CONTROL_PACKET_SIZE == 0;
+                self.send(0, data, zlp);
+            }
+            Control::Ack => self.send_status(),
+            Control::Stall => self.stall_ep0(),
+            Control::SetAddress(address) => {
+                // The core answers with the new address only after the status stage.
+                self.registers
+                    .DCFG
+                    .modify(DCFG::DEVADDR.val(address as u32));
+                self.send_status();
+            }
+            Control::Configure(then) => {
+                let (packet_size, notify) = self
+                    .gadget
+                    .as_ref()
+                    .map_or((usb::BULK_PACKET_SIZE_HS, false), |g| {
+                        (g.bulk_packet_size(), g.has_notify_endpoint())
+                    });
+                self.activate_bulk(packet_size);
+                if notify {
+                    self.activate_notify();
+                }
+                self.send_status();
+                self.bulk(then);
+            }
+            Control::Deconfigure => {
+                self.cancel_bulk_in();
+                let ep = usb::BULK_ENDPOINT as usize;
+                self.registers.DIEP[ep].CTL.modify(EPCTL::USBACTEP::CLEAR);
+                self.registers.DOEP[ep].CTL.modify(EPCTL::USBACTEP::CLEAR);
+                self.registers.DIEP[usb::NOTIFY_ENDPOINT as usize]
+                    .CTL
+                    .modify(EPCTL::USBACTEP::CLEAR);
+                self.send_status();
+            }
+            Control::ClearHalt { endpoint, then } => {
+                let ep = (endpoint & 0x0F) as usize;
+                if ep == usb::BULK_ENDPOINT as usize {
+                    let regs = if endpoint & 0x80 != 0 {
+                        &self.registers.DIEP[ep]
+                    } else {
+                        &self.registers.DOEP[ep]
+                    };
+                    regs.CTL.modify(EPCTL::STALL::CLEAR + EPCTL::SD0PID::SET);
+                }
+                self.send_status();
+                self.bulk(then);
+            }
+            Control::Reset(then) => {
+                self.cancel_bulk_in();
+                self.send_status();
+                self.bulk(then);
+            }
+        }
+    }
+
+    fn bus_reset(&mut self) {
+        self.ep0_in = None;
+        self.bulk_in = None;
+        self.registers.DCFG.modify(DCFG::DEVADDR.val(0));
+
+        let ep = usb::BULK_ENDPOINT as usize;
+        self.registers.DIEP[ep].CTL.set(0);
+        self.registers.DOEP[ep].CTL.set(0);
+        self.registers.DIEP[usb::NOTIFY_ENDPOINT as usize]
+            .CTL
+            .set(0);
+
+        self.registers
+            .DAINTMSK
+            .write(DAINT::IN.val(0b1) + DAINT::OUT.val(0b1));
+        self.registers
+            .DOEPMSK
+            .write(EPINT::SETUP::SET + EPINT::XFERCOMPL::SET);
+        self.registers.DIEPMSK.write(EPINT::XFERCOMPL::SET);
+        let _ = self.flush_fifos();
+    }
+
+    fn enumeration_done(&mut self) {
+        let high_speed = self.registers.DSTS.matches_all(DSTS::ENUMSPD::HighSpeed);
+        if let Some(gadget) = self.gadget.as_mut() {
+            gadget.bus_reset(high_speed);
+        }
+
+        // A packet size of 64 bytes.
+        self.registers.DIEP[0].CTL.modify(EPCTL::MPS.val(0));
+        self.arm_ep0_out();
+    }
+
+    /// Drain the RX FIFO.
+    fn receive(&mut self) {
+        while self.registers.GINTSTS.is_set(GINT::RXFLVL) {
+            let status = self.registers.GRXSTSP.extract();
+            let len = status.read(GRXSTSP::BCNT) as usize;
+            let ep = status.read(GRXSTSP::EPNUM) as usize;
+
+            match status.read(GRXSTSP::PKTSTS) {
+                PKTSTS_SETUP_DATA => {
+                    let mut setup = [0; 8];
+                    let mut pos = 0;
+                    self.read_fifo(len, |bytes| {
+                        let end = (pos + bytes.len()).min(setup.len());
+                        setup[pos..end].copy_from_slice(&bytes[..end - pos]);
+                        pos = end;
+                    });
+                    self.setup = setup;
+                }
+                PKTSTS_OUT_DATA if ep == usb::BULK_ENDPOINT as usize => {
+                    let mut out = core::mem::take(&mut self.bulk_out);
+                    self.read_fifo(len, |bytes| out.extend_from_slice(bytes));
+                    self.bulk_out = out;
+                }
+                // The data stage of a control OUT request. The status stage has none.
+                PKTSTS_OUT_DATA if ep == 0 && len > 0 => {
+                    let mut data = Vec::with_capacity(len);
+                    self.read_fifo(len, |bytes| data.extend_from_slice(bytes));
+                    if let Some(gadget) = self.gadget.as_mut() {
+                        gadget.control_data(&data);
+                    }
+                }
+                PKTSTS_OUT_DATA => self.read_fifo(len, |_| ()),
+                _ => (),
+            }
+        }
+    }
+
+    fn in_endpoints(&mut self) {
+        let pending = self.registers.DAINT.read(DAINT::IN);
+
+        for ep in (0..2).filter(|ep| pending & (1 << ep) != 0) {
+            let int = self.registers.DIEP[ep].INT.extract();
+            self.registers.DIEP[ep].INT.set(int.get());
+            if !int.is_set(EPINT::XFERCOMPL) {
+                continue;
+            }
+
+            let transfer = if ep == 0 { &self.ep0_in } else { &self.bulk_in };
+            let done = match transfer {
+                Some(t) => t.sent == t.data.len() && !t.zlp,
+                None => continue,
+            };
+            if !done {
+                self.continue_in(ep);
+                continue;
+            }
+
+            if ep == 0 {
+                self.ep0_in = None;
+            } else {
+                self.bulk_in = None;
+                if let Some(action) = self.gadget.as_mut().map(|g| g.sent()) {
+                    self.bulk(action);
+                }
+            }
+        }
+    }
+
+    fn out_endpoints(&mut self) {
+        let pending = self.registers.DAINT.read(DAINT::OUT);
+
+        for ep in (0..2).filter(|ep| pending & (1 << ep) != 0) {
+            let int = self.registers.DOEP[ep].INT.extract();
+            self.registers.DOEP[ep].INT.set(int.get());
+
+            if ep == 0 {
+                if int.is_set(EPINT::SETUP) {
+                    self.handle_setup();
+                }
+                if int.is_set(EPINT::SETUP) || int.is_set(EPINT::XFERCOMPL) {
+                    self.arm_ep0_out();
+                }
+            } else if int.is_set(EPINT::XFERCOMPL) {
+                let data = core::mem::take(&mut self.bulk_out);
+                if let Some(action) = self.gadget.as_mut().map(|g| g.received(&data)) {
+                    self.bulk(action);
+                }
+            }
+        }
+    }
+
+    fn handle_irq(&mut self) {
+        let pending = self.registers.GINTSTS.get() & self.registers.GINTMSK.get();
+        let pending = LocalRegisterCopy::<u32, GINT::Register>::new(pending);
+
+        // The status bits of the FIFO and endpoints clear themselves, these are written to clear.
+        self.registers.GINTSTS.write(
+            GINT::USBRST.val(pending.read(GINT::USBRST))
+                + GINT::ENUMDONE.val(pending.read(GINT::ENUMDONE))
+                + GINT::USBSUSP.val(pending.read(GINT::USBSUSP)),
+        );
+
+        if pending.is_set(GINT::USBRST) {
+            self.bus_reset();
+        }
+        if pending.is_set(GINT::ENUMDONE) {
+            self.enumeration_done();
+        }
+        if pending.is_set(GINT::RXFLVL) {
+            self.receive();
+        }
+        if pending.is_set(GINT::OEPINT) {
+            self.out_endpoints();
+        }
+        if pending.is_set(GINT::IEPINT) {
+            self.in_endpoints();
+        }
+    }
+
+    fn kick(&mut self) {
+        if self.bulk_in.is_some() {
+            return;
+        }
+
+        if let Some(action) = self.gadget.as_mut().map(|g| g.poll()) {
+            self.bulk(action);
+        }
+    }
+
+    /// Change HPRT without clearing its change bits or disabling the port.
+    fn modify_port(&self, field: FieldValue<u32, HPRT::Register>) {
+        let mut port = self.registers.HPRT.extract();
+
+        port.modify(
+            HPRT::PRTENA::CLEAR
+                + HPRT::PRTCONNDET::CLEAR
+                + HPRT::PRTENCHNG::CLEAR
+                + HPRT::PRTOVRCURRCHNG::CLEAR
+                + field,
+        );
+        self.registers.HPRT.set(port.get());
+    }
+
+    /// Reset the core into host mode, power the root port and reset the device on it.
+    fn start_host(&mut self) -> Result<Speed, &'static str> {
+        if self.host.is_some() {
+            return Err("Already running");
+        }
+        if self.gadget.is_some() {
+            return Err("Running as a gadget");
+        }
+        if self.registers.GSNPSID.get() >> 16 != SNPSID_OTG {
+            return Err("No DWC2 core found");
+        }
+
+        self.reset_core(true)?;
+
+        // The UTMI+ PHY clocks low and full speed devices at 30/60 MHz as well.
+        self.registers.HCFG.write(HCFG::FSLSPCLKSEL.val(0));
+        self.registers.GRXFSIZ.set(RX_FIFO_WORDS);
+        self.registers
+            .GNPTXFSIZ
+            .write(TXFSIZ::START.val(RX_FIFO_WORDS) + TXFSIZ::DEPTH.val(HOST_TX_FIFO_WORDS));
+        self.registers.HPTXFSIZ.write(
+            TXFSIZ::START.val(RX_FIFO_WORDS + HOST_TX_FIFO_WORDS)
+                + TXFSIZ::DEPTH.val(HOST_TX_FIFO_WORDS),
+        );
+        self.flush_fifos()?;
+        self.registers.GINTSTS.set(u32::MAX);
+
+        self.modify_port(HPRT::PRTPWR::SET);
+        if let Err(x) = wait_for_within(
+            CONNECT_TIMEOUT,
+            || self.registers.HPRT.is_set(HPRT::PRTCONNSTS),
+            "No USB device connected",
+        ) {
+            self.modify_port(HPRT::PRTPWR::CLEAR);
+            return Err(x);
+        }
+
+        // Let the connection settle, then reset the device for 50 ms and give it 10 ms to recover.
+        time::time_manager().spin_for(Duration::from_millis(100));
+        self.modify_port(HPRT::PRTRST::SET);
+        time::time_manager().spin_for(Duration::from_millis(50));
+        self.modify_port(HPRT::PRTRST::CLEAR);
+        time::time_manager().spin_for(Duration::from_millis(10));
+
+        if !self.registers.HPRT.is_set(HPRT::PRTENA) {
+            self.modify_port(HPRT::PRTPWR::CLEAR);
+            return Err("USB port not enabled after reset");
+        }
+
+        let speed = match self.registers.HPRT.read(HPRT::PRTSPD) {
+            0 => Speed::High,
+            1 => Speed::Full,
+            _ => Speed::Low,
+        };
+        self.host = Some(speed);
+
+        Ok(speed)
+    }
+
+    fn stop_host(&mut self) -> Result<(), &'static str> {
+        if self.host.take().is_none() {
+            return Err("Not running");
+        }
+
+        self.modify_port(HPRT::PRTPWR::CLEAR);
+
+        Ok(())
+    }
+
+    /// Drain the RX FIFO in host mode, keeping the IN data.
+    fn receive_host(&self, data: &mut Vec<u8>) {
+        while self.registers.GINTSTS.is_set(GINT::RXFLVL) {
+            let status = self.registers.GRXSTSP.extract();
+            let len = status.read(GRXSTSP::BCNT) as usize;
+            if status.read(GRXSTSP::PKTSTS) != PKTSTS_IN_DATA {
+                continue;
+            }
+
+            for i in (0..len).step_by(4) {
+                let word = self.registers.FIFO0.get().to_le_bytes();
+                data.extend_from_slice(&word[..(len - i).min(4)]);
+            }
+        }
+    }
+
+    /// Halt host channel 0. In slave mode, it doesn't halt by itself after a NAK.
+    fn halt_channel(&self, data: &mut Vec<u8>) {
+        self.registers
+            .HC0
+            .CHAR
+            .modify(HCCHAR::CHDIS::SET + HCCHAR::CHENA::SET);
+        let _ = wait_for(
+            || {
+                self.receive_host(data);
+                self.registers.HC0.INT.is_set(HCINT::CHHLTD)
+            },
+            "USB channel halt timed out",
+        );
+    }
+
+    /// Run a single transaction on host channel 0: send the packet `out`, or receive a packet.
+    /// Returns `None` if the device NAKed.
+    fn transaction(
+        &self,
+        ch: &Channel,
+        dir_in: bool,
+        pid: u32,
+        out: &[u8],
+    ) -> Result<Option<Vec<u8>>, &'static str> {
+        if self.host.is_none() {
+            return Err("Not running");
+        }
+
+        let regs = &self.registers.HC0;
+        let len = if dir_in { ch.packet_size } else { out.len() };
+
+        regs.INT.set(u32::MAX);
+        regs.TSIZ
+            .write(HCTSIZ::PID.val(pid) + HCTSIZ::PKTCNT.val(1) + HCTSIZ::XFERSIZE.val(len as u32));
+        // Periodic transactions go out in the next frame.
+        let odd_frame = self.registers.HFNUM.read(HFNUM::FRNUM) & 1 == 0;
+        regs.CHAR.write(
+            HCCHAR::MPS.val(ch.packet_size as u32)
+                + HCCHAR::EPNUM.val(ch.endpoint as u32)
+                + HCCHAR::EPDIR.val(dir_in as u32)
+                + HCCHAR::LSPDDEV.val(ch.low_speed as u32)
+                + HCCHAR::EPTYPE.val(ch.ep_type)
+                + HCCHAR::MC.val(1)
+                + HCCHAR::DEVADDR.val(ch.address as u32)
+                + HCCHAR::ODDFRM.val(odd_frame as u32)
+                + HCCHAR::CHENA::SET,
+        );
+        for chunk in out.chunks(4) {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.registers.FIFO0.set(u32::from_le_bytes(word));
+        }
+
+        let mut data = Vec::new();
+        let done = HCINT::XFERCOMPL::SET
+            + HCINT::CHHLTD::SET
+            + HCINT::STALL::SET
+            + HCINT::NAK::SET
+            + HCINT::XACTERR::SET
+            + HCINT::BBLERR::SET
+            + HCINT::FRMOVRUN::SET
+            + HCINT::DATATGLERR::SET
+            + HCINT::AHBERR::SET;
+        let mut int = regs.INT.extract();
+        let finished = wait_for(
+            || {
+                self.receive_host(&mut data);
+                int = regs.INT.extract();
+                int.matches_any(done)
+            },
+            "USB transaction timed out",
+        );
+        if finished.is_err() || !int.is_set(HCINT::CHHLTD) {
+            self.halt_channel(&mut data);
+        }
+        finished?;
+
+        if int.is_set(HCINT::XFERCOMPL) {
+            Ok(Some(data))
+        } else if int.is_set(HCINT::NAK) {
+            Ok(None)
+        } else if int.is_set(HCINT::STALL) {
+            Err("USB endpoint stalled")
+        } else {
+            Err("USB transaction error")
+        }
+    }
+
+    /// Repeat a transaction until the device stops NAKing it.
+    fn transaction_retry(
+        &self,
+        ch: &Channel,
+        dir_in: bool,
+        pid: u32,
+        out: &[u8],
+    ) -> Result<Vec<u8>, &'static str> {
+        let start = time::time_manager().uptime();
+
+        loop {
+            if let Some(data) = self.transaction(ch, dir_in, pid, out)? {
+                return Ok(data);
+            }
+            if time::time_manager().uptime() - start > TRANSFER_TIMEOUT {
+                return Err("USB device not responding");
+            }
+        }
+    }
+
+    fn control(
+        &self,
+        device: &Device,
+        setup: &usb::Setup,
+        data: &[u8],
+    ) -> Result<Vec<u8>, &'static str> {
+        let ch = Channel::new(device, 0, EP_TYPE_CONTROL, device.control_packet_size);
+        let dir_in = setup.request_type & 0x80 != 0;
+        let toggle = |pid| {
+            if pid == PID_DATA0 {
+                PID_DATA1
+            } else {
+                PID_DATA0
+            }
+        };
+
+        self.transaction_retry(&ch, false, PID_SETUP, &setup.to_bytes())?;
+
+        // The data stage starts with DATA1 and ends with a short packet, or when all arrived.
+        let mut received = Vec::new();
+        let mut pid = PID_DATA1;
+        if dir_in {
+            while received.len() < setup.length as usize {
+                let packet = self.transaction_retry(&ch, true, pid, &[])?;
+                received.extend_from_slice(&packet);
+                pid = toggle(pid);
+                if packet.len() < ch.packet_size {
+                    break;
+                }
+            }
+            received.truncate(setup.length as usize);
+        } else {
+            for packet in data.chunks(ch.packet_size) {
+                self.transaction_retry(&ch, false, pid, packet)?;
+                pid = toggle(pid);
+            }
+        }
+
+        // The status stage is an empty DATA1 packet in the other direction.
+        self.transaction_retry(&ch, !dir_in, PID_DATA1, &[])?;
+
+        Ok(received)
+    }
+
+    fn interrupt_in(
+        &self,
+        device: &Device,
+        endpoint: &mut InterruptEndpoint,
+    ) -> Result<Option<Vec<u8>>, &'static str> {
+        let ch = Channel::new(
+            device,
+            endpoint.number,
+            EP_TYPE_INTERRUPT,
+            endpoint.packet_size,
+        );
+        let pid = if endpoint.toggle {
+            PID_DATA1
+        } else {
+            PID_DATA0
+        };
+
+        let data = self.transaction(&ch, true, pid, &[])?;
+        if data.is_some() {
+            endpoint.toggle = !endpoint.toggle;
+        }
+
+        Ok(data)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl DwcOtg {
+    pub const COMPATIBLE: &'static str = "BCM USB OTG (DWC2)";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(DwcOtgInner::new(mmio_start_addr)),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for DwcOtg {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    fn register_and_enable_irq_handler(
+        &'static self,
+        irq_number: &Self::IRQNumberType,
+    ) -> Result<(), &'static str> {
+        use exception::asynchronous::{irq_manager, IRQHandlerDescriptor};
+
+        let descriptor = IRQHandlerDescriptor::new(*irq_number, Self::COMPATIBLE, self);
+
+        irq_manager().register_handler(descriptor)?;
+        irq_manager().enable(irq_number);
+
+        Ok(())
+    }
+}
+
+impl exception::asynchronous::interface::IRQHandler for DwcOtg {
+    fn handle(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.handle_irq());
+        usb::service();
+
+        Ok(())
+    }
+}
+
+impl usb::interface::HostController for DwcOtg {
+    fn name(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    fn start_host(&self) -> Result<Speed, &'static str> {
+        self.inner.lock(|inner| inner.start_host())
+    }
+
+    fn stop_host(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.stop_host())
+    }
+
+    fn control(
+        &self,
+        device: &Device,
+        setup: &usb::Setup,
+        data: &[u8],
+    ) -> Result<Vec<u8>, &'static str> {
+        self.inner.lock(|inner| inner.control(device, setup, data))
+    }
+
+    fn interrupt_in(
+        &self,
+        device: &Device,
+        endpoint: &mut InterruptEndpoint,
+    ) -> Result<Option<Vec<u8>>, &'static str> {
+        self.inner
+            .lock(|inner| inner.interrupt_in(device, endpoint))
+    }
+}
+
+impl usb::interface::DeviceController for DwcOtg {
+    fn name(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    fn start(&self, gadget: Gadget) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.start(gadget))
+    }
+
+    fn stop(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.stop())
+    }
+
+    fn kick(&self) {
+        self.inner.lock(|inner| inner.kick());
+    }
+
+    fn status(&self) -> usb::Status {
+        self.inner.lock(|inner| match &inner.gadget {
+            Some(gadget) => usb::Status {
+                running: true,
+                configured: gadget.is_configured(),
+                high_speed: gadget.is_high_speed(),
+                function: Some(gadget.describe()),
+            },
+            None => usb::Status {
+                running: false,
+                configured: false,
+                high_speed: false,
+                function: None,
+            },
+        })
+    }
+}
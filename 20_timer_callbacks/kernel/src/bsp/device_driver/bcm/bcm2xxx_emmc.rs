@@ -0,0 +1,780 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! EMMC (Arasan SDHCI) host controller driver.
+//!
+//! Only what is needed to talk to SDIO and SD memory cards is implemented: card identification,
+//! CMD52 and CMD53 in byte mode, and single and multiple block reads and writes. All transfers use
+//! PIO through the DATA register and poll for completion.
+//!
+//! An SD memory card is a [`block::interface::BlockDevice`]. It is identified on first access, and
+//! again after an access failed, so that a card can be swapped. On the Raspberry Pi 3 the EMMC
+//! controller talks to the Wi-Fi chip, and the SD card slot is wired to the SDHOST controller,
+//! which has no driver. The Raspberry Pi 4 has a second controller of the same kind, EMMC2, for
+//! the slot.
+//!
+//! Transfers poll for completion, so the controller is held by a [`BlockingMutex`]: a task that
+//! finds it busy with another task's SD write is parked instead of spinning, and IRQs stay unmasked
+//! meanwhile. In IRQ context, an access fails while a task holds the controller.
+//!
+//! # Resources
+//!
+//! - <https://github.com/raspberrypi/documentation/files/1888662/BCM2837-ARM-Peripherals.-.Revised.-.V2-1.pdf>
+//! - SD Specifications Part 1, Physical Layer Simplified Specification
+//! - SD Specifications Part E1, SDIO Simplified Specification
+
+use crate::{
+    block,
+    bsp::device_driver::common::MMIODerefWrapper,
+    driver,
+    exception::asynchronous::IRQNumber,
+    memory::{Address, Virtual},
+    sched::BlockingMutex,
+    synchronization, time,
+};
+use core::time::Duration;
+use tock_registers::{
+    interfaces::{ReadWriteable, Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::{ReadOnly, ReadWrite},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Base clock of the EMMC controller as set up by the firmware.
+const BASE_CLOCK_HZ: u32 = 41_666_666;
+
+/// Clock used during card identification.
+const IDENT_CLOCK_HZ: u32 = 400_000;
+
+/// Clock used once the card is selected.
+const TRANSFER_CLOCK_HZ: u32 = 25_000_000;
+
+/// How long to wait for the controller or the card before giving up.
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Maximum number of bytes of a CMD53 transfer in byte mode.
+const MAX_BYTE_TRANSFER: usize = 512;
+
+/// Blocks moved under one lock, so that IRQs aren't masked for too long.
+const MAX_BLOCKS_PER_TRANSFER: usize = 16;
+
+// OCR bits of SD memory cards.
+const OCR_VOLTAGES: u32 = 0x00ff_8000;
+const OCR_HCS: u32 = 1 << 30;
+const OCR_BUSY: u32 = 1 << 31;
+
+/// SEND_IF_COND argument: 2.7-3.6 V and a check pattern the card echoes.
+const IF_COND: u32 = 0x1aa;
+
+// EMMC registers.
+//
+// Descriptions taken from "BCM2835 ARM Peripherals", chapter 5.
+register_bitfields! {
+    u32,
+
+    /// Block Size and Count.
+    BLKSIZECNT [
+        BLKCNT OFFSET(16) NUMBITS(16) [],
+        BLKSIZE OFFSET(0) NUMBITS(10) []
+    ],
+
+    /// Command and Transfer Mode.
+    CMDTM [
+        CMD_INDEX OFFSET(24) NUMBITS(6) [],
+        CMD_ISDATA OFFSET(21) NUMBITS(1) [],
+        CMD_IXCHK_EN OFFSET(20) NUMBITS(1) [],
+        CMD_CRCCHK_EN OFFSET(19) NUMBITS(1) [],
+        CMD_RSPNS_TYPE OFFSET(16) NUMBITS(2) [
+            None = 0b00,
+            Bits136 = 0b01,
+            Bits48 = 0b10,
+            Bits48Busy = 0b11
+        ],
+        TM_MULTI_BLOCK OFFSET(5) NUMBITS(1) [],
+        TM_DAT_DIR OFFSET(4) NUMBITS(1) [
+            HostToCard = 0,
+            CardToHost = 1
+        ],
+        TM_AUTO_CMD_EN OFFSET(2) NUMBITS(2) [
+            None = 0b00,
+            Cmd12 = 0b01
+        ],
+        TM_BLKCNT_EN OFFSET(1) NUMBITS(1) []
+    ],
+
+    /// Status.
+    STATUS [
+        DAT_INHIBIT OFFSET(1) NUMBITS(1) [],
+        CMD_INHIBIT OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Host Configuration 0.
+    CONTROL0 [
+        HCTL_DWIDTH OFFSET(1) NUMBITS(1) [
+            OneBit = 0,
+            FourBit = 1
+        ]
+    ],
+
+    /// Host Configuration 1.
+    CONTROL1 [
+        SRST_DATA OFFSET(26) NUMBITS(1) [],
+        SRST_CMD OFFSET(25) NUMBITS(1) [],
+        SRST_HC OFFSET(24) NUMBITS(1) [],
+        DATA_TOUNIT OFFSET(16) NUMBITS(4) [
+            Max = 0b1110
+        ],
+        CLK_FREQ8 OFFSET(8) NUMBITS(8) [],
+        CLK_FREQ_MS2 OFFSET(6) NUMBITS(2) [],
+        CLK_EN OFFSET(2) NUMBITS(1) [],
+        CLK_STABLE OFFSET(1) NUMBITS(1) [],
+        CLK_INTLEN OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Interrupt Flags. Write 1 to clear.
+    INTERRUPT [
+        ERR OFFSET(15) NUMBITS(1) [],
+        READ_RDY OFFSET(5) NUMBITS(1) [],
+        WRITE_RDY OFFSET(4) NUMBITS(1) [],
+        DATA_DONE OFFSET(1) NUMBITS(1) [],
+        CMD_DONE OFFSET(0) NUMBITS(1) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    RegisterBlock {
+        (0x00 => ARG2: ReadWrite<u32>),
+        (0x04 => BLKSIZECNT: ReadWrite<u32, BLKSIZECNT::Register>),
+        (0x08 => ARG1: ReadWrite<u32>),
+        (0x0C => CMDTM: ReadWrite<u32, CMDTM::Register>),
+        (0x10 => RESP0: ReadOnly<u32>),
+        (0x14 => RESP1: ReadOnly<u32>),
+        (0x18 => RESP2: ReadOnly<u32>),
+        (0x1C => RESP3: ReadOnly<u32>),
+        (0x20 => DATA: ReadWrite<u32>),
+        (0x24 => STATUS: ReadOnly<u32, STATUS::Register>),
+        (0x28 => CONTROL0: ReadWrite<u32, CONTROL0::Register>),
+        (0x2C => CONTROL1: ReadWrite<u32, CONTROL1::Register>),
+        (0x30 => INTERRUPT: ReadWrite<u32, INTERRUPT::Register>),
+        (0x34 => IRPT_MASK: ReadWrite<u32>),
+        (0x38 => IRPT_EN: ReadWrite<u32>),
+        (0x3C => CONTROL2: ReadWrite<u32>),
+        (0x40 => _reserved1),
+        (0xFC => SLOTISR_VER: ReadOnly<u32>),
+        (0x100 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+/// Response types of the commands used.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Response {
+    None,
+
+    /// 48 bit response with CRC and index check (R1, R5, R6).
+    Short,
+
+    /// 48 bit response with busy signalling (R1b).
+    ShortBusy,
+
+    /// 48 bit response without CRC and index (R3, R4).
+    ShortNoCrc,
+
+    /// 136 bit response (R2).
+    Long,
+}
+
+/// Direction of a data transfer.
+enum Transfer<'a> {
+    Read(&'a mut [u8]),
+    Write(&'a [u8]),
+}
+
+/// An identified SD memory card.
+#[derive(Copy, Clone)]
+struct SdCard {
+    /// High capacity cards are addressed in blocks, standard capacity ones in bytes.
+    high_capacity: bool,
+    blocks: u64,
+}
+
+struct EmmcInner {
+    registers: Registers,
+    base_clock_hz: u32,
+    card: Option<SdCard>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the EMMC controller.
+pub struct Emmc {
+    inner: BlockingMutex<EmmcInner>,
+    compatible: &'static str,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Spin until `condition` holds or [`TIMEOUT`] expires.
+fn wait_for(mut condition: impl FnMut() -> bool, error: &'static str) -> Result<(), &'static str> {
+    let start = time::time_manager().uptime();
+
+    while !condition() {
+        if time::time_manager().uptime() - start > TIMEOUT {
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+/// Return the number of blocks from the CSD register, as the controller returns it in RESP0-3:
+/// without the CRC, i.e. CSD bits 8 to 127.
+fn csd_blocks(resp: [u32; 4]) -> Result<u64, &'static str> {
+    match (resp[3] >> 22) & 0x3 {
+        // C_SIZE in bits 62 to 73, C_SIZE_MULT in 47 to 49, READ_BL_LEN in 80 to 83.
+        0 => {
+            let c_size = ((resp[2] & 0x3) << 10 | resp[1] >> 22) as u64;
+            let mult = (resp[1] >> 7) & 0x7;
+            let read_bl_len = (resp[2] >> 8) & 0xf;
+
+            Ok(((c_size + 1) << (mult + 2) << read_bl_len) / block::BLOCK_SIZE as u64)
+        }
+        // C_SIZE in bits 48 to 69, in units of 512 KiB.
+        1 => Ok((((resp[1] >> 8) & 0x3f_ffff) as u64 + 1) * 1024),
+        _ => Err("Unknown SD card CSD version"),
+    }
+}
+
+impl Transfer<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Transfer::Read(buf) => buf.len(),
+            Transfer::Write(buf) => buf.len(),
+        }
+    }
+}
+
+impl EmmcInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+            base_clock_hz: BASE_CLOCK_HZ,
+            card: None,
+        }
+    }
+
+    /// Reset the controller and enable the identification clock.
+    fn reset(&mut self) -> Result<(), &'static str> {
+        self.registers.CONTROL0.set(0);
+        self.registers.CONTROL2.set(0);
+        self.registers.CONTROL1.write(CONTROL1::SRST_HC::SET);
+        wait_for(
+            || !self.registers.CONTROL1.is_set(CONTROL1::SRST_HC),
+            "EMMC reset timed out",
+        )?;
+
+        self.registers.CONTROL1.write(CONTROL1::DATA_TOUNIT::Max);
+        self.set_clock(IDENT_CLOCK_HZ)?;
+
+        // Report all events in INTERRUPT, but do not raise IRQs.
+        self.registers.IRPT_MASK.set(0xffff_ffff);
+        self.registers.IRPT_EN.set(0);
+        self.registers.INTERRUPT.set(0xffff_ffff);
+
+        Ok(())
+    }
+
+    fn set_clock(&mut self, hz: u32) -> Result<(), &'static str> {
+        // 10 bit divided clock mode: f = base / (2 * div).
+        let div = self.base_clock_hz.div_ceil(2 * hz).min(0x3ff);
+
+        self.registers.CONTROL1.modify(CONTROL1::CLK_EN::CLEAR);
+        self.registers.CONTROL1.modify(
+            CONTROL1::CLK_FREQ8.val(div & 0xff)
+                + CONTROL1::CLK_FREQ_MS2.val(div >> 8)
+                + CONTROL1::CLK_INTLEN::SET,
+        );
+        wait_for(
+            || self.registers.CONTROL1.is_set(CONTROL1::CLK_STABLE),
+            "EMMC clock not stable",
+        )?;
+        self.registers.CONTROL1.modify(CONTROL1::CLK_EN::SET);
+
+        Ok(())
+    }
+
+    /// Issue a command, optionally with a data transfer, and return RESP0. A transfer of more than
+    /// one block is ended by an automatic CMD12.
+    fn command(
+        &mut self,
+        index: u32,
+        arg: u32,
+        response: Response,
+        transfer: Option<Transfer>,
+    ) -> Result<u32, &'static str> {
+        wait_for(
+            || !self.registers.STATUS.is_set(STATUS::CMD_INHIBIT),
+            "EMMC command line busy",
+        )?;
+        if transfer.is_some() || response == Response::ShortBusy {
+            wait_for(
+                || !self.registers.STATUS.is_set(STATUS::DAT_INHIBIT),
+                "EMMC data line busy",
+            )?;
+        }
+
+        self.registers.INTERRUPT.set(0xffff_ffff);
+
+        let mut cmdtm = CMDTM::CMD_INDEX.val(index);
+        cmdtm += match response {
+            Response::None => CMDTM::CMD_RSPNS_TYPE::None,
+            Response::Short => {
+                CMDTM::CMD_RSPNS_TYPE::Bits48 + CMDTM::CMD_CRCCHK_EN::SET + CMDTM::CMD_IXCHK_EN::SET
+            }
+            Response::ShortBusy => {
+                CMDTM::CMD_RSPNS_TYPE::Bits48Busy
+                    + CMDTM::CMD_CRCCHK_EN::SET
+                    + CMDTM::CMD_IXCHK_EN::SET
+            }
+            Response::ShortNoCrc => CMDTM::CMD_RSPNS_TYPE::Bits48,
+            Response::Long => CMDTM::CMD_RSPNS_TYPE::Bits136 + CMDTM::CMD_CRCCHK_EN::SET,
+        };
+
+        let block_size = transfer
+            .as_ref()
+            .map_or(0, |t| t.len().min(block::BLOCK_SIZE));
+        if let Some(t) = &transfer {
+            let count = t.len() / block_size;
+            self.registers.BLKSIZECNT.write(
+                BLKSIZECNT::BLKCNT.val(count as u32) + BLKSIZECNT::BLKSIZE.val(block_size as u32),
+            );
+
+            cmdtm += CMDTM::CMD_ISDATA::SET + CMDTM::TM_BLKCNT_EN::SET;
+            if count > 1 {
+                cmdtm += CMDTM::TM_MULTI_BLOCK::SET + CMDTM::TM_AUTO_CMD_EN::Cmd12;
+            }
+            cmdtm += match t {
+                Transfer::Read(_) => CMDTM::TM_DAT_DIR::CardToHost,
+                Transfer::Write(_) => CMDTM::TM_DAT_DIR::HostToCard,
+            };
+        }
+
+        self.registers.ARG1.set(arg);
+        self.registers.CMDTM.write(cmdtm);
+
+        self.wait_interrupt(INTERRUPT::CMD_DONE::SET.value, "EMMC command timed out")?;
+        let resp = self.registers.RESP0.get();
+
+        match transfer {
+            None => (),
+            Some(Transfer::Read(buf)) => {
+                for block in buf.chunks_mut(block_size) {
+                    self.wait_interrupt(INTERRUPT::READ_RDY::SET.value, "EMMC read timed out")?;
+                    for chunk in block.chunks_mut(4) {
+                        let word = self.registers.DATA.get().to_le_bytes();
+                        chunk.copy_from_slice(&word[..chunk.len()]);
+                    }
+                }
+                self.wait_interrupt(INTERRUPT::DATA_DONE::SET.value, "EMMC data timed out")?;
+            }
+            Some(Transfer::Write(buf)) => {
+                for block in buf.chunks(block_size) {
+                    self.wait_interrupt(INTERRUPT::WRITE_RDY::SET.value, "EMMC write timed out")?;
+                    for chunk in block.chunks(4) {
+                        let mut word = [0; 4];
+                        word[..chunk.len()].copy_from_slice(chunk);
+                        self.registers.DATA.set(u32::from_le_bytes(word));
+                    }
+                }
+                self.wait_interrupt(INTERRUPT::DATA_DONE::SET.value, "EMMC data timed out")?;
+            }
+        }
+
+        Ok(resp)
+    }
+
+    /// All four response registers, for a 136 bit response.
+    fn long_response(&self) -> [u32; 4] {
+        [
+            self.registers.RESP0.get(),
+            self.registers.RESP1.get(),
+            self.registers.RESP2.get(),
+            self.registers.RESP3.get(),
+        ]
+    }
+
+    /// Wait for and clear an interrupt flag. Error flags abort the wait.
+    fn wait_interrupt(&mut self, mask: u32, error: &'static str) -> Result<(), &'static str> {
+        let err_mask = INTERRUPT::ERR::SET.value | 0xffff_0000;
+        let mut flags = 0;
+
+        wait_for(
+            || {
+                flags = self.registers.INTERRUPT.get();
+                (flags & (mask | err_mask)) != 0
+            },
+            error,
+        )?;
+
+        if (flags & err_mask) != 0 {
+            self.registers.INTERRUPT.set(flags);
+            self.registers
+                .CONTROL1
+                .modify(CONTROL1::SRST_CMD::SET + CONTROL1::SRST_DATA::SET);
+            let _ = wait_for(
+                || {
+                    !self
+                        .registers
+                        .CONTROL1
+                        .matches_any(CONTROL1::SRST_CMD::SET + CONTROL1::SRST_DATA::SET)
+                },
+                "",
+            );
+
+            return Err("EMMC command failed");
+        }
+
+        self.registers.INTERRUPT.set(mask);
+        Ok(())
+    }
+
+    /// Identify and select an SDIO card.
+    fn sdio_enumerate(&mut self) -> Result<(), &'static str> {
+        self.reset()?;
+
+        // GO_IDLE_STATE
+        self.command(0, 0, Response::None, None)?;
+
+        // IO_SEND_OP_COND: query the OCR first, then request 3.2-3.4 V until the card is ready.
+        let ocr = self.command(5, 0, Response::ShortNoCrc, None)?;
+        let start = time::time_manager().uptime();
+        loop {
+            let resp = self.command(5, ocr & 0x00ff_8000, Response::ShortNoCrc, None)?;
+            if (resp & (1 << 31)) != 0 {
+                break;
+            }
+
+            if time::time_manager().uptime() - start > TIMEOUT {
+                return Err("SDIO card not ready");
+            }
+            time::time_manager().spin_for(Duration::from_millis(1));
+        }
+
+        // SEND_RELATIVE_ADDR, then SELECT_CARD.
+        let rca = self.command(3, 0, Response::Short, None)? >> 16;
+        self.command(7, rca << 16, Response::ShortBusy, None)?;
+
+        self.set_clock(TRANSFER_CLOCK_HZ)?;
+
+        // Switch card and host to a 4 bit bus.
+        self.io_rw_direct(true, 0, 0x07, 0x02)?;
+        self.registers
+            .CONTROL0
+            .modify(CONTROL0::HCTL_DWIDTH::FourBit);
+
+        Ok(())
+    }
+
+    /// Identify and select an SD memory card.
+    fn sd_enumerate(&mut self) -> Result<SdCard, &'static str> {
+        self.card = None;
+        self.reset()?;
+
+        // GO_IDLE_STATE
+        self.command(0, 0, Response::None, None)?;
+
+        // SEND_IF_COND. Version 1 cards don't know the command.
+        let version2 = match self.command(8, IF_COND, Response::Short, None) {
+            Ok(resp) if (resp & 0xfff) == IF_COND => true,
+            Ok(_) => return Err("SD card voltage not supported"),
+            Err(_) => false,
+        };
+
+        // SD_SEND_OP_COND until the card is ready, offering high capacity support to version 2.
+        let arg = OCR_VOLTAGES | if version2 { OCR_HCS } else { 0 };
+        let start = time::time_manager().uptime();
+        let ocr = loop {
+            self.command(55, 0, Response::Short, None)?;
+            let ocr = self.command(41, arg, Response::ShortNoCrc, None)?;
+            if (ocr & OCR_BUSY) != 0 {
+                break ocr;
+            }
+
+            if time::time_manager().uptime() - start > TIMEOUT {
+                return Err("SD card not ready");
+            }
+            time::time_manager().spin_for(Duration::from_millis(1));
+        };
+
+        // ALL_SEND_CID, then SEND_RELATIVE_ADDR.
+        self.command(2, 0, Response::Long, None)?;
+        let rca = self.command(3, 0, Response::Short, None)? >> 16;
+
+        // SEND_CSD for the capacity, then SELECT_CARD.
+        self.command(9, rca << 16, Response::Long, None)?;
+        let blocks = csd_blocks(self.long_response())?;
+        self.command(7, rca << 16, Response::ShortBusy, None)?;
+
+        self.set_clock(TRANSFER_CLOCK_HZ)?;
+
+        // SET_BUS_WIDTH to 4 bits on card and host.
+        self.command(55, rca << 16, Response::Short, None)?;
+        self.command(6, 0x2, Response::Short, None)?;
+        self.registers
+            .CONTROL0
+            .modify(CONTROL0::HCTL_DWIDTH::FourBit);
+
+        // SET_BLOCKLEN, standard capacity cards only. High capacity ones always use 512 bytes.
+        let high_capacity = (ocr & OCR_HCS) != 0;
+        if !high_capacity {
+            self.command(16, block::BLOCK_SIZE as u32, Response::Short, None)?;
+        }
+
+        let card = SdCard {
+            high_capacity,
+            blocks,
+        };
+        self.card = Some(card);
+
+        Ok(card)
+    }
+
+    /// Return the SD memory card, identifying it first if needed.
+    fn sd_card(&mut self) -> Result<SdCard, &'static str> {
+        match self.card {
+            Some(card) => Ok(card),
+            None => self.sd_enumerate(),
+        }
+    }
+
+    /// Read or write whole blocks of the SD memory card from block `lba` on. A failure forgets the
+    /// card, so that the next access identifies it again.
+    fn sd_transfer(&mut self, lba: u64, transfer: Transfer) -> Result<(), &'static str> {
+        let card = self.sd_card()?;
+
+        let len = transfer.len();
+        if len == 0 || len % block::BLOCK_SIZE != 0 {
+            return Err("Not a whole number of blocks");
+        }
+        let count = (len / block::BLOCK_SIZE) as u64;
+        if lba.checked_add(count).map_or(true, |end| end > card.blocks) {
+            return Err("Block out of range");
+        }
+
+        let addr = if card.high_capacity {
+            lba
+        } else {
+            lba * block::BLOCK_SIZE as u64
+        };
+        // READ_SINGLE_BLOCK, READ_MULTIPLE_BLOCK, WRITE_BLOCK or WRITE_MULTIPLE_BLOCK.
+        let index = match (&transfer, count) {
+            (Transfer::Read(_), 1) => 17,
+            (Transfer::Read(_), _) => 18,
+            (Transfer::Write(_), 1) => 24,
+            (Transfer::Write(_), _) => 25,
+        };
+
+        let result = self.command(index, addr as u32, Response::Short, Some(transfer));
+        if result.is_err() {
+            self.card = None;
+        }
+
+        result.map(|_| ())
+    }
+
+    /// CMD52: read or write a single register byte.
+    fn io_rw_direct(
+        &mut self,
+        write: bool,
+        function: u8,
+        addr: u32,
+        data: u8,
+    ) -> Result<u8, &'static str> {
+        let arg = ((write as u32) << 31)
+            | ((function as u32 & 0x7) << 28)
+            | ((addr & 0x1_ffff) << 9)
+            | data as u32;
+        let resp = self.command(52, arg, Response::Short, None)?;
+
+        // R5 flags: COM_CRC_ERROR, ILLEGAL_COMMAND, ERROR, FUNCTION_NUMBER, OUT_OF_RANGE.
+        if (resp & 0xcb00) != 0 {
+            return Err("SDIO direct I/O failed");
+        }
+
+        Ok(resp as u8)
+    }
+
+    /// CMD53 in byte mode. `increment` selects incrementing instead of FIFO addressing.
+    fn io_rw_extended(
+        &mut self,
+        function: u8,
+        addr: u32,
+        increment: bool,
+        transfer: Transfer,
+    ) -> Result<(), &'static str> {
+        let write = matches!(transfer, Transfer::Write(_));
+        let len = transfer.len();
+        if len == 0 || len > MAX_BYTE_TRANSFER {
+            return Err("Invalid SDIO transfer length");
+        }
+
+        // A count of 0 means 512 bytes.
+        let arg = ((write as u32) << 31)
+            | ((function as u32 & 0x7) << 28)
+            | ((increment as u32) << 26)
+            | ((addr & 0x1_ffff) << 9)
+            | (len as u32 & 0x1ff);
+
+        self.command(53, arg, Response::Short, Some(transfer))
+            .map(|_| ())
+    }
+}
+
+impl Emmc {
+    /// Run `f` on the controller, or fail if it is busy in IRQ context.
+    fn with_inner<R>(
+        &self,
+        f: impl FnOnce(&mut EmmcInner) -> Result<R, &'static str>,
+    ) -> Result<R, &'static str> {
+        self.inner.lock_or_busy(f)?
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Emmc {
+    pub const COMPATIBLE: &'static str = "BCM EMMC (SDHCI)";
+
+    /// Compatibility string of the second controller of the Raspberry Pi 4.
+    #[cfg(feature = "bsp_rpi4")]
+    pub const COMPATIBLE_EMMC2: &'static str = "BCM EMMC2 (SDHCI)";
+
+    /// Name of the SD memory card as a block device.
+    pub const SD_CARD_NAME: &'static str = "sd0";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>, compatible: &'static str) -> Self {
+        Self {
+            inner: BlockingMutex::new(EmmcInner::new(mmio_start_addr)),
+            compatible,
+        }
+    }
+
+    /// Set the rate of the base clock the bus clock is divided from, if the firmware didn't set it
+    /// up like for the EMMC controller.
+    #[cfg(feature = "bsp_rpi4")]
+    pub fn set_base_clock(&self, hz: u32) {
+        self.inner.lock(|inner| inner.base_clock_hz = hz)
+    }
+
+    /// Reset the controller, then identify and select the SDIO card on the bus. Must be called
+    /// before any I/O.
+    pub fn sdio_enumerate(&self) -> Result<(), &'static str> {
+        self.with_inner(|inner| inner.sdio_enumerate())
+    }
+
+    /// Read a register byte of an SDIO function.
+    pub fn sdio_read_byte(&self, function: u8, addr: u32) -> Result<u8, &'static str> {
+        self.with_inner(|inner| inner.io_rw_direct(false, function, addr, 0))
+    }
+
+    /// Write a register byte of an SDIO function.
+    pub fn sdio_write_byte(&self, function: u8, addr: u32, data: u8) -> Result<(), &'static str> {
+        self.with_inner(|inner| inner.io_rw_direct(true, function, addr, data).map(|_| ()))
+    }
+
+    /// Read up to 512 bytes from an SDIO function.
+    pub fn sdio_read(
+        &self,
+        function: u8,
+        addr: u32,
+        increment: bool,
+        buf: &mut [u8],
+    ) -> Result<(), &'static str> {
+        self.with_inner(|inner| {
+            inner.io_rw_extended(function, addr, increment, Transfer::Read(buf))
+        })
+    }
+
+    /// Write up to 512 bytes to an SDIO function.
+    pub fn sdio_write(
+        &self,
+        function: u8,
+        addr: u32,
+        increment: bool,
+        buf: &[u8],
+    ) -> Result<(), &'static str> {
+        self.with_inner(|inner| {
+            inner.io_rw_extended(function, addr, increment, Transfer::Write(buf))
+        })
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Emmc {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        self.compatible
+    }
+}
+
+impl block::interface::BlockDevice for Emmc {
+    fn name(&self) -> &str {
+        Self::SD_CARD_NAME
+    }
+
+    /// Zero if no card answers, or the controller is busy in IRQ context.
+    fn block_count(&self) -> u64 {
+        self.with_inner(|inner| Ok(inner.sd_card().map_or(0, |card| card.blocks)))
+            .unwrap_or(0)
+    }
+
+    fn read(&self, lba: u64, buf: &mut [u8]) -> Result<(), &'static str> {
+        let chunk_size = MAX_BLOCKS_PER_TRANSFER * block::BLOCK_SIZE;
+
+        for (i, chunk) in buf.chunks_mut(chunk_size).enumerate() {
+            let lba = lba + (i * MAX_BLOCKS_PER_TRANSFER) as u64;
+            self.with_inner(|inner| inner.sd_transfer(lba, Transfer::Read(chunk)))?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&self, lba: u64, buf: &[u8]) -> Result<(), &'static str> {
+        let chunk_size = MAX_BLOCKS_PER_TRANSFER * block::BLOCK_SIZE;
+
+        for (i, chunk) in buf.chunks(chunk_size).enumerate() {
+            let lba = lba + (i * MAX_BLOCKS_PER_TRANSFER) as u64;
+            self.with_inner(|inner| inner.sd_transfer(lba, Transfer::Write(chunk)))?;
+        }
+
+        Ok(())
+    }
+}
@@ -6,14 +6,16 @@
 
 use crate::{
     bsp::device_driver::common::MMIODerefWrapper,
-    driver,
-    exception::asynchronous::IRQNumber,
+    capture, driver,
+    exception::{self, asynchronous::IRQNumber},
+    gpio_history, info,
     memory::{Address, Virtual},
     synchronization,
-    synchronization::IRQSafeNullLock,
+    synchronization::IRQSafeDeviceLock,
 };
+use core::fmt;
 use tock_registers::{
-    interfaces::{ReadWriteable, Writeable},
+    interfaces::{ReadWriteable, Readable, Writeable},
     register_bitfields, register_structs,
     registers::{ReadOnly, ReadWrite, WriteOnly},
 };
@@ -58,16 +60,28 @@ register_bitfields! {
     GPFSEL1 [
         FSEL10 OFFSET(0)  NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc0 = 0b100 ],
         FSEL11 OFFSET(3)  NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc0 = 0b100 ],
+        /// Pin 12 AltFunc0 PWM0 channel 1
         FSEL12 OFFSET(6)  NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc0 = 0b100 ],
+        /// Pin 13 AltFunc0 PWM0 channel 2
         FSEL13 OFFSET(9)  NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc0 = 0b100 ],
-        /// Pin 14 AltFunc0 PL011 UART TX
-        FSEL14 OFFSET(12) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc0 = 0b100 ],
-        /// Pin 15 AltFunc0 PL011 UART RX
-        FSEL15 OFFSET(15) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc0 = 0b100 ],
+        /// Pin 14 AltFunc0 PL011 UART TX, AltFunc5 mini UART TX
+        FSEL14 OFFSET(12) NUMBITS(3) [
+            Input = 0b000, Output = 0b001, AltFunc0 = 0b100, AltFunc5 = 0b010
+        ],
+        /// Pin 15 AltFunc0 PL011 UART RX, AltFunc5 mini UART RX
+        FSEL15 OFFSET(15) NUMBITS(3) [
+            Input = 0b000, Output = 0b001, AltFunc0 = 0b100, AltFunc5 = 0b010
+        ],
         FSEL16 OFFSET(18) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc0 = 0b100 ],
         FSEL17 OFFSET(21) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc0 = 0b100 ],
-        FSEL18 OFFSET(24) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc0 = 0b100 ],
-        FSEL19 OFFSET(27) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc0 = 0b100 ]
+        /// Pin 18 AltFunc5 PWM0 channel 1
+        FSEL18 OFFSET(24) NUMBITS(3) [
+            Input = 0b000, Output = 0b001, AltFunc0 = 0b100, AltFunc5 = 0b010
+        ],
+        /// Pin 19 AltFunc5 PWM0 channel 2
+        FSEL19 OFFSET(27) NUMBITS(3) [
+            Input = 0b000, Output = 0b001, AltFunc0 = 0b100, AltFunc5 = 0b010
+        ]
     ],
 
     GPFSEL2 [
@@ -83,6 +97,30 @@ register_bitfields! {
         FSEL29 OFFSET(27) NUMBITS(3) [ Input = 0b000, Output = 0b001]
     ],
 
+    /// GPIO Function Select 3
+    GPFSEL3 [
+        /// Pin 30 AltFunc5 CTS1 (mini UART to the onboard Bluetooth)
+        FSEL30 OFFSET(0) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc5 = 0b010 ],
+        /// Pin 31 AltFunc5 RTS1
+        FSEL31 OFFSET(3) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc5 = 0b010 ],
+        /// Pin 32 AltFunc5 TXD1
+        FSEL32 OFFSET(6) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc5 = 0b010 ],
+        /// Pin 33 AltFunc5 RXD1
+        FSEL33 OFFSET(9) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc5 = 0b010 ],
+        /// Pin 34 AltFunc3 SD1_CLK (EMMC to the onboard Wi-Fi)
+        FSEL34 OFFSET(12) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc3 = 0b111 ],
+        /// Pin 35 AltFunc3 SD1_CMD
+        FSEL35 OFFSET(15) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc3 = 0b111 ],
+        /// Pin 36 AltFunc3 SD1_DAT0
+        FSEL36 OFFSET(18) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc3 = 0b111 ],
+        /// Pin 37 AltFunc3 SD1_DAT1
+        FSEL37 OFFSET(21) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc3 = 0b111 ],
+        /// Pin 38 AltFunc3 SD1_DAT2
+        FSEL38 OFFSET(24) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc3 = 0b111 ],
+        /// Pin 39 AltFunc3 SD1_DAT3
+        FSEL39 OFFSET(27) NUMBITS(3) [ Input = 0b000, Output = 0b001, AltFunc3 = 0b111 ]
+    ],
+
     /// GPIO Pull-up/down Register
     ///
     /// BCM2837 only.
@@ -136,35 +174,103 @@ register_structs! {
         (0x00 => GPFSEL0: ReadWrite<u32, GPFSEL0::Register>),
         (0x04 => GPFSEL1: ReadWrite<u32, GPFSEL1::Register>),
         (0x08 => GPFSEL2: ReadWrite<u32, GPFSEL2::Register>),
-        (0x0C => _reserved2),
+        (0x0C => GPFSEL3: ReadWrite<u32, GPFSEL3::Register>),
+        (0x10 => _reserved2),
         (0x1C => GPSET0: WriteOnly<u32>),   // Set GPIO 0–31
         (0x20 => GPSET1: WriteOnly<u32>),   // Set GPIO 32–53
         (0x24 => _reserved3),               // 0x24 is reserved (not used)
         (0x28 => GPCLR0: WriteOnly<u32>),   // Clear GPIO 0–31
         (0x2C => GPCLR1: WriteOnly<u32>),   // Clear GPIO 32–53
         (0x30 => _reserved4),               // 0x30 reserved
+        (0x34 => GPLEV0: ReadOnly<u32>),    // Level GPIO 0–31
+        (0x38 => GPLEV1: ReadOnly<u32>),    // Level GPIO 32–53
+        (0x3C => _reserved5),
+        (0x40 => GPEDS0: ReadWrite<u32>),   // Event detect status GPIO 0–31, write 1 to clear
+        (0x44 => GPEDS1: ReadWrite<u32>),   // Event detect status GPIO 32–53
+        (0x48 => _reserved6),
+        (0x4C => GPREN0: ReadWrite<u32>),   // Rising edge detect enable GPIO 0–31
+        (0x50 => GPREN1: ReadWrite<u32>),   // Rising edge detect enable GPIO 32–53
+        (0x54 => _reserved7),
+        (0x58 => GPFEN0: ReadWrite<u32>),   // Falling edge detect enable GPIO 0–31
+        (0x5C => GPFEN1: ReadWrite<u32>),   // Falling edge detect enable GPIO 32–53
+        (0x60 => _reserved8),
+        (0x64 => GPHEN0: ReadWrite<u32>),   // High detect enable GPIO 0–31
+        (0x68 => GPHEN1: ReadWrite<u32>),   // High detect enable GPIO 32–53
+        (0x6C => _reserved9),
+        (0x70 => GPLEN0: ReadWrite<u32>),   // Low detect enable GPIO 0–31
+        (0x74 => GPLEN1: ReadWrite<u32>),   // Low detect enable GPIO 32–53
+        (0x78 => _reserved10),
         (0x94 => GPPUD: ReadWrite<u32, GPPUD::Register>),
         (0x98 => GPPUDCLK0: ReadWrite<u32, GPPUDCLK0::Register>),
-        (0x9C => _reserved5),
+        (0x9C => GPPUDCLK1: ReadWrite<u32>),
+        (0xA0 => _reserved11),
         (0xE4 => GPIO_PUP_PDN_CNTRL_REG0: ReadWrite<u32, GPIO_PUP_PDN_CNTRL_REG0::Register>),
-        (0xE8 => @END),
+        (0xE8 => GPIO_PUP_PDN_CNTRL_REG1: ReadWrite<u32>),
+        (0xEC => GPIO_PUP_PDN_CNTRL_REG2: ReadWrite<u32>),
+        (0xF0 => GPIO_PUP_PDN_CNTRL_REG3: ReadWrite<u32>),
+        (0xF4 => @END),
     }
 }
 
 /// Abstraction for the associated MMIO registers.
 type Registers = MMIODerefWrapper<RegisterBlock>;
 
+/// Pins that can't be reconfigured without force by default: 14 and 15 carry the PL011 console.
+const DEFAULT_PROTECTED_PINS: u64 = (1 << 14) | (1 << 15);
+
+/// Highest pin that can be configured or read.
+const MAX_PIN: u8 = 29;
+
+// Function select values.
+const FSEL_INPUT: u32 = 0b000;
+const FSEL_OUTPUT: u32 = 0b001;
+const FSEL_ALT0: u32 = 0b100;
+const FSEL_ALT5: u32 = 0b010;
+
+/// Pins 0 to 27, the ones on the 40-pin header. The higher ones are wired on the board.
+const HEADER_PINS_MASK: u32 = 0x0FFF_FFFF;
+
 struct GPIOInner {
     registers: Registers,
+    protected: u64,
+
+    /// Print the register writes of pin operations instead of performing them.
+    dry_run: bool,
+
+    /// Edge detection and handler of each pin with an IRQ registered.
+    pin_irqs: [Option<(Edge, PinHandler)>; MAX_PIN as usize + 1],
 }
 
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
 //--------------------------------------------------------------------------------------------------
 
+/// Pull resistor setting of a pin.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PullMode {
+    Off,
+    Down,
+    Up,
+}
+
+/// Level changes that raise a pin IRQ.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Edge {
+    Rising,
+    Falling,
+    Both,
+}
+
+/// Called in IRQ context with the pin and its level when an edge was detected.
+pub type PinHandler = fn(pin: u8, level: bool);
+
 /// Representation of the GPIO HW.
 pub struct GPIO {
-    inner: IRQSafeNullLock<GPIOInner>,
+    inner: IRQSafeDeviceLock<GPIOInner>,
+
+    /// Registers for [`driver::interface::DeviceDriver::emergency_stop()`], which must not take
+    /// the lock.
+    stop_registers: Registers,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -180,9 +286,102 @@ impl GPIOInner {
     pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
         Self {
             registers: Registers::new(mmio_start_addr),
+            protected: DEFAULT_PROTECTED_PINS,
+            dry_run: false,
+            pin_irqs: [None; MAX_PIN as usize + 1],
+        }
+    }
+
+    /// Reject pins that are out of range, or protected unless `force` is set.
+    fn check_pin(&self, pin: u8, force: bool) -> Result<(), &'static str> {
+        if pin > MAX_PIN {
+            return Err("Unsupported GPIO pin");
+        }
+        if !force && (self.protected & (1 << pin)) != 0 {
+            return Err("GPIO pin is protected");
+        }
+
+        Ok(())
+    }
+
+    /// Print a register write that is not performed.
+    fn log_write(&self, register: &str, offset: usize, value: u32) {
+        info!(
+            "GPIO dry run: {:<23} {:#04x} <- {:#010x}",
+            register, offset, value
+        );
+    }
+
+    /// Print the write that would select `function` for a pin.
+    fn dry_run_fsel(&self, pin: u8, function: u32) {
+        let shift = (pin % 10) * 3;
+        let (register, current) = match pin / 10 {
+            0 => ("GPFSEL0", self.registers.GPFSEL0.get()),
+            1 => ("GPFSEL1", self.registers.GPFSEL1.get()),
+            _ => ("GPFSEL2", self.registers.GPFSEL2.get()),
+        };
+        let value = (current & !(0b111 << shift)) | (function << shift);
+
+        self.log_write(register, (pin / 10) as usize * 4, value);
+    }
+
+    /// Print the write that would drive a pin high or low.
+    fn dry_run_level(&self, pin: u8, high: bool) {
+        if high {
+            self.log_write("GPSET0", 0x1C, 1 << pin);
+        } else {
+            self.log_write("GPCLR0", 0x28, 1 << pin);
         }
     }
 
+    /// Print the writes that would select the pull resistor of a pin.
+    #[cfg(feature = "bsp_rpi3")]
+    fn dry_run_pull(&self, pin: u8, mode: PullMode) {
+        let pud = match mode {
+            PullMode::Off => 0b00,
+            PullMode::Down => 0b01,
+            PullMode::Up => 0b10,
+        };
+
+        self.log_write("GPPUD", 0x94, pud);
+        self.log_write("GPPUDCLK0", 0x98, 1 << pin);
+        self.log_write("GPPUD", 0x94, 0);
+        self.log_write("GPPUDCLK0", 0x98, 0);
+        self.log_write("GPPUDCLK1", 0x9C, 0);
+    }
+
+    /// Print the write that would select the pull resistor of a pin.
+    #[cfg(feature = "bsp_rpi4")]
+    fn dry_run_pull(&self, pin: u8, mode: PullMode) {
+        const REGISTERS: [&str; 4] = [
+            "GPIO_PUP_PDN_CNTRL_REG0",
+            "GPIO_PUP_PDN_CNTRL_REG1",
+            "GPIO_PUP_PDN_CNTRL_REG2",
+            "GPIO_PUP_PDN_CNTRL_REG3",
+        ];
+
+        let bits = match mode {
+            PullMode::Off => 0b00,
+            PullMode::Up => 0b01,
+            PullMode::Down => 0b10,
+        };
+        let shift = (pin % 16) * 2;
+        let r = &self.registers;
+        let current = match pin / 16 {
+            0 => r.GPIO_PUP_PDN_CNTRL_REG0.get(),
+            1 => r.GPIO_PUP_PDN_CNTRL_REG1.get(),
+            2 => r.GPIO_PUP_PDN_CNTRL_REG2.get(),
+            _ => r.GPIO_PUP_PDN_CNTRL_REG3.get(),
+        };
+        let value = (current & !(0b11 << shift)) | (bits << shift);
+
+        self.log_write(
+            REGISTERS[(pin / 16) as usize],
+            0xE4 + (pin / 16) as usize * 4,
+            value,
+        );
+    }
+
     /// Disable pull-up/down on pins 14 and 15.
     #[cfg(feature = "bsp_rpi3")]
     fn disable_pud_14_15_bcm2837(&mut self) {
@@ -231,6 +430,98 @@ impl GPIOInner {
         self.disable_pud_14_15_bcm2711();
     }
 
+    /// Pull up the SD1 command and data lines, pins 35 to 39.
+    #[cfg(feature = "bsp_rpi3")]
+    fn pull_up_sd1_bcm2837(&mut self) {
+        use crate::time;
+        use core::time::Duration;
+
+        const DELAY: Duration = Duration::from_micros(1);
+
+        self.registers.GPPUD.write(GPPUD::PUD::PullUp);
+        time::time_manager().spin_for(DELAY);
+
+        // Pins 35 to 39 are bits 3 to 7 of the second bank.
+        self.registers.GPPUDCLK1.set(0b1111_1000);
+        time::time_manager().spin_for(DELAY);
+
+        self.registers.GPPUD.write(GPPUD::PUD::Off);
+        self.registers.GPPUDCLK1.set(0);
+    }
+
+    /// Pull up the SD1 command and data lines, pins 35 to 39.
+    #[cfg(feature = "bsp_rpi4")]
+    fn pull_up_sd1_bcm2711(&mut self) {
+        // Two bits per pin, starting at pin 32. 0b01 selects the pull-up.
+        let mut val = self.registers.GPIO_PUP_PDN_CNTRL_REG2.get();
+        for pin in 35..=39 {
+            let shift = (pin - 32) * 2;
+            val = (val & !(0b11 << shift)) | (0b01 << shift);
+        }
+        self.registers.GPIO_PUP_PDN_CNTRL_REG2.set(val);
+    }
+
+    /// Route the EMMC controller to the onboard Wi-Fi chip.
+    ///
+    /// Pins 34 to 39 carry SD1 clock, command and data.
+    pub fn map_sdio_wifi(&mut self) {
+        self.registers.GPFSEL3.modify(
+            GPFSEL3::FSEL34::AltFunc3
+                + GPFSEL3::FSEL35::AltFunc3
+                + GPFSEL3::FSEL36::AltFunc3
+                + GPFSEL3::FSEL37::AltFunc3
+                + GPFSEL3::FSEL38::AltFunc3
+                + GPFSEL3::FSEL39::AltFunc3,
+        );
+
+        #[cfg(feature = "bsp_rpi3")]
+        self.pull_up_sd1_bcm2837();
+
+        #[cfg(feature = "bsp_rpi4")]
+        self.pull_up_sd1_bcm2711();
+    }
+
+    /// Map the mini UART as standard output instead of the PL011.
+    ///
+    /// TX to pin 14
+    /// RX to pin 15
+    pub fn map_mini_uart_console(&mut self) {
+        self.registers
+            .GPFSEL1
+            .modify(GPFSEL1::FSEL15::AltFunc5 + GPFSEL1::FSEL14::AltFunc5);
+
+        #[cfg(feature = "bsp_rpi3")]
+        self.disable_pud_14_15_bcm2837();
+
+        #[cfg(feature = "bsp_rpi4")]
+        self.disable_pud_14_15_bcm2711();
+    }
+
+    /// Route the mini UART to the onboard Bluetooth controller.
+    ///
+    /// Pins 30 and 31 carry CTS and RTS, pins 32 and 33 TX and RX.
+    pub fn map_mini_uart_bt(&mut self) {
+        self.registers.GPFSEL3.modify(
+            GPFSEL3::FSEL30::AltFunc5
+                + GPFSEL3::FSEL31::AltFunc5
+                + GPFSEL3::FSEL32::AltFunc5
+                + GPFSEL3::FSEL33::AltFunc5,
+        );
+    }
+
+    /// Route PWM0 to one of pins 12, 13, 18 and 19.
+    fn map_pwm(&mut self, pin: u8) -> Result<(), &'static str> {
+        match pin {
+            12 => self.registers.GPFSEL1.modify(GPFSEL1::FSEL12::AltFunc0),
+            13 => self.registers.GPFSEL1.modify(GPFSEL1::FSEL13::AltFunc0),
+            18 => self.registers.GPFSEL1.modify(GPFSEL1::FSEL18::AltFunc5),
+            19 => self.registers.GPFSEL1.modify(GPFSEL1::FSEL19::AltFunc5),
+            _ => return Err("Pin has no PWM function"),
+        }
+
+        Ok(())
+    }
+
     pub fn set_gpio17_as_output(&self) {
         self.registers.GPFSEL1.modify(GPFSEL1::FSEL17::Output);
     }
@@ -279,6 +570,133 @@ impl GPIOInner {
             _ => panic!("Unsupported GPIO pin {pin}"),
         }
     }
+    /// Configure a pin as input.
+    pub fn set_pin_as_input(&self, pin: u8) {
+        assert!(pin <= 29, "Only GPIO 0–29 are supported");
+
+        // Input is function 0b000, so clearing the pin's three FSEL bits is enough.
+        let clear = !(0b111 << ((pin % 10) * 3));
+        match pin / 10 {
+            0 => self
+                .registers
+                .GPFSEL0
+                .set(self.registers.GPFSEL0.get() & clear),
+            1 => self
+                .registers
+                .GPFSEL1
+                .set(self.registers.GPFSEL1.get() & clear),
+            _ => self
+                .registers
+                .GPFSEL2
+                .set(self.registers.GPFSEL2.get() & clear),
+        }
+    }
+
+    /// Select the pull resistor of a pin.
+    #[cfg(feature = "bsp_rpi3")]
+    fn set_pull_bcm2837(&mut self, pin: u8, mode: PullMode) {
+        use crate::time;
+        use core::time::Duration;
+
+        const DELAY: Duration = Duration::from_micros(1);
+
+        // The control line is shared by all pins. It is latched into the pins whose clock is
+        // asserted.
+        self.registers.GPPUD.write(match mode {
+            PullMode::Off => GPPUD::PUD::Off,
+            PullMode::Down => GPPUD::PUD::PullDown,
+            PullMode::Up => GPPUD::PUD::PullUp,
+        });
+        time::time_manager().spin_for(DELAY);
+
+        if pin < 32 {
+            self.registers.GPPUDCLK0.set(1 << pin);
+        } else {
+            self.registers.GPPUDCLK1.set(1 << (pin - 32));
+        }
+        time::time_manager().spin_for(DELAY);
+
+        self.registers.GPPUD.write(GPPUD::PUD::Off);
+        self.registers.GPPUDCLK0.set(0);
+        self.registers.GPPUDCLK1.set(0);
+    }
+
+    /// Select the pull resistor of a pin.
+    #[cfg(feature = "bsp_rpi4")]
+    fn set_pull_bcm2711(&mut self, pin: u8, mode: PullMode) {
+        // Two bits per pin, 16 pins per register. Note that pull-up and pull-down are encoded the
+        // other way around than on the BCM2837.
+        let bits = match mode {
+            PullMode::Off => 0b00,
+            PullMode::Up => 0b01,
+            PullMode::Down => 0b10,
+        };
+        let shift = (pin % 16) * 2;
+        let update = |val: u32| (val & !(0b11 << shift)) | (bits << shift);
+
+        let r = &self.registers;
+        match pin / 16 {
+            0 => r
+                .GPIO_PUP_PDN_CNTRL_REG0
+                .set(update(r.GPIO_PUP_PDN_CNTRL_REG0.get())),
+            1 => r
+                .GPIO_PUP_PDN_CNTRL_REG1
+                .set(update(r.GPIO_PUP_PDN_CNTRL_REG1.get())),
+            2 => r
+                .GPIO_PUP_PDN_CNTRL_REG2
+                .set(update(r.GPIO_PUP_PDN_CNTRL_REG2.get())),
+            _ => r
+                .GPIO_PUP_PDN_CNTRL_REG3
+                .set(update(r.GPIO_PUP_PDN_CNTRL_REG3.get())),
+        }
+    }
+
+    /// Select the pull resistor of a pin.
+    pub fn set_pull(&mut self, pin: u8, mode: PullMode) {
+        #[cfg(feature = "bsp_rpi3")]
+        self.set_pull_bcm2837(pin, mode);
+
+        #[cfg(feature = "bsp_rpi4")]
+        self.set_pull_bcm2711(pin, mode);
+    }
+
+    /// Disable all event detection and clear pending events.
+    fn reset_event_detect(&mut self) {
+        let r = &self.registers;
+        for reg in [
+            &r.GPREN0, &r.GPREN1, &r.GPFEN0, &r.GPFEN1, &r.GPHEN0, &r.GPHEN1, &r.GPLEN0, &r.GPLEN1,
+        ] {
+            reg.set(0);
+        }
+        r.GPEDS0.set(u32::MAX);
+        r.GPEDS1.set(u32::MAX);
+    }
+
+    /// Enable detection of `edge` on a pin, or disable it for `None`. Pins are below 32.
+    fn set_edge_detect(&mut self, pin: u8, edge: Option<Edge>) {
+        let bit = 1 << pin;
+        let rising = matches!(edge, Some(Edge::Rising | Edge::Both));
+        let falling = matches!(edge, Some(Edge::Falling | Edge::Both));
+
+        let r = &self.registers;
+        r.GPREN0
+            .set((r.GPREN0.get() & !bit) | if rising { bit } else { 0 });
+        r.GPFEN0
+            .set((r.GPFEN0.get() & !bit) | if falling { bit } else { 0 });
+
+        // Drop an event that was latched before the pin was (re)configured.
+        r.GPEDS0.set(bit);
+    }
+
+    /// Return the level of a pin.
+    pub fn read_pin(&self, pin: u8) -> bool {
+        if pin < 32 {
+            self.registers.GPLEV0.get() & (1 << pin) != 0
+        } else {
+            self.registers.GPLEV1.get() & (1 << (pin - 32)) != 0
+        }
+    }
+
     pub fn set_gpio_high(&self, pin: u8) {
         assert!(pin <= 29, "Only GPIO 0–29 are supported");
         if pin < 32 {
@@ -301,6 +719,52 @@ impl GPIOInner {
 // Public Code
 //--------------------------------------------------------------------------------------------------
 
+impl core::str::FromStr for PullMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "down" => Ok(Self::Down),
+            "up" => Ok(Self::Up),
+            _ => Err("Unknown pull mode"),
+        }
+    }
+}
+
+impl fmt::Display for PullMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::Down => write!(f, "down"),
+            Self::Up => write!(f, "up"),
+        }
+    }
+}
+
+impl core::str::FromStr for Edge {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rising" => Ok(Self::Rising),
+            "falling" => Ok(Self::Falling),
+            "both" => Ok(Self::Both),
+            _ => Err("Unknown edge"),
+        }
+    }
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Rising => write!(f, "rising"),
+            Self::Falling => write!(f, "falling"),
+            Self::Both => write!(f, "both"),
+        }
+    }
+}
+
 impl GPIO {
     pub const COMPATIBLE: &'static str = "BCM GPIO";
 
@@ -311,7 +775,8 @@ impl GPIO {
     /// - The user must ensure to provide a correct MMIO start address.
     pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
         Self {
-            inner: IRQSafeNullLock::new(GPIOInner::new(mmio_start_addr)),
+            inner: IRQSafeDeviceLock::new(GPIOInner::new(mmio_start_addr)),
+            stop_registers: Registers::new(mmio_start_addr),
         }
     }
 
@@ -320,14 +785,176 @@ impl GPIO {
         self.inner.lock(|inner| inner.map_pl011_uart())
     }
 
-    pub fn set_pin_as_output(&self, pin: u8) {
-        self.inner.lock(|inner| inner.set_pin_as_output(pin))
+    /// Concurrency safe version of `GPIOInner.map_sdio_wifi()`
+    pub fn map_sdio_wifi(&self) {
+        self.inner.lock(|inner| inner.map_sdio_wifi())
     }
-    pub fn set_gpio_high(&self, pin: u8) {
-        self.inner.lock(|inner| inner.set_gpio_high(pin))
+
+    /// Concurrency safe version of `GPIOInner.map_mini_uart_console()`
+    pub fn map_mini_uart_console(&self) {
+        self.inner.lock(|inner| inner.map_mini_uart_console())
     }
-    pub fn set_gpio_low(&self, pin: u8) {
-        self.inner.lock(|inner| inner.set_gpio_low(pin))
+
+    /// Concurrency safe version of `GPIOInner.map_mini_uart_bt()`
+    pub fn map_mini_uart_bt(&self) {
+        self.inner.lock(|inner| inner.map_mini_uart_bt())
+    }
+
+    /// Route PWM0 to `pin`. Protected pins are refused unless `force` is set.
+    pub fn map_pwm(&self, pin: u8, force: bool) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            inner.check_pin(pin, force)?;
+            if !inner.dry_run {
+                return inner.map_pwm(pin);
+            }
+
+            match pin {
+                12 | 13 => inner.dry_run_fsel(pin, FSEL_ALT0),
+                18 | 19 => inner.dry_run_fsel(pin, FSEL_ALT5),
+                _ => return Err("Pin has no PWM function"),
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Configure a pin as output. Protected pins are refused unless `force` is set.
+    pub fn set_pin_as_output(&self, pin: u8, force: bool) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            inner.check_pin(pin, force)?;
+            if inner.dry_run {
+                inner.dry_run_fsel(pin, FSEL_OUTPUT);
+            } else {
+                inner.set_pin_as_output(pin);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Drive an output pin high. Protected pins are refused unless `force` is set.
+    pub fn set_gpio_high(&self, pin: u8, force: bool) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            inner.check_pin(pin, force)?;
+            if inner.dry_run {
+                inner.dry_run_level(pin, true);
+            } else {
+                inner.set_gpio_high(pin);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Drive an output pin low. Protected pins are refused unless `force` is set.
+    pub fn set_gpio_low(&self, pin: u8, force: bool) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            inner.check_pin(pin, force)?;
+            if inner.dry_run {
+                inner.dry_run_level(pin, false);
+            } else {
+                inner.set_gpio_low(pin);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Configure a pin as input. Protected pins are refused unless `force` is set.
+    pub fn set_pin_as_input(&self, pin: u8, force: bool) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            inner.check_pin(pin, force)?;
+            if inner.dry_run {
+                inner.dry_run_fsel(pin, FSEL_INPUT);
+            } else {
+                inner.set_pin_as_input(pin);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Select the pull resistor of a pin. Protected pins are refused unless `force` is set.
+    pub fn set_pull(&self, pin: u8, mode: PullMode, force: bool) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            inner.check_pin(pin, force)?;
+            if inner.dry_run {
+                inner.dry_run_pull(pin, mode);
+            } else {
+                inner.set_pull(pin, mode);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Return the level of a pin. Reading never changes the pin, so protection does not apply.
+    pub fn read_pin(&self, pin: u8) -> Result<bool, &'static str> {
+        self.inner.lock(|inner| {
+            inner.check_pin(pin, true)?;
+
+            Ok(inner.read_pin(pin))
+        })
+    }
+
+    /// Call `handler` whenever `edge` is detected on a pin, replacing a previous handler.
+    ///
+    /// Only edge detection is configured, the pin's function is left alone. Reading the pin does
+    /// not change it, so protection does not apply.
+    pub fn register_pin_irq(
+        &self,
+        pin: u8,
+        edge: Edge,
+        handler: PinHandler,
+    ) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            inner.check_pin(pin, true)?;
+            inner.pin_irqs[pin as usize] = Some((edge, handler));
+            inner.set_edge_detect(pin, Some(edge));
+
+            Ok(())
+        })
+    }
+
+    /// Stop detecting edges on a pin.
+    pub fn unregister_pin_irq(&self, pin: u8) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            inner.check_pin(pin, true)?;
+            if inner.pin_irqs[pin as usize].take().is_none() {
+                return Err("No IRQ registered for GPIO pin");
+            }
+            inner.set_edge_detect(pin, None);
+
+            Ok(())
+        })
+    }
+
+    /// Return the pins with an IRQ registered and their edges.
+    pub fn pin_irqs(&self) -> impl Iterator<Item = (u8, Edge)> {
+        let irqs = self.inner.lock(|inner| inner.pin_irqs);
+
+        (0..=MAX_PIN).filter_map(move |pin| irqs[pin as usize].map(|(edge, _)| (pin, edge)))
+    }
+
+    /// Return the protected pins as a bit mask.
+    pub fn protected_pins(&self) -> u64 {
+        self.inner.lock(|inner| inner.protected)
+    }
+
+    /// Replace the protected pins with the ones set in `mask`.
+    pub fn set_protected_pins(&self, mask: u64) {
+        self.inner.lock(|inner| inner.protected = mask)
+    }
+
+    /// Return whether pin operations only print their register writes.
+    pub fn dry_run(&self) -> bool {
+        self.inner.lock(|inner| inner.dry_run)
+    }
+
+    /// Make pin operations print the register writes they would perform instead of performing
+    /// them. Pins are still checked, so protection and range errors show up as they would.
+    pub fn set_dry_run(&self, dry_run: bool) {
+        self.inner.lock(|inner| inner.dry_run = dry_run)
     }
 }
 
@@ -342,4 +969,62 @@ impl driver::interface::DeviceDriver for GPIO {
     fn compatible(&self) -> &'static str {
         Self::COMPATIBLE
     }
+
+    unsafe fn init(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.reset_event_detect());
+
+        Ok(())
+    }
+
+    /// Drive the header pins configured as outputs low. Other pins keep their function, so the
+    /// UART still prints the panic.
+    fn emergency_stop(&self) {
+        self.stop_registers.GPCLR0.set(HEADER_PINS_MASK);
+    }
+
+    fn register_and_enable_irq_handler(
+        &'static self,
+        irq_number: &Self::IRQNumberType,
+    ) -> Result<(), &'static str> {
+        use exception::asynchronous::{irq_manager, IRQHandlerDescriptor};
+
+        let descriptor = IRQHandlerDescriptor::new(*irq_number, Self::COMPATIBLE, self);
+
+        irq_manager().register_handler(descriptor)?;
+        irq_manager().enable(irq_number);
+
+        Ok(())
+    }
+}
+
+impl exception::asynchronous::interface::IRQHandler for GPIO {
+    fn handle(&self) -> Result<(), &'static str> {
+        // Taken first, as close to the edges as possible, for pulse capture.
+        let now = crate::time::time_manager().uptime();
+
+        let (pending, levels, irqs) = self.inner.lock(|inner| {
+            let pending = inner.registers.GPEDS0.get();
+            inner.registers.GPEDS0.set(pending);
+
+            (pending, inner.registers.GPLEV0.get(), inner.pin_irqs)
+        });
+
+        // Call the handlers without holding the lock, so that they may use the GPIO.
+        for pin in (0..=MAX_PIN).filter(|p| pending & (1 << p) != 0) {
+            let level = levels & (1 << pin) != 0;
+            let event = if level {
+                gpio_history::Event::Rising
+            } else {
+                gpio_history::Event::Falling
+            };
+            gpio_history::record(pin, event, false);
+            capture::record_edge(pin, level, now);
+
+            if let Some((_, handler)) = irqs[pin as usize] {
+                handler(pin, level);
+            }
+        }
+
+        Ok(())
+    }
 }
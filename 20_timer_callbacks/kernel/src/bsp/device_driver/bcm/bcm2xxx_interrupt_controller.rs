@@ -13,6 +13,7 @@ use crate::{
     exception::{self, asynchronous::IRQHandlerDescriptor},
     memory::{Address, Virtual},
 };
+use alloc::vec::Vec;
 use core::fmt;
 
 //--------------------------------------------------------------------------------------------------
@@ -153,6 +154,50 @@ impl exception::asynchronous::interface::IRQManager for InterruptController {
         }
     }
 
+    fn remap_handler(
+        &self,
+        irq_handler_descriptor: exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>,
+    ) -> Result<exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>, &'static str>
+    {
+        let name = irq_handler_descriptor.name();
+        let handler = irq_handler_descriptor.handler();
+
+        match irq_handler_descriptor.number() {
+            IRQNumber::Local(lirq) => self
+                .local
+                .remap_handler(IRQHandlerDescriptor::new(lirq, name, handler))
+                .map(|prev| {
+                    IRQHandlerDescriptor::new(IRQNumber::Local(lirq), prev.name(), prev.handler())
+                }),
+            IRQNumber::Peripheral(pirq) => self
+                .periph
+                .remap_handler(IRQHandlerDescriptor::new(pirq, name, handler))
+                .map(|prev| {
+                    IRQHandlerDescriptor::new(
+                        IRQNumber::Peripheral(pirq),
+                        prev.name(),
+                        prev.handler(),
+                    )
+                }),
+        }
+    }
+
+    fn handler(
+        &self,
+        irq: &Self::IRQNumberType,
+    ) -> Option<exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>> {
+        match irq {
+            IRQNumber::Local(lirq) => self
+                .local
+                .handler(lirq)
+                .map(|d| IRQHandlerDescriptor::new(*irq, d.name(), d.handler())),
+            IRQNumber::Peripheral(pirq) => self
+                .periph
+                .handler(pirq)
+                .map(|d| IRQHandlerDescriptor::new(*irq, d.name(), d.handler())),
+        }
+    }
+
     fn enable(&self, irq: &Self::IRQNumberType) {
         match irq {
             IRQNumber::Local(lirq) => self.local.enable(lirq),
@@ -160,6 +205,40 @@ impl exception::asynchronous::interface::IRQManager for InterruptController {
         }
     }
 
+    fn disable(&self, irq: &Self::IRQNumberType) {
+        match irq {
+            IRQNumber::Local(lirq) => self.local.disable(lirq),
+            IRQNumber::Peripheral(pirq) => self.periph.disable(pirq),
+        }
+    }
+
+    fn disable_all_except(&self, keep: &Self::IRQNumberType) -> Vec<Self::IRQNumberType> {
+        let (local_keep, periph_keep) = match keep {
+            IRQNumber::Local(lirq) => (Some(lirq.get()), None),
+            IRQNumber::Peripheral(pirq) => (None, Some(pirq.get())),
+        };
+
+        self.local
+            .disable_all(local_keep)
+            .into_iter()
+            .map(IRQNumber::Local)
+            .chain(
+                self.periph
+                    .disable_all(periph_keep)
+                    .into_iter()
+                    .map(IRQNumber::Peripheral),
+            )
+            .collect()
+    }
+
+    fn send_ipi(
+        &self,
+        core: usize,
+        message: exception::asynchronous::IpiMessage,
+    ) -> Result<(), &'static str> {
+        self.local.send_ipi(core, message)
+    }
+
     fn handle_pending_irqs<'irq_context>(
         &'irq_context self,
         ic: &exception::asynchronous::IRQContext<'irq_context>,
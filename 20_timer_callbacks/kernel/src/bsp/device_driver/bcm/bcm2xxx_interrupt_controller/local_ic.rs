@@ -11,16 +11,16 @@
 use super::{LocalIRQ, PendingIRQs};
 use crate::{
     bsp::device_driver::common::MMIODerefWrapper,
-    exception,
+    cpu, exception,
     memory::{Address, Virtual},
     synchronization,
-    synchronization::{IRQSafeNullLock, InitStateLock},
+    synchronization::IRQSafeNullLock,
 };
 use alloc::vec::Vec;
 use tock_registers::{
     interfaces::{Readable, Writeable},
     register_structs,
-    registers::{ReadOnly, WriteOnly},
+    registers::{ReadOnly, ReadWrite, WriteOnly},
 };
 
 //--------------------------------------------------------------------------------------------------
@@ -32,7 +32,11 @@ register_structs! {
     WORegisterBlock {
         (0x00 => _reserved1),
         (0x40 => CORE0_TIMER_INTERRUPT_CONTROL: WriteOnly<u32>),
-        (0x44 => @END),
+        (0x44 => _reserved2),
+        (0x50 => CORE_MAILBOX_INTERRUPT_CONTROL: [WriteOnly<u32>; 4]),
+        (0x60 => _reserved3),
+        (0x80 => CORE_MAILBOX_WRITE_SET: [WriteOnly<u32>; 16]),
+        (0xC0 => @END),
     }
 }
 
@@ -40,8 +44,17 @@ register_structs! {
     #[allow(non_snake_case)]
     RORegisterBlock {
         (0x00 => _reserved1),
-        (0x60 => CORE0_INTERRUPT_SOURCE: ReadOnly<u32>),
-        (0x64 => @END),
+        (0x60 => CORE_INTERRUPT_SOURCE: [ReadOnly<u32>; 4]),
+        (0x70 => @END),
+    }
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    MailboxRegisterBlock {
+        (0x00 => _reserved1),
+        (0xC0 => CORE_MAILBOX_READ_CLEAR: [ReadWrite<u32>; 16]),
+        (0x100 => @END),
     }
 }
 
@@ -51,6 +64,9 @@ type WriteOnlyRegisters = MMIODerefWrapper<WORegisterBlock>;
 /// Abstraction for the ReadOnly parts of the associated MMIO registers.
 type ReadOnlyRegisters = MMIODerefWrapper<RORegisterBlock>;
 
+/// Abstraction for the mailboxes a core reads and clears.
+type MailboxRegisters = MMIODerefWrapper<MailboxRegisterBlock>;
+
 type HandlerTable = Vec<Option<exception::asynchronous::IRQHandlerDescriptor<LocalIRQ>>>;
 
 //--------------------------------------------------------------------------------------------------
@@ -65,8 +81,16 @@ pub struct LocalIC {
     /// Register read access is unguarded.
     ro_registers: ReadOnlyRegisters,
 
-    /// Stores registered IRQ handlers. Writable only during kernel init. RO afterwards.
-    handler_table: InitStateLock<HandlerTable>,
+    /// Unguarded, as each core only reads and clears its own mailboxes.
+    mailbox_registers: MailboxRegisters,
+
+    /// Stores registered IRQ handlers. Filled during kernel init, remapped afterwards.
+    handler_table: IRQSafeNullLock<HandlerTable>,
+
+    storm_detector: exception::asynchronous::StormDetector<{ LocalIRQ::MAX_INCLUSIVE + 1 }>,
+
+    /// The enabled IRQ, if any. The control register is write-only.
+    enabled: IRQSafeNullLock<Option<usize>>,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -76,6 +100,11 @@ pub struct LocalIC {
 impl LocalIC {
     // See datasheet.
     const PERIPH_IRQ_MASK: u32 = (1 << 8);
+    const MAILBOX0_IRQ_MASK: u32 = (1 << 4);
+
+    /// Each core has four mailboxes. IPIs use mailbox 0.
+    const MAILBOXES_PER_CORE: usize = 4;
+    const NUM_CORES: usize = 4;
 
     /// Create an instance.
     ///
@@ -86,29 +115,76 @@ impl LocalIC {
         Self {
             wo_registers: IRQSafeNullLock::new(WriteOnlyRegisters::new(mmio_start_addr)),
             ro_registers: ReadOnlyRegisters::new(mmio_start_addr),
-            handler_table: InitStateLock::new(Vec::new()),
+            mailbox_registers: MailboxRegisters::new(mmio_start_addr),
+            handler_table: IRQSafeNullLock::new(Vec::new()),
+            storm_detector: exception::asynchronous::StormDetector::new(),
+            enabled: IRQSafeNullLock::new(None),
         }
     }
 
     /// Called by the kernel to bring up the device.
     pub fn init(&self) {
         self.handler_table
-            .write(|table| table.resize(LocalIRQ::MAX_INCLUSIVE + 1, None));
+            .lock(|table| table.resize(LocalIRQ::MAX_INCLUSIVE + 1, None));
+
+        // Every core takes IPIs through its mailbox 0.
+        self.wo_registers.lock(|regs| {
+            for control in regs.CORE_MAILBOX_INTERRUPT_CONTROL.iter() {
+                control.set(1);
+            }
+        });
+    }
+
+    /// Read the executing core's interrupt source register.
+    fn interrupt_source(&self) -> u32 {
+        self.ro_registers.CORE_INTERRUPT_SOURCE[cpu::smp::core_id::<usize>()].get()
     }
 
     /// Query the list of pending IRQs.
-    fn pending_irqs(&self) -> PendingIRQs {
-        // Ignore the indicator bit for a peripheral IRQ.
-        PendingIRQs::new(
-            (self.ro_registers.CORE0_INTERRUPT_SOURCE.get() & !Self::PERIPH_IRQ_MASK).into(),
-        )
+    fn pending_irqs(&self, source: u32) -> PendingIRQs {
+        // Ignore the indicator bit for a peripheral IRQ and the IPI mailbox, which are handled
+        // elsewhere.
+        PendingIRQs::new((source & !(Self::PERIPH_IRQ_MASK | Self::MAILBOX0_IRQ_MASK)).into())
+    }
+
+    /// Clear the executing core's IPI mailbox and run the handlers of the messages it held.
+    fn handle_ipis(&self, ic: &exception::asynchronous::IRQContext) {
+        let mailbox = &self.mailbox_registers.CORE_MAILBOX_READ_CLEAR
+            [cpu::smp::core_id::<usize>() * Self::MAILBOXES_PER_CORE];
+
+        // Writing a 1 clears the bit. Messages that arrive in between stay pending and raise the
+        // IRQ again.
+        let messages = mailbox.get();
+        mailbox.set(messages);
+
+        exception::asynchronous::dispatch_ipis(messages, ic);
+    }
+
+    /// Disable the IRQ with the given number.
+    fn mask(&self, _irq_number: usize) {
+        // The control register is write-only and `enable()` overwrites it with a single bit, so
+        // the one enabled IRQ is disabled by clearing the register.
+        self.wo_registers
+            .lock(|regs| regs.CORE0_TIMER_INTERRUPT_CONTROL.set(0));
+        self.enabled.lock(|enabled| *enabled = None);
+    }
+
+    /// Disable the enabled IRQ unless it is `keep`, and return it.
+    pub fn disable_all(&self, keep: Option<usize>) -> Vec<LocalIRQ> {
+        match self.enabled.lock(|enabled| *enabled) {
+            Some(i) if Some(i) != keep => {
+                self.mask(i);
+                alloc::vec![LocalIRQ::new(i)]
+            }
+            _ => Vec::new(),
+        }
     }
 }
 
 //------------------------------------------------------------------------------
 // OS Interface Code
 //------------------------------------------------------------------------------
-use synchronization::interface::{Mutex, ReadWriteEx};
+use synchronization::interface::Mutex;
 
 impl exception::asynchronous::interface::IRQManager for LocalIC {
     type IRQNumberType = LocalIRQ;
@@ -117,7 +193,7 @@ impl exception::asynchronous::interface::IRQManager for LocalIC {
         &self,
         irq_handler_descriptor: exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>,
     ) -> Result<(), &'static str> {
-        self.handler_table.write(|table| {
+        self.handler_table.lock(|table| {
             let irq_number = irq_handler_descriptor.number().get();
 
             if table[irq_number].is_some() {
@@ -130,6 +206,28 @@ impl exception::asynchronous::interface::IRQManager for LocalIC {
         })
     }
 
+    fn remap_handler(
+        &self,
+        irq_handler_descriptor: exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>,
+    ) -> Result<exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>, &'static str>
+    {
+        self.handler_table.lock(|table| {
+            let irq_number = irq_handler_descriptor.number().get();
+            let previous = table[irq_number].ok_or("No IRQ handler registered")?;
+
+            table[irq_number] = Some(irq_handler_descriptor);
+
+            Ok(previous)
+        })
+    }
+
+    fn handler(
+        &self,
+        irq: &Self::IRQNumberType,
+    ) -> Option<exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>> {
+        self.handler_table.lock(|table| table[irq.get()])
+    }
+
     fn enable(&self, irq: &Self::IRQNumberType) {
         self.wo_registers.lock(|regs| {
             let enable_bit: u32 = 1 << (irq.get());
@@ -138,23 +236,58 @@ impl exception::asynchronous::interface::IRQManager for LocalIC {
             // bits are unaffected. So we don't need read and OR'ing here.
             regs.CORE0_TIMER_INTERRUPT_CONTROL.set(enable_bit);
         });
+        self.enabled.lock(|enabled| *enabled = Some(irq.get()));
+    }
+
+    fn disable(&self, irq: &Self::IRQNumberType) {
+        self.mask(irq.get());
+    }
+
+    fn disable_all_except(&self, keep: &Self::IRQNumberType) -> Vec<Self::IRQNumberType> {
+        self.disable_all(Some(keep.get()))
+    }
+
+    fn send_ipi(
+        &self,
+        core: usize,
+        message: exception::asynchronous::IpiMessage,
+    ) -> Result<(), &'static str> {
+        if core >= Self::NUM_CORES {
+            return Err("No such core");
+        }
+
+        self.wo_registers.lock(|regs| {
+            regs.CORE_MAILBOX_WRITE_SET[core * Self::MAILBOXES_PER_CORE].set(1 << message.index())
+        });
+
+        Ok(())
     }
 
     fn handle_pending_irqs<'irq_context>(
         &'irq_context self,
-        _ic: &exception::asynchronous::IRQContext<'irq_context>,
+        ic: &exception::asynchronous::IRQContext<'irq_context>,
     ) {
-        self.handler_table.read(|table| {
-            for irq_number in self.pending_irqs() {
-                match table[irq_number] {
-                    None => panic!("No handler registered for IRQ {}", irq_number),
-                    Some(descriptor) => {
-                        // Call the IRQ handler. Panics on failure.
-                        descriptor.handler().handle().expect("Error handling IRQ");
+        let source = self.interrupt_source();
+
+        if source & Self::MAILBOX0_IRQ_MASK != 0 {
+            self.handle_ipis(ic);
+        }
+
+        for irq_number in self.pending_irqs(source) {
+            // Copied out of the table, so that the handler may remap IRQs.
+            match self.handler_table.lock(|table| table[irq_number]) {
+                None => panic!("No handler registered for IRQ {}", irq_number),
+                Some(descriptor) => {
+                    // Call the IRQ handler. Panics on failure.
+                    descriptor.handler().handle().expect("Error handling IRQ");
+
+                    if self.storm_detector.record(irq_number) {
+                        self.mask(irq_number);
+                        exception::asynchronous::report_storm(irq_number, descriptor.name());
                     }
                 }
             }
-        })
+        }
     }
 
     fn print_handler(&self) {
@@ -162,7 +295,7 @@ impl exception::asynchronous::interface::IRQManager for LocalIC {
 
         info!("      Local handler:");
 
-        self.handler_table.read(|table| {
+        self.handler_table.lock(|table| {
             for (i, opt) in table.iter().enumerate() {
                 if let Some(handler) = opt {
                     info!("            {: >3}. {}", i, handler.name());
@@ -14,7 +14,7 @@ use crate::{
     exception,
     memory::{Address, Virtual},
     synchronization,
-    synchronization::{IRQSafeNullLock, InitStateLock},
+    synchronization::IRQSafeNullLock,
 };
 use alloc::vec::Vec;
 use tock_registers::{
@@ -33,7 +33,10 @@ register_structs! {
         (0x00 => _reserved1),
         (0x10 => ENABLE_1: WriteOnly<u32>),
         (0x14 => ENABLE_2: WriteOnly<u32>),
-        (0x18 => @END),
+        (0x18 => _reserved2),
+        (0x1C => DISABLE_1: WriteOnly<u32>),
+        (0x20 => DISABLE_2: WriteOnly<u32>),
+        (0x24 => @END),
     }
 }
 
@@ -67,8 +70,13 @@ pub struct PeripheralIC {
     /// Register read access is unguarded.
     ro_registers: ReadOnlyRegisters,
 
-    /// Stores registered IRQ handlers. Writable only during kernel init. RO afterwards.
-    handler_table: InitStateLock<HandlerTable>,
+    /// Stores registered IRQ handlers. Filled during kernel init, remapped afterwards.
+    handler_table: IRQSafeNullLock<HandlerTable>,
+
+    storm_detector: exception::asynchronous::StormDetector<{ PeripheralIRQ::MAX_INCLUSIVE + 1 }>,
+
+    /// The enabled IRQs, one bit each. The enable registers are write-only.
+    enabled: IRQSafeNullLock<u64>,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -85,14 +93,16 @@ impl PeripheralIC {
         Self {
             wo_registers: IRQSafeNullLock::new(WriteOnlyRegisters::new(mmio_start_addr)),
             ro_registers: ReadOnlyRegisters::new(mmio_start_addr),
-            handler_table: InitStateLock::new(Vec::new()),
+            handler_table: IRQSafeNullLock::new(Vec::new()),
+            storm_detector: exception::asynchronous::StormDetector::new(),
+            enabled: IRQSafeNullLock::new(0),
         }
     }
 
     /// Called by the kernel to bring up the device.
     pub fn init(&self) {
         self.handler_table
-            .write(|table| table.resize(PeripheralIRQ::MAX_INCLUSIVE + 1, None));
+            .lock(|table| table.resize(PeripheralIRQ::MAX_INCLUSIVE + 1, None));
     }
 
     /// Query the list of pending IRQs.
@@ -102,12 +112,44 @@ impl PeripheralIC {
 
         PendingIRQs::new(pending_mask)
     }
+
+    /// Disable the IRQ with the given number.
+    fn mask(&self, irq_number: usize) {
+        self.wo_registers.lock(|regs| {
+            let disable_reg = if irq_number <= 31 {
+                &regs.DISABLE_1
+            } else {
+                &regs.DISABLE_2
+            };
+
+            // Like enabling, writing a 1 clears only the corresponding enable bit.
+            disable_reg.set(1 << (irq_number % 32));
+        });
+        self.enabled.lock(|enabled| *enabled &= !(1 << irq_number));
+    }
+
+    /// Disable all enabled IRQs that have a handler registered, except `keep`, and return them.
+    pub fn disable_all(&self, keep: Option<usize>) -> Vec<PeripheralIRQ> {
+        let enabled = self.enabled.lock(|enabled| *enabled);
+        let mut disabled = Vec::new();
+
+        self.handler_table.lock(|table| {
+            for (i, _) in table.iter().enumerate().filter(|(_, h)| h.is_some()) {
+                if Some(i) != keep && enabled & (1 << i) != 0 {
+                    self.mask(i);
+                    disabled.push(PeripheralIRQ::new(i));
+                }
+            }
+        });
+
+        disabled
+    }
 }
 
 //------------------------------------------------------------------------------
 // OS Interface Code
 //------------------------------------------------------------------------------
-use synchronization::interface::{Mutex, ReadWriteEx};
+use synchronization::interface::Mutex;
 
 impl exception::asynchronous::interface::IRQManager for PeripheralIC {
     type IRQNumberType = PeripheralIRQ;
@@ -116,7 +158,7 @@ impl exception::asynchronous::interface::IRQManager for PeripheralIC {
         &self,
         irq_handler_descriptor: exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>,
     ) -> Result<(), &'static str> {
-        self.handler_table.write(|table| {
+        self.handler_table.lock(|table| {
             let irq_number = irq_handler_descriptor.number().get();
 
             if table[irq_number].is_some() {
@@ -129,6 +171,28 @@ impl exception::asynchronous::interface::IRQManager for PeripheralIC {
         })
     }
 
+    fn remap_handler(
+        &self,
+        irq_handler_descriptor: exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>,
+    ) -> Result<exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>, &'static str>
+    {
+        self.handler_table.lock(|table| {
+            let irq_number = irq_handler_descriptor.number().get();
+            let previous = table[irq_number].ok_or("No IRQ handler registered")?;
+
+            table[irq_number] = Some(irq_handler_descriptor);
+
+            Ok(previous)
+        })
+    }
+
+    fn handler(
+        &self,
+        irq: &Self::IRQNumberType,
+    ) -> Option<exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>> {
+        self.handler_table.lock(|table| table[irq.get()])
+    }
+
     fn enable(&self, irq: &Self::IRQNumberType) {
         self.wo_registers.lock(|regs| {
             let enable_reg = if irq.get() <= 31 {
@@ -143,23 +207,36 @@ impl exception::asynchronous::interface::IRQManager for PeripheralIC {
             // bits are unaffected. So we don't need read and OR'ing here.
             enable_reg.set(enable_bit);
         });
+        self.enabled.lock(|enabled| *enabled |= 1 << irq.get());
+    }
+
+    fn disable(&self, irq: &Self::IRQNumberType) {
+        self.mask(irq.get());
+    }
+
+    fn disable_all_except(&self, keep: &Self::IRQNumberType) -> Vec<Self::IRQNumberType> {
+        self.disable_all(Some(keep.get()))
     }
 
     fn handle_pending_irqs<'irq_context>(
         &'irq_context self,
         _ic: &exception::asynchronous::IRQContext<'irq_context>,
     ) {
-        self.handler_table.read(|table| {
-            for irq_number in self.pending_irqs() {
-                match table[irq_number] {
-                    None => panic!("No handler registered for IRQ {}", irq_number),
-                    Some(descriptor) => {
-                        // Call the IRQ handler. Panics on failure.
-                        descriptor.handler().handle().expect("Error handling IRQ");
+        for irq_number in self.pending_irqs() {
+            // Copied out of the table, so that the handler may remap IRQs.
+            match self.handler_table.lock(|table| table[irq_number]) {
+                None => panic!("No handler registered for IRQ {}", irq_number),
+                Some(descriptor) => {
+                    // Call the IRQ handler. Panics on failure.
+                    descriptor.handler().handle().expect("Error handling IRQ");
+
+                    if self.storm_detector.record(irq_number) {
+                        self.mask(irq_number);
+                        exception::asynchronous::report_storm(irq_number, descriptor.name());
                     }
                 }
             }
-        })
+        }
     }
 
     fn print_handler(&self) {
@@ -167,7 +244,7 @@ impl exception::asynchronous::interface::IRQManager for PeripheralIC {
 
         info!("      Peripheral handler:");
 
-        self.handler_table.read(|table| {
+        self.handler_table.lock(|table| {
             for (i, opt) in table.iter().enumerate() {
                 if let Some(handler) = opt {
                     info!("            {: >3}. {}", i, handler.name());
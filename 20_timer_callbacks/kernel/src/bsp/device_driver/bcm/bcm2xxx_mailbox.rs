@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! VideoCore mailbox driver.
+//!
+//! Talks to the firmware through the property channel. A request is a buffer of tags in memory
+//! whose bus address is written to the mailbox. The firmware writes its responses into the same
+//! buffer and posts the address back.
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    cpu, driver,
+    exception::asynchronous::IRQNumber,
+    memory::{self, Address, Virtual},
+    spin_until, synchronization,
+    synchronization::IRQSafeNullLock,
+};
+use core::time::Duration;
+use tock_registers::{
+    interfaces::{Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::{ReadOnly, WriteOnly},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The property channel, ARM to VideoCore.
+const CHANNEL_PROPERTY: u32 = 8;
+
+/// Offset of ARM RAM in the VideoCore's bus address space, bypassing its L2 cache.
+const BUS_ADDRESS_OFFSET: usize = 0xC000_0000;
+
+/// How long to wait for the firmware to answer.
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+const REQUEST: u32 = 0;
+const RESPONSE_SUCCESS: u32 = 0x8000_0000;
+const END_TAG: u32 = 0;
+
+/// Property tags.
+const TAG_GET_FIRMWARE_REVISION: u32 = 0x0000_0001;
+#[cfg(feature = "bsp_rpi4")]
+const TAG_GET_BOARD_MAC_ADDRESS: u32 = 0x0001_0003;
+const TAG_GET_BOARD_SERIAL: u32 = 0x0001_0004;
+const TAG_GET_POWER_STATE: u32 = 0x0002_0001;
+#[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+const TAG_SET_POWER_STATE: u32 = 0x0002_8001;
+const TAG_GET_CLOCK_RATE: u32 = 0x0003_0002;
+const TAG_GET_MAX_CLOCK_RATE: u32 = 0x0003_0004;
+const TAG_GET_TEMPERATURE: u32 = 0x0003_0006;
+const TAG_GET_MIN_CLOCK_RATE: u32 = 0x0003_0007;
+const TAG_GET_THROTTLED: u32 = 0x0003_0046;
+const TAG_SET_CLOCK_RATE: u32 = 0x0003_8002;
+
+/// The SoC's only temperature sensor.
+const TEMPERATURE_SENSOR_SOC: u32 = 0;
+
+/// Bits of a power state.
+const POWER_STATE_ON: u32 = 1 << 0;
+const POWER_STATE_MISSING: u32 = 1 << 1;
+
+/// Request bit of a power state change, to return only once the domain is stable.
+#[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+const POWER_STATE_WAIT: u32 = 1 << 1;
+
+register_bitfields! {
+    u32,
+
+    /// Mailbox 0 Status
+    STATUS [
+        /// The ARM to VideoCore mailbox is full
+        FULL OFFSET(31) NUMBITS(1) [],
+
+        /// The VideoCore to ARM mailbox is empty
+        EMPTY OFFSET(30) NUMBITS(1) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    pub RegisterBlock {
+        (0x00 => READ: ReadOnly<u32>),
+        (0x04 => _reserved1),
+        (0x18 => STATUS: ReadOnly<u32, STATUS::Register>),
+        (0x1C => _reserved2),
+        (0x20 => WRITE: WriteOnly<u32>),
+        (0x24 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+/// A property request. The firmware requires 16 byte alignment, the low bits carry the channel.
+#[repr(C, align(16))]
+struct PropertyBuffer([u32; 16]);
+
+struct MailboxInner {
+    registers: Registers,
+    buffer: PropertyBuffer,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the VideoCore mailbox.
+pub struct Mailbox {
+    inner: IRQSafeNullLock<MailboxInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl MailboxInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+            buffer: PropertyBuffer([0; 16]),
+        }
+    }
+
+    fn wait_while(&self, condition: impl Fn() -> bool) -> Result<(), &'static str> {
+        spin_until!(
+            !condition(),
+            TIMEOUT,
+            "Mailbox timeout",
+            self.registers.STATUS.get()
+        )
+        .map_err(|x| x.context)?;
+
+        Ok(())
+    }
+
+    /// Send a single tag with `request` as its value and return the response value.
+    fn property(
+        &mut self,
+        tag: u32,
+        request: &[u32],
+        response: &mut [u32],
+    ) -> Result<(), &'static str> {
+        let value_len = request.len().max(response.len());
+        let total_len = 5 + value_len + 1;
+        if total_len > self.buffer.0.len() {
+            return Err("Property request too large");
+        }
+
+        let b = &mut self.buffer.0;
+        b.fill(0);
+        b[0] = (total_len * 4) as u32;
+        b[1] = REQUEST;
+        b[2] = tag;
+        b[3] = (value_len * 4) as u32;
+        b[4] = 0;
+        b[5..5 + request.len()].copy_from_slice(request);
+        b[5 + value_len] = END_TAG;
+
+        let virt_addr = Address::<Virtual>::new(b.as_ptr() as usize);
+        let phys_addr = memory::mmu::try_kernel_virt_addr_to_phys_addr(virt_addr)?;
+        let message = (phys_addr.as_usize() | BUS_ADDRESS_OFFSET) as u32 | CHANNEL_PROPERTY;
+
+        // The firmware reads and writes the buffer behind the caches' back.
+        cpu::clean_invalidate_dcache(b.as_ptr() as usize, total_len * 4);
+
+        self.wait_while(|| self.registers.STATUS.is_set(STATUS::FULL))?;
+        self.registers.WRITE.set(message);
+
+        loop {
+            self.wait_while(|| self.registers.STATUS.is_set(STATUS::EMPTY))?;
+            if self.registers.READ.get() == message {
+                break;
+            }
+        }
+
+        cpu::clean_invalidate_dcache(self.buffer.0.as_ptr() as usize, total_len * 4);
+
+        let b = &self.buffer.0;
+        // Bit 31 of the tag's length field is set by the firmware when it filled in a response.
+        if b[1] != RESPONSE_SUCCESS || b[4] & (1 << 31) == 0 {
+            return Err("Property request failed");
+        }
+        response.copy_from_slice(&b[5..5 + response.len()]);
+
+        Ok(())
+    }
+}
+
+impl Mailbox {
+    /// Send a tag that takes a clock ID and answers with the ID and a rate.
+    fn clock_property(&self, tag: u32, clock: u32) -> Result<u32, &'static str> {
+        let mut response = [0; 2];
+        self.inner
+            .lock(|inner| inner.property(tag, &[clock], &mut response))?;
+        if response[0] != clock {
+            return Err("Property request failed");
+        }
+
+        Ok(response[1])
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Mailbox {
+    pub const COMPATIBLE: &'static str = "BCM Mailbox";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(MailboxInner::new(mmio_start_addr)),
+        }
+    }
+
+    /// Return the board's serial number.
+    pub fn board_serial(&self) -> Result<u64, &'static str> {
+        let mut serial = [0; 2];
+        self.inner
+            .lock(|inner| inner.property(TAG_GET_BOARD_SERIAL, &[], &mut serial))?;
+
+        Ok(((serial[1] as u64) << 32) | serial[0] as u64)
+    }
+
+    /// Return the MAC address the firmware assigned to the board's Ethernet port.
+    #[cfg(feature = "bsp_rpi4")]
+    pub fn board_mac_address(&self) -> Result<[u8; 6], &'static str> {
+        let mut words = [0; 2];
+        self.inner
+            .lock(|inner| inner.property(TAG_GET_BOARD_MAC_ADDRESS, &[], &mut words))?;
+
+        let [a, b, c, d] = words[0].to_le_bytes();
+        let [e, f, _, _] = words[1].to_le_bytes();
+        Ok([a, b, c, d, e, f])
+    }
+
+    /// Return the firmware's revision, the build time in seconds since the Unix epoch.
+    pub fn firmware_revision(&self) -> Result<u32, &'static str> {
+        let mut revision = [0; 1];
+        self.inner
+            .lock(|inner| inner.property(TAG_GET_FIRMWARE_REVISION, &[], &mut revision))?;
+
+        Ok(revision[0])
+    }
+
+    /// Return whether power domain `device` exists and whether it is powered on.
+    pub fn power_state(&self, device: u32) -> Result<(bool, bool), &'static str> {
+        let mut state = [0; 2];
+        self.inner
+            .lock(|inner| inner.property(TAG_GET_POWER_STATE, &[device], &mut state))?;
+        if state[0] != device {
+            return Err("Property request failed");
+        }
+
+        Ok((
+            state[1] & POWER_STATE_MISSING == 0,
+            state[1] & POWER_STATE_ON != 0,
+        ))
+    }
+
+    /// Return the rate of clock `clock` in Hz.
+    pub fn clock_rate(&self, clock: u32) -> Result<u32, &'static str> {
+        self.clock_property(TAG_GET_CLOCK_RATE, clock)
+    }
+
+    /// Return the lowest and the highest rate of clock `clock` in Hz.
+    pub fn clock_range(&self, clock: u32) -> Result<(u32, u32), &'static str> {
+        Ok((
+            self.clock_property(TAG_GET_MIN_CLOCK_RATE, clock)?,
+            self.clock_property(TAG_GET_MAX_CLOCK_RATE, clock)?,
+        ))
+    }
+
+    /// Set clock `clock` to `hz` and return the rate the firmware chose. Turbo mode is left alone.
+    pub fn set_clock_rate(&self, clock: u32, hz: u32) -> Result<u32, &'static str> {
+        let mut response = [0; 2];
+        self.inner
+            .lock(|inner| inner.property(TAG_SET_CLOCK_RATE, &[clock, hz, 1], &mut response))?;
+        if response[0] != clock || response[1] == 0 {
+            return Err("Property request failed");
+        }
+
+        Ok(response[1])
+    }
+
+    /// Return the SoC temperature in thousandths of a degree Celsius.
+    pub fn temperature(&self) -> Result<u32, &'static str> {
+        let mut response = [0; 2];
+        self.inner.lock(|inner| {
+            inner.property(
+                TAG_GET_TEMPERATURE,
+                &[TEMPERATURE_SENSOR_SOC],
+                &mut response,
+            )
+        })?;
+
+        Ok(response[1])
+    }
+
+    /// Return the firmware's throttling flags. The lower half holds the current conditions, the
+    /// upper half the ones that occurred since boot.
+    pub fn throttled(&self) -> Result<u32, &'static str> {
+        let mut flags = [0; 1];
+        self.inner
+            .lock(|inner| inner.property(TAG_GET_THROTTLED, &[0], &mut flags))?;
+
+        Ok(flags[0])
+    }
+
+    /// Switch power domain `device` on or off, waiting until it is stable.
+    #[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+    pub fn set_power_state(&self, device: u32, on: bool) -> Result<(), &'static str> {
+        let request = [device, on as u32 | POWER_STATE_WAIT];
+        let mut state = [0; 2];
+        self.inner
+            .lock(|inner| inner.property(TAG_SET_POWER_STATE, &request, &mut state))?;
+        if state[0] != device || state[1] & POWER_STATE_MISSING != 0 {
+            return Err("Property request failed");
+        }
+        if (state[1] & POWER_STATE_ON != 0) != on {
+            return Err("Power domain did not switch");
+        }
+
+        Ok(())
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Mailbox {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+}
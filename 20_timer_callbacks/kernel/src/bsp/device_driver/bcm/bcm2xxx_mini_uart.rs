@@ -0,0 +1,554 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Mini UART driver.
+//!
+//! The mini UART of the AUX block has one of two roles:
+//!
+//! - Bluetooth: the PL011 drives the console on pins 14 and 15, so the onboard Bluetooth controller
+//!   is reached through the mini UART on pins 30 to 33, with hardware flow control. The driver is
+//!   polled.
+//! - Console: the mini UART takes pins 14 and 15 at 115200 8N1, e.g. when the PL011 is wanted for
+//!   something else. Transmission is polled, received characters raise the AUX IRQ and are fed to
+//!   the shell.
+//!
+//! The mini UART only supports 7 or 8 data bits, one stop bit and no parity.
+
+use crate::{
+    bluetooth,
+    bsp::device_driver::common::MMIODerefWrapper,
+    console::{self, line_discipline, line_editor},
+    driver,
+    exception::{self, asynchronous::IRQNumber},
+    memory::{Address, Virtual},
+    shell, spin_until, synchronization,
+    synchronization::IRQSafeDeviceLock,
+};
+use core::{fmt, time::Duration};
+use tock_registers::{
+    interfaces::{ReadWriteable, Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::ReadWrite,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The mini UART's baud rate generator runs off the VPU core clock, which the firmware fixes when
+/// `enable_uart=1` is set in config.txt.
+#[cfg(feature = "bsp_rpi3")]
+const CORE_CLOCK_HZ: u32 = 250_000_000;
+
+#[cfg(feature = "bsp_rpi4")]
+const CORE_CLOCK_HZ: u32 = 500_000_000;
+
+/// Baud rate of the Bluetooth controller after power-on, also used for the console.
+const DEFAULT_BAUD_RATE: u32 = 115_200;
+
+/// Baud rate limits of the divisor at the core clock.
+const MIN_BAUD_RATE: u32 = CORE_CLOCK_HZ / (8 * 0x1_0000);
+const MAX_BAUD_RATE: u32 = CORE_CLOCK_HZ / 8;
+
+/// How long the TX FIFO may take to accept a character or to drain.
+const TX_TIMEOUT: Duration = Duration::from_millis(100);
+
+register_bitfields! {
+    u32,
+
+    /// Auxiliary Enables
+    AUX_ENABLES [
+        /// Mini UART enable
+        MINI_UART OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Mini UART Interrupt Enable. The layout is that of a 16550, the BCM2835 datasheet has it
+    /// wrong
+    AUX_MU_IER [
+        /// Undocumented, but no interrupt is raised unless both bits are set
+        LINE_STATUS OFFSET(2) NUMBITS(2) [],
+
+        /// Raise an interrupt while the receive FIFO holds data
+        RX_INTERRUPT OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Mini UART Interrupt Identify
+    AUX_MU_IIR [
+        /// On write, clear the receive and transmit FIFOs
+        FIFO_CLEAR OFFSET(1) NUMBITS(2) [
+            Rx = 0b01,
+            Tx = 0b10,
+            Both = 0b11
+        ]
+    ],
+
+    /// Mini UART Line Control
+    AUX_MU_LCR [
+        DATA_SIZE OFFSET(0) NUMBITS(2) [
+            SevenBit = 0b00,
+            EightBit = 0b11
+        ]
+    ],
+
+    /// Mini UART Line Status
+    AUX_MU_LSR [
+        /// The transmitter is idle and the FIFO is empty
+        TX_IDLE OFFSET(6) NUMBITS(1) [],
+
+        /// The transmit FIFO can accept at least one byte
+        TX_EMPTY OFFSET(5) NUMBITS(1) [],
+
+        /// The receive FIFO holds at least one byte
+        DATA_READY OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Mini UART Extra Control
+    AUX_MU_CNTL [
+        /// Stop transmitting while CTS is de-asserted
+        CTS_FLOW OFFSET(3) NUMBITS(1) [],
+
+        /// De-assert RTS when the receive FIFO fills up
+        RTS_FLOW OFFSET(2) NUMBITS(1) [],
+
+        TX_ENABLE OFFSET(1) NUMBITS(1) [],
+        RX_ENABLE OFFSET(0) NUMBITS(1) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    RegisterBlock {
+        (0x00 => AUX_IRQ: ReadWrite<u32>),
+        (0x04 => AUX_ENABLES: ReadWrite<u32, AUX_ENABLES::Register>),
+        (0x08 => _reserved1),
+        (0x40 => AUX_MU_IO: ReadWrite<u32>),
+        (0x44 => AUX_MU_IER: ReadWrite<u32, AUX_MU_IER::Register>),
+        (0x48 => AUX_MU_IIR: ReadWrite<u32, AUX_MU_IIR::Register>),
+        (0x4C => AUX_MU_LCR: ReadWrite<u32, AUX_MU_LCR::Register>),
+        (0x50 => AUX_MU_MCR: ReadWrite<u32>),
+        (0x54 => AUX_MU_LSR: ReadWrite<u32, AUX_MU_LSR::Register>),
+        (0x58 => AUX_MU_MSR: ReadWrite<u32>),
+        (0x5C => AUX_MU_SCRATCH: ReadWrite<u32>),
+        (0x60 => AUX_MU_CNTL: ReadWrite<u32, AUX_MU_CNTL::Register>),
+        (0x64 => AUX_MU_STAT: ReadWrite<u32>),
+        (0x68 => AUX_MU_BAUD: ReadWrite<u32>),
+        (0x6C => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+struct MiniUartInner {
+    registers: Registers,
+    role: MiniUartRole,
+    initialized: bool,
+    baud: u32,
+    chars_written: usize,
+    chars_read: usize,
+
+    /// Where the output of shell commands entered on this UART goes. Console role only.
+    session: Option<console::Output>,
+
+    /// The shell command being typed.
+    editor: line_editor::LineEditor,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// What the mini UART is used for.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MiniUartRole {
+    /// Transport of the onboard Bluetooth controller.
+    Bluetooth,
+
+    /// Console and shell.
+    Console,
+}
+
+/// Representation of the mini UART.
+pub struct MiniUart {
+    inner: IRQSafeDeviceLock<MiniUartInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl MiniUartInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    const unsafe fn new(mmio_start_addr: Address<Virtual>, role: MiniUartRole) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+            role,
+            initialized: false,
+            baud: DEFAULT_BAUD_RATE,
+            chars_written: 0,
+            chars_read: 0,
+            session: None,
+            editor: line_editor::LineEditor::new(),
+        }
+    }
+
+    /// Enable the mini UART with 8N1 and the default baud rate. The Bluetooth role uses hardware
+    /// flow control.
+    ///
+    /// The AUX block is shared with the SPI masters, which are not used. In the Bluetooth role,
+    /// enabling is deferred to first use so that a board without Bluetooth never touches the
+    /// block.
+    fn init(&mut self) {
+        self.registers
+            .AUX_ENABLES
+            .modify(AUX_ENABLES::MINI_UART::SET);
+
+        // Disable TX, RX and interrupts while configuring.
+        self.registers.AUX_MU_CNTL.set(0);
+        self.registers.AUX_MU_IER.set(0);
+
+        self.registers
+            .AUX_MU_LCR
+            .write(AUX_MU_LCR::DATA_SIZE::EightBit);
+        self.registers.AUX_MU_MCR.set(0);
+        self.registers
+            .AUX_MU_IIR
+            .write(AUX_MU_IIR::FIFO_CLEAR::Both);
+        self.set_baud_rate(self.baud);
+
+        let flow = match self.role {
+            MiniUartRole::Bluetooth => AUX_MU_CNTL::CTS_FLOW::SET + AUX_MU_CNTL::RTS_FLOW::SET,
+            MiniUartRole::Console => AUX_MU_CNTL::CTS_FLOW::CLEAR + AUX_MU_CNTL::RTS_FLOW::CLEAR,
+        };
+        self.registers
+            .AUX_MU_CNTL
+            .write(flow + AUX_MU_CNTL::TX_ENABLE::SET + AUX_MU_CNTL::RX_ENABLE::SET);
+
+        if self.session.is_some() {
+            self.enable_rx_interrupt();
+        }
+
+        self.initialized = true;
+    }
+
+    fn enable_rx_interrupt(&mut self) {
+        self.registers
+            .AUX_MU_IER
+            .write(AUX_MU_IER::RX_INTERRUPT::SET + AUX_MU_IER::LINE_STATUS.val(0b11));
+    }
+
+    fn ensure_initialized(&mut self) {
+        if !self.initialized {
+            self.init();
+        }
+    }
+
+    /// The baud rate is `core_clock / (8 * (divisor + 1))`.
+    fn set_baud_rate(&mut self, baud: u32) {
+        let divisor = (CORE_CLOCK_HZ / (8 * baud)).saturating_sub(1);
+        self.registers.AUX_MU_BAUD.set(divisor & 0xffff);
+        self.baud = baud;
+    }
+
+    fn write_byte(&mut self, b: u8) {
+        // Spin while the TX FIFO is full. The character is dropped if no slot frees up.
+        let slot = spin_until!(
+            self.registers.AUX_MU_LSR.is_set(AUX_MU_LSR::TX_EMPTY),
+            TX_TIMEOUT,
+            "Mini UART TX FIFO full",
+            self.registers.AUX_MU_LSR.get()
+        );
+        if slot.is_err() {
+            return;
+        }
+
+        self.registers.AUX_MU_IO.set(b as u32);
+        self.chars_written += 1;
+    }
+
+    /// Send a character, translated by the line discipline.
+    fn write_char(&mut self, c: char) {
+        line_discipline::output(c, |o| self.write_byte(o as u8));
+    }
+
+    fn flush(&self) {
+        let _ = spin_until!(
+            self.registers.AUX_MU_LSR.is_set(AUX_MU_LSR::TX_IDLE),
+            TX_TIMEOUT,
+            "Mini UART TX busy",
+            self.registers.AUX_MU_LSR.get()
+        );
+    }
+
+    fn read_byte(&mut self) -> Option<u8> {
+        if !self.registers.AUX_MU_LSR.is_set(AUX_MU_LSR::DATA_READY) {
+            return None;
+        }
+
+        self.chars_read += 1;
+        Some(self.registers.AUX_MU_IO.get() as u8)
+    }
+
+    /// Receive a character, translated by the line discipline.
+    fn read_char_converting(&mut self) -> Option<char> {
+        self.read_byte().map(|b| line_discipline::input(b as char))
+    }
+
+    /// Feed a received character to the shell, echoing it.
+    fn shell_input(&mut self, c: char) {
+        let out = match self.session {
+            Some(out) => out,
+            None => return,
+        };
+
+        // The redraw sequences go out raw, only the line end is translated.
+        let mut echo = line_editor::Echo::new();
+        let line = self.editor.input(c, &mut echo);
+        for b in echo.as_bytes() {
+            match *b {
+                b'\n' => self.write_char('\n'),
+                _ => self.write_byte(*b),
+            }
+        }
+
+        if let Some(line) = line {
+            shell::execute_input(out, line.as_str().trim());
+        }
+    }
+}
+
+impl fmt::Write for MiniUartInner {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.write_char(c);
+        }
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl MiniUart {
+    pub const COMPATIBLE: &'static str = "BCM Mini UART";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>, role: MiniUartRole) -> Self {
+        Self {
+            inner: IRQSafeDeviceLock::new(MiniUartInner::new(mmio_start_addr, role)),
+        }
+    }
+
+    /// Return what the mini UART is used for.
+    pub fn role(&self) -> MiniUartRole {
+        self.inner.lock(|inner| inner.role)
+    }
+
+    /// Discard the partially entered shell command.
+    pub fn clear_command(&self) {
+        self.inner.lock(|inner| inner.editor.clear());
+    }
+
+    /// Run shell commands entered on this UART, printing their output to `out`. Console role only.
+    pub fn attach_shell(&self, out: console::Output) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            if inner.role != MiniUartRole::Console {
+                return Err("Mini UART is not a console");
+            }
+
+            inner.session = Some(out);
+            if inner.initialized {
+                inner.enable_rx_interrupt();
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Stop running shell commands, leaving the input to the console's read functions. Returns
+    /// the shell's output, to attach it again later.
+    pub fn detach_shell(&self) -> Option<console::Output> {
+        self.inner.lock(|inner| {
+            inner.registers.AUX_MU_IER.set(0);
+            inner.editor.clear();
+            inner.session.take()
+        })
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for MiniUart {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    unsafe fn init(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            if inner.role == MiniUartRole::Console {
+                inner.init();
+            }
+        });
+
+        Ok(())
+    }
+
+    fn register_and_enable_irq_handler(
+        &'static self,
+        irq_number: &Self::IRQNumberType,
+    ) -> Result<(), &'static str> {
+        use exception::asynchronous::{irq_manager, IRQHandlerDescriptor};
+
+        let descriptor = IRQHandlerDescriptor::new(*irq_number, Self::COMPATIBLE, self);
+
+        irq_manager().register_handler(descriptor)?;
+        irq_manager().enable(irq_number);
+
+        Ok(())
+    }
+}
+
+impl console::interface::Write for MiniUart {
+    fn write_char(&self, c: char) {
+        self.inner.lock(|inner| inner.write_char(c));
+    }
+
+    fn write_array(&self, a: &[char]) {
+        self.inner
+            .lock(|inner| a.iter().for_each(|c| inner.write_char(*c)));
+    }
+
+    fn write_fmt(&self, args: core::fmt::Arguments) -> fmt::Result {
+        self.inner.lock(|inner| fmt::Write::write_fmt(inner, args))
+    }
+
+    fn flush(&self) {
+        self.inner.lock(|inner| inner.flush());
+    }
+}
+
+impl console::interface::Read for MiniUart {
+    fn read_char(&self) -> char {
+        loop {
+            if let Some(c) = self.inner.lock(|inner| inner.read_char_converting()) {
+                return c;
+            }
+        }
+    }
+
+    fn read_char_nonblocking(&self) -> Option<char> {
+        self.inner.lock(|inner| inner.read_char_converting())
+    }
+
+    fn clear_rx(&self) {
+        while self.read_char_nonblocking().is_some() {}
+    }
+
+    fn inject_char(&self, c: char) {
+        self.inner.lock(|inner| inner.shell_input(c));
+    }
+}
+
+impl console::interface::Configure for MiniUart {
+    fn line_settings(&self) -> Option<console::LineSettings> {
+        Some(console::LineSettings {
+            baud: self.inner.lock(|inner| inner.baud),
+            parity: console::Parity::None,
+            stop_bits: 1,
+        })
+    }
+
+    fn set_baud(&self, baud: u32) -> Result<(), &'static str> {
+        if !(MIN_BAUD_RATE..=MAX_BAUD_RATE).contains(&baud) {
+            return Err("Baud rate out of range");
+        }
+
+        self.inner.lock(|inner| {
+            inner.flush();
+            inner.set_baud_rate(baud);
+        });
+
+        Ok(())
+    }
+
+    fn set_parity(&self, parity: console::Parity) -> Result<(), &'static str> {
+        match parity {
+            console::Parity::None => Ok(()),
+            _ => Err("The mini UART has no parity"),
+        }
+    }
+
+    fn set_stop_bits(&self, stop_bits: u8) -> Result<(), &'static str> {
+        match stop_bits {
+            1 => Ok(()),
+            _ => Err("The mini UART only has one stop bit"),
+        }
+    }
+}
+
+impl console::interface::Statistics for MiniUart {
+    fn chars_written(&self) -> usize {
+        self.inner.lock(|inner| inner.chars_written)
+    }
+
+    fn chars_read(&self) -> usize {
+        self.inner.lock(|inner| inner.chars_read)
+    }
+}
+
+impl console::interface::All for MiniUart {}
+
+impl exception::asynchronous::interface::IRQHandler for MiniUart {
+    fn handle(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            // The RX interrupt stays asserted until the FIFO is empty.
+            while let Some(c) = inner.read_char_converting() {
+                inner.shell_input(c);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+impl bluetooth::interface::HciTransport for MiniUart {
+    fn set_baud_rate(&self, baud: u32) {
+        self.inner.lock(|inner| {
+            inner.ensure_initialized();
+            inner.flush();
+            inner.set_baud_rate(baud);
+        })
+    }
+
+    fn write(&self, data: &[u8]) {
+        self.inner.lock(|inner| {
+            inner.ensure_initialized();
+            for b in data {
+                inner.write_byte(*b);
+            }
+        })
+    }
+
+    fn read_byte(&self) -> Option<u8> {
+        self.inner.lock(|inner| {
+            inner.ensure_initialized();
+            inner.read_byte()
+        })
+    }
+}
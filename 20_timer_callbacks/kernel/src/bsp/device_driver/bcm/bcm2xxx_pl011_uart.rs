@@ -7,16 +7,18 @@
 
 use crate::{
     bsp::{device_driver::common::MMIODerefWrapper, driver::gpio_high},
-    common, console, cpu, driver,
+    common,
+    console::{self, line_discipline, line_editor},
+    cpu, driver,
     exception::{self, asynchronous::IRQNumber},
-    info,
+    hil, latency,
     memory::{Address, Virtual},
-    synchronization::{self, IRQSafeNullLock},
+    shell, spin_until,
+    synchronization::{self, IRQSafeDeviceLock},
 };
-use alloc::{boxed::Box, vec::Vec};
-use core::{arch::asm, fmt, time::Duration};
+use core::{fmt, time::Duration};
 use tock_registers::{
-    interfaces::{Readable, Writeable},
+    interfaces::{ReadWriteable, Readable, Writeable},
     register_bitfields, register_structs,
     registers::{ReadOnly, ReadWrite, WriteOnly},
 };
@@ -25,7 +27,11 @@ use tock_registers::{
 // Private Definitions
 //--------------------------------------------------------------------------------------------------
 
-const CMD_BUF_CAPACITY: usize = 64;
+/// Size of the software TX buffer in front of the TX FIFO.
+const TX_BUF_SIZE: usize = 4096;
+
+/// Size of the software RX buffer for input not going to the shell.
+const RX_BUF_SIZE: usize = 256;
 
 // PL011 UART registers.
 //
@@ -99,7 +105,17 @@ register_bitfields! {
         FEN  OFFSET(4) NUMBITS(1) [
             FifosDisabled = 0,
             FifosEnabled = 1
-        ]
+        ],
+
+        /// Two stop bits select. If this bit is set to 1, two stop bits are transmitted at the end
+        /// of the frame.
+        STP2 OFFSET(3) NUMBITS(1) [],
+
+        /// Even parity select. Selects even parity if set and parity is enabled, odd otherwise.
+        EPS OFFSET(2) NUMBITS(1) [],
+
+        /// Parity enable.
+        PEN OFFSET(1) NUMBITS(1) []
     ],
 
     /// Control Register.
@@ -122,6 +138,10 @@ register_bitfields! {
             Enabled = 1
         ],
 
+        /// Loopback enable. If this bit is set to 1, the transmit output is fed back to the
+        /// receive input internally, and the TXD pin stays high.
+        LBE OFFSET(7) NUMBITS(1) [],
+
         /// UART enable:
         ///
         /// 0 = UART is disabled. If the UART is disabled in the middle of transmission or
@@ -147,6 +167,16 @@ register_bitfields! {
             OneHalf = 0b010,
             ThreeQuarters = 0b011,
             SevenEights = 0b100
+        ],
+
+        /// Transmit interrupt FIFO level select. The transmit interrupt fires when the FIFO drains
+        /// to this level.
+        TXIFLSEL OFFSET(0) NUMBITS(3) [
+            OneEigth = 0b000,
+            OneQuarter = 0b001,
+            OneHalf = 0b010,
+            ThreeQuarters = 0b011,
+            SevenEights = 0b100
         ]
     ],
 
@@ -162,6 +192,15 @@ register_bitfields! {
             Enabled = 1
         ],
 
+        /// Transmit interrupt mask. A read returns the current mask for the UARTTXINTR interrupt.
+        ///
+        /// - On a write of 1, the mask of the UARTTXINTR interrupt is set.
+        /// - A write of 0 clears the mask.
+        TXIM OFFSET(5) NUMBITS(1) [
+            Disabled = 0,
+            Enabled = 1
+        ],
+
         /// Receive interrupt mask. A read returns the current mask for the UARTRXINTR interrupt.
         ///
         /// - On a write of 1, the mask of the UARTRXINTR interrupt is set.
@@ -178,6 +217,10 @@ register_bitfields! {
         /// UARTRTINTR interrupt.
         RTMIS OFFSET(6) NUMBITS(1) [],
 
+        /// Transmit masked interrupt status. Returns the masked interrupt state of the UARTTXINTR
+        /// interrupt.
+        TXMIS OFFSET(5) NUMBITS(1) [],
+
         /// Receive masked interrupt status. Returns the masked interrupt state of the UARTRXINTR
         /// interrupt.
         RXMIS OFFSET(4) NUMBITS(1) []
@@ -200,7 +243,7 @@ register_structs! {
         (0x24 => IBRD: WriteOnly<u32, IBRD::Register>),
         (0x28 => FBRD: WriteOnly<u32, FBRD::Register>),
         (0x2c => LCR_H: WriteOnly<u32, LCR_H::Register>),
-        (0x30 => CR: WriteOnly<u32, CR::Register>),
+        (0x30 => CR: ReadWrite<u32, CR::Register>),
         (0x34 => IFLS: ReadWrite<u32, IFLS::Register>),
         (0x38 => IMSC: ReadWrite<u32, IMSC::Register>),
         (0x3C => _reserved3),
@@ -213,6 +256,28 @@ register_structs! {
 /// Abstraction for the associated MMIO registers.
 type Registers = MMIODerefWrapper<RegisterBlock>;
 
+/// Reference clock of the UART, set in config.txt.
+const UART_CLOCK_HZ: u32 = 48_000_000;
+
+/// The UART samples each bit 16 times.
+const MAX_BAUD: u32 = UART_CLOCK_HZ / 16;
+
+/// Lowest standard baud rate. Well above the limit of the 16 bit integer divisor.
+const MIN_BAUD: u32 = 300;
+
+/// How long a character may take to leave the TX FIFO, generously above a frame at [`MIN_BAUD`].
+const TX_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long the full TX FIFO may take to drain at [`MIN_BAUD`].
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Bytes sent in the loopback test, covering alternating and constant levels.
+const LOOPBACK_PATTERN: [u8; 4] = [0x55, 0xAA, 0x00, 0xFF];
+
+/// How long a byte may take to come back in loopback, ten bit times at the slowest baud rate and
+/// then some.
+const LOOPBACK_TIMEOUT: Duration = Duration::from_millis(50);
+
 #[derive(PartialEq)]
 enum BlockingMode {
     Blocking,
@@ -223,17 +288,73 @@ struct PL011UartInner {
     registers: Registers,
     chars_written: usize,
     chars_read: usize,
-    cmd_buf: [u8; CMD_BUF_CAPACITY],
-    cmd_len: usize,
+
+    /// The shell command being typed.
+    editor: line_editor::LineEditor,
+
+    /// Characters waiting for room in the TX FIFO, sent from the TX interrupt.
+    tx_buf: [u8; TX_BUF_SIZE],
+    tx_head: usize,
+    tx_len: usize,
+
+    /// Input received without a shell attached, for the console's read functions.
+    rx_buf: [u8; RX_BUF_SIZE],
+    rx_head: usize,
+    rx_len: usize,
+
+    rx_tuning: RxTuning,
+    line: console::LineSettings,
+
+    /// Set by a console shutdown or when TX stopped making progress. Output is dropped until the
+    /// next init.
+    tx_disabled: bool,
+
+    /// Where the output of shell commands entered on this UART goes.
+    session: Option<console::Output>,
+
+    /// Hardware-in-the-loop requests multiplexed with the console input.
+    hil: hil::Receiver,
 }
 
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
 //--------------------------------------------------------------------------------------------------
 
+/// Depth of the RX FIFO in characters.
+pub const RX_FIFO_DEPTH: usize = 16;
+
+/// RX FIFO fill level that raises the RX interrupt.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RxTrigger {
+    OneEighth,
+    OneQuarter,
+    OneHalf,
+    ThreeQuarters,
+    SevenEighths,
+}
+
+/// What drives the processing of received characters.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RxMode {
+    /// The RX interrupt fires at the trigger level and the RX timeout interrupt picks up the rest.
+    Interrupt,
+
+    /// Only the RX timeout interrupt fires, once the line has been idle for 32 bit periods. This
+    /// takes the fewest IRQs, but a burst longer than the FIFO overruns it.
+    Timeout,
+}
+
+/// RX interrupt settings. A low trigger level gives the lowest per-character latency, a high one
+/// the fewest IRQs during bulk transfers.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RxTuning {
+    pub trigger: RxTrigger,
+    pub mode: RxMode,
+}
+
 /// Representation of the UART.
 pub struct PL011Uart {
-    inner: IRQSafeNullLock<PL011UartInner>,
+    inner: IRQSafeDeviceLock<PL011UartInner>,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -251,14 +372,28 @@ impl PL011UartInner {
             registers: Registers::new(mmio_start_addr),
             chars_written: 0,
             chars_read: 0,
-            cmd_buf: [0; 64],
-            cmd_len: 0,
+            editor: line_editor::LineEditor::new(),
+            tx_buf: [0; TX_BUF_SIZE],
+            tx_head: 0,
+            tx_len: 0,
+            rx_buf: [0; RX_BUF_SIZE],
+            rx_head: 0,
+            rx_len: 0,
+            rx_tuning: RxTuning::DEFAULT,
+            line: console::LineSettings {
+                baud: 921_600,
+                parity: console::Parity::None,
+                stop_bits: 1,
+            },
+            tx_disabled: false,
+            session: None,
+            hil: hil::Receiver::new(),
         }
     }
 
     /// Set up baud rate and characteristics.
     ///
-    /// This results in 8N1 and 921_600 baud.
+    /// This results in 8N1 and 921_600 baud unless the line settings were changed.
     ///
     /// The calculation for the BRD is (we set the clock to 48 MHz in config.txt):
     /// `(48_000_000 / 16) / 921_600 = 3.2552083`.
@@ -290,46 +425,176 @@ impl PL011UartInner {
         // Clear all pending interrupts.
         self.registers.ICR.write(ICR::ALL::CLEAR);
 
+        // Set the baud rate, the line settings and FIFO enabled.
+        self.write_line_settings();
+
+        // Set RX FIFO fill level and enable the RX IRQs.
+        self.apply_rx_tuning();
+
+        // Turn the UART on.
+        self.registers
+            .CR
+            .write(CR::UARTEN::Enabled + CR::TXE::Enabled + CR::RXE::Enabled);
+        self.tx_disabled = false;
+    }
+
+    /// Program the baud rate divisor and the frame format from the line settings.
+    fn write_line_settings(&mut self) {
+        // The divisor in 1/64 units, rounded: `64 * clock / (16 * baud)`.
+        let divisor =
+            (UART_CLOCK_HZ as u64 * 4 + self.line.baud as u64 / 2) / self.line.baud as u64;
+
+        let parity = match self.line.parity {
+            console::Parity::None => LCR_H::PEN::CLEAR,
+            console::Parity::Even => LCR_H::PEN::SET + LCR_H::EPS::SET,
+            console::Parity::Odd => LCR_H::PEN::SET + LCR_H::EPS::CLEAR,
+        };
+        let stop_bits = match self.line.stop_bits {
+            2 => LCR_H::STP2::SET,
+            _ => LCR_H::STP2::CLEAR,
+        };
+
         // From the PL011 Technical Reference Manual:
         //
         // The LCR_H, IBRD, and FBRD registers form the single 30-bit wide LCR Register that is
         // updated on a single write strobe generated by a LCR_H write. So, to internally update the
         // contents of IBRD or FBRD, a LCR_H write must always be performed at the end.
-        //
-        // Set the baud rate, 8N1 and FIFO enabled.
-        self.registers.IBRD.write(IBRD::BAUD_DIVINT.val(3));
-        self.registers.FBRD.write(FBRD::BAUD_DIVFRAC.val(16));
+        self.registers
+            .IBRD
+            .write(IBRD::BAUD_DIVINT.val((divisor >> 6) as u32));
+        self.registers
+            .FBRD
+            .write(FBRD::BAUD_DIVFRAC.val((divisor & 0x3F) as u32));
         self.registers
             .LCR_H
-            .write(LCR_H::WLEN::EightBit + LCR_H::FEN::FifosEnabled);
+            .write(LCR_H::WLEN::EightBit + LCR_H::FEN::FifosEnabled + parity + stop_bits);
+    }
+
+    /// Switch to new line settings once the pending output has been sent.
+    fn set_line_settings(&mut self, line: console::LineSettings) -> Result<(), &'static str> {
+        if !(MIN_BAUD..=MAX_BAUD).contains(&line.baud) {
+            return Err("Baud rate out of range");
+        }
+        if !(1..=2).contains(&line.stop_bits) {
+            return Err("Expected 1 or 2 stop bits");
+        }
 
-        // Set RX FIFO fill level at 1/8.
-        self.registers.IFLS.write(IFLS::RXIFLSEL::OneEigth);
+        // The line settings must only be changed while the UART is off.
+        self.flush();
+        self.registers.CR.set(0);
 
-        // Enable RX IRQ + RX timeout IRQ.
-        self.registers
-            .IMSC
-            .write(IMSC::RXIM::Enabled + IMSC::RTIM::Enabled);
+        self.line = line;
+        self.write_line_settings();
 
-        // Turn the UART on.
-        self.registers
-            .CR
-            .write(CR::UARTEN::Enabled + CR::TXE::Enabled + CR::RXE::Enabled);
+        if !self.tx_disabled {
+            self.registers
+                .CR
+                .write(CR::UARTEN::Enabled + CR::TXE::Enabled + CR::RXE::Enabled);
+        }
+
+        Ok(())
     }
 
-    /// Send a character.
-    fn write_char(&mut self, c: char) {
-        // Spin while TX FIFO full is set, waiting for an empty slot.
-        while self.registers.FR.matches_all(FR::TXFF::SET) {
-            cpu::nop();
+    /// Wait for the TX FIFO to drain, then mask the IRQs and turn the UART off.
+    fn shutdown(&mut self) {
+        self.flush();
+
+        self.registers.IMSC.set(0);
+        self.registers.ICR.write(ICR::ALL::CLEAR);
+        self.registers.CR.set(0);
+        self.tx_disabled = true;
+    }
+
+    /// Program the RX FIFO fill level and RX IRQ masks from the current tuning.
+    fn apply_rx_tuning(&mut self) {
+        let level = match self.rx_tuning.trigger {
+            RxTrigger::OneEighth => IFLS::RXIFLSEL::OneEigth,
+            RxTrigger::OneQuarter => IFLS::RXIFLSEL::OneQuarter,
+            RxTrigger::OneHalf => IFLS::RXIFLSEL::OneHalf,
+            RxTrigger::ThreeQuarters => IFLS::RXIFLSEL::ThreeQuarters,
+            RxTrigger::SevenEighths => IFLS::RXIFLSEL::SevenEights,
+        };
+        self.registers.IFLS.write(level + IFLS::TXIFLSEL::OneHalf);
+
+        // Leave the TX interrupt alone, it follows the TX buffer.
+        let rx = match self.rx_tuning.mode {
+            RxMode::Interrupt => IMSC::RXIM::Enabled,
+            RxMode::Timeout => IMSC::RXIM::Disabled,
+        };
+        self.registers.IMSC.modify(rx + IMSC::RTIM::Enabled);
+    }
+
+    /// Move buffered characters into the TX FIFO until it is full.
+    ///
+    /// The TX interrupt only fires when the FIFO drains past the trigger level, so it is enabled
+    /// only while characters are left over, i.e. with a full FIFO.
+    fn fill_tx_fifo(&mut self) {
+        while self.tx_len > 0 && !self.registers.FR.matches_all(FR::TXFF::SET) {
+            self.registers.DR.set(self.tx_buf[self.tx_head] as u32);
+            self.tx_head = (self.tx_head + 1) % TX_BUF_SIZE;
+            self.tx_len -= 1;
+        }
+
+        let txim = if self.tx_len > 0 {
+            IMSC::TXIM::Enabled
+        } else {
+            IMSC::TXIM::Disabled
+        };
+        self.registers.IMSC.modify(txim);
+    }
+
+    /// Spin until the TX FIFO has room, then refill it from the buffer. Returns false if no room
+    /// freed up, in which case TX is given up until the next init instead of stalling every
+    /// following character.
+    fn wait_tx_room(&mut self) -> bool {
+        let room = spin_until!(
+            !self.registers.FR.matches_all(FR::TXFF::SET),
+            TX_TIMEOUT,
+            "PL011 TX FIFO full",
+            self.registers.FR.get()
+        );
+        if room.is_err() {
+            self.tx_disabled = true;
+            self.tx_len = 0;
+            return false;
         }
 
-        // Write the character to the buffer.
-        self.registers.DR.set(c as u32);
+        self.fill_tx_fifo();
+        true
+    }
+
+    /// Send a character as is.
+    fn write_raw(&mut self, c: char) {
+        // With the UART off, the TX FIFO would never drain.
+        if self.tx_disabled {
+            return;
+        }
+
+        // Only if printing outpaces the line by the whole buffer. Make room the slow way.
+        if self.tx_len == TX_BUF_SIZE && !self.wait_tx_room() {
+            return;
+        }
+
+        // Queue the character behind the buffered ones, and send what fits right away.
+        self.tx_buf[(self.tx_head + self.tx_len) % TX_BUF_SIZE] = c as u8;
+        self.tx_len += 1;
+        self.fill_tx_fifo();
 
         self.chars_written += 1;
     }
 
+    /// Send bytes as is, bypassing the line discipline.
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.write_raw(*b as char);
+        }
+    }
+
+    /// Send a character, translated by the line discipline.
+    fn write_char(&mut self, c: char) {
+        line_discipline::output(c, |o| self.write_raw(o));
+    }
+
     /// Send a slice of characters.
     fn write_array(&mut self, a: &[char]) {
         for c in a {
@@ -338,15 +603,22 @@ impl PL011UartInner {
     }
 
     /// Block execution until the last buffered character has been physically put on the TX wire.
-    fn flush(&self) {
-        // Spin until the busy bit is cleared.
-        while self.registers.FR.matches_all(FR::BUSY::SET) {
-            cpu::nop();
-        }
+    fn flush(&mut self) {
+        // Empty the TX buffer by hand, the TX interrupt can't be taken while the lock is held.
+        while self.tx_len > 0 && self.wait_tx_room() {}
+
+        // Spin until the busy bit is cleared. A timeout is in the trace buffer, there's nowhere
+        // else to report it.
+        let _ = spin_until!(
+            !self.registers.FR.matches_all(FR::BUSY::SET),
+            FLUSH_TIMEOUT,
+            "PL011 TX busy",
+            self.registers.FR.get()
+        );
     }
 
-    /// Retrieve a character.
-    fn read_char_converting(&mut self, blocking_mode: BlockingMode) -> Option<char> {
+    /// Retrieve a byte as is.
+    fn read_byte(&mut self, blocking_mode: BlockingMode) -> Option<u8> {
         // If RX FIFO is empty,
         if self.registers.FR.matches_all(FR::RXFE::SET) {
             // immediately return in non-blocking mode.
@@ -360,19 +632,144 @@ impl PL011UartInner {
             }
         }
 
-        // Read one character.
-        let mut ret = self.registers.DR.get() as u8 as char;
-
-        // Convert carrige return to newline.
-        if ret == '\r' {
-            ret = '\n'
-        }
+        let ret = self.registers.DR.get() as u8;
 
         // Update statistics.
         self.chars_read += 1;
 
         Some(ret)
     }
+
+    /// Send a pattern with the transmitter looped back to the receiver and check it arrives.
+    /// Input received before is kept for the read functions.
+    fn loopback_test(&mut self) -> Result<(), &'static str> {
+        self.flush();
+        while let Some(b) = self.read_byte(BlockingMode::NonBlocking) {
+            self.push_rx(b as char);
+        }
+
+        let cr = self.registers.CR.get();
+        self.registers.CR.modify(CR::LBE::SET);
+
+        let mut result = Ok(());
+        for b in LOOPBACK_PATTERN {
+            self.registers.DR.set(b as u32);
+            let arrived = spin_until!(
+                !self.registers.FR.matches_all(FR::RXFE::SET),
+                LOOPBACK_TIMEOUT,
+                "PL011 loopback timeout",
+                self.registers.FR.get()
+            );
+            if let Err(x) = arrived {
+                result = Err(x.context);
+                break;
+            }
+            if self.registers.DR.get() as u8 != b {
+                result = Err("PL011 loopback mismatch");
+                break;
+            }
+        }
+
+        // Nothing sent in loopback must reach the shell.
+        while self.read_byte(BlockingMode::NonBlocking).is_some() {}
+        self.registers.CR.set(cr);
+        self.registers.ICR.write(ICR::ALL::CLEAR);
+
+        result
+    }
+
+    /// Keep a received character for the read functions. Dropped if the RX buffer is full.
+    fn push_rx(&mut self, c: char) {
+        if self.rx_len == RX_BUF_SIZE {
+            return;
+        }
+
+        self.rx_buf[(self.rx_head + self.rx_len) % RX_BUF_SIZE] = c as u8;
+        self.rx_len += 1;
+    }
+
+    /// Send an echoed character. In bounded-latency mode, it is dropped if the TX buffer is full,
+    /// rather than waiting for room with IRQs masked.
+    fn echo_raw(&mut self, c: char) {
+        if self.tx_len == TX_BUF_SIZE && latency::is_bounded() {
+            return;
+        }
+
+        self.write_raw(c);
+    }
+
+    /// Feed an input character to the shell, echoing it.
+    fn input_char(&mut self, c: char) {
+        // Without a shell, the input is echoed and kept for whoever reads the console.
+        let out = match self.session {
+            Some(out) => out,
+            None => {
+                line_discipline::output(c, |o| self.echo_raw(o));
+                self.push_rx(c);
+                return;
+            }
+        };
+
+        // The redraw sequences go out raw, only the line end is translated.
+        let mut echo = line_editor::Echo::new();
+        let line = self.editor.input(c, &mut echo);
+        for b in echo.as_bytes() {
+            match *b {
+                b'\n' => line_discipline::output('\n', |o| self.echo_raw(o)),
+                _ => self.echo_raw(*b as char),
+            }
+        }
+
+        if let Some(line) = line {
+            shell::execute_input(out, line.as_str().trim());
+        }
+    }
+
+    /// Take the oldest character from the RX buffer.
+    fn pop_rx(&mut self) -> Option<char> {
+        if self.rx_len == 0 {
+            return None;
+        }
+
+        let c = self.rx_buf[self.rx_head] as char;
+        self.rx_head = (self.rx_head + 1) % RX_BUF_SIZE;
+        self.rx_len -= 1;
+
+        Some(c)
+    }
+
+    /// Take a complete line from the RX buffer into `buf`, without the newline. Returns its
+    /// length, truncated to `buf`, or 0 if no line is complete yet. A full buffer counts as a
+    /// line, as no newline could arrive anymore.
+    fn read_line(&mut self, buf: &mut [u8]) -> usize {
+        let newline =
+            (0..self.rx_len).position(|i| self.rx_buf[(self.rx_head + i) % RX_BUF_SIZE] == b'\n');
+        let line_len = match newline {
+            Some(len) => len,
+            None if self.rx_len == RX_BUF_SIZE => RX_BUF_SIZE,
+            None => return 0,
+        };
+
+        for i in 0..line_len {
+            let c = self.pop_rx().unwrap_or_default();
+            if let Some(b) = buf.get_mut(i) {
+                *b = c as u8;
+            }
+        }
+        // The newline, if any.
+        if line_len < RX_BUF_SIZE {
+            self.pop_rx();
+        }
+
+        line_len.min(buf.len())
+    }
+
+    /// Retrieve a character.
+    fn read_char_converting(&mut self, blocking_mode: BlockingMode) -> Option<char> {
+        // Translate the character, e.g. carriage return to newline.
+        self.read_byte(blocking_mode)
+            .map(|b| line_discipline::input(b as char))
+    }
 }
 
 /// Implementing `core::fmt::Write` enables usage of the `format_args!` macros, which in turn are
@@ -398,6 +795,75 @@ impl fmt::Write for PL011UartInner {
 // Public Code
 //--------------------------------------------------------------------------------------------------
 
+impl RxTrigger {
+    /// Return the number of characters in the FIFO at which the RX interrupt fires.
+    pub fn chars(self) -> usize {
+        match self {
+            Self::OneEighth => RX_FIFO_DEPTH / 8,
+            Self::OneQuarter => RX_FIFO_DEPTH / 4,
+            Self::OneHalf => RX_FIFO_DEPTH / 2,
+            Self::ThreeQuarters => RX_FIFO_DEPTH * 3 / 4,
+            Self::SevenEighths => RX_FIFO_DEPTH * 7 / 8,
+        }
+    }
+}
+
+impl core::str::FromStr for RxTrigger {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1/8" => Ok(Self::OneEighth),
+            "1/4" => Ok(Self::OneQuarter),
+            "1/2" => Ok(Self::OneHalf),
+            "3/4" => Ok(Self::ThreeQuarters),
+            "7/8" => Ok(Self::SevenEighths),
+            _ => Err("Unknown trigger level"),
+        }
+    }
+}
+
+impl fmt::Display for RxTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OneEighth => write!(f, "1/8"),
+            Self::OneQuarter => write!(f, "1/4"),
+            Self::OneHalf => write!(f, "1/2"),
+            Self::ThreeQuarters => write!(f, "3/4"),
+            Self::SevenEighths => write!(f, "7/8"),
+        }
+    }
+}
+
+impl core::str::FromStr for RxMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "irq" => Ok(Self::Interrupt),
+            "timeout" => Ok(Self::Timeout),
+            _ => Err("Unknown RX mode"),
+        }
+    }
+}
+
+impl fmt::Display for RxMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Interrupt => write!(f, "irq"),
+            Self::Timeout => write!(f, "timeout"),
+        }
+    }
+}
+
+impl RxTuning {
+    /// Lowest latency for interactive use.
+    pub const DEFAULT: Self = Self {
+        trigger: RxTrigger::OneEighth,
+        mode: RxMode::Interrupt,
+    };
+}
+
 impl PL011Uart {
     pub const COMPATIBLE: &'static str = "BCM PL011 UART";
 
@@ -408,9 +874,48 @@ impl PL011Uart {
     /// - The user must ensure to provide a correct MMIO start address.
     pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
         Self {
-            inner: IRQSafeNullLock::new(PL011UartInner::new(mmio_start_addr)),
+            inner: IRQSafeDeviceLock::new(PL011UartInner::new(mmio_start_addr)),
         }
     }
+
+    /// Discard the partially entered shell command.
+    pub fn clear_command(&self) {
+        self.inner.lock(|inner| inner.editor.clear());
+    }
+
+    /// Run shell commands entered on this UART, printing their output to `out`. Until this is
+    /// called, input is echoed and kept for the console's read functions.
+    pub fn attach_shell(&self, out: console::Output) {
+        self.inner.lock(|inner| inner.session = Some(out));
+    }
+
+    /// Stop running shell commands, handing the input to the console's read functions. Returns
+    /// the shell's output, to attach it again later.
+    pub fn detach_shell(&self) -> Option<console::Output> {
+        self.inner.lock(|inner| {
+            inner.editor.clear();
+            inner.session.take()
+        })
+    }
+
+    /// Check the UART by sending a pattern to itself in loopback mode. Output is held back
+    /// meanwhile.
+    pub fn loopback_test(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.loopback_test())
+    }
+
+    /// Return the RX interrupt settings.
+    pub fn rx_tuning(&self) -> RxTuning {
+        self.inner.lock(|inner| inner.rx_tuning)
+    }
+
+    /// Change the RX interrupt settings. They take effect immediately and survive re-init.
+    pub fn set_rx_tuning(&self, tuning: RxTuning) {
+        self.inner.lock(|inner| {
+            inner.rx_tuning = tuning;
+            inner.apply_rx_tuning();
+        });
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -467,15 +972,31 @@ impl console::interface::Write for PL011Uart {
         // Spin until TX FIFO empty is set.
         self.inner.lock(|inner| inner.flush());
     }
+
+    fn shutdown(&self) {
+        self.inner.lock(|inner| inner.shutdown());
+    }
 }
 
 impl console::interface::Read for PL011Uart {
     fn read_char(&self) -> char {
-        self.inner
-            .lock(|inner| inner.read_char_converting(BlockingMode::Blocking).unwrap())
+        self.inner.lock(|inner| match inner.pop_rx() {
+            Some(c) => c,
+            None => inner.read_char_converting(BlockingMode::Blocking).unwrap(),
+        })
+    }
+
+    fn read_char_nonblocking(&self) -> Option<char> {
+        self.inner.lock(|inner| inner.pop_rx())
+    }
+
+    fn read_line(&self, buf: &mut [u8]) -> usize {
+        self.inner.lock(|inner| inner.read_line(buf))
     }
 
     fn clear_rx(&self) {
+        self.inner.lock(|inner| inner.rx_len = 0);
+
         // Read from the RX FIFO until it is indicating empty.
         while self
             .inner
@@ -483,6 +1004,39 @@ impl console::interface::Read for PL011Uart {
             .is_some()
         {}
     }
+
+    fn inject_char(&self, c: char) {
+        self.inner.lock(|inner| inner.input_char(c));
+    }
+}
+
+impl console::interface::Configure for PL011Uart {
+    fn line_settings(&self) -> Option<console::LineSettings> {
+        Some(self.inner.lock(|inner| inner.line))
+    }
+
+    fn set_baud(&self, baud: u32) -> Result<(), &'static str> {
+        self.inner
+            .lock(|inner| inner.set_line_settings(console::LineSettings { baud, ..inner.line }))
+    }
+
+    fn set_parity(&self, parity: console::Parity) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            inner.set_line_settings(console::LineSettings {
+                parity,
+                ..inner.line
+            })
+        })
+    }
+
+    fn set_stop_bits(&self, stop_bits: u8) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            inner.set_line_settings(console::LineSettings {
+                stop_bits,
+                ..inner.line
+            })
+        })
+    }
 }
 
 impl console::interface::Statistics for PL011Uart {
@@ -495,8 +1049,6 @@ impl console::interface::Statistics for PL011Uart {
     }
 }
 
-use crate::{bsp, memory, time};
-
 impl console::interface::All for PL011Uart {}
 
 impl exception::asynchronous::interface::IRQHandler for PL011Uart {
@@ -507,130 +1059,25 @@ impl exception::asynchronous::interface::IRQHandler for PL011Uart {
             // Clear all pending IRQs.
             inner.registers.ICR.write(ICR::ALL::CLEAR);
 
+            // Refill the TX FIFO from the TX buffer.
+            if pending.is_set(MIS::TXMIS) {
+                inner.fill_tx_fifo();
+            }
+
             // Check for any kind of RX interrupt.
             if pending.matches_any(MIS::RXMIS::SET + MIS::RTMIS::SET) {
-                // Echo any received characters.
-                while let Some(c) = inner.read_char_converting(BlockingMode::NonBlocking) {
-                    inner.write_char(c);
-
-                    match c {
-                        '\n' => {
-                            // Process the command
-                            let command = core::str::from_utf8(&inner.cmd_buf[..inner.cmd_len])
-                                .unwrap_or("")
-                                .trim();
-
-                            // Privilege level
-                            if command.starts_with("level") {
-                                let (_, privilege_level) = exception::current_privilege_level();
-                                info!("Current privilege level: {}", privilege_level);
-                            }
-                            // GPIO RESET
-                            else if command.starts_with("reset_gpio") {
-                                info!("Reset All GPIO Connections");
-                                stop_all_patterns();
-                                reset_gpio();
-                            }
-                            // GPIO ON
-                            else if command.starts_with("gpio_on") {
-                                let parts: Vec<&str> = command.split_whitespace().collect();
-                                info!("{:?}", parts);
-                                gpio_on(parts[1].parse::<i32>().unwrap() as u8);
-                                info!("{} on", parts[1]);
-                            }
-                            // GPIO OFF
-                            else if command.starts_with("gpio_off") {
-                                let parts: Vec<&str> = command.split_whitespace().collect();
-                                info!("{:?}", parts[1]);
-                                gpio_off(parts[1].parse::<i32>().unwrap() as u8);
-                                info!("{} off", parts[1]);
-                            }
-                            // Board Name
-                            else if command.starts_with("board_name") {
-                                info!("Booting on: {}", bsp::board_name());
-                            }
-                            // Timer Resolution
-                            else if command.starts_with("timer_resolution") {
-                                info!(
-                                    "Architectural timer resolution: {} ns",
-                                    time::time_manager().resolution().as_nanos()
-                                );
-                            }
-                            // MMU
-                            else if command.starts_with("mmu") {
-                                info!("MMU online:");
-                                memory::mmu::kernel_print_mappings();
-                            }
-                            // Driver
-                            else if command.starts_with("driver") {
-                                info!("Drivers loaded:");
-                                driver::driver_manager().enumerate();
-                            }
-                            // Driver
-                            else if command.starts_with("irq_handler") {
-                                info!("Registered IRQ handlers:");
-                                exception::asynchronous::irq_manager().print_handler();
-                            }
-                            // Kernel Heap
-                            else if command.starts_with("kernel_heap") {
-                                info!("Kernel heap:");
-                                memory::heap_alloc::kernel_heap_allocator().print_usage();
-                            }
-                            // Hex Counter
-                            else if command.starts_with("hex_counter") {
-                                stop_all_patterns();
-                                unsafe {
-                                    HEX_RUNNING = true;
-                                    CURRENT_PATTERN = Some(PatternType::Hex);
-                                }
-                                info!("Hex Counter:");
-                                start_hex_counter();
-                            }
-                            // Left Counter
-                            else if command.starts_with("left_counter") {
-                                stop_all_patterns();
-                                unsafe {
-                                    LEFT_RUNNING = true;
-                                    CURRENT_PATTERN = Some(PatternType::Left);
-                                }
-                                info!("Left Counter:");
-                                start_left_ring_counter();
-                            }
-                            // Right Counter
-                            else if command.starts_with("right_counter") {
-                                stop_all_patterns();
-                                unsafe {
-                                    RIGHT_RUNNING = true;
-                                    CURRENT_PATTERN = Some(PatternType::Right);
-                                }
-                                info!("Right Counter:");
-                                start_right_ring_counter();
-                            }
-                            // Dhrystone
-                            else if command.starts_with("test") {
-                                run_dhrystone();
-                            }
-                            // Not found
-                            else {
-                                info!("Command not found: ");
-                            }
-
-                            inner.cmd_len = 0;
-                        }
-
-                        _ => {
-                            if inner.cmd_len < inner.cmd_buf.len() {
-                                inner.cmd_buf[inner.cmd_len] = c as u8;
-                                inner.cmd_len += 1;
-                            } else {
-                                // Command too long, reset and notify
-                                inner.cmd_len = 0;
-                                for b in b"Command too long\n" {
-                                    inner.write_char(*b as char);
-                                }
-                            }
+                while let Some(b) = inner.read_byte(BlockingMode::NonBlocking) {
+                    // Frames of the test protocol are neither echoed nor part of a command.
+                    match inner.hil.receive(b) {
+                        hil::Input::Console => (),
+                        hil::Input::Protocol => continue,
+                        hil::Input::Reply(reply) => {
+                            inner.write_bytes(reply.as_bytes());
+                            continue;
                         }
                     }
+
+                    inner.input_char(line_discipline::input(b as char));
                 }
             }
         });
@@ -638,254 +1085,3 @@ impl exception::asynchronous::interface::IRQHandler for PL011Uart {
         Ok(())
     }
 }
-
-fn reset_gpio() {
-    for pinNumber in RING_PINS {
-        setup_output(pinNumber);
-        gpio_off(pinNumber);
-    }
-}
-
-// Programs
-fn gpio_on(pin: u8) {
-    setup_output(pin);
-    unsafe { bsp::driver::gpio_high(pin) };
-    // info!("{} on", pin);
-}
-fn gpio_off(pin: u8) {
-    setup_output(pin);
-    unsafe { bsp::driver::gpio_low(pin) };
-    // info!("{} off", pin);
-}
-
-fn gpio_on_after(pin: u8, seconds: u64) {
-    time::time_manager()
-        .set_timeout_once(Duration::from_secs(seconds), Box::new(move || gpio_on(pin)));
-}
-
-fn gpio_off_after(pin: u8, seconds: u64) {
-    time::time_manager().set_timeout_once(
-        Duration::from_secs(seconds),
-        Box::new(move || gpio_off(pin)),
-    );
-}
-
-// Counters (Move to other file)
-
-static mut HEX_RUNNING: bool = false;
-static mut LEFT_RUNNING: bool = false;
-static mut RIGHT_RUNNING: bool = false;
-
-#[derive(PartialEq, Eq, Clone, Copy)]
-enum PatternType {
-    Hex,
-    Left,
-    Right,
-}
-
-static mut CURRENT_PATTERN: Option<PatternType> = None;
-
-const HEX_PINS: [u8; 4] = [1, 2, 3, 4];
-const RING_PINS: [u8; 5] = [1, 2, 3, 4, 5];
-
-fn stop_all_patterns() {
-    unsafe {
-        HEX_RUNNING = false;
-        LEFT_RUNNING = false;
-        RIGHT_RUNNING = false;
-        CURRENT_PATTERN = None;
-    }
-}
-
-fn setup_output(pin: u8) {
-    unsafe {
-        bsp::driver::gpio_as_output(pin);
-    }
-}
-
-fn hex_counter_step(step: u8) {
-    unsafe {
-        if !HEX_RUNNING {
-            return;
-        }
-    }
-    let value = step & 0x0F;
-
-    for (i, &pin) in HEX_PINS.iter().enumerate() {
-        setup_output(pin);
-        if (value >> i) & 1 == 1 {
-            gpio_on(pin);
-        } else {
-            gpio_off(pin);
-        }
-    }
-    info!("----------------------");
-
-    if (step + 1) == 16 {
-        stop_all_patterns();
-        reset_gpio();
-        return;
-    }
-
-    // Schedule next step
-    time::time_manager().set_timeout_once(
-        Duration::from_secs(1),
-        Box::new(move || hex_counter_step((step + 1) % 16)),
-    );
-}
-
-fn start_hex_counter() {
-    hex_counter_step(0);
-}
-
-fn left_ring_counter_step(index: usize) {
-    unsafe {
-        if !LEFT_RUNNING {
-            return;
-        }
-    }
-    for (i, &pin) in RING_PINS.iter().enumerate() {
-        setup_output(pin);
-        if i == index {
-            gpio_on(pin);
-        } else {
-            gpio_off(pin);
-        }
-    }
-    info!("----------------------");
-
-    if (index + 1) == RING_PINS.len() {
-        stop_all_patterns();
-        reset_gpio();
-        return;
-    }
-
-    // Schedule next step
-    let next = (index + 1) % RING_PINS.len();
-    time::time_manager().set_timeout_once(
-        Duration::from_secs(1),
-        Box::new(move || left_ring_counter_step(next)),
-    );
-}
-
-fn start_left_ring_counter() {
-    left_ring_counter_step(0);
-}
-
-fn right_ring_counter_step(index: usize) {
-    unsafe {
-        if !RIGHT_RUNNING {
-            return;
-        }
-    }
-    for (i, &pin) in RING_PINS.iter().enumerate() {
-        setup_output(pin);
-        if i == index {
-            gpio_on(pin);
-        } else {
-            gpio_off(pin);
-        }
-    }
-    info!("----------------------");
-    // Schedule next step
-    let next = if index == 0 {
-        stop_all_patterns();
-        reset_gpio();
-        return;
-    } else {
-        index - 1
-    };
-
-    time::time_manager().set_timeout_once(
-        Duration::from_secs(1),
-        Box::new(move || right_ring_counter_step(next)),
-    );
-}
-
-fn start_right_ring_counter() {
-    right_ring_counter_step(RING_PINS.len() - 1);
-}
-
-#[repr(C)]
-struct Record<'a> {
-    ptr_comp: Option<&'a mut Record<'a>>,
-    discr: i32,
-    enum_comp: i32,
-    int_comp: i32,
-    string_comp: &'a str,
-}
-
-static STRING1: &str = "DHRYSTONE PROGRAM, 1'ST STRING";
-
-pub fn run_dhrystone() {
-    const ITERATIONS: usize = 10_000;
-
-    // Create two records on stack
-    let mut record1 = Record {
-        ptr_comp: None,
-        discr: 0,
-        enum_comp: 0,
-        int_comp: 0,
-        string_comp: STRING1,
-    };
-
-    let mut record2 = Record {
-        ptr_comp: None,
-        discr: 0,
-        enum_comp: 0,
-        int_comp: 0,
-        string_comp: STRING1,
-    };
-
-    record1.ptr_comp = Some(&mut record2);
-
-    let mut int1 = 0;
-    let mut int2 = 0;
-    let mut int3 = 0;
-
-    let mut char1 = 'A';
-    let mut char2 = 'B';
-
-    info!("Running {} Dhrystone iterations...", ITERATIONS);
-
-    let start_cycles = get_cycle_count(); // You'll implement this
-    for _ in 0..ITERATIONS {
-        // Integer ops
-        int1 = 2;
-        int2 = 3;
-        int3 = int1 + int2;
-
-        // Conditional
-        if char1 != char2 {
-            int3 += 1;
-        }
-
-        // Struct manipulation
-        if let Some(ptr) = record1.ptr_comp.as_mut() {
-            ptr.int_comp = int3;
-            ptr.string_comp = "DHRYSTONE STRING";
-        }
-
-        // Simulate some string ops
-        let _ = &record1.string_comp[0..5];
-    }
-    let end_cycles = get_cycle_count();
-
-    let total_cycles = end_cycles.wrapping_sub(start_cycles);
-    let cycles_per_iter = total_cycles as f64 / ITERATIONS as f64;
-
-    info!("Dhrystone done.");
-    info!("Total cycles: {}", total_cycles);
-    info!("Cycles per iteration: {:.2}", cycles_per_iter);
-}
-
-fn get_cycle_count() -> u64 {
-    let value: u64;
-    unsafe {
-        asm!(
-            "mrs {value}, cntvct_el0",
-            value = out(reg) value
-        );
-    }
-    value
-}
@@ -0,0 +1,335 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! PWM driver.
+//!
+//! Drives the two channels of PWM0 in mark-space mode: each period of `range` clock cycles starts
+//! with `data` cycles high. The PWM clock is generated by the clock manager from the crystal
+//! oscillator divided by [`CLOCK_DIVISOR`], and stays the same for all frequencies.
+//!
+//! Channel 1 is available on pins 12 and 18, channel 2 on pins 13 and 19. Routing a pin to its
+//! channel is up to the GPIO driver.
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    driver,
+    exception::asynchronous::IRQNumber,
+    memory::{Address, Virtual},
+    spin_until, synchronization,
+    synchronization::IRQSafeNullLock,
+};
+use core::time::Duration;
+use tock_registers::{
+    interfaces::{ReadWriteable, Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::ReadWrite,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Divisor from the oscillator to the PWM clock.
+const CLOCK_DIVISOR: u32 = 2;
+
+/// Every write to the clock manager must carry the password in its upper byte.
+const CM_PASSWORD: u32 = 0x5A00_0000;
+
+const CM_CTL_SRC_OSCILLATOR: u32 = 1;
+const CM_CTL_ENAB: u32 = 1 << 4;
+const CM_CTL_BUSY: u32 = 1 << 7;
+const CM_DIV_DIVI_SHIFT: u32 = 12;
+
+/// How long the clock generator may take to start or stop.
+const CLOCK_TIMEOUT: Duration = Duration::from_millis(10);
+
+register_bitfields! {
+    u32,
+
+    /// PWM Control
+    CTL [
+        /// Channel 2 use mark-space mode
+        MSEN2 OFFSET(15) NUMBITS(1) [],
+
+        /// Channel 2 enable
+        PWEN2 OFFSET(8) NUMBITS(1) [],
+
+        /// Channel 1 use mark-space mode
+        MSEN1 OFFSET(7) NUMBITS(1) [],
+
+        /// Channel 1 enable
+        PWEN1 OFFSET(0) NUMBITS(1) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    pub RegisterBlock {
+        (0x00 => CTL: ReadWrite<u32, CTL::Register>),
+        (0x04 => _reserved1),
+        (0x10 => RNG1: ReadWrite<u32>),
+        (0x14 => DAT1: ReadWrite<u32>),
+        (0x18 => _reserved2),
+        (0x20 => RNG2: ReadWrite<u32>),
+        (0x24 => DAT2: ReadWrite<u32>),
+        (0x28 => @END),
+    }
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    pub ClockRegisterBlock {
+        (0x00 => CM_PWMCTL: ReadWrite<u32>),
+        (0x04 => CM_PWMDIV: ReadWrite<u32>),
+        (0x08 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+type ClockRegisters = MMIODerefWrapper<ClockRegisterBlock>;
+
+#[derive(Copy, Clone)]
+struct ChannelState {
+    enabled: bool,
+    hz: u32,
+    percent: u32,
+}
+
+struct PwmInner {
+    registers: Registers,
+    clock_registers: ClockRegisters,
+    clock_hz: u32,
+    channels: [ChannelState; 2],
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the PWM controller.
+pub struct Pwm {
+    inner: IRQSafeNullLock<PwmInner>,
+
+    /// Registers for [`driver::interface::DeviceDriver::emergency_stop()`], which must not take
+    /// the lock.
+    stop_registers: Registers,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl PwmInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide correct MMIO start addresses.
+    const unsafe fn new(
+        mmio_start_addr: Address<Virtual>,
+        clock_mmio_start_addr: Address<Virtual>,
+        oscillator_hz: u32,
+    ) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+            clock_registers: ClockRegisters::new(clock_mmio_start_addr),
+            clock_hz: oscillator_hz / CLOCK_DIVISOR,
+            channels: [ChannelState {
+                enabled: false,
+                hz: 1000,
+                percent: 0,
+            }; 2],
+        }
+    }
+
+    fn wait_clock(&self, busy: bool) -> Result<(), &'static str> {
+        spin_until!(
+            (self.clock_registers.CM_PWMCTL.get() & CM_CTL_BUSY != 0) == busy,
+            CLOCK_TIMEOUT,
+            "PWM clock timeout",
+            self.clock_registers.CM_PWMCTL.get()
+        )
+        .map_err(|x| x.context)?;
+
+        Ok(())
+    }
+
+    /// Stop the channels and restart the PWM clock from the oscillator.
+    fn init(&mut self) -> Result<(), &'static str> {
+        self.registers.CTL.set(0);
+
+        // The divisor must only be changed while the generator is stopped.
+        let ctl = self.clock_registers.CM_PWMCTL.get() & !CM_CTL_ENAB & 0x00FF_FFFF;
+        self.clock_registers.CM_PWMCTL.set(CM_PASSWORD | ctl);
+        self.wait_clock(false)?;
+
+        self.clock_registers
+            .CM_PWMDIV
+            .set(CM_PASSWORD | CLOCK_DIVISOR << CM_DIV_DIVI_SHIFT);
+        self.clock_registers
+            .CM_PWMCTL
+            .set(CM_PASSWORD | CM_CTL_SRC_OSCILLATOR);
+        self.clock_registers
+            .CM_PWMCTL
+            .set(CM_PASSWORD | CM_CTL_SRC_OSCILLATOR | CM_CTL_ENAB);
+        self.wait_clock(true)?;
+
+        self.channels.iter_mut().for_each(|c| c.enabled = false);
+
+        Ok(())
+    }
+
+    fn channel(&mut self, channel: usize) -> Result<&mut ChannelState, &'static str> {
+        channel
+            .checked_sub(1)
+            .and_then(|i| self.channels.get_mut(i))
+            .ok_or("Invalid PWM channel")
+    }
+
+    /// Write a channel's range and data from its frequency and duty cycle.
+    fn apply(&mut self, channel: usize) -> Result<(), &'static str> {
+        let state = *self.channel(channel)?;
+        let range = self.clock_hz / state.hz;
+        let data = (range as u64 * state.percent as u64 / 100) as u32;
+
+        match channel {
+            1 => {
+                self.registers.RNG1.set(range);
+                self.registers.DAT1.set(data);
+            }
+            _ => {
+                self.registers.RNG2.set(range);
+                self.registers.DAT2.set(data);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_enabled(&mut self, channel: usize, enable: bool) -> Result<(), &'static str> {
+        self.channel(channel)?.enabled = enable;
+        if enable {
+            self.apply(channel)?;
+        }
+
+        let field = match (channel, enable) {
+            (1, true) => CTL::PWEN1::SET + CTL::MSEN1::SET,
+            (1, false) => CTL::PWEN1::CLEAR,
+            (_, true) => CTL::PWEN2::SET + CTL::MSEN2::SET,
+            (_, false) => CTL::PWEN2::CLEAR,
+        };
+        self.registers.CTL.modify(field);
+
+        Ok(())
+    }
+
+    fn set_frequency(&mut self, channel: usize, hz: u32) -> Result<(), &'static str> {
+        // A period needs at least two clock cycles to have a high and a low part.
+        if hz == 0 || hz > self.clock_hz / 2 {
+            return Err("Frequency out of range");
+        }
+
+        self.channel(channel)?.hz = hz;
+        self.apply(channel)
+    }
+
+    fn set_duty_cycle(&mut self, channel: usize, percent: u32) -> Result<(), &'static str> {
+        if percent > 100 {
+            return Err("Duty cycle out of range");
+        }
+
+        self.channel(channel)?.percent = percent;
+        self.apply(channel)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Pwm {
+    pub const COMPATIBLE: &'static str = "BCM PWM";
+
+    /// Create an instance. `oscillator_hz` is the frequency of the crystal oscillator.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide correct MMIO start addresses.
+    pub const unsafe fn new(
+        mmio_start_addr: Address<Virtual>,
+        clock_mmio_start_addr: Address<Virtual>,
+        oscillator_hz: u32,
+    ) -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(PwmInner::new(
+                mmio_start_addr,
+                clock_mmio_start_addr,
+                oscillator_hz,
+            )),
+            stop_registers: Registers::new(mmio_start_addr),
+        }
+    }
+
+    /// Return the channel that drives `pin`, if any.
+    pub fn channel_of(pin: u8) -> Option<usize> {
+        match pin {
+            12 | 18 => Some(1),
+            13 | 19 => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Start generating the signal on `channel`, 1 or 2.
+    pub fn pwm_enable(&self, channel: usize) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.set_enabled(channel, true))
+    }
+
+    /// Stop generating the signal on `channel`. The output stays low.
+    pub fn pwm_disable(&self, channel: usize) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.set_enabled(channel, false))
+    }
+
+    /// Change the frequency of `channel`, keeping its duty cycle.
+    pub fn set_frequency(&self, channel: usize, hz: u32) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.set_frequency(channel, hz))
+    }
+
+    /// Change the share of each period `channel` is high, in percent.
+    pub fn set_duty_cycle(&self, channel: usize, percent: u32) -> Result<(), &'static str> {
+        self.inner
+            .lock(|inner| inner.set_duty_cycle(channel, percent))
+    }
+
+    /// Return whether `channel` is enabled, its frequency and its duty cycle.
+    pub fn state(&self, channel: usize) -> Result<(bool, u32, u32), &'static str> {
+        self.inner.lock(|inner| {
+            let state = inner.channel(channel)?;
+
+            Ok((state.enabled, state.hz, state.percent))
+        })
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Pwm {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    unsafe fn init(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.init())
+    }
+
+    /// Disable both channels. Their outputs stay low.
+    fn emergency_stop(&self) {
+        self.stop_registers.CTL.set(0);
+    }
+}
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Hardware random number generator driver.
+//!
+//! The RNG of the BCM2837. The BCM2711 replaced it with an incompatible block that is not
+//! supported.
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    driver,
+    exception::asynchronous::IRQNumber,
+    memory::{Address, Virtual},
+    rand, synchronization,
+    synchronization::IRQSafeNullLock,
+};
+use tock_registers::{
+    interfaces::{Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::ReadWrite,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of initial words to discard, which are less random.
+const WARMUP_COUNT: u32 = 0x4_0000;
+
+register_bitfields! {
+    u32,
+
+    /// Control
+    RNG_CTRL [
+        /// Generator enable
+        RBGEN OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Status
+    RNG_STATUS [
+        /// On write, number of words to discard before data becomes available
+        WARMUP_COUNT OFFSET(0) NUMBITS(20) [],
+
+        /// Number of words in the FIFO
+        AVAILABLE OFFSET(24) NUMBITS(8) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    pub RegisterBlock {
+        (0x00 => RNG_CTRL: ReadWrite<u32, RNG_CTRL::Register>),
+        (0x04 => RNG_STATUS: ReadWrite<u32, RNG_STATUS::Register>),
+        (0x08 => RNG_DATA: ReadWrite<u32>),
+        (0x0C => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+struct RngInner {
+    registers: Registers,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the hardware RNG.
+pub struct Rng {
+    inner: IRQSafeNullLock<RngInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl RngInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+        }
+    }
+
+    fn init(&mut self) {
+        self.registers
+            .RNG_STATUS
+            .write(RNG_STATUS::WARMUP_COUNT.val(WARMUP_COUNT));
+        self.registers.RNG_CTRL.write(RNG_CTRL::RBGEN::SET);
+    }
+
+    fn read_u32(&self) -> Option<u32> {
+        if self.registers.RNG_STATUS.read(RNG_STATUS::AVAILABLE) == 0 {
+            return None;
+        }
+
+        Some(self.registers.RNG_DATA.get())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Rng {
+    pub const COMPATIBLE: &'static str = "BCM RNG";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(RngInner::new(mmio_start_addr)),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Rng {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    unsafe fn init(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.init());
+
+        Ok(())
+    }
+}
+
+impl rand::interface::EntropySource for Rng {
+    fn name(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    fn read_u32(&self) -> Option<u32> {
+        self.inner.lock(|inner| inner.read_u32())
+    }
+}
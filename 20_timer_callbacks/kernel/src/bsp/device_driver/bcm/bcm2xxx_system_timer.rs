@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! System timer driver.
+//!
+//! The system timer is a free running 64 bit counter at 1 MHz, derived from the crystal
+//! independently of CNTFRQ_EL0. It is only read here, as the reference the architectural counter is
+//! calibrated against. Its compare channels are left to the firmware and the GPU.
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    driver,
+    exception::asynchronous::IRQNumber,
+    memory::{Address, Virtual},
+    synchronization,
+    synchronization::IRQSafeNullLock,
+    time,
+};
+use tock_registers::{interfaces::Readable, register_structs, registers::ReadOnly};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const FREQUENCY: u32 = 1_000_000;
+
+register_structs! {
+    #[allow(non_snake_case)]
+    pub RegisterBlock {
+        (0x00 => _reserved1),
+        (0x04 => CLO: ReadOnly<u32>),
+        (0x08 => CHI: ReadOnly<u32>),
+        (0x0C => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+struct SystemTimerInner {
+    registers: Registers,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the system timer.
+pub struct SystemTimer {
+    inner: IRQSafeNullLock<SystemTimerInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl SystemTimerInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+        }
+    }
+
+    /// Read both halves. CHI is read again until it didn't change around CLO, so that a carry
+    /// between the reads isn't lost.
+    fn counter(&self) -> u64 {
+        loop {
+            let hi = self.registers.CHI.get();
+            let lo = self.registers.CLO.get();
+            if self.registers.CHI.get() == hi {
+                return (hi as u64) << 32 | lo as u64;
+            }
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl SystemTimer {
+    pub const COMPATIBLE: &'static str = "BCM System Timer";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(SystemTimerInner::new(mmio_start_addr)),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for SystemTimer {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+}
+
+impl time::interface::ReferenceClock for SystemTimer {
+    fn name(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    fn frequency(&self) -> u32 {
+        FREQUENCY
+    }
+
+    fn counter(&self) -> u64 {
+        self.inner.lock(|inner| inner.counter())
+    }
+}
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Power management watchdog driver.
+//!
+//! The watchdog resets the SoC once its counter runs down, unless it is restarted or stopped
+//! before. The counter ticks at 65536 Hz and is 20 bits wide, which limits the timeout to 16 s.
+//! Every write to the PM registers must carry the password in its upper byte.
+//!
+//! A reboot is a watchdog reset a few ticks away. The firmware reads the partition to boot from
+//! out of RSTS, and partition 63 tells it to halt instead, which is how the board is halted.
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    driver,
+    exception::asynchronous::IRQNumber,
+    memory::{Address, Virtual},
+    synchronization,
+    synchronization::IRQSafeNullLock,
+};
+use core::time::Duration;
+use tock_registers::{
+    interfaces::{Readable, Writeable},
+    register_structs,
+    registers::ReadWrite,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const PASSWORD: u32 = 0x5A00_0000;
+
+/// Reset configuration field of RSTC.
+const RSTC_WRCFG_MASK: u32 = 0x30;
+const RSTC_WRCFG_FULL_RESET: u32 = 0x20;
+
+/// Written to RSTC to stop the watchdog.
+const RSTC_RESET: u32 = 0x102;
+
+/// The boot partition is spread over the even bits 0 to 10 of RSTS.
+const RSTS_PARTITION_CLEAR: u32 = 0xFFFF_FAAA;
+const PARTITION_HALT: u32 = 63;
+
+/// Watchdog ticks before a requested reset.
+const RESET_TICKS: u32 = 10;
+
+const WDOG_TICKS_PER_SEC: u64 = 1 << 16;
+const WDOG_TIME_MASK: u32 = 0x000F_FFFF;
+
+register_structs! {
+    #[allow(non_snake_case)]
+    pub RegisterBlock {
+        (0x00 => _reserved1),
+        (0x1C => RSTC: ReadWrite<u32>),
+        (0x20 => RSTS: ReadWrite<u32>),
+        (0x24 => WDOG: ReadWrite<u32>),
+        (0x28 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+struct WatchdogInner {
+    registers: Registers,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the power management watchdog.
+pub struct Watchdog {
+    inner: IRQSafeNullLock<WatchdogInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl WatchdogInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+        }
+    }
+
+    fn start(&mut self, timeout: Duration) {
+        let ticks = (timeout.as_micros() as u64 * WDOG_TICKS_PER_SEC / 1_000_000)
+            .min(WDOG_TIME_MASK as u64) as u32;
+        let rstc = self.registers.RSTC.get() & !RSTC_WRCFG_MASK;
+
+        self.registers.WDOG.set(PASSWORD | ticks);
+        self.registers
+            .RSTC
+            .set(PASSWORD | rstc | RSTC_WRCFG_FULL_RESET);
+    }
+
+    fn stop(&mut self) {
+        self.registers.RSTC.set(PASSWORD | RSTC_RESET);
+    }
+
+    fn reset(&mut self, halt: bool) {
+        if halt {
+            let partition = (0..6).fold(0, |v, bit| v | ((PARTITION_HALT >> bit) & 1) << (bit * 2));
+            let rsts = self.registers.RSTS.get() & RSTS_PARTITION_CLEAR;
+            self.registers.RSTS.set(PASSWORD | rsts | partition);
+        }
+
+        let rstc = self.registers.RSTC.get() & !RSTC_WRCFG_MASK;
+        self.registers.WDOG.set(PASSWORD | RESET_TICKS);
+        self.registers
+            .RSTC
+            .set(PASSWORD | rstc | RSTC_WRCFG_FULL_RESET);
+    }
+
+    fn remaining(&self) -> Duration {
+        let ticks = (self.registers.WDOG.get() & WDOG_TIME_MASK) as u64;
+
+        Duration::from_micros(ticks * 1_000_000 / WDOG_TICKS_PER_SEC)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Watchdog {
+    pub const COMPATIBLE: &'static str = "BCM PM Watchdog";
+
+    /// Longest supported timeout.
+    pub const MAX_TIMEOUT: Duration = Duration::from_secs(15);
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(WatchdogInner::new(mmio_start_addr)),
+        }
+    }
+
+    /// Start the watchdog, or restart it if it runs. The board resets after `timeout`, which is
+    /// capped at [`Self::MAX_TIMEOUT`].
+    pub fn start(&self, timeout: Duration) {
+        self.inner
+            .lock(|inner| inner.start(timeout.min(Self::MAX_TIMEOUT)))
+    }
+
+    /// Stop the watchdog.
+    pub fn stop(&self) {
+        self.inner.lock(|inner| inner.stop())
+    }
+
+    /// Reset the board right away. With `halt`, the firmware halts instead of booting again.
+    pub fn reset(&self, halt: bool) {
+        self.inner.lock(|inner| inner.reset(halt))
+    }
+
+    /// Return the time left until the watchdog resets the board, if it runs.
+    pub fn remaining(&self) -> Duration {
+        self.inner.lock(|inner| inner.remaining())
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Watchdog {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+}
@@ -0,0 +1,643 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! CYW43438 Wi-Fi driver.
+//!
+//! The chip sits on the EMMC controller's SDIO bus. Function 1 gives access to the chip's
+//! internal backplane, through a 32 KiB sliding window. Function 2 carries SDPCM frames: control
+//! messages (CDC ioctls), firmware events, and eventually network data.
+//!
+//! The chip runs its own firmware, which must be downloaded into its RAM after every power-up. The
+//! firmware is not part of the kernel and must be handed to [`Cyw43438::load_firmware`], e.g. by
+//! the `wifi load` command, see [`wifi::load_firmware_from()`].
+//!
+//! WL_REG_ON is controlled through the firmware's GPIO expander and is expected to have been
+//! asserted by the boot firmware.
+//!
+//! # Resources
+//!
+//! - Linux `drivers/net/wireless/broadcom/brcm80211/brcmfmac`
+//! - <https://github.com/embassy-rs/embassy/tree/main/cyw43>
+
+use super::Emmc;
+use crate::{
+    driver,
+    exception::asynchronous::IRQNumber,
+    net::{
+        wifi::{self, ChipInfo, ScanResult},
+        MacAddress,
+    },
+    synchronization,
+    synchronization::IRQSafeNullLock,
+    time,
+};
+use alloc::{string::String, vec::Vec};
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const FUNC_BUS: u8 = 0;
+const FUNC_BACKPLANE: u8 = 1;
+const FUNC_WLAN: u8 = 2;
+
+// Card Common Control Registers (function 0).
+const CCCR_IO_ENABLE: u32 = 0x02;
+const CCCR_IO_READY: u32 = 0x03;
+const CCCR_F1_BLOCK_SIZE: u32 = 0x110;
+const CCCR_F2_BLOCK_SIZE: u32 = 0x210;
+
+// Function 1 registers.
+const SB_ADDR_LOW: u32 = 0x1000A;
+const SB_ADDR_MID: u32 = 0x1000B;
+const SB_ADDR_HIGH: u32 = 0x1000C;
+const CHIP_CLOCK_CSR: u32 = 0x1000E;
+
+const CLOCK_ALP_AVAIL_REQ: u8 = 0x08;
+const CLOCK_HT_AVAIL_REQ: u8 = 0x10;
+const CLOCK_ALP_AVAIL: u8 = 0x40;
+const CLOCK_HT_AVAIL: u8 = 0x80;
+
+/// The backplane window is 32 KiB. Accesses through it set this flag for 32 bit width.
+const WINDOW_MASK: u32 = 0x7fff;
+const WINDOW_ACCESS_32: u32 = 0x8000;
+
+// Backplane addresses.
+const CHIPCOMMON_BASE: u32 = 0x1800_0000;
+const SDIO_CORE_BASE: u32 = 0x1800_2000;
+const ARM_CORE_BASE: u32 = 0x1800_3000;
+const SOCSRAM_BASE: u32 = 0x1800_4000;
+const WRAPPER_OFFSET: u32 = 0x10_0000;
+
+const SDIO_INT_STATUS: u32 = SDIO_CORE_BASE + 0x20;
+const SDIO_INT_MASK: u32 = SDIO_CORE_BASE + 0x24;
+const SDIO_INT_FRAME_IND: u32 = 1 << 6;
+
+const SOCSRAM_BANKX_INDEX: u32 = SOCSRAM_BASE + 0x10;
+const SOCSRAM_BANKX_PDA: u32 = SOCSRAM_BASE + 0x44;
+
+// Core wrapper registers.
+const AI_IOCTRL: u32 = 0x408;
+const AI_RESETCTRL: u32 = 0x800;
+const IOCTRL_CLOCK_EN: u32 = 0x1;
+const IOCTRL_FGC: u32 = 0x2;
+const RESETCTRL_RESET: u32 = 0x1;
+
+const CHIP_ID_43430: u16 = 43430;
+const RAM_SIZE: u32 = 0x8_0000;
+
+/// Largest chunk written to the backplane in one CMD53.
+const BACKPLANE_CHUNK: usize = 64;
+
+/// Largest SDPCM frame this driver sends or receives in one CMD53.
+const MAX_FRAME: usize = 512;
+
+// SDPCM.
+const SDPCM_HEADER_LEN: usize = 12;
+const CHANNEL_CONTROL: u8 = 0;
+const CHANNEL_EVENT: u8 = 1;
+const CDC_HEADER_LEN: usize = 16;
+const CDC_FLAG_SET: u32 = 0x2;
+const BDC_HEADER_LEN: usize = 4;
+
+/// Events are Ethernet frames carrying a Broadcom header, followed by the event message.
+const EVENT_MSG_OFFSET: usize = 14 + 10;
+const EVENT_MSG_LEN: usize = 48;
+
+// Ioctls and events.
+const WLC_UP: u32 = 2;
+const WLC_SET_VAR: u32 = 263;
+const EVENT_ESCAN_RESULT: u32 = 69;
+const ESCAN_STATUS_SUCCESS: u32 = 0;
+const ESCAN_STATUS_PARTIAL: u32 = 8;
+
+const IOCTL_TIMEOUT: Duration = Duration::from_secs(1);
+const SCAN_TIMEOUT: Duration = Duration::from_secs(10);
+const CLOCK_TIMEOUT: Duration = Duration::from_millis(500);
+
+struct Cyw43438Inner {
+    sdio: &'static Emmc,
+    window: Option<u32>,
+    chip: Option<ChipInfo>,
+    running: bool,
+    tx_seq: u8,
+    ioctl_id: u16,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the Wi-Fi chip.
+pub struct Cyw43438 {
+    inner: IRQSafeNullLock<Cyw43438Inner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn le16(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buf[offset], buf[offset + 1]])
+}
+
+fn be32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        buf[offset],
+        buf[offset + 1],
+        buf[offset + 2],
+        buf[offset + 3],
+    ])
+}
+
+/// Parse the BSS info records of an escan result event into `results`.
+fn parse_escan_result(data: &[u8], results: &mut Vec<ScanResult>) {
+    // wl_escan_result: buflen, version, sync_id, bss_count, then wl_bss_info records.
+    const ESCAN_HEADER_LEN: usize = 12;
+    const BSS_INFO_MIN_LEN: usize = 80;
+
+    if data.len() < ESCAN_HEADER_LEN {
+        return;
+    }
+
+    let bss_count = le16(data, 10) as usize;
+    let mut bss = &data[ESCAN_HEADER_LEN..];
+
+    for _ in 0..bss_count {
+        if bss.len() < BSS_INFO_MIN_LEN {
+            return;
+        }
+        let len = u32::from_le_bytes([bss[4], bss[5], bss[6], bss[7]]) as usize;
+
+        let bssid = MacAddress::from_slice(&bss[8..14]);
+        let capability = le16(bss, 16);
+        let ssid_len = (bss[18] as usize).min(32);
+        let ssid = String::from_utf8_lossy(&bss[19..19 + ssid_len]).into_owned();
+        let channel = (le16(bss, 72) & 0xff) as u8;
+        let rssi = le16(bss, 78) as i16;
+
+        if !results.iter().any(|r| r.bssid == bssid) {
+            results.push(ScanResult {
+                bssid,
+                ssid,
+                channel,
+                rssi,
+                secure: (capability & 0x10) != 0,
+            });
+        }
+
+        if len == 0 || len > bss.len() {
+            return;
+        }
+        bss = &bss[len..];
+    }
+}
+
+impl Cyw43438Inner {
+    const fn new(sdio: &'static Emmc) -> Self {
+        Self {
+            sdio,
+            window: None,
+            chip: None,
+            running: false,
+            tx_seq: 0,
+            ioctl_id: 0,
+        }
+    }
+
+    fn wait(
+        &self,
+        timeout: Duration,
+        error: &'static str,
+        mut condition: impl FnMut(&Self) -> Result<bool, &'static str>,
+    ) -> Result<(), &'static str> {
+        let start = time::time_manager().uptime();
+
+        while !condition(self)? {
+            if time::time_manager().uptime() - start > timeout {
+                return Err(error);
+            }
+            time::time_manager().spin_for(Duration::from_micros(100));
+        }
+
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Backplane access
+
+    fn set_window(&mut self, addr: u32) -> Result<(), &'static str> {
+        let base = addr & !WINDOW_MASK;
+        if self.window == Some(base) {
+            return Ok(());
+        }
+
+        self.sdio
+            .sdio_write_byte(FUNC_BACKPLANE, SB_ADDR_LOW, (base >> 8) as u8)?;
+        self.sdio
+            .sdio_write_byte(FUNC_BACKPLANE, SB_ADDR_MID, (base >> 16) as u8)?;
+        self.sdio
+            .sdio_write_byte(FUNC_BACKPLANE, SB_ADDR_HIGH, (base >> 24) as u8)?;
+
+        self.window = Some(base);
+        Ok(())
+    }
+
+    fn bp_read32(&mut self, addr: u32) -> Result<u32, &'static str> {
+        self.set_window(addr)?;
+
+        let mut buf = [0; 4];
+        self.sdio.sdio_read(
+            FUNC_BACKPLANE,
+            (addr & WINDOW_MASK) | WINDOW_ACCESS_32,
+            true,
+            &mut buf,
+        )?;
+
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn bp_write32(&mut self, addr: u32, val: u32) -> Result<(), &'static str> {
+        self.set_window(addr)?;
+
+        self.sdio.sdio_write(
+            FUNC_BACKPLANE,
+            (addr & WINDOW_MASK) | WINDOW_ACCESS_32,
+            true,
+            &val.to_le_bytes(),
+        )
+    }
+
+    /// Write a block of data to the backplane, e.g. into the chip's RAM.
+    fn bp_write(&mut self, mut addr: u32, mut data: &[u8]) -> Result<(), &'static str> {
+        while !data.is_empty() {
+            // Chunks must not cross a window boundary.
+            let to_boundary = (WINDOW_MASK + 1 - (addr & WINDOW_MASK)) as usize;
+            let len = data.len().min(BACKPLANE_CHUNK).min(to_boundary);
+
+            self.set_window(addr)?;
+            self.sdio.sdio_write(
+                FUNC_BACKPLANE,
+                (addr & WINDOW_MASK) | WINDOW_ACCESS_32,
+                true,
+                &data[..len],
+            )?;
+
+            addr += len as u32;
+            data = &data[len..];
+        }
+
+        Ok(())
+    }
+
+    fn core_disable(&mut self, core_base: u32) -> Result<(), &'static str> {
+        let wrapper = core_base + WRAPPER_OFFSET;
+
+        if (self.bp_read32(wrapper + AI_RESETCTRL)? & RESETCTRL_RESET) != 0 {
+            return Ok(());
+        }
+
+        self.bp_write32(wrapper + AI_IOCTRL, 0)?;
+        self.bp_read32(wrapper + AI_IOCTRL)?;
+        time::time_manager().spin_for(Duration::from_millis(1));
+
+        self.bp_write32(wrapper + AI_RESETCTRL, RESETCTRL_RESET)?;
+        time::time_manager().spin_for(Duration::from_millis(1));
+
+        Ok(())
+    }
+
+    fn core_reset(&mut self, core_base: u32) -> Result<(), &'static str> {
+        let wrapper = core_base + WRAPPER_OFFSET;
+
+        self.core_disable(core_base)?;
+
+        self.bp_write32(wrapper + AI_IOCTRL, IOCTRL_FGC | IOCTRL_CLOCK_EN)?;
+        self.bp_read32(wrapper + AI_IOCTRL)?;
+        self.bp_write32(wrapper + AI_RESETCTRL, 0)?;
+        time::time_manager().spin_for(Duration::from_millis(1));
+
+        self.bp_write32(wrapper + AI_IOCTRL, IOCTRL_CLOCK_EN)?;
+        self.bp_read32(wrapper + AI_IOCTRL)?;
+        time::time_manager().spin_for(Duration::from_millis(1));
+
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Bring-up
+
+    /// Enumerate the SDIO card, enable the backplane function and identify the chip.
+    fn bring_up(&mut self) -> Result<ChipInfo, &'static str> {
+        if let Some(chip) = self.chip {
+            return Ok(chip);
+        }
+
+        self.sdio.sdio_enumerate()?;
+        self.window = None;
+
+        self.sdio
+            .sdio_write_byte(FUNC_BUS, CCCR_IO_ENABLE, 1 << FUNC_BACKPLANE)?;
+        self.wait(CLOCK_TIMEOUT, "Backplane function not ready", |s| {
+            Ok((s.sdio.sdio_read_byte(FUNC_BUS, CCCR_IO_READY)? & (1 << FUNC_BACKPLANE)) != 0)
+        })?;
+
+        // Block sizes: 64 bytes for the backplane, 512 bytes for WLAN.
+        self.sdio
+            .sdio_write_byte(FUNC_BUS, CCCR_F1_BLOCK_SIZE, 64)?;
+        self.sdio
+            .sdio_write_byte(FUNC_BUS, CCCR_F1_BLOCK_SIZE + 1, 0)?;
+        self.sdio.sdio_write_byte(FUNC_BUS, CCCR_F2_BLOCK_SIZE, 0)?;
+        self.sdio
+            .sdio_write_byte(FUNC_BUS, CCCR_F2_BLOCK_SIZE + 1, 2)?;
+
+        // The backplane needs the ALP clock.
+        self.sdio
+            .sdio_write_byte(FUNC_BACKPLANE, CHIP_CLOCK_CSR, CLOCK_ALP_AVAIL_REQ)?;
+        self.wait(CLOCK_TIMEOUT, "ALP clock not available", |s| {
+            Ok((s.sdio.sdio_read_byte(FUNC_BACKPLANE, CHIP_CLOCK_CSR)? & CLOCK_ALP_AVAIL) != 0)
+        })?;
+
+        let id = self.bp_read32(CHIPCOMMON_BASE)?;
+        let chip = ChipInfo {
+            name: "CYW43438",
+            id: id as u16,
+            revision: ((id >> 16) & 0xf) as u8,
+        };
+        if chip.id != CHIP_ID_43430 {
+            return Err("Unsupported Wi-Fi chip");
+        }
+
+        self.chip = Some(chip);
+        Ok(chip)
+    }
+
+    fn load_firmware(&mut self, firmware: &[u8], nvram: &[u8]) -> Result<(), &'static str> {
+        let nvram_len = nvram.len().next_multiple_of(4);
+        if firmware.len() + nvram_len + 4 > RAM_SIZE as usize {
+            return Err("Firmware too large");
+        }
+
+        self.bring_up()?;
+        self.running = false;
+
+        self.core_disable(ARM_CORE_BASE)?;
+        self.core_reset(SOCSRAM_BASE)?;
+
+        // Disable remapping of SRAM bank 3 (43430 specific).
+        self.bp_write32(SOCSRAM_BANKX_INDEX, 3)?;
+        self.bp_write32(SOCSRAM_BANKX_PDA, 0)?;
+
+        self.bp_write(0, firmware)?;
+
+        // NVRAM goes to the end of RAM, followed by its length in words and the inverted length.
+        let mut padded = Vec::from(nvram);
+        padded.resize(nvram_len, 0);
+        let nvram_addr = RAM_SIZE - 4 - nvram_len as u32;
+        self.bp_write(nvram_addr, &padded)?;
+
+        let words = (nvram_len / 4) as u32;
+        self.bp_write32(RAM_SIZE - 4, (!words << 16) | (words & 0xffff))?;
+
+        // Start the chip's CPU and wait for the firmware to bring up the HT clock.
+        self.core_reset(ARM_CORE_BASE)?;
+        self.sdio
+            .sdio_write_byte(FUNC_BACKPLANE, CHIP_CLOCK_CSR, CLOCK_HT_AVAIL_REQ)?;
+        self.wait(CLOCK_TIMEOUT, "HT clock not available", |s| {
+            Ok((s.sdio.sdio_read_byte(FUNC_BACKPLANE, CHIP_CLOCK_CSR)? & CLOCK_HT_AVAIL) != 0)
+        })?;
+
+        self.bp_write32(SDIO_INT_MASK, SDIO_INT_FRAME_IND)?;
+
+        self.sdio.sdio_write_byte(
+            FUNC_BUS,
+            CCCR_IO_ENABLE,
+            (1 << FUNC_BACKPLANE) | (1 << FUNC_WLAN),
+        )?;
+        self.wait(IOCTL_TIMEOUT, "WLAN function not ready", |s| {
+            Ok((s.sdio.sdio_read_byte(FUNC_BUS, CCCR_IO_READY)? & (1 << FUNC_WLAN)) != 0)
+        })?;
+
+        self.running = true;
+        self.ioctl(WLC_UP, true, &[]).map(|_| ())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // SDPCM
+
+    fn send_frame(&mut self, channel: u8, payload: &[u8]) -> Result<(), &'static str> {
+        let len = SDPCM_HEADER_LEN + payload.len();
+        if len > MAX_FRAME {
+            return Err("Frame too large");
+        }
+
+        let mut frame = Vec::with_capacity(len.next_multiple_of(4));
+        frame.extend_from_slice(&(len as u16).to_le_bytes());
+        frame.extend_from_slice(&(!(len as u16)).to_le_bytes());
+        frame.push(self.tx_seq);
+        frame.push(channel);
+        frame.push(0); // Next length.
+        frame.push(SDPCM_HEADER_LEN as u8);
+        frame.extend_from_slice(&[0; 4]); // Flow control, credit, reserved.
+        frame.extend_from_slice(payload);
+        frame.resize(len.next_multiple_of(4), 0);
+
+        self.tx_seq = self.tx_seq.wrapping_add(1);
+        self.sdio
+            .sdio_write(FUNC_WLAN, WINDOW_ACCESS_32, true, &frame)
+    }
+
+    /// Read the next frame from the chip, if one is pending. Returns channel and payload.
+    fn receive_frame(&mut self) -> Result<Option<(u8, Vec<u8>)>, &'static str> {
+        let status = self.bp_read32(SDIO_INT_STATUS)?;
+        if (status & SDIO_INT_FRAME_IND) == 0 {
+            return Ok(None);
+        }
+        self.bp_write32(SDIO_INT_STATUS, SDIO_INT_FRAME_IND)?;
+
+        let mut header = [0; SDPCM_HEADER_LEN];
+        self.sdio
+            .sdio_read(FUNC_WLAN, WINDOW_ACCESS_32, true, &mut header)?;
+
+        let len = le16(&header, 0) as usize;
+        if len == 0 || le16(&header, 2) != !(len as u16) || len < SDPCM_HEADER_LEN {
+            return Ok(None);
+        }
+        if len > MAX_FRAME {
+            return Err("Received frame too large");
+        }
+
+        let mut frame = Vec::from(header);
+        let rest = len - SDPCM_HEADER_LEN;
+        if rest > 0 {
+            let mut buf = alloc::vec![0; rest.next_multiple_of(4)];
+            self.sdio
+                .sdio_read(FUNC_WLAN, WINDOW_ACCESS_32, true, &mut buf)?;
+            frame.extend_from_slice(&buf[..rest]);
+        }
+
+        let channel = header[5] & 0x0f;
+        let data_offset = (header[7] as usize).min(len);
+
+        Ok(Some((channel, frame[data_offset..].to_vec())))
+    }
+
+    /// Send a CDC ioctl and wait for its completion. Returns the response data.
+    fn ioctl(&mut self, cmd: u32, set: bool, data: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if !self.running {
+            return Err("Firmware not loaded");
+        }
+
+        self.ioctl_id = self.ioctl_id.wrapping_add(1);
+        let id = self.ioctl_id;
+        let flags = ((id as u32) << 16) | if set { CDC_FLAG_SET } else { 0 };
+
+        let mut msg = Vec::with_capacity(CDC_HEADER_LEN + data.len());
+        msg.extend_from_slice(&cmd.to_le_bytes());
+        msg.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        msg.extend_from_slice(&flags.to_le_bytes());
+        msg.extend_from_slice(&0_u32.to_le_bytes());
+        msg.extend_from_slice(data);
+        self.send_frame(CHANNEL_CONTROL, &msg)?;
+
+        let start = time::time_manager().uptime();
+        loop {
+            if let Some((CHANNEL_CONTROL, payload)) = self.receive_frame()? {
+                if payload.len() >= CDC_HEADER_LEN {
+                    let rx_flags =
+                        u32::from_le_bytes([payload[8], payload[9], payload[10], payload[11]]);
+                    let status =
+                        u32::from_le_bytes([payload[12], payload[13], payload[14], payload[15]]);
+
+                    if (rx_flags >> 16) as u16 == id {
+                        if status != 0 {
+                            return Err("Wi-Fi ioctl failed");
+                        }
+                        return Ok(payload[CDC_HEADER_LEN..].to_vec());
+                    }
+                }
+            }
+
+            if time::time_manager().uptime() - start > IOCTL_TIMEOUT {
+                return Err("Wi-Fi ioctl timed out");
+            }
+        }
+    }
+
+    /// Set a firmware variable.
+    fn set_var(&mut self, name: &str, value: &[u8]) -> Result<(), &'static str> {
+        let mut data = Vec::with_capacity(name.len() + 1 + value.len());
+        data.extend_from_slice(name.as_bytes());
+        data.push(0);
+        data.extend_from_slice(value);
+
+        self.ioctl(WLC_SET_VAR, true, &data).map(|_| ())
+    }
+
+    fn scan(&mut self) -> Result<Vec<ScanResult>, &'static str> {
+        const SYNC_ID: u16 = 0x4b48;
+
+        // Enable the escan result event only.
+        let mut event_mask = [0_u8; 16];
+        event_mask[(EVENT_ESCAN_RESULT / 8) as usize] |= 1 << (EVENT_ESCAN_RESULT % 8);
+        self.set_var("event_msgs", &event_mask)?;
+
+        // wl_escan_params: version, action (start), sync id, then wl_scan_params for all SSIDs,
+        // all BSSIDs, any BSS type, active scan and default timing on all channels.
+        let mut params = Vec::new();
+        params.extend_from_slice(&1_u32.to_le_bytes());
+        params.extend_from_slice(&1_u16.to_le_bytes());
+        params.extend_from_slice(&SYNC_ID.to_le_bytes());
+        params.extend_from_slice(&[0; 4 + 32]);
+        params.extend_from_slice(&[0xff; 6]);
+        params.push(2);
+        params.push(0);
+        for _ in 0..4 {
+            params.extend_from_slice(&(-1_i32).to_le_bytes());
+        }
+        params.extend_from_slice(&0_u32.to_le_bytes());
+        params.extend_from_slice(&0_u16.to_le_bytes());
+        self.set_var("escan", &params)?;
+
+        let mut results = Vec::new();
+        let start = time::time_manager().uptime();
+        loop {
+            if let Some((CHANNEL_EVENT, payload)) = self.receive_frame()? {
+                // The BDC header's last byte is the offset of the data in words.
+                let event = match payload.get(3) {
+                    Some(&words) => payload.get(BDC_HEADER_LEN + words as usize * 4..),
+                    None => None,
+                };
+                let msg = match event.and_then(|e| e.get(EVENT_MSG_OFFSET..)) {
+                    Some(msg) if msg.len() >= EVENT_MSG_LEN => msg,
+                    _ => continue,
+                };
+
+                let event_type = be32(msg, 4);
+                let status = be32(msg, 8);
+                if event_type != EVENT_ESCAN_RESULT {
+                    continue;
+                }
+
+                match status {
+                    ESCAN_STATUS_PARTIAL => parse_escan_result(&msg[EVENT_MSG_LEN..], &mut results),
+                    ESCAN_STATUS_SUCCESS => return Ok(results),
+                    _ => return Err("Wi-Fi scan aborted"),
+                }
+            }
+
+            if time::time_manager().uptime() - start > SCAN_TIMEOUT {
+                return Err("Wi-Fi scan timed out");
+            }
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Cyw43438 {
+    pub const COMPATIBLE: &'static str = "CYW43438 Wi-Fi";
+
+    /// Create an instance on top of the given SDIO host.
+    pub const fn new(sdio: &'static Emmc) -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(Cyw43438Inner::new(sdio)),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Cyw43438 {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+}
+
+impl wifi::interface::Wifi for Cyw43438 {
+    fn chip_info(&self) -> Result<ChipInfo, &'static str> {
+        self.inner.lock(|inner| inner.bring_up())
+    }
+
+    fn load_firmware(&self, firmware: &[u8], nvram: &[u8]) -> Result<(), &'static str> {
+        self.inner
+            .lock(|inner| inner.load_firmware(firmware, nvram))
+    }
+
+    fn is_running(&self) -> bool {
+        self.inner.lock(|inner| inner.running)
+    }
+
+    fn scan(&self) -> Result<Vec<ScanResult>, &'static str> {
+        self.inner.lock(|inner| inner.scan())
+    }
+}
@@ -4,6 +4,8 @@
 
 //! Top-level BSP file for the Raspberry Pi 3 and 4.
 
+mod commands;
+
 pub mod cpu;
 pub mod driver;
 pub mod exception;
@@ -0,0 +1,386 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! BSP shell commands.
+
+use crate::{
+    bsp::{self, device_driver},
+    gpio_history, gpio_selftest, info, pattern,
+    shell::{self, register_command},
+    task, time,
+};
+use alloc::boxed::Box;
+use core::{arch::asm, time::Duration};
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn reset_gpio_command(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Reset All GPIO Connections");
+    pattern::stop();
+    reset_gpio();
+
+    Ok(())
+}
+
+fn gpio_on_command(args: &[&str]) -> Result<(), &'static str> {
+    let (pin, force) = parse_pin_command(args)?;
+    gpio_on(pin, force)?;
+    info!("{} on", pin);
+
+    Ok(())
+}
+
+fn gpio_off_command(args: &[&str]) -> Result<(), &'static str> {
+    let (pin, force) = parse_pin_command(args)?;
+    gpio_off(pin, force)?;
+    info!("{} off", pin);
+
+    Ok(())
+}
+
+/// GPIO input, protection, dry run and self-test.
+fn gpio_command(args: &[&str]) -> Result<(), &'static str> {
+    let pin = args.get(2).and_then(|p| p.parse::<u8>().ok());
+    unsafe {
+        let mask = bsp::driver::gpio_protected_pins();
+        match (args.get(1), pin) {
+            (Some(&"input"), Some(p)) => bsp::driver::gpio_as_input(p, args.contains(&"--force")),
+            (Some(&"pull"), Some(p)) => match args.get(3).map(|m| m.parse()) {
+                Some(Ok(mode)) => bsp::driver::gpio_set_pull(p, mode, args.contains(&"--force")),
+                Some(Err(x)) => Err(x),
+                None => Err("Missing pull mode"),
+            },
+            (Some(&"read"), Some(p)) => bsp::driver::gpio_read(p).map(|level| {
+                info!("{} {}", p, if level { "high" } else { "low" });
+            }),
+            (Some(&"watch"), None) => {
+                info!("Watched GPIO pins:");
+                for (p, edge) in bsp::driver::gpio_irqs() {
+                    info!("      {:<2} {}", p, edge);
+                }
+                Ok(())
+            }
+            (Some(&"watch"), Some(p)) => {
+                match args
+                    .get(3)
+                    .map_or(Ok(device_driver::Edge::Both), |e| e.parse())
+                {
+                    Ok(edge) => bsp::driver::gpio_register_irq(p, edge, |pin, level| {
+                        info!("GPIO {} {}", pin, if level { "rising" } else { "falling" });
+                    }),
+                    Err(x) => Err(x),
+                }
+            }
+            (Some(&"unwatch"), Some(p)) => bsp::driver::gpio_unregister_irq(p),
+            (Some(&"history"), _) => {
+                if args.get(2) == Some(&"clear") {
+                    gpio_history::history().clear();
+                } else {
+                    info!("GPIO history:");
+                    gpio_history::history().print();
+                }
+                Ok(())
+            }
+            (Some(&"dryrun"), _) => {
+                match args.get(2) {
+                    Some(&"on") => bsp::driver::gpio_set_dry_run(true),
+                    Some(&"off") => bsp::driver::gpio_set_dry_run(false),
+                    Some(_) => return Err("Expected on or off"),
+                    None => (),
+                }
+                info!(
+                    "GPIO dry run {}",
+                    if bsp::driver::gpio_dry_run() {
+                        "on: register writes are printed, not performed"
+                    } else {
+                        "off"
+                    }
+                );
+                Ok(())
+            }
+            (Some(&"protected"), _) => {
+                info!("Protected GPIO pins:");
+                for p in (0..64).filter(|p| mask & (1 << p) != 0) {
+                    info!("      {}", p);
+                }
+                Ok(())
+            }
+            (Some(&"selftest"), Some(out_pin)) => {
+                let force = args.contains(&"--force");
+                match args.get(3).and_then(|p| p.parse::<u8>().ok()) {
+                    Some(in_pin) => {
+                        info!("GPIO self-test {} -> {}:", out_pin, in_pin);
+                        gpio_selftest::run(out_pin, in_pin, force).map(|report| report.print())
+                    }
+                    None => Err("Missing input pin"),
+                }
+            }
+            (Some(&"protect"), Some(p)) if p < 64 => {
+                bsp::driver::gpio_set_protected_pins(mask | (1 << p));
+                bsp::driver::gpio_store_protected_pins()
+            }
+            (Some(&"unprotect"), Some(p)) if p < 64 => {
+                bsp::driver::gpio_set_protected_pins(mask & !(1 << p));
+                bsp::driver::gpio_store_protected_pins()
+            }
+            _ => {
+                info!("Usage: gpio input <pin> [--force] | pull <pin> <up|down|off> [--force] | read <pin> | watch [<pin> [rising|falling|both]] | unwatch <pin> | history [clear] | dryrun [on|off] | protected | protect <pin> | unprotect <pin> | selftest <out_pin> <in_pin> [--force]");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// UART RX tuning.
+fn uart_command(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        Some("tune") => unsafe { uart_tune(&args[2..]) },
+        _ => {
+            info!("Usage: uart tune [trigger <1/8|1/4|1/2|3/4|7/8>] [mode <irq|timeout>]");
+            Ok(())
+        }
+    }
+}
+
+/// Hardware PWM on pins 12, 13, 18 and 19.
+fn pwm_command(args: &[&str]) -> Result<(), &'static str> {
+    let force = args.contains(&"--force");
+    let pin = args.get(1).and_then(|p| p.parse::<u8>().ok());
+    let hz = args.get(2).and_then(|f| f.parse::<u32>().ok());
+    let percent = args.get(3).and_then(|d| d.parse::<u32>().ok());
+    unsafe {
+        match (pin, args.get(2), hz, percent) {
+            (None, None, ..) => {
+                info!("PWM channels:");
+                for (channel, enabled, hz, percent) in bsp::driver::pwm_state() {
+                    info!(
+                        "      {}  {:<3} {:>8} Hz {:>3} %",
+                        channel,
+                        if enabled { "on" } else { "off" },
+                        hz,
+                        percent
+                    );
+                }
+                Ok(())
+            }
+            (Some(pin), Some(&"off"), ..) => bsp::driver::pwm_disable(pin),
+            (Some(pin), _, Some(hz), Some(percent)) => {
+                bsp::driver::pwm_set_frequency(pin, hz)?;
+                bsp::driver::pwm_set_duty_cycle(pin, percent)?;
+                bsp::driver::pwm_enable(pin, force)
+            }
+            _ => {
+                info!("Usage: pwm [<pin> <hz> <percent> [--force] | <pin> off]");
+                Ok(())
+            }
+        }
+    }
+}
+
+fn dhrystone_command(_args: &[&str]) -> Result<(), &'static str> {
+    let id = task::spawn("dhrystone", run_dhrystone)?;
+    info!("Dhrystone running as task {}", id);
+
+    Ok(())
+}
+
+/// Apply `trigger <level>` and `mode <mode>` pairs to the console's RX tuning, then print it.
+unsafe fn uart_tune(args: &[&str]) -> Result<(), &'static str> {
+    let mut tuning = bsp::driver::uart_rx_tuning();
+
+    for pair in args.chunks(2) {
+        match pair {
+            ["trigger", level] => tuning.trigger = level.parse()?,
+            ["mode", mode] => tuning.mode = mode.parse()?,
+            _ => return Err("Malformed arguments"),
+        }
+    }
+
+    if !args.is_empty() {
+        bsp::driver::uart_set_rx_tuning(tuning);
+        bsp::driver::uart_store_rx_tuning()?;
+    }
+
+    info!("UART RX tuning:");
+    info!(
+        "      Trigger: {} ({} of {} chars)",
+        tuning.trigger,
+        tuning.trigger.chars(),
+        device_driver::RX_FIFO_DEPTH
+    );
+    info!("      Mode:    {}", tuning.mode);
+
+    Ok(())
+}
+
+fn reset_gpio() {
+    for pinNumber in pattern::RING_PINS {
+        if let Err(x) = gpio_off(pinNumber, false) {
+            info!("GPIO {}: {}", pinNumber, x);
+        }
+    }
+}
+
+// Programs
+fn gpio_on(pin: u8, force: bool) -> Result<(), &'static str> {
+    setup_output(pin, force)?;
+    unsafe { bsp::driver::gpio_high(pin, force) }
+}
+fn gpio_off(pin: u8, force: bool) -> Result<(), &'static str> {
+    setup_output(pin, force)?;
+    unsafe { bsp::driver::gpio_low(pin, force) }
+}
+
+fn gpio_on_after(pin: u8, seconds: u64) {
+    time::time_manager().set_timeout_once(
+        Duration::from_secs(seconds),
+        Box::new(move || {
+            let _ = gpio_on(pin, false);
+        }),
+    );
+}
+
+fn gpio_off_after(pin: u8, seconds: u64) {
+    time::time_manager().set_timeout_once(
+        Duration::from_secs(seconds),
+        Box::new(move || {
+            let _ = gpio_off(pin, false);
+        }),
+    );
+}
+
+fn setup_output(pin: u8, force: bool) -> Result<(), &'static str> {
+    unsafe { bsp::driver::gpio_as_output(pin, force) }
+}
+
+/// Parse the pin of a `gpio_on`/`gpio_off` command and whether `--force` was given.
+fn parse_pin_command(args: &[&str]) -> Result<(u8, bool), &'static str> {
+    let mut pin = None;
+    let mut force = false;
+
+    for &arg in args.iter().skip(1) {
+        if arg == "--force" {
+            force = true;
+        } else {
+            pin = Some(arg.parse::<u8>().map_err(|_| "Invalid pin")?);
+        }
+    }
+
+    Ok((pin.ok_or("Missing pin")?, force))
+}
+
+#[repr(C)]
+struct Record<'a> {
+    ptr_comp: Option<&'a mut Record<'a>>,
+    discr: i32,
+    enum_comp: i32,
+    int_comp: i32,
+    string_comp: &'a str,
+}
+
+static STRING1: &str = "DHRYSTONE PROGRAM, 1'ST STRING";
+
+pub fn run_dhrystone() {
+    const ITERATIONS: usize = 10_000;
+
+    // Create two records on stack
+    let mut record1 = Record {
+        ptr_comp: None,
+        discr: 0,
+        enum_comp: 0,
+        int_comp: 0,
+        string_comp: STRING1,
+    };
+
+    let mut record2 = Record {
+        ptr_comp: None,
+        discr: 0,
+        enum_comp: 0,
+        int_comp: 0,
+        string_comp: STRING1,
+    };
+
+    record1.ptr_comp = Some(&mut record2);
+
+    let mut int1 = 0;
+    let mut int2 = 0;
+    let mut int3 = 0;
+
+    let mut char1 = 'A';
+    let mut char2 = 'B';
+
+    info!("Running {} Dhrystone iterations...", ITERATIONS);
+    // Let whatever else is ready run first, so that it doesn't end up in the measurement.
+    task::yield_now();
+
+    let start_cycles = get_cycle_count(); // You'll implement this
+    for _ in 0..ITERATIONS {
+        // Integer ops
+        int1 = 2;
+        int2 = 3;
+        int3 = int1 + int2;
+
+        // Conditional
+        if char1 != char2 {
+            int3 += 1;
+        }
+
+        // Struct manipulation
+        if let Some(ptr) = record1.ptr_comp.as_mut() {
+            ptr.int_comp = int3;
+            ptr.string_comp = "DHRYSTONE STRING";
+        }
+
+        // Simulate some string ops
+        let _ = &record1.string_comp[0..5];
+    }
+    let end_cycles = get_cycle_count();
+
+    let total_cycles = end_cycles.wrapping_sub(start_cycles);
+    let cycles_per_iter = total_cycles as f64 / ITERATIONS as f64;
+
+    info!("Dhrystone done.");
+    info!("Total cycles: {}", total_cycles);
+    info!("Cycles per iteration: {:.2}", cycles_per_iter);
+}
+
+fn get_cycle_count() -> u64 {
+    let value: u64;
+    unsafe {
+        asm!(
+            "mrs {value}, cntvct_el0",
+            value = out(reg) value
+        );
+    }
+    value
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Register the BSP's commands.
+pub fn register() -> Result<(), &'static str> {
+    let commands: &[(&'static str, &'static str, shell::Handler)] = &[
+        ("reset_gpio", "Turn the LED ring off", reset_gpio_command),
+        ("gpio_on", "Drive a pin high", gpio_on_command),
+        ("gpio_off", "Drive a pin low", gpio_off_command),
+        (
+            "gpio",
+            "GPIO input, pulls, edges, protection and self-test",
+            gpio_command,
+        ),
+        ("uart", "Tune the console UART", uart_command),
+        ("pwm", "Drive a pin with hardware PWM", pwm_command),
+        ("test", "Run the Dhrystone benchmark", dhrystone_command),
+    ];
+
+    for (name, help, handler) in commands {
+        register_command(name, help, *handler)?;
+    }
+
+    Ok(())
+}
@@ -12,3 +12,6 @@
 #[no_mangle]
 #[link_section = ".text._start_arguments"]
 pub static BOOT_CORE_ID: u64 = 0;
+
+/// Number of cores.
+pub const NUM_CORES: usize = 4;
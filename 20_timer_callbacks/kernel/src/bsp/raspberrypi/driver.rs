@@ -5,24 +5,98 @@
 //! BSP driver support.
 
 use super::{exception, memory::map::mmio};
+#[cfg(feature = "bsp_rpi3")]
+use crate::rand;
+#[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+use crate::usb;
+#[cfg(feature = "bsp_rpi4")]
+use crate::warn;
 use crate::{
+    block, bluetooth,
     bsp::device_driver,
-    console, driver as generic_driver,
+    clocking, config, console, dma, driver as generic_driver,
     exception::{self as generic_exception},
-    memory,
+    gpio_history, memory,
     memory::mmu::MMIODescriptor,
+    net, shutdown, subsys, time, trace,
 };
+use alloc::{format, string::String, vec::Vec};
 use core::{
     mem::MaybeUninit,
     sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
 };
 
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Config store key of the protected GPIO pins.
+const GPIO_PROTECTED_KEY: &str = "gpio.protected";
+
+/// Config store keys of the console's RX tuning.
+const UART_RX_TRIGGER_KEY: &str = "uart.rx_trigger";
+const UART_RX_MODE_KEY: &str = "uart.rx_mode";
+
+/// Role of the mini UART. With the `mini_uart_console` feature it replaces the PL011 as the
+/// console, and Bluetooth has no transport.
+#[cfg(not(feature = "mini_uart_console"))]
+const MINI_UART_ROLE: device_driver::MiniUartRole = device_driver::MiniUartRole::Bluetooth;
+#[cfg(feature = "mini_uart_console")]
+const MINI_UART_ROLE: device_driver::MiniUartRole = device_driver::MiniUartRole::Console;
+
+/// Frequency of the crystal oscillator that clocks PWM.
+#[cfg(feature = "bsp_rpi3")]
+const OSCILLATOR_HZ: u32 = 19_200_000;
+#[cfg(feature = "bsp_rpi4")]
+const OSCILLATOR_HZ: u32 = 54_000_000;
+
+/// The firmware's power domain of the SD card.
+const POWER_DEVICE_SD_CARD: u32 = 0;
+
+/// The firmware's power domain of the USB controller.
+#[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+const POWER_DEVICE_USB: u32 = 3;
+
+/// The firmware's IDs of the clocks that can be changed.
+const CLOCK_ID_ARM: u32 = 3;
+const CLOCK_ID_CORE: u32 = 4;
+#[cfg(feature = "bsp_rpi4")]
+const CLOCK_ID_EMMC2: u32 = 12;
+
+/// The blocks of the SD card that hold the config store. They lie between the partition table and
+/// the first partition, which starts at block 2048 or later on common card images.
+#[cfg(feature = "bsp_rpi4")]
+const SD_CONFIG_FIRST_LBA: u64 = 1024;
+#[cfg(feature = "bsp_rpi4")]
+const SD_CONFIG_BLOCKS: u64 = 16;
+
 //--------------------------------------------------------------------------------------------------
 // Global instances
 //--------------------------------------------------------------------------------------------------
 
 static mut PL011_UART: MaybeUninit<device_driver::PL011Uart> = MaybeUninit::uninit();
 static mut GPIO: MaybeUninit<device_driver::GPIO> = MaybeUninit::uninit();
+static mut EMMC: MaybeUninit<device_driver::Emmc> = MaybeUninit::uninit();
+#[cfg(feature = "bsp_rpi4")]
+static mut SD_CARD: MaybeUninit<device_driver::Emmc> = MaybeUninit::uninit();
+#[cfg(feature = "bsp_rpi4")]
+static mut SD_CONFIG: MaybeUninit<config::BlockStorage> = MaybeUninit::uninit();
+#[cfg(feature = "bsp_rpi4")]
+static mut GENET: MaybeUninit<device_driver::Genet> = MaybeUninit::uninit();
+static mut WIFI: MaybeUninit<device_driver::Cyw43438> = MaybeUninit::uninit();
+static mut MINI_UART: MaybeUninit<device_driver::MiniUart> = MaybeUninit::uninit();
+static mut MAILBOX: MaybeUninit<device_driver::Mailbox> = MaybeUninit::uninit();
+static mut WATCHDOG: MaybeUninit<device_driver::Watchdog> = MaybeUninit::uninit();
+static mut DMA: MaybeUninit<device_driver::Dma> = MaybeUninit::uninit();
+static mut SYSTEM_TIMER: MaybeUninit<device_driver::SystemTimer> = MaybeUninit::uninit();
+static mut PWM: MaybeUninit<device_driver::Pwm> = MaybeUninit::uninit();
+
+#[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+static mut USB: MaybeUninit<device_driver::DwcOtg> = MaybeUninit::uninit();
+
+#[cfg(feature = "bsp_rpi3")]
+static mut RNG: MaybeUninit<device_driver::Rng> = MaybeUninit::uninit();
 
 #[cfg(feature = "bsp_rpi3")]
 static mut INTERRUPT_CONTROLLER: MaybeUninit<device_driver::InterruptController> =
@@ -48,7 +122,35 @@ unsafe fn instantiate_uart() -> Result<(), &'static str> {
 
 /// This must be called only after successful init of the UART driver.
 unsafe fn post_init_uart() -> Result<(), &'static str> {
+    // The commands are registered here either way, but the console may be the mini UART.
+    if MINI_UART_ROLE == device_driver::MiniUartRole::Console {
+        return super::commands::register();
+    }
+
     console::register_console(PL011_UART.assume_init_ref());
+    PL011_UART
+        .assume_init_ref()
+        .attach_shell(PL011_UART.assume_init_ref());
+    super::commands::register()?;
+
+    // Re-initializing flushes the TX FIFO and reprograms baud rate, format and RX IRQs.
+    subsys::register(
+        "console",
+        || Ok(()),
+        || generic_driver::interface::DeviceDriver::init(PL011_UART.assume_init_ref()),
+    )?;
+    shutdown::register("console", shutdown::Stage::Console, || {
+        console::shutdown();
+        Ok(())
+    })?;
+    subsys::register(
+        "shell",
+        || {
+            PL011_UART.assume_init_ref().clear_command();
+            Ok(())
+        },
+        || Ok(()),
+    )?;
 
     Ok(())
 }
@@ -66,7 +168,297 @@ unsafe fn instantiate_gpio() -> Result<(), &'static str> {
 
 /// This must be called only after successful init of the GPIO driver.
 unsafe fn post_init_gpio() -> Result<(), &'static str> {
-    GPIO.assume_init_ref().map_pl011_uart();
+    match MINI_UART_ROLE {
+        device_driver::MiniUartRole::Console => GPIO.assume_init_ref().map_mini_uart_console(),
+        device_driver::MiniUartRole::Bluetooth => GPIO.assume_init_ref().map_pl011_uart(),
+    }
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+unsafe fn instantiate_emmc() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::EMMC_START, mmio::EMMC_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::Emmc::COMPATIBLE, &mmio_descriptor)?;
+
+    EMMC.write(device_driver::Emmc::new(
+        virt_addr,
+        device_driver::Emmc::COMPATIBLE,
+    ));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the EMMC and GPIO drivers.
+///
+/// On the Raspberry Pi 3, the SD card slot is on the SDHOST controller, which has no driver, so the
+/// card is named as missing.
+unsafe fn post_init_emmc() -> Result<(), &'static str> {
+    GPIO.assume_init_ref().map_sdio_wifi();
+    #[cfg(feature = "bsp_rpi3")]
+    block::register_missing(
+        device_driver::Emmc::SD_CARD_NAME,
+        "SD card unreachable, its slot is on the SDHOST controller, which has no driver",
+    );
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+#[cfg(feature = "bsp_rpi4")]
+unsafe fn instantiate_sd_card() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::EMMC2_START, mmio::EMMC2_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::Emmc::COMPATIBLE_EMMC2, &mmio_descriptor)?;
+
+    SD_CARD.write(device_driver::Emmc::new(
+        virt_addr,
+        device_driver::Emmc::COMPATIBLE_EMMC2,
+    ));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the EMMC2 and mailbox drivers.
+///
+/// If a card answers, the config store is kept on it. Without one, settings stay in RAM.
+#[cfg(feature = "bsp_rpi4")]
+unsafe fn post_init_sd_card() -> Result<(), &'static str> {
+    use block::interface::BlockDevice;
+
+    let sd_card = SD_CARD.assume_init_ref();
+
+    sd_card.set_base_clock(MAILBOX.assume_init_ref().clock_rate(CLOCK_ID_EMMC2)?);
+    block::register_device(sd_card)?;
+
+    if sd_card.block_count() < SD_CONFIG_FIRST_LBA + SD_CONFIG_BLOCKS {
+        warn!("No SD card, settings are not persistent");
+        return Ok(());
+    }
+    SD_CONFIG.write(config::BlockStorage::new(
+        sd_card,
+        SD_CONFIG_FIRST_LBA,
+        SD_CONFIG_BLOCKS,
+    ));
+    config::register_storage(SD_CONFIG.assume_init_ref());
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+#[cfg(feature = "bsp_rpi4")]
+unsafe fn instantiate_genet() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::GENET_START, mmio::GENET_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::Genet::COMPATIBLE, &mmio_descriptor)?;
+
+    GENET.write(device_driver::Genet::new(virt_addr));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the GENET and mailbox drivers.
+///
+/// The interface is attached unconfigured, its address comes from the config store later. A
+/// controller that doesn't come up, e.g. in an emulator, only warns.
+#[cfg(feature = "bsp_rpi4")]
+unsafe fn post_init_genet() -> Result<(), &'static str> {
+    let genet = GENET.assume_init_ref();
+    let mac = MAILBOX.assume_init_ref().board_mac_address()?;
+
+    if let Err(x) = genet.start(mac) {
+        warn!("Ethernet not started: {}", x);
+        return Ok(());
+    }
+    net::net_stack().add_interface(
+        genet,
+        net::Ipv4Config {
+            address: net::Ipv4Address::UNSPECIFIED,
+            netmask: net::Ipv4Address::UNSPECIFIED,
+            gateway: None,
+        },
+    );
+
+    Ok(())
+}
+
+/// This must be called only after successful instantiation of the EMMC driver.
+unsafe fn instantiate_wifi() -> Result<(), &'static str> {
+    WIFI.write(device_driver::Cyw43438::new(EMMC.assume_init_ref()));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the Wi-Fi driver.
+unsafe fn post_init_wifi() -> Result<(), &'static str> {
+    net::wifi::register_wifi(WIFI.assume_init_ref());
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+unsafe fn instantiate_mini_uart() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::AUX_START, mmio::AUX_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::MiniUart::COMPATIBLE, &mmio_descriptor)?;
+
+    MINI_UART.write(device_driver::MiniUart::new(virt_addr, MINI_UART_ROLE));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the mini UART and GPIO drivers.
+unsafe fn post_init_mini_uart() -> Result<(), &'static str> {
+    let mini_uart = MINI_UART.assume_init_ref();
+
+    if mini_uart.role() == device_driver::MiniUartRole::Bluetooth {
+        GPIO.assume_init_ref().map_mini_uart_bt();
+        bluetooth::register_transport(mini_uart);
+
+        return Ok(());
+    }
+
+    console::register_console(mini_uart);
+    mini_uart.attach_shell(mini_uart)?;
+
+    subsys::register(
+        "console",
+        || Ok(()),
+        || generic_driver::interface::DeviceDriver::init(MINI_UART.assume_init_ref()),
+    )?;
+    shutdown::register("console", shutdown::Stage::Console, || {
+        console::shutdown();
+        Ok(())
+    })?;
+    subsys::register(
+        "shell",
+        || {
+            MINI_UART.assume_init_ref().clear_command();
+            Ok(())
+        },
+        || Ok(()),
+    )?;
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+unsafe fn instantiate_mailbox() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::MAILBOX_START, mmio::MAILBOX_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::Mailbox::COMPATIBLE, &mmio_descriptor)?;
+
+    MAILBOX.write(device_driver::Mailbox::new(virt_addr));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+unsafe fn instantiate_watchdog() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::PM_START, mmio::PM_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::Watchdog::COMPATIBLE, &mmio_descriptor)?;
+
+    WATCHDOG.write(device_driver::Watchdog::new(virt_addr));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+unsafe fn instantiate_system_timer() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::SYSTEM_TIMER_START, mmio::SYSTEM_TIMER_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::SystemTimer::COMPATIBLE, &mmio_descriptor)?;
+
+    SYSTEM_TIMER.write(device_driver::SystemTimer::new(virt_addr));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the system timer driver.
+unsafe fn post_init_system_timer() -> Result<(), &'static str> {
+    time::register_reference_clock(SYSTEM_TIMER.assume_init_ref());
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+unsafe fn instantiate_dma() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::DMA_START, mmio::DMA_SIZE);
+    let virt_addr = memory::mmu::kernel_map_mmio(device_driver::Dma::COMPATIBLE, &mmio_descriptor)?;
+
+    DMA.write(device_driver::Dma::new(virt_addr));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the DMA driver.
+unsafe fn post_init_dma() -> Result<(), &'static str> {
+    dma::register_engine(DMA.assume_init_ref());
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+unsafe fn instantiate_pwm() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::PWM_START, mmio::PWM_SIZE);
+    let virt_addr = memory::mmu::kernel_map_mmio(device_driver::Pwm::COMPATIBLE, &mmio_descriptor)?;
+
+    let clock_mmio_descriptor = MMIODescriptor::new(mmio::CM_PWM_START, mmio::CM_PWM_SIZE);
+    let clock_virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::Pwm::COMPATIBLE, &clock_mmio_descriptor)?;
+
+    PWM.write(device_driver::Pwm::new(
+        virt_addr,
+        clock_virt_addr,
+        OSCILLATOR_HZ,
+    ));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+#[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+unsafe fn instantiate_usb() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::USB_START, mmio::USB_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::DwcOtg::COMPATIBLE, &mmio_descriptor)?;
+
+    USB.write(device_driver::DwcOtg::new(virt_addr));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the USB and mailbox drivers.
+///
+/// The controller's power domain is off after boot. The driver touches the registers only once a
+/// gadget or host mode is started.
+#[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+unsafe fn post_init_usb() -> Result<(), &'static str> {
+    MAILBOX
+        .assume_init_ref()
+        .set_power_state(POWER_DEVICE_USB, true)?;
+    usb::register_controller(USB.assume_init_ref());
+    usb::register_host_controller(USB.assume_init_ref());
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+#[cfg(feature = "bsp_rpi3")]
+unsafe fn instantiate_rng() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::RNG_START, mmio::RNG_SIZE);
+    let virt_addr = memory::mmu::kernel_map_mmio(device_driver::Rng::COMPATIBLE, &mmio_descriptor)?;
+
+    RNG.write(device_driver::Rng::new(virt_addr));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the RNG driver.
+#[cfg(feature = "bsp_rpi3")]
+unsafe fn post_init_rng() -> Result<(), &'static str> {
+    rand::register_source(RNG.assume_init_ref());
+
     Ok(())
 }
 
@@ -123,8 +515,9 @@ unsafe fn driver_uart() -> Result<(), &'static str> {
         PL011_UART.assume_init_ref(),
         Some(post_init_uart),
         Some(exception::asynchronous::irq_map::PL011_UART),
+        &[device_driver::GPIO::COMPATIBLE],
     );
-    generic_driver::driver_manager().register_driver(uart_descriptor);
+    generic_driver::driver_manager().register_driver(uart_descriptor)?;
 
     Ok(())
 }
@@ -136,60 +529,765 @@ unsafe fn driver_gpio() -> Result<(), &'static str> {
     let gpio_descriptor = generic_driver::DeviceDriverDescriptor::new(
         GPIO.assume_init_ref(),
         Some(post_init_gpio),
+        Some(exception::asynchronous::irq_map::GPIO),
+        &[],
+    );
+    generic_driver::driver_manager().register_driver(gpio_descriptor)?;
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_emmc() -> Result<(), &'static str> {
+    instantiate_emmc()?;
+
+    let emmc_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        EMMC.assume_init_ref(),
+        Some(post_init_emmc),
         None,
+        &[device_driver::GPIO::COMPATIBLE],
     );
-    generic_driver::driver_manager().register_driver(gpio_descriptor);
+    generic_driver::driver_manager().register_driver(emmc_descriptor)?;
 
     Ok(())
 }
 
 /// Function needs to ensure that driver registration happens only after correct instantiation.
-unsafe fn driver_interrupt_controller() -> Result<(), &'static str> {
-    instantiate_interrupt_controller()?;
+#[cfg(feature = "bsp_rpi4")]
+unsafe fn driver_sd_card() -> Result<(), &'static str> {
+    instantiate_sd_card()?;
 
-    let interrupt_controller_descriptor = generic_driver::DeviceDriverDescriptor::new(
-        INTERRUPT_CONTROLLER.assume_init_ref(),
-        Some(post_init_interrupt_controller),
+    let sd_card_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        SD_CARD.assume_init_ref(),
+        Some(post_init_sd_card),
         None,
+        &[device_driver::Mailbox::COMPATIBLE],
     );
-    generic_driver::driver_manager().register_driver(interrupt_controller_descriptor);
+    generic_driver::driver_manager().register_driver(sd_card_descriptor)?;
 
     Ok(())
 }
 
-//--------------------------------------------------------------------------------------------------
-// Public Code
-//--------------------------------------------------------------------------------------------------
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+#[cfg(feature = "bsp_rpi4")]
+unsafe fn driver_genet() -> Result<(), &'static str> {
+    instantiate_genet()?;
 
-/// Initialize the driver subsystem.
-///
-/// # Safety
-///
-/// See child function calls.
-pub unsafe fn init() -> Result<(), &'static str> {
-    static INIT_DONE: AtomicBool = AtomicBool::new(false);
-    if INIT_DONE.load(Ordering::Relaxed) {
-        return Err("Init already done");
-    }
+    let genet_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        GENET.assume_init_ref(),
+        Some(post_init_genet),
+        None,
+        &[device_driver::Mailbox::COMPATIBLE],
+    );
+    generic_driver::driver_manager().register_driver(genet_descriptor)?;
 
-    driver_uart()?;
-    driver_gpio()?;
-    driver_interrupt_controller()?;
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_wifi() -> Result<(), &'static str> {
+    instantiate_wifi()?;
+
+    let wifi_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        WIFI.assume_init_ref(),
+        Some(post_init_wifi),
+        None,
+        &[device_driver::Emmc::COMPATIBLE],
+    );
+    generic_driver::driver_manager().register_driver(wifi_descriptor)?;
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_mini_uart() -> Result<(), &'static str> {
+    instantiate_mini_uart()?;
+
+    let irq_number = match MINI_UART_ROLE {
+        device_driver::MiniUartRole::Console => Some(exception::asynchronous::irq_map::AUX),
+        device_driver::MiniUartRole::Bluetooth => None,
+    };
+    let mini_uart_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        MINI_UART.assume_init_ref(),
+        Some(post_init_mini_uart),
+        irq_number,
+        &[device_driver::GPIO::COMPATIBLE],
+    );
+    generic_driver::driver_manager().register_driver(mini_uart_descriptor)?;
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_mailbox() -> Result<(), &'static str> {
+    instantiate_mailbox()?;
+
+    let mailbox_descriptor =
+        generic_driver::DeviceDriverDescriptor::new(MAILBOX.assume_init_ref(), None, None, &[]);
+    generic_driver::driver_manager().register_driver(mailbox_descriptor)?;
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_watchdog() -> Result<(), &'static str> {
+    instantiate_watchdog()?;
+
+    let watchdog_descriptor =
+        generic_driver::DeviceDriverDescriptor::new(WATCHDOG.assume_init_ref(), None, None, &[]);
+    generic_driver::driver_manager().register_driver(watchdog_descriptor)?;
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_system_timer() -> Result<(), &'static str> {
+    instantiate_system_timer()?;
+
+    let system_timer_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        SYSTEM_TIMER.assume_init_ref(),
+        Some(post_init_system_timer),
+        None,
+        &[],
+    );
+    generic_driver::driver_manager().register_driver(system_timer_descriptor)?;
 
-    INIT_DONE.store(true, Ordering::Relaxed);
     Ok(())
 }
 
-pub unsafe fn gpio_as_output(pin: u8) {
-    GPIO.assume_init_ref().set_pin_as_output(pin);
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_dma() -> Result<(), &'static str> {
+    instantiate_dma()?;
+
+    let dma_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        DMA.assume_init_ref(),
+        Some(post_init_dma),
+        Some(exception::asynchronous::irq_map::DMA),
+        &[],
+    );
+    generic_driver::driver_manager().register_driver(dma_descriptor)?;
+
+    Ok(())
 }
 
-pub unsafe fn gpio_high(pin: u8) {
-    GPIO.assume_init_ref().set_gpio_high(pin);
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_pwm() -> Result<(), &'static str> {
+    instantiate_pwm()?;
+
+    let pwm_descriptor =
+        generic_driver::DeviceDriverDescriptor::new(PWM.assume_init_ref(), None, None, &[]);
+    generic_driver::driver_manager().register_driver(pwm_descriptor)?;
+
+    Ok(())
 }
 
-pub unsafe fn gpio_low(pin: u8) {
-    GPIO.assume_init_ref().set_gpio_low(pin);
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+#[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+unsafe fn driver_usb() -> Result<(), &'static str> {
+    instantiate_usb()?;
+
+    let usb_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        USB.assume_init_ref(),
+        Some(post_init_usb),
+        Some(exception::asynchronous::irq_map::USB),
+        &[device_driver::Mailbox::COMPATIBLE],
+    );
+    generic_driver::driver_manager().register_driver(usb_descriptor)?;
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+///
+/// The BCM2711 has no supported RNG. The entropy pool then runs on timing jitter alone.
+#[cfg(feature = "bsp_rpi3")]
+unsafe fn driver_rng() -> Result<(), &'static str> {
+    instantiate_rng()?;
+
+    let rng_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        RNG.assume_init_ref(),
+        Some(post_init_rng),
+        None,
+        &[],
+    );
+    generic_driver::driver_manager().register_driver(rng_descriptor)?;
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_interrupt_controller() -> Result<(), &'static str> {
+    instantiate_interrupt_controller()?;
+
+    let interrupt_controller_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        INTERRUPT_CONTROLLER.assume_init_ref(),
+        Some(post_init_interrupt_controller),
+        None,
+        &[],
+    );
+    generic_driver::driver_manager().register_driver(interrupt_controller_descriptor)?;
+
+    Ok(())
+}
+
+/// The firmware's ID of `clock`.
+fn clock_id(clock: clocking::Clock) -> u32 {
+    match clock {
+        clocking::Clock::Arm => CLOCK_ID_ARM,
+        clocking::Clock::Core => CLOCK_ID_CORE,
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Initialize the driver subsystem.
+///
+/// # Safety
+///
+/// See child function calls.
+pub unsafe fn init() -> Result<(), &'static str> {
+    static INIT_DONE: AtomicBool = AtomicBool::new(false);
+    if INIT_DONE.load(Ordering::Relaxed) {
+        return Err("Init already done");
+    }
+
+    driver_uart()?;
+    driver_gpio()?;
+    driver_emmc()?;
+    driver_wifi()?;
+    driver_mini_uart()?;
+    driver_mailbox()?;
+    #[cfg(feature = "bsp_rpi4")]
+    driver_sd_card()?;
+    #[cfg(feature = "bsp_rpi4")]
+    driver_genet()?;
+    driver_watchdog()?;
+    driver_system_timer()?;
+    driver_dma()?;
+    driver_pwm()?;
+    #[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+    driver_usb()?;
+    #[cfg(feature = "bsp_rpi3")]
+    driver_rng()?;
+    driver_interrupt_controller()?;
+
+    INIT_DONE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Bring up the PL011 UART as a console for a panic that happened before the console was
+/// registered.
+///
+/// # Safety
+///
+/// - Only for the panic handler. The UART and GPIO drivers are instantiated anew, regardless of
+///   their state, and the UART's IRQ is not enabled.
+pub unsafe fn panic_console() -> Result<console::Output, &'static str> {
+    instantiate_gpio()?;
+    GPIO.assume_init_ref().map_pl011_uart();
+
+    instantiate_uart()?;
+    generic_driver::interface::DeviceDriver::init(PL011_UART.assume_init_ref())?;
+
+    Ok(PL011_UART.assume_init_ref())
+}
+
+/// Return the board's serial number, as reported by the firmware.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the mailbox driver, and not while it runs.
+pub unsafe fn board_serial() -> Result<u64, &'static str> {
+    MAILBOX.assume_init_ref().board_serial()
+}
+
+/// Return the firmware's revision.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the mailbox driver, and not while it runs.
+pub unsafe fn firmware_revision() -> Result<u32, &'static str> {
+    MAILBOX.assume_init_ref().firmware_revision()
+}
+
+/// Return whether the firmware reports the SD card as present and powered.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the mailbox driver, and not while it runs.
+pub unsafe fn sd_card_powered() -> Result<bool, &'static str> {
+    let (exists, on) = MAILBOX
+        .assume_init_ref()
+        .power_state(POWER_DEVICE_SD_CARD)?;
+
+    Ok(exists && on)
+}
+
+/// Return the rate of `clock` in Hz.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the mailbox driver, and not while it runs.
+pub unsafe fn clock_rate(clock: clocking::Clock) -> Result<u32, &'static str> {
+    MAILBOX.assume_init_ref().clock_rate(clock_id(clock))
+}
+
+/// Return the lowest and the highest rate the firmware allows for `clock`, in Hz.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the mailbox driver, and not while it runs.
+pub unsafe fn clock_range(clock: clocking::Clock) -> Result<(u32, u32), &'static str> {
+    MAILBOX.assume_init_ref().clock_range(clock_id(clock))
+}
+
+/// Set `clock` to `hz` and return the rate the firmware chose.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the mailbox driver, and not while it runs.
+pub unsafe fn set_clock_rate(clock: clocking::Clock, hz: u32) -> Result<u32, &'static str> {
+    MAILBOX
+        .assume_init_ref()
+        .set_clock_rate(clock_id(clock), hz)
+}
+
+/// Return the SoC temperature in thousandths of a degree Celsius.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the mailbox driver, and not while it runs.
+pub unsafe fn soc_temperature() -> Result<u32, &'static str> {
+    MAILBOX.assume_init_ref().temperature()
+}
+
+/// Return the firmware's throttling flags.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the mailbox driver, and not while it runs.
+pub unsafe fn throttle_flags() -> Result<u32, &'static str> {
+    MAILBOX.assume_init_ref().throttled()
+}
+
+/// Check the console UART by sending a pattern to itself in loopback mode.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the PL011 UART driver, and not while it runs.
+pub unsafe fn uart_loopback_test() -> Result<(), &'static str> {
+    if MINI_UART_ROLE == device_driver::MiniUartRole::Console {
+        return Err("PL011 is in use by Bluetooth");
+    }
+
+    PL011_UART.assume_init_ref().loopback_test()
+}
+
+/// Start the hardware watchdog, or restart it if it runs. The board resets after `timeout`.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the watchdog driver, and not while it runs.
+pub unsafe fn watchdog_start(timeout: Duration) {
+    WATCHDOG.assume_init_ref().start(timeout)
+}
+
+/// Stop the hardware watchdog.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the watchdog driver, and not while it runs.
+pub unsafe fn watchdog_stop() {
+    WATCHDOG.assume_init_ref().stop()
+}
+
+/// Return the time left until the hardware watchdog resets the board.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the watchdog driver, and not while it runs.
+pub unsafe fn watchdog_remaining() -> Duration {
+    WATCHDOG.assume_init_ref().remaining()
+}
+
+/// Reset the board through the power management block. With `halt`, the firmware halts instead of
+/// booting again. The reset follows within microseconds.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the watchdog driver, and not while it runs.
+pub unsafe fn system_reset(halt: bool) {
+    WATCHDOG.assume_init_ref().reset(halt)
+}
+
+/// Make `pin` an output. Protected pins are refused unless `force` is set.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_as_output(pin: u8, force: bool) -> Result<(), &'static str> {
+    GPIO.assume_init_ref().set_pin_as_output(pin, force)?;
+    if !gpio_dry_run() {
+        gpio_history::record(pin, gpio_history::Event::Output, force);
+    }
+
+    Ok(())
+}
+
+/// Make `pin` an input. Protected pins are refused unless `force` is set.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_as_input(pin: u8, force: bool) -> Result<(), &'static str> {
+    GPIO.assume_init_ref().set_pin_as_input(pin, force)?;
+    if !gpio_dry_run() {
+        gpio_history::record(pin, gpio_history::Event::Input, force);
+    }
+
+    Ok(())
+}
+
+/// Set the pull resistor of `pin`. Protected pins are refused unless `force` is set.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_set_pull(
+    pin: u8,
+    mode: device_driver::PullMode,
+    force: bool,
+) -> Result<(), &'static str> {
+    GPIO.assume_init_ref().set_pull(pin, mode, force)?;
+    if gpio_dry_run() {
+        return Ok(());
+    }
+
+    let event = match mode {
+        device_driver::PullMode::Off => gpio_history::Event::PullOff,
+        device_driver::PullMode::Down => gpio_history::Event::PullDown,
+        device_driver::PullMode::Up => gpio_history::Event::PullUp,
+    };
+    gpio_history::record(pin, event, force);
+
+    Ok(())
+}
+
+/// Return the level of `pin`.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_read(pin: u8) -> Result<bool, &'static str> {
+    let level = GPIO.assume_init_ref().read_pin(pin)?;
+    gpio_history::observe(pin, level);
+
+    Ok(level)
+}
+
+/// Drive `pin` high. Protected pins are refused unless `force` is set.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_high(pin: u8, force: bool) -> Result<(), &'static str> {
+    GPIO.assume_init_ref().set_gpio_high(pin, force)?;
+    if gpio_dry_run() {
+        return Ok(());
+    }
+    trace::record("gpio", "high", pin as u64);
+    gpio_history::record(pin, gpio_history::Event::High, force);
+
+    Ok(())
+}
+
+/// Drive `pin` low. Protected pins are refused unless `force` is set.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_low(pin: u8, force: bool) -> Result<(), &'static str> {
+    GPIO.assume_init_ref().set_gpio_low(pin, force)?;
+    if gpio_dry_run() {
+        return Ok(());
+    }
+    trace::record("gpio", "low", pin as u64);
+    gpio_history::record(pin, gpio_history::Event::Low, force);
+
+    Ok(())
+}
+
+/// Route PWM to `pin`, one of 12, 13, 18 and 19, and start its channel.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO and PWM drivers, and not while they run.
+pub unsafe fn pwm_enable(pin: u8, force: bool) -> Result<(), &'static str> {
+    let channel = device_driver::Pwm::channel_of(pin).ok_or("Pin has no PWM function")?;
+    if gpio_dry_run() {
+        return Err("PWM is unavailable in GPIO dry run");
+    }
+    GPIO.assume_init_ref().map_pwm(pin, force)?;
+
+    PWM.assume_init_ref().pwm_enable(channel)
+}
+
+/// Stop the PWM channel of `pin`. The pin stays routed to it and low.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO and PWM drivers, and not while they run.
+pub unsafe fn pwm_disable(pin: u8) -> Result<(), &'static str> {
+    let channel = device_driver::Pwm::channel_of(pin).ok_or("Pin has no PWM function")?;
+
+    PWM.assume_init_ref().pwm_disable(channel)
+}
+
+/// Change the frequency of the PWM channel of `pin`.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO and PWM drivers, and not while they run.
+pub unsafe fn pwm_set_frequency(pin: u8, hz: u32) -> Result<(), &'static str> {
+    if gpio_dry_run() {
+        return Err("PWM is unavailable in GPIO dry run");
+    }
+    let channel = device_driver::Pwm::channel_of(pin).ok_or("Pin has no PWM function")?;
+
+    PWM.assume_init_ref().set_frequency(channel, hz)
+}
+
+/// Change the duty cycle of the PWM channel of `pin`, in percent.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO and PWM drivers, and not while they run.
+pub unsafe fn pwm_set_duty_cycle(pin: u8, percent: u32) -> Result<(), &'static str> {
+    if gpio_dry_run() {
+        return Err("PWM is unavailable in GPIO dry run");
+    }
+    let channel = device_driver::Pwm::channel_of(pin).ok_or("Pin has no PWM function")?;
+
+    PWM.assume_init_ref().set_duty_cycle(channel, percent)
+}
+
+/// Return whether each PWM channel is enabled, its frequency and its duty cycle.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the PWM driver, and not while it runs.
+pub unsafe fn pwm_state() -> impl Iterator<Item = (usize, bool, u32, u32)> {
+    (1..=2).filter_map(|channel| {
+        let (enabled, hz, percent) = PWM.assume_init_ref().state(channel).ok()?;
+
+        Some((channel, enabled, hz, percent))
+    })
+}
+
+/// Call `handler` in IRQ context whenever `edge` is detected on a pin.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_register_irq(
+    pin: u8,
+    edge: device_driver::Edge,
+    handler: device_driver::PinHandler,
+) -> Result<(), &'static str> {
+    GPIO.assume_init_ref().register_pin_irq(pin, edge, handler)
+}
+
+/// Call `handler` in IRQ context on both edges of a pin.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_register_irq_both(
+    pin: u8,
+    handler: device_driver::PinHandler,
+) -> Result<(), &'static str> {
+    gpio_register_irq(pin, device_driver::Edge::Both, handler)
+}
+
+/// Stop detecting edges on a pin.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_unregister_irq(pin: u8) -> Result<(), &'static str> {
+    GPIO.assume_init_ref().unregister_pin_irq(pin)
+}
+
+/// Return the GPIO pins with an IRQ registered and their edges.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_irqs() -> impl Iterator<Item = (u8, device_driver::Edge)> {
+    GPIO.assume_init_ref().pin_irqs()
+}
+
+/// Return the protected GPIO pins as a bit mask.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_protected_pins() -> u64 {
+    GPIO.assume_init_ref().protected_pins()
+}
+
+/// Replace the protected GPIO pins with the ones set in `mask`.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_set_protected_pins(mask: u64) {
+    GPIO.assume_init_ref().set_protected_pins(mask)
+}
+
+/// Return whether GPIO pin operations only print the register writes they would perform.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_dry_run() -> bool {
+    GPIO.assume_init_ref().dry_run()
+}
+
+/// Switch the GPIO dry run on or off. While on, the history and trace don't record pin operations,
+/// as nothing was driven.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_set_dry_run(dry_run: bool) {
+    GPIO.assume_init_ref().set_dry_run(dry_run)
+}
+
+/// Apply the protected GPIO pins from the config store, where they are kept as a comma-separated
+/// list under `gpio.protected`. Without the setting, the driver's defaults stay in place.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_load_protected_pins() -> Result<(), &'static str> {
+    let list = match config::store().get(GPIO_PROTECTED_KEY) {
+        Some(list) => list,
+        None => return Ok(()),
+    };
+
+    let mut mask = 0;
+    for pin in list.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let pin: u8 = pin.parse().map_err(|_| "Malformed gpio.protected")?;
+        if pin >= 64 {
+            return Err("Malformed gpio.protected");
+        }
+        mask |= 1 << pin;
+    }
+    gpio_set_protected_pins(mask);
+
+    Ok(())
+}
+
+/// Write the protected GPIO pins to the config store. They are persisted on the next save.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the GPIO driver, and not while it runs.
+pub unsafe fn gpio_store_protected_pins() -> Result<(), &'static str> {
+    let mask = gpio_protected_pins();
+    let list: Vec<String> = (0..64)
+        .filter(|pin| mask & (1 << pin) != 0)
+        .map(|pin| format!("{}", pin))
+        .collect();
+
+    config::store().set(GPIO_PROTECTED_KEY, &list.join(","))
+}
+
+/// Return the console UART's RX interrupt settings.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the PL011 UART driver, and not while it runs.
+pub unsafe fn uart_rx_tuning() -> device_driver::RxTuning {
+    PL011_UART.assume_init_ref().rx_tuning()
+}
+
+/// Change the console UART's RX interrupt settings.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the PL011 UART driver, and not while it runs.
+pub unsafe fn uart_set_rx_tuning(tuning: device_driver::RxTuning) {
+    PL011_UART.assume_init_ref().set_rx_tuning(tuning)
+}
+
+/// Apply the console UART's RX tuning from the config store, kept under `uart.rx_trigger` and
+/// `uart.rx_mode`. Missing settings keep their defaults.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the PL011 UART driver, and not while it runs.
+pub unsafe fn uart_load_rx_tuning() -> Result<(), &'static str> {
+    let mut tuning = uart_rx_tuning();
+
+    if let Some(trigger) = config::store().get(UART_RX_TRIGGER_KEY) {
+        tuning.trigger = trigger.parse().map_err(|_| "Malformed uart.rx_trigger")?;
+    }
+    if let Some(mode) = config::store().get(UART_RX_MODE_KEY) {
+        tuning.mode = mode.parse().map_err(|_| "Malformed uart.rx_mode")?;
+    }
+    uart_set_rx_tuning(tuning);
+
+    Ok(())
+}
+
+/// Write the console UART's RX tuning to the config store. It is persisted on the next save.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the PL011 UART driver, and not while it runs.
+pub unsafe fn uart_store_rx_tuning() -> Result<(), &'static str> {
+    let tuning = uart_rx_tuning();
+
+    config::store().set(UART_RX_TRIGGER_KEY, &format!("{}", tuning.trigger))?;
+    config::store().set(UART_RX_MODE_KEY, &format!("{}", tuning.mode))
+}
+
+/// Hand the console's input to its read functions instead of the shell.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the console UART's driver, and not while it runs.
+pub unsafe fn uart_detach_shell() {
+    match MINI_UART_ROLE {
+        device_driver::MiniUartRole::Console => {
+            MINI_UART.assume_init_ref().detach_shell();
+        }
+        device_driver::MiniUartRole::Bluetooth => {
+            PL011_UART.assume_init_ref().detach_shell();
+        }
+    }
+}
+
+/// Run shell commands entered on the console again.
+///
+/// # Safety
+///
+/// - Only after [`init()`] instantiated the console UART's driver, and not while it runs.
+pub unsafe fn uart_attach_shell() {
+    match MINI_UART_ROLE {
+        device_driver::MiniUartRole::Console => {
+            let _ = MINI_UART
+                .assume_init_ref()
+                .attach_shell(MINI_UART.assume_init_ref());
+        }
+        device_driver::MiniUartRole::Bluetooth => PL011_UART
+            .assume_init_ref()
+            .attach_shell(PL011_UART.assume_init_ref()),
+    }
 }
 
 /// Minimal code needed to bring up the console in QEMU (for testing only). This is often less steps
@@ -21,7 +21,12 @@ pub mod irq_map {
     /// The non-secure physical timer IRQ number.
     pub const ARM_NS_PHYSICAL_TIMER: IRQNumber = IRQNumber::Local(LocalIRQ::new(1));
 
+    pub(in crate::bsp) const GPIO: IRQNumber = IRQNumber::Peripheral(PeripheralIRQ::new(49));
     pub(in crate::bsp) const PL011_UART: IRQNumber = IRQNumber::Peripheral(PeripheralIRQ::new(57));
+    pub(in crate::bsp) const AUX: IRQNumber = IRQNumber::Peripheral(PeripheralIRQ::new(29));
+    pub(in crate::bsp) const DMA: IRQNumber = IRQNumber::Peripheral(PeripheralIRQ::new(21));
+    #[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+    pub(in crate::bsp) const USB: IRQNumber = IRQNumber::Peripheral(PeripheralIRQ::new(9));
 }
 
 /// The IRQ map.
@@ -32,5 +37,10 @@ pub mod irq_map {
     /// The non-secure physical timer IRQ number.
     pub const ARM_NS_PHYSICAL_TIMER: IRQNumber = IRQNumber::new(30);
 
+    pub(in crate::bsp) const GPIO: IRQNumber = IRQNumber::new(145);
     pub(in crate::bsp) const PL011_UART: IRQNumber = IRQNumber::new(153);
+    pub(in crate::bsp) const AUX: IRQNumber = IRQNumber::new(125);
+    pub(in crate::bsp) const DMA: IRQNumber = IRQNumber::new(117);
+    #[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+    pub(in crate::bsp) const USB: IRQNumber = IRQNumber::new(105);
 }
@@ -115,15 +115,46 @@ pub(super) mod map {
     pub mod mmio {
         use super::*;
 
+        pub const SYSTEM_TIMER_START:  Address<Physical> = Address::new(0x3F00_3000);
+        pub const SYSTEM_TIMER_SIZE:   usize             =              0x0C;
+
+        pub const DMA_START:           Address<Physical> = Address::new(0x3F00_7500);
+        pub const DMA_SIZE:            usize             =              0x24;
+
         pub const PERIPHERAL_IC_START: Address<Physical> = Address::new(0x3F00_B200);
         pub const PERIPHERAL_IC_SIZE:  usize             =              0x24;
 
+        pub const MAILBOX_START:       Address<Physical> = Address::new(0x3F00_B880);
+        pub const MAILBOX_SIZE:        usize             =              0x24;
+
+        pub const PM_START:            Address<Physical> = Address::new(0x3F10_0000);
+        pub const PM_SIZE:             usize             =              0x28;
+
+        pub const CM_PWM_START:        Address<Physical> = Address::new(0x3F10_10A0);
+        pub const CM_PWM_SIZE:         usize             =              0x08;
+
+        pub const RNG_START:           Address<Physical> = Address::new(0x3F10_4000);
+        pub const RNG_SIZE:            usize             =              0x0C;
+
         pub const GPIO_START:          Address<Physical> = Address::new(0x3F20_0000);
-        pub const GPIO_SIZE:           usize             =              0xA0;
+        pub const GPIO_SIZE:           usize             =              0xF4;
 
         pub const PL011_UART_START:    Address<Physical> = Address::new(0x3F20_1000);
         pub const PL011_UART_SIZE:     usize             =              0x48;
 
+        pub const PWM_START:           Address<Physical> = Address::new(0x3F20_C000);
+        pub const PWM_SIZE:            usize             =              0x28;
+
+        pub const AUX_START:           Address<Physical> = Address::new(0x3F21_5000);
+        pub const AUX_SIZE:            usize             =              0x6C;
+
+        pub const EMMC_START:          Address<Physical> = Address::new(0x3F30_0000);
+        pub const EMMC_SIZE:           usize             =              0x100;
+
+        pub const USB_START:           Address<Physical> = Address::new(0x3F98_0000);
+        #[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+        pub const USB_SIZE:            usize             =              0x3000;
+
         pub const LOCAL_IC_START:      Address<Physical> = Address::new(0x4000_0000);
         pub const LOCAL_IC_SIZE:       usize             =              0x100;
 
@@ -135,22 +166,63 @@ pub(super) mod map {
     pub mod mmio {
         use super::*;
 
-        pub const GPIO_START:       Address<Physical> = Address::new(0xFE20_0000);
-        pub const GPIO_SIZE:        usize             =              0xA0;
+        pub const GENET_START:        Address<Physical> = Address::new(0xFD58_0000);
+        pub const GENET_SIZE:         usize             =              0x5050;
+
+        pub const SYSTEM_TIMER_START: Address<Physical> = Address::new(0xFE00_3000);
+        pub const SYSTEM_TIMER_SIZE:  usize             =              0x0C;
+
+        pub const DMA_START:          Address<Physical> = Address::new(0xFE00_7500);
+        pub const DMA_SIZE:           usize             =              0x24;
+
+        pub const MAILBOX_START:      Address<Physical> = Address::new(0xFE00_B880);
+        pub const MAILBOX_SIZE:       usize             =              0x24;
+
+        pub const PM_START:           Address<Physical> = Address::new(0xFE10_0000);
+        pub const PM_SIZE:            usize             =              0x28;
+
+        pub const CM_PWM_START:       Address<Physical> = Address::new(0xFE10_10A0);
+        pub const CM_PWM_SIZE:        usize             =              0x08;
+
+        pub const GPIO_START:         Address<Physical> = Address::new(0xFE20_0000);
+        pub const GPIO_SIZE:          usize             =              0xF4;
+
+        pub const PL011_UART_START:   Address<Physical> = Address::new(0xFE20_1000);
+        pub const PL011_UART_SIZE:    usize             =              0x48;
+
+        pub const PWM_START:          Address<Physical> = Address::new(0xFE20_C000);
+        pub const PWM_SIZE:           usize             =              0x28;
+
+        pub const AUX_START:          Address<Physical> = Address::new(0xFE21_5000);
+        pub const AUX_SIZE:           usize             =              0x6C;
 
-        pub const PL011_UART_START: Address<Physical> = Address::new(0xFE20_1000);
-        pub const PL011_UART_SIZE:  usize             =              0x48;
+        pub const EMMC_START:         Address<Physical> = Address::new(0xFE30_0000);
+        pub const EMMC_SIZE:          usize             =              0x100;
 
-        pub const GICD_START:       Address<Physical> = Address::new(0xFF84_1000);
-        pub const GICD_SIZE:        usize             =              0x824;
+        pub const EMMC2_START:        Address<Physical> = Address::new(0xFE34_0000);
+        pub const EMMC2_SIZE:         usize             =              0x100;
 
-        pub const GICC_START:       Address<Physical> = Address::new(0xFF84_2000);
-        pub const GICC_SIZE:        usize             =              0x14;
+        pub const USB_START:          Address<Physical> = Address::new(0xFE98_0000);
+        #[cfg(any(feature = "usb_gadget", feature = "usb_host"))]
+        pub const USB_SIZE:           usize             =              0x3000;
 
-        pub const END:              Address<Physical> = Address::new(0xFF85_0000);
+        pub const GICD_START:         Address<Physical> = Address::new(0xFF84_1000);
+        pub const GICD_SIZE:          usize             =              0xF04;
+
+        pub const GICC_START:         Address<Physical> = Address::new(0xFF84_2000);
+        pub const GICC_SIZE:          usize             =              0x14;
+
+        pub const END:                Address<Physical> = Address::new(0xFF85_0000);
     }
 
     pub const END: Address<Physical> = mmio::END;
+
+    /// Where the firmware loads the kernel binary to. Chainloaded images go there as well.
+    pub const BINARY_LOAD_ADDR: Address<Physical> = Address::new(0x8_0000);
+
+    /// Scratch page for the chainload copy loop. It lies at the bottom of the boot core's stack,
+    /// far below anything the stack uses, and above the firmware's spin tables.
+    pub const CHAINLOAD_TRAMPOLINE: Address<Physical> = Address::new(0x1_0000);
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -252,3 +324,55 @@ fn boot_core_stack_size() -> usize {
 pub fn phys_addr_space_end_exclusive_addr() -> PageAddress<Physical> {
     PageAddress::from(map::END)
 }
+
+/// Physical address the firmware loads the kernel binary to.
+#[inline(always)]
+pub fn phys_binary_load_addr() -> Address<Physical> {
+    map::BINARY_LOAD_ADDR
+}
+
+/// Physical address of the scratch page for the chainload copy loop.
+#[inline(always)]
+pub fn phys_chainload_trampoline_addr() -> Address<Physical> {
+    map::CHAINLOAD_TRAMPOLINE
+}
+
+/// Names and physical start addresses of the board's MMIO devices.
+pub fn mmio_devices() -> &'static [(&'static str, Address<Physical>)] {
+    use map::mmio::*;
+
+    #[cfg(feature = "bsp_rpi3")]
+    {
+        &[
+            ("Peripheral IC", PERIPHERAL_IC_START),
+            ("Mailbox", MAILBOX_START),
+            ("PM watchdog", PM_START),
+            ("System timer", SYSTEM_TIMER_START),
+            ("RNG", RNG_START),
+            ("GPIO", GPIO_START),
+            ("PL011 UART", PL011_UART_START),
+            ("AUX (mini UART)", AUX_START),
+            ("EMMC", EMMC_START),
+            ("USB OTG", USB_START),
+            ("Local IC", LOCAL_IC_START),
+        ]
+    }
+
+    #[cfg(feature = "bsp_rpi4")]
+    {
+        &[
+            ("GENET (Ethernet)", GENET_START),
+            ("Mailbox", MAILBOX_START),
+            ("PM watchdog", PM_START),
+            ("System timer", SYSTEM_TIMER_START),
+            ("GPIO", GPIO_START),
+            ("PL011 UART", PL011_UART_START),
+            ("AUX (mini UART)", AUX_START),
+            ("EMMC", EMMC_START),
+            ("EMMC2 (SD card)", EMMC2_START),
+            ("USB OTG", USB_START),
+            ("GICD", GICD_START),
+            ("GICC", GICC_START),
+        ]
+    }
+}
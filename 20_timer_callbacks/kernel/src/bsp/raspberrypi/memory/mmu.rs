@@ -33,6 +33,13 @@ pub type KernelGranule = TranslationGranule<{ 64 * 1024 }>;
 /// The kernel's virtual address space defined by this BSP.
 pub type KernelVirtAddrSpace = AddressSpace<{ kernel_virt_addr_space_size() }>;
 
+/// The virtual address space of a program, starting at address zero.
+pub type UserVirtAddrSpace = AddressSpace<{ 1024 * 1024 * 1024 }>;
+
+/// The translation tables of a program.
+pub type UserTranslationTable =
+    <UserVirtAddrSpace as AssociatedTranslationTable>::TableStartFromBottom;
+
 //--------------------------------------------------------------------------------------------------
 // Global instances
 //--------------------------------------------------------------------------------------------------
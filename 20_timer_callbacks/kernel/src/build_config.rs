@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Compile-time configuration.
+//!
+//! The constants are generated by `build.rs`, so that a binary running on a board can be matched
+//! to the build that produced it.
+
+use crate::{bsp, info, time};
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/build_config.rs"));
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+pub use generated::{FEATURES, HEAP_SIZE, PROFILE, TARGET};
+
+/// The kernel version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Print the build configuration, followed by the board's timer and MMIO layout.
+pub fn print() {
+    info!("      Version:   {}", VERSION);
+    info!("      Board:     {}", bsp::board_name());
+    info!("      Target:    {} ({})", TARGET, PROFILE);
+    info!("      Features:  {}", FEATURES.join(", "));
+    info!("      Heap size: {} KiB", HEAP_SIZE / 1024);
+    info!(
+        "      Timer:     tickless, {} resolution",
+        time::Human(time::time_manager().resolution())
+    );
+
+    info!("      MMIO:");
+    for (name, addr) in bsp::memory::mmio_devices() {
+        info!("          {:<16} {}", name, addr);
+    }
+}
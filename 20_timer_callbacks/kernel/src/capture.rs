@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Pulse capture.
+//!
+//! Measures pulse widths and periods on input pins, e.g. the echo of an HC-SR04 or the channels of
+//! an RC receiver. The GPIO IRQ handler reads the architectural counter before anything else and
+//! passes it to [`record_edge()`] for every edge of a captured pin, so a measurement has the
+//! counter's resolution plus the jitter of the IRQ latency, which mostly cancels out between the
+//! two edges of a pulse.
+//!
+//! Edges closer together than the IRQ latency are merged by the GPIO, so pulses must be longer
+//! than a few microseconds.
+
+use crate::{
+    bsp, info, spin_until,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time,
+};
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of pins that can be captured at the same time.
+const MAX_CAPTURES: usize = 4;
+
+#[derive(Copy, Clone)]
+struct Capture {
+    pin: u8,
+    last_rise: Option<Duration>,
+    measurement: Measurement,
+    on_period: Option<PeriodHandler>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The latest measurement of a pin.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct Measurement {
+    /// Width of the last complete high pulse.
+    pub width: Option<Duration>,
+
+    /// Time between the last two rising edges.
+    pub period: Option<Duration>,
+
+    /// Number of complete high pulses.
+    pub pulses: u64,
+}
+
+/// Called from the IRQ handler with every new period of a pin, for decoders that need each pulse
+/// rather than the latest one.
+pub type PeriodHandler = fn(period: Duration);
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static CAPTURES: IRQSafeNullLock<[Option<Capture>; MAX_CAPTURES]> =
+    IRQSafeNullLock::new([None; MAX_CAPTURES]);
+
+/// Captured pins as a bit mask, so that the IRQ handler skips other pins without locking.
+static CAPTURED_PINS: AtomicU64 = AtomicU64::new(0);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Capture {
+    const fn new(pin: u8) -> Self {
+        Self {
+            pin,
+            last_rise: None,
+            measurement: Measurement {
+                width: None,
+                period: None,
+                pulses: 0,
+            },
+            on_period: None,
+        }
+    }
+
+    fn edge(&mut self, level: bool, at: Duration) {
+        if level {
+            if let Some(rise) = self.last_rise {
+                self.measurement.period = Some(at - rise);
+            }
+            self.last_rise = Some(at);
+        } else if let Some(rise) = self.last_rise {
+            self.measurement.width = Some(at - rise);
+            self.measurement.pulses += 1;
+        }
+    }
+}
+
+fn find(captures: &mut [Option<Capture>], pin: u8) -> Option<&mut Capture> {
+    captures.iter_mut().flatten().find(|c| c.pin == pin)
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Start measuring pulses on `pin`. The pin must be an input.
+pub fn start(pin: u8) -> Result<(), &'static str> {
+    CAPTURES.lock(|captures| {
+        if find(captures, pin).is_some() {
+            return Err("Pin already captured");
+        }
+        let slot = captures
+            .iter_mut()
+            .find(|c| c.is_none())
+            .ok_or("Too many captured pins")?;
+
+        unsafe { bsp::driver::gpio_register_irq_both(pin, |_, _| ())? };
+        *slot = Some(Capture::new(pin));
+        CAPTURED_PINS.fetch_or(1 << pin, Ordering::Relaxed);
+
+        Ok(())
+    })
+}
+
+/// Stop measuring pulses on `pin`.
+pub fn stop(pin: u8) -> Result<(), &'static str> {
+    CAPTURES.lock(|captures| {
+        let slot = captures
+            .iter_mut()
+            .find(|c| matches!(c, Some(c) if c.pin == pin))
+            .ok_or("Pin not captured")?;
+
+        CAPTURED_PINS.fetch_and(!(1 << pin), Ordering::Relaxed);
+        *slot = None;
+
+        unsafe { bsp::driver::gpio_unregister_irq(pin) }
+    })
+}
+
+/// Record an edge of `pin` to `level`, detected at uptime `at`. Called by the GPIO IRQ handler.
+pub fn record_edge(pin: u8, level: bool, at: Duration) {
+    if CAPTURED_PINS.load(Ordering::Relaxed) & (1 << pin) == 0 {
+        return;
+    }
+
+    let new_period = CAPTURES.lock(|captures| {
+        let capture = find(captures, pin)?;
+        capture.edge(level, at);
+
+        match (level, capture.on_period) {
+            (true, Some(handler)) => Some((handler, capture.measurement.period?)),
+            _ => None,
+        }
+    });
+
+    if let Some((handler, period)) = new_period {
+        handler(period);
+    }
+}
+
+/// Call `handler` with every new period of the captured `pin`, or stop with `None`.
+pub fn set_period_handler(pin: u8, handler: Option<PeriodHandler>) -> Result<(), &'static str> {
+    CAPTURES.lock(|captures| {
+        find(captures, pin).ok_or("Pin not captured")?.on_period = handler;
+
+        Ok(())
+    })
+}
+
+/// Return the latest measurement of `pin`.
+pub fn measurement(pin: u8) -> Result<Measurement, &'static str> {
+    CAPTURES.lock(|captures| {
+        find(captures, pin)
+            .map(|c| c.measurement)
+            .ok_or("Pin not captured")
+    })
+}
+
+/// Wait for the next complete high pulse on `pin` and return its width.
+pub fn wait_pulse(pin: u8, timeout: Duration) -> Result<Duration, &'static str> {
+    let pulses = measurement(pin)?.pulses;
+
+    spin_until!(
+        measurement(pin).map_or(true, |m| m.pulses != pulses),
+        timeout,
+        "No pulse",
+        pin as u32
+    )
+    .map_err(|x| x.context)?;
+
+    measurement(pin)?.width.ok_or("No pulse")
+}
+
+/// Print the captured pins and their measurements.
+pub fn print() {
+    info!(
+        "      Resolution: {}",
+        time::Human(time::time_manager().resolution())
+    );
+
+    CAPTURES.lock(|captures| {
+        for c in captures.iter().flatten() {
+            let m = c.measurement;
+            let ns = |d: Option<Duration>| d.map_or(0, |d| d.as_nanos() as u64);
+
+            info!(
+                "      Pin {:<2}  width {:>10} ns  period {:>10} ns  pulses {}",
+                c.pin,
+                ns(m.width),
+                ns(m.period),
+                m.pulses
+            );
+        }
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A pulse is measured from its rising to its falling edge, the period between rising edges,
+    /// and a falling edge before the first rising one is ignored.
+    #[kernel_test]
+    fn pulse_width_and_period() {
+        let us = Duration::from_micros;
+        let mut capture = Capture::new(17);
+
+        capture.edge(false, us(5));
+        assert_eq!(capture.measurement, Measurement::default());
+
+        capture.edge(true, us(100));
+        capture.edge(false, us(1_600));
+        capture.edge(true, us(20_100));
+        capture.edge(false, us(21_100));
+
+        assert_eq!(capture.measurement.width, Some(us(1_000)));
+        assert_eq!(capture.measurement.period, Some(us(20_000)));
+        assert_eq!(capture.measurement.pulses, 2);
+    }
+}
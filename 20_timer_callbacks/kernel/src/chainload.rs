@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Chainloading.
+//!
+//! Boots another kernel image in place of the running one, so that a development iteration doesn't
+//! need the SD card reflashed. [`netload()`] fetches the image over TFTP into a region reserved in
+//! `.bss`. After the shutdown hooks ran, EL2 copies the image to the board's load address and jumps
+//! to it with the MMU off, the same way the firmware starts a kernel.
+
+use crate::{
+    bsp, cpu, exception, info,
+    memory::{self, Address, Virtual},
+    net, shutdown,
+};
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Largest image that can be loaded.
+const MAX_IMAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// EL2 copies the image in chunks of this many bytes.
+const COPY_CHUNK: usize = 16;
+
+/// Memory for the image. Aligned to a cache line, so that writing it back doesn't touch neighbors.
+#[repr(align(64))]
+struct LoadRegion(UnsafeCell<[u8; MAX_IMAGE_SIZE]>);
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static LOAD_REGION: LoadRegion = LoadRegion(UnsafeCell::new([0; MAX_IMAGE_SIZE]));
+
+/// Set while an image is being loaded into [`LOAD_REGION`].
+static LOADING: AtomicBool = AtomicBool::new(false);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+unsafe impl Sync for LoadRegion {}
+
+/// Run the shutdown hooks and boot the first `len` bytes of the load region.
+fn boot(len: usize) -> Result<(), &'static str> {
+    let virt_addr = LOAD_REGION.0.get() as usize;
+    let phys_addr =
+        memory::mmu::try_kernel_virt_addr_to_phys_addr(Address::<Virtual>::new(virt_addr))?;
+
+    info!("Booting {} byte image from {}", len, phys_addr);
+    shutdown::run();
+
+    // Copying whole chunks can't run past the region, as its size is a multiple of the chunk.
+    let len = len.next_multiple_of(COPY_CHUNK);
+
+    exception::asynchronous::local_irq_mask();
+    cpu::clean_invalidate_dcache(virt_addr, len);
+
+    // The region is part of the kernel image, which is mapped linearly and lies above the load
+    // address.
+    unsafe {
+        cpu::chainload(
+            phys_addr.as_usize(),
+            len,
+            bsp::memory::phys_binary_load_addr().as_usize(),
+            bsp::memory::phys_chainload_trampoline_addr().as_usize(),
+        )
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Fetch `filename` from the TFTP server at `server` and boot it. Returns only if the image could
+/// not be loaded.
+pub fn netload(server: net::Ipv4Address, filename: &str) -> Result<(), &'static str> {
+    if LOADING.swap(true, Ordering::Acquire) {
+        return Err("Another image is being loaded");
+    }
+
+    let region = unsafe { &mut *LOAD_REGION.0.get() };
+    let result = net::tftp::fetch(server, filename, region).and_then(|len| match len {
+        0 => Err("Empty image"),
+        len => boot(len),
+    });
+
+    LOADING.store(false, Ordering::Release);
+    result
+}
@@ -0,0 +1,365 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Clock policy: ARM and core clock changes guarded by temperature and power.
+//!
+//! [`set()`] asks the firmware for a new rate only while the SoC is below the temperature limit
+//! and the firmware reports no under-voltage or throttling. Raising a clock is also refused once
+//! under-voltage occurred since boot, as the supply is evidently marginal. Lowering a clock is
+//! always allowed.
+//!
+//! While a clock runs above the rate it had before the first change, a monitor samples the
+//! temperature and the throttling flags every [`MONITOR_INTERVAL`] and publishes
+//! [`Event::ThermalLimit`] and [`Event::Throttled`] on the event bus. The policy subscribes to
+//! both and puts all clocks back to their original rates.
+
+use crate::{
+    bsp,
+    event::{self, Event},
+    info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time::{self, TimeoutHandle},
+    warn,
+};
+use alloc::boxed::Box;
+use core::{fmt, time::Duration};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const MONITOR_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default temperature limit, with headroom before the firmware starts throttling by itself.
+const DEFAULT_LIMIT_MILLICELSIUS: u32 = 70_000;
+
+/// Range the limit can be set to.
+const MIN_LIMIT_MILLICELSIUS: u32 = 40_000;
+const MAX_LIMIT_MILLICELSIUS: u32 = 85_000;
+
+// Throttling flags, current conditions.
+const UNDER_VOLTAGE: u32 = 1 << 0;
+const FREQUENCY_CAPPED: u32 = 1 << 1;
+const THROTTLED: u32 = 1 << 2;
+const SOFT_TEMPERATURE_LIMIT: u32 = 1 << 3;
+const CURRENT_CONDITIONS: u32 = 0xF;
+
+// Throttling flags, conditions since boot.
+const UNDER_VOLTAGE_OCCURRED: u32 = 1 << 16;
+
+struct ClockingInner {
+    /// Rate of each clock before the first change, while it is changed.
+    original: [Option<u32>; Clock::ALL.len()],
+    limit_millicelsius: u32,
+    monitor: Option<TimeoutHandle>,
+    subscribed: bool,
+    reverts: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A clock whose rate can be changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Clock {
+    Arm,
+    Core,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static CLOCKING: IRQSafeNullLock<ClockingInner> = IRQSafeNullLock::new(ClockingInner {
+    original: [None; Clock::ALL.len()],
+    limit_millicelsius: DEFAULT_LIMIT_MILLICELSIUS,
+    monitor: None,
+    subscribed: false,
+    reverts: 0,
+});
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Check whether the conditions allow raising a clock.
+fn permits_raise(
+    millicelsius: u32,
+    limit_millicelsius: u32,
+    flags: u32,
+) -> Result<(), &'static str> {
+    if millicelsius >= limit_millicelsius {
+        return Err("SoC too hot");
+    }
+    if flags & (UNDER_VOLTAGE | UNDER_VOLTAGE_OCCURRED) != 0 {
+        return Err("Under-voltage detected, check the power supply");
+    }
+    if flags & CURRENT_CONDITIONS != 0 {
+        return Err("Firmware is throttling");
+    }
+
+    Ok(())
+}
+
+/// Sample the temperature and the throttling flags and publish what is wrong.
+fn monitor() {
+    let limit = CLOCKING.lock(|inner| inner.limit_millicelsius);
+    let sample = unsafe {
+        (
+            bsp::driver::soc_temperature(),
+            bsp::driver::throttle_flags(),
+        )
+    };
+
+    match sample {
+        (Ok(millicelsius), _) if millicelsius >= limit => {
+            event::event_bus().publish(Event::ThermalLimit { millicelsius })
+        }
+        (_, Ok(flags)) if flags & CURRENT_CONDITIONS != 0 => {
+            event::event_bus().publish(Event::Throttled { flags })
+        }
+        (Err(x), _) | (_, Err(x)) => warn!("clocking: monitor failed: {}", x),
+        _ => (),
+    }
+}
+
+/// Start or stop the monitor, depending on whether a clock runs above its original rate.
+fn update_monitor(raised: bool) {
+    let stale = CLOCKING.lock(|inner| match (raised, inner.monitor.is_some()) {
+        (true, false) => {
+            inner.monitor = Some(
+                time::time_manager().set_timeout_periodic(MONITOR_INTERVAL, Box::new(monitor)),
+            );
+            None
+        }
+        (false, true) => inner.monitor.take(),
+        _ => None,
+    });
+
+    if let Some(timeout) = stale {
+        timeout.cancel();
+    }
+}
+
+fn subscribe() {
+    if CLOCKING.lock(|inner| core::mem::replace(&mut inner.subscribed, true)) {
+        return;
+    }
+
+    event::event_bus().subscribe(Box::new(|e| match e {
+        Event::ThermalLimit { .. } | Event::Throttled { .. } => {
+            if let Err(x) = revert() {
+                warn!("clocking: revert failed: {}", x);
+            }
+        }
+        _ => (),
+    }));
+}
+
+/// Return whether any changed clock runs above its original rate.
+fn any_raised() -> Result<bool, &'static str> {
+    let original = CLOCKING.lock(|inner| inner.original);
+
+    for (clock, original) in Clock::ALL.iter().zip(original) {
+        if let Some(original) = original {
+            if unsafe { bsp::driver::clock_rate(*clock) }? > original {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Clock {
+    pub const ALL: [Self; 2] = [Self::Arm, Self::Core];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl core::str::FromStr for Clock {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "arm" => Ok(Self::Arm),
+            "core" => Ok(Self::Core),
+            _ => Err("Unknown clock"),
+        }
+    }
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Arm => f.pad("arm"),
+            Self::Core => f.pad("core"),
+        }
+    }
+}
+
+/// Set `clock` to `hz` if the temperature and power allow it. Returns the rate the firmware chose.
+pub fn set(clock: Clock, hz: u32) -> Result<u32, &'static str> {
+    let (min, max) = unsafe { bsp::driver::clock_range(clock) }?;
+    if !(min..=max).contains(&hz) {
+        return Err("Rate out of the firmware's range");
+    }
+
+    let current = unsafe { bsp::driver::clock_rate(clock) }?;
+    let original = CLOCKING.lock(|inner| inner.original[clock.index()]);
+    if hz > original.unwrap_or(current) {
+        let limit = CLOCKING.lock(|inner| inner.limit_millicelsius);
+        let millicelsius = unsafe { bsp::driver::soc_temperature() }?;
+        let flags = unsafe { bsp::driver::throttle_flags() }?;
+        permits_raise(millicelsius, limit, flags)?;
+    }
+
+    subscribe();
+    CLOCKING.lock(|inner| {
+        inner.original[clock.index()].get_or_insert(current);
+    });
+    let rate = unsafe { bsp::driver::set_clock_rate(clock, hz) }?;
+    update_monitor(any_raised()?);
+
+    Ok(rate)
+}
+
+/// Put all changed clocks back to their original rates.
+pub fn revert() -> Result<(), &'static str> {
+    let original = CLOCKING.lock(|inner| {
+        if inner.original.iter().any(Option::is_some) {
+            inner.reverts += 1;
+        }
+        core::mem::take(&mut inner.original)
+    });
+    update_monitor(false);
+
+    let mut result = Ok(());
+    for (clock, original) in Clock::ALL.iter().zip(original) {
+        if let Some(hz) = original {
+            let set = unsafe { bsp::driver::set_clock_rate(*clock, hz) };
+            if set.is_ok() {
+                warn!("clocking: {} back to {} MHz", clock, hz / 1_000_000);
+            }
+            result = result.and(set.map(|_| ()));
+        }
+    }
+
+    result
+}
+
+/// Set the temperature limit in degrees Celsius.
+pub fn set_limit(celsius: u32) -> Result<(), &'static str> {
+    let millicelsius = celsius.saturating_mul(1000);
+    if !(MIN_LIMIT_MILLICELSIUS..=MAX_LIMIT_MILLICELSIUS).contains(&millicelsius) {
+        return Err("Limit out of range");
+    }
+
+    CLOCKING.lock(|inner| inner.limit_millicelsius = millicelsius);
+
+    Ok(())
+}
+
+/// Print the clocks, the conditions and the policy's state.
+pub fn print() -> Result<(), &'static str> {
+    let (original, limit, monitoring, reverts) = CLOCKING.lock(|inner| {
+        (
+            inner.original,
+            inner.limit_millicelsius,
+            inner.monitor.is_some(),
+            inner.reverts,
+        )
+    });
+
+    for clock in Clock::ALL {
+        let rate = unsafe { bsp::driver::clock_rate(clock) }?;
+        let (min, max) = unsafe { bsp::driver::clock_range(clock) }?;
+        match original[clock.index()] {
+            Some(hz) => info!(
+                "      {:<5} {:>4} MHz ({}-{} MHz, was {} MHz)",
+                clock,
+                rate / 1_000_000,
+                min / 1_000_000,
+                max / 1_000_000,
+                hz / 1_000_000
+            ),
+            None => info!(
+                "      {:<5} {:>4} MHz ({}-{} MHz)",
+                clock,
+                rate / 1_000_000,
+                min / 1_000_000,
+                max / 1_000_000
+            ),
+        }
+    }
+
+    let millicelsius = unsafe { bsp::driver::soc_temperature() }?;
+    let flags = unsafe { bsp::driver::throttle_flags() }?;
+    info!(
+        "      Temperature: {}.{} C, limit {} C",
+        millicelsius / 1000,
+        millicelsius % 1000 / 100,
+        limit / 1000
+    );
+    info!(
+        "      Throttling:  {:#07x}{}{}{}{}",
+        flags,
+        if flags & UNDER_VOLTAGE != 0 {
+            " under-voltage"
+        } else {
+            ""
+        },
+        if flags & FREQUENCY_CAPPED != 0 {
+            " capped"
+        } else {
+            ""
+        },
+        if flags & THROTTLED != 0 {
+            " throttled"
+        } else {
+            ""
+        },
+        if flags & SOFT_TEMPERATURE_LIMIT != 0 {
+            " soft-limit"
+        } else {
+            ""
+        }
+    );
+    info!(
+        "      Monitor:     {}, {} reverts",
+        if monitoring { "running" } else { "idle" },
+        reverts
+    );
+
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Raising must be refused when too hot, on any current condition, and after under-voltage.
+    #[kernel_test]
+    fn raise_conditions() {
+        assert!(permits_raise(50_000, 70_000, 0).is_ok());
+        assert!(permits_raise(70_000, 70_000, 0).is_err());
+        assert!(permits_raise(50_000, 70_000, THROTTLED).is_err());
+        assert!(permits_raise(50_000, 70_000, UNDER_VOLTAGE_OCCURRED).is_err());
+
+        // Past throttling other than under-voltage doesn't hold a raise back.
+        assert!(permits_raise(50_000, 70_000, THROTTLED << 16).is_ok());
+    }
+}
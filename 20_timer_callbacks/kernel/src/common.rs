@@ -44,3 +44,21 @@ pub const fn size_human_readable_ceil(size: usize) -> (usize, &'static str) {
         (size, "Byte")
     }
 }
+
+/// CRC-32 (IEEE 802.3, as used by Ethernet and zlib) of `data`.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
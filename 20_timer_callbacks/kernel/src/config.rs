@@ -0,0 +1,717 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! The config store.
+//!
+//! Settings are string key/value pairs kept in memory and written to a storage backend on
+//! [`ConfigStore::save()`]. Until the BSP registers a persistent backend with
+//! [`register_storage()`], e.g. a [`BlockStorage`] on the SD card, a RAM backend is used, which
+//! loses its content on power-off.
+//!
+//! The storage area is split into two slots. A save writes the slot that is not in use and commits
+//! by writing its header last, so a power cut during a save leaves the previous slot intact. On
+//! load, the valid slot with the newest generation wins. Generations are serial numbers that may
+//! wrap around.
+//!
+//! Several boards can share one image of the store, e.g. when an SD card is moved between them.
+//! Once the board identity is known, [`ConfigStore::set_namespace()`] selects the board's
+//! namespace. A setting stored as `<namespace>/<key>` then overrides `<key>` on that board only.
+//!
+//! A slot is a header followed by one record per setting. All numbers are little-endian.
+//!
+//! - Header: the magic `KCFG`, the format version (`u16`), the number of records (`u16`), the
+//!   generation (`u32`), the length of the records (`u32`) and a CRC-32 of the preceding bytes
+//!   (`u32`).
+//! - Record: the length of the data (`u16`), the data `key=value` and a CRC-32 of length and data
+//!   (`u32`).
+
+use crate::{
+    block::{self, BLOCK_SIZE},
+    common, info,
+    sched::BlockingMutex,
+    synchronization::{
+        interface::{Mutex, ReadWriteEx},
+        IRQSafeNullLock, InitStateLock,
+    },
+};
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const MAGIC: &[u8; 4] = b"KCFG";
+const FORMAT_VERSION: u16 = 2;
+const HEADER_LEN: usize = 20;
+
+/// Length of a record's length and CRC fields.
+const RECORD_OVERHEAD: usize = 6;
+
+/// Capacity of the RAM backend.
+const RAM_STORAGE_CAPACITY: usize = 4096;
+
+struct ConfigStoreInner {
+    entries: BTreeMap<String, String>,
+
+    /// Slot and generation that were loaded or saved last.
+    active: Option<(usize, u32)>,
+
+    /// Namespace of the per-board overrides.
+    namespace: Option<String>,
+}
+
+/// The contents of a valid slot.
+struct Slot {
+    generation: u32,
+    entries: BTreeMap<String, String>,
+}
+
+/// A slot header.
+struct Header {
+    version: u16,
+    records: u16,
+    generation: u32,
+    len: u32,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Config store interfaces.
+pub mod interface {
+    /// A byte-addressed storage area for the config store.
+    pub trait Storage {
+        /// Name of the backend, for display.
+        fn name(&self) -> &'static str;
+
+        /// Return if the content survives a power cycle.
+        fn is_persistent(&self) -> bool;
+
+        /// Size of the storage area in bytes.
+        fn capacity(&self) -> usize;
+
+        /// Fill `buf` with the bytes starting at `offset`.
+        fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), &'static str>;
+
+        /// Write `data` starting at `offset`.
+        fn write(&self, offset: usize, data: &[u8]) -> Result<(), &'static str>;
+    }
+}
+
+/// Storage backend in RAM.
+pub struct RamStorage {
+    data: IRQSafeNullLock<[u8; RAM_STORAGE_CAPACITY]>,
+}
+
+/// Storage backend on a range of blocks of a block device.
+pub struct BlockStorage {
+    device: block::Device,
+    first_lba: u64,
+    blocks: u64,
+}
+
+/// The config store.
+pub struct ConfigStore {
+    inner: IRQSafeNullLock<ConfigStoreInner>,
+
+    /// Held during storage I/O, which can be slow, so that waiting tasks are parked.
+    io: BlockingMutex<()>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static RAM_STORAGE: RamStorage = RamStorage::new();
+
+static CUR_STORAGE: InitStateLock<Option<&'static (dyn interface::Storage + Sync)>> =
+    InitStateLock::new(None);
+
+static CONFIG_STORE: ConfigStore = ConfigStore::new();
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn storage() -> &'static dyn interface::Storage {
+    match CUR_STORAGE.read(|s| *s) {
+        Some(s) => s,
+        None => &RAM_STORAGE,
+    }
+}
+
+fn read_u16(b: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([b[offset], b[offset + 1]])
+}
+
+fn read_u32(b: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([b[offset], b[offset + 1], b[offset + 2], b[offset + 3]])
+}
+
+/// Size of one slot.
+fn slot_size(storage: &dyn interface::Storage) -> usize {
+    storage.capacity() / 2
+}
+
+/// Encode the settings as a slot image with the given generation.
+fn encode(entries: &BTreeMap<String, String>, generation: u32) -> Result<Vec<u8>, &'static str> {
+    let mut records = Vec::new();
+    for (key, value) in entries {
+        let len = u16::try_from(key.len() + 1 + value.len()).map_err(|_| "Setting too long")?;
+        let start = records.len();
+
+        records.extend_from_slice(&len.to_le_bytes());
+        records.extend_from_slice(key.as_bytes());
+        records.push(b'=');
+        records.extend_from_slice(value.as_bytes());
+        let crc = common::crc32(&records[start..]);
+        records.extend_from_slice(&crc.to_le_bytes());
+    }
+
+    let count = u16::try_from(entries.len()).map_err(|_| "Too many settings")?;
+
+    let mut image = Vec::with_capacity(HEADER_LEN + records.len());
+    image.extend_from_slice(MAGIC);
+    image.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    image.extend_from_slice(&count.to_le_bytes());
+    image.extend_from_slice(&generation.to_le_bytes());
+    image.extend_from_slice(&(records.len() as u32).to_le_bytes());
+    let crc = common::crc32(&image);
+    image.extend_from_slice(&crc.to_le_bytes());
+    image.extend_from_slice(&records);
+
+    Ok(image)
+}
+
+/// Return if generation `a` is newer than `b`. Generations are compared as serial numbers, so that
+/// the one written after `u32::MAX` still counts as newer.
+fn is_newer(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) > 0
+}
+
+/// Return the blocks of a [`BlockStorage`] that `len` bytes at `offset` touch, as the number of the
+/// first block and the offset into it.
+fn block_span(offset: usize, len: usize, capacity: usize) -> Result<(u64, usize), &'static str> {
+    match offset.checked_add(len) {
+        Some(end) if end <= capacity => Ok(((offset / BLOCK_SIZE) as u64, offset % BLOCK_SIZE)),
+        _ => Err("Access beyond end of storage"),
+    }
+}
+
+/// Decode a slot header. Returns `Ok(None)` for a blank slot.
+fn decode_header(header: &[u8; HEADER_LEN]) -> Result<Option<Header>, &'static str> {
+    if &header[..4] != MAGIC {
+        return Ok(None);
+    }
+    if common::crc32(&header[..16]) != read_u32(header, 16) {
+        return Err("Config store header is corrupt");
+    }
+
+    let header = Header {
+        version: read_u16(header, 4),
+        records: read_u16(header, 6),
+        generation: read_u32(header, 8),
+        len: read_u32(header, 12),
+    };
+    if header.version != FORMAT_VERSION {
+        return Err("Unsupported config store version");
+    }
+
+    Ok(Some(header))
+}
+
+/// Decode the records that follow a header.
+fn decode_records(
+    header: &Header,
+    records: &[u8],
+) -> Result<BTreeMap<String, String>, &'static str> {
+    let mut entries = BTreeMap::new();
+    let mut offset = 0;
+
+    for _ in 0..header.records {
+        let len = read_u16(
+            records
+                .get(offset..offset + 2)
+                .ok_or("Config store is corrupt")?,
+            0,
+        ) as usize;
+        let record = records
+            .get(offset..offset + 2 + len + 4)
+            .ok_or("Config store is corrupt")?;
+        if common::crc32(&record[..2 + len]) != read_u32(record, 2 + len) {
+            return Err("Config store record is corrupt");
+        }
+
+        let text =
+            core::str::from_utf8(&record[2..2 + len]).map_err(|_| "Config store is corrupt")?;
+        let (key, value) = text.split_once('=').ok_or("Config store is corrupt")?;
+        entries.insert(key.to_string(), value.to_string());
+
+        offset += len + RECORD_OVERHEAD;
+    }
+
+    if offset != records.len() {
+        return Err("Config store is corrupt");
+    }
+
+    Ok(entries)
+}
+
+/// Read and validate a slot. Returns `Ok(None)` for a blank slot.
+fn load_slot(storage: &dyn interface::Storage, slot: usize) -> Result<Option<Slot>, &'static str> {
+    let base = slot * slot_size(storage);
+
+    let mut raw_header = [0; HEADER_LEN];
+    storage.read(base, &mut raw_header)?;
+    let header = match decode_header(&raw_header)? {
+        Some(h) => h,
+        None => return Ok(None),
+    };
+
+    let len = header.len as usize;
+    if HEADER_LEN + len > slot_size(storage) {
+        return Err("Config store is corrupt");
+    }
+
+    let mut records = Vec::new();
+    records.resize(len, 0);
+    storage.read(base + HEADER_LEN, &mut records)?;
+
+    Ok(Some(Slot {
+        generation: header.generation,
+        entries: decode_records(&header, &records)?,
+    }))
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Register a persistent storage backend. Must be called before [`ConfigStore::load()`].
+pub fn register_storage(new_storage: &'static (dyn interface::Storage + Sync)) {
+    CUR_STORAGE.write(|s| *s = Some(new_storage));
+}
+
+/// Return a reference to the config store.
+pub fn store() -> &'static ConfigStore {
+    &CONFIG_STORE
+}
+
+impl RamStorage {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            data: IRQSafeNullLock::new([0; RAM_STORAGE_CAPACITY]),
+        }
+    }
+}
+
+impl interface::Storage for RamStorage {
+    fn name(&self) -> &'static str {
+        "RAM"
+    }
+
+    fn is_persistent(&self) -> bool {
+        false
+    }
+
+    fn capacity(&self) -> usize {
+        RAM_STORAGE_CAPACITY
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), &'static str> {
+        self.data.lock(|data| {
+            let src = data
+                .get(offset..offset + buf.len())
+                .ok_or("Read beyond end of storage")?;
+            buf.copy_from_slice(src);
+
+            Ok(())
+        })
+    }
+
+    fn write(&self, offset: usize, src: &[u8]) -> Result<(), &'static str> {
+        self.data.lock(|data| {
+            let dst = data
+                .get_mut(offset..offset + src.len())
+                .ok_or("Write beyond end of storage")?;
+            dst.copy_from_slice(src);
+
+            Ok(())
+        })
+    }
+}
+
+impl BlockStorage {
+    /// Create an instance on `blocks` blocks of `device`, from block `first_lba` on.
+    pub const fn new(device: block::Device, first_lba: u64, blocks: u64) -> Self {
+        Self {
+            device,
+            first_lba,
+            blocks,
+        }
+    }
+}
+
+impl interface::Storage for BlockStorage {
+    fn name(&self) -> &'static str {
+        "Block device"
+    }
+
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    fn capacity(&self) -> usize {
+        self.blocks as usize * BLOCK_SIZE
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), &'static str> {
+        let (mut lba, mut start) = block_span(offset, buf.len(), self.capacity())?;
+        let mut block = [0; BLOCK_SIZE];
+        let mut done = 0;
+
+        while done < buf.len() {
+            let len = (BLOCK_SIZE - start).min(buf.len() - done);
+            self.device.read(self.first_lba + lba, &mut block)?;
+            buf[done..done + len].copy_from_slice(&block[start..start + len]);
+
+            done += len;
+            lba += 1;
+            start = 0;
+        }
+
+        Ok(())
+    }
+
+    /// Blocks that are only partly written are read first.
+    fn write(&self, offset: usize, data: &[u8]) -> Result<(), &'static str> {
+        let (mut lba, mut start) = block_span(offset, data.len(), self.capacity())?;
+        let mut block = [0; BLOCK_SIZE];
+        let mut done = 0;
+
+        while done < data.len() {
+            let len = (BLOCK_SIZE - start).min(data.len() - done);
+            if len != BLOCK_SIZE {
+                self.device.read(self.first_lba + lba, &mut block)?;
+            }
+            block[start..start + len].copy_from_slice(&data[done..done + len]);
+            self.device.write(self.first_lba + lba, &block)?;
+
+            done += len;
+            lba += 1;
+            start = 0;
+        }
+
+        Ok(())
+    }
+}
+
+impl ConfigStore {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(ConfigStoreInner {
+                entries: BTreeMap::new(),
+                active: None,
+                namespace: None,
+            }),
+            io: BlockingMutex::new(()),
+        }
+    }
+
+    /// Replace the settings in memory with the ones on storage. Blank storage yields an empty
+    /// store. If one slot is corrupt, the other one is used.
+    pub fn load(&self) -> Result<(), &'static str> {
+        self.load_from(storage())
+    }
+
+    /// Like [`ConfigStore::load()`], but from `storage` instead of the registered backend.
+    pub fn load_from(&self, storage: &dyn interface::Storage) -> Result<(), &'static str> {
+        self.io.lock_or_busy(|_| self.load_slots(storage))?
+    }
+
+    fn load_slots(&self, storage: &dyn interface::Storage) -> Result<(), &'static str> {
+        let mut newest: Option<(usize, Slot)> = None;
+        let mut error = None;
+        for slot in 0..2 {
+            match load_slot(storage, slot) {
+                Ok(Some(s)) => {
+                    if newest
+                        .as_ref()
+                        .map_or(true, |(_, n)| is_newer(s.generation, n.generation))
+                    {
+                        newest = Some((slot, s));
+                    }
+                }
+                Ok(None) => (),
+                Err(x) => error = Some(x),
+            }
+        }
+
+        let (active, entries) = match (newest, error) {
+            (Some((slot, s)), _) => (Some((slot, s.generation)), s.entries),
+            (None, Some(x)) => return Err(x),
+            (None, None) => (None, BTreeMap::new()),
+        };
+
+        self.inner.lock(|inner| {
+            inner.entries = entries;
+            inner.active = active;
+        });
+
+        Ok(())
+    }
+
+    /// Write the settings to storage.
+    ///
+    /// The slot not in use is written, header last, so that the previous settings stay valid
+    /// until the new ones are complete.
+    pub fn save(&self) -> Result<(), &'static str> {
+        self.save_to(storage())
+    }
+
+    /// Like [`ConfigStore::save()`], but to `storage` instead of the registered backend.
+    pub fn save_to(&self, storage: &dyn interface::Storage) -> Result<(), &'static str> {
+        self.io.lock_or_busy(|_| self.save_slot(storage))?
+    }
+
+    fn save_slot(&self, storage: &dyn interface::Storage) -> Result<(), &'static str> {
+        let (slot, generation, image) = self.inner.lock(|inner| {
+            let (slot, generation) = match inner.active {
+                Some((slot, generation)) => (1 - slot, generation.wrapping_add(1)),
+                None => (0, 1),
+            };
+
+            encode(&inner.entries, generation).map(|image| (slot, generation, image))
+        })?;
+
+        if image.len() > slot_size(storage) {
+            return Err("Config store is full");
+        }
+
+        let base = slot * slot_size(storage);
+        storage.write(base + HEADER_LEN, &image[HEADER_LEN..])?;
+        storage.write(base, &image[..HEADER_LEN])?;
+
+        self.inner
+            .lock(|inner| inner.active = Some((slot, generation)));
+
+        Ok(())
+    }
+
+    /// Select the namespace of the per-board overrides.
+    pub fn set_namespace(&self, namespace: &str) {
+        self.inner
+            .lock(|inner| inner.namespace = Some(namespace.to_string()));
+    }
+
+    /// Return the namespace of the per-board overrides, if one was selected.
+    pub fn namespace(&self) -> Option<String> {
+        self.inner.lock(|inner| inner.namespace.clone())
+    }
+
+    /// Return the value of a setting. A per-board override takes precedence.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.inner.lock(|inner| {
+            inner
+                .namespace
+                .as_ref()
+                .and_then(|ns| inner.entries.get(&format!("{}/{}", ns, key)))
+                .or_else(|| inner.entries.get(key))
+                .cloned()
+        })
+    }
+
+    /// Return the value of a numeric setting. Missing or malformed values read as `None`.
+    pub fn get_u64(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(|v| v.parse().ok())
+    }
+
+    /// Change a setting in memory. Keys must not contain `=`, values must not contain line
+    /// breaks.
+    pub fn set(&self, key: &str, value: &str) -> Result<(), &'static str> {
+        if key.is_empty() || key.contains('=') || key.contains('\n') || value.contains('\n') {
+            return Err("Invalid key or value");
+        }
+
+        self.inner
+            .lock(|inner| inner.entries.insert(key.to_string(), value.to_string()));
+
+        Ok(())
+    }
+
+    /// Change the per-board override of a setting in memory.
+    pub fn set_board(&self, key: &str, value: &str) -> Result<(), &'static str> {
+        let namespace = self.namespace().ok_or("Board identity unknown")?;
+
+        self.set(&format!("{}/{}", namespace, key), value)
+    }
+
+    /// Remove a setting in memory.
+    pub fn remove(&self, key: &str) -> Result<(), &'static str> {
+        self.inner
+            .lock(|inner| inner.entries.remove(key))
+            .map(|_| ())
+            .ok_or("No such setting")
+    }
+
+    /// Print all settings and the storage backend.
+    pub fn print(&self) {
+        let storage = storage();
+
+        self.inner.lock(|inner| {
+            for (key, value) in &inner.entries {
+                info!("      {} = {}", key, value);
+            }
+            info!("      {} settings", inner.entries.len());
+            if let Some(ns) = &inner.namespace {
+                info!("      Board namespace: {}", ns);
+            }
+            if let Some((slot, generation)) = inner.active {
+                info!(
+                    "      Format {}, generation {} in slot {}",
+                    FORMAT_VERSION, generation, slot
+                );
+            }
+        });
+        info!(
+            "      Storage: {}{}",
+            storage.name(),
+            if storage.is_persistent() {
+                ""
+            } else {
+                " (not persistent)"
+            }
+        );
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use interface::Storage;
+    use test_macros::kernel_test;
+
+    /// Settings must survive an encode/decode round trip unchanged, and a flipped bit in a record
+    /// must be detected.
+    #[kernel_test]
+    fn encode_decode_round_trip() {
+        let mut entries = BTreeMap::new();
+        entries.insert("boot.count".to_string(), "42".to_string());
+        entries.insert("console.baud".to_string(), "921600".to_string());
+
+        let mut image = encode(&entries, 7).unwrap();
+        let header = decode_header(image[..HEADER_LEN].try_into().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.generation, 7);
+        assert_eq!(
+            decode_records(&header, &image[HEADER_LEN..]).unwrap(),
+            entries
+        );
+
+        image[HEADER_LEN + 4] ^= 1;
+        assert!(decode_records(&header, &image[HEADER_LEN..]).is_err());
+    }
+    static STORAGE: RamStorage = RamStorage::new();
+
+    /// Save `value` as `boot.count` in a new generation.
+    fn save_count(store: &ConfigStore, value: &str) {
+        store.set("boot.count", value).unwrap();
+        store.save_slot(&STORAGE).unwrap();
+    }
+
+    /// Load a fresh store from [`STORAGE`] and return its `boot.count`.
+    fn load_count() -> Option<String> {
+        let store = ConfigStore::new();
+        store.load_slots(&STORAGE).unwrap();
+
+        store.get("boot.count")
+    }
+
+    /// Clear [`STORAGE`], then save two generations: "1" in slot 0 and "2" in slot 1.
+    fn two_generations() -> ConfigStore {
+        let blank = [0; RAM_STORAGE_CAPACITY];
+        STORAGE.write(0, &blank).unwrap();
+
+        let store = ConfigStore::new();
+        save_count(&store, "1");
+        save_count(&store, "2");
+        assert_eq!(load_count().as_deref(), Some("2"));
+
+        store
+    }
+
+    /// A bad record CRC in the newest slot must fall back to the previous slot.
+    #[kernel_test]
+    fn corrupt_slot_falls_back() {
+        two_generations();
+
+        let offset = slot_size(&STORAGE) + HEADER_LEN + 2;
+        let mut byte = [0];
+        STORAGE.read(offset, &mut byte).unwrap();
+        STORAGE.write(offset, &[byte[0] ^ 1]).unwrap();
+
+        assert_eq!(load_count().as_deref(), Some("1"));
+    }
+
+    /// A save that stops half-way through its records must leave the previous slot in use.
+    #[kernel_test]
+    fn torn_save_falls_back() {
+        let store = two_generations();
+
+        store.set("boot.count", "3").unwrap();
+        let image = store.inner.lock(|inner| encode(&inner.entries, 3)).unwrap();
+        // The header reached storage, but only half of the records did.
+        let torn = HEADER_LEN + (image.len() - HEADER_LEN) / 2;
+        STORAGE.write(0, &image[..torn]).unwrap();
+        STORAGE.write(torn, &[0; 16]).unwrap();
+
+        assert_eq!(load_count().as_deref(), Some("2"));
+    }
+
+    /// A slot written in another format version must be skipped.
+    #[kernel_test]
+    fn version_mismatch_falls_back() {
+        two_generations();
+
+        let base = slot_size(&STORAGE);
+        let mut header = [0; HEADER_LEN];
+        STORAGE.read(base, &mut header).unwrap();
+        header[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        let crc = common::crc32(&header[..16]);
+        header[16..].copy_from_slice(&crc.to_le_bytes());
+        STORAGE.write(base, &header).unwrap();
+
+        assert_eq!(load_count().as_deref(), Some("1"));
+    }
+
+    /// The generation after `u32::MAX` must win over `u32::MAX`.
+    #[kernel_test]
+    fn generation_wraps_around() {
+        let store = ConfigStore::new();
+        store.set("boot.count", "old").unwrap();
+        let old = store
+            .inner
+            .lock(|inner| encode(&inner.entries, u32::MAX))
+            .unwrap();
+        store.set("boot.count", "new").unwrap();
+        let new = store.inner.lock(|inner| encode(&inner.entries, 0)).unwrap();
+        STORAGE.write(0, &old).unwrap();
+        STORAGE.write(slot_size(&STORAGE), &new).unwrap();
+
+        assert_eq!(load_count().as_deref(), Some("new"));
+    }
+}
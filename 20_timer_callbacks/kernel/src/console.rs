@@ -3,10 +3,41 @@
 // Copyright (c) 2018-2023 Andre Richter <andre.o.richter@gmail.com>
 
 //! System console.
+//!
+//! Printing goes to the registered console and every enabled sink, unless a session's output is
+//! installed with [`with_output()`]. The shell does so while running a command, so that the
+//! command's output reaches the console that issued it.
+//!
+//! Sinks are write-only destinations like the in-memory log or a framebuffer, registered with
+//! [`register_sink()`] and switched on and off by name with [`set_sink_enabled()`]. The registered
+//! console is the sink `console`, which can be switched off as well, as long as another sink stays
+//! on. Input is always read from the registered console.
+//!
+//! Tasks take turns writing to the console and the sinks through a [`BlockingMutex`], so a task
+//! that finds another one printing is parked instead of spinning. Handlers and code running with
+//! IRQs masked can't wait for it and write right away, which may interleave with the task's line.
 
 mod buffer_console;
+pub mod line_discipline;
+pub mod line_editor;
+pub mod log_console;
 
-use crate::synchronization;
+use crate::{
+    sched::BlockingMutex,
+    synchronization::{self, interface::Mutex, IRQSafeNullLock},
+};
+use core::fmt;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+#[derive(Copy, Clone)]
+struct SinkEntry {
+    name: &'static str,
+    sink: Option<Sink>,
+    enabled: bool,
+}
 
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
@@ -14,7 +45,9 @@ use crate::synchronization;
 
 /// Console interfaces.
 pub mod interface {
-    use core::fmt;
+    use crate::{cpu, exception, power, time};
+    use alloc::boxed::Box;
+    use core::{fmt, time::Duration};
 
     /// Console write functions.
     pub trait Write {
@@ -29,6 +62,12 @@ pub mod interface {
 
         /// Block until the last buffered character has been physically put on the TX wire.
         fn flush(&self);
+
+        /// Drain the output and stop the device. Characters written afterwards are dropped until
+        /// the device is initialized again.
+        fn shutdown(&self) {
+            self.flush();
+        }
     }
 
     /// Console read functions.
@@ -38,8 +77,85 @@ pub mod interface {
             ' '
         }
 
+        /// Take a received character, if there is one.
+        fn read_char_nonblocking(&self) -> Option<char> {
+            None
+        }
+
+        /// Wait up to `timeout` for a received character.
+        ///
+        /// The core sleeps in between, woken by the console's RX IRQ or by a one-shot timeout at
+        /// the deadline. With IRQs masked, e.g. in IRQ context, it spins instead, and only
+        /// characters that were already received can be returned.
+        fn read_char_timeout(&self, timeout: Duration) -> Option<char> {
+            let deadline = time::time_manager().uptime() + timeout;
+            let can_sleep = power::can_standby();
+            // Only there to wake the core.
+            let wakeup =
+                can_sleep.then(|| time::time_manager().set_timeout_once(timeout, Box::new(|| ())));
+
+            let c = loop {
+                // Checking and waiting happen with IRQs masked, so that a character arriving in
+                // between can't be missed. A pending IRQ still ends the wait.
+                let result = exception::asynchronous::exec_with_irq_masked(|| {
+                    if let Some(c) = self.read_char_nonblocking() {
+                        return Some(Some(c));
+                    }
+                    if time::time_manager().uptime() >= deadline {
+                        return Some(None);
+                    }
+                    if can_sleep {
+                        cpu::wait_for_interrupt();
+                    }
+
+                    None
+                });
+                if let Some(c) = result {
+                    break c;
+                }
+            };
+
+            if let Some(handle) = wakeup {
+                handle.cancel();
+            }
+            c
+        }
+
+        /// Take a complete received line into `buf`, without the newline, and return its length.
+        /// Returns 0 if no line is complete yet. Longer lines are truncated.
+        fn read_line(&self, _buf: &mut [u8]) -> usize {
+            0
+        }
+
         /// Clear RX buffers, if any.
         fn clear_rx(&self);
+
+        /// Take a character typed on another input device, e.g. a USB keyboard, as if it was
+        /// received. Consoles that can't take input this way drop it.
+        fn inject_char(&self, _c: char) {}
+    }
+
+    /// Serial line settings. Consoles that aren't serial lines keep the defaults.
+    pub trait Configure {
+        /// Return the line settings, or `None` if the console isn't a serial line.
+        fn line_settings(&self) -> Option<super::LineSettings> {
+            None
+        }
+
+        /// Change the baud rate.
+        fn set_baud(&self, _baud: u32) -> Result<(), &'static str> {
+            Err("Not a serial console")
+        }
+
+        /// Change the parity.
+        fn set_parity(&self, _parity: super::Parity) -> Result<(), &'static str> {
+            Err("Not a serial console")
+        }
+
+        /// Change the number of stop bits, 1 or 2.
+        fn set_stop_bits(&self, _bits: u8) -> Result<(), &'static str> {
+            Err("Not a serial console")
+        }
     }
 
     /// Console statistics.
@@ -56,15 +172,65 @@ pub mod interface {
     }
 
     /// Trait alias for a full-fledged console.
-    pub trait All: Write + Read + Statistics {}
+    pub trait All: Write + Read + Statistics + Configure {}
 }
 
+/// Parity of a serial line.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Parity {
+    None,
+    Even,
+    Odd,
+}
+
+/// Settings of a serial line with 8 data bits.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LineSettings {
+    pub baud: u32,
+    pub parity: Parity,
+    pub stop_bits: u8,
+}
+
+/// The console a session prints to.
+pub type Output = &'static (dyn interface::All + Sync);
+
+/// A write-only output destination.
+pub type Sink = &'static (dyn interface::Write + Sync);
+
+/// Number of sinks that can be registered, including `console` and `log`.
+pub const MAX_SINKS: usize = 4;
+
+/// Name of the sink standing for the registered console.
+pub const CONSOLE_SINK: &str = "console";
+
 //--------------------------------------------------------------------------------------------------
 // Global instances
 //--------------------------------------------------------------------------------------------------
 
-static CUR_CONSOLE: InitStateLock<&'static (dyn interface::All + Sync)> =
-    InitStateLock::new(&buffer_console::BUFFER_CONSOLE);
+static CUR_CONSOLE: InitStateLock<Output> = InitStateLock::new(&buffer_console::BUFFER_CONSOLE);
+
+/// Output installed by [`with_output()`], overriding the registered console.
+static CUR_OUTPUT: IRQSafeNullLock<Option<Output>> = IRQSafeNullLock::new(None);
+
+/// Registered sinks. The registered console is written to through the `console` entry, which has
+/// no sink of its own.
+static SINKS: IRQSafeNullLock<[Option<SinkEntry>; MAX_SINKS]> = IRQSafeNullLock::new([
+    Some(SinkEntry {
+        name: CONSOLE_SINK,
+        sink: None,
+        enabled: true,
+    }),
+    Some(SinkEntry {
+        name: "log",
+        sink: Some(&log_console::LOG_CONSOLE),
+        enabled: false,
+    }),
+    None,
+    None,
+]);
+
+/// Held by the task writing to the registered console and the sinks.
+static WRITER: BlockingMutex<()> = BlockingMutex::new(());
 
 //--------------------------------------------------------------------------------------------------
 // Public Code
@@ -72,7 +238,7 @@ static CUR_CONSOLE: InitStateLock<&'static (dyn interface::All + Sync)> =
 use synchronization::{interface::ReadWriteEx, InitStateLock};
 
 /// Register a new console.
-pub fn register_console(new_console: &'static (dyn interface::All + Sync)) {
+pub fn register_console(new_console: Output) {
     CUR_CONSOLE.write(|con| *con = new_console);
 
     static FIRST_SWITCH: InitStateLock<bool> = InitStateLock::new(true);
@@ -85,9 +251,156 @@ pub fn register_console(new_console: &'static (dyn interface::All + Sync)) {
     });
 }
 
+/// Return if a console was registered. Until then, printing goes to a buffer that is replayed on
+/// the first registered console.
+pub fn is_registered() -> bool {
+    let buffer = &buffer_console::BUFFER_CONSOLE as *const _ as *const ();
+
+    console() as *const dyn interface::All as *const () != buffer
+}
+
 /// Return a reference to the currently registered console.
 ///
 /// This is the global console used by all printing macros.
 pub fn console() -> &'static dyn interface::All {
     CUR_CONSOLE.read(|con| *con)
 }
+
+/// Return the registered console as a session output.
+pub fn console_output() -> Output {
+    CUR_CONSOLE.read(|con| *con)
+}
+
+/// Return the console that printing currently goes to: the installed session output, or the
+/// registered console.
+pub fn output() -> Output {
+    CUR_OUTPUT.lock(|out| *out).unwrap_or_else(console_output)
+}
+
+/// Run `f` with all printing going to `out`, then restore the previous output.
+///
+/// The override is global, which is sound only as long as `f` can't be preempted by code that
+/// prints, i.e. it runs with IRQs masked. Shell commands run from IRQ handlers, so it holds.
+pub fn with_output<R>(out: Output, f: impl FnOnce() -> R) -> R {
+    let previous = CUR_OUTPUT.lock(|cur| cur.replace(out));
+    let result = f();
+    CUR_OUTPUT.lock(|cur| *cur = previous);
+
+    result
+}
+
+/// Write `args` to the installed session output, or else to the registered console and every
+/// enabled sink. This is what the printing macros use.
+pub fn write_fmt(args: fmt::Arguments) -> fmt::Result {
+    if let Some(out) = CUR_OUTPUT.lock(|out| *out) {
+        return out.write_fmt(args);
+    }
+
+    // Copied out, so that no lock is held while the sinks write.
+    let sinks = SINKS.lock(|sinks| *sinks);
+    let write_all = || {
+        let mut result = Ok(());
+        for entry in sinks.iter().flatten().filter(|e| e.enabled) {
+            let written = match entry.sink {
+                Some(sink) => sink.write_fmt(args),
+                None => console().write_fmt(args),
+            };
+            result = result.and(written);
+        }
+
+        result
+    };
+
+    // Busy only if this can't wait.
+    WRITER
+        .lock_or_busy(|_| write_all())
+        .unwrap_or_else(|_| write_all())
+}
+
+/// Register a sink, enabled.
+pub fn register_sink(name: &'static str, sink: Sink) -> Result<(), &'static str> {
+    SINKS.lock(|sinks| {
+        if sinks.iter().flatten().any(|e| e.name == name) {
+            return Err("Sink already registered");
+        }
+
+        let slot = sinks
+            .iter_mut()
+            .find(|e| e.is_none())
+            .ok_or("No free sink slot")?;
+        *slot = Some(SinkEntry {
+            name,
+            sink: Some(sink),
+            enabled: true,
+        });
+
+        Ok(())
+    })
+}
+
+/// Switch the sink `name` on or off. The last enabled sink can't be switched off.
+pub fn set_sink_enabled(name: &str, enabled: bool) -> Result<(), &'static str> {
+    SINKS.lock(|sinks| {
+        let others_enabled = sinks.iter().flatten().any(|e| e.name != name && e.enabled);
+        let entry = sinks
+            .iter_mut()
+            .flatten()
+            .find(|e| e.name == name)
+            .ok_or("No such sink")?;
+
+        if !enabled && !others_enabled {
+            return Err("Can't switch off the last enabled sink");
+        }
+        entry.enabled = enabled;
+
+        Ok(())
+    })
+}
+
+/// Print the sinks.
+pub fn print_sinks() {
+    let sinks = SINKS.lock(|sinks| *sinks);
+
+    for entry in sinks.iter().flatten() {
+        crate::info!(
+            "      {:<10} {}",
+            entry.name,
+            if entry.enabled { "on" } else { "off" }
+        );
+    }
+}
+
+impl core::str::FromStr for Parity {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "even" => Ok(Self::Even),
+            "odd" => Ok(Self::Odd),
+            _ => Err("Expected none, even or odd"),
+        }
+    }
+}
+
+impl fmt::Display for LineSettings {
+    /// Print the settings in the usual short form, e.g. `921600 8N1`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Even => 'E',
+            Parity::Odd => 'O',
+        };
+
+        write!(f, "{} 8{}{}", self.baud, parity, self.stop_bits)
+    }
+}
+
+/// Print a final marker and shut the console down once everything written so far is on the wire.
+///
+/// Called last on the way out of the kernel, so that the final lines of output aren't cut off by a
+/// reset.
+pub fn shutdown() {
+    with_output(console_output(), || crate::info!("Console shut down"));
+    console().shutdown();
+}
@@ -104,5 +104,6 @@ impl interface::Read for BufferConsole {
     fn clear_rx(&self) {}
 }
 
+impl interface::Configure for BufferConsole {}
 impl interface::Statistics for BufferConsole {}
 impl interface::All for BufferConsole {}
@@ -0,0 +1,271 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Console line discipline.
+//!
+//! Translates characters between the console driver and the rest of the kernel: carriage returns
+//! on input, line feeds on output, and how control characters are shown. The defaults suit an
+//! interactive terminal; binary-ish protocols and other terminal emulators can turn translations
+//! off. The options are kept in the config store under `console.*`.
+
+use crate::{
+    config, info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+};
+use alloc::format;
+use core::fmt;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const KEY_CR_TO_LF: &str = "console.cr_to_lf";
+const KEY_LF_TO_CRLF: &str = "console.lf_to_crlf";
+const KEY_CONTROL: &str = "console.control";
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// How control characters other than line feed, carriage return and tab are written.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ControlChars {
+    /// Unchanged.
+    Raw,
+
+    /// In caret notation, e.g. `^C`.
+    Caret,
+
+    /// As an escaped hex code, e.g. `\x03`.
+    Hex,
+
+    /// Not at all.
+    Drop,
+}
+
+/// Line discipline options.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Options {
+    /// Convert received carriage returns to line feeds.
+    pub cr_to_lf: bool,
+
+    /// Expand written line feeds to carriage return and line feed.
+    pub lf_to_crlf: bool,
+
+    /// Rendering of written control characters.
+    pub control: ControlChars,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static OPTIONS: IRQSafeNullLock<Options> = IRQSafeNullLock::new(Options::DEFAULT);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn parse_switch(s: &str) -> Result<bool, &'static str> {
+    match s {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err("Expected on or off"),
+    }
+}
+
+fn switch(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl core::str::FromStr for ControlChars {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(Self::Raw),
+            "caret" => Ok(Self::Caret),
+            "hex" => Ok(Self::Hex),
+            "drop" => Ok(Self::Drop),
+            _ => Err("Unknown control character rendering"),
+        }
+    }
+}
+
+impl fmt::Display for ControlChars {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Raw => write!(f, "raw"),
+            Self::Caret => write!(f, "caret"),
+            Self::Hex => write!(f, "hex"),
+            Self::Drop => write!(f, "drop"),
+        }
+    }
+}
+
+impl Options {
+    /// Interactive terminal that sends carriage returns and handles bare line feeds.
+    pub const DEFAULT: Self = Self {
+        cr_to_lf: true,
+        lf_to_crlf: false,
+        control: ControlChars::Raw,
+    };
+
+    /// Translate a received character.
+    pub fn input(&self, c: char) -> char {
+        if self.cr_to_lf && c == '\r' {
+            '\n'
+        } else {
+            c
+        }
+    }
+
+    /// Translate a character to be written, passing the result to `emit` one character at a time.
+    pub fn output(&self, c: char, mut emit: impl FnMut(char)) {
+        match c {
+            '\n' => {
+                if self.lf_to_crlf {
+                    emit('\r');
+                }
+                emit('\n');
+            }
+            '\r' | '\t' => emit(c),
+            _ if c.is_ascii_control() => match self.control {
+                ControlChars::Raw => emit(c),
+                ControlChars::Caret => {
+                    emit('^');
+                    emit(((c as u8) ^ 0x40) as char);
+                }
+                ControlChars::Hex => {
+                    for b in [
+                        b'\\',
+                        b'x',
+                        HEX_DIGITS[c as usize >> 4],
+                        HEX_DIGITS[c as usize & 0xf],
+                    ] {
+                        emit(b as char);
+                    }
+                }
+                ControlChars::Drop => (),
+            },
+            _ => emit(c),
+        }
+    }
+
+    /// Change an option by name.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), &'static str> {
+        match name {
+            "cr_to_lf" => self.cr_to_lf = parse_switch(value)?,
+            "lf_to_crlf" => self.lf_to_crlf = parse_switch(value)?,
+            "control" => self.control = value.parse()?,
+            _ => return Err("Unknown option"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Return the current options.
+pub fn options() -> Options {
+    OPTIONS.lock(|o| *o)
+}
+
+/// Replace the current options.
+pub fn set_options(options: Options) {
+    OPTIONS.lock(|o| *o = options);
+}
+
+/// Translate a received character with the current options.
+pub fn input(c: char) -> char {
+    options().input(c)
+}
+
+/// Translate a character to be written with the current options.
+pub fn output(c: char, emit: impl FnMut(char)) {
+    options().output(c, emit)
+}
+
+/// Apply the options from the config store. Missing settings keep their defaults.
+pub fn load() -> Result<(), &'static str> {
+    let mut options = options();
+
+    for (key, name) in [
+        (KEY_CR_TO_LF, "cr_to_lf"),
+        (KEY_LF_TO_CRLF, "lf_to_crlf"),
+        (KEY_CONTROL, "control"),
+    ] {
+        if let Some(value) = config::store().get(key) {
+            options.set(name, &value)?;
+        }
+    }
+    set_options(options);
+
+    Ok(())
+}
+
+/// Write the options to the config store. They are persisted on the next save.
+pub fn store() -> Result<(), &'static str> {
+    let options = options();
+
+    config::store().set(KEY_CR_TO_LF, switch(options.cr_to_lf))?;
+    config::store().set(KEY_LF_TO_CRLF, switch(options.lf_to_crlf))?;
+    config::store().set(KEY_CONTROL, &format!("{}", options.control))
+}
+
+/// Print the current options.
+pub fn print() {
+    let options = options();
+
+    info!("      cr_to_lf:   {}", switch(options.cr_to_lf));
+    info!("      lf_to_crlf: {}", switch(options.lf_to_crlf));
+    info!("      control:    {}", options.control);
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use test_macros::kernel_test;
+
+    /// Output translation must expand line feeds and render control characters as configured.
+    #[kernel_test]
+    fn output_translation() {
+        let render = |options: &Options, s: &str| {
+            let mut out = String::new();
+            for c in s.chars() {
+                options.output(c, |o| out.push(o));
+            }
+            out
+        };
+
+        let mut options = Options::DEFAULT;
+        assert_eq!(render(&options, "a\x03\n"), "a\x03\n");
+        assert_eq!(options.input('\r'), '\n');
+
+        options.set("lf_to_crlf", "on").unwrap();
+        options.set("control", "caret").unwrap();
+        assert_eq!(render(&options, "a\x03\n"), "a^C\r\n");
+
+        options.set("control", "hex").unwrap();
+        assert_eq!(render(&options, "\x1b"), "\\x1b");
+
+        options.set("cr_to_lf", "off").unwrap();
+        assert_eq!(options.input('\r'), '\r');
+        assert!(options.set("control", "bogus").is_err());
+    }
+}
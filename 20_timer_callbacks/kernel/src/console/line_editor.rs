@@ -0,0 +1,371 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Command line editing.
+//!
+//! Collects the characters of a shell command as they are typed, after the line discipline's input
+//! translation. Backspace and delete remove characters, Ctrl-U removes everything before the
+//! cursor, Ctrl-C drops the line. The ANSI cursor keys move within the line and step through the
+//! last [`HISTORY_LEN`] commands, Home and End jump to either end.
+//!
+//! The editor doesn't write to the terminal itself. Each character yields an [`Echo`] holding what
+//! the driver sends back to redraw the line.
+
+use core::fmt::{self, Write};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const ESC: char = '\x1b';
+const CTRL_C: char = '\x03';
+const BACKSPACE: char = '\x08';
+const CTRL_U: char = '\x15';
+const DEL: char = '\x7f';
+const BELL: u8 = 0x07;
+
+/// Enough to redraw a full line and move the cursor across it.
+const ECHO_CAPACITY: usize = 2 * LINE_CAPACITY + 16;
+
+/// Progress through an escape sequence.
+#[derive(Copy, Clone)]
+enum Escape {
+    None,
+
+    /// Received ESC.
+    Start,
+
+    /// Received ESC and `[` or `O`, followed by the numeric parameter so far.
+    Csi(u8),
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Longest command in characters.
+pub const LINE_CAPACITY: usize = 64;
+
+/// Number of commands kept in the history.
+pub const HISTORY_LEN: usize = 8;
+
+/// A command line.
+#[derive(Copy, Clone)]
+pub struct Line {
+    buf: [u8; LINE_CAPACITY],
+    len: usize,
+}
+
+/// Characters to send back to the terminal.
+pub struct Echo {
+    buf: [u8; ECHO_CAPACITY],
+    len: usize,
+}
+
+/// Editing state of a command line.
+pub struct LineEditor {
+    line: Line,
+    cursor: usize,
+    escape: Escape,
+
+    /// Ring of past commands, `history_next` is where the next one goes.
+    history: [Line; HISTORY_LEN],
+    history_count: usize,
+    history_next: usize,
+
+    /// How far back the shown line is in the history, 0 for the line being typed.
+    browsing: usize,
+
+    /// The line being typed, while browsing the history.
+    draft: Line,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Line {
+    const EMPTY: Self = Self {
+        buf: [0; LINE_CAPACITY],
+        len: 0,
+    };
+
+    fn insert(&mut self, at: usize, b: u8) {
+        self.buf.copy_within(at..self.len, at + 1);
+        self.buf[at] = b;
+        self.len += 1;
+    }
+
+    fn remove(&mut self, from: usize, to: usize) {
+        self.buf.copy_within(to..self.len, from);
+        self.len -= to - from;
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl Echo {
+    fn push(&mut self, bytes: &[u8]) {
+        let n = bytes.len().min(ECHO_CAPACITY - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+        self.len += n;
+    }
+
+    /// Move the terminal cursor `n` columns, right if `right` is set.
+    fn move_cursor(&mut self, n: usize, right: bool) {
+        if n > 0 {
+            let _ = write!(self, "\x1b[{}{}", n, if right { 'C' } else { 'D' });
+        }
+    }
+}
+
+impl fmt::Write for Echo {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push(s.as_bytes());
+
+        Ok(())
+    }
+}
+
+impl LineEditor {
+    /// Redraw the line from column `from`, where the terminal cursor is, and place the terminal
+    /// cursor at the editing cursor.
+    fn refresh(&self, from: usize, echo: &mut Echo) {
+        echo.push(&self.line.bytes()[from..]);
+        echo.push(b"\x1b[K");
+        echo.move_cursor(self.line.len - self.cursor, false);
+    }
+
+    /// Replace the shown line, leaving the cursor at its end.
+    fn show(&mut self, line: Line, echo: &mut Echo) {
+        echo.move_cursor(self.cursor, false);
+        self.line = line;
+        self.cursor = line.len;
+        self.refresh(0, echo);
+    }
+
+    fn history_entry(&self, back: usize) -> Line {
+        self.history[(self.history_next + HISTORY_LEN - back) % HISTORY_LEN]
+    }
+
+    fn history_up(&mut self, echo: &mut Echo) {
+        if self.browsing == self.history_count {
+            return;
+        }
+        if self.browsing == 0 {
+            self.draft = self.line;
+        }
+
+        self.browsing += 1;
+        self.show(self.history_entry(self.browsing), echo);
+    }
+
+    fn history_down(&mut self, echo: &mut Echo) {
+        if self.browsing == 0 {
+            return;
+        }
+
+        self.browsing -= 1;
+        let line = match self.browsing {
+            0 => self.draft,
+            n => self.history_entry(n),
+        };
+        self.show(line, echo);
+    }
+
+    fn remember(&mut self, line: Line) {
+        if line.len == 0
+            || (self.history_count > 0 && self.history_entry(1).bytes() == line.bytes())
+        {
+            return;
+        }
+
+        self.history[self.history_next] = line;
+        self.history_next = (self.history_next + 1) % HISTORY_LEN;
+        self.history_count = (self.history_count + 1).min(HISTORY_LEN);
+    }
+
+    /// Act on the final character of an escape sequence with parameter `param`.
+    fn escape(&mut self, c: char, param: u8, echo: &mut Echo) {
+        match (c, param) {
+            ('A', _) => self.history_up(echo),
+            ('B', _) => self.history_down(echo),
+            ('C', _) if self.cursor < self.line.len => {
+                self.cursor += 1;
+                echo.move_cursor(1, true);
+            }
+            ('D', _) if self.cursor > 0 => {
+                self.cursor -= 1;
+                echo.move_cursor(1, false);
+            }
+            ('H', _) | ('~', 1) => {
+                echo.move_cursor(self.cursor, false);
+                self.cursor = 0;
+            }
+            ('F', _) | ('~', 4) => {
+                echo.move_cursor(self.line.len - self.cursor, true);
+                self.cursor = self.line.len;
+            }
+            ('~', 3) if self.cursor < self.line.len => {
+                self.line.remove(self.cursor, self.cursor + 1);
+                self.refresh(self.cursor, echo);
+            }
+            _ => (),
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Line {
+    /// The command as text.
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(self.bytes()).unwrap_or("")
+    }
+}
+
+impl Echo {
+    /// Create an empty instance.
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; ECHO_CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// The characters to send.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl LineEditor {
+    /// Create an instance with an empty line and history.
+    pub const fn new() -> Self {
+        Self {
+            line: Line::EMPTY,
+            cursor: 0,
+            escape: Escape::None,
+            history: [Line::EMPTY; HISTORY_LEN],
+            history_count: 0,
+            history_next: 0,
+            browsing: 0,
+            draft: Line::EMPTY,
+        }
+    }
+
+    /// Drop the line being typed. The history is kept.
+    pub fn clear(&mut self) {
+        self.line = Line::EMPTY;
+        self.cursor = 0;
+        self.escape = Escape::None;
+        self.browsing = 0;
+    }
+
+    /// Feed a received character. Returns the line once it is ended with a line feed.
+    pub fn input(&mut self, c: char, echo: &mut Echo) -> Option<Line> {
+        match self.escape {
+            Escape::Start => {
+                self.escape = match c {
+                    '[' | 'O' => Escape::Csi(0),
+                    _ => Escape::None,
+                };
+                return None;
+            }
+            Escape::Csi(param) => {
+                self.escape = Escape::None;
+                match c.to_digit(10) {
+                    Some(d) => {
+                        self.escape = Escape::Csi(param.saturating_mul(10).saturating_add(d as u8))
+                    }
+                    None => self.escape(c, param, echo),
+                }
+                return None;
+            }
+            Escape::None => (),
+        }
+
+        match c {
+            '\n' => {
+                let line = self.line;
+                echo.push(b"\n");
+                self.clear();
+                self.remember(line);
+
+                return Some(line);
+            }
+            ESC => self.escape = Escape::Start,
+            CTRL_C => {
+                echo.push(b"^C\n");
+                self.clear();
+            }
+            CTRL_U => {
+                echo.move_cursor(self.cursor, false);
+                self.line.remove(0, self.cursor);
+                self.cursor = 0;
+                self.refresh(0, echo);
+            }
+            BACKSPACE | DEL if self.cursor > 0 => {
+                echo.move_cursor(1, false);
+                self.cursor -= 1;
+                self.line.remove(self.cursor, self.cursor + 1);
+                self.refresh(self.cursor, echo);
+            }
+            ' '..='~' => {
+                if self.line.len == LINE_CAPACITY {
+                    echo.push(&[BELL]);
+                } else {
+                    self.line.insert(self.cursor, c as u8);
+                    self.cursor += 1;
+                    self.refresh(self.cursor - 1, echo);
+                }
+            }
+            _ => (),
+        }
+
+        None
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Editing keys must change the line at the cursor, and up/down must recall earlier commands
+    /// without losing the line being typed.
+    #[kernel_test]
+    fn edit_and_recall() {
+        let mut editor = LineEditor::new();
+        let feed = |editor: &mut LineEditor, s: &str| {
+            let mut line = None;
+            for c in s.chars() {
+                line = editor.input(c, &mut Echo::new()).or(line);
+            }
+            line
+        };
+
+        let line = feed(&mut editor, "lx\x7fs\x1b[D-\x1b[3~\n").unwrap();
+        assert_eq!(line.as_str(), "l-");
+
+        assert_eq!(
+            feed(&mut editor, "junk\x15uptime\n").unwrap().as_str(),
+            "uptime"
+        );
+        assert_eq!(feed(&mut editor, "abc\x03\n").unwrap().as_str(), "");
+
+        feed(&mut editor, "dr");
+        assert_eq!(feed(&mut editor, "\x1b[A\x1b[A\n").unwrap().as_str(), "l-");
+        feed(&mut editor, "dr");
+        assert_eq!(feed(&mut editor, "\x1b[A\x1b[B\n").unwrap().as_str(), "dr");
+    }
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! In-memory log.
+//!
+//! A console sink that keeps the last [`LOG_SIZE`] bytes of output in a ring, so that output can
+//! be read back after the fact, e.g. once a terminal was attached. Registered as the `log` sink,
+//! off until enabled.
+
+use super::interface;
+use crate::synchronization::{interface::Mutex, IRQSafeNullLock};
+use alloc::vec::Vec;
+use core::fmt;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+struct Ring {
+    buf: [u8; LOG_SIZE],
+    head: usize,
+    len: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of bytes the log keeps.
+pub const LOG_SIZE: usize = 16 * 1024;
+
+pub struct LogConsole {
+    ring: IRQSafeNullLock<Ring>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+pub static LOG_CONSOLE: LogConsole = LogConsole {
+    ring: IRQSafeNullLock::new(Ring::new()),
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Ring {
+    const fn new() -> Self {
+        Self {
+            // Zeroed, so this lands in .bss.
+            buf: [0; LOG_SIZE],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Append a byte, overwriting the oldest one once full.
+    fn push(&mut self, b: u8) {
+        self.buf[(self.head + self.len) % LOG_SIZE] = b;
+        if self.len == LOG_SIZE {
+            self.head = (self.head + 1) % LOG_SIZE;
+        } else {
+            self.len += 1;
+        }
+    }
+
+    fn push_char(&mut self, c: char) {
+        let mut utf8 = [0; 4];
+        c.encode_utf8(&mut utf8).bytes().for_each(|b| self.push(b));
+    }
+
+    fn contents(&self) -> Vec<u8> {
+        (0..self.len)
+            .map(|i| self.buf[(self.head + i) % LOG_SIZE])
+            .collect()
+    }
+}
+
+impl fmt::Write for Ring {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.bytes().for_each(|b| self.push(b));
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl LogConsole {
+    /// Return the logged output, oldest first.
+    pub fn contents(&self) -> Vec<u8> {
+        self.ring.lock(|r| r.contents())
+    }
+
+    /// Drop the logged output.
+    pub fn clear(&self) {
+        self.ring.lock(|r| {
+            r.head = 0;
+            r.len = 0;
+        });
+    }
+}
+
+impl interface::Write for LogConsole {
+    fn write_char(&self, c: char) {
+        self.ring.lock(|r| r.push_char(c));
+    }
+
+    fn write_array(&self, a: &[char]) {
+        self.ring.lock(|r| a.iter().for_each(|c| r.push_char(*c)));
+    }
+
+    fn write_fmt(&self, args: fmt::Arguments) -> fmt::Result {
+        self.ring.lock(|r| fmt::Write::write_fmt(r, args))
+    }
+
+    fn flush(&self) {}
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_macros::kernel_test;
+
+    /// Once full, the ring must drop the oldest bytes first.
+    #[kernel_test]
+    fn ring_keeps_newest() {
+        LOG_CONSOLE.clear();
+        LOG_CONSOLE
+            .ring
+            .lock(|r| (0..LOG_SIZE + 3).for_each(|i| r.push(i as u8)));
+
+        let contents = LOG_CONSOLE.contents();
+        LOG_CONSOLE.clear();
+        assert_eq!(contents.len(), LOG_SIZE);
+        assert_eq!(contents[0], 3);
+        assert_eq!(contents[LOG_SIZE - 1], (LOG_SIZE + 2) as u8);
+    }
+}
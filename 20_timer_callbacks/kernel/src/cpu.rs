@@ -11,11 +11,46 @@ mod arch_cpu;
 mod boot;
 
 pub mod smp;
+pub mod spin;
 
 //--------------------------------------------------------------------------------------------------
 // Architectural Public Reexports
 //--------------------------------------------------------------------------------------------------
-pub use arch_cpu::{nop, wait_forever};
+pub use arch_cpu::{
+    chainload, clean_invalidate_dcache, cycle_count, enable_cycle_counter, invalidate_icache, nop,
+    wait_for_interrupt, wait_forever,
+};
 
 #[cfg(feature = "test_build")]
 pub use arch_cpu::{qemu_exit_failure, qemu_exit_success};
+
+use crate::{bsp, exception, shutdown};
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Run the shutdown hooks, then reset the board with IRQs masked so that nothing restarts the
+/// watchdog meanwhile.
+fn reset(halt: bool) -> ! {
+    shutdown::run();
+
+    exception::asynchronous::local_irq_mask();
+    unsafe { bsp::driver::system_reset(halt) };
+
+    wait_forever()
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Run the shutdown hooks and restart the board.
+pub fn reboot() -> ! {
+    reset(false)
+}
+
+/// Run the shutdown hooks and halt the board. It stays halted until it is power-cycled.
+pub fn halt() -> ! {
+    reset(true)
+}
@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Bounded busy-waiting.
+//!
+//! [`spin_until!`](crate::spin_until) polls a condition until it holds or a timeout expires, so
+//! that a wedged device shows up as an error instead of a silent hang. The [`Timeout`] carries a
+//! register value of the device for diagnosis, and is recorded in the trace buffer as well, since
+//! a wedged console can't print it.
+
+use crate::{time, trace};
+use core::{fmt, time::Duration};
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A condition didn't hold in time.
+#[derive(Copy, Clone, Debug)]
+pub struct Timeout {
+    /// What was waited for.
+    pub context: &'static str,
+
+    /// Register state when giving up.
+    pub state: u32,
+
+    /// How long was waited.
+    pub waited: Duration,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Spin until `$condition` holds, at most for `$timeout`. Evaluates to `Ok(())`, or to a
+/// [`Timeout`] with `$context` and the value of `$state` evaluated at the time of giving up.
+#[macro_export]
+macro_rules! spin_until {
+    ($condition:expr, $timeout:expr, $context:expr, $state:expr) => {{
+        let start = $crate::time::time_manager().uptime();
+
+        loop {
+            if $condition {
+                break Ok(());
+            }
+
+            let waited = $crate::time::time_manager().uptime() - start;
+            if waited > $timeout {
+                break Err($crate::cpu::spin::timed_out($context, $state, waited));
+            }
+            $crate::cpu::nop();
+        }
+    }};
+}
+
+#[doc(hidden)]
+pub fn timed_out(context: &'static str, state: u32, waited: Duration) -> Timeout {
+    trace::record("spin", context, state as u64);
+
+    Timeout {
+        context,
+        state,
+        waited,
+    }
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: no progress after {}, state {:#010x}",
+            self.context,
+            time::Human(self.waited),
+            self.state
+        )
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A condition that holds must return at once, one that never holds must time out with the
+    /// context and state.
+    #[kernel_test]
+    fn spin_times_out() {
+        let timeout = Duration::from_millis(1);
+
+        assert!(crate::spin_until!(true, timeout, "true", 0).is_ok());
+
+        let result = crate::spin_until!(false, timeout, "false", 0xAB);
+        let err = result.unwrap_err();
+        assert_eq!(err.context, "false");
+        assert_eq!(err.state, 0xAB);
+        assert!(err.waited > timeout);
+    }
+}
@@ -0,0 +1,527 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! C runtime support.
+//!
+//! Lets freestanding C code, e.g. the Dhrystone and CoreMark benchmarks or vendor drivers, be
+//! linked into the kernel. Built with the `c_runtime` feature, which `make C_SOURCES=...` turns on
+//! after compiling the sources into an archive. The build script links the archive, and makes the
+//! entry points listed in `C_ENTRIES` callable with [`run()`] and the `cexec` command. An entry
+//! point has the signature of `main()`.
+//!
+//! The C code gets:
+//!
+//! - `memcpy()`, `memmove()`, `memset()`, `memcmp()`, `strlen()`, `strcmp()` and `strcpy()`. They
+//!   are weak, so the compiler's own versions win where they exist.
+//! - `printf()`, `puts()` and `putchar()`, printing to the console. `printf()` knows the flags `-`
+//!   and `0`, a width, a precision for strings, the length modifiers `h`, `l`, `ll` and `z`, and
+//!   the conversions `d`, `i`, `u`, `x`, `X`, `o`, `c`, `s`, `p` and `%`.
+//! - `malloc()`, `calloc()` and `free()` on the kernel heap.
+//! - `khros_uptime_us()`, the uptime in microseconds, for timing.
+//!
+//! There is no floating point: the kernel doesn't save the FP registers, so C code must be built
+//! with `-mgeneral-regs-only`.
+
+// The exported functions follow the C library's contracts.
+#![allow(clippy::missing_safety_doc)]
+
+use crate::{print, time};
+use alloc::{alloc::Layout, vec::Vec};
+use core::{
+    ffi::{c_char, c_int, c_void, CStr, VaListImpl},
+    fmt::{self, Write},
+    ptr,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// An entry point of the linked C code.
+type EntryFn = unsafe extern "C" fn(argc: c_int, argv: *const *const c_char) -> c_int;
+
+include!(concat!(env!("OUT_DIR"), "/c_entries.rs"));
+
+/// Bytes in front of each `malloc()` block, holding its size. Also the blocks' alignment.
+const MALLOC_HEADER: usize = 16;
+
+/// Source of the arguments of `printf()`.
+trait Args {
+    /// Fetch a signed integer, a `long` if `long` is set.
+    unsafe fn int(&mut self, long: bool) -> i64;
+
+    /// Fetch an unsigned integer, an `unsigned long` if `long` is set.
+    unsafe fn uint(&mut self, long: bool) -> u64;
+
+    /// Fetch a pointer.
+    unsafe fn ptr(&mut self) -> usize;
+}
+
+/// Counts what is written through it.
+struct Counter<'a> {
+    out: &'a mut dyn Write,
+    count: usize,
+}
+
+/// A conversion specification.
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    long: bool,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Args for VaListImpl<'_> {
+    unsafe fn int(&mut self, long: bool) -> i64 {
+        if long {
+            self.arg::<i64>()
+        } else {
+            self.arg::<c_int>() as i64
+        }
+    }
+
+    unsafe fn uint(&mut self, long: bool) -> u64 {
+        if long {
+            self.arg::<u64>()
+        } else {
+            self.arg::<u32>() as u64
+        }
+    }
+
+    unsafe fn ptr(&mut self) -> usize {
+        self.arg::<usize>()
+    }
+}
+
+impl Write for Counter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.count += s.len();
+        self.out.write_str(s)
+    }
+}
+
+impl Counter<'_> {
+    fn pad(&mut self, n: usize, c: char) {
+        for _ in 0..n {
+            let _ = self.write_char(c);
+        }
+    }
+
+    /// Write bytes as Latin-1 characters.
+    fn bytes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            let _ = self.write_char(*b as char);
+        }
+    }
+
+    /// Write `body` after `sign`, padded to the width of `spec`.
+    fn field(&mut self, spec: &Spec, sign: &str, body: &[u8]) {
+        let fill = spec.width.saturating_sub(sign.len() + body.len());
+
+        if spec.left {
+            let _ = self.write_str(sign);
+            self.bytes(body);
+            self.pad(fill, ' ');
+        } else if spec.zero {
+            let _ = self.write_str(sign);
+            self.pad(fill, '0');
+            self.bytes(body);
+        } else {
+            self.pad(fill, ' ');
+            let _ = self.write_str(sign);
+            self.bytes(body);
+        }
+    }
+}
+
+/// Write the digits of `value` in `radix` to the end of `buf` and return them.
+fn digits(buf: &mut [u8; 24], mut value: u64, radix: u64, upper: bool) -> &[u8] {
+    let set = if upper {
+        b"0123456789ABCDEF"
+    } else {
+        b"0123456789abcdef"
+    };
+
+    let mut pos = buf.len();
+    loop {
+        pos -= 1;
+        buf[pos] = set[(value % radix) as usize];
+        value /= radix;
+        if value == 0 {
+            break;
+        }
+    }
+
+    &buf[pos..]
+}
+
+/// Format `fmt` with the arguments from `args` to `out`. Returns the number of bytes written.
+unsafe fn format(out: &mut dyn Write, fmt: &[u8], args: &mut impl Args) -> usize {
+    let mut out = Counter { out, count: 0 };
+    let mut buf = [0; 24];
+    let mut i = 0;
+
+    let number = |fmt: &[u8], i: &mut usize| {
+        let mut n = 0;
+        while let Some(d) = fmt.get(*i).filter(|c| c.is_ascii_digit()) {
+            n = n * 10 + (d - b'0') as usize;
+            *i += 1;
+        }
+        n
+    };
+
+    while i < fmt.len() {
+        if fmt[i] != b'%' {
+            out.bytes(&fmt[i..=i]);
+            i += 1;
+            continue;
+        }
+        i += 1;
+
+        let mut spec = Spec::default();
+        while let Some(flag) = fmt.get(i) {
+            match flag {
+                b'-' => spec.left = true,
+                b'0' => spec.zero = true,
+                b' ' | b'+' | b'#' => (),
+                _ => break,
+            }
+            i += 1;
+        }
+        if fmt.get(i) == Some(&b'*') {
+            spec.width = args.int(false).max(0) as usize;
+            i += 1;
+        } else {
+            spec.width = number(fmt, &mut i);
+        }
+        if fmt.get(i) == Some(&b'.') {
+            i += 1;
+            spec.precision = Some(number(fmt, &mut i));
+        }
+        while let Some(modifier) = fmt.get(i) {
+            match modifier {
+                b'l' | b'z' | b'j' | b't' => spec.long = true,
+                b'h' => (),
+                _ => break,
+            }
+            i += 1;
+        }
+
+        let conversion = match fmt.get(i) {
+            Some(c) => *c,
+            None => break,
+        };
+        i += 1;
+
+        match conversion {
+            b'd' | b'i' => {
+                let value = args.int(spec.long);
+                let sign = if value < 0 { "-" } else { "" };
+                let body = digits(&mut buf, value.unsigned_abs(), 10, false);
+                out.field(&spec, sign, body);
+            }
+            b'u' | b'x' | b'X' | b'o' => {
+                let radix = match conversion {
+                    b'u' => 10,
+                    b'o' => 8,
+                    _ => 16,
+                };
+                let body = digits(&mut buf, args.uint(spec.long), radix, conversion == b'X');
+                out.field(&spec, "", body);
+            }
+            b'p' => {
+                let body = digits(&mut buf, args.ptr() as u64, 16, false);
+                out.field(&spec, "0x", body);
+            }
+            b'c' => {
+                let c = [args.int(false) as u8];
+                spec.zero = false;
+                out.field(&spec, "", &c);
+            }
+            b's' => {
+                let s = match args.ptr() {
+                    0 => &b"(null)"[..],
+                    p => CStr::from_ptr(p as *const c_char).to_bytes(),
+                };
+                let s = &s[..spec.precision.unwrap_or(s.len()).min(s.len())];
+                spec.zero = false;
+                out.field(&spec, "", s);
+            }
+            b'%' => {
+                let _ = out.write_char('%');
+            }
+            _ => (),
+        }
+    }
+
+    out.count
+}
+
+/// The console as a [`Write`] target.
+struct Console;
+
+impl Write for Console {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        print!("{}", s);
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return the names of the linked C entry points.
+pub fn entries() -> impl Iterator<Item = &'static str> {
+    ENTRIES.iter().map(|(name, _)| *name)
+}
+
+/// Call the C entry point `name` with `args` as its `argv`, the first being the program name.
+/// Returns what it returned.
+pub fn run(name: &str, args: &[&str]) -> Result<i32, &'static str> {
+    let entry = ENTRIES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, entry)| *entry)
+        .ok_or("No such C entry point")?;
+
+    let strings: Vec<Vec<u8>> = args
+        .iter()
+        .map(|a| a.bytes().chain(core::iter::once(0)).collect())
+        .collect();
+    let mut argv: Vec<*const c_char> = strings.iter().map(|s| s.as_ptr().cast()).collect();
+    argv.push(ptr::null());
+
+    Ok(unsafe { entry(args.len() as c_int, argv.as_ptr()) })
+}
+
+#[no_mangle]
+#[linkage = "weak"]
+pub unsafe extern "C" fn memcpy(dst: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    // Volatile, so that the compiler can't turn the loops into calls to this very function.
+    let mut i = 0;
+    if (dst as usize | src as usize) % 8 == 0 {
+        while i + 8 <= n {
+            ptr::write_volatile(
+                dst.add(i).cast::<u64>(),
+                ptr::read_volatile(src.add(i).cast()),
+            );
+            i += 8;
+        }
+    }
+    while i < n {
+        ptr::write_volatile(dst.add(i), ptr::read_volatile(src.add(i)));
+        i += 1;
+    }
+
+    dst
+}
+
+#[no_mangle]
+#[linkage = "weak"]
+pub unsafe extern "C" fn memmove(dst: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    if (dst as usize) <= (src as usize) || (dst as usize) >= (src as usize) + n {
+        return memcpy(dst, src, n);
+    }
+
+    for i in (0..n).rev() {
+        ptr::write_volatile(dst.add(i), ptr::read_volatile(src.add(i)));
+    }
+
+    dst
+}
+
+#[no_mangle]
+#[linkage = "weak"]
+pub unsafe extern "C" fn memset(dst: *mut u8, c: c_int, n: usize) -> *mut u8 {
+    let b = c as u8;
+    let mut i = 0;
+    if dst as usize % 8 == 0 {
+        let word = u64::from_ne_bytes([b; 8]);
+        while i + 8 <= n {
+            ptr::write_volatile(dst.add(i).cast::<u64>(), word);
+            i += 8;
+        }
+    }
+    while i < n {
+        ptr::write_volatile(dst.add(i), b);
+        i += 1;
+    }
+
+    dst
+}
+
+#[no_mangle]
+#[linkage = "weak"]
+pub unsafe extern "C" fn memcmp(a: *const u8, b: *const u8, n: usize) -> c_int {
+    for i in 0..n {
+        let (x, y) = (ptr::read_volatile(a.add(i)), ptr::read_volatile(b.add(i)));
+        if x != y {
+            return x as c_int - y as c_int;
+        }
+    }
+
+    0
+}
+
+#[no_mangle]
+#[linkage = "weak"]
+pub unsafe extern "C" fn strlen(s: *const c_char) -> usize {
+    let mut n = 0;
+    while ptr::read_volatile(s.add(n)) != 0 {
+        n += 1;
+    }
+
+    n
+}
+
+#[no_mangle]
+#[linkage = "weak"]
+pub unsafe extern "C" fn strcmp(a: *const c_char, b: *const c_char) -> c_int {
+    let mut i = 0;
+    loop {
+        let (x, y) = (*a.add(i) as u8, *b.add(i) as u8);
+        if x != y || x == 0 {
+            return x as c_int - y as c_int;
+        }
+        i += 1;
+    }
+}
+
+#[no_mangle]
+#[linkage = "weak"]
+pub unsafe extern "C" fn strcpy(dst: *mut c_char, src: *const c_char) -> *mut c_char {
+    memcpy(dst.cast(), src.cast(), strlen(src) + 1);
+
+    dst
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn printf(fmt: *const c_char, mut args: ...) -> c_int {
+    format(&mut Console, CStr::from_ptr(fmt).to_bytes(), &mut args) as c_int
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn puts(s: *const c_char) -> c_int {
+    let s = CStr::from_ptr(s).to_bytes();
+    s.iter().for_each(|b| print!("{}", *b as char));
+    print!("\n");
+
+    s.len() as c_int + 1
+}
+
+#[no_mangle]
+pub extern "C" fn putchar(c: c_int) -> c_int {
+    print!("{}", c as u8 as char);
+
+    c
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn malloc(size: usize) -> *mut c_void {
+    let layout = match Layout::from_size_align(size + MALLOC_HEADER, MALLOC_HEADER) {
+        Ok(layout) => layout,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let block = alloc::alloc::alloc(layout);
+    if block.is_null() {
+        return ptr::null_mut();
+    }
+    block.cast::<usize>().write(size);
+
+    block.add(MALLOC_HEADER).cast()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn calloc(count: usize, size: usize) -> *mut c_void {
+    let size = match count.checked_mul(size) {
+        Some(size) => size,
+        None => return ptr::null_mut(),
+    };
+
+    let block = malloc(size);
+    if !block.is_null() {
+        memset(block.cast(), 0, size);
+    }
+
+    block
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn free(p: *mut c_void) {
+    if p.is_null() {
+        return;
+    }
+
+    let block = p.cast::<u8>().sub(MALLOC_HEADER);
+    let size = block.cast::<usize>().read();
+    alloc::alloc::dealloc(
+        block,
+        Layout::from_size_align_unchecked(size + MALLOC_HEADER, MALLOC_HEADER),
+    );
+}
+
+#[no_mangle]
+pub extern "C" fn khros_uptime_us() -> u64 {
+    time::time_manager().uptime().as_micros() as u64
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use test_macros::kernel_test;
+
+    impl Args for core::slice::Iter<'_, u64> {
+        unsafe fn int(&mut self, _long: bool) -> i64 {
+            *self.next().unwrap() as i64
+        }
+
+        unsafe fn uint(&mut self, _long: bool) -> u64 {
+            *self.next().unwrap()
+        }
+
+        unsafe fn ptr(&mut self) -> usize {
+            *self.next().unwrap() as usize
+        }
+    }
+
+    /// Conversions must honor flags, width and precision like the C library's `printf()`.
+    #[kernel_test]
+    fn printf_conversions() {
+        let s = b"hello\0";
+        let args = [
+            -42i64 as u64,
+            255,
+            255,
+            s.as_ptr() as u64,
+            s.as_ptr() as u64,
+            b'x' as u64,
+        ];
+
+        let mut out = String::new();
+        let n = unsafe {
+            format(
+                &mut out,
+                b"[%5d|%-4x|%08X|%.3s|%7s|%c|%%]",
+                &mut args.iter(),
+            )
+        };
+
+        assert_eq!(out, "[  -42|ff  |000000FF|hel|  hello|x|%]");
+        assert_eq!(n, out.len());
+    }
+}
@@ -0,0 +1,248 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Hardware diagnostics.
+//!
+//! A quick check of the parts a broken setup usually shows up in first, printed as a PASS/FAIL
+//! table:
+//!
+//! - Timer: the system counter is measured against the CPU's cycle counter. A counter frequency
+//!   that config.txt or the firmware got wrong puts the CPU clock out of any plausible range.
+//! - UART: the console UART sends a pattern to itself in loopback mode. Skipped unless the
+//!   `diag.uart_loopback` setting is `on`, as output is held back meanwhile.
+//! - Heap: a block is written and verified with several patterns.
+//! - SD card: the firmware must report the card as present and powered.
+//! - Mailbox: the firmware must answer a property request.
+//!
+//! With the `diag.boot` setting `on`, the checks run at the end of the kernel's init.
+
+use crate::{bsp, config, cpu, info, time, warn};
+use alloc::{format, string::String, vec::Vec};
+use core::{fmt, time::Duration};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const KEY_BOOT: &str = "diag.boot";
+const KEY_UART_LOOPBACK: &str = "diag.uart_loopback";
+
+/// How long the timer is measured against the cycle counter.
+const TIMER_SAMPLE: Duration = Duration::from_millis(10);
+
+/// Plausible CPU clocks of the supported boards, with room for QEMU.
+const CPU_MHZ_MIN: u64 = 100;
+const CPU_MHZ_MAX: u64 = 3_000;
+
+/// Size of the heap block checked, in words.
+const HEAP_WORDS: usize = 16 * 1024;
+
+/// Patterns written to the heap block. The last one is replaced by each word's index.
+const HEAP_PATTERNS: [u32; 3] = [0x5555_5555, 0xAAAA_AAAA, 0];
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Result of a check.
+pub enum Outcome {
+    Pass,
+    Fail,
+    Skip,
+}
+
+/// A check and its result.
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    pub detail: String,
+}
+
+/// Results of all checks.
+pub struct Report {
+    pub checks: Vec<Check>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Check {
+    fn new(name: &'static str, result: Result<String, String>) -> Self {
+        let (outcome, detail) = match result {
+            Ok(detail) => (Outcome::Pass, detail),
+            Err(detail) => (Outcome::Fail, detail),
+        };
+
+        Self {
+            name,
+            outcome,
+            detail,
+        }
+    }
+}
+
+fn check_timer() -> Result<String, String> {
+    let resolution = time::time_manager().resolution();
+    if resolution.is_zero() || resolution > Duration::from_micros(1) {
+        return Err(format!("resolution {}", time::Human(resolution)));
+    }
+
+    let start = time::time_manager().uptime();
+    let cycles = cpu::cycle_count();
+    time::time_manager().spin_for(TIMER_SAMPLE);
+    let cycles = cpu::cycle_count() - cycles;
+    let elapsed = time::time_manager().uptime() - start;
+
+    let mhz = cycles / elapsed.as_micros().max(1) as u64;
+    let detail = format!("resolution {}, CPU {} MHz", time::Human(resolution), mhz);
+    if !(CPU_MHZ_MIN..=CPU_MHZ_MAX).contains(&mhz) {
+        return Err(detail);
+    }
+
+    Ok(detail)
+}
+
+fn check_uart() -> Check {
+    if config::store().get(KEY_UART_LOOPBACK).as_deref() != Some("on") {
+        return Check {
+            name: "uart",
+            outcome: Outcome::Skip,
+            detail: format!("{} is off", KEY_UART_LOOPBACK),
+        };
+    }
+
+    let result = unsafe { bsp::driver::uart_loopback_test() };
+    Check::new(
+        "uart",
+        result
+            .map(|_| String::from("loopback ok"))
+            .map_err(String::from),
+    )
+}
+
+/// Write the patterns to `words` and return the number of words that read back wrong.
+fn pattern_errors(words: &mut [u32]) -> usize {
+    let mut errors = 0;
+
+    for pattern in HEAP_PATTERNS {
+        let value = |i: usize| if pattern == 0 { i as u32 } else { pattern };
+        for (i, w) in words.iter_mut().enumerate() {
+            unsafe { core::ptr::write_volatile(w, value(i)) };
+        }
+        errors += words
+            .iter()
+            .enumerate()
+            .filter(|(i, w)| unsafe { core::ptr::read_volatile(*w) } != value(*i))
+            .count();
+    }
+
+    errors
+}
+
+fn check_heap() -> Result<String, String> {
+    let mut words = Vec::new();
+    words
+        .try_reserve_exact(HEAP_WORDS)
+        .map_err(|_| String::from("allocation failed"))?;
+    words.resize(HEAP_WORDS, 0u32);
+
+    match pattern_errors(&mut words) {
+        0 => Ok(format!("{} KiB verified", HEAP_WORDS * 4 / 1024)),
+        n => Err(format!("{} words wrong", n)),
+    }
+}
+
+fn check_sd_card() -> Result<String, String> {
+    match unsafe { bsp::driver::sd_card_powered() } {
+        Ok(true) => Ok(String::from("present")),
+        Ok(false) => Err(String::from("missing or unpowered")),
+        Err(x) => Err(String::from(x)),
+    }
+}
+
+fn check_mailbox() -> Result<String, String> {
+    unsafe { bsp::driver::firmware_revision() }
+        .map(|revision| format!("firmware {:#x}", revision))
+        .map_err(String::from)
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Pass => f.pad("PASS"),
+            Self::Fail => f.pad("FAIL"),
+            Self::Skip => f.pad("SKIP"),
+        }
+    }
+}
+
+impl Report {
+    /// Return the number of failed checks.
+    pub fn failed(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|c| matches!(c.outcome, Outcome::Fail))
+            .count()
+    }
+
+    /// Print the table.
+    pub fn print(&self) {
+        for c in self.checks.iter() {
+            info!("      {:<8} {:<4}  {}", c.name, c.outcome, c.detail);
+        }
+    }
+}
+
+/// Run all checks.
+pub fn run() -> Report {
+    Report {
+        checks: alloc::vec![
+            Check::new("timer", check_timer()),
+            check_uart(),
+            Check::new("heap", check_heap()),
+            Check::new("sd card", check_sd_card()),
+            Check::new("mailbox", check_mailbox()),
+        ],
+    }
+}
+
+/// Run the checks and print the table if the `diag.boot` setting is `on`. Must be called after
+/// the config store was loaded.
+pub fn run_at_boot() {
+    if config::store().get(KEY_BOOT).as_deref() != Some("on") {
+        return;
+    }
+
+    info!("Hardware diagnostics:");
+    let report = run();
+    report.print();
+    if report.failed() > 0 {
+        warn!("{} diagnostic checks failed", report.failed());
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Working memory must read back every pattern, and the block must end up holding the index
+    /// pattern.
+    #[kernel_test]
+    fn heap_patterns_verify() {
+        let mut words = [0u32; 64];
+
+        assert_eq!(pattern_errors(&mut words), 0);
+        assert_eq!(words[63], 63);
+    }
+}
@@ -0,0 +1,367 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! DMA offload.
+//!
+//! [`fill()`] hands large memory fills to the DMA engine the BSP registered with
+//! [`register_engine()`]. The engine transfers whole cache lines only, so that it never shares a
+//! line with data the CPU writes meanwhile. The unaligned head and tail, small fills, and fills
+//! without an engine or whose transfer failed are done by the CPU, so [`fill()`] always succeeds.
+//!
+//! [`start()`] runs a [`Transfer`] in the background instead: a chain of copies within a buffer and
+//! moves between the buffer and peripheral FIFOs. The transfer owns the buffer until it ended, and
+//! every step is checked against the buffer as it is added, so the engine can't reach any other
+//! memory. The completion is called in IRQ context with the buffer.
+
+use crate::{
+    info,
+    synchronization::{
+        interface::{Mutex, ReadWriteEx},
+        IRQSafeNullLock, InitStateLock,
+    },
+    time,
+};
+use alloc::{boxed::Box, vec, vec::Vec};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Largest cache line of the supported cores. The engine's part of a fill is aligned to it.
+const CACHE_LINE: usize = 64;
+
+/// Fills below this size are faster on the CPU than setting up a transfer.
+const MIN_DMA_LEN: usize = 4096;
+
+struct Stats {
+    dma_bytes: u64,
+    cpu_bytes: u64,
+    fallbacks: u64,
+    transfers: u64,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// DMA interfaces.
+pub mod interface {
+    use super::{Completion, Transfer};
+
+    /// A DMA engine that can fill memory and run transfers.
+    pub trait Engine {
+        /// Name of the engine.
+        fn name(&self) -> &'static str;
+
+        /// Set every byte of `dst` to `value`. Blocks until the transfer ended.
+        fn fill(&self, dst: &mut [u8], value: u8) -> Result<(), &'static str>;
+
+        /// Start `transfer` and return. `done` is called in IRQ context when it ended.
+        fn start(&self, transfer: Transfer, done: Completion) -> Result<(), &'static str>;
+    }
+}
+
+/// A peripheral FIFO that transfers can write to and read from, paced by the peripheral's data
+/// requests. The peripheral's driver must have enabled them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Peripheral {
+    Uart,
+    Spi,
+    Pwm,
+}
+
+/// One step of a transfer. Offsets and lengths are in bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Copy `len` bytes of the buffer from `from` to `to`.
+    Copy { from: usize, to: usize, len: usize },
+
+    /// Write `len` bytes of the buffer from `from` to a peripheral.
+    Send {
+        from: usize,
+        len: usize,
+        to: Peripheral,
+    },
+
+    /// Read `len` bytes from a peripheral into the buffer at `to`.
+    Receive {
+        from: Peripheral,
+        to: usize,
+        len: usize,
+    },
+}
+
+/// Steps run by the engine one after the other, on a buffer the transfer owns.
+pub struct Transfer {
+    buffer: Vec<u8>,
+    steps: Vec<Step>,
+}
+
+/// Called in IRQ context when a transfer ended, with its buffer and whether all steps succeeded.
+pub type Completion = Box<dyn FnOnce(Vec<u8>, Result<(), &'static str>) + Send>;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static CUR_ENGINE: InitStateLock<Option<&'static (dyn interface::Engine + Sync)>> =
+    InitStateLock::new(None);
+
+static STATS: IRQSafeNullLock<Stats> = IRQSafeNullLock::new(Stats {
+    dma_bytes: 0,
+    cpu_bytes: 0,
+    fallbacks: 0,
+    transfers: 0,
+});
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Split a fill of `len` bytes at `addr` into the lengths of the unaligned head and of the
+/// cache-line aligned middle. The rest is the tail.
+fn split(addr: usize, len: usize) -> (usize, usize) {
+    let head = ((CACHE_LINE - addr % CACHE_LINE) % CACHE_LINE).min(len);
+    let middle = (len - head) / CACHE_LINE * CACHE_LINE;
+
+    (head, middle)
+}
+
+impl Transfer {
+    /// Check that `len` bytes at `offset` are within the buffer.
+    fn check(&self, offset: usize, len: usize) -> Result<(), &'static str> {
+        match offset.checked_add(len) {
+            Some(end) if len > 0 && end <= self.buffer.len() => Ok(()),
+            _ => Err("Range outside the buffer"),
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Transfer {
+    /// Peripheral FIFOs are accessed in words, so steps to and from them move whole words.
+    pub const FIFO_WORD: usize = 4;
+
+    /// Create a transfer without steps on `buffer`.
+    pub fn new(buffer: Vec<u8>) -> Self {
+        Self {
+            buffer,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Add a copy of `len` bytes from `from` to `to`. The ranges must not overlap.
+    pub fn copy(mut self, from: usize, to: usize, len: usize) -> Result<Self, &'static str> {
+        self.check(from, len)?;
+        self.check(to, len)?;
+        if from < to + len && to < from + len {
+            return Err("Ranges overlap");
+        }
+
+        self.steps.push(Step::Copy { from, to, len });
+
+        Ok(self)
+    }
+
+    /// Add writing `len` bytes from `from` to a peripheral.
+    pub fn send(mut self, from: usize, len: usize, to: Peripheral) -> Result<Self, &'static str> {
+        self.check(from, len)?;
+        if len % Self::FIFO_WORD != 0 {
+            return Err("Length is not a multiple of the FIFO word");
+        }
+
+        self.steps.push(Step::Send { from, len, to });
+
+        Ok(self)
+    }
+
+    /// Add reading `len` bytes from a peripheral into `to`.
+    pub fn receive(
+        mut self,
+        from: Peripheral,
+        to: usize,
+        len: usize,
+    ) -> Result<Self, &'static str> {
+        self.check(to, len)?;
+        if len % Self::FIFO_WORD != 0 {
+            return Err("Length is not a multiple of the FIFO word");
+        }
+
+        self.steps.push(Step::Receive { from, to, len });
+
+        Ok(self)
+    }
+
+    /// The buffer.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Take the buffer, to hand it to the engine or back to the user.
+    pub fn into_buffer(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    /// The steps in the order they run.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+/// Register the DMA engine.
+pub fn register_engine(new_engine: &'static (dyn interface::Engine + Sync)) {
+    CUR_ENGINE.write(|e| *e = Some(new_engine));
+}
+
+/// Set every byte of `dst` to `value`, offloading large fills to the DMA engine.
+pub fn fill(dst: &mut [u8], value: u8) {
+    let engine = match CUR_ENGINE.read(|e| *e) {
+        Some(engine) if dst.len() >= MIN_DMA_LEN => engine,
+        _ => {
+            dst.fill(value);
+            STATS.lock(|s| s.cpu_bytes += dst.len() as u64);
+            return;
+        }
+    };
+
+    let (head, middle) = split(dst.as_ptr() as usize, dst.len());
+    let (head, rest) = dst.split_at_mut(head);
+    let (middle, tail) = rest.split_at_mut(middle);
+
+    head.fill(value);
+    tail.fill(value);
+    let result = engine.fill(middle, value);
+    if result.is_err() {
+        middle.fill(value);
+    }
+
+    STATS.lock(|s| {
+        s.cpu_bytes += (head.len() + tail.len()) as u64;
+        match result {
+            Ok(()) => s.dma_bytes += middle.len() as u64,
+            Err(_) => {
+                s.cpu_bytes += middle.len() as u64;
+                s.fallbacks += 1;
+            }
+        }
+    });
+}
+
+/// Start `transfer` on the DMA engine. `done` is called in IRQ context when it ended.
+pub fn start(transfer: Transfer, done: Completion) -> Result<(), &'static str> {
+    if transfer.steps.is_empty() {
+        return Err("Transfer has no steps");
+    }
+    let engine = CUR_ENGINE.read(|e| *e).ok_or("No DMA engine")?;
+
+    engine.start(transfer, done)?;
+    STATS.lock(|s| s.transfers += 1);
+
+    Ok(())
+}
+
+/// Copy `len` bytes in the background with a transfer and print the time it took and whether the
+/// copy is correct when it ended.
+pub fn copy_test(len: usize) -> Result<(), &'static str> {
+    let mut buffer = vec![0u8; 2 * len];
+    for (i, b) in buffer[..len].iter_mut().enumerate() {
+        *b = i as u8 ^ (i >> 8) as u8;
+    }
+
+    let transfer = Transfer::new(buffer).copy(0, len, len)?;
+    let start_time = time::time_manager().uptime();
+
+    start(
+        transfer,
+        Box::new(move |buffer, result| {
+            let elapsed = time::time_manager().uptime() - start_time;
+            let (source, copy) = buffer.split_at(len);
+            match result {
+                Ok(()) => info!(
+                    "DMA copy: {} Byte in {}{}",
+                    len,
+                    time::Human(elapsed),
+                    if source == copy {
+                        ""
+                    } else {
+                        " (wrong content)"
+                    }
+                ),
+                Err(x) => info!("DMA copy: {}", x),
+            }
+        }),
+    )
+}
+
+/// Fill a buffer of `len` bytes once with the CPU and once through [`fill()`], and print both
+/// times.
+pub fn benchmark(len: usize) {
+    let mut buf = vec![0u8; len];
+
+    let start = time::time_manager().uptime();
+    buf.fill(0xA5);
+    let cpu = time::time_manager().uptime() - start;
+
+    let start = time::time_manager().uptime();
+    fill(&mut buf, 0x5A);
+    let offloaded = time::time_manager().uptime() - start;
+
+    let correct = buf.iter().all(|b| *b == 0x5A);
+    info!("      CPU:  {:>12}", time::Human(cpu));
+    info!(
+        "      fill: {:>12}{}",
+        time::Human(offloaded),
+        if correct { "" } else { " (wrong content)" }
+    );
+}
+
+/// Print the engine and how many bytes it filled.
+pub fn print() {
+    match CUR_ENGINE.read(|e| *e) {
+        Some(engine) => info!("      Engine:    {}", engine.name()),
+        None => info!("      Engine:    none"),
+    }
+    STATS.lock(|s| {
+        info!("      DMA bytes: {}", s.dma_bytes);
+        info!("      CPU bytes: {}", s.cpu_bytes);
+        info!("      Fallbacks: {}", s.fallbacks);
+        info!("      Transfers: {}", s.transfers);
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// The engine's part must start and end on a cache line and cover all whole lines.
+    #[kernel_test]
+    fn split_on_cache_lines() {
+        assert_eq!(split(0x1000, 4096), (0, 4096));
+        assert_eq!(split(0x1001, 4096), (63, 3968));
+        assert_eq!(split(0x1030, 4200), (16, 4160));
+        assert_eq!(split(0x1001, 10), (10, 0));
+    }
+
+    /// Steps must stay within the buffer, copies must not overlap and FIFOs take whole words.
+    #[kernel_test]
+    fn transfer_checks_steps() {
+        let transfer = || Transfer::new(vec![0; 64]);
+
+        assert!(transfer().copy(0, 32, 32).is_ok());
+        assert!(transfer().copy(0, 16, 32).is_err());
+        assert!(transfer().copy(0, 40, 32).is_err());
+        assert!(transfer().copy(usize::MAX, 0, 2).is_err());
+        assert!(transfer().send(60, 4, Peripheral::Uart).is_ok());
+        assert!(transfer().send(0, 6, Peripheral::Spi).is_err());
+        assert!(transfer().receive(Peripheral::Pwm, 0, 0).is_err());
+    }
+}
@@ -3,14 +3,26 @@
 // Copyright (c) 2018-2023 Andre Richter <andre.o.richter@gmail.com>
 
 //! Driver support.
+//!
+//! Drivers name the drivers they depend on by their compatible string. The driver manager
+//! initializes them in dependency order, level by level: a driver's level is one more than the
+//! highest level among its dependencies, so drivers of the same level don't depend on each other
+//! and could be brought up in parallel.
 
 use crate::{
     exception, info,
     synchronization::{interface::ReadWriteEx, InitStateLock},
 };
-use alloc::vec::Vec;
+use alloc::{string::String, vec, vec::Vec};
 use core::fmt;
 
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A driver's name and the names of the drivers it depends on.
+type Node = (&'static str, &'static [&'static str]);
+
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
 //--------------------------------------------------------------------------------------------------
@@ -34,6 +46,13 @@ pub mod interface {
             Ok(())
         }
 
+        /// Called by the panic handler to put the device's outputs into a safe state, e.g. stop
+        /// motors and signals, before the kernel halts.
+        ///
+        /// The panic may have happened while the driver's lock was held, so implementations must
+        /// write the registers directly instead of taking the lock, and must not block or allocate.
+        fn emergency_stop(&self) {}
+
         /// Called by the kernel to register and enable the device's IRQ handler.
         ///
         /// Rust's type system will prevent a call to this function unless the calling instance
@@ -62,6 +81,7 @@ where
     device_driver: &'static (dyn interface::DeviceDriver<IRQNumberType = T> + Sync),
     post_init_callback: Option<DeviceDriverPostInitCallback>,
     irq_number: Option<T>,
+    dependencies: &'static [&'static str],
 }
 
 /// Provides device driver management functions.
@@ -78,21 +98,105 @@ where
 
 static DRIVER_MANAGER: DriverManager<exception::asynchronous::IRQNumber> = DriverManager::new();
 
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return the init level of each node.
+///
+/// Dependencies on names that are not in `nodes` are an error, unless `allow_unknown` is set, in
+/// which case they are ignored.
+fn levels(nodes: &[Node], allow_unknown: bool) -> Result<Vec<usize>, &'static str> {
+    let mut levels: Vec<Option<usize>> = vec![None; nodes.len()];
+
+    for (name, deps) in nodes {
+        if deps.contains(name) {
+            return Err("Driver depends on itself");
+        }
+        if !allow_unknown && deps.iter().any(|d| !nodes.iter().any(|(n, _)| n == d)) {
+            return Err("Unknown dependency");
+        }
+    }
+
+    // Every pass resolves at least one node, unless the remaining ones form a cycle.
+    for _ in 0..nodes.len() {
+        for i in 0..nodes.len() {
+            if levels[i].is_some() {
+                continue;
+            }
+
+            let mut level = Some(0);
+            for dep in nodes[i].1 {
+                let dep_level = match nodes.iter().position(|(n, _)| n == dep) {
+                    Some(pos) => levels[pos].map(|l| l + 1),
+                    None => Some(0),
+                };
+                level = level.zip(dep_level).map(|(a, b)| a.max(b));
+            }
+            levels[i] = level;
+        }
+    }
+
+    levels
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .ok_or("Dependency cycle")
+}
+
+/// Print the drivers depending on `nodes[parent]`, indented below it.
+fn print_dependents(nodes: &[Node], parent: usize, prefix: &mut String) {
+    let dependents: Vec<usize> = (0..nodes.len())
+        .filter(|&i| nodes[i].1.contains(&nodes[parent].0))
+        .collect();
+
+    for (n, &i) in dependents.iter().enumerate() {
+        let last = n + 1 == dependents.len();
+
+        info!(
+            "      {}{}{}",
+            prefix,
+            if last { "`- " } else { "+- " },
+            nodes[i].0
+        );
+
+        let len = prefix.len();
+        prefix.push_str(if last { "   " } else { "|  " });
+        print_dependents(nodes, i, prefix);
+        prefix.truncate(len);
+    }
+}
+
+impl<T> DriverManager<T>
+where
+    T: fmt::Display,
+{
+    fn nodes(descriptors: &[DeviceDriverDescriptor<T>]) -> Vec<Node> {
+        descriptors
+            .iter()
+            .map(|d| (d.device_driver.compatible(), d.dependencies))
+            .collect()
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
 
 impl<T> DeviceDriverDescriptor<T> {
     /// Create an instance.
+    ///
+    /// `dependencies` are the compatible strings of the drivers that must be initialized first.
     pub fn new(
         device_driver: &'static (dyn interface::DeviceDriver<IRQNumberType = T> + Sync),
         post_init_callback: Option<DeviceDriverPostInitCallback>,
         irq_number: Option<T>,
+        dependencies: &'static [&'static str],
     ) -> Self {
         Self {
             device_driver,
             post_init_callback,
             irq_number,
+            dependencies,
         }
     }
 }
@@ -114,9 +218,29 @@ where
     }
 
     /// Register a device driver with the kernel.
-    pub fn register_driver(&self, descriptor: DeviceDriverDescriptor<T>) {
-        self.descriptors
-            .write(|descriptors| descriptors.push(descriptor));
+    ///
+    /// Dependencies may name drivers that are registered later, but must not form a cycle with the
+    /// drivers registered so far.
+    pub fn register_driver(
+        &self,
+        descriptor: DeviceDriverDescriptor<T>,
+    ) -> Result<(), &'static str> {
+        self.descriptors.write(|descriptors| {
+            let compatible = descriptor.device_driver.compatible();
+            if descriptors
+                .iter()
+                .any(|d| d.device_driver.compatible() == compatible)
+            {
+                return Err("Driver already registered");
+            }
+
+            let mut nodes = Self::nodes(descriptors);
+            nodes.push((compatible, descriptor.dependencies));
+            levels(&nodes, true)?;
+
+            descriptors.push(descriptor);
+            Ok(())
+        })
     }
 
     /// Fully initialize all drivers and their interrupts handlers.
@@ -126,7 +250,14 @@ where
     /// - During init, drivers might do stuff with system-wide impact.
     pub unsafe fn init_drivers_and_irqs(&self) {
         self.descriptors.read(|descriptors| {
-            for descriptor in descriptors {
+            let levels = match levels(&Self::nodes(descriptors), false) {
+                Ok(levels) => levels,
+                Err(x) => panic!("Error ordering driver init: {}", x),
+            };
+            let mut order: Vec<usize> = (0..descriptors.len()).collect();
+            order.sort_by_key(|&i| levels[i]);
+
+            for descriptor in order.iter().map(|&i| &descriptors[i]) {
                 // 1. Initialize driver.
                 if let Err(x) = descriptor.device_driver.init() {
                     panic!(
@@ -167,6 +298,16 @@ where
         })
     }
 
+    /// Call [`interface::DeviceDriver::emergency_stop()`] on all registered drivers, in
+    /// registration order.
+    pub fn emergency_stop_all(&self) {
+        self.descriptors.read(|descriptors| {
+            for descriptor in descriptors {
+                descriptor.device_driver.emergency_stop();
+            }
+        });
+    }
+
     /// Enumerate all registered device drivers.
     pub fn enumerate(&self) {
         self.descriptors.read(|descriptors| {
@@ -175,4 +316,49 @@ where
             }
         });
     }
+
+    /// Print the dependency graph as a tree below each driver without dependencies. A driver with
+    /// several dependencies shows up below each of them.
+    pub fn print_tree(&self) {
+        self.descriptors.read(|descriptors| {
+            let nodes = Self::nodes(descriptors);
+            let mut prefix = String::new();
+
+            for (i, (name, deps)) in nodes.iter().enumerate() {
+                if deps.is_empty() {
+                    info!("      {}", name);
+                    print_dependents(&nodes, i, &mut prefix);
+                }
+            }
+        });
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Levels must follow the dependencies regardless of registration order, and cycles and
+    /// unknown dependencies must be rejected.
+    #[kernel_test]
+    fn dependency_levels() {
+        let nodes: [Node; 4] = [
+            ("wifi", &["emmc"]),
+            ("uart", &["gpio"]),
+            ("emmc", &["gpio"]),
+            ("gpio", &[]),
+        ];
+        assert_eq!(levels(&nodes, false), Ok(vec![2, 1, 1, 0]));
+
+        assert!(levels(&[("fat", &["emmc"])], false).is_err());
+        assert_eq!(levels(&[("fat", &["emmc"])], true), Ok(vec![0]));
+
+        assert!(levels(&[("a", &["b"]), ("b", &["a"])], true).is_err());
+        assert!(levels(&[("a", &["a"])], true).is_err());
+    }
 }
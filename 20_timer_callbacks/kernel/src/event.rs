@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! The kernel event bus.
+//!
+//! Subsystems publish notable state changes as [`Event`]s. Other subsystems subscribe with a
+//! handler and are called synchronously, in the context of the publisher, which may be an IRQ
+//! handler. Handlers should therefore be short.
+
+use crate::synchronization::{interface::Mutex, IRQSafeNullLock};
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+struct Subscriber {
+    id: SubscriptionId,
+    handler: EventHandler,
+}
+
+struct EventBusInner {
+    subscribers: Vec<Subscriber>,
+    next_id: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Events that can be published on the bus.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A network interface's link came up.
+    LinkUp { interface: &'static str },
+
+    /// A network interface's link went down.
+    LinkDown { interface: &'static str },
+
+    /// A BLE central connected.
+    BleConnected { handle: u16 },
+
+    /// A BLE central disconnected.
+    BleDisconnected { handle: u16 },
+
+    /// An IRQ fired too often and was masked.
+    IrqStorm { irq: usize, handler: &'static str },
+
+    /// The SoC got hotter than the clocking limit.
+    ThermalLimit { millicelsius: u32 },
+
+    /// The firmware reports under-voltage or throttling, with its flags.
+    Throttled { flags: u32 },
+}
+
+/// The handler type used by subscribers.
+pub type EventHandler = Box<dyn Fn(&Event) + Send>;
+
+/// Reference to a subscription, used to unsubscribe.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SubscriptionId(usize);
+
+/// The event bus.
+pub struct EventBus {
+    inner: IRQSafeNullLock<EventBusInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static EVENT_BUS: EventBus = EventBus::new();
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::LinkUp { interface } => write!(f, "{}: link up", interface),
+            Self::LinkDown { interface } => write!(f, "{}: link down", interface),
+            Self::BleConnected { handle } => write!(f, "ble: connected, handle {:#05x}", handle),
+            Self::BleDisconnected { handle } => {
+                write!(f, "ble: disconnected, handle {:#05x}", handle)
+            }
+            Self::IrqStorm { irq, handler } => {
+                write!(f, "irq: storm on {} ({}), masked", irq, handler)
+            }
+            Self::ThermalLimit { millicelsius } => write!(
+                f,
+                "thermal: {}.{} C, over the limit",
+                millicelsius / 1000,
+                millicelsius % 1000 / 100
+            ),
+            Self::Throttled { flags } => write!(f, "power: throttled, flags {:#x}", flags),
+        }
+    }
+}
+
+/// Return a reference to the global event bus.
+pub fn event_bus() -> &'static EventBus {
+    &EVENT_BUS
+}
+
+impl EventBus {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(EventBusInner {
+                subscribers: Vec::new(),
+                next_id: 0,
+            }),
+        }
+    }
+
+    /// Register a handler that is called for every published event.
+    ///
+    /// Handlers must not subscribe or unsubscribe themselves.
+    pub fn subscribe(&self, handler: EventHandler) -> SubscriptionId {
+        self.inner.lock(|inner| {
+            let id = SubscriptionId(inner.next_id);
+            inner.next_id += 1;
+            inner.subscribers.push(Subscriber { id, handler });
+
+            id
+        })
+    }
+
+    /// Remove a handler.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            let pos = inner
+                .subscribers
+                .iter()
+                .position(|s| s.id == id)
+                .ok_or("No such subscription")?;
+            inner.subscribers.remove(pos);
+
+            Ok(())
+        })
+    }
+
+    /// Publish an event to all subscribers.
+    pub fn publish(&self, event: Event) {
+        self.inner.lock(|inner| {
+            for subscriber in &inner.subscribers {
+                (subscriber.handler)(&event);
+            }
+        });
+    }
+}
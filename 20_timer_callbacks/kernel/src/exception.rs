@@ -13,7 +13,7 @@ pub mod asynchronous;
 //--------------------------------------------------------------------------------------------------
 // Architectural Public Reexports
 //--------------------------------------------------------------------------------------------------
-pub use arch_exception::{current_privilege_level, handling_init};
+pub use arch_exception::{current_privilege_level, handling_init, RegisterDump};
 
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
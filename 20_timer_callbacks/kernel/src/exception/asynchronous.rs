@@ -9,8 +9,33 @@
 mod arch_asynchronous;
 mod null_irq_manager;
 
-use crate::{bsp, synchronization};
-use core::marker::PhantomData;
+use crate::{
+    bsp,
+    event::{self, Event},
+    latency,
+    synchronization::{self, interface::Mutex, IRQSafeDeviceLock, IRQSafeNullLock},
+    time, warn,
+};
+use core::{marker::PhantomData, time::Duration};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Length of the window in which IRQs are counted for storm detection.
+const STORM_WINDOW: Duration = Duration::from_millis(10);
+
+/// An IRQ that fires more often than this within one window is considered a storm.
+const STORM_THRESHOLD: u32 = 1000;
+
+/// Handlers of the inter-processor interrupt messages, indexed by [`IpiMessage`].
+type IpiHandlerTable = [Option<fn()>; IpiMessage::COUNT];
+
+#[derive(Copy, Clone)]
+struct StormWindow {
+    start: Duration,
+    count: u32,
+}
 
 //--------------------------------------------------------------------------------------------------
 // Architectural Public Reexports
@@ -43,6 +68,31 @@ where
     handler: &'static (dyn interface::IRQHandler + Sync),
 }
 
+/// Counts how often each of `N` IRQs fires, to detect interrupt storms.
+///
+/// Owned by an interrupt controller driver, which calls [`StormDetector::record()`] for every
+/// handled IRQ and masks the ones that are reported.
+pub struct StormDetector<const N: usize> {
+    windows: IRQSafeNullLock<[StormWindow; N]>,
+}
+
+/// Messages one core can send to another with an inter-processor interrupt (IPI).
+///
+/// Each message maps to its own software-generated interrupt or mailbox bit, so the receiver learns
+/// which ones arrived. Sending a message again before the receiver handled it has no extra effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IpiMessage {
+    /// Pick the next task to run.
+    Reschedule,
+
+    /// Take over the due timer callbacks.
+    RunTimers,
+}
+
+// Message indices double as the 16 software-generated interrupts of the GIC. The array length
+// underflows, failing the build, if there are more messages.
+const _: [(); 16 - IpiMessage::COUNT] = [(); 16 - IpiMessage::COUNT];
+
 /// IRQContext token.
 ///
 /// An instance of this type indicates that the local core is currently executing in IRQ
@@ -57,6 +107,7 @@ pub struct IRQContext<'irq_context> {
 
 /// Asynchronous exception handling interfaces.
 pub mod interface {
+    use alloc::vec::Vec;
 
     /// Implemented by types that handle IRQs.
     pub trait IRQHandler {
@@ -78,9 +129,36 @@ pub mod interface {
             irq_handler_descriptor: super::IRQHandlerDescriptor<Self::IRQNumberType>,
         ) -> Result<(), &'static str>;
 
+        /// Route an IRQ that already has a handler registered to a different handler. Returns the
+        /// previous descriptor, which can be passed back in later to restore the old routing.
+        fn remap_handler(
+            &self,
+            irq_handler_descriptor: super::IRQHandlerDescriptor<Self::IRQNumberType>,
+        ) -> Result<super::IRQHandlerDescriptor<Self::IRQNumberType>, &'static str>;
+
+        /// Return the descriptor an IRQ is currently routed to, if any.
+        fn handler(
+            &self,
+            irq_number: &Self::IRQNumberType,
+        ) -> Option<super::IRQHandlerDescriptor<Self::IRQNumberType>>;
+
         /// Enable an interrupt in the controller.
         fn enable(&self, irq_number: &Self::IRQNumberType);
 
+        /// Disable an interrupt in the controller.
+        fn disable(&self, irq_number: &Self::IRQNumberType);
+
+        /// Disable all enabled interrupts that have a handler registered, except `keep`. Returns
+        /// the disabled ones, to be re-enabled with `enable()`.
+        fn disable_all_except(&self, _keep: &Self::IRQNumberType) -> Vec<Self::IRQNumberType> {
+            Vec::new()
+        }
+
+        /// Raise an inter-processor interrupt carrying `message` on core `core`.
+        fn send_ipi(&self, _core: usize, _message: super::IpiMessage) -> Result<(), &'static str> {
+            Err("IPIs are not supported by the interrupt controller")
+        }
+
         /// Handle pending interrupts.
         ///
         /// This function is called directly from the CPU's IRQ exception vector. On AArch64,
@@ -107,6 +185,9 @@ static CUR_IRQ_MANAGER: InitStateLock<
     &'static (dyn interface::IRQManager<IRQNumberType = IRQNumber> + Sync),
 > = InitStateLock::new(&null_irq_manager::NULL_IRQ_MANAGER);
 
+static IPI_HANDLERS: IRQSafeDeviceLock<IpiHandlerTable> =
+    IRQSafeDeviceLock::new([None; IpiMessage::COUNT]);
+
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
@@ -162,14 +243,124 @@ impl<'irq_context> IRQContext<'irq_context> {
     }
 }
 
+impl IpiMessage {
+    /// Number of messages.
+    pub const COUNT: usize = 2;
+
+    const ALL: [Self; Self::COUNT] = [Self::Reschedule, Self::RunTimers];
+
+    /// The message's index, which is also its software-generated interrupt or mailbox bit.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl<const N: usize> StormDetector<N> {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            windows: IRQSafeNullLock::new(
+                [StormWindow {
+                    start: Duration::ZERO,
+                    count: 0,
+                }; N],
+            ),
+        }
+    }
+
+    /// Count one occurrence of IRQ `irq`. Return true if it exceeded the storm threshold within
+    /// the current window. The count starts over afterwards.
+    pub fn record(&self, irq: usize) -> bool {
+        let now = time::time_manager().uptime();
+
+        self.windows.lock(|windows| {
+            let w = &mut windows[irq];
+
+            if now - w.start >= STORM_WINDOW {
+                w.start = now;
+                w.count = 0;
+            }
+            w.count += 1;
+
+            if w.count > STORM_THRESHOLD {
+                w.count = 0;
+                return true;
+            }
+
+            false
+        })
+    }
+}
+
+/// Log an interrupt storm and publish it on the event bus. To be called by interrupt controller
+/// drivers after they masked the IRQ.
+pub fn report_storm(irq: usize, handler: &'static str) {
+    warn!(
+        "IRQ storm: {} ({}) fired more than {} times in {} ms, masked",
+        irq,
+        handler,
+        STORM_THRESHOLD,
+        STORM_WINDOW.as_millis()
+    );
+
+    event::event_bus().publish(Event::IrqStorm { irq, handler });
+}
+
+/// Register the handler that runs when `message` arrives. It runs in IRQ context on the receiving
+/// core.
+pub fn register_ipi_handler(message: IpiMessage, handler: fn()) -> Result<(), &'static str> {
+    IPI_HANDLERS.lock(|handlers| {
+        let slot = &mut handlers[message.index()];
+        if slot.is_some() {
+            return Err("IPI handler already registered");
+        }
+
+        *slot = Some(handler);
+        Ok(())
+    })
+}
+
+/// Send `message` to core `core`, which may be the executing one.
+pub fn send_ipi(core: usize, message: IpiMessage) -> Result<(), &'static str> {
+    irq_manager().send_ipi(core, message)
+}
+
+/// Run the handlers of the messages set in `messages`, bit `i` standing for the message with index
+/// `i`. To be called by interrupt controller drivers when an IPI arrived.
+///
+/// Messages without a registered handler are dropped.
+pub fn dispatch_ipis(messages: u32, _ic: &IRQContext) {
+    for message in IpiMessage::ALL {
+        if messages & (1 << message.index()) == 0 {
+            continue;
+        }
+
+        // Copied out of the table, so that the handler may register others.
+        if let Some(handler) = IPI_HANDLERS.lock(|handlers| handlers[message.index()]) {
+            handler();
+        }
+    }
+}
+
 /// Executes the provided closure while IRQs are masked on the executing core.
 ///
 /// While the function temporarily changes the HW state of the executing core, it restores it to the
 /// previous state before returning, so this is deemed safe.
+///
+/// The outermost section is measured for the [`latency`] audit, under the caller's location.
 #[inline(always)]
+#[track_caller]
 pub fn exec_with_irq_masked<T>(f: impl FnOnce() -> T) -> T {
+    // `is_local_irq_masked()` is inverted, it returns true while IRQs are unmasked.
+    let outermost = is_local_irq_masked();
     let saved = local_irq_mask_save();
+    if outermost {
+        latency::masked();
+    }
     let ret = f();
+    if outermost {
+        latency::unmasking();
+    }
     local_irq_restore(saved);
 
     ret
@@ -188,3 +379,21 @@ pub fn register_irq_manager(
 pub fn irq_manager() -> &'static dyn interface::IRQManager<IRQNumberType = IRQNumber> {
     CUR_IRQ_MANAGER.read(|manager| *manager)
 }
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Message indices double as SGI numbers and mailbox bits, so they must be dense.
+    #[kernel_test]
+    fn ipi_message_indices() {
+        for (i, message) in IpiMessage::ALL.iter().enumerate() {
+            assert_eq!(message.index(), i);
+        }
+    }
+}
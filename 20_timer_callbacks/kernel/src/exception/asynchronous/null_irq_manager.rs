@@ -32,10 +32,28 @@ impl interface::IRQManager for NullIRQManager {
         panic!("No IRQ Manager registered yet");
     }
 
+    fn remap_handler(
+        &self,
+        _descriptor: IRQHandlerDescriptor<Self::IRQNumberType>,
+    ) -> Result<IRQHandlerDescriptor<Self::IRQNumberType>, &'static str> {
+        panic!("No IRQ Manager registered yet");
+    }
+
+    fn handler(
+        &self,
+        _irq_number: &Self::IRQNumberType,
+    ) -> Option<IRQHandlerDescriptor<Self::IRQNumberType>> {
+        panic!("No IRQ Manager registered yet");
+    }
+
     fn enable(&self, _irq_number: &Self::IRQNumberType) {
         panic!("No IRQ Manager registered yet");
     }
 
+    fn disable(&self, _irq_number: &Self::IRQNumberType) {
+        panic!("No IRQ Manager registered yet");
+    }
+
     fn handle_pending_irqs<'irq_context>(&'irq_context self, _ic: &IRQContext<'irq_context>) {
         panic!("No IRQ Manager registered yet");
     }
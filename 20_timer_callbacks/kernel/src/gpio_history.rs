@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! GPIO history.
+//!
+//! An always-on ring buffer of the last GPIO commands and edges, so that when an attached circuit
+//! misbehaves it can be reconstructed what the kernel drove and when. Unlike the trace buffer it
+//! is statically allocated and can't be disabled.
+//!
+//! Edges are recorded by the GPIO IRQ handler for pins with edge detection enabled, and otherwise
+//! when a read observes a different level than the last known one.
+
+use crate::{
+    info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time::{self, Timestamp},
+};
+use core::fmt;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of records kept.
+const CAPACITY: usize = 128;
+
+struct HistoryInner {
+    records: [Option<Record>; CAPACITY],
+
+    /// Index of the slot that is written next.
+    next: usize,
+
+    /// Last known level of each pin, valid where the bit in `known` is set.
+    levels: u64,
+    known: u64,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Something that happened to a pin.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Event {
+    Output,
+    Input,
+    High,
+    Low,
+    PullOff,
+    PullDown,
+    PullUp,
+    Rising,
+    Falling,
+}
+
+/// A history record.
+#[derive(Copy, Clone, Debug)]
+pub struct Record {
+    pub stamp: Timestamp,
+    pub pin: u8,
+    pub event: Event,
+
+    /// The command overrode pin protection.
+    pub forced: bool,
+}
+
+/// The GPIO history.
+pub struct History {
+    inner: IRQSafeNullLock<HistoryInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static HISTORY: History = History::new();
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl HistoryInner {
+    fn push(&mut self, pin: u8, event: Event, forced: bool) {
+        self.records[self.next] = Some(Record {
+            stamp: time::timestamp(),
+            pin,
+            event,
+            forced,
+        });
+        self.next = (self.next + 1) % CAPACITY;
+    }
+
+    fn set_level(&mut self, pin: u8, level: bool) {
+        let bit = 1 << pin;
+
+        self.known |= bit;
+        if level {
+            self.levels |= bit;
+        } else {
+            self.levels &= !bit;
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Output => write!(f, "output"),
+            Self::Input => write!(f, "input"),
+            Self::High => write!(f, "high"),
+            Self::Low => write!(f, "low"),
+            Self::PullOff => write!(f, "pull off"),
+            Self::PullDown => write!(f, "pull down"),
+            Self::PullUp => write!(f, "pull up"),
+            Self::Rising => write!(f, "rising"),
+            Self::Falling => write!(f, "falling"),
+        }
+    }
+}
+
+/// Return a reference to the global GPIO history.
+pub fn history() -> &'static History {
+    &HISTORY
+}
+
+/// Add a command to the global GPIO history.
+pub fn record(pin: u8, event: Event, forced: bool) {
+    HISTORY.record(pin, event, forced);
+}
+
+/// Tell the global GPIO history the level a pin was read at, recording an edge if it changed.
+pub fn observe(pin: u8, level: bool) {
+    HISTORY.observe(pin, level);
+}
+
+impl History {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(HistoryInner {
+                records: [None; CAPACITY],
+                next: 0,
+                levels: 0,
+                known: 0,
+            }),
+        }
+    }
+
+    /// Add a command, overwriting the oldest record if the history is full.
+    pub fn record(&self, pin: u8, event: Event, forced: bool) {
+        self.inner.lock(|inner| {
+            inner.push(pin, event, forced);
+
+            // Driving a pin or an edge sets its level, so that reading it back is not taken for
+            // another edge.
+            match event {
+                Event::High | Event::Rising => inner.set_level(pin, true),
+                Event::Low | Event::Falling => inner.set_level(pin, false),
+                _ => (),
+            }
+        });
+    }
+
+    /// Record an edge if `level` differs from the last known level of the pin.
+    pub fn observe(&self, pin: u8, level: bool) {
+        self.inner.lock(|inner| {
+            let bit = 1 << pin;
+            let changed = (inner.known & bit) != 0 && ((inner.levels & bit) != 0) != level;
+
+            if changed {
+                let event = if level { Event::Rising } else { Event::Falling };
+                inner.push(pin, event, false);
+            }
+            inner.set_level(pin, level);
+        });
+    }
+
+    /// Remove all records and forget the known levels.
+    pub fn clear(&self) {
+        self.inner.lock(|inner| {
+            inner.records = [None; CAPACITY];
+            inner.next = 0;
+            inner.known = 0;
+        });
+    }
+
+    /// Call `f` for each record, oldest first.
+    pub fn for_each(&self, mut f: impl FnMut(&Record)) {
+        self.inner.lock(|inner| {
+            let (newer, older) = inner.records.split_at(inner.next);
+
+            for r in older.iter().chain(newer.iter()).flatten() {
+                f(r);
+            }
+        });
+    }
+
+    /// Print the records, oldest first.
+    pub fn print(&self) {
+        self.for_each(|r| {
+            info!(
+                "      {}  pin {:<2} {}{}",
+                r.stamp,
+                r.pin,
+                r.event,
+                if r.forced { " (forced)" } else { "" }
+            );
+        });
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use test_macros::kernel_test;
+
+    /// Reads must only record edges against a known level, and driving a pin sets its level.
+    #[kernel_test]
+    fn edges_from_reads() {
+        let history = History::new();
+
+        history.observe(3, true);
+        history.observe(3, true);
+        history.observe(3, false);
+        history.record(4, Event::High, true);
+        history.observe(4, true);
+        history.observe(4, false);
+
+        let mut events = Vec::new();
+        history.for_each(|r| events.push((r.pin, r.event)));
+        assert_eq!(
+            events,
+            [(3, Event::Falling), (4, Event::High), (4, Event::Falling)]
+        );
+    }
+}
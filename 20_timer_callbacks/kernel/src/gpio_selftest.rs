@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! GPIO loopback self-test.
+//!
+//! An output pin is jumpered to an input pin. The output is driven through a fixed bit sequence and
+//! every level is verified on the input, measuring how long each transition takes to arrive. The
+//! margin is the gap between the slowest transition and the timeout.
+
+use crate::{bsp, info, time};
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Levels driven on the output. Covers both edges, repeated levels and fast toggling.
+const SEQUENCE: &[bool] = &[
+    false, true, false, true, true, false, false, true, false, true, false, true, true, true,
+    false, false, false, true, false,
+];
+
+/// Number of times the sequence is run.
+const ROUNDS: usize = 16;
+
+/// Time allowed for a level to arrive on the input.
+const TIMEOUT: Duration = Duration::from_millis(1);
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Outcome of a self-test run.
+#[derive(Default)]
+pub struct Report {
+    /// Levels that arrived on the input in time.
+    pub passed: usize,
+
+    /// Levels that did not arrive on the input in time.
+    pub failed: usize,
+
+    /// Fastest arrival.
+    pub min: Option<Duration>,
+
+    /// Slowest arrival.
+    pub max: Option<Duration>,
+
+    /// Sum of all arrival times, for the average.
+    pub total: Duration,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Drive `level` and wait for it on `in_pin`. Returns how long it took, or `None` on timeout.
+unsafe fn drive_and_wait(
+    out_pin: u8,
+    in_pin: u8,
+    level: bool,
+    force: bool,
+) -> Result<Option<Duration>, &'static str> {
+    let start = time::time_manager().uptime();
+
+    if level {
+        bsp::driver::gpio_high(out_pin, force)?;
+    } else {
+        bsp::driver::gpio_low(out_pin, force)?;
+    }
+
+    loop {
+        let elapsed = time::time_manager().uptime() - start;
+
+        if bsp::driver::gpio_read(in_pin)? == level {
+            return Ok(Some(elapsed));
+        }
+        if elapsed > TIMEOUT {
+            return Ok(None);
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Report {
+    /// Account for one driven level.
+    pub fn record(&mut self, arrival: Option<Duration>) {
+        let t = match arrival {
+            Some(t) => t,
+            None => {
+                self.failed += 1;
+                return;
+            }
+        };
+
+        self.passed += 1;
+        self.total += t;
+        self.min = Some(self.min.map_or(t, |m| m.min(t)));
+        self.max = Some(self.max.map_or(t, |m| m.max(t)));
+    }
+
+    /// Return the average arrival time.
+    pub fn average(&self) -> Option<Duration> {
+        if self.passed == 0 {
+            return None;
+        }
+
+        Some(self.total / self.passed as u32)
+    }
+
+    /// Print the report.
+    pub fn print(&self) {
+        info!("      Passed:  {}", self.passed);
+        info!("      Failed:  {}", self.failed);
+
+        if let (Some(min), Some(max), Some(avg)) = (self.min, self.max, self.average()) {
+            info!(
+                "      Arrival: min {}, avg {}, max {}",
+                time::Human(min),
+                time::Human(avg),
+                time::Human(max)
+            );
+            info!(
+                "      Margin:  {} to the {} timeout",
+                time::Human(TIMEOUT.saturating_sub(max)),
+                time::Human(TIMEOUT)
+            );
+        }
+    }
+}
+
+/// Run the loopback self-test from `out_pin` to `in_pin`.
+///
+/// Both pins are left as inputs afterwards, so that the jumper can be removed safely.
+pub fn run(out_pin: u8, in_pin: u8, force: bool) -> Result<Report, &'static str> {
+    if out_pin == in_pin {
+        return Err("Output and input pin must differ");
+    }
+
+    let mut report = Report::default();
+
+    unsafe {
+        bsp::driver::gpio_as_input(in_pin, force)?;
+        bsp::driver::gpio_as_output(out_pin, force)?;
+
+        let result = (|| {
+            for _ in 0..ROUNDS {
+                for &level in SEQUENCE {
+                    report.record(drive_and_wait(out_pin, in_pin, level, force)?);
+                }
+            }
+
+            Ok(())
+        })();
+
+        bsp::driver::gpio_low(out_pin, force)?;
+        bsp::driver::gpio_as_input(out_pin, force)?;
+        result?;
+    }
+
+    Ok(report)
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Timeouts must count as failures and stay out of the timing statistics.
+    #[kernel_test]
+    fn report_statistics() {
+        let mut report = Report::default();
+        report.record(Some(Duration::from_nanos(100)));
+        report.record(None);
+        report.record(Some(Duration::from_nanos(300)));
+
+        assert_eq!(report.passed, 2);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.min, Some(Duration::from_nanos(100)));
+        assert_eq!(report.max, Some(Duration::from_nanos(300)));
+        assert_eq!(report.average(), Some(Duration::from_nanos(200)));
+    }
+}
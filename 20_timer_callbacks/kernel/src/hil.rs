@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Hardware-in-the-loop test protocol.
+//!
+//! Lets a host drive the board programmatically over a UART, e.g. CI with a real Pi toggling GPIOs
+//! and checking what it reads back. Requests and responses are framed, see [`frame`], so they can
+//! share the UART with the console: the UART driver passes every received byte to its
+//! [`Receiver`] first, and treats it as console input only if it doesn't belong to a frame.
+//!
+//! The host side is the `hil` crate next to the kernel.
+
+pub mod frame;
+
+use crate::{bsp, time};
+use core::time::Duration;
+use frame::{kind, Frame};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A frame is abandoned after this long without a byte, so that a stray SOH doesn't swallow
+/// console input.
+const BYTE_TIMEOUT: Duration = Duration::from_millis(100);
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// What a received byte turned out to be.
+///
+/// The reply is returned by value rather than boxed, so that receiving needs no allocation.
+#[allow(clippy::large_enum_variant)]
+pub enum Input {
+    /// Console input.
+    Console,
+
+    /// Part of a frame.
+    Protocol,
+
+    /// The end of a request. The response must be sent back.
+    Reply(frame::Encoded),
+}
+
+/// Protocol state of a UART.
+pub struct Receiver {
+    decoder: frame::Decoder,
+    last_byte: Duration,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn pin_arg(request: &Frame) -> Result<u8, &'static str> {
+    request.payload().first().copied().ok_or("Missing pin")
+}
+
+/// Run a request and return the payload of the response.
+fn handle(request: &Frame, response: &mut [u8; frame::MAX_PAYLOAD]) -> Result<usize, &'static str> {
+    match request.kind {
+        kind::PING => {
+            let payload = request.payload();
+            response[..payload.len()].copy_from_slice(payload);
+
+            Ok(payload.len())
+        }
+        kind::UPTIME => {
+            let uptime = time::time_manager().uptime().as_micros() as u64;
+            response[..8].copy_from_slice(&uptime.to_le_bytes());
+
+            Ok(8)
+        }
+        kind::GPIO_WRITE => {
+            let pin = pin_arg(request)?;
+            let level = *request.payload().get(1).ok_or("Missing level")? != 0;
+            unsafe {
+                bsp::driver::gpio_as_output(pin, false)?;
+                if level {
+                    bsp::driver::gpio_high(pin, false)?;
+                } else {
+                    bsp::driver::gpio_low(pin, false)?;
+                }
+            }
+
+            Ok(0)
+        }
+        kind::GPIO_READ => {
+            response[0] = unsafe { bsp::driver::gpio_read(pin_arg(request)?)? } as u8;
+
+            Ok(1)
+        }
+        kind::GPIO_INPUT => {
+            unsafe { bsp::driver::gpio_as_input(pin_arg(request)?, false)? };
+
+            Ok(0)
+        }
+        _ => Err("Unknown request"),
+    }
+}
+
+/// Run a request and build the response.
+fn respond(request: &Frame) -> Frame {
+    let mut payload = [0; frame::MAX_PAYLOAD];
+
+    let response = match handle(request, &mut payload) {
+        Ok(len) => Frame::new(request.kind | kind::RESPONSE, request.seq, &payload[..len]),
+        Err(x) => Frame::new(kind::ERROR, request.seq, x.as_bytes()),
+    };
+
+    // Payloads are bounded by the request's, and error messages are short.
+    response.unwrap()
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Receiver {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            decoder: frame::Decoder::new(),
+            last_byte: Duration::ZERO,
+        }
+    }
+
+    /// Feed a received byte.
+    pub fn receive(&mut self, byte: u8) -> Input {
+        let now = time::time_manager().uptime();
+        if self.decoder.in_frame() && now - self.last_byte > BYTE_TIMEOUT {
+            self.decoder.reset();
+        }
+        self.last_byte = now;
+
+        if !self.decoder.in_frame() && byte != frame::SOH {
+            return Input::Console;
+        }
+
+        match self.decoder.push(byte) {
+            Some(request) => Input::Reply(respond(&request).encode()),
+            None => Input::Protocol,
+        }
+    }
+}
+
+impl Default for Receiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A ping must be answered with its payload, console bytes around it must pass through, and a
+    /// corrupted frame must be dropped.
+    #[kernel_test]
+    fn ping_round_trip() {
+        let request = Frame::new(kind::PING, 7, b"hello").unwrap().encode();
+        let mut receiver = Receiver::new();
+
+        assert!(matches!(receiver.receive(b'l'), Input::Console));
+
+        let (last, bytes) = request.as_bytes().split_last().unwrap();
+        for b in bytes {
+            assert!(matches!(receiver.receive(*b), Input::Protocol));
+        }
+        let reply = match receiver.receive(*last) {
+            Input::Reply(reply) => reply,
+            _ => panic!("No reply"),
+        };
+
+        let mut decoder = frame::Decoder::new();
+        let (last, bytes) = reply.as_bytes().split_last().unwrap();
+        bytes
+            .iter()
+            .for_each(|b| assert!(decoder.push(*b).is_none()));
+        let response = decoder.push(*last).unwrap();
+        assert_eq!(response.kind, kind::PING | kind::RESPONSE);
+        assert_eq!(response.seq, 7);
+        assert_eq!(response.payload(), b"hello");
+
+        assert!(matches!(receiver.receive(b's'), Input::Console));
+
+        let mut corrupted = [0; frame::MAX_FRAME];
+        let len = request.as_bytes().len();
+        corrupted[..len].copy_from_slice(request.as_bytes());
+        corrupted[6] ^= 1;
+        for b in &corrupted[..len] {
+            assert!(matches!(receiver.receive(*b), Input::Protocol));
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Framing of the hardware-in-the-loop protocol.
+//!
+//! A frame is `SOH | type | sequence number | length (u16) | payload | CRC-32 (u32)`, little
+//! endian, with the CRC covering everything between SOH and the CRC. SOH is never typed into the
+//! shell, so frames can share a UART with the console.
+//!
+//! This module depends on nothing but `core` and is compiled into the host-side harness as well.
+
+use crate::common::crc32;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// SOH, type, sequence number and length.
+const HEADER_LEN: usize = 5;
+
+const CRC_LEN: usize = 4;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Start of a frame.
+pub const SOH: u8 = 0x01;
+
+/// Largest payload of a frame.
+pub const MAX_PAYLOAD: usize = 256;
+
+/// Largest encoded frame.
+pub const MAX_FRAME: usize = HEADER_LEN + MAX_PAYLOAD + CRC_LEN;
+
+/// Frame types. A response has the type of its request with [`RESPONSE`](kind::RESPONSE) set, or
+/// is an [`ERROR`](kind::ERROR) carrying a message.
+pub mod kind {
+    /// Returns the payload unchanged.
+    pub const PING: u8 = 0x01;
+
+    /// Returns the uptime in microseconds as `u64`.
+    pub const UPTIME: u8 = 0x02;
+
+    /// Payload: pin, level. Drives the pin as an output.
+    pub const GPIO_WRITE: u8 = 0x03;
+
+    /// Payload: pin. Returns the level of the pin, without changing its function.
+    pub const GPIO_READ: u8 = 0x04;
+
+    /// Payload: pin. Makes the pin an input.
+    pub const GPIO_INPUT: u8 = 0x05;
+
+    /// The request failed. Payload: the error message.
+    pub const ERROR: u8 = 0x7F;
+
+    /// Set in the type of a response.
+    pub const RESPONSE: u8 = 0x80;
+}
+
+/// A decoded frame.
+#[derive(Copy, Clone)]
+pub struct Frame {
+    pub kind: u8,
+    pub seq: u8,
+    len: usize,
+    payload: [u8; MAX_PAYLOAD],
+}
+
+/// An encoded frame, ready to be sent.
+pub struct Encoded {
+    buf: [u8; MAX_FRAME],
+    len: usize,
+}
+
+/// Reassembles frames from a byte stream.
+pub struct Decoder {
+    buf: [u8; MAX_FRAME],
+    len: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Frame {
+    /// Create a frame. Returns `None` if the payload is too long.
+    pub fn new(kind: u8, seq: u8, payload: &[u8]) -> Option<Self> {
+        if payload.len() > MAX_PAYLOAD {
+            return None;
+        }
+
+        let mut frame = Self {
+            kind,
+            seq,
+            len: payload.len(),
+            payload: [0; MAX_PAYLOAD],
+        };
+        frame.payload[..payload.len()].copy_from_slice(payload);
+
+        Some(frame)
+    }
+
+    /// Return the payload.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload[..self.len]
+    }
+
+    /// Encode the frame.
+    pub fn encode(&self) -> Encoded {
+        let mut buf = [0; MAX_FRAME];
+        let crc_start = HEADER_LEN + self.len;
+
+        buf[0] = SOH;
+        buf[1] = self.kind;
+        buf[2] = self.seq;
+        buf[3..HEADER_LEN].copy_from_slice(&(self.len as u16).to_le_bytes());
+        buf[HEADER_LEN..crc_start].copy_from_slice(self.payload());
+        let crc = crc32(&buf[1..crc_start]);
+        buf[crc_start..crc_start + CRC_LEN].copy_from_slice(&crc.to_le_bytes());
+
+        Encoded {
+            buf,
+            len: crc_start + CRC_LEN,
+        }
+    }
+}
+
+impl Encoded {
+    /// Return the bytes to send.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl Decoder {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; MAX_FRAME],
+            len: 0,
+        }
+    }
+
+    /// Return whether a frame has been started but not completed.
+    pub fn in_frame(&self) -> bool {
+        self.len > 0
+    }
+
+    /// Abandon the frame in progress.
+    pub fn reset(&mut self) {
+        self.len = 0;
+    }
+
+    /// Feed a received byte. Returns the frame it completed, if any. Bytes outside of frames are
+    /// ignored, and frames with an invalid length or CRC are dropped.
+    pub fn push(&mut self, byte: u8) -> Option<Frame> {
+        if self.len == 0 && byte != SOH {
+            return None;
+        }
+        self.buf[self.len] = byte;
+        self.len += 1;
+
+        if self.len < HEADER_LEN {
+            return None;
+        }
+        let payload_len = u16::from_le_bytes([self.buf[3], self.buf[4]]) as usize;
+        if payload_len > MAX_PAYLOAD {
+            self.len = 0;
+            return None;
+        }
+        let crc_start = HEADER_LEN + payload_len;
+        if self.len < crc_start + CRC_LEN {
+            return None;
+        }
+        self.len = 0;
+
+        let mut crc = [0; CRC_LEN];
+        crc.copy_from_slice(&self.buf[crc_start..crc_start + CRC_LEN]);
+        if crc32(&self.buf[1..crc_start]) != u32::from_le_bytes(crc) {
+            return None;
+        }
+
+        Frame::new(self.buf[1], self.buf[2], &self.buf[HEADER_LEN..crc_start])
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Board identity.
+//!
+//! Tells several boards running the same image apart. The board ID is the SoC serial number
+//! reported by the firmware. The hostname defaults to `khros-` followed by the last four hex
+//! digits of the ID, and can be changed with the `identity.hostname` setting.
+//!
+//! The ID selects the config store namespace, so that settings can be overridden per board. With
+//! `identity.log_prefix` set to `on`, every log line is prefixed with the hostname.
+
+use crate::{
+    bsp, config, info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+};
+use alloc::{format, string::String};
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Maximum length of a hostname, in bytes.
+const HOSTNAME_CAPACITY: usize = 32;
+
+/// Hostname used when the board ID is unknown.
+const DEFAULT_HOSTNAME: &str = "khros";
+
+const KEY_HOSTNAME: &str = "identity.hostname";
+const KEY_LOG_PREFIX: &str = "identity.log_prefix";
+
+struct IdentityInner {
+    id: Option<u64>,
+
+    /// Kept in a fixed buffer so that printing the log prefix doesn't allocate.
+    hostname: [u8; HOSTNAME_CAPACITY],
+    hostname_len: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The log prefix. Displays as the hostname and a space if the prefix is enabled, and as nothing
+/// otherwise.
+pub struct LogPrefix;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static IDENTITY: IRQSafeNullLock<IdentityInner> = IRQSafeNullLock::new(IdentityInner {
+    id: None,
+    hostname: [0; HOSTNAME_CAPACITY],
+    hostname_len: 0,
+});
+
+static LOG_PREFIX: AtomicBool = AtomicBool::new(false);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Check that `name` is a valid hostname label: letters, digits and inner hyphens.
+fn validate_hostname(name: &str) -> Result<(), &'static str> {
+    let valid = !name.is_empty()
+        && name.len() <= HOSTNAME_CAPACITY
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+        && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-');
+
+    if !valid {
+        return Err("Invalid hostname");
+    }
+
+    Ok(())
+}
+
+/// The hostname of a board with the given ID.
+fn default_hostname(id: Option<u64>) -> String {
+    match id {
+        Some(id) => format!("{}-{:04x}", DEFAULT_HOSTNAME, id & 0xFFFF),
+        None => String::from(DEFAULT_HOSTNAME),
+    }
+}
+
+impl IdentityInner {
+    fn hostname(&self) -> &str {
+        // Only validated ASCII is ever stored.
+        core::str::from_utf8(&self.hostname[..self.hostname_len]).unwrap_or(DEFAULT_HOSTNAME)
+    }
+
+    fn set_hostname(&mut self, name: &str) {
+        self.hostname[..name.len()].copy_from_slice(name.as_bytes());
+        self.hostname_len = name.len();
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl fmt::Display for LogPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !LOG_PREFIX.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        IDENTITY.lock(|inner| write!(f, "{} ", inner.hostname()))
+    }
+}
+
+/// Determine the board ID, select its config namespace and apply the identity settings. Must be
+/// called after the config store was loaded.
+///
+/// Without a board ID, the default hostname is used and no namespace is selected.
+pub fn init() -> Result<(), &'static str> {
+    // The firmware reports 0 where the board has no serial, e.g. in QEMU.
+    let id = unsafe { bsp::driver::board_serial() }
+        .ok()
+        .filter(|id| *id != 0);
+
+    if let Some(id) = id {
+        config::store().set_namespace(&format!("{:016x}", id));
+    }
+
+    let hostname = match config::store().get(KEY_HOSTNAME) {
+        Some(name) if validate_hostname(&name).is_ok() => name,
+        _ => default_hostname(id),
+    };
+    IDENTITY.lock(|inner| {
+        inner.id = id;
+        inner.set_hostname(&hostname);
+    });
+
+    set_log_prefix(config::store().get(KEY_LOG_PREFIX).as_deref() == Some("on"));
+
+    if id.is_none() {
+        return Err("Board ID unavailable");
+    }
+
+    Ok(())
+}
+
+/// Return the board ID, if it is known.
+pub fn id() -> Option<u64> {
+    IDENTITY.lock(|inner| inner.id)
+}
+
+/// Return the hostname.
+pub fn hostname() -> String {
+    IDENTITY.lock(|inner| String::from(inner.hostname()))
+}
+
+/// Change the hostname and store it as a per-board setting, or as a global one while the board ID
+/// is unknown.
+pub fn set_hostname(name: &str) -> Result<(), &'static str> {
+    validate_hostname(name)?;
+
+    if config::store().namespace().is_some() {
+        config::store().set_board(KEY_HOSTNAME, name)?;
+    } else {
+        config::store().set(KEY_HOSTNAME, name)?;
+    }
+    IDENTITY.lock(|inner| inner.set_hostname(name));
+
+    Ok(())
+}
+
+/// Enable or disable the hostname prefix on log lines.
+pub fn set_log_prefix(enable: bool) {
+    LOG_PREFIX.store(enable, Ordering::Relaxed);
+}
+
+/// Print the board identity.
+pub fn print() {
+    let (id, hostname) = IDENTITY.lock(|inner| (inner.id, String::from(inner.hostname())));
+
+    info!("      Hostname:   {}", hostname);
+    match id {
+        Some(id) => info!("      Board ID:   {:016x}", id),
+        None => info!("      Board ID:   unknown"),
+    }
+    info!(
+        "      Log prefix: {}",
+        if LOG_PREFIX.load(Ordering::Relaxed) {
+            "on"
+        } else {
+            "off"
+        }
+    );
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Default hostnames must be derived from the ID and pass validation, and invalid names must
+    /// be rejected.
+    #[kernel_test]
+    fn hostnames() {
+        assert_eq!(default_hostname(Some(0x1234_5678_9abc)), "khros-9abc");
+        assert_eq!(default_hostname(None), "khros");
+        assert!(validate_hostname(&default_hostname(Some(7))).is_ok());
+
+        assert!(validate_hostname("").is_err());
+        assert!(validate_hostname("-pi").is_err());
+        assert!(validate_hostname("pi 3").is_err());
+        assert!(validate_hostname("a-very-long-hostname-of-more-than-32").is_err());
+    }
+}
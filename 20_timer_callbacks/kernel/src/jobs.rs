@@ -0,0 +1,396 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Scheduled and background shell commands.
+//!
+//! `at` runs a command once when the uptime reaches a given time of day, `every` runs it
+//! periodically. Commands are executed by [`shell::execute()`] from timer IRQ context, so
+//! long-running commands delay other timeouts. Their output goes to the console the job was
+//! scheduled from.
+//!
+//! Background tasks, i.e. commands ending in `&`, are queued instead and run one after the other
+//! by the idle loop through [`run_background()`]. They run with IRQs unmasked, so the console
+//! stays responsive. A running task can't be preempted; killing it sets a flag that long-running
+//! commands poll with [`cancelled()`].
+//!
+//! In bounded-latency mode, lines typed at a console and due `at` and `every` commands are queued
+//! the same way with [`defer()`], ahead of the background tasks, so that they don't run in IRQ
+//! context.
+
+use crate::{
+    console, cpu, exception, info, latency, sched, shell,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time,
+};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Shortest interval of periodic jobs, so that a typo can't flood the timer.
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+struct Job {
+    id: usize,
+    command: String,
+    out: console::Output,
+    period: Option<Duration>,
+    due: Duration,
+
+    /// Set right after the job was added, as the callback needs the job id.
+    timeout: Option<time::TimeoutHandle>,
+}
+
+struct Task {
+    id: usize,
+    command: String,
+    out: console::Output,
+    running: bool,
+
+    /// Set for a line typed at a console, see [`defer()`].
+    deferred: bool,
+}
+
+struct JobTable {
+    jobs: Vec<Job>,
+
+    /// Background tasks in queue order. Jobs and tasks share the id space.
+    tasks: Vec<Task>,
+    next_id: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static JOBS: IRQSafeNullLock<JobTable> = IRQSafeNullLock::new(JobTable {
+    jobs: Vec::new(),
+    tasks: Vec::new(),
+    next_id: 1,
+});
+
+/// Set when the running background task is killed.
+static CANCEL: AtomicBool = AtomicBool::new(false);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Parse a non-negative number that fits the given bound.
+fn parse_bounded(s: &str, max: u64) -> Result<u64, &'static str> {
+    match s.parse::<u64>() {
+        Ok(v) if v <= max => Ok(v),
+        _ => Err("Invalid time"),
+    }
+}
+
+/// Called by the timer when job `id` is due.
+fn run(id: usize) {
+    // A job cancelled while its timeout fired isn't found anymore.
+    let command = JOBS.lock(|table| {
+        let pos = table.jobs.iter().position(|j| j.id == id)?;
+        let job = &mut table.jobs[pos];
+
+        match job.period {
+            Some(period) => {
+                job.due += period;
+                Some((job.out, job.command.clone()))
+            }
+            None => {
+                let job = table.jobs.remove(pos);
+                Some((job.out, job.command))
+            }
+        }
+    });
+
+    match command {
+        Some((out, command)) if latency::is_bounded() => defer(out, &command),
+        Some((out, command)) => shell::execute(out, &command),
+        None => (),
+    }
+}
+
+impl JobTable {
+    fn next_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        id
+    }
+}
+
+fn add(
+    out: console::Output,
+    command: &str,
+    due: Duration,
+    period: Option<Duration>,
+) -> Result<usize, &'static str> {
+    if command.is_empty() {
+        return Err("Missing command");
+    }
+
+    let id = JOBS.lock(|table| {
+        let id = table.next_id();
+        table.jobs.push(Job {
+            id,
+            command: command.to_string(),
+            out,
+            period,
+            due,
+            timeout: None,
+        });
+
+        id
+    });
+
+    let delay = due.saturating_sub(time::time_manager().uptime());
+    let timeout = match period {
+        Some(period) => {
+            time::time_manager().set_timeout_periodic(period, Box::new(move || run(id)))
+        }
+        None => time::time_manager().set_timeout_once(delay, Box::new(move || run(id))),
+    };
+    JOBS.lock(|table| {
+        if let Some(job) = table.jobs.iter_mut().find(|j| j.id == id) {
+            job.timeout = Some(timeout);
+        }
+    });
+
+    Ok(id)
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Parse a time of day as `hh:mm:ss`, returned as the offset from midnight.
+pub fn parse_time_of_day(s: &str) -> Result<Duration, &'static str> {
+    let mut parts = s.split(':');
+    let (h, m, sec) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(h), Some(m), Some(sec), None) => (h, m, sec),
+        _ => return Err("Invalid time"),
+    };
+
+    let secs = parse_bounded(h, u32::MAX as u64)? * 3600
+        + parse_bounded(m, 59)? * 60
+        + parse_bounded(sec, 59)?;
+
+    Ok(Duration::from_secs(secs))
+}
+
+/// Parse an interval, either as `hh:mm:ss` or as a number with one of the units `ms`, `s`, `m`
+/// or `h`.
+pub fn parse_interval(s: &str) -> Result<Duration, &'static str> {
+    if s.contains(':') {
+        return parse_time_of_day(s);
+    }
+
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or("Missing unit")?;
+    let value = parse_bounded(&s[..split], u32::MAX as u64)?;
+
+    match &s[split..] {
+        "ms" => Ok(Duration::from_millis(value)),
+        "s" => Ok(Duration::from_secs(value)),
+        "m" => Ok(Duration::from_secs(value * 60)),
+        "h" => Ok(Duration::from_secs(value * 3600)),
+        _ => Err("Unknown unit"),
+    }
+}
+
+/// Run `command` once, when the uptime reaches `at`, printing to `out`. Returns the job id.
+pub fn schedule_at(
+    out: console::Output,
+    at: Duration,
+    command: &str,
+) -> Result<usize, &'static str> {
+    if at <= time::time_manager().uptime() {
+        return Err("Time already passed");
+    }
+
+    add(out, command, at, None)
+}
+
+/// Run `command` every `interval`, starting one interval from now, printing to `out`. Returns the
+/// job id.
+pub fn schedule_every(
+    out: console::Output,
+    interval: Duration,
+    command: &str,
+) -> Result<usize, &'static str> {
+    if interval < MIN_INTERVAL {
+        return Err("Interval too short");
+    }
+
+    add(
+        out,
+        command,
+        time::time_manager().uptime() + interval,
+        Some(interval),
+    )
+}
+
+/// Queue `command` to run in the background, printing to `out`. Returns the task id.
+pub fn spawn(out: console::Output, command: &str) -> Result<usize, &'static str> {
+    if command.is_empty() {
+        return Err("Missing command");
+    }
+
+    Ok(JOBS.lock(|table| {
+        let id = table.next_id();
+        table.tasks.push(Task {
+            id,
+            command: command.to_string(),
+            out,
+            running: false,
+            deferred: false,
+        });
+
+        id
+    }))
+}
+
+/// Queue a line, typed at a console or run by a due job, to run from the idle loop, after the
+/// lines queued before but ahead of the background tasks.
+///
+/// Can be called from IRQ context.
+pub fn defer(out: console::Output, line: &str) {
+    JOBS.lock(|table| {
+        let id = table.next_id();
+        let pos = table
+            .tasks
+            .iter()
+            .position(|t| !t.running && !t.deferred)
+            .unwrap_or(table.tasks.len());
+
+        table.tasks.insert(
+            pos,
+            Task {
+                id,
+                command: line.to_string(),
+                out,
+                running: false,
+                deferred: true,
+            },
+        );
+    });
+}
+
+/// Run the next queued background task, or wait for an interrupt if there is none and no
+/// scheduler task is ready either.
+///
+/// Must be called in a loop from thread context with IRQs unmasked.
+pub fn run_background() {
+    // Checking the queue and waiting happen with IRQs masked, so that a task queued in between
+    // can't be missed. A pending IRQ still ends the wait.
+    let task = exception::asynchronous::exec_with_irq_masked(|| {
+        let task = JOBS.lock(|table| {
+            let task = table.tasks.first_mut()?;
+            task.running = true;
+
+            Some((task.id, task.out, task.command.clone()))
+        });
+        if task.is_none() && !sched::is_ready() {
+            cpu::wait_for_interrupt();
+            latency::restart();
+        }
+
+        task
+    });
+
+    let (id, out, command) = match task {
+        Some(t) => t,
+        None => return,
+    };
+
+    shell::execute(out, &command);
+    JOBS.lock(|table| table.tasks.retain(|t| t.id != id));
+    CANCEL.store(false, Ordering::Relaxed);
+}
+
+/// Return if the running background task was killed. Long-running commands poll this and stop
+/// early.
+pub fn cancelled() -> bool {
+    CANCEL.load(Ordering::Relaxed)
+}
+
+/// Cancel a scheduled job or kill a background task.
+pub fn cancel(id: usize) -> Result<(), &'static str> {
+    JOBS.lock(|table| {
+        if let Some(pos) = table.jobs.iter().position(|j| j.id == id) {
+            if let Some(timeout) = table.jobs.remove(pos).timeout {
+                timeout.cancel();
+            }
+            return Ok(());
+        }
+
+        let pos = table
+            .tasks
+            .iter()
+            .position(|t| t.id == id)
+            .ok_or("No such job")?;
+        if table.tasks[pos].running {
+            CANCEL.store(true, Ordering::Relaxed);
+        } else {
+            table.tasks.remove(pos);
+        }
+
+        Ok(())
+    })
+}
+
+/// Print the scheduled jobs and background tasks.
+pub fn print() {
+    JOBS.lock(|table| {
+        for j in &table.jobs {
+            let when = match j.period {
+                Some(p) => format!("every {}", time::Human(p)),
+                None => format!("at {}", time::Clock(j.due)),
+            };
+
+            info!("      {:>3}  {:<20} {}", j.id, when, j.command);
+        }
+        for t in &table.tasks {
+            let state = if t.running { "running" } else { "queued" };
+
+            info!("      {:>3}  {:<20} {}", t.id, state, t.command);
+        }
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Times and intervals must parse in all supported notations and reject malformed input.
+    #[kernel_test]
+    fn parse_times() {
+        assert_eq!(parse_time_of_day("01:02:03"), Ok(Duration::from_secs(3723)));
+        assert!(parse_time_of_day("00:60:00").is_err());
+        assert!(parse_time_of_day("12:00").is_err());
+
+        assert_eq!(parse_interval("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_interval("10s"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_interval("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_interval("00:00:30"), Ok(Duration::from_secs(30)));
+        assert!(parse_interval("10").is_err());
+        assert!(parse_interval("10d").is_err());
+    }
+}
@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! IRQ latency audit.
+//!
+//! Measures how long IRQs stay masked on each core: every outermost section of
+//! [`exception::asynchronous::exec_with_irq_masked()`], which the IRQ-safe locks use, and every IRQ
+//! handler. The worst time is kept per call site, for `latency report`.
+//!
+//! Setting a bound enables bounded-latency mode. Lines typed at a console are then queued for the
+//! idle loop instead of running in the console's IRQ handler, and the PL011 UART drops echoed
+//! characters instead of waiting for room in its TX buffer. Sections that still exceed the bound
+//! are counted per site.
+//!
+//! Recording takes no lock and doesn't allocate, so it is safe in any context. Call sites beyond
+//! [`NUM_SITES`] are only counted in the totals.
+
+use crate::{bsp, config, cpu, exception, info, time};
+use alloc::{format, vec::Vec};
+use core::{
+    panic::Location,
+    ptr,
+    sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, Ordering},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of call sites that are told apart.
+const NUM_SITES: usize = 64;
+
+const BOUND_KEY: &str = "latency.bound_us";
+
+/// The worst masked section of one call site.
+struct Site {
+    location: AtomicPtr<Location<'static>>,
+    handler: AtomicBool,
+    sections: AtomicU64,
+    worst_ns: AtomicU64,
+    over_bound: AtomicU64,
+}
+
+/// A snapshot of a [`Site`], for the report.
+struct SiteReport {
+    location: &'static Location<'static>,
+    handler: bool,
+    sections: u64,
+    worst_ns: u64,
+    over_bound: u64,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_SITE: Site = Site {
+    location: AtomicPtr::new(ptr::null_mut()),
+    handler: AtomicBool::new(false),
+    sections: AtomicU64::new(0),
+    worst_ns: AtomicU64::new(0),
+    over_bound: AtomicU64::new(0),
+};
+
+static SITES: [Site; NUM_SITES] = [EMPTY_SITE; NUM_SITES];
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_START: AtomicU64 = AtomicU64::new(0);
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_LOCATION: AtomicPtr<Location<'static>> = AtomicPtr::new(ptr::null_mut());
+
+/// Start of the open section of each core, in nanoseconds of uptime.
+static START_NS: [AtomicU64; bsp::cpu::NUM_CORES] = [NO_START; bsp::cpu::NUM_CORES];
+
+/// Call site of the open section of each core.
+static START_LOCATION: [AtomicPtr<Location<'static>>; bsp::cpu::NUM_CORES] =
+    [NO_LOCATION; bsp::cpu::NUM_CORES];
+
+/// The bound in nanoseconds, 0 if bounded-latency mode is off.
+static BOUND_NS: AtomicU64 = AtomicU64::new(0);
+
+static TOTAL_SECTIONS: AtomicU64 = AtomicU64::new(0);
+static TOTAL_OVER_BOUND: AtomicU64 = AtomicU64::new(0);
+static UNTRACKED: AtomicU64 = AtomicU64::new(0);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return the slot of `location`, taking a free one if it has none yet.
+fn site(location: *mut Location<'static>) -> Option<&'static Site> {
+    for site in SITES.iter() {
+        let current = site.location.load(Ordering::Acquire);
+        if current == location {
+            return Some(site);
+        }
+        if current.is_null() {
+            match site.location.compare_exchange(
+                ptr::null_mut(),
+                location,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(site),
+                // Taken by another core meanwhile, maybe for the same location.
+                Err(other) if other == location => return Some(site),
+                Err(_) => (),
+            }
+        }
+    }
+
+    None
+}
+
+fn start(location: &'static Location<'static>) {
+    let core = cpu::smp::core_id::<usize>();
+
+    START_LOCATION[core].store(location as *const _ as *mut _, Ordering::Relaxed);
+    START_NS[core].store(time::uptime_ns(), Ordering::Relaxed);
+}
+
+fn end(handler: bool) {
+    let core = cpu::smp::core_id::<usize>();
+    let location = START_LOCATION[core].swap(ptr::null_mut(), Ordering::Relaxed);
+    if location.is_null() {
+        return;
+    }
+    let duration = time::uptime_ns().saturating_sub(START_NS[core].load(Ordering::Relaxed));
+
+    let bound = BOUND_NS.load(Ordering::Relaxed);
+    let over = bound != 0 && duration > bound;
+    TOTAL_SECTIONS.fetch_add(1, Ordering::Relaxed);
+    if over {
+        TOTAL_OVER_BOUND.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let site = match site(location) {
+        Some(site) => site,
+        None => {
+            UNTRACKED.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    };
+    site.handler.store(handler, Ordering::Relaxed);
+    site.sections.fetch_add(1, Ordering::Relaxed);
+    site.worst_ns.fetch_max(duration, Ordering::Relaxed);
+    if over {
+        site.over_bound.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn reports() -> Vec<SiteReport> {
+    let mut reports: Vec<SiteReport> = SITES
+        .iter()
+        .filter_map(|s| {
+            let location = s.location.load(Ordering::Acquire);
+            if location.is_null() {
+                return None;
+            }
+
+            Some(SiteReport {
+                location: unsafe { &*location },
+                handler: s.handler.load(Ordering::Relaxed),
+                sections: s.sections.load(Ordering::Relaxed),
+                worst_ns: s.worst_ns.load(Ordering::Relaxed),
+                over_bound: s.over_bound.load(Ordering::Relaxed),
+            })
+        })
+        .collect();
+    reports.sort_unstable_by_key(|r| u64::MAX - r.worst_ns);
+
+    reports
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Note that the caller just masked IRQs that were unmasked before.
+#[track_caller]
+pub fn masked() {
+    start(Location::caller());
+}
+
+/// Note that the caller is about to restore IRQs, ending the section begun by [`masked()`].
+pub fn unmasking() {
+    end(false);
+}
+
+/// Note the start of an IRQ handler.
+#[track_caller]
+pub fn handler_entered() {
+    start(Location::caller());
+}
+
+/// Note the end of an IRQ handler, before anything else may run on the core.
+pub fn handler_leaving() {
+    end(true);
+}
+
+/// Restart the open section of the executing core, after waiting for an interrupt with IRQs masked.
+/// Waiting delays no IRQ, as a pending one ends the wait.
+pub fn restart() {
+    START_NS[cpu::smp::core_id::<usize>()].store(time::uptime_ns(), Ordering::Relaxed);
+}
+
+/// Return if bounded-latency mode is on.
+pub fn is_bounded() -> bool {
+    BOUND_NS.load(Ordering::Relaxed) != 0
+}
+
+/// Return the bound in microseconds, 0 if bounded-latency mode is off.
+pub fn bound_us() -> u64 {
+    BOUND_NS.load(Ordering::Relaxed) / 1000
+}
+
+/// Set the bound in microseconds, turning bounded-latency mode on, or off with 0.
+pub fn set_bound_us(us: u64) {
+    BOUND_NS.store(us.saturating_mul(1000), Ordering::Relaxed);
+}
+
+/// Apply the bound from the config store, if set.
+pub fn load_bound() -> Result<(), &'static str> {
+    match config::store().get(BOUND_KEY) {
+        Some(us) => {
+            set_bound_us(us.parse().map_err(|_| "Malformed latency bound")?);
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Write the bound to the config store. It is persisted on the next save.
+pub fn store_bound() -> Result<(), &'static str> {
+    config::store().set(BOUND_KEY, &format!("{}", bound_us()))
+}
+
+/// Forget the measurements.
+pub fn reset() {
+    exception::asynchronous::exec_with_irq_masked(|| {
+        for s in SITES.iter() {
+            s.sections.store(0, Ordering::Relaxed);
+            s.worst_ns.store(0, Ordering::Relaxed);
+            s.over_bound.store(0, Ordering::Relaxed);
+        }
+        TOTAL_SECTIONS.store(0, Ordering::Relaxed);
+        TOTAL_OVER_BOUND.store(0, Ordering::Relaxed);
+        UNTRACKED.store(0, Ordering::Relaxed);
+    });
+}
+
+/// Print the call sites, longest masked section first.
+pub fn print() {
+    let bound = BOUND_NS.load(Ordering::Relaxed);
+    match bound {
+        0 => info!("      Bound:      off"),
+        ns => info!("      Bound:      {} us", ns / 1000),
+    }
+    info!(
+        "      Sections:   {}, {} over the bound, {} from untracked sites",
+        TOTAL_SECTIONS.load(Ordering::Relaxed),
+        TOTAL_OVER_BOUND.load(Ordering::Relaxed),
+        UNTRACKED.load(Ordering::Relaxed)
+    );
+    info!(
+        "      {:>10} {:>10} {:>8}  {:<7}  Site",
+        "Worst us", "Sections", "Over", "Kind"
+    );
+
+    for r in reports() {
+        let mark = if bound != 0 && r.worst_ns > bound {
+            " !"
+        } else {
+            ""
+        };
+
+        info!(
+            "      {:>10} {:>10} {:>8}  {:<7}  {}:{}{}",
+            r.worst_ns / 1000,
+            r.sections,
+            r.over_bound,
+            if r.handler { "handler" } else { "masked" },
+            r.location.file(),
+            r.location.line(),
+            mark
+        );
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A measured section must land at its call site, and one over the bound must be counted.
+    #[kernel_test]
+    fn sections_are_recorded_per_site() {
+        let location = Location::caller();
+        let find = || {
+            reports()
+                .into_iter()
+                .find(|r| ptr::eq(r.location, location))
+        };
+
+        // Masked, so that no IRQ handler ends the section early.
+        set_bound_us(1);
+        exception::asynchronous::exec_with_irq_masked(|| {
+            start(location);
+            time::time_manager().spin_for(core::time::Duration::from_micros(10));
+            end(false);
+        });
+        set_bound_us(0);
+
+        let report = find().unwrap();
+        assert_eq!(report.sections, 1);
+        assert_eq!(report.over_bound, 1);
+        assert!(report.worst_ns >= 10_000);
+
+        // Without an open section, nothing is recorded.
+        end(false);
+        assert_eq!(find().unwrap().sections, 1);
+    }
+}
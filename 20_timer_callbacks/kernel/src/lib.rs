@@ -111,7 +111,9 @@
 #![allow(clippy::upper_case_acronyms)]
 #![allow(incomplete_features)]
 #![feature(alloc_error_handler)]
+#![feature(allocator_api)]
 #![feature(asm_const)]
+#![feature(c_variadic)]
 #![feature(const_option)]
 #![feature(core_intrinsics)]
 #![feature(format_args_nl)]
@@ -137,17 +139,57 @@ mod panic_wait;
 mod synchronization;
 
 pub mod backtrace;
+pub mod bench;
+pub mod block;
+pub mod bluetooth;
 pub mod bsp;
+pub mod build_config;
+pub mod capture;
+pub mod chainload;
+pub mod clocking;
 pub mod common;
+pub mod config;
 pub mod console;
 pub mod cpu;
+#[cfg(feature = "c_runtime")]
+pub mod crt;
+pub mod diag;
+pub mod dma;
 pub mod driver;
+pub mod event;
 pub mod exception;
+pub mod gpio_history;
+pub mod gpio_selftest;
+pub mod hil;
+pub mod identity;
+pub mod jobs;
+pub mod latency;
+pub mod log;
 pub mod memory;
+pub mod motor;
+pub mod net;
+pub mod pattern;
+pub mod power;
 pub mod print;
+pub mod process;
+pub mod rand;
+pub mod rc;
+pub mod sched;
+pub mod session;
+pub mod shell;
+pub mod shutdown;
+pub mod siggen;
 pub mod state;
+pub mod stats;
+pub mod subsys;
 pub mod symbols;
+pub mod syscall;
+pub mod sysreg;
+pub mod task;
 pub mod time;
+pub mod trace;
+pub mod usb;
+pub mod watchdog;
 
 //--------------------------------------------------------------------------------------------------
 // Public Code
@@ -0,0 +1,259 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Kernel log.
+//!
+//! Every line printed with `info!` or `warn!` is also kept here, with its level and timestamp, in a
+//! ring of [`LOG_SIZE`] bytes that drops the oldest lines first. Recording doesn't depend on any
+//! console, so the log still holds what scrolled past, went to a slow or switched off console, or
+//! was printed before the UART came up. Read it with [`snapshot()`] or the `dmesg` command.
+//!
+//! Unlike the console's `log` sink, which keeps the raw output while switched on, the log is always
+//! on and keeps lines, not characters. Lines longer than [`MAX_LINE`] bytes are cut.
+//!
+//! Nothing here allocates until a snapshot is taken, so lines logged before the heap is up are kept
+//! too.
+
+use crate::synchronization::{interface::Mutex, IRQSafeNullLock};
+use alloc::{string::String, vec::Vec};
+use core::{fmt, time::Duration};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Bytes a record takes before its text: the level, the timestamp in microseconds and the length.
+const HEADER_SIZE: usize = 1 + 8 + 2;
+
+/// Ring of records, each a header followed by the text.
+struct Ring {
+    buf: [u8; LOG_SIZE],
+    head: usize,
+    len: usize,
+    lines: usize,
+    dropped: u64,
+}
+
+/// Formats into a fixed buffer, cutting what doesn't fit.
+struct LineBuffer {
+    buf: [u8; MAX_LINE],
+    len: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of bytes the log keeps, headers included.
+pub const LOG_SIZE: usize = 32 * 1024;
+
+/// Longest line kept, in bytes.
+pub const MAX_LINE: usize = 256;
+
+/// Severity of a line.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Level {
+    Info,
+    Warn,
+}
+
+/// A logged line.
+pub struct Entry {
+    pub level: Level,
+
+    /// Uptime when the line was logged.
+    pub timestamp: Duration,
+
+    pub text: String,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static LOG: IRQSafeNullLock<Ring> = IRQSafeNullLock::new(Ring::new());
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Level {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Warn,
+            _ => Self::Info,
+        }
+    }
+}
+
+impl Ring {
+    const fn new() -> Self {
+        Self {
+            // Zeroed, so this lands in .bss.
+            buf: [0; LOG_SIZE],
+            head: 0,
+            len: 0,
+            lines: 0,
+            dropped: 0,
+        }
+    }
+
+    fn byte(&self, offset: usize) -> u8 {
+        self.buf[(self.head + offset) % LOG_SIZE]
+    }
+
+    /// Return the size of the record at `offset`.
+    fn record_size(&self, offset: usize) -> usize {
+        let len = u16::from_le_bytes([self.byte(offset + 9), self.byte(offset + 10)]);
+
+        HEADER_SIZE + len as usize
+    }
+
+    fn drop_oldest(&mut self) {
+        let size = self.record_size(0);
+        self.head = (self.head + size) % LOG_SIZE;
+        self.len -= size;
+        self.lines -= 1;
+        self.dropped += 1;
+    }
+
+    fn push(&mut self, level: Level, timestamp: Duration, text: &[u8]) {
+        let size = HEADER_SIZE + text.len();
+        while LOG_SIZE - self.len < size {
+            self.drop_oldest();
+        }
+
+        let micros = timestamp.as_micros() as u64;
+        let header = core::iter::once(level as u8)
+            .chain(micros.to_le_bytes())
+            .chain((text.len() as u16).to_le_bytes());
+        for (i, b) in header.chain(text.iter().copied()).enumerate() {
+            self.buf[(self.head + self.len + i) % LOG_SIZE] = b;
+        }
+        self.len += size;
+        self.lines += 1;
+    }
+
+    fn entries(&self) -> Vec<Entry> {
+        let mut entries = Vec::with_capacity(self.lines);
+        let mut offset = 0;
+
+        while offset < self.len {
+            let size = self.record_size(offset);
+            let mut micros = [0; 8];
+            micros
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, b)| *b = self.byte(offset + 1 + i));
+            let text: Vec<u8> = (offset + HEADER_SIZE..offset + size)
+                .map(|i| self.byte(i))
+                .collect();
+
+            entries.push(Entry {
+                level: Level::from_u8(self.byte(offset)),
+                timestamp: Duration::from_micros(u64::from_le_bytes(micros)),
+                text: String::from_utf8_lossy(&text).into_owned(),
+            });
+            offset += size;
+        }
+
+        entries
+    }
+}
+
+impl LineBuffer {
+    const fn new() -> Self {
+        Self {
+            buf: [0; MAX_LINE],
+            len: 0,
+        }
+    }
+}
+
+impl fmt::Write for LineBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // Cut at a character boundary, so that the text stays valid UTF-8.
+        for c in s.chars() {
+            let mut utf8 = [0; 4];
+            let c = c.encode_utf8(&mut utf8).as_bytes();
+            if self.len + c.len() > MAX_LINE {
+                break;
+            }
+            self.buf[self.len..self.len + c.len()].copy_from_slice(c);
+            self.len += c.len();
+        }
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Level {
+    /// The marker of the level in printed lines.
+    pub fn marker(&self) -> char {
+        match self {
+            Self::Info => ' ',
+            Self::Warn => 'W',
+        }
+    }
+}
+
+/// Keep a line. Called by the printing macros.
+pub fn record(level: Level, timestamp: Duration, args: fmt::Arguments) {
+    let mut line = LineBuffer::new();
+    let _ = fmt::Write::write_fmt(&mut line, args);
+
+    LOG.lock(|log| log.push(level, timestamp, &line.buf[..line.len]));
+}
+
+/// Return a copy of the logged lines, oldest first.
+pub fn snapshot() -> Vec<Entry> {
+    LOG.lock(|log| log.entries())
+}
+
+/// Drop all logged lines.
+pub fn clear() {
+    LOG.lock(|log| {
+        log.head = 0;
+        log.len = 0;
+        log.lines = 0;
+    });
+}
+
+/// Return the number of lines dropped to make room for newer ones.
+pub fn dropped() -> u64 {
+    LOG.lock(|log| log.dropped)
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Once full, the ring must drop whole lines, oldest first.
+    #[kernel_test]
+    fn ring_drops_oldest_lines() {
+        let line = [b'x'; MAX_LINE];
+        let per_line = HEADER_SIZE + MAX_LINE;
+        let fit = LOG_SIZE / per_line;
+        let mut ring = alloc::boxed::Box::new(Ring::new());
+
+        for i in 0..fit + 2 {
+            ring.push(Level::Info, Duration::from_micros(i as u64), &line);
+        }
+
+        let entries = ring.entries();
+        assert_eq!(entries.len(), fit);
+        assert_eq!(ring.dropped, 2);
+        assert_eq!(entries[0].timestamp, Duration::from_micros(2));
+        assert_eq!(entries[0].text.len(), MAX_LINE);
+    }
+}
@@ -9,7 +9,13 @@ extern crate alloc;
 use core::time::Duration;
 
 use alloc::boxed::Box;
-use libkernel::{bsp, cpu, driver, exception, info, memory, state, time};
+#[cfg(feature = "usb_host")]
+use libkernel::usb;
+use libkernel::{
+    bsp, config, console, cpu, diag, driver, event, exception, identity, info, jobs, latency,
+    memory, motor, net, pattern, sched, shell, shutdown, siggen, state, stats, subsys, time, trace,
+    warn, watchdog,
+};
 
 /// - Only a single core must be active and running this function.
 /// - Printing will not work until the respective driver's MMIO is remapped.
@@ -18,10 +24,16 @@ unsafe fn kernel_init() -> ! {
     exception::handling_init();
     memory::init();
 
+    // Allocate the trace buffer, now that the heap is available.
+    if let Err(x) = trace::init() {
+        panic!("Error initializing trace buffer: {}", x);
+    }
+
     // Initialize the timer subsystem.
     if let Err(x) = time::init() {
         panic!("Error initializing timer subsystem: {}", x);
     }
+    cpu::enable_cycle_counter();
 
     // Initialize the BSP driver subsystem.
     if let Err(x) = bsp::driver::init() {
@@ -31,6 +43,105 @@ unsafe fn kernel_init() -> ! {
     // Initialize all device drivers.
     driver::driver_manager().init_drivers_and_irqs();
 
+    // Check the counter frequency before anything relies on delays.
+    if let Err(x) = time::calibrate() {
+        warn!("Error calibrating the timer: {}", x);
+    }
+
+    // Log all kernel events.
+    event::event_bus().subscribe(Box::new(|e| info!("Event: {}", e)));
+
+    // Initialize the network stack.
+    if let Err(x) = net::init() {
+        panic!("Error initializing network subsystem: {}", x);
+    }
+
+    // Register the kernel's shell commands. The BSP registered its own with the console.
+    if let Err(x) = shell::init() {
+        warn!("Error registering shell commands: {}", x);
+    }
+
+    // Load the settings and count this boot. Neither is needed to boot, so failures only warn.
+    if let Err(x) = config::store().load() {
+        warn!("Error loading config store: {}", x);
+    }
+    if let Err(x) = identity::init() {
+        warn!("Error initializing board identity: {}", x);
+    }
+    if let Err(x) = stats::init() {
+        warn!("Error initializing boot statistics: {}", x);
+    }
+    if let Err(x) = bsp::driver::gpio_load_protected_pins() {
+        warn!("Error loading protected GPIO pins: {}", x);
+    }
+    if let Err(x) = console::line_discipline::load() {
+        warn!("Error loading console options: {}", x);
+    }
+    if let Err(x) = net::net_stack().load_config() {
+        warn!("Error loading network configuration: {}", x);
+    }
+    if let Err(x) = bsp::driver::uart_load_rx_tuning() {
+        warn!("Error loading UART RX tuning: {}", x);
+    }
+    if let Err(x) = time::time_manager().load_overload_policy() {
+        warn!("Error loading timer overload policy: {}", x);
+    }
+    if let Err(x) = pattern::load_brightness() {
+        warn!("Error loading LED brightness: {}", x);
+    }
+    if let Err(x) = latency::load_bound() {
+        warn!("Error loading latency bound: {}", x);
+    }
+    if let Err(x) = sched::init() {
+        warn!("Error initializing scheduler: {}", x);
+    }
+    if let Err(x) = watchdog::init() {
+        warn!("Error initializing watchdog: {}", x);
+    }
+
+    // Restarting the patterns stops the running one and turns the LEDs off.
+    if let Err(x) = subsys::register(
+        "patterns",
+        || {
+            pattern::stop();
+            pattern::reset_pins();
+            Ok(())
+        },
+        || Ok(()),
+    ) {
+        warn!("Error registering patterns subsystem: {}", x);
+    }
+
+    // Leave the LEDs and the signal generator's pin low for the next image.
+    if let Err(x) = shutdown::register("patterns", shutdown::Stage::Quiesce, || {
+        pattern::stop();
+        pattern::reset_pins();
+        Ok(())
+    }) {
+        warn!("Error registering patterns shutdown hook: {}", x);
+    }
+    if let Err(x) = shutdown::register("siggen", shutdown::Stage::Quiesce, || {
+        siggen::stop();
+        Ok(())
+    }) {
+        warn!("Error registering siggen shutdown hook: {}", x);
+    }
+    if let Err(x) = shutdown::register("motors", shutdown::Stage::Quiesce, || {
+        motor::emergency_stop();
+        Ok(())
+    }) {
+        warn!("Error registering motors shutdown hook: {}", x);
+    }
+
+    // Take console input from a USB keyboard, if one is plugged in.
+    #[cfg(feature = "usb_host")]
+    if let Err(x) = usb::host::start() {
+        warn!("Error starting USB keyboard: {}", x);
+    }
+
+    diag::run_at_boot();
+    shell::run_autoexec();
+
     bsp::memory::mmu::kernel_add_mapping_records_for_precomputed();
 
     // Unmask interrupts on the boot CPU core.
@@ -45,14 +156,21 @@ unsafe fn kernel_init() -> ! {
 
 /// The main function running after the early init.
 fn kernel_main() -> ! {
-    use alloc::boxed::Box;
-    use core::time::Duration;
-
     show_logo();
     reset_gpio();
 
+    // The idle loop runs the background shell commands and the scheduler's tasks.
+    if let Err(x) = watchdog::register("shell", Duration::from_secs(60)) {
+        warn!("Error watching the shell: {}", x);
+    }
+
     info!("Echoing input now");
-    cpu::wait_forever();
+    loop {
+        watchdog::check_in("shell");
+        watchdog::check();
+        sched::run();
+        jobs::run_background();
+    }
 }
 
 fn show_logo() {
@@ -73,19 +191,16 @@ fn show_logo() {
 
 fn reset_gpio() {
     for pin_number in [1, 2, 3, 4, 5, 6] {
-        setup_output(pin_number);
         gpio_off(pin_number);
     }
 }
 
 fn gpio_off(pin: u8) {
-    setup_output(pin);
-    unsafe { bsp::driver::gpio_low(pin) };
-    // info!("{} off", pin);
-}
-
-fn setup_output(pin: u8) {
     unsafe {
-        bsp::driver::gpio_as_output(pin);
+        if let Err(x) =
+            bsp::driver::gpio_as_output(pin, false).and_then(|_| bsp::driver::gpio_low(pin, false))
+        {
+            warn!("GPIO {}: {}", pin, x);
+        }
     }
 }
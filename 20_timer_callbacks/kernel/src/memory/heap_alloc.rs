@@ -3,27 +3,171 @@
 // Copyright (c) 2022-2023 Andre Richter <andre.o.richter@gmail.com>
 
 //! Heap allocation.
+//!
+//! Blocks of up to 256 bytes are served by the [`slab`] caches, larger ones by an allocator that
+//! merges neighbouring free blocks. The caches take their slabs from that allocator as well. It is
+//! chosen at build time:
+//!
+//! - [`linked_list`], the default, takes the first free block that fits. The time that takes grows
+//!   with the number of free blocks.
+//! - [`tlsf`], with the `heap_tlsf` feature, takes a block from segregated free lists in constant
+//!   time, for builds where allocation latency matters more than the last bit of memory.
+//!
+//! The fragmentation reported is the share of the free memory that isn't in the largest block an
+//! allocation can get.
+//!
+//! When an allocation fails, the registered reclaimers are asked to free their caches and the
+//! allocation is retried once. If it still fails, the heap usage is printed and the kernel panics.
+//!
+//! Subsystems that could take more than their share, like the network stack, allocate through a
+//! [`quota::Quota`]. It refuses their allocations beyond a limit, so that the memory the console
+//! and timers need stays available.
+//!
+//! Reallocation avoids the copy where it can: a block keeps its place when its rounded size or its
+//! slab class doesn't change. A heap block gives its tail back when shrinking, and takes the memory
+//! right behind it when that is where the grown block would be allocated anyway.
+
+#[cfg(not(feature = "heap_tlsf"))]
+mod linked_list;
+pub mod quota;
+mod slab;
+#[cfg(feature = "heap_tlsf")]
+mod tlsf;
 
 use crate::{
     backtrace, bsp, common, debug, info,
     memory::{Address, Virtual},
-    synchronization,
+    rand, synchronization,
     synchronization::IRQSafeNullLock,
-    warn,
+    time, warn,
+};
+use alloc::{
+    alloc::{GlobalAlloc, Layout},
+    vec::Vec,
 };
-use alloc::alloc::{GlobalAlloc, Layout};
-use core::sync::atomic::{AtomicBool, Ordering};
-use linked_list_allocator::Heap as LinkedListHeap;
+use core::{
+    ptr::NonNull,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+use interface::Allocator;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Maximum number of reclaimers.
+const MAX_RECLAIMERS: usize = 8;
+
+#[derive(Copy, Clone)]
+struct ReclaimerEntry {
+    name: &'static str,
+    reclaim: Reclaimer,
+}
+
+/// Number of blocks the stress test keeps alive at most.
+const STRESS_SLOTS: usize = 64;
+
+#[cfg(not(feature = "heap_tlsf"))]
+type Backend = linked_list::LinkedList;
+
+#[cfg(feature = "heap_tlsf")]
+type Backend = tlsf::Tlsf;
+
+struct HeapInner {
+    heap: Backend,
+    slabs: slab::Caches,
+}
 
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
 //--------------------------------------------------------------------------------------------------
 
+/// Heap allocator interfaces.
+pub mod interface {
+    use alloc::alloc::Layout;
+    use core::ptr::NonNull;
+
+    /// An allocator managing one region of memory.
+    pub trait Allocator {
+        /// Name of the allocator.
+        const NAME: &'static str;
+
+        /// Manage the `size` bytes from `bottom` on.
+        ///
+        /// # Safety
+        ///
+        /// - The memory must be unused and stay valid for as long as the allocator is used.
+        unsafe fn init(&mut self, bottom: *mut u8, size: usize);
+
+        /// Allocate a block for `layout`.
+        fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>>;
+
+        /// Free a block.
+        ///
+        /// # Safety
+        ///
+        /// - `ptr` must have been allocated with [`Allocator::allocate()`] and `layout`.
+        unsafe fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout);
+
+        /// Resize the block at `ptr` to `new_size` bytes without moving it. Returns whether that
+        /// worked.
+        ///
+        /// # Safety
+        ///
+        /// - `ptr` must have been allocated with [`Allocator::allocate()`] and `layout`.
+        unsafe fn resize_in_place(&mut self, ptr: *mut u8, layout: Layout, new_size: usize)
+            -> bool;
+
+        /// Bytes in use.
+        fn used(&self) -> usize;
+
+        /// Bytes free, possibly scattered over many blocks.
+        fn free(&self) -> usize;
+
+        /// Size of the largest allocation that would currently succeed, in bytes.
+        fn largest_free_block(&mut self) -> usize;
+    }
+}
+
 /// A heap allocator that can be lazyily initialized.
 pub struct HeapAllocator {
-    inner: IRQSafeNullLock<LinkedListHeap>,
+    inner: IRQSafeNullLock<HeapInner>,
+
+    /// Highest number of bytes in use so far.
+    high_water: AtomicUsize,
+
+    /// Number of live allocations.
+    allocations: AtomicUsize,
+
+    /// Number of allocations that failed at the first attempt.
+    oom_events: AtomicUsize,
 }
 
+/// A snapshot of the heap usage, in bytes unless noted.
+#[derive(Copy, Clone, Debug)]
+pub struct Stats {
+    pub used: usize,
+    pub free: usize,
+    pub largest_free_block: usize,
+
+    /// Share of the free bytes outside the largest free block, in percent.
+    pub fragmentation: usize,
+
+    /// Number of slabs, and the bytes in them that are free. These bytes count as free.
+    pub slabs: usize,
+    pub slab_free: usize,
+
+    /// Number of live allocations.
+    pub allocations: usize,
+    pub high_water: usize,
+}
+
+/// Frees memory held by a cache and returns the number of bytes freed.
+///
+/// Reclaimers run inside the allocator, possibly while the allocating code holds other locks. They
+/// must not allocate, and must only free memory behind a lock that is never held while allocating.
+pub type Reclaimer = fn() -> usize;
+
 //--------------------------------------------------------------------------------------------------
 // Global instances
 //--------------------------------------------------------------------------------------------------
@@ -31,6 +175,10 @@ pub struct HeapAllocator {
 #[global_allocator]
 static KERNEL_HEAP_ALLOCATOR: HeapAllocator = HeapAllocator::new();
 
+/// Fixed size, so that registering and running reclaimers does not allocate.
+static RECLAIMERS: IRQSafeNullLock<[Option<ReclaimerEntry>; MAX_RECLAIMERS]> =
+    IRQSafeNullLock::new([None; MAX_RECLAIMERS]);
+
 //--------------------------------------------------------------------------------------------------
 // Private Code
 //--------------------------------------------------------------------------------------------------
@@ -57,6 +205,83 @@ fn debug_print_alloc_dealloc(operation: &'static str, ptr: *mut u8, layout: Layo
     );
 }
 
+impl HeapInner {
+    const fn new() -> Self {
+        Self {
+            heap: Backend::empty(),
+            slabs: slab::Caches::new(),
+        }
+    }
+
+    /// Bytes in use, not counting the free objects in slabs.
+    fn used(&self) -> usize {
+        self.heap.used() - self.slabs.cached()
+    }
+
+    fn free(&self) -> usize {
+        self.heap.free() + self.slabs.cached()
+    }
+
+    fn alloc(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        match slab::Caches::class_of(layout) {
+            Some(class) => self.slabs.alloc(&mut self.heap, class),
+            None => self.heap.allocate(layout),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated with [`HeapInner::alloc()`] and `layout`.
+    unsafe fn dealloc(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        match slab::Caches::class_of(layout) {
+            Some(class) => self.slabs.dealloc(&mut self.heap, class, ptr),
+            None => self.heap.deallocate(ptr, layout),
+        }
+    }
+
+    /// Resize the block at `ptr` without moving it. Returns whether that worked.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated with [`HeapInner::alloc()`] and `layout`.
+    unsafe fn resize_in_place(&mut self, ptr: *mut u8, layout: Layout, new_size: usize) -> bool {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+
+        // A block must be freed where the layout it is freed with says, so it can't change between
+        // a slab and the heap.
+        match (
+            slab::Caches::class_of(layout),
+            slab::Caches::class_of(new_layout),
+        ) {
+            (None, None) => self.heap.resize_in_place(ptr, layout, new_size),
+            (old, new) => old.is_some() && old == new,
+        }
+    }
+}
+
+/// Share of `free` outside a block of `largest` bytes, in percent.
+fn fragmentation(free: usize, largest: usize) -> usize {
+    match free {
+        0 => 0,
+        _ => (free - largest.min(free)) * 100 / free,
+    }
+}
+
+/// Run all reclaimers. Returns the number of bytes freed.
+fn reclaim() -> usize {
+    let reclaimers = RECLAIMERS.lock(|r| *r);
+
+    reclaimers
+        .iter()
+        .flatten()
+        .map(|r| {
+            let freed = (r.reclaim)();
+            warn!("Kernel Heap: {} freed {} Byte", r.name, freed);
+            freed
+        })
+        .sum()
+}
+
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
@@ -64,9 +289,29 @@ use synchronization::interface::Mutex;
 
 #[alloc_error_handler]
 fn alloc_error_handler(layout: Layout) -> ! {
+    warn!("Kernel Heap: out of memory, reclaiming did not help");
+    KERNEL_HEAP_ALLOCATOR.print_usage();
+
     panic!("Allocation error: {:?}", layout)
 }
 
+/// Register a reclaimer that frees a cache when the heap is exhausted.
+pub fn register_reclaimer(name: &'static str, reclaim: Reclaimer) -> Result<(), &'static str> {
+    RECLAIMERS.lock(|reclaimers| {
+        if reclaimers.iter().flatten().any(|r| r.name == name) {
+            return Err("Reclaimer already registered");
+        }
+
+        let slot = reclaimers
+            .iter_mut()
+            .find(|r| r.is_none())
+            .ok_or("Too many reclaimers")?;
+        *slot = Some(ReclaimerEntry { name, reclaim });
+
+        Ok(())
+    })
+}
+
 /// Return a reference to the kernel's heap allocator.
 pub fn kernel_heap_allocator() -> &'static HeapAllocator {
     &KERNEL_HEAP_ALLOCATOR
@@ -76,16 +321,68 @@ impl HeapAllocator {
     /// Create an instance.
     pub const fn new() -> Self {
         Self {
-            inner: IRQSafeNullLock::new(LinkedListHeap::empty()),
+            inner: IRQSafeNullLock::new(HeapInner::new()),
+            high_water: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+            oom_events: AtomicUsize::new(0),
+        }
+    }
+
+    /// Bytes in use, including the bookkeeping of the slabs.
+    pub fn used(&self) -> usize {
+        self.inner.lock(|inner| inner.used())
+    }
+
+    /// Bytes free, possibly scattered over many blocks.
+    pub fn free(&self) -> usize {
+        self.inner.lock(|inner| inner.free())
+    }
+
+    /// Size of the largest allocation that would currently succeed, in bytes.
+    pub fn largest_free_block(&self) -> usize {
+        self.inner.lock(|inner| inner.heap.largest_free_block())
+    }
+
+    /// Number of live allocations.
+    pub fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::Relaxed)
+    }
+
+    /// Highest number of bytes in use so far.
+    pub fn high_water(&self) -> usize {
+        self.high_water.load(Ordering::Relaxed)
+    }
+
+    /// Return all of the usage at once, consistent with each other.
+    pub fn stats(&self) -> Stats {
+        let (used, free, largest_free_block, slabs, slab_free) = self.inner.lock(|inner| {
+            (
+                inner.used(),
+                inner.free(),
+                inner.heap.largest_free_block(),
+                inner.slabs.slabs(),
+                inner.slabs.cached(),
+            )
+        });
+
+        Stats {
+            used,
+            free,
+            largest_free_block,
+            fragmentation: fragmentation(free, largest_free_block),
+            slabs,
+            slab_free,
+            allocations: self.allocations(),
+            high_water: self.high_water(),
         }
     }
 
     /// Print the current heap usage.
     pub fn print_usage(&self) {
-        let (used, free) = KERNEL_HEAP_ALLOCATOR
-            .inner
-            .lock(|inner| (inner.used(), inner.free()));
+        let stats = self.stats();
+        let (used, free) = (stats.used, stats.free);
 
+        info!("      Allocator: {}", Backend::NAME);
         if used >= 1024 {
             let (used_h, used_unit) = common::size_human_readable_ceil(used);
             info!("      Used: {} Byte ({} {})", used, used_h, used_unit);
@@ -99,20 +396,65 @@ impl HeapAllocator {
         } else {
             info!("      Free: {} Byte", free);
         }
+
+        let (largest_h, largest_unit) = common::size_human_readable_ceil(stats.largest_free_block);
+        info!(
+            "      Largest free block: {} Byte ({} {})",
+            stats.largest_free_block, largest_h, largest_unit
+        );
+        info!("      Fragmentation: {}%", stats.fragmentation);
+        info!(
+            "      Slabs: {} ({} Byte free in them)",
+            stats.slabs, stats.slab_free
+        );
+        info!("      Allocations: {}", stats.allocations);
+
+        let (high_h, high_unit) = common::size_human_readable_ceil(stats.high_water);
+        info!(
+            "      High water: {} Byte ({} {})",
+            stats.high_water, high_h, high_unit
+        );
+        info!(
+            "      Out of memory events: {}",
+            self.oom_events.load(Ordering::Relaxed)
+        );
+    }
+
+    /// Try to allocate, tracking the high-water mark.
+    fn try_alloc(&self, layout: Layout) -> Option<*mut u8> {
+        let (result, used) = self.inner.lock(|inner| (inner.alloc(layout), inner.used()));
+        self.high_water.fetch_max(used, Ordering::Relaxed);
+
+        result.map(|allocation| {
+            self.allocations.fetch_add(1, Ordering::Relaxed);
+            allocation.as_ptr()
+        })
     }
 }
 
 unsafe impl GlobalAlloc for HeapAllocator {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        let result = KERNEL_HEAP_ALLOCATOR
-            .inner
-            .lock(|inner| inner.allocate_first_fit(layout).ok());
+        let result = KERNEL_HEAP_ALLOCATOR.try_alloc(layout).or_else(|| {
+            KERNEL_HEAP_ALLOCATOR
+                .oom_events
+                .fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "Kernel Heap: out of memory allocating {} Byte, reclaiming caches",
+                layout.size()
+            );
+
+            let released = KERNEL_HEAP_ALLOCATOR
+                .inner
+                .lock(|inner| inner.slabs.release(&mut inner.heap));
+            if released + reclaim() == 0 {
+                return None;
+            }
+            KERNEL_HEAP_ALLOCATOR.try_alloc(layout)
+        });
 
         match result {
             None => core::ptr::null_mut(),
-            Some(allocation) => {
-                let ptr = allocation.as_ptr();
-
+            Some(ptr) => {
                 debug_print_alloc_dealloc("Allocation", ptr, layout);
 
                 ptr
@@ -123,10 +465,41 @@ unsafe impl GlobalAlloc for HeapAllocator {
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
         KERNEL_HEAP_ALLOCATOR
             .inner
-            .lock(|inner| inner.deallocate(core::ptr::NonNull::new_unchecked(ptr), layout));
+            .lock(|inner| inner.dealloc(NonNull::new_unchecked(ptr), layout));
+        KERNEL_HEAP_ALLOCATOR
+            .allocations
+            .fetch_sub(1, Ordering::Relaxed);
 
         debug_print_alloc_dealloc("Free", ptr, layout);
     }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let (resized, used) = KERNEL_HEAP_ALLOCATOR.inner.lock(|inner| {
+            let resized = inner.resize_in_place(ptr, layout, new_size);
+            (resized, inner.used())
+        });
+
+        if resized {
+            KERNEL_HEAP_ALLOCATOR
+                .high_water
+                .fetch_max(used, Ordering::Relaxed);
+            debug_print_alloc_dealloc(
+                "Reallocation in place",
+                ptr,
+                Layout::from_size_align_unchecked(new_size, layout.align()),
+            );
+
+            return ptr;
+        }
+
+        let new_ptr = self.alloc(Layout::from_size_align_unchecked(new_size, layout.align()));
+        if !new_ptr.is_null() {
+            core::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+            self.dealloc(ptr, layout);
+        }
+
+        new_ptr
+    }
 }
 
 /// Query the BSP for the heap region and initialize the kernel's heap allocator with it.
@@ -140,8 +513,118 @@ pub fn kernel_init_heap_allocator() {
     let region = bsp::memory::mmu::virt_heap_region();
 
     KERNEL_HEAP_ALLOCATOR.inner.lock(|inner| unsafe {
-        inner.init(region.start_addr().as_usize() as *mut u8, region.size())
+        inner
+            .heap
+            .init(region.start_addr().as_usize() as *mut u8, region.size())
     });
 
     INIT_DONE.store(true, Ordering::Relaxed);
 }
+
+/// Allocate, grow and free blocks of random sizes for `rounds` rounds, checking that every block
+/// keeps its contents. Then print the time taken and how the heap looks compared to before.
+///
+/// Most blocks are small, like boxed closures, with larger ones in between for the small ones to
+/// fragment the heap around.
+pub fn stress_test(rounds: usize) -> Result<(), &'static str> {
+    let mut slots: Vec<Option<Vec<u8>>> = (0..STRESS_SLOTS).map(|_| None).collect();
+    let heap = kernel_heap_allocator();
+    let before = heap.stats();
+
+    // Xorshift, seeded once, is plenty for picking sizes and far cheaper than the entropy pool.
+    let seed = rand::next_u64() | 1;
+    let mut state = seed;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let mut allocations = 0;
+    let start = time::time_manager().uptime();
+    for round in 0..rounds {
+        let r = next();
+        let slot = &mut slots[r as usize % STRESS_SLOTS];
+
+        match slot.take() {
+            Some(mut block) => {
+                let fill = block[0];
+                if block.iter().any(|b| *b != fill) {
+                    return Err("Block lost its contents");
+                }
+
+                // Every fourth block grows instead of being freed.
+                if (r >> 32) % 4 == 0 && block.len() < 16384 {
+                    let len = block.len() + 1 + next() as usize % block.len();
+                    block.resize(len, fill);
+                    *slot = Some(block);
+                }
+            }
+            None => {
+                let (min, max) = match (r >> 32) % 20 {
+                    0 => (2049, 16384),
+                    1..=4 => (257, 2048),
+                    _ => (1, 256),
+                };
+                let size = min + next() as usize % (max - min + 1);
+                *slot = Some(alloc::vec![round as u8; size]);
+                allocations += 1;
+            }
+        }
+    }
+    let elapsed = time::time_manager().uptime() - start;
+
+    for slot in &mut slots {
+        *slot = None;
+    }
+    let after = heap.stats();
+
+    info!("      Seed:         {:#018x}", seed);
+    info!(
+        "      Rounds:       {} ({} allocations)",
+        rounds, allocations
+    );
+    info!("      Time:         {}", time::Human(elapsed));
+    info!("      Used:         {} -> {} Byte", before.used, after.used);
+    info!(
+        "      Largest free: {} -> {} Byte",
+        before.largest_free_block, after.largest_free_block
+    );
+    info!(
+        "      Fragmented:   {}% -> {}%",
+        before.fragmentation, after.fragmentation
+    );
+    info!("      Slabs:        {} -> {}", before.slabs, after.slabs);
+
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use test_macros::kernel_test;
+
+    /// Growing and shrinking must keep the contents, and freeing must give back all of it.
+    #[kernel_test]
+    fn realloc_keeps_contents() {
+        let heap = kernel_heap_allocator();
+        let (used, allocations) = (heap.used(), heap.allocations());
+
+        let mut v: Vec<u8> = (0..40).collect();
+        v.reserve_exact(4000);
+        assert!(v.iter().enumerate().all(|(i, b)| *b == i as u8));
+        v.shrink_to_fit();
+        assert!(v.iter().enumerate().all(|(i, b)| *b == i as u8));
+        assert_eq!(heap.allocations(), allocations + 1);
+
+        drop(v);
+        assert_eq!(heap.used(), used);
+        assert!(heap.largest_free_block() <= heap.free());
+    }
+}
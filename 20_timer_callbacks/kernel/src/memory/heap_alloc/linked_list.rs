@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! First-fit allocator on a linked list of free blocks.
+//!
+//! Allocation walks the list until a block fits, so it takes longer the more the heap is
+//! fragmented. Freeing merges the block with its free neighbours.
+
+use super::interface;
+use alloc::alloc::Layout;
+use core::ptr::NonNull;
+use linked_list_allocator::{hole::HoleList, Heap as LinkedListHeap};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Every block is a multiple of this, so that a free block can hold the list node.
+const BLOCK_ALIGN: usize = core::mem::size_of::<usize>();
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The allocator.
+pub struct LinkedList {
+    heap: LinkedListHeap,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// The size the heap actually reserves for `size` bytes.
+fn block_size(size: usize, align: usize) -> usize {
+    HoleList::align_layout(Layout::from_size_align(size, align).unwrap()).size()
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl LinkedList {
+    /// Create an instance without memory.
+    pub const fn empty() -> Self {
+        Self {
+            heap: LinkedListHeap::empty(),
+        }
+    }
+}
+
+impl interface::Allocator for LinkedList {
+    const NAME: &'static str = "linked list (first fit)";
+
+    unsafe fn init(&mut self, bottom: *mut u8, size: usize) {
+        self.heap.init(bottom, size)
+    }
+
+    fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        self.heap.allocate_first_fit(layout).ok()
+    }
+
+    unsafe fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        self.heap.deallocate(ptr, layout)
+    }
+
+    unsafe fn resize_in_place(&mut self, ptr: *mut u8, layout: Layout, new_size: usize) -> bool {
+        let old_block = block_size(layout.size(), layout.align());
+        let new_block = block_size(new_size, layout.align());
+
+        if new_block == old_block {
+            return true;
+        }
+
+        if new_block < old_block {
+            // A tail too small to hold the list node can't be given back.
+            let tail = old_block - new_block;
+            if tail < HoleList::min_size() {
+                return false;
+            }
+
+            let tail_layout = Layout::from_size_align(tail, BLOCK_ALIGN).unwrap();
+            self.heap
+                .deallocate(NonNull::new_unchecked(ptr.add(new_block)), tail_layout);
+            return true;
+        }
+
+        // First fit takes the lowest hole that is big enough. If that is right behind the block,
+        // the two become one and the excess at the end is given back.
+        let grown = Layout::from_size_align(new_size, layout.align()).unwrap();
+        let next = match self.heap.allocate_first_fit(grown) {
+            Ok(next) => next.as_ptr(),
+            Err(()) => return false,
+        };
+        if next != ptr.add(old_block) {
+            self.heap.deallocate(NonNull::new_unchecked(next), grown);
+            return false;
+        }
+
+        let excess = Layout::from_size_align(old_block, BLOCK_ALIGN).unwrap();
+        self.heap
+            .deallocate(NonNull::new_unchecked(ptr.add(new_block)), excess);
+
+        true
+    }
+
+    fn used(&self) -> usize {
+        self.heap.used()
+    }
+
+    fn free(&self) -> usize {
+        self.heap.free()
+    }
+
+    /// Found by trying allocations, each given back right away. Freeing merges the block back into
+    /// its hole, so the heap ends up as before.
+    fn largest_free_block(&mut self) -> usize {
+        let (mut fits, mut fails) = (0, self.heap.free() / BLOCK_ALIGN + 1);
+
+        while fails - fits > 1 {
+            let mid = (fits + fails) / 2;
+            let layout = Layout::from_size_align(mid * BLOCK_ALIGN, BLOCK_ALIGN).unwrap();
+
+            match self.heap.allocate_first_fit(layout) {
+                Ok(ptr) => {
+                    unsafe { self.heap.deallocate(ptr, layout) };
+                    fits = mid;
+                }
+                Err(()) => fails = mid,
+            }
+        }
+
+        fits * BLOCK_ALIGN
+    }
+}
@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Per-subsystem memory quotas.
+//!
+//! A quota is an allocator that forwards to the kernel heap and counts the bytes a subsystem holds
+//! through it. Collections built with it, e.g. `Vec::new_in(&NET_QUOTA)`, are refused memory
+//! once the subsystem would exceed its limit, while the rest of the heap stays available to the
+//! console, timers and everything else.
+//!
+//! The bytes counted are the ones requested. What the heap rounds up to is not charged.
+
+use crate::{
+    common, info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+};
+use alloc::{
+    alloc::{AllocError, Allocator, Global, Layout},
+    vec::Vec,
+};
+use core::{
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Maximum number of quotas.
+const MAX_QUOTAS: usize = 8;
+
+/// A limit of zero.
+const UNLIMITED: usize = 0;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A subsystem's share of the heap.
+pub struct Quota {
+    name: &'static str,
+
+    /// In bytes. Zero means no limit.
+    limit: AtomicUsize,
+    used: AtomicUsize,
+    peak: AtomicUsize,
+
+    /// Number of allocations refused because of the limit.
+    refused: AtomicUsize,
+}
+
+/// Error returned when an allocation would exceed the quota.
+pub const QUOTA_EXCEEDED: &str = "Memory quota exceeded";
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static QUOTAS: IRQSafeNullLock<[Option<&'static Quota>; MAX_QUOTAS]> =
+    IRQSafeNullLock::new([None; MAX_QUOTAS]);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Quota {
+    /// Count `bytes` against the limit. Fails without counting if they don't fit.
+    fn charge(&self, bytes: usize) -> bool {
+        let limit = self.limit.load(Ordering::Relaxed);
+        let result = self
+            .used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                let new = used.checked_add(bytes)?;
+                (limit == UNLIMITED || new <= limit).then_some(new)
+            });
+
+        match result {
+            Ok(old) => {
+                self.peak.fetch_max(old + bytes, Ordering::Relaxed);
+                true
+            }
+            Err(_) => {
+                self.refused.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+        }
+    }
+
+    fn uncharge(&self, bytes: usize) {
+        self.used.fetch_sub(bytes, Ordering::Relaxed);
+    }
+}
+
+fn print_size(label: &str, bytes: usize) {
+    let (size_h, size_unit) = common::size_human_readable_ceil(bytes);
+    info!(
+        "          {:<8} {} Byte ({} {})",
+        label, bytes, size_h, size_unit
+    );
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Quota {
+    /// Create an instance that allows `limit` bytes. Zero means no limit.
+    pub const fn new(name: &'static str, limit: usize) -> Self {
+        Self {
+            name,
+            limit: AtomicUsize::new(limit),
+            used: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            refused: AtomicUsize::new(0),
+        }
+    }
+
+    /// The subsystem's name.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The limit in bytes, `None` if there is none.
+    pub fn limit(&self) -> Option<usize> {
+        match self.limit.load(Ordering::Relaxed) {
+            UNLIMITED => None,
+            limit => Some(limit),
+        }
+    }
+
+    /// Change the limit. Memory already held is kept even if it exceeds the new limit.
+    pub fn set_limit(&self, limit: Option<usize>) {
+        self.limit
+            .store(limit.unwrap_or(UNLIMITED), Ordering::Relaxed);
+    }
+
+    /// Bytes currently held.
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Number of allocations refused because of the limit.
+    pub fn refused(&self) -> usize {
+        self.refused.load(Ordering::Relaxed)
+    }
+
+    /// Create a vector with room for exactly `capacity` elements.
+    ///
+    /// Tells a refusal by the quota apart from an exhausted heap, so that the caller can report
+    /// which one it was.
+    pub fn try_vec<T>(
+        &'static self,
+        capacity: usize,
+    ) -> Result<Vec<T, &'static Self>, &'static str> {
+        let bytes = capacity.saturating_mul(core::mem::size_of::<T>());
+        if let Some(limit) = self.limit() {
+            if self.used().saturating_add(bytes) > limit {
+                self.refused.fetch_add(1, Ordering::Relaxed);
+                return Err(QUOTA_EXCEEDED);
+            }
+        }
+
+        let mut v = Vec::new_in(self);
+        v.try_reserve_exact(capacity)
+            .map_err(|_| "Out of heap memory")?;
+
+        Ok(v)
+    }
+
+    /// Print the usage.
+    pub fn print(&self) {
+        info!("      {}:", self.name);
+        match self.limit() {
+            None => info!("          Limit:   none"),
+            Some(limit) => print_size("Limit:", limit),
+        }
+        print_size("Used:", self.used());
+        print_size("Peak:", self.peak.load(Ordering::Relaxed));
+        info!("          Refused: {}", self.refused());
+    }
+}
+
+unsafe impl Allocator for Quota {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if !self.charge(layout.size()) {
+            return Err(AllocError);
+        }
+
+        let result = Global.allocate(layout);
+        if result.is_err() {
+            self.uncharge(layout.size());
+        }
+
+        result
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        Global.deallocate(ptr, layout);
+        self.uncharge(layout.size());
+    }
+}
+
+/// Register a quota, so that it is listed and its limit can be changed by name.
+pub fn register(quota: &'static Quota) -> Result<(), &'static str> {
+    QUOTAS.lock(|quotas| {
+        if quotas.iter().flatten().any(|q| q.name == quota.name) {
+            return Err("Quota already registered");
+        }
+
+        let slot = quotas
+            .iter_mut()
+            .find(|q| q.is_none())
+            .ok_or("Too many quotas")?;
+        *slot = Some(quota);
+
+        Ok(())
+    })
+}
+
+/// Find a registered quota by name.
+pub fn find(name: &str) -> Option<&'static Quota> {
+    QUOTAS.lock(|quotas| quotas.iter().flatten().find(|q| q.name == name).copied())
+}
+
+/// Print the usage of all registered quotas.
+pub fn print() {
+    let quotas = QUOTAS.lock(|quotas| *quotas);
+    if quotas.iter().all(|q| q.is_none()) {
+        info!("      None registered");
+    }
+
+    for quota in quotas.iter().flatten() {
+        quota.print();
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Allocations beyond the limit are refused, and freeing gives the bytes back.
+    #[kernel_test]
+    fn limit_is_enforced() {
+        static QUOTA: Quota = Quota::new("test", 64);
+
+        let mut first = QUOTA.try_vec::<u8>(48).unwrap();
+        first.extend_from_slice(&[0; 48]);
+        assert_eq!(QUOTA.used(), 48);
+
+        assert_eq!(QUOTA.try_vec::<u8>(32).err(), Some(QUOTA_EXCEEDED));
+        assert!(Vec::<u8, _>::new_in(&QUOTA).try_reserve(32).is_err());
+        assert_eq!(QUOTA.refused(), 2);
+
+        drop(first);
+        assert_eq!(QUOTA.used(), 0);
+        assert!(QUOTA.try_vec::<u8>(64).is_ok());
+        assert_eq!(QUOTA.used(), 0);
+    }
+}
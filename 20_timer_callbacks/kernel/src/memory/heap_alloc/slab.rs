@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Slab caches for small blocks.
+//!
+//! Small blocks come and go at a high rate, e.g. the boxed closures of periodic timer callbacks.
+//! Taken from the general heap, each one that outlives its neighbours leaves a hole behind that is
+//! too small for anything but another small block. Here, every size class instead carves slabs of
+//! [`SLAB_SIZE`] bytes into equal objects, so that small blocks only ever share memory with blocks
+//! of their own class.
+//!
+//! A slab whose last object is freed goes back to the heap, which merges it with its free
+//! neighbours. One empty slab per class is kept as a spare, so that a block allocated and freed in
+//! a loop doesn't take and give back a whole slab each time. [`Caches::release()`] gives the spares
+//! back when the heap runs out.
+
+use super::{interface::Allocator, Backend};
+use alloc::alloc::Layout;
+use core::ptr::{self, NonNull};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Object sizes, each also the alignment its objects get.
+const CLASSES: [usize; 5] = [16, 32, 64, 128, 256];
+
+const SLAB_LAYOUT: Layout = unsafe { Layout::from_size_align_unchecked(SLAB_SIZE, SLAB_SIZE) };
+
+/// Start of a slab. The objects follow, beginning at the first multiple of the object size.
+#[repr(C)]
+struct Slab {
+    prev: *mut Slab,
+    next: *mut Slab,
+
+    /// Singly linked through the free objects themselves.
+    free: *mut FreeObject,
+    in_use: usize,
+}
+
+struct FreeObject {
+    next: *mut FreeObject,
+}
+
+struct Cache {
+    size: usize,
+
+    /// Slabs with free objects, in use or not. Full slabs are not linked anywhere, they are found
+    /// again through the address of an object when it is freed.
+    partial: *mut Slab,
+
+    /// An empty slab kept back from the heap.
+    spare: *mut Slab,
+
+    slabs: usize,
+    in_use: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Size and alignment of a slab.
+pub const SLAB_SIZE: usize = 4096;
+
+/// The caches of all size classes.
+pub struct Caches {
+    caches: [Cache; CLASSES.len()],
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Cache {
+    const fn new(size: usize) -> Self {
+        Self {
+            size,
+            partial: ptr::null_mut(),
+            spare: ptr::null_mut(),
+            slabs: 0,
+            in_use: 0,
+        }
+    }
+
+    /// Offset of the first object in a slab.
+    fn first_object(&self) -> usize {
+        (core::mem::size_of::<Slab>() + self.size - 1) / self.size * self.size
+    }
+
+    /// Take a slab from the heap and put all of its objects on its free list.
+    fn grow(&mut self, heap: &mut Backend) -> Option<*mut Slab> {
+        let slab = heap.allocate(SLAB_LAYOUT)?.as_ptr() as *mut Slab;
+        self.slabs += 1;
+
+        let mut free = ptr::null_mut();
+        let mut offset = SLAB_SIZE - self.size;
+        while offset >= self.first_object() {
+            let object = unsafe { (slab as *mut u8).add(offset) } as *mut FreeObject;
+            unsafe { object.write(FreeObject { next: free }) };
+            free = object;
+            offset -= self.size;
+        }
+
+        unsafe {
+            slab.write(Slab {
+                prev: ptr::null_mut(),
+                next: ptr::null_mut(),
+                free,
+                in_use: 0,
+            })
+        };
+
+        Some(slab)
+    }
+
+    unsafe fn link(&mut self, slab: *mut Slab) {
+        (*slab).prev = ptr::null_mut();
+        (*slab).next = self.partial;
+        if !self.partial.is_null() {
+            (*self.partial).prev = slab;
+        }
+        self.partial = slab;
+    }
+
+    unsafe fn unlink(&mut self, slab: *mut Slab) {
+        if (*slab).prev.is_null() {
+            self.partial = (*slab).next;
+        } else {
+            (*(*slab).prev).next = (*slab).next;
+        }
+        if !(*slab).next.is_null() {
+            (*(*slab).next).prev = (*slab).prev;
+        }
+    }
+
+    fn alloc(&mut self, heap: &mut Backend) -> Option<NonNull<u8>> {
+        if self.partial.is_null() {
+            let slab = match core::mem::replace(&mut self.spare, ptr::null_mut()) {
+                spare if !spare.is_null() => spare,
+                _ => self.grow(heap)?,
+            };
+            unsafe { self.link(slab) };
+        }
+
+        unsafe {
+            let slab = self.partial;
+            let object = (*slab).free;
+            (*slab).free = (*object).next;
+            (*slab).in_use += 1;
+            if (*slab).free.is_null() {
+                self.unlink(slab);
+            }
+            self.in_use += 1;
+
+            NonNull::new(object as *mut u8)
+        }
+    }
+
+    /// # Safety
+    ///
+    /// - `object` must have been allocated from this cache.
+    unsafe fn dealloc(&mut self, heap: &mut Backend, object: NonNull<u8>) {
+        let slab = (object.as_ptr() as usize & !(SLAB_SIZE - 1)) as *mut Slab;
+        let object = object.as_ptr() as *mut FreeObject;
+
+        if (*slab).free.is_null() {
+            self.link(slab);
+        }
+        object.write(FreeObject { next: (*slab).free });
+        (*slab).free = object;
+        (*slab).in_use -= 1;
+        self.in_use -= 1;
+
+        if (*slab).in_use != 0 {
+            return;
+        }
+
+        self.unlink(slab);
+        if self.spare.is_null() {
+            self.spare = slab;
+        } else {
+            heap.deallocate(NonNull::new_unchecked(slab as *mut u8), SLAB_LAYOUT);
+            self.slabs -= 1;
+        }
+    }
+
+    /// Give the spare slab back to the heap. Returns the number of bytes freed.
+    fn release(&mut self, heap: &mut Backend) -> usize {
+        let spare = core::mem::replace(&mut self.spare, ptr::null_mut());
+        if spare.is_null() {
+            return 0;
+        }
+
+        unsafe { heap.deallocate(NonNull::new_unchecked(spare as *mut u8), SLAB_LAYOUT) };
+        self.slabs -= 1;
+
+        SLAB_SIZE
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+// The slabs are only ever reached through the lock around the heap.
+unsafe impl Send for Caches {}
+
+impl Caches {
+    /// Create an instance without slabs.
+    pub const fn new() -> Self {
+        Self {
+            caches: [
+                Cache::new(CLASSES[0]),
+                Cache::new(CLASSES[1]),
+                Cache::new(CLASSES[2]),
+                Cache::new(CLASSES[3]),
+                Cache::new(CLASSES[4]),
+            ],
+        }
+    }
+
+    /// Return the class that serves `layout`, if it is small enough for one.
+    pub fn class_of(layout: Layout) -> Option<usize> {
+        let size = layout.size().max(layout.align());
+
+        CLASSES.iter().position(|class| size <= *class)
+    }
+
+    /// Allocate an object of `class`, taking a new slab from `heap` if needed.
+    pub fn alloc(&mut self, heap: &mut Backend, class: usize) -> Option<NonNull<u8>> {
+        self.caches[class].alloc(heap)
+    }
+
+    /// Free an object. A slab left empty goes back to `heap` unless it is kept as the spare.
+    ///
+    /// # Safety
+    ///
+    /// - `object` must have been allocated with [`Caches::alloc()`] from `class` and `heap`.
+    pub unsafe fn dealloc(&mut self, heap: &mut Backend, class: usize, object: NonNull<u8>) {
+        self.caches[class].dealloc(heap, object)
+    }
+
+    /// Give all spare slabs back to `heap`. Returns the number of bytes freed.
+    pub fn release(&mut self, heap: &mut Backend) -> usize {
+        self.caches.iter_mut().map(|c| c.release(heap)).sum()
+    }
+
+    /// Number of slabs, including the spares.
+    pub fn slabs(&self) -> usize {
+        self.caches.iter().map(|c| c.slabs).sum()
+    }
+
+    /// Bytes held by slabs but not by objects in use.
+    pub fn cached(&self) -> usize {
+        self.caches
+            .iter()
+            .map(|c| c.slabs * SLAB_SIZE - c.in_use * c.size)
+            .sum()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use test_macros::kernel_test;
+
+    /// Objects must be spread over slabs, an emptied slab must be kept as the spare or given back,
+    /// and releasing the spares must return all memory to the heap.
+    #[kernel_test]
+    fn slabs_return_to_heap() {
+        // Allocators that put a header in front of blocks need room to align the slabs.
+        let area = Layout::from_size_align(8 * SLAB_SIZE, SLAB_SIZE).unwrap();
+        let bottom = unsafe { alloc::alloc::alloc(area) };
+        assert!(!bottom.is_null());
+
+        let mut heap = Backend::empty();
+        unsafe { heap.init(bottom, area.size()) };
+        let mut caches = Caches::new();
+        let class = Caches::class_of(Layout::new::<[u64; 5]>()).unwrap();
+        assert_eq!(CLASSES[class], 64);
+
+        let objects: Vec<_> = (0..80)
+            .map(|_| caches.alloc(&mut heap, class).unwrap())
+            .collect();
+        assert_eq!(caches.slabs(), 2);
+        assert!(objects
+            .iter()
+            .all(|o| o.as_ptr() as usize % CLASSES[class] == 0));
+
+        for object in objects {
+            unsafe { caches.dealloc(&mut heap, class, object) };
+        }
+        assert_eq!(caches.slabs(), 1);
+        assert_eq!(caches.cached(), SLAB_SIZE);
+
+        assert_eq!(caches.release(&mut heap), SLAB_SIZE);
+        assert_eq!(heap.used(), 0);
+
+        unsafe { alloc::alloc::dealloc(bottom, area) };
+    }
+}
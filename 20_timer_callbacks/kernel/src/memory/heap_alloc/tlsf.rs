@@ -0,0 +1,454 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Two-level segregated fit allocator.
+//!
+//! Free blocks are kept in lists by size: the first level splits sizes at powers of two, the second
+//! level splits each power of two into [`SL_COUNT`] equal ranges. A bitmap per level marks the
+//! lists that aren't empty, so the list to take a block from is found with a few bit operations,
+//! however many blocks there are. Allocating and freeing take constant time.
+//!
+//! A request is rounded up to the start of the next range, so that any block of the list found
+//! fits without searching it. This is good fit rather than best fit: a block a little larger than
+//! the request may be passed over for a larger one.
+//!
+//! Every block starts with a header holding its size and a link to the block right in front of it,
+//! so that a freed block is merged with free neighbours on both sides right away.
+//!
+//! # Resources
+//!
+//! - M. Masmano, I. Ripoll, A. Crespo, J. Real: TLSF: a New Dynamic Memory Allocator for Real-Time
+//!   Systems
+
+use super::interface;
+use alloc::alloc::Layout;
+use core::ptr::{self, NonNull};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const ALIGN_LOG2: u32 = 4;
+
+/// Alignment of every block and payload. Block sizes are multiples of it.
+const ALIGN: usize = 1 << ALIGN_LOG2;
+
+const SL_LOG2: u32 = 4;
+const SL_COUNT: usize = 1 << SL_LOG2;
+
+/// Blocks below this size are all in the first first-level list, in steps of [`ALIGN`].
+const SMALL_BLOCK: usize = 1 << (SL_LOG2 + ALIGN_LOG2);
+
+/// Enough first-level lists for blocks below 4 GiB.
+const FL_COUNT: usize = (32 - SL_LOG2 - ALIGN_LOG2 + 1) as usize;
+
+const MAX_BLOCK: usize = (1 << 32) - ALIGN;
+
+/// Set in the size of a free block.
+const FREE: usize = 1;
+
+/// Bytes in front of every payload.
+const HEADER: usize = 2 * core::mem::size_of::<usize>();
+
+/// Smallest block: the header plus the free list links.
+const MIN_BLOCK: usize = core::mem::size_of::<Block>();
+
+#[repr(C)]
+struct Block {
+    /// The block in front of this one, null for the first.
+    prev_phys: *mut Block,
+
+    /// Size including the header, with [`FREE`] in bit 0.
+    size: usize,
+
+    // Only while the block is free, in its payload.
+    next_free: *mut Block,
+    prev_free: *mut Block,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The allocator.
+pub struct Tlsf {
+    fl_bitmap: u32,
+    sl_bitmap: [u32; FL_COUNT],
+    heads: [[*mut Block; SL_COUNT]; FL_COUNT],
+
+    /// Bytes in all blocks, and in the allocated ones, headers included.
+    total: usize,
+    used: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+const fn align_up(value: usize, align: usize) -> usize {
+    (value + align - 1) & !(align - 1)
+}
+
+fn log2(value: usize) -> u32 {
+    usize::BITS - 1 - value.leading_zeros()
+}
+
+/// Return the lists a block of `size` bytes goes to.
+fn mapping(size: usize) -> (usize, usize) {
+    if size < SMALL_BLOCK {
+        return (0, size >> ALIGN_LOG2);
+    }
+
+    let log2 = log2(size);
+    let fl = (log2 - SL_LOG2 - ALIGN_LOG2 + 1) as usize;
+    let sl = (size >> (log2 - SL_LOG2)) ^ SL_COUNT;
+
+    (fl, sl)
+}
+
+/// Return the first list all of whose blocks hold `size` bytes.
+fn mapping_search(size: usize) -> (usize, usize) {
+    if size < SMALL_BLOCK {
+        return mapping(size);
+    }
+
+    mapping(size + (1 << (log2(size) - SL_LOG2)) - 1)
+}
+
+/// Smallest block size of a list.
+fn list_start(fl: usize, sl: usize) -> usize {
+    match fl {
+        0 => sl << ALIGN_LOG2,
+        _ => (SL_COUNT + sl) << (fl as u32 + ALIGN_LOG2 - 1),
+    }
+}
+
+/// Block size for a payload of `size` bytes.
+fn block_for(size: usize) -> usize {
+    (align_up(size.max(1), ALIGN) + HEADER).max(MIN_BLOCK)
+}
+
+unsafe fn block_size(block: *mut Block) -> usize {
+    (*block).size & !FREE
+}
+
+unsafe fn is_free(block: *mut Block) -> bool {
+    (*block).size & FREE != 0
+}
+
+unsafe fn set_size(block: *mut Block, size: usize, free: bool) {
+    (*block).size = size | if free { FREE } else { 0 };
+}
+
+unsafe fn next_phys(block: *mut Block) -> *mut Block {
+    (block as *mut u8).add(block_size(block)) as *mut Block
+}
+
+unsafe fn payload(block: *mut Block) -> *mut u8 {
+    (block as *mut u8).add(HEADER)
+}
+
+/// Cut `block` after `size` bytes and return the second part. Both parts keep the block's flags.
+unsafe fn split(block: *mut Block, size: usize) -> *mut Block {
+    let rest = (block as *mut u8).add(size) as *mut Block;
+
+    (*rest).prev_phys = block;
+    (*rest).size = (*block).size - size;
+    (*next_phys(rest)).prev_phys = rest;
+    (*block).size = (*block).size - block_size(block) + size;
+
+    rest
+}
+
+/// Append `next` to `block`, whose flags are kept.
+unsafe fn absorb(block: *mut Block, next: *mut Block) {
+    (*block).size += block_size(next);
+    (*next_phys(block)).prev_phys = block;
+}
+
+impl Tlsf {
+    fn insert(&mut self, block: *mut Block) {
+        let (fl, sl) = mapping(unsafe { block_size(block) });
+        let head = self.heads[fl][sl];
+
+        unsafe {
+            (*block).next_free = head;
+            (*block).prev_free = ptr::null_mut();
+            if !head.is_null() {
+                (*head).prev_free = block;
+            }
+        }
+        self.heads[fl][sl] = block;
+        self.fl_bitmap |= 1 << fl;
+        self.sl_bitmap[fl] |= 1 << sl;
+    }
+
+    fn remove(&mut self, block: *mut Block) {
+        let (fl, sl) = mapping(unsafe { block_size(block) });
+
+        unsafe {
+            let (prev, next) = ((*block).prev_free, (*block).next_free);
+            if !next.is_null() {
+                (*next).prev_free = prev;
+            }
+            if prev.is_null() {
+                self.heads[fl][sl] = next;
+            } else {
+                (*prev).next_free = next;
+            }
+        }
+
+        if self.heads[fl][sl].is_null() {
+            self.sl_bitmap[fl] &= !(1 << sl);
+            if self.sl_bitmap[fl] == 0 {
+                self.fl_bitmap &= !(1 << fl);
+            }
+        }
+    }
+
+    /// Return the first non-empty list at or above `(fl, sl)`.
+    fn find_list(&self, fl: usize, sl: usize) -> Option<(usize, usize)> {
+        if fl >= FL_COUNT {
+            return None;
+        }
+
+        let sl_map = self.sl_bitmap[fl] & (!0 << sl);
+        if sl_map != 0 {
+            return Some((fl, sl_map.trailing_zeros() as usize));
+        }
+
+        let fl_map = self.fl_bitmap & (!0u32).checked_shl(fl as u32 + 1).unwrap_or(0);
+        if fl_map == 0 {
+            return None;
+        }
+        let fl = fl_map.trailing_zeros() as usize;
+
+        Some((fl, self.sl_bitmap[fl].trailing_zeros() as usize))
+    }
+
+    /// Take a block of at least `size` bytes off its list, marked as used.
+    fn take(&mut self, size: usize) -> Option<*mut Block> {
+        let (fl, sl) = mapping_search(size);
+        let (fl, sl) = self.find_list(fl, sl)?;
+        let block = self.heads[fl][sl];
+
+        self.remove(block);
+        unsafe { set_size(block, block_size(block), false) };
+
+        Some(block)
+    }
+
+    /// Mark `block` free, merge it with free neighbours and put it on its list.
+    unsafe fn release(&mut self, mut block: *mut Block) {
+        set_size(block, block_size(block), true);
+
+        let next = next_phys(block);
+        if is_free(next) {
+            self.remove(next);
+            absorb(block, next);
+        }
+
+        let prev = (*block).prev_phys;
+        if !prev.is_null() && is_free(prev) {
+            self.remove(prev);
+            absorb(prev, block);
+            block = prev;
+        }
+
+        self.insert(block);
+    }
+
+    /// Give the part of the used `block` beyond `size` bytes back, if it makes a block.
+    unsafe fn trim(&mut self, block: *mut Block, size: usize) {
+        if block_size(block) - size >= MIN_BLOCK {
+            let rest = split(block, size);
+            self.release(rest);
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+// The blocks are only ever reached through the lock around the heap.
+unsafe impl Send for Tlsf {}
+
+impl Tlsf {
+    /// Create an instance without memory.
+    pub const fn empty() -> Self {
+        Self {
+            fl_bitmap: 0,
+            sl_bitmap: [0; FL_COUNT],
+            heads: [[ptr::null_mut(); SL_COUNT]; FL_COUNT],
+            total: 0,
+            used: 0,
+        }
+    }
+}
+
+impl interface::Allocator for Tlsf {
+    const NAME: &'static str = "TLSF (constant time)";
+
+    /// The memory from `bottom` on becomes one free block, followed by a header that marks the end.
+    unsafe fn init(&mut self, bottom: *mut u8, size: usize) {
+        let start = align_up(bottom as usize, ALIGN);
+        let end = (bottom as usize + size) & !(ALIGN - 1);
+        if end < start + MIN_BLOCK + HEADER {
+            return;
+        }
+        let end = end.min(start + MAX_BLOCK + HEADER);
+
+        let block = start as *mut Block;
+        let sentinel = (end - HEADER) as *mut Block;
+        ptr::addr_of_mut!((*block).prev_phys).write(ptr::null_mut());
+        set_size(block, sentinel as usize - start, false);
+        ptr::addr_of_mut!((*sentinel).prev_phys).write(block);
+        set_size(sentinel, 0, false);
+
+        set_size(block, block_size(block), true);
+        self.total = block_size(block);
+        self.insert(block);
+    }
+
+    fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        let size = block_for(layout.size());
+        let align = layout.align();
+        if align <= ALIGN {
+            let block = self.take(size)?;
+            unsafe {
+                self.trim(block, size);
+                self.used += block_size(block);
+
+                return NonNull::new(payload(block));
+            }
+        }
+
+        // Room to cut off a free block in front, so that the payload is aligned.
+        let mut block = self.take(size + align + MIN_BLOCK)?;
+        unsafe {
+            let start = payload(block) as usize;
+            let mut aligned = align_up(start, align);
+            if aligned != start && aligned - start < MIN_BLOCK {
+                aligned += align;
+            }
+            if aligned != start {
+                let rest = split(block, aligned - start);
+                self.release(block);
+                block = rest;
+            }
+
+            self.trim(block, size);
+            self.used += block_size(block);
+
+            NonNull::new(payload(block))
+        }
+    }
+
+    unsafe fn deallocate(&mut self, ptr: NonNull<u8>, _layout: Layout) {
+        let block = ptr.as_ptr().sub(HEADER) as *mut Block;
+
+        self.used -= block_size(block);
+        self.release(block);
+    }
+
+    /// Shrinking always works. Growing works if the block behind is free and large enough.
+    unsafe fn resize_in_place(&mut self, ptr: *mut u8, _layout: Layout, new_size: usize) -> bool {
+        let block = ptr.sub(HEADER) as *mut Block;
+        let old = block_size(block);
+        let size = block_for(new_size);
+
+        if size > old {
+            let next = next_phys(block);
+            if !is_free(next) || old + block_size(next) < size {
+                return false;
+            }
+
+            self.remove(next);
+            absorb(block, next);
+        }
+        self.trim(block, size);
+        self.used = self.used - old + block_size(block);
+
+        true
+    }
+
+    fn used(&self) -> usize {
+        self.used
+    }
+
+    fn free(&self) -> usize {
+        self.total - self.used
+    }
+
+    /// A request is rounded up to the next range, so the largest that succeeds is the start of the
+    /// highest non-empty list.
+    fn largest_free_block(&mut self) -> usize {
+        if self.fl_bitmap == 0 {
+            return 0;
+        }
+        let fl = log2(self.fl_bitmap as usize) as usize;
+        let sl = log2(self.sl_bitmap[fl] as usize) as usize;
+
+        list_start(fl, sl).saturating_sub(HEADER) & !(ALIGN - 1)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use interface::Allocator;
+    use test_macros::kernel_test;
+
+    /// Blocks must be aligned and merged again when freed, in any order, so that the whole area is
+    /// one free block at the end.
+    #[kernel_test]
+    fn blocks_merge_when_freed() {
+        for size in [16, 240, 256, 304, 4080, 1 << 20] {
+            let (fl, sl) = mapping(size);
+            assert!(list_start(fl, sl) <= size);
+            let (fl, sl) = mapping_search(size);
+            assert!(list_start(fl, sl) >= size);
+        }
+
+        let area = Layout::from_size_align(64 * 1024, 4096).unwrap();
+        let bottom = unsafe { alloc::alloc::alloc(area) };
+        assert!(!bottom.is_null());
+
+        let mut tlsf = Tlsf::empty();
+        unsafe { tlsf.init(bottom, area.size()) };
+        let (free, largest) = (tlsf.free(), tlsf.largest_free_block());
+
+        let layouts = [
+            Layout::from_size_align(24, 8).unwrap(),
+            Layout::from_size_align(1000, 8).unwrap(),
+            Layout::from_size_align(4096, 4096).unwrap(),
+            Layout::from_size_align(100, 64).unwrap(),
+        ];
+        let blocks: alloc::vec::Vec<_> = layouts
+            .iter()
+            .map(|l| (tlsf.allocate(*l).unwrap(), *l))
+            .collect();
+        assert!(blocks
+            .iter()
+            .all(|(b, l)| b.as_ptr() as usize % l.align() == 0));
+
+        unsafe {
+            assert!(tlsf.resize_in_place(blocks[1].0.as_ptr(), blocks[1].1, 200));
+            tlsf.deallocate(blocks[2].0, blocks[2].1);
+            tlsf.deallocate(blocks[0].0, blocks[0].1);
+            tlsf.deallocate(blocks[3].0, blocks[3].1);
+            tlsf.deallocate(blocks[1].0, blocks[1].1);
+        }
+        assert_eq!(tlsf.used(), 0);
+        assert_eq!(tlsf.free(), free);
+        assert_eq!(tlsf.largest_free_block(), largest);
+
+        unsafe { alloc::alloc::dealloc(bottom, area) };
+    }
+}
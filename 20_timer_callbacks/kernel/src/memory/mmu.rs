@@ -12,6 +12,7 @@ mod mapping_record;
 mod page_alloc;
 mod translation_table;
 mod types;
+mod user;
 
 use crate::{
     bsp,
@@ -21,6 +22,7 @@ use crate::{
 use core::{fmt, num::NonZeroUsize};
 
 pub use types::*;
+pub use user::UserAddressSpace;
 
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
@@ -52,6 +54,18 @@ pub mod interface {
 
         /// Returns true if the MMU is enabled, false otherwise.
         fn is_enabled(&self) -> bool;
+
+        /// Drop the cached translations of the kernel's tables on all cores, after pages were
+        /// unmapped.
+        fn invalidate_kernel_tlb(&self);
+
+        /// Translate the lower half of the virtual addresses through the program tables at
+        /// `phys_tables_base_addr`, or not at all with `None`.
+        ///
+        /// # Safety
+        ///
+        /// - The tables must stay alive until they are replaced.
+        unsafe fn set_user_tables(&self, phys_tables_base_addr: Option<Address<Physical>>);
     }
 }
 
@@ -91,6 +105,7 @@ use translation_table::interface::TranslationTable;
 /// - Does not prevent aliasing.
 unsafe fn kernel_map_at_unchecked(
     name: &'static str,
+    owner: Option<&'static str>,
     virt_region: &MemoryRegion<Virtual>,
     phys_region: &MemoryRegion<Physical>,
     attr: &AttributeFields,
@@ -98,15 +113,57 @@ unsafe fn kernel_map_at_unchecked(
     bsp::memory::mmu::kernel_translation_tables()
         .write(|tables| tables.map_at(virt_region, phys_region, attr))?;
 
-    kernel_add_mapping_record(name, virt_region, phys_region, attr);
+    mapping_record::kernel_add(name, owner, virt_region, phys_region, attr);
 
     Ok(())
 }
 
+/// See [`kernel_map_mmio()`] and [`kernel_map_mmio_owned()`].
+unsafe fn kernel_map_mmio_for(
+    name: &'static str,
+    owner: Option<&'static str>,
+    mmio_descriptor: &MMIODescriptor,
+) -> Result<Address<Virtual>, &'static str> {
+    let phys_region = MemoryRegion::from(*mmio_descriptor);
+    let offset_into_start_page = mmio_descriptor.start_addr().offset_into_page();
+
+    // Check if an identical region has been mapped for another driver. If so, reuse it.
+    let virt_addr = if let Some(addr) =
+        mapping_record::kernel_find_and_insert_mmio_duplicate(mmio_descriptor, name, owner)
+    {
+        addr
+    // Otherwise, allocate a new region and map it.
+    } else {
+        let num_pages = match NonZeroUsize::new(phys_region.num_pages()) {
+            None => return Err("Requested 0 pages"),
+            Some(x) => x,
+        };
+
+        let virt_region =
+            page_alloc::kernel_mmio_va_allocator().lock(|allocator| allocator.alloc(num_pages))?;
+
+        kernel_map_at_unchecked(
+            name,
+            owner,
+            &virt_region,
+            &phys_region,
+            &AttributeFields {
+                mem_attributes: MemAttributes::Device,
+                acc_perms: AccessPermissions::ReadWrite,
+                execute_never: true,
+            },
+        )?;
+
+        virt_region.start_addr()
+    };
+
+    Ok(virt_addr + offset_into_start_page)
+}
+
 /// Try to translate a kernel virtual address to a physical address.
 ///
 /// Will only succeed if there exists a valid mapping for the input address.
-fn try_kernel_virt_addr_to_phys_addr(
+pub fn try_kernel_virt_addr_to_phys_addr(
     virt_addr: Address<Virtual>,
 ) -> Result<Address<Physical>, &'static str> {
     bsp::memory::mmu::kernel_translation_tables()
@@ -175,7 +232,7 @@ pub fn kernel_add_mapping_record(
     phys_region: &MemoryRegion<Physical>,
     attr: &AttributeFields,
 ) {
-    mapping_record::kernel_add(name, virt_region, phys_region, attr);
+    mapping_record::kernel_add(name, None, virt_region, phys_region, attr);
 }
 
 /// MMIO remapping in the kernel translation tables.
@@ -189,39 +246,52 @@ pub unsafe fn kernel_map_mmio(
     name: &'static str,
     mmio_descriptor: &MMIODescriptor,
 ) -> Result<Address<Virtual>, &'static str> {
-    let phys_region = MemoryRegion::from(*mmio_descriptor);
-    let offset_into_start_page = mmio_descriptor.start_addr().offset_into_page();
+    kernel_map_mmio_for(name, None, mmio_descriptor)
+}
 
-    // Check if an identical region has been mapped for another driver. If so, reuse it.
-    let virt_addr = if let Some(addr) =
-        mapping_record::kernel_find_and_insert_mmio_duplicate(mmio_descriptor, name)
-    {
-        addr
-    // Otherwise, allocate a new region and map it.
-    } else {
-        let num_pages = match NonZeroUsize::new(phys_region.num_pages()) {
-            None => return Err("Requested 0 pages"),
-            Some(x) => x,
-        };
+/// MMIO remapping on behalf of the subsystem `owner`, see [`crate::subsys`].
+///
+/// The owner must unmap the region with [`kernel_unmap_mmio()`] when it is torn down, or the
+/// mapping is flagged as leaked.
+///
+/// # Safety
+///
+/// - See [`kernel_map_mmio()`].
+pub unsafe fn kernel_map_mmio_owned(
+    name: &'static str,
+    owner: &'static str,
+    mmio_descriptor: &MMIODescriptor,
+) -> Result<Address<Virtual>, &'static str> {
+    kernel_map_mmio_for(name, Some(owner), mmio_descriptor)
+}
 
-        let virt_region =
-            page_alloc::kernel_mmio_va_allocator().lock(|allocator| allocator.alloc(num_pages))?;
+/// Drop the use by `name` of the MMIO mapping at `virt_addr`. The pages are unmapped once no one
+/// uses them anymore. Their virtual addresses are not handed out again.
+///
+/// Like mapping, unmapping the pages is only possible during kernel init.
+///
+/// # Safety
+///
+/// - Nothing may access the region through `name` afterwards.
+pub unsafe fn kernel_unmap_mmio(
+    name: &'static str,
+    virt_addr: Address<Virtual>,
+) -> Result<(), &'static str> {
+    let virt_region = match mapping_record::kernel_remove_user(name, virt_addr)? {
+        None => return Ok(()),
+        Some(x) => x,
+    };
 
-        kernel_map_at_unchecked(
-            name,
-            &virt_region,
-            &phys_region,
-            &AttributeFields {
-                mem_attributes: MemAttributes::Device,
-                acc_perms: AccessPermissions::ReadWrite,
-                execute_never: true,
-            },
-        )?;
+    bsp::memory::mmu::kernel_translation_tables().write(|tables| tables.unmap_at(&virt_region))?;
+    arch_mmu::mmu().invalidate_kernel_tlb();
 
-        virt_region.start_addr()
-    };
+    Ok(())
+}
 
-    Ok(virt_addr + offset_into_start_page)
+/// Flag the mappings the subsystem `owner` left behind when it was torn down. Returns their
+/// number.
+pub fn kernel_flag_mapping_leaks(owner: &str) -> usize {
+    mapping_record::kernel_flag_leaks(owner)
 }
 
 /// Try to translate a kernel virtual page address to a physical page address.
@@ -244,11 +314,32 @@ pub fn try_kernel_page_attributes(
         .read(|tables| tables.try_page_attributes(virt_page_addr))
 }
 
+/// Return the entity a kernel virtual address is mapped for, e.g. a driver's MMIO region, and the
+/// offset into its mapping.
+pub fn kernel_mapping_of(virt_addr: Address<Virtual>) -> Option<(&'static str, usize)> {
+    mapping_record::kernel_find(virt_addr)
+}
+
 /// Human-readable print of all recorded kernel mappings.
 pub fn kernel_print_mappings() {
     mapping_record::kernel_print()
 }
 
+/// Print the kernel mappings flagged as leaked.
+pub fn kernel_print_mapping_leaks() {
+    mapping_record::kernel_print_leaks()
+}
+
+/// Translate the lower half of the virtual addresses through a program's tables, see
+/// [`UserAddressSpace::phys_base_address()`], or not at all with `None`.
+///
+/// # Safety
+///
+/// - The tables must stay alive until they are replaced.
+pub unsafe fn set_user_tables(phys_tables_base_addr: Option<Address<Physical>>) {
+    arch_mmu::mmu().set_user_tables(phys_tables_base_addr)
+}
+
 /// Enable the MMU and data + instruction caching.
 ///
 /// # Safety
@@ -3,22 +3,42 @@
 // Copyright (c) 2020-2023 Andre Richter <andre.o.richter@gmail.com>
 
 //! A record of mapped pages.
+//!
+//! Every mapping of the kernel's tables is recorded with the entities that use it, and for each
+//! entity optionally the subsystem that owns it. Unmapping drops a user, and the pages go once the
+//! last user is gone. A subsystem that is torn down should have dropped its mappings; those it
+//! left behind are flagged as leaked.
 
 use super::{
     AccessPermissions, Address, AttributeFields, MMIODescriptor, MemAttributes, MemoryRegion,
-    Physical, Virtual,
+    PageAddress, Physical, Virtual,
+};
+use crate::{
+    bsp, common, info,
+    synchronization::{interface::Mutex, IRQSafeDeviceLock},
 };
-use crate::{bsp, common, info, synchronization, synchronization::InitStateLock};
 use alloc::{vec, vec::Vec};
+use core::fmt;
 
 //--------------------------------------------------------------------------------------------------
 // Private Definitions
 //--------------------------------------------------------------------------------------------------
 
+/// An entity using a mapping.
+struct MappingUser {
+    name: &'static str,
+
+    /// The subsystem that maps and unmaps it, if any.
+    owner: Option<&'static str>,
+
+    /// Set if the owner was torn down while the mapping was still in use.
+    leaked: bool,
+}
+
 /// Type describing a virtual memory mapping.
 #[allow(missing_docs)]
 struct MappingRecordEntry {
-    pub users: Vec<&'static str>,
+    pub users: Vec<MappingUser>,
     pub phys_start_addr: Address<Physical>,
     pub virt_start_addr: Address<Virtual>,
     pub num_pages: usize,
@@ -27,14 +47,19 @@ struct MappingRecordEntry {
 
 struct MappingRecord {
     inner: Vec<MappingRecordEntry>,
+
+    /// Mappings added and removed since boot.
+    maps: usize,
+    unmaps: usize,
 }
 
 //--------------------------------------------------------------------------------------------------
 // Global instances
 //--------------------------------------------------------------------------------------------------
 
-static KERNEL_MAPPING_RECORD: InitStateLock<MappingRecord> =
-    InitStateLock::new(MappingRecord::new());
+// Written after init as well, when subsystems are torn down.
+static KERNEL_MAPPING_RECORD: IRQSafeDeviceLock<MappingRecord> =
+    IRQSafeDeviceLock::new(MappingRecord::new());
 
 //--------------------------------------------------------------------------------------------------
 // Private Code
@@ -43,12 +68,17 @@ static KERNEL_MAPPING_RECORD: InitStateLock<MappingRecord> =
 impl MappingRecordEntry {
     pub fn new(
         name: &'static str,
+        owner: Option<&'static str>,
         virt_region: &MemoryRegion<Virtual>,
         phys_region: &MemoryRegion<Physical>,
         attr: &AttributeFields,
     ) -> Self {
         Self {
-            users: vec![name],
+            users: vec![MappingUser {
+                name,
+                owner,
+                leaked: false,
+            }],
             phys_start_addr: phys_region.start_addr(),
             virt_start_addr: virt_region.start_addr(),
             num_pages: phys_region.num_pages(),
@@ -56,14 +86,45 @@ impl MappingRecordEntry {
         }
     }
 
-    pub fn add_user(&mut self, user: &'static str) {
-        self.users.push(user);
+    pub fn add_user(&mut self, name: &'static str, owner: Option<&'static str>) {
+        self.users.push(MappingUser {
+            name,
+            owner,
+            leaked: false,
+        });
+    }
+
+    fn size(&self) -> usize {
+        self.num_pages * bsp::memory::mmu::KernelGranule::SIZE
+    }
+
+    fn contains(&self, virt_addr: Address<Virtual>) -> bool {
+        match virt_addr
+            .as_usize()
+            .checked_sub(self.virt_start_addr.as_usize())
+        {
+            Some(offset) => offset < self.size(),
+            None => false,
+        }
+    }
+
+    fn virt_region(&self) -> MemoryRegion<Virtual> {
+        let start = PageAddress::from(self.virt_start_addr);
+
+        MemoryRegion::new(
+            start,
+            start.checked_offset(self.num_pages as isize).unwrap(),
+        )
     }
 }
 
 impl MappingRecord {
     pub const fn new() -> Self {
-        Self { inner: Vec::new() }
+        Self {
+            inner: Vec::new(),
+            maps: 0,
+            unmaps: 0,
+        }
     }
 
     fn sort(&mut self) {
@@ -95,20 +156,83 @@ impl MappingRecord {
     pub fn add(
         &mut self,
         name: &'static str,
+        owner: Option<&'static str>,
         virt_region: &MemoryRegion<Virtual>,
         phys_region: &MemoryRegion<Physical>,
         attr: &AttributeFields,
     ) {
         self.inner.push(MappingRecordEntry::new(
             name,
+            owner,
             virt_region,
             phys_region,
             attr,
         ));
+        self.maps += 1;
 
         self.sort();
     }
 
+    /// Drop the user `name` of the mapping that contains `virt_addr`. Returns the mapping's region
+    /// if that was the last user, and the mapping is gone from the record.
+    fn remove_user(
+        &mut self,
+        name: &str,
+        virt_addr: Address<Virtual>,
+    ) -> Result<Option<MemoryRegion<Virtual>>, &'static str> {
+        let index = self
+            .inner
+            .iter()
+            .position(|i| i.contains(virt_addr))
+            .ok_or("No mapping at this address")?;
+        let entry = &mut self.inner[index];
+        let user = entry
+            .users
+            .iter()
+            .position(|u| u.name == name)
+            .ok_or("Mapping not used by this entity")?;
+
+        entry.users.remove(user);
+        if !entry.users.is_empty() {
+            return Ok(None);
+        }
+
+        let region = entry.virt_region();
+        self.inner.remove(index);
+        self.unmaps += 1;
+
+        Ok(Some(region))
+    }
+
+    /// Flag the mappings still used on behalf of `owner` as leaked. Returns their number.
+    fn flag_leaks(&mut self, owner: &str) -> usize {
+        let mut leaks = 0;
+        for u in self.inner.iter_mut().flat_map(|i| i.users.iter_mut()) {
+            if u.owner == Some(owner) {
+                u.leaked = true;
+                leaks += 1;
+            }
+        }
+
+        leaks
+    }
+
+    fn num_leaks(&self) -> usize {
+        self.inner
+            .iter()
+            .flat_map(|i| i.users.iter())
+            .filter(|u| u.leaked)
+            .count()
+    }
+
+    /// Return the first user of the mapping that contains `virt_addr`, and the offset into it.
+    fn find(&self, virt_addr: Address<Virtual>) -> Option<(&'static str, usize)> {
+        self.inner
+            .iter()
+            .find(|i| i.contains(virt_addr))
+            .map(|i| (i.users[0].name, (virt_addr - i.virt_start_addr).as_usize()))
+    }
+
     pub fn print(&self) {
         info!("      -------------------------------------------------------------------------------------------------------------------------------------------");
         info!(
@@ -118,7 +242,7 @@ impl MappingRecord {
         info!("      -------------------------------------------------------------------------------------------------------------------------------------------");
 
         for i in self.inner.iter() {
-            let size = i.num_pages * bsp::memory::mmu::KernelGranule::SIZE;
+            let size = i.size();
             let virt_start = i.virt_start_addr;
             let virt_end_inclusive = virt_start + (size - 1);
             let phys_start = i.phys_start_addr;
@@ -134,6 +258,8 @@ impl MappingRecord {
             let acc_p = match i.attribute_fields.acc_perms {
                 AccessPermissions::ReadOnly => "RO",
                 AccessPermissions::ReadWrite => "RW",
+                AccessPermissions::ReadOnlyUser => "ROU",
+                AccessPermissions::ReadWriteUser => "RWU",
             };
 
             let xn = if i.attribute_fields.execute_never {
@@ -165,40 +291,139 @@ impl MappingRecord {
         }
 
         info!("      -------------------------------------------------------------------------------------------------------------------------------------------");
+        info!(
+            "      {} mappings, {} pages, {} mapped and {} unmapped since boot, {} leaked",
+            self.inner.len(),
+            self.inner.iter().map(|i| i.num_pages).sum::<usize>(),
+            self.maps,
+            self.unmaps,
+            self.num_leaks()
+        );
+    }
+
+    fn print_leaks(&self) {
+        for i in self.inner.iter() {
+            for u in i.users.iter().filter(|u| u.leaked) {
+                info!("      {} {}", i.virt_start_addr, u);
+            }
+        }
     }
 }
 
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
-use synchronization::interface::ReadWriteEx;
+
+impl fmt::Display for MappingUser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(owner) = self.owner {
+            write!(f, " ({})", owner)?;
+        }
+        if self.leaked {
+            write!(f, " LEAKED")?;
+        }
+
+        Ok(())
+    }
+}
 
 /// Add an entry to the mapping info record.
 pub fn kernel_add(
     name: &'static str,
+    owner: Option<&'static str>,
     virt_region: &MemoryRegion<Virtual>,
     phys_region: &MemoryRegion<Physical>,
     attr: &AttributeFields,
 ) {
-    KERNEL_MAPPING_RECORD.write(|mr| mr.add(name, virt_region, phys_region, attr))
+    KERNEL_MAPPING_RECORD.lock(|mr| mr.add(name, owner, virt_region, phys_region, attr))
 }
 
 pub fn kernel_find_and_insert_mmio_duplicate(
     mmio_descriptor: &MMIODescriptor,
     new_user: &'static str,
+    owner: Option<&'static str>,
 ) -> Option<Address<Virtual>> {
     let phys_region: MemoryRegion<Physical> = (*mmio_descriptor).into();
 
-    KERNEL_MAPPING_RECORD.write(|mr| {
+    KERNEL_MAPPING_RECORD.lock(|mr| {
         let dup = mr.find_duplicate(&phys_region)?;
 
-        dup.add_user(new_user);
+        dup.add_user(new_user, owner);
 
         Some(dup.virt_start_addr)
     })
 }
 
+/// Drop the user `name` of the mapping that contains `virt_addr`. Returns the mapping's region if
+/// it has no users left and must be unmapped.
+pub fn kernel_remove_user(
+    name: &str,
+    virt_addr: Address<Virtual>,
+) -> Result<Option<MemoryRegion<Virtual>>, &'static str> {
+    KERNEL_MAPPING_RECORD.lock(|mr| mr.remove_user(name, virt_addr))
+}
+
+/// Flag the mappings still used on behalf of `owner` as leaked. Returns their number.
+pub fn kernel_flag_leaks(owner: &str) -> usize {
+    KERNEL_MAPPING_RECORD.lock(|mr| mr.flag_leaks(owner))
+}
+
+/// Return the entity a kernel virtual address is mapped for, and the offset into its mapping.
+pub fn kernel_find(virt_addr: Address<Virtual>) -> Option<(&'static str, usize)> {
+    KERNEL_MAPPING_RECORD.lock(|mr| mr.find(virt_addr))
+}
+
 /// Human-readable print of all recorded kernel mappings.
 pub fn kernel_print() {
-    KERNEL_MAPPING_RECORD.read(|mr| mr.print());
+    KERNEL_MAPPING_RECORD.lock(|mr| mr.print());
+}
+
+/// Print the mappings flagged as leaked.
+pub fn kernel_print_leaks() {
+    KERNEL_MAPPING_RECORD.lock(|mr| mr.print_leaks());
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Unmapping must drop users until the last one, and leaks must only flag the owner's users.
+    #[kernel_test]
+    fn record_unmap_and_leaks() {
+        const PAGE: usize = bsp::memory::mmu::KernelGranule::SIZE;
+
+        let descriptor = MMIODescriptor::new(Address::new(0x3F20_0000), PAGE);
+        let phys_region = MemoryRegion::<Physical>::from(descriptor);
+        let virt_region = MemoryRegion::<Virtual>::new(
+            PageAddress::from(0x1_0000 * PAGE),
+            PageAddress::from(0x1_0002 * PAGE),
+        );
+        let attr = AttributeFields {
+            mem_attributes: MemAttributes::Device,
+            acc_perms: AccessPermissions::ReadWrite,
+            execute_never: true,
+        };
+        let virt_addr = virt_region.start_addr() + 8;
+
+        let mut mr = MappingRecord::new();
+        mr.add("a", Some("net"), &virt_region, &phys_region, &attr);
+        mr.find_duplicate(&phys_region).unwrap().add_user("b", None);
+
+        assert_eq!(mr.flag_leaks("net"), 1);
+        assert_eq!(mr.num_leaks(), 1);
+        assert_eq!(mr.find(virt_addr), Some(("a", 8)));
+
+        assert!(mr.remove_user("c", virt_addr).is_err());
+        assert_eq!(mr.remove_user("a", virt_addr), Ok(None));
+        assert_eq!(mr.num_leaks(), 0);
+        assert_eq!(mr.remove_user("b", virt_addr), Ok(Some(virt_region)));
+        assert_eq!(mr.find(virt_addr), None);
+        assert_eq!((mr.maps, mr.unmaps), (1, 1));
+    }
 }
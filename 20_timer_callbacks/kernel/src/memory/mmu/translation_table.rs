@@ -53,6 +53,16 @@ pub mod interface {
             attr: &AttributeFields,
         ) -> Result<(), &'static str>;
 
+        /// Unmap the given virtual memory region. Every page of it must be mapped.
+        ///
+        /// # Safety
+        ///
+        /// - The caller must invalidate the TLB afterwards, and nothing may use the region anymore.
+        unsafe fn unmap_at(
+            &mut self,
+            virt_region: &MemoryRegion<Virtual>,
+        ) -> Result<(), &'static str>;
+
         /// Try to translate a virtual page address to a physical page address.
         ///
         /// Will only succeed if there exists a valid mapping for the input page.
@@ -134,4 +144,42 @@ mod tests {
         let phys_addr = phys_start_page_addr.into_inner() + 0x100;
         assert_eq!(tables.try_virt_addr_to_phys_addr(virt_addr), Ok(phys_addr));
     }
+
+    /// Unmapped pages must be marked invalid, and unmapping them again must fail.
+    #[kernel_test]
+    fn unmap_invalidates_pages() {
+        // This will occupy a lot of space on the stack.
+        let mut tables = MinSizeTranslationTable::new_for_runtime();
+
+        assert_eq!(tables.init(), Ok(()));
+
+        let virt_end_exclusive_page_addr: PageAddress<Virtual> = PageAddress::MAX;
+        let virt_start_page_addr: PageAddress<Virtual> =
+            virt_end_exclusive_page_addr.checked_offset(-2).unwrap();
+
+        let phys_start_page_addr: PageAddress<Physical> = PageAddress::from(0);
+        let phys_end_exclusive_page_addr: PageAddress<Physical> =
+            phys_start_page_addr.checked_offset(2).unwrap();
+
+        let virt_region = MemoryRegion::new(virt_start_page_addr, virt_end_exclusive_page_addr);
+        let phys_region = MemoryRegion::new(phys_start_page_addr, phys_end_exclusive_page_addr);
+
+        let attr = AttributeFields {
+            mem_attributes: MemAttributes::Device,
+            acc_perms: AccessPermissions::ReadWrite,
+            execute_never: true,
+        };
+
+        unsafe { assert_eq!(tables.map_at(&virt_region, &phys_region, &attr), Ok(())) };
+        unsafe { assert_eq!(tables.unmap_at(&virt_region), Ok(())) };
+
+        assert_eq!(
+            tables.try_page_attributes(virt_start_page_addr),
+            Err("Page marked invalid")
+        );
+        assert!(tables
+            .try_virt_page_addr_to_phys_page_addr(virt_start_page_addr)
+            .is_err());
+        unsafe { assert!(tables.unmap_at(&virt_region).is_err()) };
+    }
 }
@@ -35,12 +35,15 @@ pub enum MemAttributes {
     Device,
 }
 
-/// Architecture agnostic access permissions.
+/// Architecture agnostic access permissions. The `User` variants give programs at EL0 the same
+/// access as the kernel.
 #[allow(missing_docs)]
 #[derive(Copy, Clone, Debug, Eq, PartialOrd, PartialEq)]
 pub enum AccessPermissions {
     ReadOnly,
     ReadWrite,
+    ReadOnlyUser,
+    ReadWriteUser,
 }
 
 /// Collection of memory attributes.
@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Program address spaces.
+//!
+//! A program sees its own address space in the lower half of the virtual addresses, translated
+//! through TTBR0 while it runs. The kernel in the upper half stays out of its reach. The pages of a
+//! program are taken from the kernel heap, so the kernel can reach them through its own mapping as
+//! well, e.g. to load the program's code.
+
+use super::{
+    translation_table::interface::TranslationTable, AccessPermissions, AttributeFields,
+    MemAttributes, MemoryRegion, PageAddress,
+};
+use crate::{
+    bsp::{self, memory::mmu::KernelGranule},
+    memory::{Address, Physical, Virtual},
+};
+use alloc::{
+    alloc::{alloc_zeroed, dealloc, Layout},
+    vec::Vec,
+};
+use core::ptr::NonNull;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+type UserTranslationTable = bsp::memory::mmu::UserTranslationTable;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The translation tables of a program and the memory mapped through them.
+pub struct UserAddressSpace {
+    tables: NonNull<UserTranslationTable>,
+
+    /// Memory of the mapped regions, as the kernel sees it.
+    regions: Vec<(NonNull<u8>, Layout)>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl UserAddressSpace {
+    fn tables(&self) -> &UserTranslationTable {
+        unsafe { self.tables.as_ref() }
+    }
+
+    fn tables_mut(&mut self) -> &mut UserTranslationTable {
+        unsafe { self.tables.as_mut() }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+// The address space owns its tables and memory, no one else points to them.
+unsafe impl Send for UserAddressSpace {}
+
+impl UserAddressSpace {
+    /// Create an empty address space.
+    pub fn new() -> Result<Self, &'static str> {
+        // The tables are too large for a task's stack, so they are built in place. All zeroes are
+        // empty tables.
+        let layout = Layout::new::<UserTranslationTable>();
+        let tables = unsafe { alloc_zeroed(layout) } as *mut UserTranslationTable;
+        let tables = NonNull::new(tables).ok_or("Out of memory")?;
+
+        let mut space = Self {
+            tables,
+            regions: Vec::new(),
+        };
+        space.tables_mut().init()?;
+
+        Ok(space)
+    }
+
+    /// Map `size` bytes of zeroed memory at the page aligned `virt_addr`, rounded up to whole
+    /// pages. Returns the memory as the kernel sees it.
+    ///
+    /// `acc_perms` should be one of the `User` variants, or the program can't access the memory.
+    pub fn map(
+        &mut self,
+        virt_addr: usize,
+        size: usize,
+        acc_perms: AccessPermissions,
+        execute_never: bool,
+    ) -> Result<&mut [u8], &'static str> {
+        if !Address::<Virtual>::new(virt_addr).is_page_aligned() {
+            return Err("Address not page aligned");
+        }
+        let size = Address::<Virtual>::new(size).align_up_page().as_usize();
+        if size == 0 {
+            return Err("Requested 0 pages");
+        }
+
+        let layout = Layout::from_size_align(size, KernelGranule::SIZE).map_err(|_| "Bad size")?;
+        let memory = NonNull::new(unsafe { alloc_zeroed(layout) }).ok_or("Out of memory")?;
+        // Freed with the address space, even if the mapping fails.
+        self.regions.push((memory, layout));
+
+        // Regions of the kernel are mapped contiguously, so this holds for the whole allocation.
+        let phys_addr =
+            super::try_kernel_virt_addr_to_phys_addr(Address::new(memory.as_ptr() as usize))?;
+
+        let virt_region = MemoryRegion::<Virtual>::new(
+            PageAddress::from(virt_addr),
+            PageAddress::from(virt_addr.checked_add(size).ok_or("Region out of bounds")?),
+        );
+        let phys_region = MemoryRegion::<Physical>::new(
+            PageAddress::from(phys_addr.as_usize()),
+            PageAddress::from(phys_addr.as_usize() + size),
+        );
+        let attr = AttributeFields {
+            mem_attributes: MemAttributes::CacheableDRAM,
+            acc_perms,
+            execute_never,
+        };
+        unsafe {
+            self.tables_mut()
+                .map_at(&virt_region, &phys_region, &attr)?
+        };
+
+        Ok(unsafe { core::slice::from_raw_parts_mut(memory.as_ptr(), size) })
+    }
+
+    /// Return if the program may access `len` bytes at `addr`, for writing if `write` is set.
+    pub fn is_accessible(&self, addr: usize, len: usize, write: bool) -> bool {
+        let end = match addr.checked_add(len) {
+            Some(end) => end,
+            None => return false,
+        };
+
+        let start = Address::<Virtual>::new(addr).align_down_page().as_usize();
+        (start..end).step_by(KernelGranule::SIZE).all(|page| {
+            match self.tables().try_page_attributes(PageAddress::from(page)) {
+                Ok(attr) => match attr.acc_perms {
+                    AccessPermissions::ReadWriteUser => true,
+                    AccessPermissions::ReadOnlyUser => !write,
+                    _ => false,
+                },
+                Err(_) => false,
+            }
+        })
+    }
+
+    /// The physical address of the tables, for [`super::set_user_tables()`].
+    pub fn phys_base_address(&self) -> Result<Address<Physical>, &'static str> {
+        self.tables().phys_base_address()
+    }
+}
+
+impl Drop for UserAddressSpace {
+    fn drop(&mut self) {
+        for (memory, layout) in self.regions.drain(..) {
+            unsafe { dealloc(memory.as_ptr(), layout) };
+        }
+
+        let layout = Layout::new::<UserTranslationTable>();
+        unsafe { dealloc(self.tables.as_ptr() as *mut u8, layout) };
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Accesses must stay within mapped pages and respect their permissions.
+    #[kernel_test]
+    fn user_access_checks() {
+        const PAGE: usize = KernelGranule::SIZE;
+
+        let mut space = UserAddressSpace::new().unwrap();
+        assert_eq!(
+            space
+                .map(PAGE, 1, AccessPermissions::ReadOnlyUser, false)
+                .map(|m| m.len()),
+            Ok(PAGE)
+        );
+        assert!(space
+            .map(3 * PAGE, PAGE, AccessPermissions::ReadWriteUser, true)
+            .is_ok());
+        assert!(space
+            .map(PAGE + 8, PAGE, AccessPermissions::ReadWriteUser, true)
+            .is_err());
+
+        assert!(space.is_accessible(PAGE, PAGE, false));
+        assert!(!space.is_accessible(PAGE, 1, true));
+        assert!(space.is_accessible(3 * PAGE + 8, 8, true));
+
+        // Runs into the unmapped page between the regions.
+        assert!(!space.is_accessible(PAGE, PAGE + 1, false));
+        assert!(!space.is_accessible(0, 1, false));
+        assert!(!space.is_accessible(usize::MAX, 2, false));
+    }
+}
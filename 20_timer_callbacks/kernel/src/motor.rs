@@ -0,0 +1,380 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! DC motors on H-bridges.
+//!
+//! A motor is driven through two direction pins and a PWM enable pin, as on the L298N and similar
+//! drivers:
+//!
+//! | IN1  | IN2  | Bridge  |
+//! |------|------|---------|
+//! | low  | low  | coast   |
+//! | high | low  | forward |
+//! | low  | high | reverse |
+//! | high | high | brake   |
+//!
+//! Changing between forward, reverse and brake goes through coast for at least [`DEADTIME`], so
+//! that the two switches of a half bridge never conduct at once and the motor current decays
+//! before it is reversed.
+//!
+//! [`emergency_stop()`] lets all motors coast and refuses further commands until [`release()`].
+//! It is safe to call from IRQ context, e.g. from the button set with [`set_stop_button()`].
+
+use crate::{
+    bsp, info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time, trace,
+};
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// PWM frequency of the enable pins, above the audible range.
+const PWM_FREQ_HZ: u32 = 20_000;
+
+#[derive(Copy, Clone)]
+struct Motor {
+    in1: u8,
+    in2: u8,
+    enable: u8,
+    force: bool,
+    drive: Drive,
+    percent: u32,
+
+    /// Uptime at which the motor started to coast.
+    coast_since: Duration,
+}
+
+struct State {
+    motors: [Option<Motor>; MAX_MOTORS],
+    stop_button: Option<u8>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of motors, one per PWM channel.
+pub const MAX_MOTORS: usize = 2;
+
+/// Shortest time a motor coasts between forward, reverse and brake.
+pub const DEADTIME: Duration = Duration::from_millis(2);
+
+/// State of an H-bridge.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Drive {
+    Coast,
+    Forward,
+    Reverse,
+    Brake,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static STATE: IRQSafeNullLock<State> = IRQSafeNullLock::new(State {
+    motors: [None; MAX_MOTORS],
+    stop_button: None,
+});
+
+/// Set by an emergency stop, checked before every command.
+static STOPPED: AtomicBool = AtomicBool::new(false);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Drive {
+    /// Levels of IN1 and IN2.
+    fn levels(self) -> (bool, bool) {
+        match self {
+            Self::Coast => (false, false),
+            Self::Forward => (true, false),
+            Self::Reverse => (false, true),
+            Self::Brake => (true, true),
+        }
+    }
+}
+
+fn set_pin(pin: u8, level: bool, force: bool) -> Result<(), &'static str> {
+    unsafe {
+        if level {
+            bsp::driver::gpio_high(pin, force)
+        } else {
+            bsp::driver::gpio_low(pin, force)
+        }
+    }
+}
+
+impl Motor {
+    /// Return how long the motor must still coast before it may change to `to`.
+    fn wait_before(&self, to: Drive, now: Duration) -> Duration {
+        if to == self.drive || to == Drive::Coast {
+            Duration::ZERO
+        } else if self.drive == Drive::Coast {
+            (self.coast_since + DEADTIME).saturating_sub(now)
+        } else {
+            DEADTIME
+        }
+    }
+
+    /// Drive the bridge. The duty cycle goes down before and up after the direction pins change.
+    fn apply(&mut self, drive: Drive, percent: u32, now: Duration) -> Result<(), &'static str> {
+        let (in1, in2) = drive.levels();
+
+        unsafe { bsp::driver::pwm_set_duty_cycle(self.enable, percent.min(self.percent))? };
+        set_pin(self.in1, in1, self.force)?;
+        set_pin(self.in2, in2, self.force)?;
+        unsafe { bsp::driver::pwm_set_duty_cycle(self.enable, percent)? };
+
+        if drive == Drive::Coast && self.drive != Drive::Coast {
+            self.coast_since = now;
+        }
+        self.drive = drive;
+        self.percent = percent;
+
+        Ok(())
+    }
+}
+
+fn motor(state: &mut State, id: usize) -> Result<&mut Motor, &'static str> {
+    state
+        .motors
+        .get_mut(id)
+        .and_then(|m| m.as_mut())
+        .ok_or("No such motor")
+}
+
+fn check_released() -> Result<(), &'static str> {
+    if STOPPED.load(Ordering::Acquire) {
+        return Err("Emergency stop active");
+    }
+
+    Ok(())
+}
+
+/// Change motor `id` to `drive` at `percent` duty cycle, coasting for the deadtime first if needed.
+fn set(id: usize, drive: Drive, percent: u32) -> Result<(), &'static str> {
+    if percent > 100 {
+        return Err("Speed out of range");
+    }
+    check_released()?;
+
+    let wait = STATE.lock(|s| {
+        let now = time::time_manager().uptime();
+        let m = motor(s, id)?;
+        let wait = m.wait_before(drive, now);
+        if wait > Duration::ZERO && m.drive != Drive::Coast {
+            m.apply(Drive::Coast, 0, now)?;
+        }
+
+        Ok::<_, &'static str>(wait)
+    })?;
+
+    // Not under the lock, so that an emergency stop can get in.
+    time::time_manager().spin_for(wait);
+
+    STATE.lock(|s| {
+        check_released()?;
+
+        let now = time::time_manager().uptime();
+        let m = motor(s, id)?;
+        if m.wait_before(drive, now) > Duration::ZERO {
+            return Err("Motor changed meanwhile");
+        }
+
+        m.apply(drive, percent, now)
+    })
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl fmt::Display for Drive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Coast => write!(f, "coast"),
+            Self::Forward => write!(f, "forward"),
+            Self::Reverse => write!(f, "reverse"),
+            Self::Brake => write!(f, "brake"),
+        }
+    }
+}
+
+/// Add a motor with direction pins `in1` and `in2` and enable pin `enable`, which must have a PWM
+/// function on a channel no other motor uses. The motor starts coasting. Returns its id.
+pub fn add(in1: u8, in2: u8, enable: u8, force: bool) -> Result<usize, &'static str> {
+    STATE.lock(|s| {
+        if s.motors.iter().flatten().any(|m| {
+            [m.in1, m.in2, m.enable]
+                .iter()
+                .any(|p| [in1, in2, enable].contains(p))
+        }) {
+            return Err("Pin used by another motor");
+        }
+        let id = s
+            .motors
+            .iter()
+            .position(|m| m.is_none())
+            .ok_or("Too many motors")?;
+
+        unsafe {
+            bsp::driver::gpio_as_output(in1, force)?;
+            bsp::driver::gpio_as_output(in2, force)?;
+            bsp::driver::pwm_set_frequency(enable, PWM_FREQ_HZ)?;
+            bsp::driver::pwm_set_duty_cycle(enable, 0)?;
+            bsp::driver::pwm_enable(enable, force)?;
+        }
+        set_pin(in1, false, force)?;
+        set_pin(in2, false, force)?;
+
+        s.motors[id] = Some(Motor {
+            in1,
+            in2,
+            enable,
+            force,
+            drive: Drive::Coast,
+            percent: 0,
+            coast_since: time::time_manager().uptime(),
+        });
+
+        Ok(id)
+    })
+}
+
+/// Drive motor `id` at `speed` percent, forward if positive and reverse if negative. 0 coasts.
+pub fn set_speed(id: usize, speed: i32) -> Result<(), &'static str> {
+    let drive = match speed {
+        0 => Drive::Coast,
+        s if s > 0 => Drive::Forward,
+        _ => Drive::Reverse,
+    };
+
+    set(id, drive, speed.unsigned_abs())
+}
+
+/// Short the terminals of motor `id`, stopping it quickly.
+pub fn brake(id: usize) -> Result<(), &'static str> {
+    set(id, Drive::Brake, 100)
+}
+
+/// Let motor `id` run freely.
+pub fn coast(id: usize) -> Result<(), &'static str> {
+    set(id, Drive::Coast, 0)
+}
+
+/// Let all motors coast and refuse commands until [`release()`]. Safe in IRQ context.
+pub fn emergency_stop() {
+    STOPPED.store(true, Ordering::Release);
+    trace::record("motor", "estop", 0);
+
+    STATE.lock(|s| {
+        let now = time::time_manager().uptime();
+        for m in s.motors.iter_mut().flatten() {
+            let _ = m.apply(Drive::Coast, 0, now);
+        }
+    });
+}
+
+/// Accept commands again after an emergency stop. The motors keep coasting.
+pub fn release() {
+    STOPPED.store(false, Ordering::Release);
+}
+
+/// Return whether an emergency stop is active.
+pub fn is_stopped() -> bool {
+    STOPPED.load(Ordering::Acquire)
+}
+
+/// Trigger an emergency stop when `pin` goes low, or remove the stop button with `None`. The
+/// button must pull the pin to ground against a pull-up.
+pub fn set_stop_button(pin: Option<u8>) -> Result<(), &'static str> {
+    if let Some(old) = STATE.lock(|s| s.stop_button.take()) {
+        unsafe { bsp::driver::gpio_unregister_irq(old)? };
+    }
+
+    if let Some(pin) = pin {
+        unsafe {
+            bsp::driver::gpio_as_input(pin, false)?;
+            bsp::driver::gpio_register_irq_both(pin, |_, level| {
+                if !level {
+                    emergency_stop();
+                }
+            })?;
+        }
+        STATE.lock(|s| s.stop_button = Some(pin));
+    }
+
+    Ok(())
+}
+
+/// Print the emergency stop state and the motors.
+pub fn print() {
+    STATE.lock(|s| {
+        info!(
+            "      Emergency stop: {}",
+            if is_stopped() { "active" } else { "released" }
+        );
+        match s.stop_button {
+            Some(pin) => info!("      Stop button:    pin {}", pin),
+            None => info!("      Stop button:    none"),
+        }
+
+        for (id, m) in s.motors.iter().enumerate() {
+            if let Some(m) = m {
+                info!(
+                    "      {}: pins {}/{} PWM {}  {} {} %",
+                    id, m.in1, m.in2, m.enable, m.drive, m.percent
+                );
+            }
+        }
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Changes between forward, reverse and brake must coast for the deadtime first, while
+    /// coasting, speed changes and a long enough coast need no wait.
+    #[kernel_test]
+    fn deadtime_between_directions() {
+        let ms = Duration::from_millis;
+        let mut m = Motor {
+            in1: 5,
+            in2: 6,
+            enable: 12,
+            force: false,
+            drive: Drive::Forward,
+            percent: 50,
+            coast_since: ms(0),
+        };
+
+        assert_eq!(m.wait_before(Drive::Forward, ms(10)), Duration::ZERO);
+        assert_eq!(m.wait_before(Drive::Coast, ms(10)), Duration::ZERO);
+        assert_eq!(m.wait_before(Drive::Reverse, ms(10)), DEADTIME);
+        assert_eq!(m.wait_before(Drive::Brake, ms(10)), DEADTIME);
+
+        m.drive = Drive::Coast;
+        m.coast_since = ms(10);
+        assert_eq!(m.wait_before(Drive::Reverse, ms(11)), DEADTIME - ms(1));
+        assert_eq!(
+            m.wait_before(Drive::Reverse, ms(10) + DEADTIME),
+            Duration::ZERO
+        );
+    }
+}
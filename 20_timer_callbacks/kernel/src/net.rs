@@ -0,0 +1,844 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Networking.
+//!
+//! A small IPv4 stack: Ethernet II framing, ARP, IPv4, ICMP and UDP. Network devices implement
+//! [`interface::NetDevice`] and are attached to the stack with [`NetStack::add_interface()`].
+//! Applications do not use the protocol layers directly, but go through the socket API in
+//! [`socket`].
+//!
+//! The stack polls its devices from a periodic timer, so that the board answers ARP requests and
+//! pings on its own. The addresses of the interfaces are kept in the config store.
+
+mod arp;
+mod ethernet;
+mod icmp;
+mod ipv4;
+mod loopback;
+mod types;
+mod udp;
+
+pub mod diag;
+pub mod socket;
+pub mod tftp;
+pub mod wifi;
+
+use crate::{
+    config,
+    event::{self, Event},
+    info,
+    memory::heap_alloc::quota::{self, Quota},
+    subsys,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time, trace, watchdog,
+};
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+pub use types::*;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Largest IPv4 packet that is sent in a single Ethernet frame.
+const MTU: usize = 1500;
+
+/// Interval at which the devices are polled for received frames.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Netmask of an interface whose address is configured without one.
+const DEFAULT_NETMASK: Ipv4Address = Ipv4Address::new(255, 255, 255, 0);
+
+/// Interval at which stale neighbor cache entries are removed.
+const NEIGHBOR_AGING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// An attached network device plus its configuration.
+struct NetInterface {
+    device: &'static (dyn interface::NetDevice + Sync),
+    config: Ipv4Config,
+    stats: InterfaceStats,
+
+    /// Link state seen at the last poll, used to detect changes.
+    link_up: bool,
+}
+
+/// Reasons why a packet could not be transmitted.
+enum TxError {
+    /// The next hop's MAC address is not known yet. An ARP request has been sent.
+    Unresolved,
+
+    /// Any other error.
+    Failed(&'static str),
+}
+
+/// Reasons why a received frame was not consumed.
+enum RxError {
+    /// The frame or one of the packets inside failed validation.
+    Malformed,
+
+    /// The frame was valid, but nobody wanted it, e.g. an unknown protocol or a full socket queue.
+    Dropped,
+}
+
+struct NetStackInner {
+    interfaces: Vec<NetInterface>,
+    neighbors: arp::NeighborCache,
+    sockets: socket::SocketTable,
+    echo: icmp::EchoTracker,
+    next_ip_id: u16,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Network interfaces.
+pub mod interface {
+    use super::MacAddress;
+    use alloc::vec::Vec;
+
+    /// Network device functions.
+    ///
+    /// Devices exchange raw Ethernet II frames (without FCS) with the stack.
+    pub trait NetDevice {
+        /// Short interface name, e.g. `eth0`.
+        fn name(&self) -> &'static str;
+
+        /// The device's MAC address.
+        fn mac_address(&self) -> MacAddress;
+
+        /// Queue a frame for transmission.
+        fn transmit(&self, frame: &[u8]) -> Result<(), &'static str>;
+
+        /// Return the next received frame, if any.
+        fn receive(&self) -> Option<Vec<u8>>;
+
+        /// Return if the link is up.
+        fn is_link_up(&self) -> bool {
+            true
+        }
+
+        /// Return the link speed in Mbit/s, if the device knows it and the link is up.
+        fn link_speed(&self) -> Option<u32> {
+            None
+        }
+
+        /// Return if this is a loopback device.
+        fn is_loopback(&self) -> bool {
+            false
+        }
+    }
+}
+
+/// IPv4 configuration of an interface.
+#[derive(Copy, Clone)]
+pub struct Ipv4Config {
+    /// The interface's own address.
+    pub address: Ipv4Address,
+
+    /// The subnet mask.
+    pub netmask: Ipv4Address,
+
+    /// The default gateway, if any.
+    pub gateway: Option<Ipv4Address>,
+}
+
+/// Traffic counters of an interface.
+#[derive(Copy, Clone, Default)]
+pub struct InterfaceStats {
+    pub rx_packets: u64,
+    pub rx_bytes: u64,
+    pub rx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_packets: u64,
+    pub tx_bytes: u64,
+    pub tx_errors: u64,
+    pub tx_dropped: u64,
+}
+
+/// The network stack.
+pub struct NetStack {
+    inner: IRQSafeNullLock<NetStackInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static NET_STACK: NetStack = NetStack::new();
+
+/// Datagrams waiting in socket queues are held within this quota, so that a flood can't take the
+/// heap from the rest of the kernel.
+static NET_QUOTA: Quota = Quota::new("net", 64 * 1024);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl NetStackInner {
+    const fn new() -> Self {
+        Self {
+            interfaces: Vec::new(),
+            neighbors: arp::NeighborCache::new(),
+            sockets: socket::SocketTable::new(),
+            echo: icmp::EchoTracker::new(),
+            next_ip_id: 0,
+        }
+    }
+
+    /// Select the outgoing interface and the next hop for `dst`.
+    fn route(&self, dst: Ipv4Address) -> Option<(usize, Ipv4Address)> {
+        let ifaces = || self.interfaces.iter().enumerate();
+
+        if dst.is_loopback() {
+            return ifaces()
+                .find(|(_, i)| i.device.is_loopback())
+                .map(|(idx, _)| (idx, dst));
+        }
+
+        let external = || ifaces().filter(|(_, i)| !i.device.is_loopback());
+
+        if dst.is_broadcast() {
+            return external().next().map(|(idx, _)| (idx, dst));
+        }
+
+        if let Some((idx, _)) =
+            external().find(|(_, i)| dst.same_subnet(i.config.address, i.config.netmask))
+        {
+            return Some((idx, dst));
+        }
+
+        external().find_map(|(idx, i)| i.config.gateway.map(|gw| (idx, gw)))
+    }
+
+    /// Hand a frame to the device of interface `idx` and account for it.
+    fn transmit_frame(&mut self, idx: usize, frame: &[u8]) -> Result<(), TxError> {
+        let iface = &mut self.interfaces[idx];
+
+        match iface.device.transmit(frame) {
+            Ok(()) => {
+                iface.stats.tx_packets += 1;
+                iface.stats.tx_bytes += frame.len() as u64;
+                trace::record("net", "tx", frame.len() as u64);
+                Ok(())
+            }
+            Err(x) => {
+                iface.stats.tx_errors += 1;
+                Err(TxError::Failed(x))
+            }
+        }
+    }
+
+    /// Account for a packet to `dst` that is given up on because its next hop didn't resolve.
+    ///
+    /// Senders retry while the address is being resolved, so the drop is counted once they time
+    /// out, not on every [`TxError::Unresolved`].
+    fn drop_unresolved(&mut self, dst: Ipv4Address) {
+        if let Some((idx, _)) = self.route(dst) {
+            self.interfaces[idx].stats.tx_dropped += 1;
+        }
+    }
+
+    /// Send an IPv4 packet out of interface `idx`.
+    #[allow(clippy::too_many_arguments)]
+    fn transmit_ipv4(
+        &mut self,
+        idx: usize,
+        next_hop: Ipv4Address,
+        dst: Ipv4Address,
+        protocol: u8,
+        ttl: u8,
+        payload: &[u8],
+    ) -> Result<(), TxError> {
+        if ipv4::HEADER_LEN + payload.len() > MTU {
+            return Err(TxError::Failed("Packet exceeds MTU"));
+        }
+
+        let iface = &self.interfaces[idx];
+        let device = iface.device;
+        let src = iface.config.address;
+
+        let dst_mac = if device.is_loopback() {
+            device.mac_address()
+        } else if next_hop.is_broadcast() {
+            MacAddress::BROADCAST
+        } else {
+            match self.neighbors.lookup(next_hop) {
+                Some(mac) => mac,
+                None => {
+                    let now = time::time_manager().uptime();
+                    if self.neighbors.should_request(next_hop, now) {
+                        let request = arp::build(
+                            arp::Operation::Request,
+                            device.mac_address(),
+                            src,
+                            MacAddress::ZERO,
+                            next_hop,
+                        );
+                        let frame = ethernet::build(
+                            MacAddress::BROADCAST,
+                            device.mac_address(),
+                            ethernet::ETHERTYPE_ARP,
+                            &request,
+                        );
+                        self.transmit_frame(idx, &frame)?;
+                    }
+
+                    return Err(TxError::Unresolved);
+                }
+            }
+        };
+
+        self.next_ip_id = self.next_ip_id.wrapping_add(1);
+        let packet = ipv4::build(src, dst, protocol, ttl, self.next_ip_id, payload);
+        let frame = ethernet::build(
+            dst_mac,
+            device.mac_address(),
+            ethernet::ETHERTYPE_IPV4,
+            &packet,
+        );
+
+        self.transmit_frame(idx, &frame)
+    }
+
+    /// Send a UDP datagram.
+    fn send_udp(&mut self, src_port: u16, dst: SocketAddr, payload: &[u8]) -> Result<(), TxError> {
+        let (idx, next_hop) = self
+            .route(dst.addr)
+            .ok_or(TxError::Failed("No route to host"))?;
+        let src = self.interfaces[idx].config.address;
+
+        let datagram = udp::build(src, dst.addr, src_port, dst.port, payload);
+
+        self.transmit_ipv4(
+            idx,
+            next_hop,
+            dst.addr,
+            ipv4::PROTOCOL_UDP,
+            ipv4::DEFAULT_TTL,
+            &datagram,
+        )
+    }
+
+    /// Send an ICMP echo request with the given TTL.
+    fn send_icmp_echo(
+        &mut self,
+        dst: Ipv4Address,
+        ident: u16,
+        seq: u16,
+        ttl: u8,
+        data: &[u8],
+    ) -> Result<(), TxError> {
+        let (idx, next_hop) = self.route(dst).ok_or(TxError::Failed("No route to host"))?;
+
+        let message = icmp::build_echo_request(ident, seq, data);
+        self.transmit_ipv4(idx, next_hop, dst, ipv4::PROTOCOL_ICMP, ttl, &message)?;
+
+        self.echo.sent(ident, seq, time::time_manager().uptime());
+        Ok(())
+    }
+
+    fn process_arp(&mut self, idx: usize, payload: &[u8]) -> Result<(), RxError> {
+        let packet = arp::Packet::parse(payload).ok_or(RxError::Malformed)?;
+
+        let iface = &self.interfaces[idx];
+        let device = iface.device;
+        let own_ip = iface.config.address;
+        let now = time::time_manager().uptime();
+
+        if packet.target_ip != own_ip {
+            // Not for us, but refresh the sender if we already know it (RFC 826).
+            if self.neighbors.lookup(packet.sender_ip).is_some() {
+                self.neighbors
+                    .insert(packet.sender_ip, packet.sender_mac, now);
+            }
+            return Ok(());
+        }
+
+        self.neighbors
+            .insert(packet.sender_ip, packet.sender_mac, now);
+
+        if packet.operation == arp::Operation::Request {
+            let reply = arp::build(
+                arp::Operation::Reply,
+                device.mac_address(),
+                own_ip,
+                packet.sender_mac,
+                packet.sender_ip,
+            );
+            let frame = ethernet::build(
+                packet.sender_mac,
+                device.mac_address(),
+                ethernet::ETHERTYPE_ARP,
+                &reply,
+            );
+
+            // Best effort. The peer will retry.
+            let _ = self.transmit_frame(idx, &frame);
+        }
+
+        Ok(())
+    }
+
+    fn process_ipv4(&mut self, idx: usize, payload: &[u8]) -> Result<(), RxError> {
+        let packet = ipv4::Packet::parse(payload).ok_or(RxError::Malformed)?;
+
+        let iface = &self.interfaces[idx];
+        let for_us = packet.dst == iface.config.address
+            || packet.dst.is_broadcast()
+            || (iface.device.is_loopback() && packet.dst.is_loopback());
+        if !for_us {
+            return Ok(());
+        }
+
+        match packet.protocol {
+            ipv4::PROTOCOL_ICMP => self.process_icmp(&packet),
+            ipv4::PROTOCOL_UDP => {
+                let datagram = udp::Datagram::parse(packet.src, packet.dst, packet.payload)
+                    .ok_or(RxError::Malformed)?;
+                let from = SocketAddr::new(packet.src, datagram.src_port);
+
+                if !self
+                    .sockets
+                    .deliver(datagram.dst_port, from, datagram.payload)
+                {
+                    return Err(RxError::Dropped);
+                }
+                Ok(())
+            }
+            _ => Err(RxError::Dropped),
+        }
+    }
+
+    fn process_icmp(&mut self, packet: &ipv4::Packet) -> Result<(), RxError> {
+        let message = icmp::Message::parse(packet.payload).ok_or(RxError::Malformed)?;
+
+        let now = time::time_manager().uptime();
+        let (ident, seq, kind) = match message {
+            icmp::Message::EchoRequest { ident, seq, data } => {
+                // Broadcast pings are not answered.
+                if packet.dst.is_broadcast() {
+                    return Ok(());
+                }
+
+                let reply = icmp::build_echo_reply(ident, seq, data);
+                if let Some((idx, next_hop)) = self.route(packet.src) {
+                    // Best effort. The peer will retry.
+                    let result = self.transmit_ipv4(
+                        idx,
+                        next_hop,
+                        packet.src,
+                        ipv4::PROTOCOL_ICMP,
+                        ipv4::DEFAULT_TTL,
+                        &reply,
+                    );
+                    if let Err(TxError::Unresolved) = result {
+                        self.interfaces[idx].stats.tx_dropped += 1;
+                    }
+                }
+                return Ok(());
+            }
+            icmp::Message::EchoReply { ident, seq } => (ident, seq, icmp::ResponseKind::EchoReply),
+            icmp::Message::TimeExceeded { ident, seq } => {
+                (ident, seq, icmp::ResponseKind::TimeExceeded)
+            }
+            icmp::Message::Unreachable { ident, seq, code } => {
+                (ident, seq, icmp::ResponseKind::Unreachable(code))
+            }
+        };
+
+        self.echo
+            .received(ident, seq, kind, packet.src, packet.ttl, now);
+        Ok(())
+    }
+
+    fn process_frame(&mut self, idx: usize, raw: &[u8]) -> Result<(), RxError> {
+        let frame = ethernet::Frame::parse(raw).ok_or(RxError::Malformed)?;
+
+        let device = self.interfaces[idx].device;
+        if !device.is_loopback()
+            && frame.dst != device.mac_address()
+            && frame.dst != MacAddress::BROADCAST
+        {
+            return Ok(());
+        }
+
+        match frame.ethertype {
+            ethernet::ETHERTYPE_ARP => self.process_arp(idx, frame.payload),
+            ethernet::ETHERTYPE_IPV4 => self.process_ipv4(idx, frame.payload),
+            _ => Err(RxError::Dropped),
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return a reference to the global network stack.
+pub fn net_stack() -> &'static NetStack {
+    &NET_STACK
+}
+
+impl NetStack {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(NetStackInner::new()),
+        }
+    }
+
+    /// Attach a network device to the stack.
+    pub fn add_interface(
+        &self,
+        device: &'static (dyn interface::NetDevice + Sync),
+        config: Ipv4Config,
+    ) {
+        self.inner.lock(|inner| {
+            inner.interfaces.push(NetInterface {
+                device,
+                config,
+                stats: InterfaceStats::default(),
+                link_up: device.is_link_up(),
+            })
+        });
+    }
+
+    /// Change the IPv4 configuration of the interface with the given name.
+    pub fn configure_interface(&self, name: &str, config: Ipv4Config) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            let iface = inner
+                .interfaces
+                .iter_mut()
+                .find(|i| i.device.name() == name)
+                .ok_or("No such interface")?;
+            iface.config = config;
+
+            Ok(())
+        })
+    }
+
+    /// Apply the IPv4 configuration of the interfaces from the config store, kept under
+    /// `net.<interface>.address`, `.netmask` and `.gateway`. Interfaces without an address there
+    /// keep theirs.
+    pub fn load_config(&self) -> Result<(), &'static str> {
+        let names: Vec<&'static str> = self.inner.lock(|inner| {
+            inner
+                .interfaces
+                .iter()
+                .filter(|i| !i.device.is_loopback())
+                .map(|i| i.device.name())
+                .collect()
+        });
+
+        for name in names {
+            let get = |field| config::store().get(&format!("net.{}.{}", name, field));
+            let address = match get("address") {
+                Some(address) => address.parse().map_err(|_| "Malformed net address")?,
+                None => continue,
+            };
+            let netmask = match get("netmask") {
+                Some(netmask) => netmask.parse().map_err(|_| "Malformed net netmask")?,
+                None => DEFAULT_NETMASK,
+            };
+            let gateway = match get("gateway") {
+                Some(gateway) => Some(gateway.parse().map_err(|_| "Malformed net gateway")?),
+                None => None,
+            };
+
+            self.configure_interface(
+                name,
+                Ipv4Config {
+                    address,
+                    netmask,
+                    gateway,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the IPv4 configuration of the interface with the given name to the config store. It
+    /// is persisted on the next save.
+    pub fn store_config(&self, name: &str) -> Result<(), &'static str> {
+        let c = self.inner.lock(|inner| {
+            inner
+                .interfaces
+                .iter()
+                .find(|i| i.device.name() == name)
+                .map(|i| i.config)
+                .ok_or("No such interface")
+        })?;
+        let key = |field| format!("net.{}.{}", name, field);
+
+        config::store().set(&key("address"), &format!("{}", c.address))?;
+        config::store().set(&key("netmask"), &format!("{}", c.netmask))?;
+        match c.gateway {
+            Some(gw) => config::store().set(&key("gateway"), &format!("{}", gw))?,
+            // Nothing to remove if there was no gateway before.
+            None => {
+                let _ = config::store().remove(&key("gateway"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Print the neighbor cache.
+    pub fn print_neighbors(&self) {
+        let now = time::time_manager().uptime();
+
+        self.inner.lock(|inner| {
+            for n in inner.neighbors.entries(now) {
+                if n.is_static {
+                    info!("      {:<15}  {}  static", n.ip, n.mac);
+                } else {
+                    info!("      {:<15}  {}  {} s", n.ip, n.mac, n.age.as_secs());
+                }
+            }
+        });
+    }
+
+    /// Add a static neighbor cache entry.
+    pub fn add_static_neighbor(
+        &self,
+        ip: Ipv4Address,
+        mac: MacAddress,
+    ) -> Result<(), &'static str> {
+        self.inner
+            .lock(|inner| inner.neighbors.insert_static(ip, mac))
+    }
+
+    /// Remove all dynamic neighbor cache entries.
+    pub fn flush_neighbors(&self) {
+        self.inner.lock(|inner| inner.neighbors.flush());
+    }
+
+    /// Reset the stack's transient state: drop dynamic neighbor cache entries, outstanding echo
+    /// requests and frames queued on the devices. Interfaces, sockets and static neighbors are
+    /// kept.
+    pub fn reset(&self) {
+        self.inner.lock(|inner| {
+            inner.neighbors.flush();
+            inner.echo = icmp::EchoTracker::new();
+
+            for iface in &inner.interfaces {
+                while iface.device.receive().is_some() {}
+            }
+        });
+    }
+
+    /// Print the configuration and link state of all interfaces.
+    pub fn print_interfaces(&self) {
+        self.inner.lock(|inner| {
+            for iface in &inner.interfaces {
+                let c = &iface.config;
+
+                let speed = match iface.device.link_speed() {
+                    Some(mbit) if iface.link_up => format!(" {} Mbit/s", mbit),
+                    _ => String::new(),
+                };
+
+                info!(
+                    "      {:<5} {}  {}/{}  link {}{}",
+                    iface.device.name(),
+                    iface.device.mac_address(),
+                    c.address,
+                    c.netmask,
+                    if iface.link_up { "up" } else { "down" },
+                    speed
+                );
+                if let Some(gw) = c.gateway {
+                    info!("            gateway {}", gw);
+                }
+            }
+        });
+    }
+
+    /// Print the traffic counters of all interfaces.
+    pub fn print_stats(&self) {
+        self.inner.lock(|inner| {
+            for iface in &inner.interfaces {
+                let s = &iface.stats;
+
+                info!("      {}:", iface.device.name());
+                info!(
+                    "        RX packets {}  bytes {}  errors {}  dropped {}",
+                    s.rx_packets, s.rx_bytes, s.rx_errors, s.rx_dropped
+                );
+                info!(
+                    "        TX packets {}  bytes {}  errors {}  dropped {}",
+                    s.tx_packets, s.tx_bytes, s.tx_errors, s.tx_dropped
+                );
+            }
+        });
+    }
+
+    /// Return the traffic counters of the interface with the given name.
+    pub fn stats(&self, name: &str) -> Option<InterfaceStats> {
+        self.inner.lock(|inner| {
+            inner
+                .interfaces
+                .iter()
+                .find(|i| i.device.name() == name)
+                .map(|i| i.stats)
+        })
+    }
+
+    /// Process all frames that are pending on the attached devices.
+    ///
+    /// Link state changes are published on the event bus.
+    pub fn poll(&self) {
+        let mut link_changes: Vec<Event> = Vec::new();
+
+        self.inner.lock(|inner| {
+            for idx in 0..inner.interfaces.len() {
+                let device = inner.interfaces[idx].device;
+
+                let link_up = device.is_link_up();
+                if link_up != inner.interfaces[idx].link_up {
+                    inner.interfaces[idx].link_up = link_up;
+                    link_changes.push(if link_up {
+                        Event::LinkUp {
+                            interface: device.name(),
+                        }
+                    } else {
+                        Event::LinkDown {
+                            interface: device.name(),
+                        }
+                    });
+                }
+
+                while let Some(frame) = device.receive() {
+                    trace::record("net", "rx", frame.len() as u64);
+                    let result = inner.process_frame(idx, &frame);
+                    let stats = &mut inner.interfaces[idx].stats;
+
+                    stats.rx_packets += 1;
+                    stats.rx_bytes += frame.len() as u64;
+                    match result {
+                        Ok(()) => (),
+                        Err(RxError::Malformed) => stats.rx_errors += 1,
+                        Err(RxError::Dropped) => stats.rx_dropped += 1,
+                    }
+                }
+            }
+        });
+
+        watchdog::check_in("net");
+
+        // Publish outside of the lock, so that handlers may use the stack.
+        for e in link_changes {
+            event::event_bus().publish(e);
+        }
+    }
+}
+
+/// Initialize the network subsystem and attach the loopback device.
+pub fn init() -> Result<(), &'static str> {
+    static INIT_DONE: AtomicBool = AtomicBool::new(false);
+    if INIT_DONE.load(Ordering::Relaxed) {
+        return Err("Init already done");
+    }
+
+    quota::register(&NET_QUOTA)?;
+
+    net_stack().add_interface(
+        &loopback::LOOPBACK,
+        Ipv4Config {
+            address: Ipv4Address::LOOPBACK,
+            netmask: Ipv4Address::new(255, 0, 0, 0),
+            gateway: None,
+        },
+    );
+
+    time::time_manager().set_timeout_periodic(POLL_INTERVAL, Box::new(|| net_stack().poll()));
+    time::time_manager().set_timeout_periodic(
+        NEIGHBOR_AGING_INTERVAL,
+        Box::new(|| {
+            let now = time::time_manager().uptime();
+            net_stack().inner.lock(|inner| inner.neighbors.expire(now));
+            watchdog::check_in("net");
+        }),
+    );
+    watchdog::register("net", NEIGHBOR_AGING_INTERVAL * 3)?;
+
+    subsys::register(
+        "net",
+        || {
+            net_stack().reset();
+            Ok(())
+        },
+        || {
+            net_stack().poll();
+            Ok(())
+        },
+    )?;
+
+    INIT_DONE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A device that discards every frame, so that no neighbor ever answers.
+    struct Void;
+
+    impl interface::NetDevice for Void {
+        fn name(&self) -> &'static str {
+            "void0"
+        }
+
+        fn mac_address(&self) -> MacAddress {
+            MacAddress([0x02, 0, 0, 0, 0, 1])
+        }
+
+        fn transmit(&self, _frame: &[u8]) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        fn receive(&self) -> Option<Vec<u8>> {
+            None
+        }
+    }
+
+    static VOID: Void = Void;
+
+    /// Retrying a send to an unresolved neighbor must count a single drop, when it is given up on.
+    #[kernel_test]
+    fn unresolved_send_drops_once() {
+        let stack = NetStack::new();
+        stack.add_interface(
+            &VOID,
+            Ipv4Config {
+                address: Ipv4Address::new(10, 0, 0, 1),
+                netmask: DEFAULT_NETMASK,
+                gateway: None,
+            },
+        );
+        let dst = SocketAddr::new(Ipv4Address::new(10, 0, 0, 2), 7);
+
+        for _ in 0..3 {
+            let result = stack.inner.lock(|inner| inner.send_udp(1024, dst, b"ping"));
+            assert!(matches!(result, Err(TxError::Unresolved)));
+        }
+        assert_eq!(stack.stats("void0").unwrap().tx_dropped, 0);
+
+        stack.inner.lock(|inner| inner.drop_unresolved(dst.addr));
+        assert_eq!(stack.stats("void0").unwrap().tx_dropped, 1);
+    }
+}
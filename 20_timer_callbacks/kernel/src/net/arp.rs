@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! ARP and the neighbor cache.
+
+use super::{Ipv4Address, MacAddress};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const PACKET_LEN: usize = 28;
+const HTYPE_ETHERNET: u16 = 1;
+const OPER_REQUEST: u16 = 1;
+const OPER_REPLY: u16 = 2;
+
+/// Maximum number of cached neighbors. The oldest dynamic entry is evicted when full.
+const CACHE_CAPACITY: usize = 32;
+
+/// Dynamic entries that were not refreshed for this long are removed by [`NeighborCache::expire`].
+const ENTRY_LIFETIME: Duration = Duration::from_secs(300);
+
+/// Minimum interval between two requests for the same address.
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Neighbor {
+    ip: Ipv4Address,
+    mac: MacAddress,
+
+    /// Time the entry was last confirmed.
+    updated: Duration,
+
+    /// Static entries are neither aged nor overwritten by received packets.
+    is_static: bool,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// ARP operation.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Operation {
+    Request,
+    Reply,
+}
+
+/// A parsed ARP packet for IPv4 over Ethernet.
+pub struct Packet {
+    pub operation: Operation,
+    pub sender_mac: MacAddress,
+    pub sender_ip: Ipv4Address,
+    pub target_ip: Ipv4Address,
+}
+
+/// A neighbor cache entry, as reported by [`NeighborCache::entries`].
+pub struct NeighborInfo {
+    pub ip: Ipv4Address,
+    pub mac: MacAddress,
+    pub age: Duration,
+    pub is_static: bool,
+}
+
+/// Mapping of IPv4 addresses to MAC addresses.
+pub struct NeighborCache {
+    entries: Vec<Neighbor>,
+
+    /// Unresolved addresses and the time the last request for them was sent.
+    pending: Vec<(Ipv4Address, Duration)>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Packet {
+    /// Parse a raw packet.
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        if buf.len() < PACKET_LEN {
+            return None;
+        }
+
+        let htype = u16::from_be_bytes([buf[0], buf[1]]);
+        let ptype = u16::from_be_bytes([buf[2], buf[3]]);
+        if htype != HTYPE_ETHERNET || ptype != super::ethernet::ETHERTYPE_IPV4 {
+            return None;
+        }
+
+        let operation = match u16::from_be_bytes([buf[6], buf[7]]) {
+            OPER_REQUEST => Operation::Request,
+            OPER_REPLY => Operation::Reply,
+            _ => return None,
+        };
+
+        Some(Self {
+            operation,
+            sender_mac: MacAddress::from_slice(&buf[8..14]),
+            sender_ip: Ipv4Address::from_slice(&buf[14..18]),
+            target_ip: Ipv4Address::from_slice(&buf[24..28]),
+        })
+    }
+}
+
+/// Build a raw packet.
+pub fn build(
+    operation: Operation,
+    sender_mac: MacAddress,
+    sender_ip: Ipv4Address,
+    target_mac: MacAddress,
+    target_ip: Ipv4Address,
+) -> Vec<u8> {
+    let oper = match operation {
+        Operation::Request => OPER_REQUEST,
+        Operation::Reply => OPER_REPLY,
+    };
+
+    let mut packet = Vec::with_capacity(PACKET_LEN);
+    packet.extend_from_slice(&HTYPE_ETHERNET.to_be_bytes());
+    packet.extend_from_slice(&super::ethernet::ETHERTYPE_IPV4.to_be_bytes());
+    packet.push(6);
+    packet.push(4);
+    packet.extend_from_slice(&oper.to_be_bytes());
+    packet.extend_from_slice(&sender_mac.0);
+    packet.extend_from_slice(&sender_ip.0);
+    packet.extend_from_slice(&target_mac.0);
+    packet.extend_from_slice(&target_ip.0);
+
+    packet
+}
+
+impl NeighborCache {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Look up the MAC address of a neighbor.
+    pub fn lookup(&self, ip: Ipv4Address) -> Option<MacAddress> {
+        self.entries.iter().find(|n| n.ip == ip).map(|n| n.mac)
+    }
+
+    /// Insert or refresh a dynamic neighbor at time `now`.
+    pub fn insert(&mut self, ip: Ipv4Address, mac: MacAddress, now: Duration) {
+        self.pending.retain(|(p, _)| *p != ip);
+
+        if let Some(n) = self.entries.iter_mut().find(|n| n.ip == ip) {
+            if !n.is_static {
+                n.mac = mac;
+                n.updated = now;
+            }
+            return;
+        }
+
+        if self.entries.len() == CACHE_CAPACITY {
+            match self.entries.iter().position(|n| !n.is_static) {
+                None => return,
+                Some(oldest) => self.entries.remove(oldest),
+            };
+        }
+
+        self.entries.push(Neighbor {
+            ip,
+            mac,
+            updated: now,
+            is_static: false,
+        });
+    }
+
+    /// Insert a static neighbor, replacing any existing entry for `ip`.
+    pub fn insert_static(&mut self, ip: Ipv4Address, mac: MacAddress) -> Result<(), &'static str> {
+        self.pending.retain(|(p, _)| *p != ip);
+        self.entries.retain(|n| n.ip != ip);
+
+        if self.entries.len() == CACHE_CAPACITY {
+            match self.entries.iter().position(|n| !n.is_static) {
+                None => return Err("Neighbor cache full"),
+                Some(oldest) => self.entries.remove(oldest),
+            };
+        }
+
+        self.entries.push(Neighbor {
+            ip,
+            mac,
+            updated: Duration::ZERO,
+            is_static: true,
+        });
+        Ok(())
+    }
+
+    /// Remove all dynamic entries and forget pending requests. Static entries are kept.
+    pub fn flush(&mut self) {
+        self.entries.retain(|n| n.is_static);
+        self.pending.clear();
+    }
+
+    /// Remove dynamic entries that were not refreshed within their lifetime.
+    pub fn expire(&mut self, now: Duration) {
+        self.entries
+            .retain(|n| n.is_static || now.saturating_sub(n.updated) < ENTRY_LIFETIME);
+    }
+
+    /// Iterate over all entries, with their age at time `now`.
+    pub fn entries(&self, now: Duration) -> impl Iterator<Item = NeighborInfo> + '_ {
+        self.entries.iter().map(move |n| NeighborInfo {
+            ip: n.ip,
+            mac: n.mac,
+            age: now.saturating_sub(n.updated),
+            is_static: n.is_static,
+        })
+    }
+
+    /// Check if a request for `ip` should be sent at time `now`, and note it if so.
+    ///
+    /// Rate-limits requests for addresses that do not answer.
+    pub fn should_request(&mut self, ip: Ipv4Address, now: Duration) -> bool {
+        match self.pending.iter_mut().find(|(p, _)| *p == ip) {
+            Some((_, last)) if now.saturating_sub(*last) < REQUEST_INTERVAL => false,
+            Some((_, last)) => {
+                *last = now;
+                true
+            }
+            None => {
+                if self.pending.len() == CACHE_CAPACITY {
+                    self.pending.remove(0);
+                }
+                self.pending.push((ip, now));
+                true
+            }
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Dynamic entries must age out, static ones must survive aging and flushing.
+    #[kernel_test]
+    fn static_entries_survive_aging() {
+        let mut cache = NeighborCache::new();
+        let dynamic = Ipv4Address::new(10, 0, 0, 1);
+        let fixed = Ipv4Address::new(10, 0, 0, 2);
+        let mac = MacAddress([2, 0, 0, 0, 0, 1]);
+
+        cache.insert(dynamic, mac, Duration::ZERO);
+        cache.insert_static(fixed, mac).unwrap();
+        cache.insert(fixed, MacAddress::BROADCAST, Duration::ZERO);
+        assert_eq!(cache.lookup(fixed), Some(mac));
+
+        cache.expire(ENTRY_LIFETIME);
+        assert_eq!(cache.lookup(dynamic), None);
+        assert_eq!(cache.lookup(fixed), Some(mac));
+
+        cache.flush();
+        assert_eq!(cache.lookup(fixed), Some(mac));
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Network diagnostics.
+//!
+//! `ping` and `traceroute`, both built on ICMP echo. The traceroute sends echo requests with
+//! increasing TTL and reports the routers that answer with a time exceeded error.
+
+use super::{icmp, net_stack, Ipv4Address, TxError};
+use crate::{cpu, info, jobs, synchronization::interface::Mutex, time};
+use core::{
+    sync::atomic::{AtomicU16, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of data bytes in each echo request.
+const PING_DATA_LEN: usize = 56;
+
+/// Delay between two pings.
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait for the response to a single echo request.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Identifier of the next ping or traceroute run, so concurrent runs do not steal each other's
+/// responses.
+static NEXT_IDENT: AtomicU16 = AtomicU16::new(1);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn spin_for(duration: Duration) {
+    let start = time::time_manager().uptime();
+
+    while time::time_manager().uptime() - start < duration {
+        net_stack().poll();
+        cpu::nop();
+    }
+}
+
+/// Send one echo request and wait for the response.
+///
+/// Returns `Ok(None)` if nothing arrived within `PROBE_TIMEOUT`, including when the next hop's
+/// address could not be resolved.
+fn probe(
+    dst: Ipv4Address,
+    ident: u16,
+    seq: u16,
+    ttl: u8,
+) -> Result<Option<icmp::Response>, &'static str> {
+    let stack = net_stack();
+    let start = time::time_manager().uptime();
+    let timed_out = || time::time_manager().uptime() - start >= PROBE_TIMEOUT;
+
+    let mut data = [0_u8; PING_DATA_LEN];
+    for (i, byte) in data.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+
+    loop {
+        match stack
+            .inner
+            .lock(|inner| inner.send_icmp_echo(dst, ident, seq, ttl, &data))
+        {
+            Ok(()) => break,
+            Err(TxError::Failed(x)) => return Err(x),
+            Err(TxError::Unresolved) => {
+                if timed_out() {
+                    stack.inner.lock(|inner| inner.drop_unresolved(dst));
+                    return Ok(None);
+                }
+
+                stack.poll();
+                cpu::nop();
+            }
+        }
+    }
+
+    loop {
+        stack.poll();
+
+        if let Some(response) = stack.inner.lock(|inner| inner.echo.take(ident, seq)) {
+            return Ok(Some(response));
+        }
+
+        if timed_out() {
+            stack.inner.lock(|inner| inner.echo.cancel(ident, seq));
+            return Ok(None);
+        }
+
+        cpu::nop();
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Send `count` echo requests to `dst` and print the responses and round-trip statistics.
+pub fn ping(dst: Ipv4Address, count: u16) -> Result<(), &'static str> {
+    let ident = NEXT_IDENT.fetch_add(1, Ordering::Relaxed);
+    let mut sent: u16 = 0;
+    let mut received: u16 = 0;
+    let mut rtt_min = Duration::MAX;
+    let mut rtt_max = Duration::ZERO;
+    let mut rtt_sum = Duration::ZERO;
+
+    info!("PING {}: {} data bytes", dst, PING_DATA_LEN);
+
+    for seq in 0..count {
+        if jobs::cancelled() {
+            break;
+        }
+        if seq != 0 {
+            spin_for(PING_INTERVAL);
+        }
+
+        let response = probe(dst, ident, seq, super::ipv4::DEFAULT_TTL)?;
+        sent += 1;
+
+        match response {
+            None => info!("Request timeout for icmp_seq={}", seq),
+            Some(r) => match r.kind {
+                icmp::ResponseKind::EchoReply => {
+                    received += 1;
+                    rtt_min = rtt_min.min(r.rtt);
+                    rtt_max = rtt_max.max(r.rtt);
+                    rtt_sum += r.rtt;
+
+                    info!(
+                        "{} bytes from {}: icmp_seq={} ttl={} time={}",
+                        PING_DATA_LEN + 8,
+                        r.from,
+                        seq,
+                        r.ttl,
+                        time::Human(r.rtt)
+                    );
+                }
+                icmp::ResponseKind::TimeExceeded => {
+                    info!("From {}: icmp_seq={} Time to live exceeded", r.from, seq)
+                }
+                icmp::ResponseKind::Unreachable(code) => info!(
+                    "From {}: icmp_seq={} Destination unreachable (code {})",
+                    r.from, seq, code
+                ),
+            },
+        }
+    }
+
+    info!("--- {} ping statistics ---", dst);
+    info!(
+        "{} packets transmitted, {} received, {}% packet loss",
+        sent,
+        received,
+        if sent == 0 {
+            0
+        } else {
+            (sent - received) as u32 * 100 / sent as u32
+        }
+    );
+    if received != 0 {
+        info!(
+            "rtt min/avg/max = {} / {} / {}",
+            time::Human(rtt_min),
+            time::Human(rtt_sum / received as u32),
+            time::Human(rtt_max)
+        );
+    }
+
+    Ok(())
+}
+
+/// Print the route to `dst`, probing at most `max_hops` hops.
+pub fn traceroute(dst: Ipv4Address, max_hops: u8) -> Result<(), &'static str> {
+    let ident = NEXT_IDENT.fetch_add(1, Ordering::Relaxed);
+
+    info!("traceroute to {}, {} hops max", dst, max_hops);
+
+    for ttl in 1..=max_hops {
+        if jobs::cancelled() {
+            break;
+        }
+        match probe(dst, ident, ttl as u16, ttl)? {
+            None => info!("{:>3}  *", ttl),
+            Some(r) => {
+                info!("{:>3}  {}  {}", ttl, r.from, time::Human(r.rtt));
+
+                if r.kind != icmp::ResponseKind::TimeExceeded {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
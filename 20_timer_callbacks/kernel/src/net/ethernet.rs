@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Ethernet II framing.
+
+use super::MacAddress;
+use alloc::vec::Vec;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Length of the Ethernet II header.
+pub const HEADER_LEN: usize = 14;
+
+/// Minimum frame length without FCS. Shorter frames are padded.
+const MIN_FRAME_LEN: usize = 60;
+
+/// EtherType of IPv4.
+pub const ETHERTYPE_IPV4: u16 = 0x0800;
+
+/// EtherType of ARP.
+pub const ETHERTYPE_ARP: u16 = 0x0806;
+
+/// A parsed Ethernet II frame.
+pub struct Frame<'a> {
+    pub dst: MacAddress,
+    pub src: MacAddress,
+    pub ethertype: u16,
+    pub payload: &'a [u8],
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl<'a> Frame<'a> {
+    /// Parse a raw frame.
+    pub fn parse(buf: &'a [u8]) -> Option<Self> {
+        if buf.len() < HEADER_LEN {
+            return None;
+        }
+
+        Some(Self {
+            dst: MacAddress::from_slice(&buf[0..6]),
+            src: MacAddress::from_slice(&buf[6..12]),
+            ethertype: u16::from_be_bytes([buf[12], buf[13]]),
+            payload: &buf[HEADER_LEN..],
+        })
+    }
+}
+
+/// Build a raw frame around `payload`.
+pub fn build(dst: MacAddress, src: MacAddress, ethertype: u16, payload: &[u8]) -> Vec<u8> {
+    let len = (HEADER_LEN + payload.len()).max(MIN_FRAME_LEN);
+    let mut frame = Vec::with_capacity(len);
+
+    frame.extend_from_slice(&dst.0);
+    frame.extend_from_slice(&src.0);
+    frame.extend_from_slice(&ethertype.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame.resize(len, 0);
+
+    frame
+}
@@ -0,0 +1,289 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! ICMP messages and echo bookkeeping.
+//!
+//! Only the messages needed for `ping` and `traceroute` are understood: echo request and reply,
+//! plus time exceeded and destination unreachable errors that refer to one of our echo requests.
+
+use super::{ipv4, Ipv4Address};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const HEADER_LEN: usize = 8;
+const TYPE_ECHO_REPLY: u8 = 0;
+const TYPE_DEST_UNREACHABLE: u8 = 3;
+const TYPE_ECHO_REQUEST: u8 = 8;
+const TYPE_TIME_EXCEEDED: u8 = 11;
+
+/// Maximum number of outstanding echo requests, and of responses nobody collected yet.
+const TRACKER_CAPACITY: usize = 16;
+
+struct Outstanding {
+    ident: u16,
+    seq: u16,
+    sent: Duration,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A parsed ICMP message.
+pub enum Message<'a> {
+    EchoRequest {
+        ident: u16,
+        seq: u16,
+        data: &'a [u8],
+    },
+    EchoReply {
+        ident: u16,
+        seq: u16,
+    },
+
+    /// A router dropped one of our echo requests because its TTL ran out.
+    TimeExceeded {
+        ident: u16,
+        seq: u16,
+    },
+
+    /// One of our echo requests could not be delivered.
+    Unreachable {
+        ident: u16,
+        seq: u16,
+        code: u8,
+    },
+}
+
+/// Kinds of responses to an echo request.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ResponseKind {
+    EchoReply,
+    TimeExceeded,
+
+    /// Destination unreachable, with the ICMP code.
+    Unreachable(u8),
+}
+
+/// The response to an echo request.
+#[derive(Copy, Clone)]
+pub struct Response {
+    pub kind: ResponseKind,
+
+    /// The host that sent the response.
+    pub from: Ipv4Address,
+
+    /// TTL of the packet carrying the response.
+    pub ttl: u8,
+
+    /// Round-trip time.
+    pub rtt: Duration,
+}
+
+/// Matches responses to the echo requests that were sent.
+pub struct EchoTracker {
+    outstanding: Vec<Outstanding>,
+    responses: Vec<(u16, u16, Response)>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn build_echo(icmp_type: u8, ident: u16, seq: u16, data: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(HEADER_LEN + data.len());
+
+    message.push(icmp_type);
+    message.push(0);
+    message.extend_from_slice(&[0, 0]);
+    message.extend_from_slice(&ident.to_be_bytes());
+    message.extend_from_slice(&seq.to_be_bytes());
+    message.extend_from_slice(data);
+
+    let csum = ipv4::checksum(0, &message);
+    message[2..4].copy_from_slice(&csum.to_be_bytes());
+
+    message
+}
+
+/// Extract identifier and sequence number of our echo request from the payload of an error
+/// message, which carries the offending IP header plus the first 8 bytes of its payload.
+fn parse_quoted_echo(buf: &[u8]) -> Option<(u16, u16)> {
+    if buf.len() < ipv4::HEADER_LEN {
+        return None;
+    }
+
+    let header_len = ((buf[0] & 0x0f) as usize) * 4;
+    if buf[9] != ipv4::PROTOCOL_ICMP || buf.len() < header_len + HEADER_LEN {
+        return None;
+    }
+
+    let quoted = &buf[header_len..];
+    if quoted[0] != TYPE_ECHO_REQUEST {
+        return None;
+    }
+
+    Some((
+        u16::from_be_bytes([quoted[4], quoted[5]]),
+        u16::from_be_bytes([quoted[6], quoted[7]]),
+    ))
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl<'a> Message<'a> {
+    /// Parse and validate a raw message.
+    pub fn parse(buf: &'a [u8]) -> Option<Self> {
+        if buf.len() < HEADER_LEN || ipv4::checksum(0, buf) != 0 {
+            return None;
+        }
+
+        let word = |i: usize| u16::from_be_bytes([buf[i], buf[i + 1]]);
+
+        match buf[0] {
+            TYPE_ECHO_REQUEST => Some(Self::EchoRequest {
+                ident: word(4),
+                seq: word(6),
+                data: &buf[HEADER_LEN..],
+            }),
+            TYPE_ECHO_REPLY => Some(Self::EchoReply {
+                ident: word(4),
+                seq: word(6),
+            }),
+            TYPE_TIME_EXCEEDED => parse_quoted_echo(&buf[HEADER_LEN..])
+                .map(|(ident, seq)| Self::TimeExceeded { ident, seq }),
+            TYPE_DEST_UNREACHABLE => {
+                parse_quoted_echo(&buf[HEADER_LEN..]).map(|(ident, seq)| Self::Unreachable {
+                    ident,
+                    seq,
+                    code: buf[1],
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Build a raw echo request.
+pub fn build_echo_request(ident: u16, seq: u16, data: &[u8]) -> Vec<u8> {
+    build_echo(TYPE_ECHO_REQUEST, ident, seq, data)
+}
+
+/// Build a raw echo reply.
+pub fn build_echo_reply(ident: u16, seq: u16, data: &[u8]) -> Vec<u8> {
+    build_echo(TYPE_ECHO_REPLY, ident, seq, data)
+}
+
+impl EchoTracker {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            outstanding: Vec::new(),
+            responses: Vec::new(),
+        }
+    }
+
+    /// Note that an echo request was sent at time `now`.
+    pub fn sent(&mut self, ident: u16, seq: u16, now: Duration) {
+        if self.outstanding.len() == TRACKER_CAPACITY {
+            self.outstanding.remove(0);
+        }
+
+        self.outstanding.push(Outstanding {
+            ident,
+            seq,
+            sent: now,
+        });
+    }
+
+    /// Record a response that arrived at time `now`. Responses to unknown requests are ignored.
+    pub fn received(
+        &mut self,
+        ident: u16,
+        seq: u16,
+        kind: ResponseKind,
+        from: Ipv4Address,
+        ttl: u8,
+        now: Duration,
+    ) {
+        let pos = match self
+            .outstanding
+            .iter()
+            .position(|o| o.ident == ident && o.seq == seq)
+        {
+            None => return,
+            Some(p) => p,
+        };
+        let request = self.outstanding.remove(pos);
+
+        if self.responses.len() == TRACKER_CAPACITY {
+            self.responses.remove(0);
+        }
+
+        self.responses.push((
+            ident,
+            seq,
+            Response {
+                kind,
+                from,
+                ttl,
+                rtt: now.saturating_sub(request.sent),
+            },
+        ));
+    }
+
+    /// Collect the response to an echo request, if it arrived.
+    pub fn take(&mut self, ident: u16, seq: u16) -> Option<Response> {
+        let pos = self
+            .responses
+            .iter()
+            .position(|(i, s, _)| *i == ident && *s == seq)?;
+
+        Some(self.responses.remove(pos).2)
+    }
+
+    /// Forget an echo request that timed out.
+    pub fn cancel(&mut self, ident: u16, seq: u16) {
+        self.outstanding
+            .retain(|o| !(o.ident == ident && o.seq == seq));
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A time exceeded error must be matched back to the echo request it quotes.
+    #[kernel_test]
+    fn time_exceeded_quotes_request() {
+        let src = Ipv4Address::new(10, 0, 0, 1);
+        let dst = Ipv4Address::new(10, 0, 0, 2);
+        let request = build_echo_request(0x1234, 7, &[0; 32]);
+        let packet = ipv4::build(src, dst, ipv4::PROTOCOL_ICMP, 1, 1, &request);
+
+        let mut error = Vec::from([TYPE_TIME_EXCEEDED, 0, 0, 0, 0, 0, 0, 0]);
+        error.extend_from_slice(&packet[..ipv4::HEADER_LEN + HEADER_LEN]);
+        let csum = ipv4::checksum(0, &error);
+        error[2..4].copy_from_slice(&csum.to_be_bytes());
+
+        match Message::parse(&error) {
+            Some(Message::TimeExceeded { ident, seq }) => {
+                assert_eq!(ident, 0x1234);
+                assert_eq!(seq, 7);
+            }
+            _ => panic!("not parsed as time exceeded"),
+        }
+    }
+}
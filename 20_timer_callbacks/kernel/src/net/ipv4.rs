@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! IPv4 packets.
+//!
+//! Options and fragmentation are not supported. Fragmented packets are dropped on receive.
+
+use super::Ipv4Address;
+use alloc::vec::Vec;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Length of an IPv4 header without options.
+pub const HEADER_LEN: usize = 20;
+
+/// Default time-to-live of outgoing packets.
+pub const DEFAULT_TTL: u8 = 64;
+
+/// Protocol number of ICMP.
+pub const PROTOCOL_ICMP: u8 = 1;
+
+/// Protocol number of UDP.
+pub const PROTOCOL_UDP: u8 = 17;
+
+/// A parsed IPv4 packet.
+pub struct Packet<'a> {
+    pub src: Ipv4Address,
+    pub dst: Ipv4Address,
+    pub protocol: u8,
+    pub ttl: u8,
+    pub payload: &'a [u8],
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Compute the internet checksum (RFC 1071) over `data`, starting from the partial sum `initial`.
+pub fn checksum(initial: u32, data: &[u8]) -> u16 {
+    let mut sum = initial;
+
+    for chunk in data.chunks(2) {
+        let word = match *chunk {
+            [hi, lo] => u16::from_be_bytes([hi, lo]),
+            [hi] => u16::from_be_bytes([hi, 0]),
+            _ => unreachable!(),
+        };
+        sum += word as u32;
+    }
+
+    while (sum >> 16) != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+
+    !(sum as u16)
+}
+
+impl<'a> Packet<'a> {
+    /// Parse and validate a raw packet.
+    pub fn parse(buf: &'a [u8]) -> Option<Self> {
+        if buf.len() < HEADER_LEN || (buf[0] >> 4) != 4 {
+            return None;
+        }
+
+        let header_len = ((buf[0] & 0x0f) as usize) * 4;
+        let total_len = u16::from_be_bytes([buf[2], buf[3]]) as usize;
+        if header_len < HEADER_LEN || total_len < header_len || total_len > buf.len() {
+            return None;
+        }
+
+        if checksum(0, &buf[..header_len]) != 0 {
+            return None;
+        }
+
+        // Drop fragments: MF flag set or non-zero fragment offset.
+        let flags_fragment = u16::from_be_bytes([buf[6], buf[7]]);
+        if (flags_fragment & 0x3fff) != 0 {
+            return None;
+        }
+
+        Some(Self {
+            src: Ipv4Address::from_slice(&buf[12..16]),
+            dst: Ipv4Address::from_slice(&buf[16..20]),
+            protocol: buf[9],
+            ttl: buf[8],
+            payload: &buf[header_len..total_len],
+        })
+    }
+}
+
+/// Build a raw packet around `payload`.
+pub fn build(
+    src: Ipv4Address,
+    dst: Ipv4Address,
+    protocol: u8,
+    ttl: u8,
+    id: u16,
+    payload: &[u8],
+) -> Vec<u8> {
+    let total_len = (HEADER_LEN + payload.len()) as u16;
+    let mut packet = Vec::with_capacity(total_len as usize);
+
+    packet.push(0x45); // Version 4, IHL 5.
+    packet.push(0);
+    packet.extend_from_slice(&total_len.to_be_bytes());
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&0x4000_u16.to_be_bytes()); // Don't fragment.
+    packet.push(ttl);
+    packet.push(protocol);
+    packet.extend_from_slice(&[0, 0]);
+    packet.extend_from_slice(&src.0);
+    packet.extend_from_slice(&dst.0);
+
+    let csum = checksum(0, &packet[..HEADER_LEN]);
+    packet[10..12].copy_from_slice(&csum.to_be_bytes());
+
+    packet.extend_from_slice(payload);
+
+    packet
+}
+
+/// Partial checksum of the pseudo header used by UDP and TCP.
+pub fn pseudo_header_sum(src: Ipv4Address, dst: Ipv4Address, protocol: u8, len: u16) -> u32 {
+    let words = [
+        u16::from_be_bytes([src.0[0], src.0[1]]),
+        u16::from_be_bytes([src.0[2], src.0[3]]),
+        u16::from_be_bytes([dst.0[0], dst.0[1]]),
+        u16::from_be_bytes([dst.0[2], dst.0[3]]),
+        protocol as u16,
+        len,
+    ];
+
+    words.iter().map(|&w| w as u32).sum()
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A built packet must parse back with a valid header checksum.
+    #[kernel_test]
+    fn build_parse_roundtrip() {
+        let src = Ipv4Address::new(10, 0, 0, 1);
+        let dst = Ipv4Address::new(10, 0, 0, 2);
+        let raw = build(src, dst, PROTOCOL_UDP, DEFAULT_TTL, 7, &[1, 2, 3]);
+
+        let packet = Packet::parse(&raw).unwrap();
+        assert_eq!(packet.src, src);
+        assert_eq!(packet.dst, dst);
+        assert_eq!(packet.protocol, PROTOCOL_UDP);
+        assert_eq!(packet.payload, &[1, 2, 3]);
+    }
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! The loopback network device.
+
+use super::{interface, MacAddress};
+use crate::synchronization::{interface::Mutex, IRQSafeNullLock};
+use alloc::vec::Vec;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Frames beyond this many are dropped until the stack polls the device.
+const QUEUE_CAPACITY: usize = 32;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A device that receives every frame it transmits.
+pub struct Loopback {
+    queue: IRQSafeNullLock<Vec<Vec<u8>>>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+pub static LOOPBACK: Loopback = Loopback::new();
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Loopback {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            queue: IRQSafeNullLock::new(Vec::new()),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+
+impl interface::NetDevice for Loopback {
+    fn name(&self) -> &'static str {
+        "lo"
+    }
+
+    fn mac_address(&self) -> MacAddress {
+        MacAddress::ZERO
+    }
+
+    fn transmit(&self, frame: &[u8]) -> Result<(), &'static str> {
+        self.queue.lock(|queue| {
+            if queue.len() == QUEUE_CAPACITY {
+                return Err("Loopback queue full");
+            }
+
+            queue.push(frame.to_vec());
+            Ok(())
+        })
+    }
+
+    fn receive(&self) -> Option<Vec<u8>> {
+        self.queue.lock(|queue| {
+            if queue.is_empty() {
+                None
+            } else {
+                Some(queue.remove(0))
+            }
+        })
+    }
+
+    fn is_loopback(&self) -> bool {
+        true
+    }
+}
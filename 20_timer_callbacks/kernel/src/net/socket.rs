@@ -0,0 +1,377 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Socket API.
+//!
+//! A BSD-flavoured interface to the transport protocols. Sockets are referred to by a
+//! [`SocketHandle`], which wraps a small integer so that it can cross the syscall boundary as-is.
+//!
+//! The functions poll the network stack while they wait, so they work from any context that may
+//! spin. A task that waits yields to the other tasks between polls. Waiting functions take an
+//! optional timeout.
+//!
+//! A socket opened for a program, see [`crate::syscall`], belongs to the program's task. Only that
+//! task may use it through syscalls, and it is closed when the task ends.
+//!
+//! Received datagrams are queued within the `net` memory quota. When it is used up, further
+//! datagrams are dropped like those for a full queue.
+
+use super::{net_stack, udp, SocketAddr, TxError, MTU, NET_QUOTA};
+use crate::{
+    cpu, memory::heap_alloc::quota::Quota, rand, sched, synchronization::interface::Mutex, time,
+};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Maximum number of simultaneously open sockets.
+const MAX_SOCKETS: usize = 16;
+
+/// Datagrams beyond this many are dropped until the application reads from the socket.
+const RX_QUEUE_CAPACITY: usize = 8;
+
+/// Range of ports that are assigned to sockets which send without binding first.
+const EPHEMERAL_PORTS: core::ops::RangeInclusive<u16> = 49152..=65535;
+
+/// How long `send_to()` waits for the next hop's address to be resolved.
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A received datagram's payload.
+type Payload = Vec<u8, &'static Quota>;
+
+struct Socket {
+    local_port: Option<u16>,
+    rx_queue: Vec<(SocketAddr, Payload)>,
+
+    /// Task of the program the socket was opened for, if any.
+    owner: Option<usize>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Largest UDP payload that fits into the MTU, and so the largest datagram [`send_to()`] accepts.
+pub const MAX_UDP_PAYLOAD: usize = MTU - super::ipv4::HEADER_LEN - udp::HEADER_LEN;
+
+/// Kinds of sockets.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum SocketType {
+    /// Connectionless, unreliable datagrams (UDP).
+    Datagram,
+
+    /// Connection-oriented byte streams (TCP).
+    Stream,
+}
+
+/// Reference to an open socket.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SocketHandle(usize);
+
+/// The table of open sockets.
+pub(super) struct SocketTable {
+    slots: Vec<Option<Socket>>,
+    /// Chosen at random on first use, so that port numbers differ across boots.
+    next_ephemeral: Option<u16>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn open(kind: SocketType, owner: Option<usize>) -> Result<SocketHandle, &'static str> {
+    if kind == SocketType::Stream {
+        return Err("Stream sockets are not supported yet");
+    }
+
+    net_stack().inner.lock(|inner| inner.sockets.open(owner))
+}
+
+/// Pause between two polls. Lets the other tasks run, which the cooperative scheduler needs to make
+/// progress while a task waits.
+fn pause() {
+    sched::yield_now();
+    cpu::nop();
+}
+
+impl SocketTable {
+    pub const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            next_ephemeral: None,
+        }
+    }
+
+    fn open(&mut self, owner: Option<usize>) -> Result<SocketHandle, &'static str> {
+        let socket = Socket {
+            local_port: None,
+            rx_queue: Vec::new(),
+            owner,
+        };
+
+        if let Some(idx) = self.slots.iter().position(|s| s.is_none()) {
+            self.slots[idx] = Some(socket);
+            return Ok(SocketHandle(idx));
+        }
+
+        if self.slots.len() == MAX_SOCKETS {
+            return Err("Too many open sockets");
+        }
+
+        self.slots.push(Some(socket));
+        Ok(SocketHandle(self.slots.len() - 1))
+    }
+
+    fn get_mut(&mut self, handle: SocketHandle) -> Result<&mut Socket, &'static str> {
+        self.slots
+            .get_mut(handle.0)
+            .and_then(|s| s.as_mut())
+            .ok_or("Invalid socket handle")
+    }
+
+    fn port_in_use(&self, port: u16) -> bool {
+        self.slots
+            .iter()
+            .flatten()
+            .any(|s| s.local_port == Some(port))
+    }
+
+    fn bind(&mut self, handle: SocketHandle, port: u16) -> Result<(), &'static str> {
+        if self.get_mut(handle)?.local_port.is_some() {
+            return Err("Socket already bound");
+        }
+
+        if port == 0 {
+            return self.bind_ephemeral(handle).map(|_| ());
+        }
+
+        if self.port_in_use(port) {
+            return Err("Port already in use");
+        }
+
+        self.get_mut(handle)?.local_port = Some(port);
+        Ok(())
+    }
+
+    fn bind_ephemeral(&mut self, handle: SocketHandle) -> Result<u16, &'static str> {
+        let num_ephemeral = EPHEMERAL_PORTS.len();
+
+        for _ in 0..num_ephemeral {
+            let port = self.next_ephemeral.unwrap_or_else(|| {
+                EPHEMERAL_PORTS.start() + (rand::next_u64() % num_ephemeral as u64) as u16
+            });
+            self.next_ephemeral = Some(if port == *EPHEMERAL_PORTS.end() {
+                *EPHEMERAL_PORTS.start()
+            } else {
+                port + 1
+            });
+
+            if !self.port_in_use(port) {
+                self.get_mut(handle)?.local_port = Some(port);
+                return Ok(port);
+            }
+        }
+
+        Err("No free ephemeral port")
+    }
+
+    /// Return the local port of the socket, binding to an ephemeral port first if needed.
+    fn ensure_bound(&mut self, handle: SocketHandle) -> Result<u16, &'static str> {
+        match self.get_mut(handle)?.local_port {
+            Some(port) => Ok(port),
+            None => self.bind_ephemeral(handle),
+        }
+    }
+
+    fn close(&mut self, handle: SocketHandle) -> Result<(), &'static str> {
+        self.get_mut(handle)?;
+        self.slots[handle.0] = None;
+
+        Ok(())
+    }
+
+    fn close_owned(&mut self, task: usize) {
+        for slot in self.slots.iter_mut() {
+            if slot.as_ref().map_or(false, |s| s.owner == Some(task)) {
+                *slot = None;
+            }
+        }
+    }
+
+    fn dequeue(
+        &mut self,
+        handle: SocketHandle,
+    ) -> Result<Option<(SocketAddr, Payload)>, &'static str> {
+        let socket = self.get_mut(handle)?;
+        if socket.local_port.is_none() {
+            return Err("Socket not bound");
+        }
+
+        if socket.rx_queue.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(socket.rx_queue.remove(0)))
+    }
+
+    /// Hand a received datagram to the socket bound to `port`.
+    ///
+    /// Returns false if no socket is bound to the port, its queue is full or the `net` quota is
+    /// used up.
+    pub fn deliver(&mut self, port: u16, from: SocketAddr, payload: &[u8]) -> bool {
+        let socket = match self
+            .slots
+            .iter_mut()
+            .flatten()
+            .find(|s| s.local_port == Some(port))
+        {
+            None => return false,
+            Some(s) => s,
+        };
+
+        if socket.rx_queue.len() == RX_QUEUE_CAPACITY {
+            return false;
+        }
+
+        let mut data = match NET_QUOTA.try_vec(payload.len()) {
+            Ok(data) => data,
+            Err(_) => return false,
+        };
+        data.extend_from_slice(payload);
+
+        socket.rx_queue.push((from, data));
+        true
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl SocketHandle {
+    /// Create a handle from its raw representation, e.g. a syscall argument.
+    pub const fn from_raw(raw: usize) -> Self {
+        Self(raw)
+    }
+
+    /// Return the raw representation of the handle.
+    pub const fn into_raw(self) -> usize {
+        self.0
+    }
+}
+
+/// Open a new socket.
+pub fn socket(kind: SocketType) -> Result<SocketHandle, &'static str> {
+    open(kind, None)
+}
+
+/// Open a new socket for the program that runs in task `task`.
+pub fn socket_for(kind: SocketType, task: usize) -> Result<SocketHandle, &'static str> {
+    open(kind, Some(task))
+}
+
+/// Fail unless the socket was opened for the program that runs in task `task`.
+pub fn check_owner(handle: SocketHandle, task: usize) -> Result<(), &'static str> {
+    net_stack().inner.lock(|inner| {
+        if inner.sockets.get_mut(handle)?.owner != Some(task) {
+            return Err("Invalid socket handle");
+        }
+
+        Ok(())
+    })
+}
+
+/// Bind a socket to a local port. Port 0 selects a free ephemeral port.
+pub fn bind(handle: SocketHandle, port: u16) -> Result<(), &'static str> {
+    net_stack()
+        .inner
+        .lock(|inner| inner.sockets.bind(handle, port))
+}
+
+/// Return the local port the socket is bound to, if any.
+pub fn local_port(handle: SocketHandle) -> Result<Option<u16>, &'static str> {
+    net_stack()
+        .inner
+        .lock(|inner| inner.sockets.get_mut(handle).map(|s| s.local_port))
+}
+
+/// Send a datagram to `dst`.
+///
+/// Unbound sockets are bound to an ephemeral port first. Blocks while the next hop's address is
+/// being resolved.
+pub fn send_to(handle: SocketHandle, buf: &[u8], dst: SocketAddr) -> Result<usize, &'static str> {
+    if buf.len() > MAX_UDP_PAYLOAD {
+        return Err("Datagram too large");
+    }
+
+    let stack = net_stack();
+    let src_port = stack
+        .inner
+        .lock(|inner| inner.sockets.ensure_bound(handle))?;
+    let start = time::time_manager().uptime();
+
+    loop {
+        match stack.inner.lock(|inner| inner.send_udp(src_port, dst, buf)) {
+            Ok(()) => return Ok(buf.len()),
+            Err(TxError::Failed(x)) => return Err(x),
+            Err(TxError::Unresolved) => {
+                if time::time_manager().uptime() - start >= RESOLVE_TIMEOUT {
+                    stack.inner.lock(|inner| inner.drop_unresolved(dst.addr));
+                    return Err("Destination unreachable");
+                }
+
+                stack.poll();
+                pause();
+            }
+        }
+    }
+}
+
+/// Receive a datagram.
+///
+/// Copies as much of the datagram into `buf` as fits and returns the copied length and the
+/// sender's address. Returns `Ok(None)` if nothing arrived within `timeout`. A timeout of `None`
+/// waits forever.
+pub fn recv_from(
+    handle: SocketHandle,
+    buf: &mut [u8],
+    timeout: Option<Duration>,
+) -> Result<Option<(usize, SocketAddr)>, &'static str> {
+    let stack = net_stack();
+    let start = time::time_manager().uptime();
+
+    loop {
+        stack.poll();
+
+        if let Some((from, data)) = stack.inner.lock(|inner| inner.sockets.dequeue(handle))? {
+            let len = data.len().min(buf.len());
+            buf[..len].copy_from_slice(&data[..len]);
+
+            return Ok(Some((len, from)));
+        }
+
+        if let Some(timeout) = timeout {
+            if time::time_manager().uptime() - start >= timeout {
+                return Ok(None);
+            }
+        }
+
+        pause();
+    }
+}
+
+/// Close a socket. Queued datagrams are discarded.
+pub fn close(handle: SocketHandle) -> Result<(), &'static str> {
+    net_stack().inner.lock(|inner| inner.sockets.close(handle))
+}
+
+/// Close the sockets of the program that ran in task `task`.
+pub fn close_owned(task: usize) {
+    net_stack()
+        .inner
+        .lock(|inner| inner.sockets.close_owned(task))
+}
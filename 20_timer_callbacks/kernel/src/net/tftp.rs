@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! TFTP client.
+//!
+//! Reads a file from a TFTP server in octet mode with the standard 512 byte blocks. A lost request,
+//! block or acknowledgement is recovered by sending the last packet again after a timeout. The
+//! transfer is abandoned after a few timeouts in a row.
+//!
+//! # Resources
+//!
+//! - RFC 1350, The TFTP Protocol (Revision 2)
+
+use super::{
+    socket::{self, SocketHandle, SocketType},
+    Ipv4Address, SocketAddr,
+};
+use crate::{info, jobs};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The server's well-known port. It answers from another port, the transfer ID.
+const SERVER_PORT: u16 = 69;
+
+/// Payload of a full data block. A shorter block ends the transfer.
+const BLOCK_SIZE: usize = 512;
+
+/// Opcode plus block number or error code.
+const HEADER_LEN: usize = 4;
+
+/// How long to wait for the next block before sending the last packet again.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Timeouts in a row after which the transfer is abandoned.
+const MAX_RETRIES: usize = 5;
+
+// Opcodes.
+const OP_RRQ: u16 = 1;
+const OP_DATA: u16 = 3;
+const OP_ACK: u16 = 4;
+const OP_ERROR: u16 = 5;
+
+/// Error code telling the server that the file doesn't fit.
+const ERR_DISK_FULL: u16 = 3;
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Encode a read request for `filename`.
+fn read_request(filename: &str) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(2 + filename.len() + 7);
+    packet.extend_from_slice(&OP_RRQ.to_be_bytes());
+    packet.extend_from_slice(filename.as_bytes());
+    packet.push(0);
+    packet.extend_from_slice(b"octet");
+    packet.push(0);
+
+    packet
+}
+
+/// Encode the acknowledgement of `block`.
+fn ack(block: u16) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(HEADER_LEN);
+    packet.extend_from_slice(&OP_ACK.to_be_bytes());
+    packet.extend_from_slice(&block.to_be_bytes());
+
+    packet
+}
+
+/// Tell the server the transfer is abandoned. Best effort, the server times out otherwise.
+fn send_error(sock: SocketHandle, dst: SocketAddr, code: u16, message: &str) {
+    let mut packet = Vec::with_capacity(HEADER_LEN + message.len() + 1);
+    packet.extend_from_slice(&OP_ERROR.to_be_bytes());
+    packet.extend_from_slice(&code.to_be_bytes());
+    packet.extend_from_slice(message.as_bytes());
+    packet.push(0);
+
+    let _ = socket::send_to(sock, &packet, dst);
+}
+
+fn transfer(
+    sock: SocketHandle,
+    server: Ipv4Address,
+    filename: &str,
+    buf: &mut [u8],
+) -> Result<usize, &'static str> {
+    let mut dst = SocketAddr {
+        addr: server,
+        port: SERVER_PORT,
+    };
+    // The server's port for this transfer, known with the first block.
+    let mut tid = None;
+    let mut last_sent = read_request(filename);
+    let mut next_block: u16 = 1;
+    let mut len = 0;
+    let mut retries = 0;
+    let mut rx = [0; HEADER_LEN + BLOCK_SIZE];
+
+    socket::send_to(sock, &last_sent, dst)?;
+
+    loop {
+        if jobs::cancelled() {
+            if tid.is_some() {
+                send_error(sock, dst, 0, "Cancelled");
+            }
+            return Err("Transfer cancelled");
+        }
+
+        let (n, from) = match socket::recv_from(sock, &mut rx, Some(TIMEOUT))? {
+            Some(received) => received,
+            None => {
+                retries += 1;
+                if retries > MAX_RETRIES {
+                    return Err("TFTP server not responding");
+                }
+                socket::send_to(sock, &last_sent, dst)?;
+                continue;
+            }
+        };
+
+        // Ignore anything that doesn't belong to this transfer.
+        if n < HEADER_LEN || from.addr != server || tid.map_or(false, |port| port != from.port) {
+            continue;
+        }
+
+        let opcode = u16::from_be_bytes([rx[0], rx[1]]);
+        let block = u16::from_be_bytes([rx[2], rx[3]]);
+        match opcode {
+            OP_DATA => (),
+            OP_ERROR => {
+                let message = &rx[HEADER_LEN..n];
+                let end = message
+                    .iter()
+                    .position(|b| *b == 0)
+                    .unwrap_or(message.len());
+                info!(
+                    "TFTP error {}: {}",
+                    block,
+                    core::str::from_utf8(&message[..end]).unwrap_or("?")
+                );
+                return Err("TFTP server reported an error");
+            }
+            _ => continue,
+        }
+
+        tid = Some(from.port);
+        dst.port = from.port;
+
+        if block == next_block {
+            let data = &rx[HEADER_LEN..n];
+            match buf.get_mut(len..len + data.len()) {
+                Some(slot) => slot.copy_from_slice(data),
+                None => {
+                    send_error(sock, from, ERR_DISK_FULL, "File too large");
+                    return Err("File too large");
+                }
+            }
+            len += data.len();
+            next_block = next_block.wrapping_add(1);
+            retries = 0;
+
+            last_sent = ack(block);
+            socket::send_to(sock, &last_sent, dst)?;
+            if data.len() < BLOCK_SIZE {
+                return Ok(len);
+            }
+        } else if block == next_block.wrapping_sub(1) {
+            // Our acknowledgement got lost and the server sent the block again.
+            socket::send_to(sock, &last_sent, dst)?;
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Read `filename` from the TFTP server at `server` into `buf`. Returns the file's length.
+///
+/// Fails if the file doesn't fit into `buf`. Polls [`jobs::cancelled()`], so a background transfer
+/// can be killed.
+pub fn fetch(server: Ipv4Address, filename: &str, buf: &mut [u8]) -> Result<usize, &'static str> {
+    let sock = socket::socket(SocketType::Datagram)?;
+    let result = transfer(sock, server, filename, buf);
+    let _ = socket::close(sock);
+
+    result
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Requests must name the file and the octet mode, each NUL terminated.
+    #[kernel_test]
+    fn packet_encoding() {
+        assert_eq!(read_request("kernel8.img"), b"\0\x01kernel8.img\0octet\0");
+        assert_eq!(ack(0x1234), [0, 4, 0x12, 0x34]);
+    }
+}
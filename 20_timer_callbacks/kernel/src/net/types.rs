@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Network address types.
+
+use alloc::format;
+use core::{fmt, str::FromStr};
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// An Ethernet MAC address.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MacAddress(pub [u8; 6]);
+
+/// An IPv4 address.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Ipv4Address(pub [u8; 4]);
+
+/// An IPv4 address plus a transport layer port.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SocketAddr {
+    /// The IPv4 address.
+    pub addr: Ipv4Address,
+
+    /// The port.
+    pub port: u16,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl MacAddress {
+    /// The broadcast address.
+    pub const BROADCAST: Self = Self([0xff; 6]);
+
+    /// The all-zero address.
+    pub const ZERO: Self = Self([0; 6]);
+
+    /// Create an instance from the first six bytes of a slice.
+    pub fn from_slice(s: &[u8]) -> Self {
+        let mut mac = [0; 6];
+        mac.copy_from_slice(&s[..6]);
+
+        Self(mac)
+    }
+}
+
+impl Ipv4Address {
+    /// The unspecified address `0.0.0.0`.
+    pub const UNSPECIFIED: Self = Self([0; 4]);
+
+    /// The limited broadcast address `255.255.255.255`.
+    pub const BROADCAST: Self = Self([0xff; 4]);
+
+    /// The loopback address `127.0.0.1`.
+    pub const LOOPBACK: Self = Self([127, 0, 0, 1]);
+
+    /// Create an instance from four octets.
+    pub const fn new(a: u8, b: u8, c: u8, d: u8) -> Self {
+        Self([a, b, c, d])
+    }
+
+    /// Create an instance from the first four bytes of a slice.
+    pub fn from_slice(s: &[u8]) -> Self {
+        let mut addr = [0; 4];
+        addr.copy_from_slice(&s[..4]);
+
+        Self(addr)
+    }
+
+    /// Return the address as a big-endian integer.
+    pub const fn to_u32(self) -> u32 {
+        u32::from_be_bytes(self.0)
+    }
+
+    /// Create an instance from a big-endian integer.
+    pub const fn from_u32(value: u32) -> Self {
+        Self(value.to_be_bytes())
+    }
+
+    /// Check if the address is in `127.0.0.0/8`.
+    pub const fn is_loopback(self) -> bool {
+        self.0[0] == 127
+    }
+
+    /// Check if the address is the limited broadcast address.
+    pub fn is_broadcast(self) -> bool {
+        self == Self::BROADCAST
+    }
+
+    /// Check if `other` is in the same subnet as `self`, given the subnet mask.
+    pub const fn same_subnet(self, other: Self, netmask: Self) -> bool {
+        (self.to_u32() & netmask.to_u32()) == (other.to_u32() & netmask.to_u32())
+    }
+}
+
+impl SocketAddr {
+    /// Create an instance.
+    pub const fn new(addr: Ipv4Address, port: u16) -> Self {
+        Self { addr, port }
+    }
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let m = &self.0;
+
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            m[0], m[1], m[2], m[3], m[4], m[5]
+        )
+    }
+}
+
+impl fmt::Display for Ipv4Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let a = &self.0;
+
+        // Go through `pad()` so that width and alignment flags are honored.
+        f.pad(&format!("{}.{}.{}.{}", a[0], a[1], a[2], a[3]))
+    }
+}
+
+impl fmt::Display for SocketAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.addr, self.port)
+    }
+}
+
+impl FromStr for Ipv4Address {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut addr = [0; 4];
+        let mut parts = s.split('.');
+
+        for octet in addr.iter_mut() {
+            *octet = parts
+                .next()
+                .ok_or("Too few octets in IPv4 address")?
+                .parse()
+                .map_err(|_| "Invalid octet in IPv4 address")?;
+        }
+
+        if parts.next().is_some() {
+            return Err("Too many octets in IPv4 address");
+        }
+
+        Ok(Self(addr))
+    }
+}
+
+impl FromStr for MacAddress {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut mac = [0; 6];
+        let mut parts = s.split(':');
+
+        for byte in mac.iter_mut() {
+            let part = parts.next().ok_or("Too few bytes in MAC address")?;
+            *byte = u8::from_str_radix(part, 16).map_err(|_| "Invalid byte in MAC address")?;
+        }
+
+        if parts.next().is_some() {
+            return Err("Too many bytes in MAC address");
+        }
+
+        Ok(Self(mac))
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Address parsing must round-trip and reject malformed input.
+    #[kernel_test]
+    fn address_parsing() {
+        let ip: Ipv4Address = "192.168.1.20".parse().unwrap();
+        assert_eq!(ip, Ipv4Address::new(192, 168, 1, 20));
+        assert!("192.168.1".parse::<Ipv4Address>().is_err());
+        assert!("192.168.1.256".parse::<Ipv4Address>().is_err());
+
+        let mac: MacAddress = "b8:27:eb:00:11:ff".parse().unwrap();
+        assert_eq!(mac, MacAddress([0xb8, 0x27, 0xeb, 0x00, 0x11, 0xff]));
+        assert!("b8:27:eb:00:11".parse::<MacAddress>().is_err());
+    }
+}
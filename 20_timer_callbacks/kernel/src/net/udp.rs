@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! UDP datagrams.
+
+use super::{ipv4, Ipv4Address};
+use alloc::vec::Vec;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Length of the UDP header.
+pub const HEADER_LEN: usize = 8;
+
+/// A parsed UDP datagram.
+pub struct Datagram<'a> {
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub payload: &'a [u8],
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl<'a> Datagram<'a> {
+    /// Parse and validate a raw datagram.
+    pub fn parse(src: Ipv4Address, dst: Ipv4Address, buf: &'a [u8]) -> Option<Self> {
+        if buf.len() < HEADER_LEN {
+            return None;
+        }
+
+        let len = u16::from_be_bytes([buf[4], buf[5]]) as usize;
+        if len < HEADER_LEN || len > buf.len() {
+            return None;
+        }
+
+        // A zero checksum means the sender did not compute one.
+        let csum = u16::from_be_bytes([buf[6], buf[7]]);
+        if csum != 0 {
+            let pseudo = ipv4::pseudo_header_sum(src, dst, ipv4::PROTOCOL_UDP, len as u16);
+            if ipv4::checksum(pseudo, &buf[..len]) != 0 {
+                return None;
+            }
+        }
+
+        Some(Self {
+            src_port: u16::from_be_bytes([buf[0], buf[1]]),
+            dst_port: u16::from_be_bytes([buf[2], buf[3]]),
+            payload: &buf[HEADER_LEN..len],
+        })
+    }
+}
+
+/// Build a raw datagram around `payload`.
+pub fn build(
+    src: Ipv4Address,
+    dst: Ipv4Address,
+    src_port: u16,
+    dst_port: u16,
+    payload: &[u8],
+) -> Vec<u8> {
+    let len = (HEADER_LEN + payload.len()) as u16;
+    let mut datagram = Vec::with_capacity(len as usize);
+
+    datagram.extend_from_slice(&src_port.to_be_bytes());
+    datagram.extend_from_slice(&dst_port.to_be_bytes());
+    datagram.extend_from_slice(&len.to_be_bytes());
+    datagram.extend_from_slice(&[0, 0]);
+    datagram.extend_from_slice(payload);
+
+    let pseudo = ipv4::pseudo_header_sum(src, dst, ipv4::PROTOCOL_UDP, len);
+    let csum = match ipv4::checksum(pseudo, &datagram) {
+        // A computed zero is transmitted as all ones (RFC 768).
+        0 => 0xffff,
+        x => x,
+    };
+    datagram[6..8].copy_from_slice(&csum.to_be_bytes());
+
+    datagram
+}
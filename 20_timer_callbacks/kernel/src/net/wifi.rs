@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Wi-Fi.
+//!
+//! The BSP registers the board's Wi-Fi chip with [`register_wifi()`]. For now, only bring-up,
+//! firmware download and scanning are supported. Association, and attaching the chip to the
+//! network stack as an interface, are future work.
+//!
+//! The chip needs its firmware and NVRAM configuration before it can scan. They are not part of
+//! the kernel. [`load_firmware_from()`] reads them from a block device, where they were written
+//! with e.g. `dd`, the NVRAM starting in the block after the end of the firmware.
+
+use super::MacAddress;
+use crate::{
+    block::{self, BLOCK_SIZE},
+    info,
+    synchronization::{interface::ReadWriteEx, InitStateLock},
+};
+use alloc::{string::String, vec::Vec};
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Wi-Fi interfaces.
+pub mod interface {
+    use super::{ChipInfo, ScanResult};
+    use alloc::vec::Vec;
+
+    /// Wi-Fi chip functions.
+    pub trait Wifi {
+        /// Bring up the bus to the chip, if needed, and identify it.
+        fn chip_info(&self) -> Result<ChipInfo, &'static str>;
+
+        /// Download firmware and NVRAM configuration, then start the chip's CPU.
+        fn load_firmware(&self, firmware: &[u8], nvram: &[u8]) -> Result<(), &'static str>;
+
+        /// Return if firmware has been loaded and the chip is running.
+        fn is_running(&self) -> bool;
+
+        /// Scan all channels for access points.
+        fn scan(&self) -> Result<Vec<ScanResult>, &'static str>;
+    }
+}
+
+/// Identification of a Wi-Fi chip.
+#[derive(Copy, Clone)]
+pub struct ChipInfo {
+    pub name: &'static str,
+    pub id: u16,
+    pub revision: u8,
+}
+
+/// An access point found by a scan.
+#[derive(Clone)]
+pub struct ScanResult {
+    pub bssid: MacAddress,
+    pub ssid: String,
+    pub channel: u8,
+
+    /// Signal strength in dBm.
+    pub rssi: i16,
+
+    /// True if the network requires authentication.
+    pub secure: bool,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static CUR_WIFI: InitStateLock<Option<&'static (dyn interface::Wifi + Sync)>> =
+    InitStateLock::new(None);
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Register the board's Wi-Fi chip.
+pub fn register_wifi(new_wifi: &'static (dyn interface::Wifi + Sync)) {
+    CUR_WIFI.write(|wifi| *wifi = Some(new_wifi));
+}
+
+/// Return a reference to the registered Wi-Fi chip, if any.
+pub fn wifi() -> Result<&'static dyn interface::Wifi, &'static str> {
+    CUR_WIFI
+        .read(|wifi| *wifi)
+        .map(|w| w as &'static dyn interface::Wifi)
+        .ok_or("No Wi-Fi chip")
+}
+
+/// Download firmware of `firmware_len` bytes from block `lba` of `device` into the Wi-Fi chip, with
+/// NVRAM configuration of `nvram_len` bytes from the first block after the firmware.
+pub fn load_firmware_from(
+    device: block::Device,
+    lba: u64,
+    firmware_len: usize,
+    nvram_len: usize,
+) -> Result<(), &'static str> {
+    let wifi = wifi()?;
+    let nvram_lba = lba + ((firmware_len + BLOCK_SIZE - 1) / BLOCK_SIZE) as u64;
+
+    let firmware = block::read_bytes(device, lba, firmware_len)?;
+    let nvram = block::read_bytes(device, nvram_lba, nvram_len)?;
+
+    wifi.load_firmware(&firmware, &nvram)
+}
+
+/// Print identification and state of the Wi-Fi chip.
+pub fn print_info() -> Result<(), &'static str> {
+    let wifi = wifi()?;
+    let chip = wifi.chip_info()?;
+
+    info!(
+        "      {} (id {:#06x}, rev {})",
+        chip.name, chip.id, chip.revision
+    );
+    info!(
+        "      Firmware: {}",
+        if wifi.is_running() {
+            "running"
+        } else {
+            "not loaded"
+        }
+    );
+
+    Ok(())
+}
+
+/// Scan and print the access points found, strongest first.
+pub fn print_scan() -> Result<(), &'static str> {
+    let mut results: Vec<ScanResult> = wifi()?.scan()?;
+    results.sort_unstable_by_key(|r| -r.rssi);
+
+    for r in &results {
+        info!(
+            "      {}  ch {:>2}  {:>4} dBm  {}  {}",
+            r.bssid,
+            r.channel,
+            r.rssi,
+            if r.secure { "secured" } else { "open   " },
+            r.ssid
+        );
+    }
+    info!("      {} access points", results.len());
+
+    Ok(())
+}
@@ -4,7 +4,7 @@
 
 //! A panic handler that infinitely waits.
 
-use crate::{backtrace, cpu, exception, println};
+use crate::{backtrace, bsp, console, cpu, driver, exception, println, state};
 use core::panic::PanicInfo;
 
 //--------------------------------------------------------------------------------------------------
@@ -64,6 +64,17 @@ fn panic(info: &PanicInfo) -> ! {
     // Protect against panic infinite loops if any of the following code panics itself.
     panic_prevent_reenter();
 
+    // Stop outputs that could do harm while the kernel halts, before the slow printing below.
+    driver::driver_manager().emergency_stop_all();
+
+    // Before a console is registered, the message would only land in the pre-UART buffer. Bring
+    // up the UART instead, which replays the buffer, so that early boot failures are seen.
+    if !console::is_registered() && state::state_manager().is_init() {
+        if let Ok(out) = unsafe { bsp::driver::panic_console() } {
+            console::register_console(out);
+        }
+    }
+
     let timestamp = crate::time::time_manager().uptime();
     let (location, line, column) = match info.location() {
         Some(loc) => (loc.file(), loc.line(), loc.column()),
@@ -71,18 +82,22 @@ fn panic(info: &PanicInfo) -> ! {
     };
 
     println!(
-        "[  {:>3}.{:06}] Kernel panic!\n\n\
+        "[  {}] Kernel panic!\n\n\
         Panic location:\n      File '{}', line {}, column {}\n\n\
         {}\n\n\
+        {}\n\
         {}",
-        timestamp.as_secs(),
-        timestamp.subsec_micros(),
+        crate::time::Seconds(timestamp),
         location,
         line,
         column,
         info.message().unwrap_or(&format_args!("")),
+        exception::RegisterDump,
         backtrace::Backtrace
     );
 
+    // With IRQs masked, buffered output is only sent on a flush.
+    console::output().flush();
+
     _panic_exit()
 }
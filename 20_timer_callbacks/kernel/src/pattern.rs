@@ -0,0 +1,594 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! LED patterns.
+//!
+//! A pattern drives a set of LEDs one step per second from a kernel thread, until it has run
+//! through once or is stopped. By default the hex counter drives the first four pins of
+//! [`RING_PINS`] and the ring counters all of them, but a pattern can be started on any pins.
+//! Several patterns run at the same time as long as their pins don't overlap; starting one on pins
+//! that another pattern drives fails.
+//!
+//! Patterns can be chained: [`start_chain`] runs a list of patterns one after the other in the
+//! same thread and on the same pins, keeping the step cadence across hand-offs, and calls an
+//! optional [`OnComplete`] once the last one has run through. Stopping the chain drops the rest of
+//! it and the callback.
+//!
+//! Steps are due at fixed offsets from the start of the pattern. The time a step takes, e.g. while
+//! the console is busy, is taken off the wait for the next one, so that delays don't add up. A step
+//! that is already late runs right away.
+//!
+//! Lit LEDs can be dimmed. The LED pins have no hardware PWM function, so below full brightness a
+//! software PWM carrier driven by timer callbacks switches them, like the signal generator's ramps.
+//! The brightness is kept in the config store.
+
+use crate::{
+    bsp, config, info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    task, time, trace,
+};
+use alloc::{boxed::Box, format, vec::Vec};
+use core::{
+    fmt,
+    sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const HEX_PINS: [u8; 4] = [1, 2, 3, 4];
+
+/// The hex counter counts on at most this many pins, i.e. from 0 to 15.
+const HEX_DIGIT_BITS: usize = 4;
+
+/// Time between two steps.
+const STEP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Period of the PWM carrier that dims the LEDs. Fast enough not to flicker.
+const PWM_CARRIER_PERIOD: Duration = Duration::from_millis(5);
+
+/// Shortest time between two carrier callbacks, which bounds the IRQ load.
+const MIN_CARRIER_INTERVAL: Duration = Duration::from_micros(50);
+
+const BRIGHTNESS_KEY: &str = "pattern.brightness";
+
+/// A running chain of patterns.
+struct Run {
+    id: usize,
+
+    /// The pins the chain drives.
+    pins: Vec<u8>,
+
+    /// The same pins as a mask, to check for overlaps.
+    mask: u64,
+
+    /// The pattern of the chain that runs now.
+    pattern: Pattern,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The pins the LEDs are attached to.
+pub const RING_PINS: [u8; 5] = [1, 2, 3, 4, 5];
+
+/// The available patterns.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Pattern {
+    /// Count from 0 to 15 in binary on the first four pins.
+    Hex,
+
+    /// Walk a single lit LED from the first to the last pin.
+    Left,
+
+    /// Walk a single lit LED from the last to the first pin.
+    Right,
+}
+
+/// Called from the pattern's thread when a chain of patterns has run through.
+pub type OnComplete = Box<dyn FnOnce() + Send>;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+/// The running chains. A chain's thread stops once its entry is gone.
+static RUNNING: IRQSafeNullLock<Vec<Run>> = IRQSafeNullLock::new(Vec::new());
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Brightness of lit LEDs in percent.
+static BRIGHTNESS: AtomicU32 = AtomicU32::new(100);
+
+/// The pins whose LEDs are lit, as a mask. The carrier switches these.
+static LIT: AtomicU64 = AtomicU64::new(0);
+
+/// Incremented whenever the brightness changes. Carrier callbacks of an older generation end their
+/// chain.
+static CARRIER_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+// Patterns never force, so a protected pin in the ring is silently left alone.
+fn drive(pin: u8, high: bool) {
+    unsafe {
+        let _ = bsp::driver::gpio_as_output(pin, false).and_then(|_| {
+            if high {
+                bsp::driver::gpio_high(pin, false)
+            } else {
+                bsp::driver::gpio_low(pin, false)
+            }
+        });
+    }
+}
+
+/// Light the LED on `pin`. While dimmed, the carrier switches it on at its next edge.
+fn gpio_on(pin: u8) {
+    if pin >= 64 {
+        return;
+    }
+
+    LIT.fetch_or(1 << pin, Ordering::Relaxed);
+    match BRIGHTNESS.load(Ordering::Relaxed) {
+        100 => drive(pin, true),
+        0 => drive(pin, false),
+        _ => (),
+    }
+}
+
+fn gpio_off(pin: u8) {
+    if pin < 64 {
+        LIT.fetch_and(!(1 << pin), Ordering::Relaxed);
+    }
+    drive(pin, false);
+}
+
+/// Iterate over the pins set in `mask`.
+fn pins_of(mask: u64) -> impl Iterator<Item = u8> {
+    (0..64).filter(move |pin| mask & (1 << pin) != 0)
+}
+
+/// Return the carrier level at `t` for `percent` brightness, and how long it holds.
+fn carrier_level_at(percent: u32, t: Duration) -> (bool, Duration) {
+    // Integer math keeps soft-float out of the IRQ path.
+    let on_time = PWM_CARRIER_PERIOD * percent / 100;
+    let c = Duration::from_nanos((t.as_nanos() % PWM_CARRIER_PERIOD.as_nanos()) as u64);
+
+    if c < on_time {
+        (true, on_time - c)
+    } else {
+        (false, PWM_CARRIER_PERIOD - c)
+    }
+}
+
+/// Switch the lit LEDs to the carrier's level and schedule the next edge on `time`. Runs in IRQ
+/// context.
+fn carrier_step(time: &'static time::TimeManager, generation: usize) {
+    if CARRIER_GENERATION.load(Ordering::Relaxed) != generation {
+        return;
+    }
+
+    let percent = BRIGHTNESS.load(Ordering::Relaxed);
+    let (level, hold) = carrier_level_at(percent, time.uptime());
+    for pin in pins_of(LIT.load(Ordering::Relaxed)) {
+        drive(pin, level);
+    }
+
+    time.set_timeout_once(
+        hold.max(MIN_CARRIER_INTERVAL),
+        Box::new(move || carrier_step(time, generation)),
+    );
+}
+
+/// Return `pins` as a mask. Fails for an empty set, repeated pins and pins beyond the mask.
+fn pin_mask(pins: &[u8]) -> Result<u64, &'static str> {
+    if pins.is_empty() {
+        return Err("No pins given");
+    }
+
+    let mut mask = 0;
+    for &pin in pins {
+        let bit = 1u64.checked_shl(pin as u32).ok_or("Invalid pin")?;
+        if mask & bit != 0 {
+            return Err("Pin given twice");
+        }
+        mask |= bit;
+    }
+
+    Ok(mask)
+}
+
+/// Return, for each step of `pattern` on `num_pins` pins, the mask of the pins that are lit.
+fn steps(pattern: Pattern, num_pins: usize) -> Vec<u64> {
+    match pattern {
+        Pattern::Hex => (0..1 << num_pins.min(HEX_DIGIT_BITS)).collect(),
+        Pattern::Left => (0..num_pins).map(|i| 1 << i).collect(),
+        Pattern::Right => (0..num_pins).rev().map(|i| 1 << i).collect(),
+    }
+}
+
+/// Return how long to wait at `now` for step `n` of a pattern that started at `start`.
+fn wait_for_step(start: Duration, n: u32, now: Duration) -> Duration {
+    (start + STEP_INTERVAL * n).saturating_sub(now)
+}
+
+/// Make `pattern` the one chain `id` runs now, unless the chain was stopped. Returns whether it
+/// was.
+fn hand_off(id: usize, pattern: Pattern) -> bool {
+    RUNNING.lock(|running| match running.iter_mut().find(|r| r.id == id) {
+        Some(run) => {
+            run.pattern = pattern;
+            true
+        }
+        None => false,
+    })
+}
+
+fn is_running(id: usize) -> bool {
+    RUNNING.lock(|running| running.iter().any(|r| r.id == id))
+}
+
+/// Stop the chains for which `f` returns true.
+fn stop_where(f: impl Fn(&Run) -> bool) {
+    RUNNING.lock(|running| running.retain(|r| !f(r)));
+}
+
+/// Step through `patterns` one after the other on `num_pins` pins as chain `id`, timed by `time`.
+/// `wait` waits for the next step, and `show` is called with the mask of the lit pins of each step.
+/// Returns whether the chain ran through, i.e. wasn't stopped.
+fn run_steps(
+    id: usize,
+    time: &time::TimeManager,
+    wait: impl Fn(Duration),
+    num_pins: usize,
+    patterns: &[Pattern],
+    mut show: impl FnMut(u64),
+) -> bool {
+    let start = time.uptime();
+    // Counts across the chain, so that the first step of the next pattern keeps the cadence.
+    let mut n = 0;
+
+    for &pattern in patterns {
+        if !hand_off(id, pattern) {
+            return false;
+        }
+
+        for mask in steps(pattern, num_pins) {
+            let left = wait_for_step(start, n, time.uptime());
+            if !left.is_zero() {
+                wait(left);
+            }
+            if !is_running(id) {
+                return false;
+            }
+            let step_start = time.uptime();
+
+            show(mask);
+            trace::record(
+                "pattern",
+                "step_us",
+                (time.uptime() - step_start).as_micros() as u64,
+            );
+            n += 1;
+        }
+    }
+
+    true
+}
+
+/// Run through `patterns` one after the other on `pins` as chain `id`. Runs as a kernel thread.
+fn run(id: usize, pins: Vec<u8>, patterns: Vec<Pattern>, on_complete: Option<OnComplete>) {
+    let show = |mask: u64| {
+        for (i, &pin) in pins.iter().enumerate() {
+            if (mask >> i) & 1 == 1 {
+                gpio_on(pin);
+            } else {
+                gpio_off(pin);
+            }
+        }
+        info!("----------------------");
+    };
+    if !run_steps(
+        id,
+        time::time_manager(),
+        task::sleep,
+        pins.len(),
+        &patterns,
+        show,
+    ) {
+        return;
+    }
+
+    let finished = RUNNING.lock(|running| match running.iter().position(|r| r.id == id) {
+        Some(pos) => {
+            running.remove(pos);
+            true
+        }
+        None => false,
+    });
+    if !finished {
+        return;
+    }
+    for pin in pins {
+        gpio_off(pin);
+    }
+
+    // Runs after the chain gave up its pins, so the callback is free to start another pattern.
+    if let Some(f) = on_complete {
+        f();
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl core::str::FromStr for Pattern {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(Self::Hex),
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            _ => Err("Unknown pattern"),
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Hex => f.pad("hex"),
+            Self::Left => f.pad("left"),
+            Self::Right => f.pad("right"),
+        }
+    }
+}
+
+/// Return the pins a chain of `patterns` drives when no pins are given: the hex counter's four
+/// pins if it's all hex counters, the whole ring otherwise.
+pub fn default_pins(patterns: &[Pattern]) -> &'static [u8] {
+    if patterns.iter().all(|p| *p == Pattern::Hex) {
+        &HEX_PINS
+    } else {
+        &RING_PINS
+    }
+}
+
+/// Stop the patterns on the default pins of `pattern` and start it there. Returns the id of the
+/// new pattern.
+pub fn start(pattern: Pattern) -> Result<usize, &'static str> {
+    let pins = default_pins(&[pattern]);
+    stop_on(pins);
+
+    start_chain(&[pattern], Some(pins), None)
+}
+
+/// Run `patterns` one after the other on `pins`, or on their default pins if `None`. `on_complete`
+/// is called once the last one has run through, but not if the chain is stopped before. Returns
+/// the id of the chain.
+///
+/// Fails if another pattern drives any of the pins.
+pub fn start_chain(
+    patterns: &[Pattern],
+    pins: Option<&[u8]>,
+    on_complete: Option<OnComplete>,
+) -> Result<usize, &'static str> {
+    let first = *patterns.first().ok_or("No pattern to start")?;
+    let pins = pins.unwrap_or_else(|| default_pins(patterns));
+    let mask = pin_mask(pins)?;
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+    RUNNING.lock(|running| {
+        if running.iter().any(|r| r.mask & mask != 0) {
+            return Err("Pins in use by another pattern");
+        }
+        running.push(Run {
+            id,
+            pins: pins.to_vec(),
+            mask,
+            pattern: first,
+        });
+
+        Ok(())
+    })?;
+
+    let (pins, patterns) = (pins.to_vec(), patterns.to_vec());
+    if let Err(x) = task::spawn("pattern", move || run(id, pins, patterns, on_complete)) {
+        stop_id(id)?;
+        return Err(x);
+    }
+
+    Ok(id)
+}
+
+/// Stop all patterns. The LEDs keep their current state.
+pub fn stop() {
+    stop_where(|_| true);
+}
+
+/// Stop the pattern with the given id. Its LEDs keep their current state.
+pub fn stop_id(id: usize) -> Result<(), &'static str> {
+    if !is_running(id) {
+        return Err("No such pattern");
+    }
+    stop_where(|r| r.id == id);
+
+    Ok(())
+}
+
+/// Stop the patterns that drive any of `pins`.
+pub fn stop_on(pins: &[u8]) {
+    stop_where(|r| r.pins.iter().any(|p| pins.contains(p)));
+}
+
+/// Print the running patterns.
+pub fn print() {
+    RUNNING.lock(|running| {
+        for r in running.iter() {
+            info!("      {:>3} {:<5} pins {:?}", r.id, r.pattern, r.pins);
+        }
+    });
+}
+
+/// Set the brightness of lit LEDs in percent.
+pub fn set_brightness(percent: u32) -> Result<(), &'static str> {
+    if percent > 100 {
+        return Err("Brightness out of range");
+    }
+
+    BRIGHTNESS.store(percent, Ordering::Relaxed);
+    let generation = CARRIER_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    match percent {
+        0 | 100 => {
+            for pin in pins_of(LIT.load(Ordering::Relaxed)) {
+                drive(pin, percent == 100);
+            }
+        }
+        _ => carrier_step(time::time_manager(), generation),
+    }
+
+    Ok(())
+}
+
+/// Return the brightness of lit LEDs in percent.
+pub fn brightness() -> u32 {
+    BRIGHTNESS.load(Ordering::Relaxed)
+}
+
+/// Apply the brightness from the config store, if set.
+pub fn load_brightness() -> Result<(), &'static str> {
+    match config::store().get(BRIGHTNESS_KEY) {
+        Some(percent) => set_brightness(percent.parse().map_err(|_| "Malformed brightness")?),
+        None => Ok(()),
+    }
+}
+
+/// Write the brightness to the config store. It is persisted on the next save.
+pub fn store_brightness() -> Result<(), &'static str> {
+    config::store().set(BRIGHTNESS_KEY, &format!("{}", brightness()))
+}
+
+/// Switch the LED on `pin` on or off.
+pub fn set_pin(pin: u8, on: bool) {
+    if on {
+        gpio_on(pin);
+    } else {
+        gpio_off(pin);
+    }
+}
+
+/// Switch all LEDs off.
+pub fn reset_pins() {
+    for pin in RING_PINS {
+        gpio_off(pin);
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A slow step must shorten the wait for the next one, and a late step must not wait at all.
+    #[kernel_test]
+    fn step_cadence() {
+        let start = Duration::from_secs(10);
+        let ms = Duration::from_millis;
+
+        assert_eq!(wait_for_step(start, 0, start), Duration::ZERO);
+        assert_eq!(wait_for_step(start, 1, start + ms(300)), ms(700));
+        assert_eq!(wait_for_step(start, 3, start + ms(2_900)), ms(100));
+        assert_eq!(wait_for_step(start, 2, start + ms(2_500)), Duration::ZERO);
+    }
+
+    /// Follow-up patterns are given by name, so every pattern must parse back from its name.
+    #[kernel_test]
+    fn pattern_names() {
+        for pattern in [Pattern::Hex, Pattern::Left, Pattern::Right] {
+            assert!(alloc::format!("{}", pattern).parse::<Pattern>() == Ok(pattern));
+        }
+        assert!("ring".parse::<Pattern>().is_err());
+    }
+
+    /// Pin sets must be checked before they are claimed, and patterns must fit any number of pins.
+    #[kernel_test]
+    fn pin_sets() {
+        assert_eq!(pin_mask(&[1, 5]), Ok(0b10_0010));
+        assert!(pin_mask(&[]).is_err());
+        assert!(pin_mask(&[3, 3]).is_err());
+        assert!(pin_mask(&[64]).is_err());
+
+        assert_eq!(steps(Pattern::Hex, 2), [0, 1, 2, 3]);
+        assert_eq!(steps(Pattern::Hex, 6).len(), 16);
+        assert_eq!(steps(Pattern::Right, 3), [4, 2, 1]);
+    }
+
+    /// Steps must be due a second apart from the start of the chain, across hand-offs and however
+    /// long a step takes.
+    #[kernel_test]
+    fn chain_cadence() {
+        static TIMER: time::ManualTimer = time::ManualTimer::new();
+
+        let manager = time::TimeManager::with_timer(&TIMER);
+        let ms = Duration::from_millis;
+        let pins = [62, 63];
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        RUNNING.lock(|running| {
+            running.push(Run {
+                id,
+                pins: pins.to_vec(),
+                mask: pin_mask(&pins).unwrap(),
+                pattern: Pattern::Left,
+            })
+        });
+
+        let mut shown = Vec::new();
+        let ran = run_steps(
+            id,
+            &manager,
+            |d| TIMER.advance(d),
+            pins.len(),
+            &[Pattern::Left, Pattern::Right],
+            |mask| {
+                shown.push((manager.uptime(), mask));
+                TIMER.advance(ms(300));
+            },
+        );
+        stop_id(id).unwrap();
+
+        assert!(ran);
+        assert_eq!(
+            shown,
+            [
+                (ms(0), 0b01),
+                (ms(1000), 0b10),
+                (ms(2000), 0b10),
+                (ms(3000), 0b01)
+            ]
+        );
+    }
+
+    /// The carrier must be on for the brightness' share of each period.
+    #[kernel_test]
+    fn carrier_duty_cycle() {
+        let ms = Duration::from_millis;
+        let us = Duration::from_micros;
+
+        assert_eq!(carrier_level_at(40, ms(10)), (true, ms(2)));
+        assert_eq!(carrier_level_at(40, ms(10) + us(1_500)), (true, us(500)));
+        assert_eq!(carrier_level_at(40, ms(12)), (false, ms(3)));
+        assert_eq!(carrier_level_at(0, ms(1)), (false, ms(4)));
+    }
+}
@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Sleep states.
+//!
+//! [`standby()`] parks the core in WFI with all interrupts except the timer's disabled, and brings
+//! them back when it wakes up. Timeouts keep running while in standby, so a periodic timeout can
+//! sample a sensor and go back to sleep. Each of them ends one WFI.
+//!
+//! Input on the console is not seen until the core is back, but stays in the UART's FIFO.
+
+use crate::{cpu, exception, info, time};
+use alloc::boxed::Box;
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// What happened during a standby.
+#[derive(Copy, Clone, Debug)]
+pub struct StandbyReport {
+    /// Time actually spent in standby.
+    pub slept: Duration,
+
+    /// Number of times WFI returned.
+    pub wakeups: u64,
+
+    /// Number of interrupts that were disabled.
+    pub disabled_irqs: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+/// Set by the wakeup timeout.
+static WAKE: AtomicBool = AtomicBool::new(false);
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return if [`standby()`] can be called here, i.e. IRQs are unmasked.
+pub fn can_standby() -> bool {
+    // `is_local_irq_masked()` is inverted, it returns true while IRQs are unmasked.
+    exception::asynchronous::is_local_irq_masked()
+}
+
+/// Sleep for `duration` with only the timer interrupt enabled.
+///
+/// Must be called with IRQs unmasked, i.e. not from IRQ context, or the timer could not wake the
+/// core.
+pub fn standby(duration: Duration) -> Result<StandbyReport, &'static str> {
+    if !can_standby() {
+        return Err("IRQs are masked");
+    }
+    if duration.is_zero() {
+        return Err("Invalid duration");
+    }
+
+    let irq_manager = exception::asynchronous::irq_manager();
+    let start = time::time_manager().uptime();
+    let mut wakeups = 0;
+
+    WAKE.store(false, Ordering::Relaxed);
+    time::time_manager()
+        .set_timeout_once(duration, Box::new(|| WAKE.store(true, Ordering::Relaxed)));
+    let disabled = irq_manager.disable_all_except(&time::timeout_irq());
+
+    // Check the flag and wait with IRQs masked, so that the wakeup can't slip in between. WFI
+    // returns on a pending IRQ even while masked, which is then taken on unmasking.
+    loop {
+        let woke = exception::asynchronous::exec_with_irq_masked(|| {
+            if WAKE.load(Ordering::Relaxed) {
+                return true;
+            }
+            cpu::wait_for_interrupt();
+            wakeups += 1;
+
+            false
+        });
+        if woke {
+            break;
+        }
+    }
+
+    for irq in &disabled {
+        irq_manager.enable(irq);
+    }
+
+    Ok(StandbyReport {
+        slept: time::time_manager().uptime() - start,
+        wakeups,
+        disabled_irqs: disabled.len(),
+    })
+}
+
+impl StandbyReport {
+    /// Print the report.
+    pub fn print(&self) {
+        info!("      Slept:         {}", time::Human(self.slept));
+        info!("      Wakeups:       {}", self.wakeups);
+        info!("      IRQs disabled: {}", self.disabled_irqs);
+    }
+}
@@ -4,7 +4,7 @@
 
 //! Printing.
 
-use crate::console;
+use crate::{console, identity, log, time};
 use core::fmt;
 
 //--------------------------------------------------------------------------------------------------
@@ -13,7 +13,22 @@ use core::fmt;
 
 #[doc(hidden)]
 pub fn _print(args: fmt::Arguments) {
-    console::console().write_fmt(args).unwrap();
+    console::write_fmt(args).unwrap();
+}
+
+/// Print a timestamped line and keep it in the kernel log.
+#[doc(hidden)]
+pub fn _print_log(level: log::Level, args: fmt::Arguments) {
+    let timestamp = time::time_manager().uptime();
+
+    log::record(level, timestamp, args);
+    _print(format_args!(
+        "{}[{} {}] {}\n",
+        identity::LogPrefix,
+        level.marker(),
+        time::Seconds(timestamp),
+        args
+    ));
 }
 
 /// Prints without a newline.
@@ -35,51 +50,27 @@ macro_rules! println {
     })
 }
 
-/// Prints an info, with a newline.
+/// Prints an info, with a newline, and keeps it in the kernel log.
+///
+/// Lines start with the board's hostname if the log prefix is enabled, see [`crate::identity`].
 #[macro_export]
 macro_rules! info {
     ($string:expr) => ({
-        let timestamp = $crate::time::time_manager().uptime();
-
-        $crate::print::_print(format_args_nl!(
-            concat!("[  {:>3}.{:06}] ", $string),
-            timestamp.as_secs(),
-            timestamp.subsec_micros(),
-        ));
+        $crate::print::_print_log($crate::log::Level::Info, format_args!($string));
     });
     ($format_string:expr, $($arg:tt)*) => ({
-        let timestamp = $crate::time::time_manager().uptime();
-
-        $crate::print::_print(format_args_nl!(
-            concat!("[  {:>3}.{:06}] ", $format_string),
-            timestamp.as_secs(),
-            timestamp.subsec_micros(),
-            $($arg)*
-        ));
+        $crate::print::_print_log($crate::log::Level::Info, format_args!($format_string, $($arg)*));
     })
 }
 
-/// Prints a warning, with a newline.
+/// Prints a warning, with a newline, and keeps it in the kernel log.
 #[macro_export]
 macro_rules! warn {
     ($string:expr) => ({
-        let timestamp = $crate::time::time_manager().uptime();
-
-        $crate::print::_print(format_args_nl!(
-            concat!("[W {:>3}.{:06}] ", $string),
-            timestamp.as_secs(),
-            timestamp.subsec_micros(),
-        ));
+        $crate::print::_print_log($crate::log::Level::Warn, format_args!($string));
     });
     ($format_string:expr, $($arg:tt)*) => ({
-        let timestamp = $crate::time::time_manager().uptime();
-
-        $crate::print::_print(format_args_nl!(
-            concat!("[W {:>3}.{:06}] ", $format_string),
-            timestamp.as_secs(),
-            timestamp.subsec_micros(),
-            $($arg)*
-        ));
+        $crate::print::_print_log($crate::log::Level::Warn, format_args!($format_string, $($arg)*));
     })
 }
 
@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Processes: the scheduler's tasks and the programs at EL0 some of them run.
+//!
+//! A process is identified by the id of its task, its PID. [`print_table()`] lists every task
+//! with its CPU time and stack use, marks those that run a program, and shows the exit status of
+//! the last tasks that exited. [`kill()`] ends a runaway one.
+//!
+//! A program runs in an address space of its own, see [`memory::mmu::UserAddressSpace`]: its code
+//! is copied to [`CODE_START`], read-only, and it gets a stack ending at [`STACK_END`]. Nothing
+//! else is mapped, so the program can only act on the rest of the system through the syscalls in
+//! [`crate::syscall`], and a fault ends the program instead of the kernel.
+//!
+//! Each program runs in a scheduler task of its own and is preempted like any other task. The
+//! program's registers are kept on the task's kernel stack while an IRQ or a syscall is handled,
+//! and syscalls run on that stack, so they can block.
+//!
+//! The sockets a program opened are closed when it ends.
+//!
+//! The programs are linked into the kernel, see [`print_programs()`].
+
+#[cfg(target_arch = "aarch64")]
+#[path = "_arch/aarch64/process.rs"]
+mod arch_process;
+
+use crate::{
+    bsp, cpu, exception, info,
+    memory::{
+        self,
+        mmu::{AccessPermissions, UserAddressSpace},
+    },
+    net, sched,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+};
+use alloc::{boxed::Box, vec::Vec};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+struct Process {
+    /// Id of the task running the program.
+    task: usize,
+    name: &'static str,
+    space: UserAddressSpace,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Address of a program's first instruction. The page below stays unmapped, so that null pointers
+/// fault.
+pub const CODE_START: usize = bsp::memory::mmu::KernelGranule::SIZE;
+
+/// End of a program's stack, the end of its address space.
+pub const STACK_END: usize = bsp::memory::mmu::UserVirtAddrSpace::SIZE;
+
+/// Size of a program's stack, in bytes.
+pub const STACK_SIZE: usize = 64 * 1024;
+
+/// Exit status of a program that was ended by a fault.
+pub const STATUS_FAULT: i64 = -1;
+
+/// Exit status of a process that was ended by [`kill()`].
+pub const STATUS_KILLED: i64 = -9;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static PROCESSES: IRQSafeNullLock<Vec<Process>> = IRQSafeNullLock::new(Vec::new());
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Build the address space of a program with the code `image`.
+fn load(image: &[u8]) -> Result<UserAddressSpace, &'static str> {
+    let mut space = UserAddressSpace::new()?;
+
+    let code = space.map(
+        CODE_START,
+        image.len(),
+        AccessPermissions::ReadOnlyUser,
+        false,
+    )?;
+    code[..image.len()].copy_from_slice(image);
+    // The code was written through the data cache, but is fetched through the instruction cache.
+    cpu::clean_invalidate_dcache(code.as_ptr() as usize, code.len());
+    cpu::invalidate_icache();
+
+    space.map(
+        STACK_END - STACK_SIZE,
+        STACK_SIZE,
+        AccessPermissions::ReadWriteUser,
+        true,
+    )?;
+
+    Ok(space)
+}
+
+/// Free the programs whose task was killed. Their tables aren't active, as the scheduler turns
+/// them off when it leaves a task.
+fn reap() {
+    let reaped: Vec<Process> = PROCESSES.lock(|p| {
+        let (alive, reaped) = core::mem::take(p)
+            .into_iter()
+            .partition(|p| sched::is_alive(p.task));
+        *p = alive;
+
+        reaped
+    });
+    for p in reaped.iter() {
+        net::socket::close_owned(p.task);
+    }
+    // Freed outside of the lock.
+    drop(reaped);
+}
+
+/// Run `f` on the process of the calling task, if it runs one.
+fn with_current<R>(f: impl FnOnce(&Process) -> R) -> Option<R> {
+    let task = sched::current()?;
+
+    PROCESSES.lock(|p| p.iter().find(|p| p.task == task).map(f))
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Start the program `name` in a new task, with `arg` in x0. Returns the task id.
+///
+/// Can be called from IRQ context.
+pub fn spawn(name: &str, arg: u64) -> Result<usize, &'static str> {
+    let (name, image) = arch_process::programs()
+        .into_iter()
+        .find(|(n, _)| *n == name)
+        .ok_or("No such program")?;
+
+    reap();
+    let space = load(image)?;
+    let tables = space.phys_base_address()?;
+    let entry = Box::new(move || unsafe { arch_process::enter(CODE_START, STACK_END, arg) });
+
+    // The task must not run before its process is known.
+    exception::asynchronous::exec_with_irq_masked(|| {
+        // The tables live in the process table until the program exits.
+        let task = unsafe { sched::spawn_user(name, entry, tables)? };
+        PROCESSES.lock(|p| p.push(Process { task, name, space }));
+
+        Ok(task)
+    })
+}
+
+/// End the program of the calling task with `status` and free its memory.
+///
+/// Must be called while handling an exception of the program, e.g. a syscall.
+pub fn exit(status: i64) -> ! {
+    // Nothing may switch to the program's tables once they are freed.
+    exception::asynchronous::local_irq_mask();
+    unsafe { memory::mmu::set_user_tables(None) };
+
+    let task = sched::current();
+    let process = PROCESSES.lock(|p| {
+        let i = p.iter().position(|p| Some(p.task) == task)?;
+
+        Some(p.remove(i))
+    });
+    if let Some(process) = process {
+        net::socket::close_owned(process.task);
+        info!(
+            "Process {} ({}) exited with {}",
+            process.task, process.name, status
+        );
+    }
+
+    sched::exit(status)
+}
+
+/// End process `pid`, freeing its program's memory, if any.
+///
+/// Can be called from IRQ context. A process interrupted by the caller ends when the IRQ
+/// handling is done, and its program's memory is freed on the next call to this module.
+pub fn kill(pid: usize) -> Result<(), &'static str> {
+    sched::kill(pid, STATUS_KILLED)?;
+    reap();
+
+    Ok(())
+}
+
+/// Return the name of the program the calling task runs, if any.
+pub fn current() -> Option<&'static str> {
+    with_current(|p| p.name)
+}
+
+/// Run `f` on the `len` bytes at `addr` in the calling program's address space, if the program may
+/// read them.
+pub fn with_user_bytes<R>(
+    addr: usize,
+    len: usize,
+    f: impl FnOnce(&[u8]) -> R,
+) -> Result<R, &'static str> {
+    if with_current(|p| p.space.is_accessible(addr, len, false)) != Some(true) {
+        return Err("Address not accessible to the program");
+    }
+
+    // The program's tables are active while it is handled, and it can't change its mappings.
+    let bytes = unsafe { core::slice::from_raw_parts(addr as *const u8, len) };
+
+    Ok(f(bytes))
+}
+
+/// Like [`with_user_bytes()`], for bytes the program may write.
+pub fn with_user_bytes_mut<R>(
+    addr: usize,
+    len: usize,
+    f: impl FnOnce(&mut [u8]) -> R,
+) -> Result<R, &'static str> {
+    if with_current(|p| p.space.is_accessible(addr, len, true)) != Some(true) {
+        return Err("Address not accessible to the program");
+    }
+
+    // The program's tables are active while it is handled, and it can't change its mappings.
+    let bytes = unsafe { core::slice::from_raw_parts_mut(addr as *mut u8, len) };
+
+    Ok(f(bytes))
+}
+
+/// Print the programs linked into the kernel.
+pub fn print_programs() {
+    for (name, image) in arch_process::programs() {
+        info!("      {:<8} {:>6} bytes", name, image.len());
+    }
+}
+
+/// Print the processes and the exit status of the last ones that exited.
+pub fn print_table() {
+    reap();
+    let programs: Vec<usize> = PROCESSES.lock(|p| p.iter().map(|p| p.task).collect());
+
+    info!(
+        "      {:>3}  {:<9} {:>4} {:>10} {:>12}  Name",
+        "PID", "State", "Prio", "Mcycles", "Stack"
+    );
+    for t in sched::tasks() {
+        let el0 = if programs.contains(&t.id) {
+            " (EL0)"
+        } else {
+            ""
+        };
+        info!(
+            "      {:>3}  {:<9} {:>4} {:>10} {:>5}/{:>2} KiB  {}{}",
+            t.id,
+            t.state,
+            t.priority,
+            t.cycles / 1_000_000,
+            (t.stack_used + 1023) / 1024,
+            t.stack_size / 1024,
+            t.name,
+            el0
+        );
+    }
+
+    let exited = sched::exited();
+    if exited.is_empty() {
+        return;
+    }
+    info!("      Exited:");
+    for e in exited {
+        info!(
+            "      {:>3}  {:<9} {:>4}  {}",
+            e.id, "exited", e.status, e.name
+        );
+    }
+}
+
+/// Print the running programs.
+pub fn print() {
+    reap();
+    PROCESSES.lock(|p| {
+        for p in p.iter() {
+            info!("      {:>3}  {}", p.task, p.name);
+        }
+    });
+}
@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Random numbers.
+//!
+//! An entropy pool that mixes in the jitter of IRQ arrival times, measured with the cycle counter,
+//! and the output of a hardware RNG if the BSP registered one with [`register_source()`]. Without a
+//! hardware source the pool still produces varying output, so [`fill()`] works on every board and
+//! QEMU configuration.
+//!
+//! The output is generated with xoshiro256** from the pool state. It is good enough for port
+//! numbers, sequence numbers and test fuzzing, but not for cryptography.
+
+use crate::{
+    cpu, info,
+    synchronization::{
+        interface::{Mutex, ReadWriteEx},
+        IRQSafeNullLock, InitStateLock,
+    },
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of hardware words mixed in per call to `fill()`.
+const HW_WORDS_PER_FILL: usize = 2;
+
+struct Pool {
+    state: [u64; 4],
+    next_word: usize,
+    timing_samples: u64,
+    hw_words: u64,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Random number interfaces.
+pub mod interface {
+    /// A source of entropy, e.g. a hardware RNG.
+    pub trait EntropySource {
+        /// Name of the source.
+        fn name(&self) -> &'static str;
+
+        /// Return a random word, or `None` if none is available right now. Does not block.
+        fn read_u32(&self) -> Option<u32>;
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static CUR_SOURCE: InitStateLock<Option<&'static (dyn interface::EntropySource + Sync)>> =
+    InitStateLock::new(None);
+
+static POOL: IRQSafeNullLock<Pool> = IRQSafeNullLock::new(Pool::new());
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// The splitmix64 finalizer. Spreads the few varying low bits of a sample over the whole word.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Pool {
+    /// The state must not be all zero, so start from arbitrary non-zero constants.
+    const fn new() -> Self {
+        Self {
+            state: [
+                0x6a09_e667_f3bc_c908,
+                0xbb67_ae85_84ca_a73b,
+                0x3c6e_f372_fe94_f82b,
+                0xa54f_f53a_5f1d_36f1,
+            ],
+            next_word: 0,
+            timing_samples: 0,
+            hw_words: 0,
+        }
+    }
+
+    fn add(&mut self, sample: u64) {
+        self.state[self.next_word] ^= mix(sample);
+        self.next_word = (self.next_word + 1) % self.state.len();
+
+        if self.state == [0; 4] {
+            *self = Self::new();
+        }
+    }
+
+    /// xoshiro256**.
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Register a hardware entropy source.
+pub fn register_source(new_source: &'static (dyn interface::EntropySource + Sync)) {
+    CUR_SOURCE.write(|s| *s = Some(new_source));
+}
+
+/// Mix a sample into the pool.
+pub fn add_entropy(sample: u64) {
+    POOL.lock(|pool| pool.add(sample));
+}
+
+/// Mix the current cycle count into the pool. Called on events with jittery timing, e.g. IRQs.
+pub fn add_timing_sample() {
+    POOL.lock(|pool| {
+        pool.add(cpu::cycle_count() ^ pool.timing_samples.rotate_left(32));
+        pool.timing_samples += 1;
+    });
+}
+
+/// Fill `buf` with random bytes.
+pub fn fill(buf: &mut [u8]) {
+    let source = CUR_SOURCE.read(|s| *s);
+
+    POOL.lock(|pool| {
+        pool.add(cpu::cycle_count());
+        if let Some(source) = source {
+            for _ in 0..HW_WORDS_PER_FILL {
+                if let Some(w) = source.read_u32() {
+                    pool.add(w as u64);
+                    pool.hw_words += 1;
+                }
+            }
+        }
+
+        for chunk in buf.chunks_mut(8) {
+            let bytes = pool.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    });
+}
+
+/// Return a random `u64`.
+pub fn next_u64() -> u64 {
+    let mut buf = [0; 8];
+    fill(&mut buf);
+
+    u64::from_le_bytes(buf)
+}
+
+/// Print the sources feeding the pool.
+pub fn print() {
+    let (timing_samples, hw_words) = POOL.lock(|pool| (pool.timing_samples, pool.hw_words));
+
+    match CUR_SOURCE.read(|s| *s) {
+        Some(source) => info!(
+            "      Hardware source: {} ({} words)",
+            source.name(),
+            hw_words
+        ),
+        None => info!("      Hardware source: none"),
+    }
+    info!("      Timing samples:  {}", timing_samples);
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Consecutive fills must differ and must not be all zero, with or without a hardware source.
+    #[kernel_test]
+    fn fills_differ() {
+        let mut a = [0u8; 33];
+        let mut b = [0u8; 33];
+
+        add_timing_sample();
+        fill(&mut a);
+        fill(&mut b);
+
+        assert_ne!(a, b);
+        assert!(a.iter().any(|x| *x != 0));
+        assert_ne!(next_u64(), next_u64());
+    }
+}
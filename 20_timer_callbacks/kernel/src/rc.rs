@@ -0,0 +1,359 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! RC receiver input.
+//!
+//! Decodes the two common receiver outputs into channel values in microseconds, 1000 to 2000 with
+//! 1500 at center:
+//!
+//! - PPM: all channels as a train of pulses on one pin, a channel's value being the time between
+//!   two rising edges, with a gap of several milliseconds after the last channel. The pin is
+//!   measured by pulse capture, see [`crate::capture`].
+//! - SBUS: 25 byte frames of 16 channels with 11 bits each, at 100000 baud 8E2 with the signal
+//!   inverted. The UART receiving them hands every byte to [`sbus_receive()`].
+//!
+//! Controllers read the latest frame with [`channels()`], which returns nothing once the receiver
+//! has been silent for [`SIGNAL_TIMEOUT`] or reported failsafe, so that they can stop safely.
+
+use crate::{
+    capture, info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time,
+};
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A PPM period longer than this ends the frame.
+const PPM_SYNC_GAP: Duration = Duration::from_micros(2_700);
+
+/// Valid PPM channel periods in microseconds.
+const PPM_MIN_US: u64 = 700;
+const PPM_MAX_US: u64 = 2_300;
+
+/// Frames with fewer channels are taken for noise.
+const PPM_MIN_CHANNELS: usize = 4;
+
+const SBUS_FRAME_LEN: usize = 25;
+const SBUS_HEADER: u8 = 0x0F;
+const SBUS_FLAG_FAILSAFE: u8 = 1 << 3;
+
+/// SBUS frames are sent every 7 or 14 ms. A gap this long between bytes starts a new frame.
+const SBUS_FRAME_GAP: Duration = Duration::from_millis(3);
+
+/// Collects the channel periods of a PPM frame.
+struct PpmDecoder {
+    values: [u16; MAX_CHANNELS],
+    count: usize,
+
+    /// A period out of range or too many channels spoiled the frame. Also set until the first sync
+    /// gap, before which the position in the frame is unknown.
+    bad: bool,
+}
+
+/// Collects the bytes of an SBUS frame.
+struct SbusDecoder {
+    buf: [u8; SBUS_FRAME_LEN],
+    len: usize,
+    last_byte: Option<Duration>,
+}
+
+struct State {
+    ppm_pin: Option<u8>,
+    ppm: PpmDecoder,
+    sbus: SbusDecoder,
+    latest: Option<Channels>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Most channels a frame can carry.
+pub const MAX_CHANNELS: usize = 16;
+
+/// How long a frame stays valid.
+pub const SIGNAL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Where a frame came from.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Source {
+    Ppm,
+    Sbus,
+}
+
+/// A decoded frame.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Channels {
+    pub source: Source,
+
+    /// Channel values in microseconds. Only the first `count` are valid.
+    pub values: [u16; MAX_CHANNELS],
+    pub count: usize,
+
+    /// The receiver lost the transmitter and sends its failsafe values.
+    pub failsafe: bool,
+
+    /// Uptime at which the frame was complete.
+    pub received: Duration,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static STATE: IRQSafeNullLock<State> = IRQSafeNullLock::new(State {
+    ppm_pin: None,
+    ppm: PpmDecoder::new(),
+    sbus: SbusDecoder::new(),
+    latest: None,
+});
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl PpmDecoder {
+    const fn new() -> Self {
+        Self {
+            values: [0; MAX_CHANNELS],
+            count: 0,
+            bad: true,
+        }
+    }
+
+    /// Feed the time between two rising edges. Returns the frame once its sync gap arrived.
+    fn period(&mut self, period: Duration, now: Duration) -> Option<Channels> {
+        if period > PPM_SYNC_GAP {
+            let frame = (!self.bad && self.count >= PPM_MIN_CHANNELS).then_some(Channels {
+                source: Source::Ppm,
+                values: self.values,
+                count: self.count,
+                failsafe: false,
+                received: now,
+            });
+            *self = Self::new();
+            self.bad = false;
+
+            return frame;
+        }
+
+        let us = period.as_micros() as u64;
+        if !(PPM_MIN_US..=PPM_MAX_US).contains(&us) || self.count == MAX_CHANNELS {
+            self.bad = true;
+        } else {
+            self.values[self.count] = us as u16;
+            self.count += 1;
+        }
+
+        None
+    }
+}
+
+impl SbusDecoder {
+    const fn new() -> Self {
+        Self {
+            buf: [0; SBUS_FRAME_LEN],
+            len: 0,
+            last_byte: None,
+        }
+    }
+
+    /// Feed a byte received at `now`. Returns the frame once its last byte arrived.
+    fn push(&mut self, b: u8, now: Duration) -> Option<Channels> {
+        if matches!(self.last_byte, Some(last) if now - last > SBUS_FRAME_GAP) {
+            self.len = 0;
+        }
+        self.last_byte = Some(now);
+
+        // Skip to the next header.
+        if self.len == 0 && b != SBUS_HEADER {
+            return None;
+        }
+
+        self.buf[self.len] = b;
+        self.len += 1;
+        if self.len < SBUS_FRAME_LEN {
+            return None;
+        }
+        self.len = 0;
+
+        // SBUS2 receivers put telemetry slot numbers in the upper nibble of the footer.
+        if self.buf[24] & 0x0F != 0 && self.buf[24] & 0x0F != 0x04 {
+            return None;
+        }
+
+        let mut values = [0; MAX_CHANNELS];
+        for (i, value) in values.iter_mut().enumerate() {
+            let bit = i * 11;
+            let raw = self.buf[1 + bit / 8] as u32
+                | (self.buf[2 + bit / 8] as u32) << 8
+                | (self.buf[3 + bit / 8] as u32) << 16;
+            let raw = (raw >> (bit % 8)) & 0x7FF;
+
+            // 172 to 1811 are the 1000 to 2000 us end points of the common transmitters.
+            *value = (880 + raw * 5 / 8) as u16;
+        }
+
+        let flags = self.buf[23];
+        Some(Channels {
+            source: Source::Sbus,
+            values,
+            count: MAX_CHANNELS,
+            failsafe: flags & SBUS_FLAG_FAILSAFE != 0,
+            received: now,
+        })
+    }
+}
+
+fn ppm_period(period: Duration) {
+    let now = time::time_manager().uptime();
+
+    STATE.lock(|s| {
+        if let Some(frame) = s.ppm.period(period, now) {
+            s.latest = Some(frame);
+        }
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Decode a PPM signal on `pin`, which must be an input. Replaces a previous PPM pin.
+pub fn start_ppm(pin: u8) -> Result<(), &'static str> {
+    stop_ppm();
+
+    capture::start(pin)?;
+    capture::set_period_handler(pin, Some(ppm_period))?;
+    STATE.lock(|s| {
+        s.ppm_pin = Some(pin);
+        s.ppm = PpmDecoder::new();
+    });
+
+    Ok(())
+}
+
+/// Stop decoding PPM.
+pub fn stop_ppm() {
+    if let Some(pin) = STATE.lock(|s| s.ppm_pin.take()) {
+        let _ = capture::stop(pin);
+    }
+}
+
+/// Feed a byte received from an SBUS receiver. Called by the UART it is connected to.
+pub fn sbus_receive(b: u8) {
+    let now = time::time_manager().uptime();
+
+    STATE.lock(|s| {
+        if let Some(frame) = s.sbus.push(b, now) {
+            s.latest = Some(frame);
+        }
+    });
+}
+
+/// Return the latest frame, unless it is older than [`SIGNAL_TIMEOUT`] or failsafe.
+pub fn channels() -> Option<Channels> {
+    let frame = STATE.lock(|s| s.latest)?;
+    let age = time::time_manager().uptime().saturating_sub(frame.received);
+
+    (age <= SIGNAL_TIMEOUT && !frame.failsafe).then_some(frame)
+}
+
+/// Return the value of channel `index`, counted from 0, in microseconds.
+pub fn channel(index: usize) -> Option<u16> {
+    channels()
+        .filter(|c| index < c.count)
+        .map(|c| c.values[index])
+}
+
+/// Print the inputs and the latest frame.
+pub fn print() {
+    let (ppm_pin, latest) = STATE.lock(|s| (s.ppm_pin, s.latest));
+
+    match ppm_pin {
+        Some(pin) => info!("      PPM:      pin {}", pin),
+        None => info!("      PPM:      off"),
+    }
+
+    let frame = match latest {
+        Some(frame) => frame,
+        None => {
+            info!("      No frame received");
+            return;
+        }
+    };
+
+    let age = time::time_manager().uptime().saturating_sub(frame.received);
+    info!(
+        "      Source:   {:?}, {} ago{}",
+        frame.source,
+        time::Human(age),
+        if frame.failsafe { ", failsafe" } else { "" }
+    );
+    for (i, value) in frame.values[..frame.count].iter().enumerate() {
+        info!("      Ch {:<2}    {} us", i + 1, value);
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// PPM frames must end at the sync gap, and SBUS channels must be unpacked from their 11 bit
+    /// fields with the failsafe flag honored.
+    #[kernel_test]
+    fn decode_ppm_and_sbus() {
+        let us = Duration::from_micros;
+
+        let mut ppm = PpmDecoder::new();
+        assert_eq!(ppm.period(us(1_500), us(0)), None);
+        assert_eq!(ppm.period(us(12_000), us(0)), None);
+        for period in [1_000, 1_500, 2_000, 1_200] {
+            assert_eq!(ppm.period(us(period), us(0)), None);
+        }
+        let frame = ppm.period(us(12_000), us(0)).unwrap();
+        assert_eq!(&frame.values[..frame.count], &[1_000, 1_500, 2_000, 1_200]);
+
+        for period in [1_000, 500, 1_500, 2_000, 1_200] {
+            ppm.period(us(period), us(0));
+        }
+        assert_eq!(ppm.period(us(12_000), us(0)), None);
+
+        // Channel 1 at 172, channel 2 at 1811, the rest at 992.
+        let mut raw = [992u32; MAX_CHANNELS];
+        raw[0] = 172;
+        raw[1] = 1811;
+        let mut bytes = [0u8; SBUS_FRAME_LEN];
+        bytes[0] = SBUS_HEADER;
+        for (i, value) in raw.iter().enumerate() {
+            for bit in 0..11 {
+                if value & (1 << bit) != 0 {
+                    let pos = i * 11 + bit;
+                    bytes[1 + pos / 8] |= 1 << (pos % 8);
+                }
+            }
+        }
+
+        let mut sbus = SbusDecoder::new();
+        let mut frame = None;
+        for b in [0x55].iter().chain(bytes.iter()) {
+            frame = sbus.push(*b, us(0));
+        }
+        let frame = frame.unwrap();
+        assert_eq!(&frame.values[..3], &[987, 2011, 1500]);
+        assert!(!frame.failsafe);
+
+        bytes[23] = SBUS_FLAG_FAILSAFE;
+        let frame = bytes.iter().filter_map(|b| sbus.push(*b, us(0))).last();
+        assert!(frame.unwrap().failsafe);
+    }
+}
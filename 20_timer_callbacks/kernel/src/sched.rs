@@ -0,0 +1,756 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Task scheduler.
+//!
+//! A task is a closure that runs on its own stack, in thread context with IRQs unmasked. Tasks are
+//! started by [`spawn()`] and run one at a time from the idle loop through [`run()`], round-robin.
+//! A task runs until it returns, reaches a yield point, i.e. [`yield_now()`] or [`sleep()`], or
+//! uses up its time slice. A periodic tick marks the slice as used up every [`TIME_SLICE`], and the
+//! IRQ exit path then calls [`preempt()`], which switches from the interrupted task back to the
+//! idle loop. The task's exception context stays on its stack until it is switched to again.
+//!
+//! Contexts are only ever switched with IRQs masked, so an IRQ never sees a half-done switch.
+//! Critical sections that mask IRQs, like all [`IRQSafeNullLock`]s, can't be preempted.
+//!
+//! Tasks with a higher priority run first in each round. A task waiting for a [`BlockingMutex`]
+//! is parked until the mutex is released, and lends its priority to the owner meanwhile, so that a
+//! low priority owner isn't starved by the tasks in between.
+//!
+//! A task can run a program at EL0, see [`crate::process`]. Switching to such a task switches to
+//! the program's translation tables, and switching back to the idle loop turns them off again.
+//!
+//! Task stacks have no guard page. They are filled with a pattern when a task is spawned, so that
+//! the deepest use of a stack shows in [`tasks()`]. Printing from a task goes to the registered
+//! console, as a session output installed with [`crate::console::with_output()`] can't be kept
+//! across a switch.
+//!
+//! A task ends with an exit status, and the last [`MAX_EXITED`] ones are kept for [`exited()`].
+//! [`kill()`] ends a task from the outside. That runs no destructors and releases no
+//! [`BlockingMutex`] the task holds, so it is meant for runaway tasks.
+
+#[cfg(target_arch = "aarch64")]
+#[path = "_arch/aarch64/sched.rs"]
+mod arch_sched;
+
+mod mutex;
+
+use crate::{
+    cpu, exception, info,
+    memory::{self, Address, Physical},
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time,
+};
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use arch_sched::Context;
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Size of a task's stack, in bytes.
+const STACK_SIZE: usize = 32 * 1024;
+
+/// Most tasks that can exist at the same time, so that a runaway command can't exhaust the heap.
+const MAX_TASKS: usize = 16;
+
+/// Most exited tasks kept for [`exited()`].
+const MAX_EXITED: usize = 8;
+
+/// Fills the untouched part of a task's stack.
+const STACK_FILL: u128 = 0x5A5A_5A5A_5A5A_5A5A_5A5A_5A5A_5A5A_5A5A;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum State {
+    Ready,
+    Running,
+    Sleeping(Duration),
+
+    /// Waiting for the mutex with the given address.
+    Blocked(usize),
+    Finished,
+}
+
+struct Task {
+    id: usize,
+    name: String,
+    state: State,
+
+    /// Taken by the task when it first runs.
+    entry: Option<Box<dyn FnMut() + Send>>,
+
+    context: Context,
+    switches: u64,
+    priority: u8,
+
+    /// Highest priority of the tasks waiting for a mutex the task holds.
+    inherited: u8,
+
+    /// The task holding the mutex this task is blocked on, if it runs as a task.
+    lends_to: Option<usize>,
+
+    /// Translation tables of the program the task runs, if any.
+    user_tables: Option<Address<Physical>>,
+
+    /// CPU cycles spent in the task, including the IRQs that interrupted it.
+    cycles: u64,
+
+    /// Set by [`kill()`] while the task runs, ends it at the next [`preempt()`].
+    killed: bool,
+    status: i64,
+
+    /// Kept as 16 byte units for the alignment the stack pointer needs.
+    stack: Vec<u128>,
+}
+
+struct SchedulerInner {
+    /// Boxed, so that a task's context keeps its address while the vector grows.
+    #[allow(clippy::vec_box)]
+    tasks: Vec<Box<Task>>,
+
+    /// The running task, if any.
+    current: Option<usize>,
+
+    /// The idle loop's context while a task runs.
+    idle: Context,
+    next_id: usize,
+
+    /// The last tasks that exited, oldest first.
+    exited: Vec<ExitRecord>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+pub use mutex::BlockingMutex;
+
+/// How long a task runs before it is preempted, unless it yields earlier.
+pub const TIME_SLICE: Duration = Duration::from_millis(10);
+
+/// Priority of a newly spawned task. Higher values run first.
+pub const DEFAULT_PRIORITY: u8 = 100;
+
+/// A snapshot of a task, see [`tasks()`].
+pub struct TaskInfo {
+    pub id: usize,
+    pub name: String,
+    pub state: &'static str,
+    pub priority: u8,
+    pub switches: u64,
+    pub cycles: u64,
+
+    /// Deepest use of the stack, in bytes.
+    pub stack_used: usize,
+    pub stack_size: usize,
+}
+
+/// A task that exited, see [`exited()`].
+#[derive(Clone)]
+pub struct ExitRecord {
+    pub id: usize,
+    pub name: String,
+    pub status: i64,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static SCHED: IRQSafeNullLock<SchedulerInner> = IRQSafeNullLock::new(SchedulerInner {
+    tasks: Vec::new(),
+    current: None,
+    idle: Context::empty(),
+    next_id: 1,
+    exited: Vec::new(),
+});
+
+/// Set by the tick when the running task's time slice is used up.
+static PREEMPT: AtomicBool = AtomicBool::new(false);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl State {
+    /// Return if a task in this state can run at `now`.
+    fn is_runnable(&self, now: Duration) -> bool {
+        match self {
+            Self::Ready => true,
+            Self::Sleeping(until) => *until <= now,
+            Self::Running | Self::Blocked(_) | Self::Finished => false,
+        }
+    }
+}
+
+impl State {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Ready => "ready",
+            Self::Running => "running",
+            Self::Sleeping(_) => "sleeping",
+            Self::Blocked(_) => "blocked",
+            Self::Finished => "finished",
+        }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl Task {
+    fn effective_priority(&self) -> u8 {
+        self.priority.max(self.inherited)
+    }
+
+    /// The bytes below the top of the stack that were ever written.
+    fn stack_used(&self) -> usize {
+        let untouched = self.stack.iter().take_while(|w| **w == STACK_FILL).count();
+
+        (self.stack.len() - untouched) * 16
+    }
+}
+
+impl SchedulerInner {
+    fn task_mut(&mut self, id: usize) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|t| t.id == id).map(|t| &mut **t)
+    }
+
+    /// Return the ids of the tasks that can run at `now`, highest priority first and in spawn
+    /// order within a priority.
+    fn runnable(&self, now: Duration) -> Vec<usize> {
+        let mut runnable: Vec<&Task> = self
+            .tasks
+            .iter()
+            .filter(|t| t.state.is_runnable(now))
+            .map(|t| &**t)
+            .collect();
+        runnable.sort_by_key(|t| core::cmp::Reverse(t.effective_priority()));
+
+        runnable.iter().map(|t| t.id).collect()
+    }
+
+    /// Return the highest priority of the tasks blocked on a mutex task `id` holds, 0 if none.
+    fn lent_to(&self, id: usize) -> u8 {
+        self.tasks
+            .iter()
+            .filter(|t| matches!(t.state, State::Blocked(_)) && t.lends_to == Some(id))
+            .map(|t| t.effective_priority())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Return if the caller runs in thread context, i.e. not in an IRQ handler.
+fn in_thread_context() -> bool {
+    // IRQ handlers run with IRQs masked. `is_local_irq_masked()` is inverted, it returns true while
+    // IRQs are unmasked.
+    exception::asynchronous::is_local_irq_masked()
+}
+
+/// Leave the running task in `state` and switch back to the idle loop. Returns when the task is
+/// switched to again. Does nothing outside of a running task.
+fn switch_to_idle(state: State) {
+    exception::asynchronous::exec_with_irq_masked(|| {
+        let contexts = SCHED.lock(|s| {
+            let id = s.current?;
+            let idle = &s.idle as *const Context;
+            let task = s.task_mut(id)?;
+
+            // A task that is already leaving can be interrupted before it switched.
+            if task.state != State::Running {
+                return None;
+            }
+            task.state = state;
+
+            Some((&mut task.context as *mut Context, idle))
+        });
+
+        if let Some((from, to)) = contexts {
+            unsafe { arch_sched::switch(from, to) };
+        }
+    })
+}
+
+/// Run task `id` until it yields, finishes or is preempted.
+fn switch_to(id: usize) {
+    let now = time::time_manager().uptime();
+
+    exception::asynchronous::exec_with_irq_masked(|| {
+        let contexts = SCHED.lock(|s| {
+            let idle = &mut s.idle as *mut Context;
+            let task = s.task_mut(id)?;
+            if !task.state.is_runnable(now) {
+                return None;
+            }
+            task.state = State::Running;
+            task.switches += 1;
+            let to = &task.context as *const Context;
+            let user_tables = task.user_tables;
+            s.current = Some(id);
+
+            Some((idle, to, user_tables))
+        });
+
+        if let Some((from, to, user_tables)) = contexts {
+            // The task gets a full slice.
+            PREEMPT.store(false, Ordering::Relaxed);
+            if user_tables.is_some() {
+                unsafe { memory::mmu::set_user_tables(user_tables) };
+            }
+            let start = cpu::cycle_count();
+            unsafe { arch_sched::switch(from, to) };
+            let cycles = cpu::cycle_count().wrapping_sub(start);
+            if user_tables.is_some() {
+                unsafe { memory::mmu::set_user_tables(None) };
+            }
+            SCHED.lock(|s| {
+                s.current = None;
+                if let Some(t) = s.task_mut(id) {
+                    t.cycles += cycles;
+                }
+            });
+        }
+    })
+}
+
+/// Park the running task until [`wake_blocked()`] is called for `key`, lending its priority to
+/// task `owner`. Must be called with IRQs masked, after checking that the task has to wait.
+fn block(key: usize, owner: Option<usize>) {
+    SCHED.lock(|s| {
+        let priority = match s.current.and_then(|id| s.task_mut(id)) {
+            Some(t) => {
+                t.lends_to = owner;
+                t.effective_priority()
+            }
+            None => return,
+        };
+        if let Some(owner) = owner.and_then(|id| s.task_mut(id)) {
+            owner.inherited = owner.inherited.max(priority);
+        }
+    });
+
+    switch_to_idle(State::Blocked(key));
+}
+
+/// Make the tasks waiting for `key` ready. Task `owner` keeps only the priority lent by the tasks
+/// still waiting for other mutexes it holds.
+fn wake_blocked(key: usize, owner: Option<usize>) {
+    SCHED.lock(|s| {
+        for t in s.tasks.iter_mut() {
+            if t.state == State::Blocked(key) {
+                t.state = State::Ready;
+                t.lends_to = None;
+            }
+        }
+
+        if let Some(owner) = owner {
+            let inherited = s.lent_to(owner);
+            if let Some(t) = s.task_mut(owner) {
+                t.inherited = inherited;
+            }
+        }
+    });
+}
+
+/// Start a task that runs `entry` once, with the program tables `user_tables` while it runs.
+fn spawn_with(
+    name: &str,
+    entry: Box<dyn FnMut() + Send>,
+    user_tables: Option<Address<Physical>>,
+) -> Result<usize, &'static str> {
+    let stack = vec![STACK_FILL; STACK_SIZE / 16];
+    let stack_top = stack.as_ptr() as usize + STACK_SIZE;
+
+    SCHED.lock(|s| {
+        if s.tasks.len() >= MAX_TASKS {
+            return Err("Too many tasks");
+        }
+
+        let id = s.next_id;
+        s.next_id += 1;
+        s.tasks.push(Box::new(Task {
+            id,
+            name: String::from(name),
+            state: State::Ready,
+            entry: Some(entry),
+            context: Context::new(stack_top, task_main, id),
+            switches: 0,
+            priority: DEFAULT_PRIORITY,
+            inherited: 0,
+            lends_to: None,
+            user_tables,
+            cycles: 0,
+            killed: false,
+            status: 0,
+            stack,
+        }));
+
+        Ok(id)
+    })
+}
+
+/// The first function of every task.
+extern "C" fn task_main(id: usize) -> ! {
+    // Switched to with IRQs masked, like every switch.
+    exception::asynchronous::local_irq_unmask();
+
+    let entry = SCHED.lock(|s| s.task_mut(id).and_then(|t| t.entry.take()));
+    if let Some(mut entry) = entry {
+        entry();
+    }
+
+    // The idle loop frees the stack once it is back on its own.
+    switch_to_idle(State::Finished);
+    unreachable!("Finished task {} was switched to", id);
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Start the tick that preempts tasks.
+pub fn init() -> Result<(), &'static str> {
+    static INIT_DONE: AtomicBool = AtomicBool::new(false);
+    if INIT_DONE.load(Ordering::Relaxed) {
+        return Err("Init already done");
+    }
+
+    time::time_manager().set_timeout_periodic(
+        TIME_SLICE,
+        Box::new(|| PREEMPT.store(true, Ordering::Relaxed)),
+    );
+
+    // Another core asks this one to switch tasks at the end of the IPI.
+    exception::asynchronous::register_ipi_handler(
+        exception::asynchronous::IpiMessage::Reschedule,
+        || PREEMPT.store(true, Ordering::Relaxed),
+    )?;
+
+    INIT_DONE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Start a task that runs `entry` once. Returns the task id.
+///
+/// The task runs the next time the idle loop gets to it. Can be called from IRQ context.
+pub fn spawn(name: &str, entry: Box<dyn FnMut() + Send>) -> Result<usize, &'static str> {
+    spawn_with(name, entry, None)
+}
+
+/// Like [`spawn()`], for a task that runs a program with the translation tables at `user_tables`.
+///
+/// # Safety
+///
+/// - The tables must stay alive until the task has finished.
+pub unsafe fn spawn_user(
+    name: &str,
+    entry: Box<dyn FnMut() + Send>,
+    user_tables: Address<Physical>,
+) -> Result<usize, &'static str> {
+    spawn_with(name, entry, Some(user_tables))
+}
+
+/// Give the other ready tasks a turn.
+///
+/// In a task, this returns once the task is switched to again. In thread context outside of a
+/// task, e.g. in a background shell command, it runs each ready task once. In IRQ context it does
+/// nothing.
+pub fn yield_now() {
+    if !in_thread_context() {
+        return;
+    }
+
+    match current() {
+        Some(_) => switch_to_idle(State::Ready),
+        None => run(),
+    }
+}
+
+/// Let the other tasks run for at least `duration`. Outside of a task, this busy-waits.
+pub fn sleep(duration: Duration) {
+    if current().is_none() || !in_thread_context() {
+        time::time_manager().spin_for(duration);
+        return;
+    }
+
+    // The timeout only needs to end the idle loop's wait for an interrupt.
+    time::time_manager().set_timeout_once(duration, Box::new(|| ()));
+    switch_to_idle(State::Sleeping(time::time_manager().uptime() + duration));
+}
+
+/// End the running task with `status`. Must be called in a task.
+pub fn exit(status: i64) -> ! {
+    SCHED.lock(|s| {
+        if let Some(t) = s.current.and_then(|id| s.task_mut(id)) {
+            t.status = status;
+        }
+    });
+
+    switch_to_idle(State::Finished);
+    unreachable!("Task exited outside of a task, or was switched to after it finished");
+}
+
+/// End task `id` with `status`. A task that is running, i.e. was interrupted by the caller, ends
+/// when the IRQ handling is done.
+///
+/// Can be called from IRQ context. Must not be called by the task itself, see [`exit()`].
+pub fn kill(id: usize, status: i64) -> Result<(), &'static str> {
+    SCHED.lock(|s| {
+        let task = s.task_mut(id).ok_or("No such task")?;
+        match task.state {
+            State::Finished => return Err("Task already finished"),
+            State::Running => {
+                task.killed = true;
+                PREEMPT.store(true, Ordering::Relaxed);
+            }
+            _ => task.state = State::Finished,
+        }
+        task.status = status;
+
+        // A killed waiter no longer lends its priority.
+        if let Some(owner) = task.lends_to.take() {
+            let inherited = s.lent_to(owner);
+            if let Some(t) = s.task_mut(owner) {
+                t.inherited = inherited;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Switch from the interrupted task back to the idle loop if its time slice is used up.
+///
+/// Must only be called at the end of IRQ handling, after the IRQ was acknowledged. The task
+/// continues with the rest of the IRQ exit path when it is switched to again.
+pub fn preempt() {
+    if !PREEMPT.swap(false, Ordering::Relaxed) {
+        return;
+    }
+
+    let killed = SCHED.lock(|s| {
+        s.current
+            .and_then(|id| s.task_mut(id))
+            .map_or(false, |t| t.killed)
+    });
+    switch_to_idle(if killed {
+        State::Finished
+    } else {
+        State::Ready
+    });
+}
+
+/// Change the priority of task `id`. Higher values run first.
+pub fn set_priority(id: usize, priority: u8) -> Result<(), &'static str> {
+    SCHED.lock(|s| {
+        s.task_mut(id).ok_or("No such task")?.priority = priority;
+
+        Ok(())
+    })
+}
+
+/// Return the id of the running task, if any.
+pub fn current() -> Option<usize> {
+    SCHED.lock(|s| s.current)
+}
+
+/// Return if a task can run now, i.e. the idle loop must not wait for an interrupt.
+pub fn is_ready() -> bool {
+    let now = time::time_manager().uptime();
+
+    SCHED.lock(|s| s.tasks.iter().any(|t| t.state.is_runnable(now)))
+}
+
+/// Run each task that can run once, until it yields or finishes, and free the finished ones.
+///
+/// Must be called from thread context outside of a task, i.e. from the idle loop.
+pub fn run() {
+    if current().is_some() {
+        return;
+    }
+
+    let runnable = SCHED.lock(|s| s.runnable(time::time_manager().uptime()));
+    for id in runnable {
+        switch_to(id);
+    }
+
+    let finished: Vec<Box<Task>> = SCHED.lock(|s| {
+        let (finished, alive): (Vec<Box<Task>>, _) = core::mem::take(&mut s.tasks)
+            .into_iter()
+            .partition(|t| t.state == State::Finished);
+        s.tasks = alive;
+
+        for t in &finished {
+            if s.exited.len() == MAX_EXITED {
+                s.exited.remove(0);
+            }
+            s.exited.push(ExitRecord {
+                id: t.id,
+                name: t.name.clone(),
+                status: t.status,
+            });
+        }
+
+        finished
+    });
+    // Freed outside of the lock.
+    drop(finished);
+}
+
+/// Return if task `id` exists and hasn't finished.
+pub fn is_alive(id: usize) -> bool {
+    SCHED.lock(|s| s.task_mut(id).map_or(false, |t| t.state != State::Finished))
+}
+
+/// Return a snapshot of the tasks.
+pub fn tasks() -> Vec<TaskInfo> {
+    SCHED.lock(|s| {
+        s.tasks
+            .iter()
+            .map(|t| TaskInfo {
+                id: t.id,
+                name: t.name.clone(),
+                state: t.state.name(),
+                priority: t.effective_priority(),
+                switches: t.switches,
+                cycles: t.cycles,
+                stack_used: t.stack_used(),
+                stack_size: STACK_SIZE,
+            })
+            .collect()
+    })
+}
+
+/// Return the last tasks that exited, oldest first.
+pub fn exited() -> Vec<ExitRecord> {
+    SCHED.lock(|s| s.exited.clone())
+}
+
+/// Print the tasks.
+pub fn print() {
+    SCHED.lock(|s| {
+        for t in &s.tasks {
+            let inherited = if t.inherited > t.priority { "+" } else { "" };
+            info!(
+                "      {:>3}  {:<9} {:>3}{:<1} {:>8}  {}",
+                t.id,
+                t.state,
+                t.effective_priority(),
+                inherited,
+                t.switches,
+                t.name
+            );
+        }
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::heap_alloc::kernel_heap_allocator;
+    use test_macros::kernel_test;
+
+    /// The names of the tasks in the order they got to run.
+    static RAN: IRQSafeNullLock<Vec<&'static str>> = IRQSafeNullLock::new(Vec::new());
+
+    fn note(name: &'static str) {
+        RAN.lock(|ran| ran.push(name));
+    }
+
+    fn take_ran() -> Vec<&'static str> {
+        RAN.lock(core::mem::take)
+    }
+
+    /// Only ready tasks and sleeping tasks whose time has come may run.
+    #[kernel_test]
+    fn runnable_states() {
+        let now = Duration::from_secs(10);
+
+        assert!(State::Ready.is_runnable(now));
+        assert!(State::Sleeping(now).is_runnable(now));
+        assert!(!State::Sleeping(now + Duration::from_millis(1)).is_runnable(now));
+        assert!(!State::Running.is_runnable(now));
+        assert!(!State::Blocked(0x1000).is_runnable(now));
+        assert!(!State::Finished.is_runnable(now));
+    }
+
+    /// Tasks with a higher priority must run first, and tasks of one priority in spawn order.
+    #[kernel_test]
+    fn priority_order() {
+        take_ran();
+        for (name, priority) in [("low", 50), ("mid", DEFAULT_PRIORITY), ("high", 200)] {
+            let id = spawn(name, Box::new(move || note(name))).unwrap();
+            set_priority(id, priority).unwrap();
+        }
+        let later = spawn("later", Box::new(|| note("later"))).unwrap();
+        set_priority(later, 200).unwrap();
+
+        run();
+        assert_eq!(take_ran(), ["high", "later", "mid", "low"]);
+    }
+
+    /// A task that yields must let the others run and resume in the next round.
+    #[kernel_test]
+    fn yield_now_resumes() {
+        take_ran();
+        spawn(
+            "yielding",
+            Box::new(|| {
+                note("before");
+                yield_now();
+                note("after");
+            }),
+        )
+        .unwrap();
+        spawn("other", Box::new(|| note("other"))).unwrap();
+
+        run();
+        assert_eq!(take_ran(), ["before", "other"]);
+        run();
+        assert_eq!(take_ran(), ["after"]);
+    }
+
+    /// A killed task must end with the given status, and its stack must be freed.
+    #[kernel_test]
+    fn killed_task_is_freed() {
+        let id = spawn(
+            "spinner",
+            Box::new(|| loop {
+                yield_now();
+            }),
+        )
+        .unwrap();
+        run();
+        assert!(is_alive(id));
+        let with_stack = kernel_heap_allocator().used();
+
+        assert_eq!(kill(id, -9), Ok(()));
+        assert!(kill(id, -9).is_err());
+        run();
+
+        assert!(!is_alive(id));
+        assert!(tasks().iter().all(|t| t.id != id));
+        let record = exited().pop().unwrap();
+        assert_eq!(
+            (record.id, record.name.as_str(), record.status),
+            (id, "spinner", -9)
+        );
+
+        // The exit record takes a little of it back.
+        assert!(with_stack - kernel_heap_allocator().used() > STACK_SIZE - 1024);
+    }
+}
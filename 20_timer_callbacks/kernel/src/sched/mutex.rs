@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Blocking mutex.
+//!
+//! Unlike an [`IRQSafeNullLock`], a [`BlockingMutex`] keeps IRQs unmasked while it is held, so the
+//! holder can be preempted during slow work like storage I/O. A task that finds the mutex taken is
+//! parked until it is released instead of spinning, and lends its priority to the holder.
+//!
+//! Outside of a task, waiting runs the other tasks. In IRQ context nothing can wait, as the holder
+//! can't run until the handler returns: [`BlockingMutex::lock_or_busy()`] then reports the mutex
+//! as busy, while [`Mutex::lock()`] panics.
+//!
+//! The coarse locks use it: the console's writer, the SD card controller and the config store's
+//! storage. The console's per-device locks stay IRQ-safe, as handlers print and run shell commands
+//! through them. A handler that finds the writer taken writes right away instead.
+
+use super::{block, wake_blocked};
+use crate::{
+    exception,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+};
+use core::cell::UnsafeCell;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+struct LockState {
+    locked: bool,
+
+    /// The task holding the mutex. `None` while free or held outside of a task.
+    owner: Option<usize>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A mutex that parks waiting tasks.
+pub struct BlockingMutex<T> {
+    state: IRQSafeNullLock<LockState>,
+    data: UnsafeCell<T>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl<T> BlockingMutex<T> {
+    /// The key under which waiting tasks are parked.
+    fn key(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// Take the mutex if it is free. Returns the owner otherwise.
+    fn try_acquire(&self) -> Result<(), Option<usize>> {
+        self.state.lock(|s| {
+            if s.locked {
+                return Err(s.owner);
+            }
+            s.locked = true;
+            s.owner = super::current();
+
+            Ok(())
+        })
+    }
+
+    /// Take the mutex, waiting for it unless in IRQ context. Returns whether it was taken.
+    fn acquire(&self) -> bool {
+        loop {
+            if !super::in_thread_context() {
+                return self.try_acquire().is_ok();
+            }
+
+            // With IRQs masked, the holder can't release the mutex between the check and parking.
+            let taken = exception::asynchronous::exec_with_irq_masked(|| {
+                match (self.try_acquire(), super::current()) {
+                    (Ok(()), _) => true,
+                    (Err(owner), Some(_)) => {
+                        block(self.key(), owner);
+                        false
+                    }
+                    (Err(_), None) => false,
+                }
+            });
+            if taken {
+                return true;
+            }
+
+            if super::current().is_none() {
+                super::yield_now();
+            }
+        }
+    }
+
+    fn release(&self) {
+        let owner = self.state.lock(|s| {
+            s.locked = false;
+            s.owner.take()
+        });
+
+        wake_blocked(self.key(), owner);
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+unsafe impl<T> Send for BlockingMutex<T> where T: Send {}
+unsafe impl<T> Sync for BlockingMutex<T> where T: Send {}
+
+impl<T> BlockingMutex<T> {
+    /// Create an instance.
+    pub const fn new(data: T) -> Self {
+        Self {
+            state: IRQSafeNullLock::new(LockState {
+                locked: false,
+                owner: None,
+            }),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Like [`Mutex::lock()`], but returns an error instead of panicking if the mutex is taken in
+    /// IRQ context.
+    pub fn lock_or_busy<'a, R>(
+        &'a self,
+        f: impl FnOnce(&'a mut T) -> R,
+    ) -> Result<R, &'static str> {
+        if !self.acquire() {
+            return Err("Lock busy");
+        }
+
+        let result = f(unsafe { &mut *self.data.get() });
+        self.release();
+
+        Ok(result)
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+
+impl<T> Mutex for BlockingMutex<T> {
+    type Data = T;
+
+    fn lock<'a, R>(&'a self, f: impl FnOnce(&'a mut Self::Data) -> R) -> R {
+        match self.lock_or_busy(f) {
+            Ok(result) => result,
+            Err(_) => panic!("BlockingMutex taken in IRQ context"),
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sched;
+    use alloc::{boxed::Box, vec::Vec};
+    use test_macros::kernel_test;
+
+    static FIRST: BlockingMutex<()> = BlockingMutex::new(());
+    static SECOND: BlockingMutex<()> = BlockingMutex::new(());
+
+    /// The tasks that got to run, in order, with their effective priority at the time.
+    static RAN: IRQSafeNullLock<Vec<(&'static str, u8)>> = IRQSafeNullLock::new(Vec::new());
+
+    fn note(name: &'static str) {
+        let id = sched::current().unwrap();
+        let priority = sched::tasks()
+            .into_iter()
+            .find(|t| t.id == id)
+            .unwrap()
+            .priority;
+
+        RAN.lock(|ran| ran.push((name, priority)));
+    }
+
+    /// The mutex must be held while the closure runs and be free again afterwards.
+    #[kernel_test]
+    fn lock_and_release() {
+        let m = BlockingMutex::new(1);
+
+        m.lock(|v| {
+            *v += 1;
+            assert!(m.state.lock(|s| s.locked));
+        });
+        assert!(!m.state.lock(|s| s.locked));
+        assert_eq!(m.lock_or_busy(|v| *v), Ok(2));
+    }
+
+    /// Waiters must park until the mutex is released and lend their priority to a low priority
+    /// holder meanwhile. Releasing one mutex must keep what is lent through the other.
+    #[kernel_test]
+    fn waiters_lend_priority() {
+        let low = sched::spawn(
+            "low",
+            Box::new(|| {
+                FIRST.lock(|_| {
+                    SECOND.lock(|_| {
+                        for (name, priority, m) in [("high", 200, &FIRST), ("mid", 150, &SECOND)] {
+                            let id = sched::spawn(name, Box::new(move || m.lock(|_| note(name))))
+                                .unwrap();
+                            sched::set_priority(id, priority).unwrap();
+                        }
+
+                        // Both waiters run and park.
+                        sched::yield_now();
+                        note("low");
+                    });
+                    note("low");
+                });
+                note("low");
+            }),
+        )
+        .unwrap();
+        sched::set_priority(low, 10).unwrap();
+
+        for _ in 0..3 {
+            sched::run();
+        }
+
+        assert_eq!(
+            RAN.lock(|ran| ran.clone()),
+            [
+                ("low", 200),
+                ("low", 200),
+                ("low", 10),
+                ("high", 200),
+                ("mid", 150)
+            ]
+        );
+        assert!(!sched::is_alive(low));
+    }
+}
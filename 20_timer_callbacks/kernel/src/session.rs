@@ -0,0 +1,357 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Shell session recording and replay.
+//!
+//! While a recording runs, every line typed at a console is kept with the time since the recording
+//! started. Lines run by jobs and scripts are not recorded: replaying the line that created them
+//! creates them again.
+//!
+//! A session is kept as text, one `<milliseconds> <line>` entry per line, so that it can be saved
+//! to a block device, pasted into a bug report, and loaded on another board. [`replay()`] runs the
+//! lines again from timer callbacks, at their recorded times divided by a speed-up factor. Like
+//! the signal generator, each line is due relative to the start of the replay, so a slow command
+//! doesn't shift the rest.
+
+use crate::{
+    block, console, info, shell,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time,
+};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt::Write as _,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Most lines a session keeps. Later lines are dropped.
+const MAX_ENTRIES: usize = 512;
+
+/// Most blocks a saved session takes.
+const MAX_BLOCKS: usize = 64;
+
+/// First line of a saved session.
+const HEADER: &str = "# KHROS session";
+
+#[derive(Clone)]
+struct Entry {
+    at: Duration,
+    line: String,
+}
+
+struct Replay {
+    entries: Vec<Entry>,
+    next: usize,
+    speed: u32,
+    out: console::Output,
+    start: Duration,
+}
+
+struct SessionInner {
+    /// Uptime the running recording started at.
+    recording: Option<Duration>,
+    entries: Vec<Entry>,
+    dropped: usize,
+    replay: Option<Replay>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static SESSION: IRQSafeNullLock<SessionInner> = IRQSafeNullLock::new(SessionInner {
+    recording: None,
+    entries: Vec::new(),
+    dropped: 0,
+    replay: None,
+});
+
+/// Incremented to stop the callbacks of a replay.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn to_text(entries: &[Entry]) -> String {
+    let mut text = String::from(HEADER);
+    text.push('\n');
+    for e in entries {
+        let _ = writeln!(text, "{} {}", e.at.as_millis(), e.line);
+    }
+
+    text
+}
+
+/// Parse a session. Empty lines and lines starting with `#` are skipped.
+fn parse(text: &str) -> Result<Vec<Entry>, &'static str> {
+    let mut entries: Vec<Entry> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (ms, command) = line.split_once(' ').ok_or("Malformed session line")?;
+        let at = Duration::from_millis(ms.parse().map_err(|_| "Malformed session time")?);
+        if entries.last().map_or(false, |last| at < last.at) {
+            return Err("Session times go backwards");
+        }
+        if entries.len() == MAX_ENTRIES {
+            return Err("Session too long");
+        }
+
+        entries.push(Entry {
+            at,
+            line: command.trim().to_string(),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Run the next line of the replay and schedule the one after it.
+fn step(generation: usize) {
+    if GENERATION.load(Ordering::Relaxed) != generation {
+        return;
+    }
+
+    let line = SESSION.lock(|s| {
+        let replay = s.replay.as_mut()?;
+        let entry = replay.entries.get(replay.next)?;
+        replay.next += 1;
+
+        Some((replay.out, entry.line.clone()))
+    });
+    let (out, line) = match line {
+        Some(line) => line,
+        None => {
+            SESSION.lock(|s| s.replay = None);
+            return;
+        }
+    };
+
+    console::with_output(out, || info!("replay> {}", line));
+    shell::execute(out, &line);
+
+    // The command may have stopped the replay.
+    if GENERATION.load(Ordering::Relaxed) != generation {
+        return;
+    }
+    let delay = SESSION.lock(|s| {
+        let replay = s.replay.as_ref()?;
+        let entry = replay.entries.get(replay.next)?;
+        let due = replay.start + entry.at / replay.speed;
+
+        Some(due.saturating_sub(time::time_manager().uptime()))
+    });
+
+    match delay {
+        Some(delay) => {
+            time::time_manager().set_timeout_once(delay, Box::new(move || step(generation)));
+        }
+        None => {
+            SESSION.lock(|s| s.replay = None);
+            console::with_output(out, || info!("Replay done"));
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Keep a line typed at a console if a recording runs. Session commands themselves are not kept.
+pub fn record(line: &str) {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with("session") || line.starts_with("replay") {
+        return;
+    }
+
+    let now = time::time_manager().uptime();
+    SESSION.lock(|s| {
+        let start = match s.recording {
+            Some(start) => start,
+            None => return,
+        };
+        if s.entries.len() == MAX_ENTRIES {
+            s.dropped += 1;
+            return;
+        }
+
+        s.entries.push(Entry {
+            at: now - start,
+            line: line.to_string(),
+        });
+    });
+}
+
+/// Start recording, dropping the previous session.
+pub fn start() {
+    SESSION.lock(|s| {
+        s.recording = Some(time::time_manager().uptime());
+        s.entries.clear();
+        s.dropped = 0;
+    });
+}
+
+/// Stop recording. The session is kept for replay and saving.
+pub fn stop() {
+    SESSION.lock(|s| s.recording = None);
+}
+
+/// Write the session to the start of `device`, overwriting what was there.
+pub fn save(device: &str) -> Result<usize, &'static str> {
+    let device = block::lookup(device)?;
+    let text = SESSION.lock(|s| to_text(&s.entries));
+
+    // The end of the text is marked by a NUL byte.
+    let blocks = text.len() / block::BLOCK_SIZE + 1;
+    if blocks > MAX_BLOCKS || blocks as u64 > device.block_count() {
+        return Err("Session too long for the device");
+    }
+    let mut buf = vec![0; blocks * block::BLOCK_SIZE];
+    buf[..text.len()].copy_from_slice(text.as_bytes());
+    device.write(0, &buf)?;
+
+    Ok(text.len())
+}
+
+/// Replace the session with the one saved at the start of `device`. Returns the number of lines.
+pub fn load(device: &str) -> Result<usize, &'static str> {
+    let device = block::lookup(device)?;
+
+    let mut text = Vec::new();
+    let mut buf = [0; block::BLOCK_SIZE];
+    for lba in 0..(MAX_BLOCKS as u64).min(device.block_count()) {
+        device.read(lba, &mut buf)?;
+        match buf.iter().position(|b| *b == 0) {
+            Some(end) => {
+                text.extend_from_slice(&buf[..end]);
+                break;
+            }
+            None => text.extend_from_slice(&buf),
+        }
+    }
+
+    let text = core::str::from_utf8(&text).map_err(|_| "Session is not text")?;
+    if !text.starts_with(HEADER) {
+        return Err("No session on the device");
+    }
+    let entries = parse(text)?;
+    let lines = entries.len();
+
+    SESSION.lock(|s| {
+        s.recording = None;
+        s.entries = entries;
+        s.dropped = 0;
+    });
+
+    Ok(lines)
+}
+
+/// Run the session's lines again at `speed` times their recorded pace, with output to `out`,
+/// replacing a running replay.
+pub fn replay(out: console::Output, speed: u32) -> Result<(), &'static str> {
+    if speed == 0 {
+        return Err("Speed must be at least 1");
+    }
+
+    stop_replay();
+    let generation = GENERATION.load(Ordering::Relaxed);
+    let first = SESSION.lock(|s| {
+        if s.recording.is_some() {
+            return Err("Recording, stop it first");
+        }
+        let first = s.entries.first().ok_or("Session is empty")?.at / speed;
+        s.replay = Some(Replay {
+            entries: s.entries.clone(),
+            next: 0,
+            speed,
+            out,
+            start: time::time_manager().uptime(),
+        });
+
+        Ok(first)
+    })?;
+
+    time::time_manager().set_timeout_once(first, Box::new(move || step(generation)));
+
+    Ok(())
+}
+
+/// Stop the running replay. The line being run completes.
+pub fn stop_replay() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    SESSION.lock(|s| s.replay = None);
+}
+
+/// Print the recording state and the session.
+pub fn print() {
+    SESSION.lock(|s| {
+        info!(
+            "      Recording: {}",
+            if s.recording.is_some() { "on" } else { "off" }
+        );
+        match &s.replay {
+            Some(r) => info!(
+                "      Replay:    line {} of {} at {}x",
+                r.next,
+                r.entries.len(),
+                r.speed
+            ),
+            None => info!("      Replay:    idle"),
+        }
+        if s.dropped != 0 {
+            info!("      Dropped:   {} lines", s.dropped);
+        }
+        for e in &s.entries {
+            info!("      {:>8} ms  {}", e.at.as_millis(), e.line);
+        }
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A session must survive a text round trip, and malformed text must be refused.
+    #[kernel_test]
+    fn text_round_trip() {
+        let entries = [
+            Entry {
+                at: Duration::from_millis(0),
+                line: "gpio_on 5".to_string(),
+            },
+            Entry {
+                at: Duration::from_millis(1500),
+                line: "every 2 \"gpio read 6\"".to_string(),
+            },
+        ];
+
+        let parsed = parse(&to_text(&entries)).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].at, Duration::from_millis(1500));
+        assert_eq!(parsed[1].line, entries[1].line);
+
+        assert!(parse("10 a\n5 b").is_err());
+        assert!(parse("x gpio_on 5").is_err());
+        assert!(parse("\n# comment\n\n").unwrap().is_empty());
+    }
+}
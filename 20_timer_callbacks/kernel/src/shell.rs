@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! The command shell.
+//!
+//! Commands are registered by name with [`register_command()`], by the kernel's subsystems as well
+//! as by the BSP and drivers. Consoles feed each entered line to [`execute_input()`], which hands
+//! it to a [`session`](crate::session) recording and to [`execute()`]. That splits it into
+//! arguments and runs the matching command with its output going to the console the line came
+//! from.
+//!
+//! Arguments are separated by whitespace. An argument starting with `"` extends to the next `"`
+//! and may contain whitespace.
+//!
+//! Lines starting with `repeat`, `while` or `if`, and the argument of `run`, are scripts. See
+//! [`script`] for the syntax.
+
+mod commands;
+mod script;
+mod syntax;
+
+use crate::{
+    console, cpu, info, jobs, latency, session,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time, trace,
+};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+#[derive(Copy, Clone)]
+struct Command {
+    name: &'static str,
+    help: &'static str,
+    handler: Handler,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A command handler. `args[0]` is the command name. An error is printed after the command name.
+pub type Handler = fn(args: &[&str]) -> Result<(), &'static str>;
+
+pub use script::run_autoexec;
+pub use syntax::{join, tokenize};
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+/// The registered commands, sorted by name.
+static COMMANDS: IRQSafeNullLock<Vec<Command>> = IRQSafeNullLock::new(Vec::new());
+
+/// Report the execution time of every command.
+static TIME_COMMANDS: AtomicBool = AtomicBool::new(false);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Look up and run a command. Returns `None` if there is no command `args[0]`.
+fn call(args: &[&str]) -> Option<Result<(), &'static str>> {
+    let name = args.first()?;
+
+    // The handler is called without holding the registry lock, so it may register commands.
+    let command = COMMANDS.lock(|commands| commands.iter().find(|c| c.name == *name).copied())?;
+
+    Some((command.handler)(args))
+}
+
+/// Look up and run a command line.
+fn dispatch(line: &str) {
+    let args = match tokenize(line) {
+        Ok(args) => args,
+        Err(x) => {
+            info!("{}", x);
+            return;
+        }
+    };
+    if args.is_empty() {
+        return;
+    }
+
+    match call(&args) {
+        Some(Ok(())) => (),
+        Some(Err(x)) => info!("{}: {}", args[0], x),
+        None => info!("Command not found: {}", args[0]),
+    }
+}
+
+/// Run a command and report how long it took.
+fn dispatch_timed(line: &str) {
+    let start = time::time_manager().uptime();
+    let start_cycles = cpu::cycle_count();
+
+    dispatch(line);
+
+    let cycles = cpu::cycle_count().wrapping_sub(start_cycles);
+    let elapsed = time::time_manager().uptime() - start;
+    trace::record("shell", "cycles", cycles);
+    info!("Command took {} cycles, {}", cycles, time::Human(elapsed));
+}
+
+fn help(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Commands:");
+    COMMANDS.lock(|commands| {
+        for c in commands.iter() {
+            info!("      {:<16} {}", c.name, c.help);
+        }
+    });
+
+    Ok(())
+}
+
+fn timing(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        Some("on") => TIME_COMMANDS.store(true, Ordering::Relaxed),
+        Some("off") => TIME_COMMANDS.store(false, Ordering::Relaxed),
+        None => (),
+        Some(_) => info!("Usage: timing [on|off]"),
+    }
+    info!(
+        "Command timing: {}",
+        if TIME_COMMANDS.load(Ordering::Relaxed) {
+            "on"
+        } else {
+            "off"
+        }
+    );
+
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Register the kernel's commands.
+pub fn init() -> Result<(), &'static str> {
+    register_command("help", "List the commands", help)?;
+    register_command(
+        "timing",
+        "Report the execution time of every command",
+        timing,
+    )?;
+
+    commands::register()
+}
+
+/// Register a command.
+pub fn register_command(
+    name: &'static str,
+    help: &'static str,
+    handler: Handler,
+) -> Result<(), &'static str> {
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err("Invalid command name");
+    }
+
+    COMMANDS.lock(
+        |commands| match commands.binary_search_by(|c| c.name.cmp(name)) {
+            Ok(_) => Err("Command already registered"),
+            Err(pos) => {
+                commands.insert(
+                    pos,
+                    Command {
+                        name,
+                        help,
+                        handler,
+                    },
+                );
+                Ok(())
+            }
+        },
+    )
+}
+
+/// Run a command line, printing its output to `out`.
+///
+/// Lines ending in `&` are queued to run in the background. Lines prefixed with `time`, or all
+/// lines while `timing` is on, are followed by their execution time.
+pub fn execute(out: console::Output, line: &str) {
+    let line = line.trim();
+
+    if let Some(background) = line.strip_suffix('&') {
+        match jobs::spawn(out, background.trim()) {
+            Ok(id) => info!("[{}] {}", id, background.trim()),
+            Err(x) => info!("&: {}", x),
+        }
+        return;
+    }
+
+    console::with_output(out, || match line.strip_prefix("time ") {
+        Some(timed) => dispatch_timed(timed.trim()),
+        None if TIME_COMMANDS.load(Ordering::Relaxed) && !line.is_empty() => dispatch_timed(line),
+        None => dispatch(line),
+    });
+}
+
+/// Run a line typed at a console. Unlike lines from jobs and scripts, it is kept by a session
+/// recording.
+///
+/// In bounded-latency mode, the line is queued for the idle loop instead, see [`jobs::defer()`].
+pub fn execute_input(out: console::Output, line: &str) {
+    session::record(line);
+
+    if latency::is_bounded() {
+        jobs::defer(out, line);
+        return;
+    }
+    execute(out, line);
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Quoted arguments must keep their whitespace and survive a join/tokenize round trip.
+    #[kernel_test]
+    fn tokenize_quoted() {
+        let args = tokenize("  every 10 \"gpio_on 5\"  \"\" x").unwrap();
+        assert_eq!(args, ["every", "10", "gpio_on 5", "", "x"]);
+        assert_eq!(tokenize(&join(&args)).unwrap(), args);
+
+        assert!(tokenize("echo \"open").is_err());
+        assert!(tokenize("   ").unwrap().is_empty());
+    }
+}
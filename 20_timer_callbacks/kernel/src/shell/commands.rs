@@ -0,0 +1,1291 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! The kernel's commands.
+
+use super::{join, register_command, script};
+#[cfg(feature = "c_runtime")]
+use crate::crt;
+use crate::{
+    bench, block, bluetooth, bsp, build_config, capture, chainload, clocking, config,
+    console::{self, line_discipline},
+    cpu, diag, dma, driver, exception, identity, info, jobs, latency, log, memory, motor, net,
+    pattern, power, process, rand, rc, sched, session, shutdown, siggen, stats, subsys, syscall,
+    sysreg, time, trace, usb, watchdog,
+};
+use alloc::{string::String, vec};
+use core::{fmt::Write as _, time::Duration};
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn level(_args: &[&str]) -> Result<(), &'static str> {
+    let (_, privilege_level) = exception::current_privilege_level();
+    info!("Current privilege level: {}", privilege_level);
+
+    Ok(())
+}
+
+fn siggen(args: &[&str]) -> Result<(), &'static str> {
+    let force = args.contains(&"--force");
+    let pin = args.get(1).and_then(|p| p.parse::<u8>().ok());
+    let waveform = args.get(2).map(|w| w.parse::<siggen::Waveform>());
+    let freq = args.get(3).and_then(|f| f.parse::<f32>().ok());
+    match (args.get(1), pin, waveform, freq) {
+        (None, ..) => {
+            info!("Signal generator:");
+            siggen::print_status();
+        }
+        (Some(&"stop"), ..) => siggen::stop(),
+        (_, Some(pin), Some(Ok(waveform)), Some(freq)) => {
+            siggen::start(pin, waveform, freq, force)?;
+            info!("{} at {} Hz on pin {}", waveform, freq, pin);
+        }
+        (_, _, Some(Err(x)), _) => return Err(x),
+        _ => info!("Usage: siggen [stop | <pin> <square|ramp> <freq> [--force]]"),
+    }
+
+    Ok(())
+}
+
+fn board_name(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Booting on: {}", bsp::board_name());
+
+    Ok(())
+}
+
+fn timer_overload(args: &[&str]) -> Result<(), &'static str> {
+    let result = match args.get(1) {
+        Some(policy) => policy.parse().and_then(|policy| {
+            time::time_manager().set_overload_policy(policy);
+            time::time_manager().store_overload_policy()
+        }),
+        None => Ok(()),
+    };
+    match result {
+        Ok(()) => {
+            info!("Timer overload:");
+            time::time_manager().print_overload();
+        }
+        Err(x) => info!("timer_overload: {} (log|skip|coalesce)", x),
+    }
+
+    Ok(())
+}
+
+fn timer_resolution(_args: &[&str]) -> Result<(), &'static str> {
+    info!(
+        "Architectural timer resolution: {}",
+        time::Human(time::time_manager().resolution())
+    );
+
+    Ok(())
+}
+
+fn timer_calibration(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        None => (),
+        Some("run") => {
+            if let Err(x) = time::calibrate() {
+                info!("timer_calibration: {}", x);
+                return Ok(());
+            }
+        }
+        Some(_) => {
+            info!("Usage: timer_calibration [run]");
+            return Ok(());
+        }
+    }
+
+    info!("Timer calibration:");
+    time::print_calibration();
+
+    Ok(())
+}
+
+fn latency(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1).copied(), args.get(2).copied()) {
+        (None | Some("report"), None) => {
+            info!("IRQ-masked sections:");
+            latency::print();
+        }
+        (Some("reset"), None) => latency::reset(),
+        (Some("bound"), None) => match latency::bound_us() {
+            0 => info!("Bound: off"),
+            us => info!("Bound: {} us", us),
+        },
+        (Some("bound"), Some(bound)) => {
+            let us = match bound {
+                "off" => 0,
+                us => us.parse().map_err(|_| "Invalid bound")?,
+            };
+            latency::set_bound_us(us);
+            latency::store_bound()?;
+        }
+        _ => info!("Usage: latency [report | reset | bound [<us> | off]]"),
+    }
+
+    Ok(())
+}
+
+fn mmu(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        None => {
+            info!("MMU online:");
+            memory::mmu::kernel_print_mappings();
+        }
+        Some("leaks") => {
+            info!("Leaked mappings:");
+            memory::mmu::kernel_print_mapping_leaks();
+        }
+        Some(_) => info!("Usage: mmu [leaks]"),
+    }
+
+    Ok(())
+}
+
+fn drivers(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        None => {
+            info!("Drivers loaded:");
+            driver::driver_manager().enumerate();
+        }
+        Some("tree") => {
+            info!("Driver dependencies:");
+            driver::driver_manager().print_tree();
+        }
+        Some(_) => info!("Usage: driver [tree]"),
+    }
+
+    Ok(())
+}
+
+fn irq_handler(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Registered IRQ handlers:");
+    exception::asynchronous::irq_manager().print_handler();
+
+    Ok(())
+}
+
+fn sysreg(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1), args.get(2)) {
+        (Some(&"read"), Some(name)) => match sysreg::find(name) {
+            Some(reg) => reg.print(),
+            None => info!("sysreg: Unknown register {}", name),
+        },
+        (Some(&"list"), _) => {
+            info!("System registers:");
+            sysreg::print_list();
+        }
+        _ => info!("Usage: sysreg list | read <name>"),
+    }
+
+    Ok(())
+}
+
+/// `at <hh:mm:ss> <command>` and `every <interval> <command>`.
+fn schedule(args: &[&str]) -> Result<(), &'static str> {
+    let out = console::output();
+    let job_command = join(args.get(2..).unwrap_or(&[]));
+    let id = match args.get(1) {
+        Some(t) if args[0] == "at" => {
+            jobs::parse_time_of_day(t).and_then(|at| jobs::schedule_at(out, at, &job_command))
+        }
+        Some(t) => jobs::parse_interval(t)
+            .and_then(|interval| jobs::schedule_every(out, interval, &job_command)),
+        None => Err("Missing time"),
+    }?;
+    info!("Job {} scheduled", id);
+
+    Ok(())
+}
+
+fn jobs(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1), args.get(2).and_then(|id| id.parse().ok())) {
+        (None, _) => {
+            info!("Jobs:");
+            jobs::print();
+        }
+        (Some(&"cancel"), Some(id)) => jobs::cancel(id)?,
+        _ => info!("Usage: jobs [cancel <id>]"),
+    }
+
+    Ok(())
+}
+
+/// `kill <pid>` ends a process, `kill %<id>` a background task or job, like in a Unix shell.
+fn kill(args: &[&str]) -> Result<(), &'static str> {
+    let arg = args.get(1).copied().unwrap_or("");
+    match arg.strip_prefix('%').map(|id| id.parse()) {
+        Some(Ok(id)) => jobs::cancel(id)?,
+        Some(Err(_)) => info!("Usage: kill <pid> | %<id>"),
+        None => match arg.parse() {
+            Ok(pid) => {
+                process::kill(pid)?;
+                info!("Killed {}", pid);
+            }
+            Err(_) => info!("Usage: kill <pid> | %<id>"),
+        },
+    }
+
+    Ok(())
+}
+
+fn ps(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Processes:");
+    process::print_table();
+
+    Ok(())
+}
+
+fn run(args: &[&str]) -> Result<(), &'static str> {
+    // `run` takes the script as its arguments, the other commands are the script's first word.
+    let source = match args[0] {
+        "run" => join(&args[1..]),
+        _ => join(args),
+    };
+    if source.is_empty() {
+        info!("Usage: run <script>");
+        return Ok(());
+    }
+
+    let id = script::start(&source)?;
+    info!("Script running as task {}", id);
+
+    Ok(())
+}
+
+fn scripts(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1), args.get(2).and_then(|id| id.parse().ok())) {
+        (None, _) => {
+            info!("Scripts:");
+            script::print();
+        }
+        (Some(&"stop"), Some(id)) => script::stop(id)?,
+        _ => info!("Usage: scripts [stop <id>]"),
+    }
+
+    Ok(())
+}
+
+fn tasks(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Tasks:");
+    info!(
+        "      {:>3}  {:<9} {:>4} {:>8}  {}",
+        "id", "state", "prio", "switches", "name"
+    );
+    sched::print();
+
+    Ok(())
+}
+
+/// Start a program at EL0, with an optional number for x0, e.g. the pin for `blink`.
+fn exec(args: &[&str]) -> Result<(), &'static str> {
+    let name = match args.get(1) {
+        Some(name) => *name,
+        None => {
+            info!("Programs:");
+            process::print_programs();
+            info!("Running:");
+            process::print();
+            return Ok(());
+        }
+    };
+    let arg = match args.get(2) {
+        Some(arg) => arg.parse::<u64>().map_err(|_| "Invalid argument")?,
+        None => 0,
+    };
+
+    let id = process::spawn(name, arg)?;
+    info!("Process {} ({})", id, name);
+
+    Ok(())
+}
+
+fn watchdog(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1).copied(), args.get(2).copied()) {
+        (None, _) => {
+            info!("Watchdog:");
+            watchdog::print();
+        }
+        (Some("hardware"), Some("on")) => watchdog::configure_hardware(true)?,
+        (Some("hardware"), Some("off")) => watchdog::configure_hardware(false)?,
+        _ => info!("Usage: watchdog [hardware <on|off>]"),
+    }
+
+    Ok(())
+}
+
+fn config_show(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Kernel configuration:");
+    build_config::print();
+
+    Ok(())
+}
+
+fn identity(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1).copied(), args.get(2).copied()) {
+        (None, _) => {
+            identity::print();
+            Ok(())
+        }
+        (Some("hostname"), Some(name)) => identity::set_hostname(name),
+        (Some("prefix"), Some(v @ ("on" | "off"))) => {
+            identity::set_log_prefix(v == "on");
+            config::store().set("identity.log_prefix", v)
+        }
+        _ => {
+            info!("Usage: identity [hostname <name> | prefix <on|off>]");
+            Ok(())
+        }
+    }
+}
+
+fn config(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        None => {
+            info!("Settings:");
+            config::store().print();
+            Ok(())
+        }
+        Some("set") if args.len() == 4 => config::store().set(args[2], args[3]),
+        Some("set") if args.len() == 5 && args[2] == "--board" => {
+            config::store().set_board(args[3], args[4])
+        }
+        Some("unset") if args.len() == 3 => config::store().remove(args[2]),
+        Some("save") => config::store().save().map(|()| info!("Settings saved")),
+        _ => {
+            info!("Usage: config [set [--board] <key> <value> | unset <key> | save]");
+            Ok(())
+        }
+    }
+}
+
+fn console(args: &[&str]) -> Result<(), &'static str> {
+    let mut options = line_discipline::options();
+    args[1..].chunks(2).try_for_each(|pair| match pair {
+        [name, value] => options.set(name, value),
+        _ => {
+            Err("Usage: console [cr_to_lf on|off] [lf_to_crlf on|off] [control raw|caret|hex|drop]")
+        }
+    })?;
+
+    if args.len() > 1 {
+        line_discipline::set_options(options);
+        line_discipline::store()?;
+    }
+    info!("Console options:");
+    line_discipline::print();
+
+    Ok(())
+}
+
+fn baud(args: &[&str]) -> Result<(), &'static str> {
+    let con = console::console();
+    let line = con.line_settings().ok_or("Not a serial console")?;
+
+    if let Some(rate) = args.get(1) {
+        let baud = rate
+            .parse()
+            .map_err(|_| "Usage: baud [<rate> [none|even|odd] [1|2]]")?;
+        let parity = match args.get(2) {
+            Some(x) => x.parse()?,
+            None => line.parity,
+        };
+        let stop_bits = match args.get(3).copied() {
+            None => line.stop_bits,
+            Some("1") => 1,
+            Some("2") => 2,
+            Some(_) => return Err("Expected 1 or 2 stop bits"),
+        };
+
+        // Announced at the old settings, so that the other side of the link knows what to switch
+        // to.
+        let new = console::LineSettings {
+            baud,
+            parity,
+            stop_bits,
+        };
+        info!("Switching the console to {}", new);
+        con.set_baud(new.baud)?;
+        con.set_parity(new.parity)?;
+        con.set_stop_bits(new.stop_bits)?;
+    }
+
+    if let Some(line) = con.line_settings() {
+        info!("Console: {}", line);
+    }
+
+    Ok(())
+}
+
+fn sinks(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1).copied(), args.get(2)) {
+        (None, _) => (),
+        (Some("on"), Some(name)) => console::set_sink_enabled(name, true)?,
+        (Some("off"), Some(name)) => console::set_sink_enabled(name, false)?,
+        _ => return Err("Usage: sinks [on|off <name>]"),
+    }
+
+    info!("Console sinks:");
+    console::print_sinks();
+
+    Ok(())
+}
+
+fn sink_log(args: &[&str]) -> Result<(), &'static str> {
+    let log = &console::log_console::LOG_CONSOLE;
+
+    match args.get(1).copied() {
+        None => {
+            // Straight to the session, so that the dump doesn't land in the log again.
+            let contents = log.contents();
+            console::output()
+                .write_fmt(format_args!("{}", String::from_utf8_lossy(&contents)))
+                .map_err(|_| "Write failed")?;
+        }
+        Some("clear") => log.clear(),
+        _ => return Err("Usage: log [clear]"),
+    }
+
+    Ok(())
+}
+
+fn dmesg(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        None => {
+            // Straight to the session, so that the dump isn't logged again.
+            let out = console::output();
+            for entry in log::snapshot() {
+                out.write_fmt(format_args!(
+                    "[{} {}] {}\n",
+                    entry.level.marker(),
+                    time::Seconds(entry.timestamp),
+                    entry.text
+                ))
+                .map_err(|_| "Write failed")?;
+            }
+            if log::dropped() > 0 {
+                info!("{} older lines dropped", log::dropped());
+            }
+        }
+        Some("clear") => log::clear(),
+        _ => return Err("Usage: dmesg [clear]"),
+    }
+
+    Ok(())
+}
+
+fn stats(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        Some("boot") => {
+            info!("Boot statistics:");
+            stats::print_boot();
+        }
+        _ => info!("Usage: stats boot"),
+    }
+
+    Ok(())
+}
+
+fn subsys(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1), args.get(2)) {
+        (None, _) => {
+            info!("Subsystems:");
+            subsys::print();
+        }
+        (Some(&"restart"), Some(name)) => {
+            subsys::restart(name)?;
+            info!("Restarted {}", name);
+        }
+        _ => info!("Usage: subsys [restart <name>]"),
+    }
+
+    Ok(())
+}
+
+fn shutdown(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Shutdown hooks:");
+    shutdown::print();
+
+    Ok(())
+}
+
+#[cfg(feature = "c_runtime")]
+fn cexec(args: &[&str]) -> Result<(), &'static str> {
+    let name = match args.get(1) {
+        Some(name) => *name,
+        None => {
+            info!("C entry points:");
+            for name in crt::entries() {
+                info!("      {}", name);
+            }
+            return Ok(());
+        }
+    };
+
+    // C code may run for long, which it mustn't do in the console's IRQ handler.
+    if exception::asynchronous::is_local_irq_masked() {
+        let id = jobs::spawn(console::output(), &join(args))?;
+        info!("[{}] {}", id, join(args));
+        return Ok(());
+    }
+
+    let status = crt::run(name, &args[1..])?;
+    info!("{} exited with {}", name, status);
+
+    Ok(())
+}
+
+fn block(args: &[&str]) -> Result<(), &'static str> {
+    let device = block::lookup;
+    let seed = args
+        .get(3)
+        .map_or(Ok(0), |s| s.parse::<u32>())
+        .map_err(|_| "Invalid seed");
+
+    match (args.get(1), args.get(2)) {
+        (None, _) => {
+            info!("Block devices:");
+            block::print();
+        }
+        (Some(&"ramdisk"), Some(kib)) => {
+            let kib: usize = kib.parse().map_err(|_| "Invalid size")?;
+            let name = block::create_ram_disk(kib * 1024 / block::BLOCK_SIZE)?;
+            info!("Created {}", name);
+        }
+        (Some(&"fill"), Some(name)) => {
+            let blocks = block::fill(device(name)?, seed?)?;
+            info!("Filled {} blocks of {}", blocks, name);
+        }
+        (Some(&"verify"), Some(name)) => match block::verify(device(name)?, seed?)? {
+            0 => info!("{} verified", name),
+            bad => info!("{}: {} blocks differ", name, bad),
+        },
+        (Some(&"hexdump"), Some(name)) => {
+            let lba = args.get(3).map_or(Ok(0), |l| l.parse::<u64>());
+            let count = args.get(4).map_or(Ok(1), |c| c.parse::<usize>());
+            match (lba, count) {
+                (Ok(lba), Ok(count @ 1..=16)) => block::hexdump(device(name)?, lba, count)?,
+                _ => info!("Usage: block hexdump <device> [<lba> [<1-16 blocks>]]"),
+            }
+        }
+        _ => info!(
+            "Usage: block [ramdisk <KiB>|fill <device> [seed]|verify <device> [seed]|\
+             hexdump <device> [<lba> [blocks]]]"
+        ),
+    }
+
+    Ok(())
+}
+
+fn usb(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1), args.get(2)) {
+        (None, _) => {
+            info!("USB:");
+            usb::print()?;
+        }
+        (Some(&"export"), Some(name)) => {
+            usb::export(name)?;
+            info!("Exporting {}", name);
+        }
+        (Some(&"serial"), None) => {
+            usb::serial()?;
+            info!("Serial console started");
+        }
+        (Some(&"host"), None) => {
+            usb::host::start()?;
+            info!("USB keyboard attached");
+        }
+        (Some(&"stop"), None) => usb::stop()?,
+        _ => info!("Usage: usb [export <device>|serial|host|stop]"),
+    }
+
+    Ok(())
+}
+
+fn diag(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Hardware diagnostics:");
+    diag::run().print();
+
+    Ok(())
+}
+
+fn reboot(args: &[&str]) -> Result<(), &'static str> {
+    if args[0] == "halt" {
+        info!("Halting");
+        cpu::halt()
+    }
+
+    info!("Rebooting");
+    cpu::reboot()
+}
+
+fn rand(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).map(|n| n.parse::<usize>()) {
+        None => {
+            info!("Entropy pool:");
+            rand::print();
+        }
+        Some(Ok(count @ 1..=256)) => {
+            let mut buf = [0u8; 256];
+            rand::fill(&mut buf[..count]);
+            for chunk in buf[..count].chunks(16) {
+                let mut line = String::new();
+                for b in chunk {
+                    let _ = write!(line, "{:02x}", b);
+                }
+                info!("      {}", line);
+            }
+        }
+        _ => info!("Usage: rand [1-256]"),
+    }
+
+    Ok(())
+}
+
+fn capture(args: &[&str]) -> Result<(), &'static str> {
+    let pin = || {
+        args.get(2)
+            .and_then(|x| x.parse().ok())
+            .ok_or("Usage: capture [start|stop <pin>]")
+    };
+
+    match args.get(1).copied() {
+        Some("start") => capture::start(pin()?),
+        Some("stop") => capture::stop(pin()?),
+        Some(_) => Err("Usage: capture [start|stop <pin>]"),
+        None => {
+            info!("Pulse capture:");
+            capture::print();
+            Ok(())
+        }
+    }
+}
+
+fn rc(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1).copied(), args.get(2).map(|x| x.parse())) {
+        (Some("ppm"), Some(Ok(pin))) => rc::start_ppm(pin),
+        (Some("stop"), None) => {
+            rc::stop_ppm();
+            Ok(())
+        }
+        (None, _) => {
+            info!("RC receiver:");
+            rc::print();
+            Ok(())
+        }
+        _ => Err("Usage: rc [ppm <pin>|stop]"),
+    }
+}
+
+fn motor(args: &[&str]) -> Result<(), &'static str> {
+    const USAGE: &str =
+        "Usage: motor [add <in1> <in2> <pwm pin> [--force] | <id> <speed|brake|coast> | stop | release | button <pin|none>]";
+    let num = |i: usize| args.get(i).and_then(|x| x.parse::<u8>().ok()).ok_or(USAGE);
+
+    match args.get(1).copied() {
+        None => {
+            info!("Motors:");
+            motor::print();
+        }
+        Some("add") => {
+            let id = motor::add(num(2)?, num(3)?, num(4)?, args.contains(&"--force"))?;
+            info!("Added motor {}", id);
+        }
+        Some("stop") => motor::emergency_stop(),
+        Some("release") => motor::release(),
+        Some("button") => match args.get(2).copied() {
+            Some("none") => motor::set_stop_button(None)?,
+            _ => motor::set_stop_button(Some(num(2)?))?,
+        },
+        Some(id) => {
+            let id = id.parse().map_err(|_| USAGE)?;
+            match args.get(2).copied() {
+                Some("brake") => motor::brake(id)?,
+                Some("coast") => motor::coast(id)?,
+                Some(speed) => motor::set_speed(id, speed.parse().map_err(|_| USAGE)?)?,
+                None => return Err(USAGE),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn bench(args: &[&str]) -> Result<(), &'static str> {
+    let usage = "Usage: bench coremark [<iterations>]";
+
+    match (args.get(1).copied(), args.get(2)) {
+        (Some("coremark"), iterations) => {
+            let iterations = iterations
+                .map(|n| n.parse().map_err(|_| usage))
+                .transpose()?;
+            let id = bench::coremark(iterations)?;
+            info!("CoreMark running as task {}", id);
+        }
+        _ => info!("{}", usage),
+    }
+
+    Ok(())
+}
+
+fn dma(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1), args.get(2).map(|k| k.parse::<usize>())) {
+        (None, _) => {
+            info!("DMA:");
+            dma::print();
+        }
+        (Some(&"bench"), Some(Ok(kib @ 1..=4096))) => {
+            info!("Filling {} KiB:", kib);
+            dma::benchmark(kib * 1024);
+        }
+        (Some(&"copy"), Some(Ok(kib @ 1..=4096))) => dma::copy_test(kib * 1024)?,
+        _ => info!("Usage: dma [bench <1-4096 KiB> | copy <1-4096 KiB>]"),
+    }
+
+    Ok(())
+}
+
+fn session(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1).copied(), args.get(2)) {
+        (None, _) => {
+            info!("Session:");
+            session::print();
+        }
+        (Some("start"), None) => {
+            session::start();
+            info!("Recording");
+        }
+        (Some("stop"), None) => session::stop(),
+        (Some("save"), Some(device)) => {
+            let len = session::save(device)?;
+            info!("Saved {} Byte to {}", len, device);
+        }
+        (Some("load"), Some(device)) => {
+            let lines = session::load(device)?;
+            info!("Loaded {} lines from {}", lines, device);
+        }
+        _ => info!("Usage: session [start | stop | save <device> | load <device>]"),
+    }
+
+    Ok(())
+}
+
+/// Replay the session, or one saved on a block device, at a speed given as e.g. `10x`.
+fn replay(args: &[&str]) -> Result<(), &'static str> {
+    let usage = "Usage: replay [buffer | <device>] [<speed>x] | replay stop";
+    let speed = match args.iter().skip(1).find_map(|a| a.strip_suffix('x')) {
+        Some(speed) => speed.parse().map_err(|_| usage)?,
+        None => 1,
+    };
+
+    match args.get(1).copied() {
+        Some("stop") => {
+            session::stop_replay();
+            return Ok(());
+        }
+        None | Some("buffer") => (),
+        Some(device) if !device.ends_with('x') => {
+            let lines = session::load(device)?;
+            info!("Loaded {} lines from {}", lines, device);
+        }
+        Some(_) => (),
+    }
+
+    session::replay(console::output(), speed)?;
+    info!("Replaying at {}x", speed);
+
+    Ok(())
+}
+
+fn syscalls(_args: &[&str]) -> Result<(), &'static str> {
+    info!("System calls:");
+    syscall::print();
+
+    Ok(())
+}
+
+fn kernel_heap(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Kernel heap:");
+    memory::heap_alloc::kernel_heap_allocator().print_usage();
+
+    Ok(())
+}
+
+fn quota(args: &[&str]) -> Result<(), &'static str> {
+    use memory::heap_alloc::quota;
+
+    match (args.get(1), args.get(2)) {
+        (None, _) => {
+            info!("Memory quotas:");
+            quota::print();
+        }
+        (Some(name), Some(limit)) => {
+            let quota = quota::find(name).ok_or("No such quota")?;
+            let limit = match *limit {
+                "none" => None,
+                kib => Some(kib.parse::<usize>().map_err(|_| "Invalid limit")? * 1024),
+            };
+            quota.set_limit(limit);
+            info!("Quota {} set", name);
+        }
+        _ => info!("Usage: quota [<name> <KiB|none>]"),
+    }
+
+    Ok(())
+}
+
+fn heap_test(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).map_or(Ok(10_000), |r| r.parse::<usize>()) {
+        Ok(rounds @ 1..=1_000_000) => {
+            info!("Heap stress test:");
+            memory::heap_alloc::stress_test(rounds)?;
+        }
+        _ => info!("Usage: heap_test [<1-1000000 rounds>]"),
+    }
+
+    Ok(())
+}
+
+fn ping(args: &[&str]) -> Result<(), &'static str> {
+    let count = args.get(2).and_then(|c| c.parse().ok()).unwrap_or(4);
+    match args.get(1).map(|a| a.parse::<net::Ipv4Address>()) {
+        Some(Ok(addr)) => net::diag::ping(addr, count)?,
+        _ => info!("Usage: ping <address> [count]"),
+    }
+
+    Ok(())
+}
+
+fn netload(args: &[&str]) -> Result<(), &'static str> {
+    match (
+        args.get(1).map(|a| a.parse::<net::Ipv4Address>()),
+        args.get(2),
+    ) {
+        (Some(Ok(server)), Some(filename)) => {
+            info!("Fetching {} from {}", filename, server);
+            chainload::netload(server, filename)?;
+        }
+        _ => info!("Usage: netload <server> <file>"),
+    }
+
+    Ok(())
+}
+
+fn traceroute(args: &[&str]) -> Result<(), &'static str> {
+    let max_hops = args.get(2).and_then(|h| h.parse().ok()).unwrap_or(30);
+    match args.get(1).map(|a| a.parse::<net::Ipv4Address>()) {
+        Some(Ok(addr)) => net::diag::traceroute(addr, max_hops)?,
+        _ => info!("Usage: traceroute <address> [max_hops]"),
+    }
+
+    Ok(())
+}
+
+fn trace(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        None => {
+            info!("Trace buffer:");
+            trace::trace_buffer().print();
+        }
+        Some("clear") => trace::trace_buffer().clear()?,
+        _ => info!("Usage: trace [clear]"),
+    }
+
+    Ok(())
+}
+
+fn hci(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        Some("info") => {
+            info!("Bluetooth controller:");
+            bluetooth::print_info()?;
+        }
+        Some("reset") => {
+            bluetooth::reset()?;
+            info!("Bluetooth controller reset");
+        }
+        Some("load") => {
+            let number = |i: usize| args.get(i).and_then(|a| a.parse::<u64>().ok());
+            match (args.get(2), number(3), number(4)) {
+                (Some(device), Some(lba), Some(len)) => {
+                    bluetooth::load_firmware_from(block::lookup(device)?, lba, len as usize)?;
+                    info!("Bluetooth firmware patched");
+                }
+                _ => info!("Usage: hci load <device> <lba> <bytes>"),
+            }
+        }
+        _ => info!("Usage: hci <info|reset|load <device> <lba> <bytes>>"),
+    }
+
+    Ok(())
+}
+
+fn ble(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        Some("start") => {
+            bluetooth::peripheral::start().map(|()| info!("BLE LED service advertising"))
+        }
+        Some("stop") => bluetooth::peripheral::stop().map(|()| info!("BLE LED service stopped")),
+        _ => {
+            info!("BLE peripheral:");
+            bluetooth::peripheral::print_status();
+            Ok(())
+        }
+    }
+}
+
+fn wifi(args: &[&str]) -> Result<(), &'static str> {
+    let number = |i: usize| args.get(i).and_then(|a| a.parse::<u64>().ok());
+
+    match args.get(1).copied() {
+        Some("scan") => {
+            info!("Scanning for access points:");
+            net::wifi::print_scan()
+        }
+        Some("load") => match (args.get(2), number(3), number(4), number(5)) {
+            (Some(device), Some(lba), Some(firmware_len), Some(nvram_len)) => {
+                net::wifi::load_firmware_from(
+                    block::lookup(device)?,
+                    lba,
+                    firmware_len as usize,
+                    nvram_len as usize,
+                )?;
+                info!("Wi-Fi firmware running");
+                Ok(())
+            }
+            _ => {
+                info!("Usage: wifi load <device> <lba> <firmware bytes> <nvram bytes>");
+                Ok(())
+            }
+        },
+        _ => {
+            info!("Wi-Fi:");
+            net::wifi::print_info()
+        }
+    }
+}
+
+fn net(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        None => {
+            info!("Network interfaces:");
+            net::net_stack().print_interfaces();
+        }
+        Some("stats") => {
+            info!("Network interface statistics:");
+            net::net_stack().print_stats();
+        }
+        Some("set") => {
+            let parse = |i: usize| args.get(i).map(|a| a.parse::<net::Ipv4Address>());
+            match (args.get(2), parse(3), parse(4), parse(5)) {
+                (Some(name), Some(Ok(address)), Some(Ok(netmask)), None | Some(Ok(_))) => {
+                    let gateway = parse(5).and_then(Result::ok);
+                    let config = net::Ipv4Config {
+                        address,
+                        netmask,
+                        gateway,
+                    };
+
+                    net::net_stack().configure_interface(name, config)?;
+                    net::net_stack().store_config(name)?;
+                    info!("{} is {}/{}", name, address, netmask);
+                }
+                _ => info!("Usage: net set <interface> <address> <netmask> [gateway]"),
+            }
+        }
+        Some(_) => info!("Usage: net [stats | set <interface> <address> <netmask> [gateway]]"),
+    }
+
+    Ok(())
+}
+
+fn arp(args: &[&str]) -> Result<(), &'static str> {
+    match args.get(1).copied() {
+        None => {
+            info!("Neighbor cache:");
+            net::net_stack().print_neighbors();
+        }
+        Some("flush") => {
+            net::net_stack().flush_neighbors();
+            info!("Neighbor cache flushed");
+        }
+        Some("add") => match (
+            args.get(2).map(|a| a.parse::<net::Ipv4Address>()),
+            args.get(3).map(|m| m.parse::<net::MacAddress>()),
+        ) {
+            (Some(Ok(ip)), Some(Ok(mac))) => {
+                net::net_stack().add_static_neighbor(ip, mac)?;
+                info!("{} is at {}", ip, mac);
+            }
+            _ => info!("Usage: arp add <address> <mac>"),
+        },
+        Some(_) => info!("Usage: arp [add <address> <mac> | flush]"),
+    }
+
+    Ok(())
+}
+
+fn standby(args: &[&str]) -> Result<(), &'static str> {
+    let seconds = match args.get(1).map(|s| s.parse::<u64>()) {
+        Some(Ok(s)) if s > 0 => s,
+        _ => {
+            info!("Usage: standby <seconds>");
+            return Ok(());
+        }
+    };
+
+    // Commands entered on the console run in its IRQ handler, where the timer can't wake the
+    // core. Run from the idle loop instead.
+    if !power::can_standby() {
+        let id = jobs::spawn(console::output(), &join(args))?;
+        info!("[{}] {}", id, join(args));
+        return Ok(());
+    }
+
+    info!("Standby for {} s", seconds);
+    console::output().flush();
+    let report = power::standby(Duration::from_secs(seconds))?;
+    info!("Awake:");
+    report.print();
+
+    Ok(())
+}
+
+fn clock(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1), args.get(2).map(|a| a.parse::<u32>())) {
+        (None, _) => {
+            info!("Clocks:");
+            clocking::print()?;
+        }
+        (Some(&"revert"), None) => clocking::revert()?,
+        (Some(&"limit"), Some(Ok(celsius))) => {
+            clocking::set_limit(celsius)?;
+            info!("Limit set to {} C", celsius);
+        }
+        (Some(name), Some(Ok(mhz))) if *name != "limit" => {
+            let clock = name.parse::<clocking::Clock>()?;
+            let hz = clocking::set(clock, mhz.saturating_mul(1_000_000))?;
+            info!("{} clock at {} MHz", clock, hz / 1_000_000);
+        }
+        _ => info!("Usage: clock [<arm|core> <MHz> | limit <C> | revert]"),
+    }
+
+    Ok(())
+}
+
+fn counter(args: &[&str]) -> Result<(), &'static str> {
+    let (title, pattern) = match args[0] {
+        "hex_counter" => ("Hex Counter:", pattern::Pattern::Hex),
+        "left_counter" => ("Left Counter:", pattern::Pattern::Left),
+        _ => ("Right Counter:", pattern::Pattern::Right),
+    };
+    let mut patterns = vec![pattern];
+    let mut pins = vec![];
+
+    // Pins follow `on`, further patterns follow `then`.
+    let mut section = None;
+    for arg in &args[1..] {
+        match (*arg, section) {
+            ("on" | "then", _) => section = Some(*arg),
+            (pin, Some("on")) => pins.push(pin.parse::<u8>().map_err(|_| "Invalid pin")?),
+            (name, Some(_)) => patterns.push(name.parse()?),
+            _ => section = None,
+        }
+        if section.is_none() {
+            info!(
+                "Usage: {} [on <pin>...] [then <hex|left|right>...]",
+                args[0]
+            );
+            return Ok(());
+        }
+    }
+
+    // Without pins, the counter replaces whatever runs on its default pins.
+    let pins = if pins.is_empty() {
+        let pins = pattern::default_pins(&patterns);
+        pattern::stop_on(pins);
+        pins
+    } else {
+        &pins[..]
+    };
+
+    info!("{}", title);
+    let id = pattern::start_chain(&patterns, Some(pins), None)?;
+    info!("Pattern {} on pins {:?}", id, pins);
+
+    Ok(())
+}
+
+fn pattern(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1), args.get(2).map(|a| a.parse::<u32>())) {
+        (None, _) => {
+            info!("Running patterns:");
+            pattern::print();
+            info!("Brightness: {} %", pattern::brightness());
+        }
+        (Some(&"stop"), None) => {
+            pattern::stop();
+            pattern::reset_pins();
+        }
+        (Some(&"stop"), Some(Ok(id))) => pattern::stop_id(id as usize)?,
+        (Some(&"brightness"), None) => info!("Brightness: {} %", pattern::brightness()),
+        (Some(&"brightness"), Some(Ok(percent))) => {
+            pattern::set_brightness(percent)?;
+            pattern::store_brightness()?;
+            info!("Brightness: {} %", percent);
+        }
+        _ => info!("Usage: pattern [stop [<id>] | brightness [<percent>]]"),
+    }
+
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Register the kernel's commands.
+pub fn register() -> Result<(), &'static str> {
+    let commands: &[(&'static str, &'static str, super::Handler)] = &[
+        ("level", "Print the current privilege level", level),
+        ("siggen", "Generate a waveform on a pin", siggen),
+        ("board_name", "Print the board name", board_name),
+        (
+            "timer_overload",
+            "Show or set the timer overload policy",
+            timer_overload,
+        ),
+        (
+            "timer_resolution",
+            "Print the timer resolution",
+            timer_resolution,
+        ),
+        (
+            "timer_calibration",
+            "Show or redo the timer frequency calibration",
+            timer_calibration,
+        ),
+        (
+            "mmu",
+            "Print the kernel's MMU mappings or the leaked ones",
+            mmu,
+        ),
+        (
+            "latency",
+            "Report the longest IRQ-masked sections or bound them",
+            latency,
+        ),
+        (
+            "driver",
+            "List the loaded drivers or their dependencies",
+            drivers,
+        ),
+        (
+            "irq_handler",
+            "List the registered IRQ handlers",
+            irq_handler,
+        ),
+        ("sysreg", "List or read system registers", sysreg),
+        ("at", "Run a command at a time of day", schedule),
+        ("every", "Run a command periodically", schedule),
+        ("jobs", "List or cancel scheduled jobs", jobs),
+        ("session", "Record, save or load a shell session", session),
+        ("replay", "Replay a recorded shell session", replay),
+        (
+            "kill",
+            "Kill a process, or a background task or job with %",
+            kill,
+        ),
+        ("ps", "List the processes", ps),
+        ("tasks", "List the scheduler's tasks", tasks),
+        ("exec", "List or start the programs that run at EL0", exec),
+        ("run", "Run a script in the background", run),
+        ("repeat", "Run a block a number of times", run),
+        ("while", "Run a block while a command succeeds", run),
+        ("if", "Run a block if a command succeeds", run),
+        ("scripts", "List or stop running scripts", scripts),
+        (
+            "watchdog",
+            "Show the watchdog or toggle the hardware one",
+            watchdog,
+        ),
+        ("config_show", "Print the kernel configuration", config_show),
+        ("identity", "Show or set the board identity", identity),
+        ("config", "Show, change or save settings", config),
+        ("console", "Show or set console options", console),
+        ("baud", "Show or set the console's line settings", baud),
+        ("sinks", "Show or switch console sinks", sinks),
+        ("log", "Print or clear the in-memory log", sink_log),
+        ("dmesg", "Print or clear the kernel log", dmesg),
+        ("stats", "Print boot statistics", stats),
+        ("subsys", "List or restart subsystems", subsys),
+        ("shutdown", "List the shutdown hooks", shutdown),
+        ("diag", "Run the hardware diagnostics", diag),
+        ("reboot", "Run the shutdown hooks and restart", reboot),
+        ("halt", "Run the shutdown hooks and halt", reboot),
+        ("rand", "Show the entropy pool or print random bytes", rand),
+        (
+            "capture",
+            "Measure pulse widths and periods on pins",
+            capture,
+        ),
+        ("rc", "Show RC receiver channels or decode PPM", rc),
+        ("motor", "Drive motors or trigger an emergency stop", motor),
+        ("dma", "Show DMA offload or benchmark fills and copies", dma),
+        (
+            "block",
+            "List, create, fill, verify or dump block devices",
+            block,
+        ),
+        (
+            "usb",
+            "Export a device over USB or attach a USB keyboard",
+            usb,
+        ),
+        ("bench", "Run a benchmark", bench),
+        (
+            "syscalls",
+            "List the system calls and ABI features",
+            syscalls,
+        ),
+        ("kernel_heap", "Print kernel heap usage", kernel_heap),
+        ("quota", "Show or set per-subsystem memory quotas", quota),
+        (
+            "heap_test",
+            "Stress the kernel heap with alloc/free cycles",
+            heap_test,
+        ),
+        ("ping", "Send ICMP echo requests", ping),
+        ("traceroute", "Trace the route to a host", traceroute),
+        ("netload", "Boot a kernel image fetched over TFTP", netload),
+        ("trace", "Print or clear the trace buffer", trace),
+        ("hci", "Bluetooth controller info, reset or patch", hci),
+        ("ble", "Start or stop the BLE LED service", ble),
+        ("wifi", "Show Wi-Fi status, load its firmware or scan", wifi),
+        ("net", "Show or configure network interfaces", net),
+        ("arp", "Show or change the neighbor cache", arp),
+        ("standby", "Sleep with only the timer enabled", standby),
+        ("clock", "Show or change the ARM and core clocks", clock),
+        ("hex_counter", "Count in hex on the LED ring", counter),
+        ("left_counter", "Run the LED ring to the left", counter),
+        ("right_counter", "Run the LED ring to the right", counter),
+        (
+            "pattern",
+            "List, stop or dim the running LED patterns",
+            pattern,
+        ),
+    ];
+
+    for (name, help, handler) in commands {
+        register_command(name, help, *handler)?;
+    }
+
+    #[cfg(feature = "c_runtime")]
+    register_command("cexec", "List or run the linked C entry points", cexec)?;
+
+    Ok(())
+}
@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Shell scripts.
+//!
+//! A script is a sequence of commands separated by `;` or line breaks, with a few statements of
+//! its own:
+//!
+//! - `wait <interval>` pauses, for milliseconds if the interval has no unit.
+//! - `repeat <n> { ... }` runs the block `n` times.
+//! - `while [!] <command> { ... }` runs the block as long as the command succeeds, or fails with
+//!   `!`.
+//! - `if [!] <command> { ... } [else { ... }]` runs the first block if the command succeeds, or
+//!   fails with `!`, and the second one otherwise.
+//!
+//! For example: `repeat 10 { gpio_on 18; wait 500; gpio_off 18; wait 500 }`.
+//!
+//! A script is parsed completely before it starts, and runs as a kernel thread, so that `wait`
+//! lets everything else run. Output goes to the registered console. A failing command prints its
+//! error and the script goes on. `scripts stop <id>` ends a script before its next statement.
+//!
+//! The script in the `shell.autoexec` setting is started at boot.
+
+use super::{
+    call,
+    syntax::{self, Condition, Statement},
+    tokenize,
+};
+use crate::{
+    config, info, jobs,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    task, warn,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const KEY_AUTOEXEC: &str = "shell.autoexec";
+
+struct Script {
+    id: usize,
+    source: String,
+    stop: bool,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static SCRIPTS: IRQSafeNullLock<Vec<Script>> = IRQSafeNullLock::new(Vec::new());
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn parse_wait(s: &str) -> Result<Duration, &'static str> {
+    match s.parse() {
+        Ok(ms) => Ok(Duration::from_millis(ms)),
+        Err(_) => jobs::parse_interval(s),
+    }
+}
+
+fn parse(source: &str) -> Result<Vec<Statement>, &'static str> {
+    syntax::script(source, parse_wait)
+}
+
+fn stopped() -> bool {
+    let id = task::current();
+
+    SCRIPTS.lock(|scripts| scripts.iter().any(|s| Some(s.id) == id && s.stop))
+}
+
+/// Run a command line, returning whether it succeeded. Errors are printed unless `quiet`.
+fn run_command(line: &str, quiet: bool) -> bool {
+    let args = match tokenize(line) {
+        Ok(args) => args,
+        Err(x) => {
+            info!("{}", x);
+            return false;
+        }
+    };
+
+    match call(&args) {
+        Some(Ok(())) => true,
+        Some(Err(x)) => {
+            if !quiet {
+                info!("{}: {}", args[0], x);
+            }
+            false
+        }
+        None => {
+            info!("Command not found: {}", args[0]);
+            false
+        }
+    }
+}
+
+impl Condition {
+    fn holds(&self) -> bool {
+        run_command(&self.command, true) != self.negate
+    }
+}
+
+/// Run statements. Returns `false` once the script was stopped.
+fn run_statements(statements: &[Statement]) -> bool {
+    for statement in statements {
+        if stopped() {
+            return false;
+        }
+
+        let go_on = match statement {
+            Statement::Command(line) => {
+                run_command(line, false);
+                true
+            }
+            Statement::Wait(duration) => {
+                task::sleep(*duration);
+                true
+            }
+            Statement::Repeat(count, block) => (0..*count).all(|_| run_statements(block)),
+            Statement::While(condition, block) => {
+                while !stopped() && condition.holds() {
+                    if !run_statements(block) {
+                        return false;
+                    }
+                }
+                true
+            }
+            Statement::If(condition, then, otherwise) => {
+                run_statements(if condition.holds() { then } else { otherwise })
+            }
+        };
+        if !go_on {
+            return false;
+        }
+    }
+
+    true
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Parse `source` and start running it. Returns the script's thread id.
+pub fn start(source: &str) -> Result<usize, &'static str> {
+    let statements = parse(source)?;
+
+    // The registry is locked while spawning, so that the script can't end before it is listed.
+    let id = SCRIPTS.lock(|scripts| {
+        let id = task::spawn("script", move || {
+            run_statements(&statements);
+
+            let id = task::current();
+            SCRIPTS.lock(|scripts| scripts.retain(|s| Some(s.id) != id));
+        })?;
+        scripts.push(Script {
+            id,
+            source: source.to_string(),
+            stop: false,
+        });
+
+        Ok::<_, &'static str>(id)
+    })?;
+
+    Ok(id)
+}
+
+/// Stop script `id` before its next statement.
+pub fn stop(id: usize) -> Result<(), &'static str> {
+    SCRIPTS.lock(|scripts| {
+        let script = scripts
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or("No such script")?;
+        script.stop = true;
+
+        Ok(())
+    })
+}
+
+/// Print the running scripts.
+pub fn print() {
+    SCRIPTS.lock(|scripts| {
+        for s in scripts.iter() {
+            info!(
+                "      {:>3}  {}{}",
+                s.id,
+                s.source,
+                if s.stop { " (stopping)" } else { "" }
+            );
+        }
+    });
+}
+
+/// Start the script in the `shell.autoexec` setting, if any. Must be called after the config
+/// store was loaded and the scheduler was initialized.
+pub fn run_autoexec() {
+    let source = match config::store().get(KEY_AUTOEXEC) {
+        Some(source) if !source.trim().is_empty() => source,
+        _ => return,
+    };
+
+    match start(&source) {
+        Ok(id) => info!("Autoexec script running as task {}", id),
+        Err(x) => warn!("Error starting autoexec script: {}", x),
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Blocks must nest, separators must split statements and quotes must keep them together.
+    #[kernel_test]
+    fn parse_blocks() {
+        let cmd = |s: &str| Statement::Command(s.to_string());
+        let script =
+            parse("repeat 2 { gpio_on 18; wait 500 }\nif ! echo \"a;b\" { x } else { y }").unwrap();
+
+        assert_eq!(
+            script,
+            [
+                Statement::Repeat(
+                    2,
+                    alloc::vec![
+                        cmd("gpio_on 18"),
+                        Statement::Wait(Duration::from_millis(500))
+                    ]
+                ),
+                Statement::If(
+                    Condition {
+                        negate: true,
+                        command: "echo \"a;b\"".to_string()
+                    },
+                    alloc::vec![cmd("x")],
+                    alloc::vec![cmd("y")]
+                ),
+            ]
+        );
+        assert!(parse("repeat 2 { x").is_err());
+        assert!(parse("x }").is_err());
+    }
+}
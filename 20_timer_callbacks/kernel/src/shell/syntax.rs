@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Command line and script syntax.
+//!
+//! Depends on nothing but `core` and `alloc`, so that the fuzz targets can compile it for the host.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Deepest nesting of blocks.
+const MAX_DEPTH: usize = 8;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Open,
+    Close,
+    End,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+
+    /// Parses the interval of `wait`.
+    interval: fn(&str) -> Result<Duration, &'static str>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A command whose success is tested.
+#[derive(Debug, PartialEq)]
+pub struct Condition {
+    pub negate: bool,
+    pub command: String,
+}
+
+/// A statement of a script.
+#[derive(Debug, PartialEq)]
+pub enum Statement {
+    Command(String),
+    Wait(Duration),
+    Repeat(u32, Vec<Statement>),
+    While(Condition, Vec<Statement>),
+    If(Condition, Vec<Statement>, Vec<Statement>),
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Split a script into words and separators. Quoted text stays in its word, quotes included, so
+/// that commands are tokenized as typed.
+fn lex(source: &str) -> Result<Vec<Token>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+
+    let flush = |word: &mut String, tokens: &mut Vec<Token>| {
+        if !word.is_empty() {
+            tokens.push(Token::Word(core::mem::take(word)));
+        }
+    };
+
+    for c in source.chars() {
+        if quoted {
+            word.push(c);
+            quoted = c != '"';
+            continue;
+        }
+
+        match c {
+            '"' => {
+                word.push(c);
+                quoted = true;
+            }
+            '{' | '}' | ';' | '\n' => {
+                flush(&mut word, &mut tokens);
+                tokens.push(match c {
+                    '{' => Token::Open,
+                    '}' => Token::Close,
+                    _ => Token::End,
+                });
+            }
+            c if c.is_whitespace() => flush(&mut word, &mut tokens),
+            c => word.push(c),
+        }
+    }
+    if quoted {
+        return Err("Unterminated quote");
+    }
+    flush(&mut word, &mut tokens);
+
+    Ok(tokens)
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn skip_ends(&mut self) {
+        while self.peek() == Some(&Token::End) {
+            self.pos += 1;
+        }
+    }
+
+    /// Take the words up to the next separator.
+    fn words(&mut self) -> Vec<String> {
+        let mut words = Vec::new();
+        while let Some(Token::Word(w)) = self.peek() {
+            words.push(w.clone());
+            self.pos += 1;
+        }
+
+        words
+    }
+
+    fn block(&mut self, depth: usize) -> Result<Vec<Statement>, &'static str> {
+        if depth >= MAX_DEPTH {
+            return Err("Blocks nested too deeply");
+        }
+        self.skip_ends();
+        if self.peek() != Some(&Token::Open) {
+            return Err("Expected {");
+        }
+        self.pos += 1;
+
+        let statements = self.statements(depth + 1)?;
+        if self.peek() != Some(&Token::Close) {
+            return Err("Missing }");
+        }
+        self.pos += 1;
+
+        Ok(statements)
+    }
+
+    fn condition(&mut self) -> Result<Condition, &'static str> {
+        let mut words = self.words();
+        let negate = words.first().map(String::as_str) == Some("!");
+        if negate {
+            words.remove(0);
+        }
+        if words.is_empty() {
+            return Err("Missing condition");
+        }
+
+        Ok(Condition {
+            negate,
+            command: words.join(" "),
+        })
+    }
+
+    /// Parse statements up to the end of the script or of the enclosing block.
+    fn statements(&mut self, depth: usize) -> Result<Vec<Statement>, &'static str> {
+        let mut statements = Vec::new();
+
+        loop {
+            self.skip_ends();
+            let keyword = match self.peek() {
+                None | Some(Token::Close) => return Ok(statements),
+                Some(Token::Open) => return Err("Unexpected {"),
+                Some(Token::Word(w)) => w.clone(),
+                Some(Token::End) => unreachable!(),
+            };
+
+            let statement = match keyword.as_str() {
+                "repeat" => {
+                    self.pos += 1;
+                    let count = match self.words().as_slice() {
+                        [n] => n.parse().map_err(|_| "Invalid repeat count")?,
+                        _ => return Err("Usage: repeat <n> { ... }"),
+                    };
+                    Statement::Repeat(count, self.block(depth)?)
+                }
+                "while" => {
+                    self.pos += 1;
+                    Statement::While(self.condition()?, self.block(depth)?)
+                }
+                "if" => {
+                    self.pos += 1;
+                    let condition = self.condition()?;
+                    let then = self.block(depth)?;
+                    let save = self.pos;
+                    self.skip_ends();
+                    let otherwise = if self.peek() == Some(&Token::Word("else".to_string())) {
+                        self.pos += 1;
+                        self.block(depth)?
+                    } else {
+                        self.pos = save;
+                        Vec::new()
+                    };
+                    Statement::If(condition, then, otherwise)
+                }
+                "wait" => match self.words().as_slice() {
+                    [_, interval] => Statement::Wait((self.interval)(interval)?),
+                    _ => return Err("Usage: wait <interval>"),
+                },
+                "else" => return Err("else without if"),
+                _ => Statement::Command(self.words().join(" ")),
+            };
+            statements.push(statement);
+
+            match self.peek() {
+                None | Some(Token::End) | Some(Token::Close) => (),
+                _ => return Err("Expected ; after statement"),
+            }
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Split a command line into arguments.
+pub fn tokenize(line: &str) -> Result<Vec<&str>, &'static str> {
+    let mut args = Vec::new();
+    let mut rest = line.trim_start();
+
+    while !rest.is_empty() {
+        let (arg, tail) = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').ok_or("Unterminated quote")?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+        };
+
+        args.push(arg);
+        rest = tail.trim_start();
+    }
+
+    Ok(args)
+}
+
+/// Join arguments back into a command line, quoting where needed.
+pub fn join(args: &[&str]) -> String {
+    let mut line = String::new();
+
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            line.push(' ');
+        }
+        if arg.is_empty() || arg.contains(char::is_whitespace) {
+            line.push('"');
+            line.push_str(arg);
+            line.push('"');
+        } else {
+            line.push_str(arg);
+        }
+    }
+
+    line
+}
+
+/// Parse script `source`. `interval` parses the argument of `wait`.
+pub fn script(
+    source: &str,
+    interval: fn(&str) -> Result<Duration, &'static str>,
+) -> Result<Vec<Statement>, &'static str> {
+    let mut parser = Parser {
+        tokens: lex(source)?,
+        pos: 0,
+        interval,
+    };
+
+    let statements = parser.statements(0)?;
+    if parser.peek().is_some() {
+        return Err("Unmatched }");
+    }
+
+    Ok(statements)
+}
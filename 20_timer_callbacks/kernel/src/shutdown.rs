@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Shutdown hooks.
+//!
+//! Subsystems register hooks that bring them to a safe state before the kernel reboots, powers off
+//! or chainloads another image. Hooks run by stage, and within a stage in registration order, so
+//! that activity stops before storage is synced, storage is synced before the devices it needs are
+//! parked, and the console is flushed last.
+
+use crate::{
+    info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    warn,
+};
+use alloc::vec::Vec;
+use core::fmt;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+#[derive(Copy, Clone)]
+struct Entry {
+    name: &'static str,
+    stage: Stage,
+    hook: Hook,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A shutdown hook.
+pub type Hook = fn() -> Result<(), &'static str>;
+
+/// Shutdown stages, in the order they run.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Stage {
+    /// Stop producing work, e.g. patterns, signal generators and jobs.
+    Quiesce,
+
+    /// Write out data, e.g. the config store and file systems.
+    Storage,
+
+    /// Put devices into a state the next image can take over, e.g. GPIO and DMA.
+    Devices,
+
+    /// Flush logs and the console.
+    Console,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static HOOKS: IRQSafeNullLock<Vec<Entry>> = IRQSafeNullLock::new(Vec::new());
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Quiesce => write!(f, "quiesce"),
+            Self::Storage => write!(f, "storage"),
+            Self::Devices => write!(f, "devices"),
+            Self::Console => write!(f, "console"),
+        }
+    }
+}
+
+/// Register a shutdown hook.
+pub fn register(name: &'static str, stage: Stage, hook: Hook) -> Result<(), &'static str> {
+    HOOKS.lock(|hooks| {
+        if hooks.iter().any(|e| e.name == name) {
+            return Err("Shutdown hook already registered");
+        }
+
+        // Keep the list sorted by stage. Inserting after all entries of the same or an earlier
+        // stage preserves the registration order within a stage.
+        let pos = hooks.partition_point(|e| e.stage <= stage);
+        hooks.insert(pos, Entry { name, stage, hook });
+
+        Ok(())
+    })
+}
+
+/// Run all shutdown hooks. A failing hook is reported and does not stop the others. Returns the
+/// number of hooks that failed.
+///
+/// The hooks are called without holding the registry lock, so they may use other subsystems.
+pub fn run() -> usize {
+    let hooks = HOOKS.lock(|hooks| hooks.clone());
+    let mut failed = 0;
+
+    for e in hooks {
+        if let Err(x) = (e.hook)() {
+            warn!("Shutdown hook {} failed: {}", e.name, x);
+            failed += 1;
+        }
+    }
+
+    failed
+}
+
+/// Print the registered hooks in the order they run.
+pub fn print() {
+    HOOKS.lock(|hooks| {
+        for e in hooks.iter() {
+            info!("      {:<8} {}", e.stage, e.name);
+        }
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use test_macros::kernel_test;
+
+    static ORDER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Hooks must run by stage regardless of registration order, and names must be unique.
+    #[kernel_test]
+    fn hooks_run_by_stage() {
+        let console: Hook = || {
+            ORDER.store(ORDER.load(Ordering::Relaxed) * 10 + 2, Ordering::Relaxed);
+            Ok(())
+        };
+        let quiesce: Hook = || {
+            ORDER.store(ORDER.load(Ordering::Relaxed) * 10 + 1, Ordering::Relaxed);
+            Err("test failure")
+        };
+
+        register("console", Stage::Console, console).unwrap();
+        register("quiesce", Stage::Quiesce, quiesce).unwrap();
+        assert!(register("console", Stage::Devices, console).is_err());
+
+        assert_eq!(run(), 1);
+        assert_eq!(ORDER.load(Ordering::Relaxed), 12);
+    }
+}
@@ -0,0 +1,240 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Signal generator.
+//!
+//! Waveforms are played back on a GPIO pin from timer callbacks. Each callback sets the pin to the
+//! level the waveform has at the current time and schedules itself for the next edge, so a late
+//! callback does not shift the rest of the signal.
+//!
+//! A square wave toggles the pin at half the period. A ramp is produced with software PWM: a
+//! carrier of fixed frequency whose duty cycle sweeps from 0 to 100 % once per period, which reads
+//! as a rising voltage after an RC low-pass filter.
+
+use crate::{
+    bsp, info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time,
+};
+use alloc::boxed::Box;
+use core::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Period of the PWM carrier of ramps.
+const PWM_CARRIER_PERIOD: Duration = Duration::from_millis(1);
+
+/// Shortest time between two callbacks, which bounds the IRQ load.
+const MIN_INTERVAL: Duration = Duration::from_micros(50);
+
+/// Highest square wave frequency in Hz.
+const MAX_SQUARE_FREQ: f32 = 1000.0;
+
+/// Highest ramp frequency in Hz. The carrier must complete several cycles per ramp.
+const MAX_RAMP_FREQ: f32 = 10.0;
+
+/// Lowest frequency in Hz.
+const MIN_FREQ: f32 = 0.01;
+
+#[derive(Copy, Clone)]
+struct Signal {
+    pin: u8,
+    waveform: Waveform,
+    freq: f32,
+    period: Duration,
+    start: Duration,
+    force: bool,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Supported waveforms.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Waveform {
+    Square,
+    Ramp,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static SIGNAL: IRQSafeNullLock<Option<Signal>> = IRQSafeNullLock::new(None);
+
+/// Incremented on every start and stop. Callbacks of an older generation end their chain, which
+/// is simpler than cancelling the handle of every step.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn rem(t: Duration, period: Duration) -> Duration {
+    Duration::from_nanos((t.as_nanos() % period.as_nanos()) as u64)
+}
+
+/// Return the level of the waveform at `t` after start, and how long it holds.
+fn level_at(waveform: Waveform, period: Duration, t: Duration) -> (bool, Duration) {
+    match waveform {
+        Waveform::Square => {
+            let half = period / 2;
+            let p = rem(t, period);
+
+            if p < half {
+                (true, half - p)
+            } else {
+                (false, period - p)
+            }
+        }
+        Waveform::Ramp => {
+            // The duty cycle is the position within the period. Integer math keeps soft-float out
+            // of the IRQ path.
+            let on_time = Duration::from_nanos(
+                (PWM_CARRIER_PERIOD.as_nanos() * rem(t, period).as_nanos() / period.as_nanos())
+                    as u64,
+            );
+            let c = rem(t, PWM_CARRIER_PERIOD);
+
+            if c < on_time {
+                (true, on_time - c)
+            } else {
+                (false, PWM_CARRIER_PERIOD - c)
+            }
+        }
+    }
+}
+
+fn step(generation: usize) {
+    if GENERATION.load(Ordering::Relaxed) != generation {
+        return;
+    }
+
+    let signal = match SIGNAL.lock(|s| *s) {
+        Some(s) => s,
+        None => return,
+    };
+
+    let now = time::time_manager().uptime();
+    let (level, hold) = level_at(signal.waveform, signal.period, now - signal.start);
+
+    let result = unsafe {
+        if level {
+            bsp::driver::gpio_high(signal.pin, signal.force)
+        } else {
+            bsp::driver::gpio_low(signal.pin, signal.force)
+        }
+    };
+    if let Err(x) = result {
+        info!("siggen: {}", x);
+        stop();
+        return;
+    }
+
+    time::time_manager()
+        .set_timeout_once(hold.max(MIN_INTERVAL), Box::new(move || step(generation)));
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl core::str::FromStr for Waveform {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "square" => Ok(Self::Square),
+            "ramp" => Ok(Self::Ramp),
+            _ => Err("Unknown waveform"),
+        }
+    }
+}
+
+impl fmt::Display for Waveform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Square => write!(f, "square"),
+            Self::Ramp => write!(f, "ramp"),
+        }
+    }
+}
+
+/// Start emitting `waveform` at `freq` Hz on `pin`, replacing a running signal.
+pub fn start(pin: u8, waveform: Waveform, freq: f32, force: bool) -> Result<(), &'static str> {
+    let max_freq = match waveform {
+        Waveform::Square => MAX_SQUARE_FREQ,
+        Waveform::Ramp => MAX_RAMP_FREQ,
+    };
+    if !(MIN_FREQ..=max_freq).contains(&freq) {
+        return Err("Frequency out of range");
+    }
+
+    stop();
+    unsafe { bsp::driver::gpio_as_output(pin, force)? };
+
+    SIGNAL.lock(|s| {
+        *s = Some(Signal {
+            pin,
+            waveform,
+            freq,
+            period: Duration::from_secs_f32(1.0 / freq),
+            start: time::time_manager().uptime(),
+            force,
+        })
+    });
+
+    step(GENERATION.fetch_add(1, Ordering::Relaxed) + 1);
+
+    Ok(())
+}
+
+/// Stop the running signal and drive its pin low.
+pub fn stop() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+
+    if let Some(s) = SIGNAL.lock(|s| s.take()) {
+        let _ = unsafe { bsp::driver::gpio_low(s.pin, s.force) };
+    }
+}
+
+/// Print the running signal.
+pub fn print_status() {
+    match SIGNAL.lock(|s| *s) {
+        Some(s) => info!("      {} at {} Hz on pin {}", s.waveform, s.freq, s.pin),
+        None => info!("      Stopped"),
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A square wave must be high for the first half period and low for the second.
+    #[kernel_test]
+    fn square_wave_levels() {
+        let period = Duration::from_millis(10);
+
+        assert_eq!(
+            level_at(Waveform::Square, period, Duration::from_millis(2)),
+            (true, Duration::from_millis(3))
+        );
+        assert_eq!(
+            level_at(Waveform::Square, period, Duration::from_millis(17)),
+            (false, Duration::from_millis(3))
+        );
+    }
+}
@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Boot statistics.
+//!
+//! A boot counter and the cumulative uptime are kept in the config store, for long-running
+//! reliability testing. They only survive a reset if the BSP placed the store on persistent
+//! storage, e.g. the SD card. The uptime of the running session is checkpointed periodically, so that a
+//! session that ends without [`record_shutdown()`] still counts, up to the last checkpoint, and is
+//! reported as unclean.
+
+use crate::{
+    config::{self, ConfigStore},
+    info, shutdown, time,
+};
+use alloc::{boxed::Box, format};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const KEY_BOOT_COUNT: &str = "boot.count";
+const KEY_CLEAN_SHUTDOWNS: &str = "boot.clean_shutdowns";
+const KEY_UNCLEAN_SHUTDOWNS: &str = "boot.unclean_shutdowns";
+
+/// Uptime of all finished sessions, in seconds.
+const KEY_UPTIME_TOTAL: &str = "boot.uptime_total";
+
+/// Uptime of the running session at the last checkpoint, in seconds.
+const KEY_UPTIME_SESSION: &str = "boot.uptime_session";
+
+/// Set while a session is running, cleared by a clean shutdown.
+const KEY_RUNNING: &str = "boot.running";
+
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn get(store: &ConfigStore, key: &str) -> u64 {
+    store.get_u64(key).unwrap_or(0)
+}
+
+fn set(store: &ConfigStore, key: &str, value: u64) {
+    // Keys are constants and values numbers, so this can't fail.
+    let _ = store.set(key, &format!("{}", value));
+}
+
+/// Count a boot in `store`. A previous session that is still marked running ended uncleanly, its
+/// uptime counts up to the last checkpoint.
+fn count_boot(store: &ConfigStore) {
+    if get(store, KEY_RUNNING) != 0 {
+        set(
+            store,
+            KEY_UPTIME_TOTAL,
+            get(store, KEY_UPTIME_TOTAL) + get(store, KEY_UPTIME_SESSION),
+        );
+        set(
+            store,
+            KEY_UNCLEAN_SHUTDOWNS,
+            get(store, KEY_UNCLEAN_SHUTDOWNS) + 1,
+        );
+    }
+
+    set(store, KEY_BOOT_COUNT, get(store, KEY_BOOT_COUNT) + 1);
+    set(store, KEY_RUNNING, 1);
+    set(store, KEY_UPTIME_SESSION, 0);
+}
+
+/// End the session in `store` cleanly, after `uptime` seconds.
+fn end_session(store: &ConfigStore, uptime: u64) {
+    set(
+        store,
+        KEY_UPTIME_TOTAL,
+        get(store, KEY_UPTIME_TOTAL) + uptime,
+    );
+    set(store, KEY_UPTIME_SESSION, 0);
+    set(store, KEY_RUNNING, 0);
+    set(
+        store,
+        KEY_CLEAN_SHUTDOWNS,
+        get(store, KEY_CLEAN_SHUTDOWNS) + 1,
+    );
+}
+
+fn checkpoint() {
+    set(
+        config::store(),
+        KEY_UPTIME_SESSION,
+        time::time_manager().uptime().as_secs(),
+    );
+
+    if let Err(x) = config::store().save() {
+        info!("stats: checkpoint failed: {}", x);
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Count this boot and start checkpointing the uptime. The config store must have been loaded.
+pub fn init() -> Result<(), &'static str> {
+    static INIT_DONE: AtomicBool = AtomicBool::new(false);
+    if INIT_DONE.load(Ordering::Relaxed) {
+        return Err("Init already done");
+    }
+
+    count_boot(config::store());
+    config::store().save()?;
+
+    time::time_manager().set_timeout_periodic(CHECKPOINT_INTERVAL, Box::new(checkpoint));
+    shutdown::register("stats", shutdown::Stage::Storage, record_shutdown)?;
+
+    INIT_DONE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Add the uptime of this session to the total and mark the shutdown as clean. Registered as a
+/// shutdown hook by [`init()`].
+pub fn record_shutdown() -> Result<(), &'static str> {
+    end_session(config::store(), time::time_manager().uptime().as_secs());
+
+    config::store().save()
+}
+
+/// Print the boot statistics.
+pub fn print_boot() {
+    let store = config::store();
+    let uptime = time::time_manager().uptime();
+
+    info!("      Boots:             {}", get(store, KEY_BOOT_COUNT));
+    info!(
+        "      Clean shutdowns:   {}",
+        get(store, KEY_CLEAN_SHUTDOWNS)
+    );
+    info!(
+        "      Unclean shutdowns: {}",
+        get(store, KEY_UNCLEAN_SHUTDOWNS)
+    );
+    info!("      Uptime:            {}", time::Clock(uptime));
+    info!(
+        "      Total uptime:      {}",
+        time::Clock(Duration::from_secs(get(store, KEY_UPTIME_TOTAL)) + uptime)
+    );
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{self, RamDisk};
+    use alloc::string::String;
+    use test_macros::kernel_test;
+
+    /// Load a fresh store from `storage`, as a boot does.
+    fn reload(storage: &config::BlockStorage) -> ConfigStore {
+        let store = ConfigStore::new();
+        store.load_from(storage).unwrap();
+
+        store
+    }
+
+    /// Every boot must count up from what the previous one saved, and only a clean shutdown must
+    /// count as clean.
+    #[kernel_test]
+    fn boot_count_persists() {
+        let disk: block::Device = Box::leak(Box::new(RamDisk::new(String::from("stats"), 8)));
+        let storage = config::BlockStorage::new(disk, 2, 4);
+
+        for boot in 1..=3 {
+            let store = reload(&storage);
+            count_boot(&store);
+            store.save_to(&storage).unwrap();
+
+            assert_eq!(get(&reload(&storage), KEY_BOOT_COUNT), boot);
+        }
+
+        let store = reload(&storage);
+        end_session(&store, 10);
+        store.save_to(&storage).unwrap();
+
+        let store = reload(&storage);
+        assert_eq!(get(&store, KEY_CLEAN_SHUTDOWNS), 1);
+        assert_eq!(get(&store, KEY_UNCLEAN_SHUTDOWNS), 2);
+        assert_eq!(get(&store, KEY_UPTIME_TOTAL), 10);
+        assert_eq!(get(&store, KEY_RUNNING), 0);
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Subsystem restart.
+//!
+//! Subsystems register a teardown and an init hook. Restarting a subsystem runs both in order,
+//! which recovers a wedged subsystem without rebooting the board. Hooks run in the context of the
+//! caller, usually the console's IRQ handler, and must not block for long.
+//!
+//! Teardown must unmap the MMIO regions the subsystem mapped for itself, see
+//! [`memory::mmu::kernel_map_mmio_owned()`]. Those left behind are flagged as leaked.
+
+use crate::{
+    info, memory,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    warn,
+};
+use alloc::vec::Vec;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+struct Subsystem {
+    name: &'static str,
+    teardown: Hook,
+    init: Hook,
+    restarts: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A teardown or init hook.
+pub type Hook = fn() -> Result<(), &'static str>;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static SUBSYSTEMS: IRQSafeNullLock<Vec<Subsystem>> = IRQSafeNullLock::new(Vec::new());
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Register a restartable subsystem.
+pub fn register(name: &'static str, teardown: Hook, init: Hook) -> Result<(), &'static str> {
+    SUBSYSTEMS.lock(|subsystems| {
+        if subsystems.iter().any(|s| s.name == name) {
+            return Err("Subsystem already registered");
+        }
+
+        subsystems.push(Subsystem {
+            name,
+            teardown,
+            init,
+            restarts: 0,
+        });
+
+        Ok(())
+    })
+}
+
+/// Tear down and re-initialize a subsystem.
+///
+/// The hooks are called without holding the registry lock, so they may use other subsystems.
+/// Init runs even if teardown failed; the first error is returned.
+pub fn restart(name: &str) -> Result<(), &'static str> {
+    let (teardown, init) = SUBSYSTEMS.lock(|subsystems| {
+        let s = subsystems
+            .iter_mut()
+            .find(|s| s.name == name)
+            .ok_or("No such subsystem")?;
+        s.restarts += 1;
+
+        Ok((s.teardown, s.init))
+    })?;
+
+    let teardown_result = teardown();
+    let leaks = memory::mmu::kernel_flag_mapping_leaks(name);
+    if leaks > 0 {
+        warn!("{} left {} mappings behind", name, leaks);
+    }
+    let init_result = init();
+
+    teardown_result.and(init_result)
+}
+
+/// Print the registered subsystems.
+pub fn print() {
+    SUBSYSTEMS.lock(|subsystems| {
+        for s in subsystems.iter() {
+            info!("      {:<12} {} restarts", s.name, s.restarts);
+        }
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use test_macros::kernel_test;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Restart must run both hooks and reject unknown or duplicate names.
+    #[kernel_test]
+    fn restart_runs_hooks() {
+        let hook: Hook = || {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        };
+
+        register("test", hook, hook).unwrap();
+        assert!(register("test", hook, hook).is_err());
+
+        restart("test").unwrap();
+        assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+        assert!(restart("missing").is_err());
+    }
+}
@@ -5,7 +5,7 @@
 //! Debug symbol support.
 
 use crate::memory::{Address, Virtual};
-use core::{cell::UnsafeCell, slice};
+use core::{cell::UnsafeCell, fmt, slice};
 use debug_symbol_types::Symbol;
 
 //--------------------------------------------------------------------------------------------------
@@ -17,6 +17,13 @@ extern "Rust" {
     static __kernel_symbols_start: UnsafeCell<()>;
 }
 
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Prints an address as the symbol containing it plus the offset into it, e.g. `kernel_init+0x1c`.
+pub struct SymbolOffset(pub Address<Virtual>);
+
 //--------------------------------------------------------------------------------------------------
 // Global instances
 //--------------------------------------------------------------------------------------------------
@@ -59,6 +66,15 @@ pub fn lookup_symbol(addr: Address<Virtual>) -> Option<&'static Symbol> {
         .find(|&i| i.contains(addr.as_usize()))
 }
 
+impl fmt::Display for SymbolOffset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match lookup_symbol(self.0) {
+            Some(sym) => write!(f, "{}+{:#x}", sym.name(), self.0.as_usize() - sym.start()),
+            None => write!(f, "Symbol not found"),
+        }
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 // Testing
 //--------------------------------------------------------------------------------------------------
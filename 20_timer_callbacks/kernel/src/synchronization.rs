@@ -4,13 +4,22 @@
 
 //! Synchronization primitives.
 //!
+//! The pseudo-locks only mask IRQs, which is enough while a single core runs. [`Spinlock`] and
+//! [`IRQSafeSpinlock`] provide mutual exclusion across cores. Their flag is an atomic that compiles
+//! to an LDAXR/STXR loop, or to CAS with the Large System Extensions, so they must only be used
+//! once the MMU and caches are on. Drivers that code on several cores may share use
+//! [`IRQSafeDeviceLock`], which is a real spinlock with the `smp` feature.
+//!
 //! # Resources
 //!
 //!   - <https://doc.rust-lang.org/book/ch16-04-extensible-concurrency-sync-and-send.html>
 //!   - <https://stackoverflow.com/questions/59428096/understanding-the-send-trait>
 //!   - <https://doc.rust-lang.org/std/cell/index.html>
 
-use core::cell::UnsafeCell;
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
@@ -59,6 +68,36 @@ where
     data: UnsafeCell<T>,
 }
 
+/// A spinlock.
+///
+/// Busy-waits until the lock is free. It leaves IRQs alone, so it must not be taken both by IRQ
+/// handlers and by code they interrupt; see [`IRQSafeSpinlock`]. Not re-entrant: taking the lock
+/// again on the core that holds it deadlocks.
+pub struct Spinlock<T>
+where
+    T: ?Sized,
+{
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+/// A spinlock that masks IRQs on the local core while it is held, so that an IRQ handler can't
+/// spin on a lock its core holds.
+pub struct IRQSafeSpinlock<T>
+where
+    T: ?Sized,
+{
+    inner: Spinlock<T>,
+}
+
+/// The lock of drivers that code on any core may use.
+#[cfg(feature = "smp")]
+pub type IRQSafeDeviceLock<T> = IRQSafeSpinlock<T>;
+
+/// The lock of drivers that code on any core may use.
+#[cfg(not(feature = "smp"))]
+pub type IRQSafeDeviceLock<T> = IRQSafeNullLock<T>;
+
 /// A pseudo-lock that is RW during the single-core kernel init phase and RO afterwards.
 ///
 /// Intended to encapsulate data that is populated during kernel init when no concurrency exists.
@@ -85,6 +124,50 @@ impl<T> IRQSafeNullLock<T> {
     }
 }
 
+unsafe impl<T> Send for Spinlock<T> where T: ?Sized + Send {}
+unsafe impl<T> Sync for Spinlock<T> where T: ?Sized + Send {}
+
+impl<T> Spinlock<T> {
+    /// Create an instance. Only needed with the `smp` feature, see [`IRQSafeDeviceLock`].
+    #[cfg_attr(not(feature = "smp"), allow(dead_code))]
+    pub const fn new(data: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+}
+
+impl<T: ?Sized> Spinlock<T> {
+    fn acquire(&self) {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            // Wait with plain loads, so that waiting cores don't take the cache line from the
+            // holder.
+            while self.locked.load(Ordering::Relaxed) {
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    fn release(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
+}
+
+impl<T> IRQSafeSpinlock<T> {
+    /// Create an instance. Only needed with the `smp` feature, see [`IRQSafeDeviceLock`].
+    #[cfg_attr(not(feature = "smp"), allow(dead_code))]
+    pub const fn new(data: T) -> Self {
+        Self {
+            inner: Spinlock::new(data),
+        }
+    }
+}
+
 unsafe impl<T> Send for InitStateLock<T> where T: ?Sized + Send {}
 unsafe impl<T> Sync for InitStateLock<T> where T: ?Sized + Send {}
 
@@ -105,6 +188,8 @@ use crate::{exception, state};
 impl<T> interface::Mutex for IRQSafeNullLock<T> {
     type Data = T;
 
+    // The masked section is measured under the lock's caller.
+    #[track_caller]
     fn lock<'a, R>(&'a self, f: impl FnOnce(&'a mut Self::Data) -> R) -> R {
         // In a real lock, there would be code encapsulating this line that ensures that this
         // mutable reference will ever only be given out once at a time.
@@ -115,6 +200,32 @@ impl<T> interface::Mutex for IRQSafeNullLock<T> {
     }
 }
 
+impl<T> interface::Mutex for Spinlock<T> {
+    type Data = T;
+
+    fn lock<'a, R>(&'a self, f: impl FnOnce(&'a mut Self::Data) -> R) -> R {
+        self.acquire();
+
+        // The flag ensures that this is the only reference until the release below.
+        let data = unsafe { &mut *self.data.get() };
+        let result = f(data);
+
+        self.release();
+        result
+    }
+}
+
+impl<T> interface::Mutex for IRQSafeSpinlock<T> {
+    type Data = T;
+
+    // The masked section is measured under the lock's caller.
+    #[track_caller]
+    fn lock<'a, R>(&'a self, f: impl FnOnce(&'a mut Self::Data) -> R) -> R {
+        // Mask first, so that an IRQ can't arrive while the lock is held.
+        exception::asynchronous::exec_with_irq_masked(|| self.inner.lock(f))
+    }
+}
+
 impl<T> interface::ReadWriteEx for InitStateLock<T> {
     type Data = T;
 
@@ -156,4 +267,17 @@ mod tests {
 
         assert_eq!(size_of::<InitStateLock<u64>>(), size_of::<u64>());
     }
+
+    /// A spinlock must be free again after the closure, and keep the changes made in it.
+    #[kernel_test]
+    fn spinlock_releases() {
+        use interface::Mutex;
+
+        let lock = IRQSafeSpinlock::new(0);
+        lock.lock(|x| *x += 1);
+        lock.lock(|x| *x += 1);
+
+        assert_eq!(lock.lock(|x| *x), 2);
+        assert!(!lock.inner.locked.load(Ordering::Relaxed));
+    }
 }
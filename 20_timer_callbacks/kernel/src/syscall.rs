@@ -0,0 +1,543 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! System call interface for EL0 programs.
+//!
+//! A program issues `svc #0` with the syscall number in x8 and the arguments in x0 to x5. The
+//! result comes back in x0, with errors as negative numbers, see [`Error::code()`]. Syscalls run on
+//! the program's task with IRQs unmasked, so they can block like any task. Addresses passed by a
+//! program are checked against its address space, see [`crate::process`].
+//!
+//! The ABI has a version and a bitmap of optional features. A program calls [`SYS_NEGOTIATE`] at
+//! startup with the version it was built against and the features it needs, and stops with a clear
+//! error if the kernel is older or lacks a feature, instead of failing later on a syscall that
+//! doesn't exist. Minor versions only add syscalls, so a program built against an older minor
+//! version runs unchanged. [`SYS_QUERY`] tells whether a single syscall is available.
+//!
+//! The socket syscalls wrap [`crate::net::socket`]. A program can only use the sockets it opened
+//! itself, and they are closed when it ends.
+
+use crate::{
+    bsp, info,
+    net::{
+        socket::{self, SocketHandle, SocketType},
+        Ipv4Address, SocketAddr,
+    },
+    print, process, sched, time,
+};
+use alloc::vec;
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+struct Syscall {
+    number: u64,
+    name: &'static str,
+
+    /// Minor ABI version that added the syscall.
+    since: u16,
+
+    /// Feature the syscall belongs to, or 0 for the core syscalls.
+    feature: u64,
+    handler: Handler,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Major ABI version. Changed on incompatible changes, which programs can't work around.
+pub const ABI_MAJOR: u16 = 1;
+
+/// Minor ABI version. Incremented whenever syscalls are added.
+pub const ABI_MINOR: u16 = 2;
+
+/// Optional features.
+pub mod feature {
+    /// Uptime.
+    pub const TIME: u64 = 1 << 0;
+
+    /// Giving up the CPU.
+    pub const SCHED: u64 = 1 << 1;
+
+    /// Console output.
+    pub const CONSOLE: u64 = 1 << 2;
+
+    /// GPIO pins.
+    pub const GPIO: u64 = 1 << 3;
+
+    /// UDP sockets.
+    pub const NET: u64 = 1 << 4;
+}
+
+/// The features this kernel provides.
+pub const FEATURES: u64 =
+    feature::TIME | feature::SCHED | feature::CONSOLE | feature::GPIO | feature::NET;
+
+/// Check the ABI version and features. Arguments: major and minor version the program was built
+/// against, required features. Returns the kernel's version as `major << 16 | minor`.
+pub const SYS_NEGOTIATE: u64 = 0;
+
+/// Arguments: a syscall number. Returns 1 if it is available, 0 otherwise.
+pub const SYS_QUERY: u64 = 1;
+
+/// Returns the uptime in nanoseconds.
+pub const SYS_UPTIME: u64 = 2;
+
+/// Give the other tasks a turn. Returns 0.
+pub const SYS_YIELD: u64 = 3;
+
+/// End the program. Arguments: exit status. Doesn't return.
+pub const SYS_EXIT: u64 = 4;
+
+/// Print UTF-8 text. Arguments: address and length of the text. Returns the length.
+pub const SYS_WRITE: u64 = 5;
+
+/// Let the other tasks run. Arguments: duration in nanoseconds. Returns 0.
+pub const SYS_SLEEP: u64 = 6;
+
+/// Drive a pin, making it an output. Arguments: pin, level (0 for low). Returns 0.
+pub const SYS_GPIO_SET: u64 = 7;
+
+/// Read a pin. Arguments: pin. Returns its level, 0 or 1.
+pub const SYS_GPIO_GET: u64 = 8;
+
+/// Open a socket. Arguments: type, 0 for datagrams (UDP). Returns the socket's handle.
+pub const SYS_SOCKET: u64 = 9;
+
+/// Bind a socket to a local port. Arguments: handle, port, 0 for a free ephemeral one. Returns 0.
+pub const SYS_BIND: u64 = 10;
+
+/// Send a datagram. Arguments: handle, address and length of the data, destination IPv4 address
+/// as `a << 24 | b << 16 | c << 8 | d`, destination port. Returns the length.
+pub const SYS_SEND_TO: u64 = 11;
+
+/// Receive a datagram. Arguments: handle, address and length of the buffer, timeout in
+/// nanoseconds or `u64::MAX` to wait forever, address of a `u64` that receives the sender as
+/// `ipv4 << 16 | port`, or 0. Returns the length of the part of the datagram that fit.
+pub const SYS_RECV_FROM: u64 = 12;
+
+/// Close a socket. Arguments: handle. Returns 0.
+pub const SYS_CLOSE: u64 = 13;
+
+/// Syscall errors.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// No such syscall.
+    NoSys,
+
+    /// The program was built against an incompatible ABI version.
+    Version,
+
+    /// A required feature is missing.
+    Unsupported,
+
+    /// An address the program passed isn't accessible to it.
+    Fault,
+
+    /// An argument is out of range or refused, e.g. a protected pin.
+    Invalid,
+
+    /// Nothing arrived in time.
+    TimedOut,
+
+    /// The operation failed, e.g. a datagram couldn't be sent.
+    Io,
+}
+
+/// A syscall handler, called with x0 to x5.
+pub type Handler = fn(args: &[u64; 6]) -> Result<u64, Error>;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static TABLE: &[Syscall] = &[
+    Syscall {
+        number: SYS_NEGOTIATE,
+        name: "negotiate",
+        since: 0,
+        feature: 0,
+        handler: sys_negotiate,
+    },
+    Syscall {
+        number: SYS_QUERY,
+        name: "query",
+        since: 0,
+        feature: 0,
+        handler: sys_query,
+    },
+    Syscall {
+        number: SYS_UPTIME,
+        name: "uptime",
+        since: 0,
+        feature: feature::TIME,
+        handler: sys_uptime,
+    },
+    Syscall {
+        number: SYS_YIELD,
+        name: "yield",
+        since: 0,
+        feature: feature::SCHED,
+        handler: sys_yield,
+    },
+    Syscall {
+        number: SYS_EXIT,
+        name: "exit",
+        since: 1,
+        feature: 0,
+        handler: sys_exit,
+    },
+    Syscall {
+        number: SYS_WRITE,
+        name: "write",
+        since: 1,
+        feature: feature::CONSOLE,
+        handler: sys_write,
+    },
+    Syscall {
+        number: SYS_SLEEP,
+        name: "sleep",
+        since: 1,
+        feature: feature::SCHED,
+        handler: sys_sleep,
+    },
+    Syscall {
+        number: SYS_GPIO_SET,
+        name: "gpio_set",
+        since: 1,
+        feature: feature::GPIO,
+        handler: sys_gpio_set,
+    },
+    Syscall {
+        number: SYS_GPIO_GET,
+        name: "gpio_get",
+        since: 1,
+        feature: feature::GPIO,
+        handler: sys_gpio_get,
+    },
+    Syscall {
+        number: SYS_SOCKET,
+        name: "socket",
+        since: 2,
+        feature: feature::NET,
+        handler: sys_socket,
+    },
+    Syscall {
+        number: SYS_BIND,
+        name: "bind",
+        since: 2,
+        feature: feature::NET,
+        handler: sys_bind,
+    },
+    Syscall {
+        number: SYS_SEND_TO,
+        name: "send_to",
+        since: 2,
+        feature: feature::NET,
+        handler: sys_send_to,
+    },
+    Syscall {
+        number: SYS_RECV_FROM,
+        name: "recv_from",
+        since: 2,
+        feature: feature::NET,
+        handler: sys_recv_from,
+    },
+    Syscall {
+        number: SYS_CLOSE,
+        name: "close",
+        since: 2,
+        feature: feature::NET,
+        handler: sys_close,
+    },
+];
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn lookup(number: u64) -> Option<&'static Syscall> {
+    TABLE.iter().find(|s| s.number == number)
+}
+
+fn sys_negotiate(args: &[u64; 6]) -> Result<u64, Error> {
+    let (major, minor, features) = (args[0], args[1], args[2]);
+
+    if major != ABI_MAJOR as u64 || minor > ABI_MINOR as u64 {
+        return Err(Error::Version);
+    }
+    if features & !FEATURES != 0 {
+        return Err(Error::Unsupported);
+    }
+
+    Ok((ABI_MAJOR as u64) << 16 | ABI_MINOR as u64)
+}
+
+fn sys_query(args: &[u64; 6]) -> Result<u64, Error> {
+    Ok(lookup(args[0]).is_some() as u64)
+}
+
+fn sys_uptime(_args: &[u64; 6]) -> Result<u64, Error> {
+    Ok(time::time_manager().uptime().as_nanos() as u64)
+}
+
+fn sys_yield(_args: &[u64; 6]) -> Result<u64, Error> {
+    sched::yield_now();
+
+    Ok(0)
+}
+
+fn sys_exit(args: &[u64; 6]) -> Result<u64, Error> {
+    // Only a program can end itself this way.
+    if process::current().is_none() {
+        return Err(Error::NoSys);
+    }
+
+    process::exit(args[0] as i64)
+}
+
+fn sys_write(args: &[u64; 6]) -> Result<u64, Error> {
+    let (addr, len) = (args[0] as usize, args[1] as usize);
+
+    process::with_user_bytes(addr, len, |bytes| {
+        let text = core::str::from_utf8(bytes).map_err(|_| Error::Invalid)?;
+        print!("{}", text);
+
+        Ok(len as u64)
+    })
+    .map_err(|_| Error::Fault)?
+}
+
+fn sys_sleep(args: &[u64; 6]) -> Result<u64, Error> {
+    sched::sleep(Duration::from_nanos(args[0]));
+
+    Ok(0)
+}
+
+fn pin(arg: u64) -> Result<u8, Error> {
+    u8::try_from(arg).map_err(|_| Error::Invalid)
+}
+
+fn sys_gpio_set(args: &[u64; 6]) -> Result<u64, Error> {
+    let pin = pin(args[0])?;
+
+    // Programs never override the pin protection.
+    unsafe {
+        bsp::driver::gpio_as_output(pin, false).map_err(|_| Error::Invalid)?;
+        match args[1] {
+            0 => bsp::driver::gpio_low(pin, false),
+            _ => bsp::driver::gpio_high(pin, false),
+        }
+        .map_err(|_| Error::Invalid)?;
+    }
+
+    Ok(0)
+}
+
+fn sys_gpio_get(args: &[u64; 6]) -> Result<u64, Error> {
+    let pin = pin(args[0])?;
+    let level = unsafe { bsp::driver::gpio_read(pin) }.map_err(|_| Error::Invalid)?;
+
+    Ok(level as u64)
+}
+
+fn port(arg: u64) -> Result<u16, Error> {
+    u16::try_from(arg).map_err(|_| Error::Invalid)
+}
+
+/// Return the socket `arg` refers to, if the calling program opened it.
+fn owned_socket(arg: u64) -> Result<SocketHandle, Error> {
+    let handle = SocketHandle::from_raw(usize::try_from(arg).map_err(|_| Error::Invalid)?);
+    let task = sched::current().ok_or(Error::Invalid)?;
+    socket::check_owner(handle, task).map_err(|_| Error::Invalid)?;
+
+    Ok(handle)
+}
+
+fn sys_socket(args: &[u64; 6]) -> Result<u64, Error> {
+    let kind = match args[0] {
+        0 => SocketType::Datagram,
+        1 => return Err(Error::Unsupported),
+        _ => return Err(Error::Invalid),
+    };
+    let task = sched::current().ok_or(Error::Invalid)?;
+
+    socket::socket_for(kind, task)
+        .map(|handle| handle.into_raw() as u64)
+        .map_err(|_| Error::Io)
+}
+
+fn sys_bind(args: &[u64; 6]) -> Result<u64, Error> {
+    let port = port(args[1])?;
+    let handle = owned_socket(args[0])?;
+    socket::bind(handle, port).map_err(|_| Error::Invalid)?;
+
+    Ok(0)
+}
+
+fn sys_send_to(args: &[u64; 6]) -> Result<u64, Error> {
+    let (addr, len) = (args[1] as usize, args[2] as usize);
+    if len > socket::MAX_UDP_PAYLOAD {
+        return Err(Error::Invalid);
+    }
+    let ip = u32::try_from(args[3]).map_err(|_| Error::Invalid)?;
+    let dst = SocketAddr::new(Ipv4Address(ip.to_be_bytes()), port(args[4])?);
+    let handle = owned_socket(args[0])?;
+
+    // Copied, as send_to() may yield while the address is resolved, and the program's memory is
+    // only mapped while its task runs.
+    let data =
+        process::with_user_bytes(addr, len, |bytes| bytes.to_vec()).map_err(|_| Error::Fault)?;
+    socket::send_to(handle, &data, dst).map_err(|_| Error::Io)?;
+
+    Ok(len as u64)
+}
+
+fn sys_recv_from(args: &[u64; 6]) -> Result<u64, Error> {
+    let (addr, len, from_addr) = (args[1] as usize, args[2] as usize, args[4] as usize);
+    let timeout = match args[3] {
+        u64::MAX => None,
+        ns => Some(Duration::from_nanos(ns)),
+    };
+    let handle = owned_socket(args[0])?;
+
+    // Checked before waiting, so that a bad address fails at once.
+    process::with_user_bytes_mut(addr, len, |_| ()).map_err(|_| Error::Fault)?;
+    if from_addr != 0 {
+        process::with_user_bytes_mut(from_addr, 8, |_| ()).map_err(|_| Error::Fault)?;
+    }
+
+    let mut data = vec![0; len.min(socket::MAX_UDP_PAYLOAD)];
+    let (received, from) = socket::recv_from(handle, &mut data, timeout)
+        .map_err(|_| Error::Io)?
+        .ok_or(Error::TimedOut)?;
+
+    process::with_user_bytes_mut(addr, received, |bytes| {
+        bytes.copy_from_slice(&data[..received])
+    })
+    .map_err(|_| Error::Fault)?;
+    if from_addr != 0 {
+        let from = (u32::from_be_bytes(from.addr.0) as u64) << 16 | from.port as u64;
+        process::with_user_bytes_mut(from_addr, 8, |bytes| {
+            bytes.copy_from_slice(&from.to_ne_bytes())
+        })
+        .map_err(|_| Error::Fault)?;
+    }
+
+    Ok(received as u64)
+}
+
+fn sys_close(args: &[u64; 6]) -> Result<u64, Error> {
+    let handle = owned_socket(args[0])?;
+    socket::close(handle).map_err(|_| Error::Invalid)?;
+
+    Ok(0)
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Error {
+    /// The value returned in x0.
+    pub fn code(self) -> i64 {
+        match self {
+            Self::NoSys => -38,
+            Self::Version => -1000,
+            Self::Unsupported => -1001,
+            Self::Fault => -14,
+            Self::Invalid => -22,
+            Self::TimedOut => -110,
+            Self::Io => -5,
+        }
+    }
+}
+
+/// Run syscall `number` and return the value for x0.
+pub fn dispatch(number: u64, args: &[u64; 6]) -> u64 {
+    let result = match lookup(number) {
+        Some(syscall) => (syscall.handler)(args),
+        None => Err(Error::NoSys),
+    };
+
+    match result {
+        Ok(value) => value,
+        Err(e) => e.code() as u64,
+    }
+}
+
+/// Print the ABI version, the features and the syscall table.
+pub fn print() {
+    info!("      ABI version: {}.{}", ABI_MAJOR, ABI_MINOR);
+    info!("      Features:    {:#018x}", FEATURES);
+    for s in TABLE {
+        info!(
+            "      {:>3}  {:<10} since 1.{}  feature {:#x}",
+            s.number, s.name, s.since, s.feature
+        );
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Programs built against an older minor version must be accepted, newer ones and missing
+    /// features rejected, and unknown syscalls must fail with `NoSys`.
+    #[kernel_test]
+    fn negotiate_versions() {
+        let args =
+            |major: u16, minor: u16, features| [major as u64, minor as u64, features, 0, 0, 0];
+
+        assert!(sys_negotiate(&args(ABI_MAJOR, 0, feature::TIME)).is_ok());
+        assert_eq!(
+            sys_negotiate(&args(ABI_MAJOR, ABI_MINOR + 1, 0)),
+            Err(Error::Version)
+        );
+        assert_eq!(
+            sys_negotiate(&args(ABI_MAJOR + 1, 0, 0)),
+            Err(Error::Version)
+        );
+        assert_eq!(
+            sys_negotiate(&args(ABI_MAJOR, 0, 1 << 63)),
+            Err(Error::Unsupported)
+        );
+
+        assert_eq!(dispatch(SYS_QUERY, &[SYS_UPTIME, 0, 0, 0, 0, 0]), 1);
+        assert_eq!(dispatch(SYS_QUERY, &[999, 0, 0, 0, 0, 0]), 0);
+        assert_eq!(dispatch(999, &[0; 6]), Error::NoSys.code() as u64);
+    }
+    /// Socket syscalls must refuse malformed arguments and sockets the caller didn't open before
+    /// touching the network stack.
+    #[kernel_test]
+    fn socket_arguments() {
+        let args = |a: u64, b: u64, c: u64, d: u64, e: u64| [a, b, c, d, e, 0];
+
+        assert_eq!(sys_socket(&args(2, 0, 0, 0, 0)), Err(Error::Invalid));
+        assert_eq!(sys_bind(&args(0, 1 << 16, 0, 0, 0)), Err(Error::Invalid));
+        assert_eq!(
+            sys_send_to(&args(0, 0, socket::MAX_UDP_PAYLOAD as u64 + 1, 0, 0)),
+            Err(Error::Invalid)
+        );
+        assert_eq!(sys_send_to(&args(0, 0, 0, 1 << 32, 0)), Err(Error::Invalid));
+        assert_eq!(sys_send_to(&args(0, 0, 0, 0, 1 << 16)), Err(Error::Invalid));
+
+        // A socket opened by the kernel belongs to no program.
+        let handle = socket::socket(SocketType::Datagram).unwrap().into_raw() as u64;
+        assert_eq!(sys_close(&args(handle, 0, 0, 0, 0)), Err(Error::Invalid));
+        assert_eq!(
+            sys_recv_from(&args(handle, 0, 0, 0, 0)),
+            Err(Error::Invalid)
+        );
+        socket::close(SocketHandle::from_raw(handle as usize)).unwrap();
+    }
+}
@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! System register inspection.
+//!
+//! A table of known system registers, each with a read function and the layout of its fields, so
+//! that registers can be dumped and decoded from the shell.
+
+#[cfg(target_arch = "aarch64")]
+#[path = "_arch/aarch64/sysreg.rs"]
+mod arch_sysreg;
+
+use crate::info;
+use alloc::format;
+
+//--------------------------------------------------------------------------------------------------
+// Architectural Public Reexports
+//--------------------------------------------------------------------------------------------------
+pub use arch_sysreg::REGISTERS;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A bit field within a system register.
+pub struct Field {
+    pub name: &'static str,
+
+    /// Position of the lowest bit.
+    pub lsb: u8,
+
+    /// Number of bits.
+    pub width: u8,
+}
+
+/// A readable system register.
+pub struct SysReg {
+    pub name: &'static str,
+    pub read: fn() -> u64,
+    pub fields: &'static [Field],
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Field {
+    /// Create an instance.
+    pub const fn new(name: &'static str, lsb: u8, width: u8) -> Self {
+        Self { name, lsb, width }
+    }
+
+    /// Return the field's value within the register value `reg`.
+    pub fn extract(&self, reg: u64) -> u64 {
+        let mask = if self.width >= 64 {
+            u64::MAX
+        } else {
+            (1 << self.width) - 1
+        };
+
+        (reg >> self.lsb) & mask
+    }
+}
+
+impl SysReg {
+    /// Read the register and print its value and decoded fields.
+    pub fn print(&self) {
+        let value = (self.read)();
+
+        info!("      {} = {:#018x}", self.name, value);
+        for f in self.fields {
+            let bits = if f.width == 1 {
+                format!("[{}]", f.lsb)
+            } else {
+                format!("[{}:{}]", f.lsb + f.width - 1, f.lsb)
+            };
+
+            info!(
+                "          {:<10} {:<8} {:#x}",
+                f.name,
+                bits,
+                f.extract(value)
+            );
+        }
+    }
+}
+
+/// Look up a register by name, ignoring case.
+pub fn find(name: &str) -> Option<&'static SysReg> {
+    REGISTERS.iter().find(|r| r.name.eq_ignore_ascii_case(name))
+}
+
+/// Print the names of all known registers.
+pub fn print_list() {
+    for r in REGISTERS {
+        info!("      {}", r.name);
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Fields must be extracted at their position and width.
+    #[kernel_test]
+    fn field_extract() {
+        let reg = 0xABCD_0000_0000_1235;
+
+        assert_eq!(Field::new("M", 0, 1).extract(reg), 1);
+        assert_eq!(Field::new("A", 1, 1).extract(reg), 0);
+        assert_eq!(Field::new("X", 4, 8).extract(reg), 0x23);
+        assert_eq!(Field::new("Top", 48, 16).extract(reg), 0xABCD);
+        assert_eq!(Field::new("All", 0, 64).extract(reg), reg);
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Kernel threads.
+//!
+//! A kernel thread is a scheduler task: it has its own stack, is preempted once its time slice is
+//! used up, and can block in [`sleep()`] while the other threads run. See [`crate::sched`] for how
+//! the threads are scheduled.
+
+use crate::sched;
+use alloc::boxed::Box;
+use core::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Start a kernel thread that runs `f`. Returns the thread's id.
+///
+/// Can be called from IRQ context.
+pub fn spawn(name: &str, f: impl FnOnce() + Send + 'static) -> Result<usize, &'static str> {
+    let mut f = Some(f);
+
+    sched::spawn(
+        name,
+        Box::new(move || {
+            if let Some(f) = f.take() {
+                f();
+            }
+        }),
+    )
+}
+
+/// Block the calling thread for at least `duration`, letting the other threads run. Outside of a
+/// thread, this busy-waits.
+pub fn sleep(duration: Duration) {
+    sched::sleep(duration)
+}
+
+/// Let the other threads run before continuing.
+pub fn yield_now() {
+    sched::yield_now()
+}
+
+/// Return the id of the calling thread, or `None` outside of a thread.
+pub fn current() -> Option<usize> {
+    sched::current()
+}
+
+/// Change the priority of thread `id`. Higher values run first, the default is
+/// [`sched::DEFAULT_PRIORITY`].
+pub fn set_priority(id: usize, priority: u8) -> Result<(), &'static str> {
+    sched::set_priority(id, priority)
+}
@@ -4,6 +4,32 @@
 
 //! Timer primitives.
 //!
+//! A periodic timeout whose next due time has already passed when its callback returns overran its
+//! period. After [`OVERLOAD_THRESHOLD`] consecutive overruns it is overloaded, and the configured
+//! [`OverloadPolicy`] decides whether the missed ticks are still run, skipped or coalesced.
+//!
+//! Timeouts are kept in a min-heap ordered by due time, and the timer comparator is armed for the
+//! earliest one only, so that there is no periodic polling. Due times have the resolution of the
+//! architectural counter, i.e. well below a microsecond. One IRQ runs all timeouts that are due.
+//!
+//! Setting a timeout returns a [`TimeoutHandle`], which cancels the timeout. A periodic timeout
+//! cancelled from its own callback isn't rescheduled.
+//!
+//! Each core has its own queue and comparator, and runs the callbacks of its queue from its own
+//! timer IRQ. Timeouts are set on the executing core unless another one is named, in which case
+//! an IPI asks that core to arm its comparator. A core only runs callbacks once its timer IRQ is
+//! enabled in the interrupt controller.
+//!
+//! All durations are converted with the counter frequency the firmware put in CNTFRQ_EL0. If it is
+//! wrong, every delay is silently off by the same factor. [`calibrate()`] counts the architectural
+//! counter against a [`interface::ReferenceClock`] of the board, and if the two disagree by more
+//! than [`CALIBRATION_TOLERANCE_PPM`], it warns and converts with the measured frequency instead.
+//! The counter is also checked for going backwards on every timer IRQ.
+//!
+//! A [`TimeManager`] reads the uptime and arms the comparator through an [`interface::Timer`]. The
+//! global one runs on the architectural timer. Tests build their own on a [`ManualTimer`], advance
+//! it by hand and run the due timeouts with [`TimeManager::run_due()`].
+//!
 //! # Resources
 //!
 //! - <https://stackoverflow.com/questions/41081240/idiomatic-callbacks-in-rust>
@@ -13,15 +39,24 @@
 #[path = "_arch/aarch64/time.rs"]
 mod arch_time;
 
+mod format;
+mod manual;
+
 use crate::{
-    driver, exception,
+    bsp, config, cpu, driver, exception,
     exception::asynchronous::IRQNumber,
-    synchronization::{interface::Mutex, IRQSafeNullLock},
+    info,
+    synchronization::{
+        interface::{Mutex, ReadWriteEx},
+        IRQSafeDeviceLock, IRQSafeNullLock, InitStateLock,
+    },
     warn,
 };
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, format, vec::Vec};
 use core::{
-    sync::atomic::{AtomicBool, Ordering},
+    fmt,
+    num::NonZeroU32,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
     time::Duration,
 };
 
@@ -29,27 +64,152 @@ use core::{
 // Private Definitions
 //--------------------------------------------------------------------------------------------------
 
+/// Config store key of the overload policy.
+const OVERLOAD_POLICY_KEY: &str = "time.overload_policy";
+
+/// Most timeouts run by one IRQ, so that a burst of due timeouts can't hold off other IRQs.
+const MAX_TIMEOUTS_PER_IRQ: usize = 16;
+
+/// Part of a second the counters are compared over.
+const CALIBRATION_WINDOW_DIVISOR: u32 = 20;
+
+/// Result of the last calibration.
+#[derive(Copy, Clone)]
+struct Calibration {
+    reference: &'static str,
+    measured: u32,
+    deviation_ppm: i64,
+}
+
 struct Timeout {
+    id: u64,
     due_time: Duration,
     period: Option<Duration>,
     callback: TimeoutCallback,
+
+    /// Consecutive overruns of the period.
+    overruns: u32,
 }
 
+struct OverloadStats {
+    overruns: u64,
+    episodes: u64,
+    skipped: u64,
+}
+
+/// The architectural timer of the executing core.
+struct ArchTimer;
+
+/// A binary min-heap of timeouts, ordered by due time and then by id, so that timeouts due at the
+/// same time fire in the order they were set.
 struct OrderedTimeoutQueue {
     // Can be replaced with a BinaryHeap once it's new() becomes const.
     inner: Vec<Timeout>,
+
+    /// The timeout whose callback runs, taken out of `inner` meanwhile.
+    running: Option<u64>,
+
+    /// Set if the running timeout was cancelled by its callback.
+    running_cancelled: bool,
 }
 
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
 //--------------------------------------------------------------------------------------------------
 
+pub use format::{Clock, Human, Seconds};
+pub use manual::ManualTimer;
+
+/// Timer interfaces.
+pub mod interface {
+    use core::time::Duration;
+
+    /// The counter and comparator a [`super::TimeManager`] runs its timeouts on.
+    pub trait Timer {
+        /// The uptime since power-on of the device.
+        fn uptime(&self) -> Duration;
+
+        /// Spin for a given duration.
+        fn spin_for(&self, duration: Duration);
+
+        /// The smallest step of the uptime.
+        fn resolution(&self) -> Duration;
+
+        /// Fire the executing core's timeout IRQ once the uptime reached `due_time`. Replaces the
+        /// previous due time.
+        fn set_timeout_irq(&self, due_time: Duration);
+
+        /// Don't fire the executing core's timeout IRQ until it is armed again.
+        fn clear_timeout_irq(&self);
+
+        /// Have core `core` arm its comparator for the earliest timeout of its queue.
+        fn wake(&self, core: usize) -> Result<(), &'static str>;
+    }
+
+    /// A clock that runs independently of the architectural counter.
+    pub trait ReferenceClock {
+        /// Name of the clock.
+        fn name(&self) -> &'static str;
+
+        /// Frequency of the counter in Hz.
+        fn frequency(&self) -> u32;
+
+        /// Read the counter.
+        fn counter(&self) -> u64;
+    }
+}
+
 /// The callback type used by timer IRQs.
 pub type TimeoutCallback = Box<dyn Fn() + Send>;
 
+/// Identifies a timeout that was set, so that it can be cancelled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimeoutHandle {
+    id: u64,
+    core: usize,
+}
+
+/// A point in time plus a sequence number.
+///
+/// Sequence numbers are unique and increase monotonically across all subsystems, so records taken
+/// from different sources can be ordered even when their times are equal.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp {
+    /// Sequence number. Compared first, so that sorting yields the order of creation.
+    pub seq: u64,
+
+    /// Uptime in nanoseconds, from the architectural counter.
+    pub ns: u64,
+}
+
+/// Deviation from the firmware's counter frequency that calibration accepts, in parts per million.
+/// Beyond it the firmware's value is wrong rather than the crystal off.
+pub const CALIBRATION_TOLERANCE_PPM: i64 = 1000;
+
+/// Consecutive overruns after which a periodic timeout counts as overloaded.
+pub const OVERLOAD_THRESHOLD: u32 = 10;
+
+/// What to do with the ticks an overloaded periodic timeout missed.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum OverloadPolicy {
+    /// Log and run every missed tick, catching up as fast as possible.
+    Log,
+
+    /// Drop the missed ticks and continue on the original schedule.
+    Skip,
+
+    /// Drop the missed ticks and restart the schedule from now.
+    Coalesce,
+}
+
 /// Provides time management functions.
 pub struct TimeManager {
-    queue: IRQSafeNullLock<OrderedTimeoutQueue>,
+    timer: &'static (dyn interface::Timer + Sync),
+
+    /// One queue per core. Other cores add to it, so it needs a real lock.
+    queues: [IRQSafeDeviceLock<OrderedTimeoutQueue>; bsp::cpu::NUM_CORES],
+    overload_policy: IRQSafeNullLock<OverloadPolicy>,
+    overload_stats: IRQSafeNullLock<OverloadStats>,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -58,11 +218,28 @@ pub struct TimeManager {
 
 static TIME_MANAGER: TimeManager = TimeManager::new();
 
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+static NEXT_TIMEOUT_ID: AtomicU64 = AtomicU64::new(1);
+
+static REFERENCE_CLOCK: InitStateLock<Option<&'static (dyn interface::ReferenceClock + Sync)>> =
+    InitStateLock::new(None);
+
+static CALIBRATION: IRQSafeNullLock<Option<Calibration>> = IRQSafeNullLock::new(None);
+
+/// Highest counter value seen, and the number of times the counter was below it.
+static LAST_COUNTER: AtomicU64 = AtomicU64::new(0);
+static BACKWARDS: AtomicU64 = AtomicU64::new(0);
+
 //--------------------------------------------------------------------------------------------------
 // Private Code
 //--------------------------------------------------------------------------------------------------
 
 impl Timeout {
+    fn key(&self) -> (Duration, u64) {
+        (self.due_time, self.id)
+    }
+
     pub fn is_periodic(&self) -> bool {
         self.period.is_some()
     }
@@ -76,25 +253,164 @@ impl Timeout {
 
 impl OrderedTimeoutQueue {
     pub const fn new() -> Self {
-        Self { inner: Vec::new() }
+        Self {
+            inner: Vec::new(),
+            running: None,
+            running_cancelled: false,
+        }
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if self.inner[i].key() >= self.inner[parent].key() {
+                break;
+            }
+
+            self.inner.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let mut first = i;
+            for child in [2 * i + 1, 2 * i + 2] {
+                if child < self.inner.len() && self.inner[child].key() < self.inner[first].key() {
+                    first = child;
+                }
+            }
+            if first == i {
+                break;
+            }
+
+            self.inner.swap(i, first);
+            i = first;
+        }
+    }
+
+    /// Remove the timeout at heap position `pos`.
+    fn remove(&mut self, pos: usize) -> Timeout {
+        let timeout = self.inner.swap_remove(pos);
+        if pos < self.inner.len() {
+            self.sift_down(pos);
+            self.sift_up(pos);
+        }
+
+        timeout
     }
 
     pub fn push(&mut self, timeout: Timeout) {
         self.inner.push(timeout);
-
-        // Note reverse compare order so that earliest expiring item is at end of vec. We do this so
-        // that we can use Vec::pop below to retrieve the item that is next due.
-        self.inner.sort_by(|a, b| b.due_time.cmp(&a.due_time));
+        self.sift_up(self.inner.len() - 1);
     }
 
     pub fn peek_next_due_time(&self) -> Option<Duration> {
-        let timeout = self.inner.last()?;
+        let timeout = self.inner.first()?;
 
         Some(timeout.due_time)
     }
 
     pub fn pop(&mut self) -> Option<Timeout> {
-        self.inner.pop()
+        if self.inner.is_empty() {
+            return None;
+        }
+
+        Some(self.remove(0))
+    }
+
+    /// Remove timeout `id`. Returns if it was still active.
+    pub fn cancel(&mut self, id: u64) -> bool {
+        if self.running == Some(id) {
+            return !core::mem::replace(&mut self.running_cancelled, true);
+        }
+
+        match self.inner.iter().position(|t| t.id == id) {
+            Some(pos) => {
+                self.remove(pos);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return if timeout `id` is queued or its callback runs.
+    pub fn is_active(&self, id: u64) -> bool {
+        if self.running == Some(id) {
+            return !self.running_cancelled;
+        }
+
+        self.inner.iter().any(|t| t.id == id)
+    }
+}
+
+/// Deviation of `measured` from `nominal` in parts per million.
+fn deviation_ppm(nominal: u32, measured: u32) -> i64 {
+    (measured as i64 - nominal as i64) * 1_000_000 / nominal as i64
+}
+
+/// Round a measured frequency to whole kHz, which absorbs the error of the measurement.
+fn round_frequency(hz: u64) -> u64 {
+    (hz + 500) / 1000 * 1000
+}
+
+/// Warn if the counter is below a value it had before.
+fn check_monotonic() {
+    let now = arch_time::counter();
+    let last = LAST_COUNTER.fetch_max(now, Ordering::Relaxed);
+
+    if now < last {
+        BACKWARDS.fetch_add(1, Ordering::Relaxed);
+        warn!("Timer counter went backwards by {} ticks", last - now);
+    }
+}
+
+/// Count the architectural counter over a window of `clock` and return its frequency.
+fn measure(clock: &dyn interface::ReferenceClock) -> Result<u32, &'static str> {
+    let window = (clock.frequency() / CALIBRATION_WINDOW_DIVISOR) as u64;
+    let limit = arch_time::firmware_frequency().get() as u64;
+
+    // Start on an edge of the reference, so that only the end is off by up to one tick.
+    let first = clock.counter();
+    let start_counter = arch_time::counter();
+    let (ref_start, start) = loop {
+        let now = clock.counter();
+        if now != first {
+            break (now, arch_time::counter());
+        }
+        if arch_time::counter().wrapping_sub(start_counter) > limit {
+            return Err("Reference clock stuck");
+        }
+    };
+
+    let (ref_end, end) = loop {
+        let now = clock.counter();
+        let counter = arch_time::counter();
+        if now.wrapping_sub(ref_start) >= window {
+            break (now, counter);
+        }
+        if counter.wrapping_sub(start) > limit {
+            return Err("Reference clock stuck");
+        }
+    };
+
+    let hz = end.wrapping_sub(start) as u128 * clock.frequency() as u128
+        / ref_end.wrapping_sub(ref_start) as u128;
+    let hz = round_frequency(hz as u64);
+    if hz == 0 || hz > u32::MAX as u64 {
+        return Err("Measured frequency out of range");
+    }
+
+    Ok(hz as u32)
+}
+
+impl OverloadStats {
+    const fn new() -> Self {
+        Self {
+            overruns: 0,
+            episodes: 0,
+            skipped: 0,
+        }
     }
 }
 
@@ -102,68 +418,488 @@ impl OrderedTimeoutQueue {
 // Public Code
 //--------------------------------------------------------------------------------------------------
 
+impl OverloadPolicy {
+    /// Return the next due time of a periodic timeout with the given `period` that was due at
+    /// `due` and is late at `now`, and the number of ticks skipped.
+    pub fn reschedule(self, due: Duration, period: Duration, now: Duration) -> (Duration, u64) {
+        if period.is_zero() || due > now {
+            return (due, 0);
+        }
+
+        // Number of ticks due at or before now.
+        let missed = ((now - due).as_nanos() / period.as_nanos()) as u64 + 1;
+
+        match self {
+            Self::Log => (due, 0),
+            Self::Skip => (due + period * missed as u32, missed),
+            Self::Coalesce => (now + period, missed),
+        }
+    }
+}
+
+impl core::str::FromStr for OverloadPolicy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "log" => Ok(Self::Log),
+            "skip" => Ok(Self::Skip),
+            "coalesce" => Ok(Self::Coalesce),
+            _ => Err("Unknown overload policy"),
+        }
+    }
+}
+
+impl fmt::Display for OverloadPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Log => write!(f, "log"),
+            Self::Skip => write!(f, "skip"),
+            Self::Coalesce => write!(f, "coalesce"),
+        }
+    }
+}
+
+impl TimeoutHandle {
+    /// Cancel the timeout. Returns if it was still active, i.e. a one-shot timeout hadn't fired
+    /// yet or a periodic one wasn't cancelled before.
+    ///
+    /// Can be called from IRQ context, including from the timeout's own callback.
+    pub fn cancel(&self) -> bool {
+        TIME_MANAGER.cancel(self)
+    }
+
+    /// Return if the timeout will still fire, or its callback runs right now.
+    pub fn is_active(&self) -> bool {
+        TIME_MANAGER.is_active(self)
+    }
+}
+
+/// Register the clock the counter frequency is calibrated against.
+pub fn register_reference_clock(clock: &'static (dyn interface::ReferenceClock + Sync)) {
+    REFERENCE_CLOCK.write(|c| *c = Some(clock));
+}
+
+/// Measure the counter frequency against the reference clock. If the firmware's value is off by
+/// more than [`CALIBRATION_TOLERANCE_PPM`], durations are converted with the measured one from now
+/// on, otherwise with the firmware's again. Returns the frequency in use.
+pub fn calibrate() -> Result<u32, &'static str> {
+    let clock = REFERENCE_CLOCK.read(|c| *c).ok_or("No reference clock")?;
+    check_monotonic();
+
+    let measured = measure(clock)?;
+    let firmware = arch_time::firmware_frequency();
+    let deviation = deviation_ppm(firmware.get(), measured);
+    let frequency = if deviation.abs() > CALIBRATION_TOLERANCE_PPM {
+        warn!(
+            "CNTFRQ_EL0 reports {} Hz, measured {} Hz against {}. Using the measured value",
+            firmware,
+            measured,
+            clock.name()
+        );
+        NonZeroU32::new(measured).unwrap()
+    } else {
+        firmware
+    };
+
+    // Armed with the old conversion, the comparator must be set again.
+    time_manager().local_queue().lock(|queue| {
+        arch_time::set_frequency(frequency);
+        if let Some(due_time) = queue.peek_next_due_time() {
+            arch_time::set_timeout_irq(due_time);
+        }
+    });
+    CALIBRATION.lock(|c| {
+        *c = Some(Calibration {
+            reference: clock.name(),
+            measured,
+            deviation_ppm: deviation,
+        })
+    });
+
+    Ok(frequency.get())
+}
+
+/// Print the counter frequencies and the result of the last calibration.
+pub fn print_calibration() {
+    info!("      Firmware:   {} Hz", arch_time::firmware_frequency());
+    info!("      In use:     {} Hz", arch_time::frequency());
+    match CALIBRATION.lock(|c| *c) {
+        Some(c) => info!(
+            "      Measured:   {} Hz against {}, {:+} ppm",
+            c.measured, c.reference, c.deviation_ppm
+        ),
+        None => info!("      Measured:   never"),
+    }
+    info!("      Backwards:  {}", BACKWARDS.load(Ordering::Relaxed));
+}
+
 /// Return a reference to the global TimeManager.
 pub fn time_manager() -> &'static TimeManager {
     &TIME_MANAGER
 }
 
+/// Return the IRQ that drives the timeouts.
+pub fn timeout_irq() -> IRQNumber {
+    arch_time::timeout_irq()
+}
+
+/// The uptime in nanoseconds, read straight from the architectural timer. Takes no lock, for
+/// measurements in any context.
+pub fn uptime_ns() -> u64 {
+    arch_time::uptime().as_nanos() as u64
+}
+
+/// Take a timestamp.
+pub fn timestamp() -> Timestamp {
+    Timestamp {
+        seq: NEXT_SEQ.fetch_add(1, Ordering::Relaxed),
+        ns: arch_time::uptime().as_nanos() as u64,
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "#{} {}.{:09}",
+            self.seq,
+            self.ns / 1_000_000_000,
+            self.ns % 1_000_000_000
+        )
+    }
+}
+
 impl TimeManager {
     /// Compatibility string.
     pub const COMPATIBLE: &'static str = "ARM Architectural Timer";
 
-    /// Create an instance.
+    /// Only used to initialize the per-core queues, as array repeat expressions need a
+    /// constant. Each use makes a new queue, none is shared.
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY_QUEUE: IRQSafeDeviceLock<OrderedTimeoutQueue> =
+        IRQSafeDeviceLock::new(OrderedTimeoutQueue::new());
+
+    /// Create an instance on the architectural timer.
     pub const fn new() -> Self {
+        Self::with_timer(&ArchTimer)
+    }
+
+    /// Create an instance on `timer`.
+    pub const fn with_timer(timer: &'static (dyn interface::Timer + Sync)) -> Self {
         Self {
-            queue: IRQSafeNullLock::new(OrderedTimeoutQueue::new()),
+            timer,
+            queues: [Self::EMPTY_QUEUE; bsp::cpu::NUM_CORES],
+            overload_policy: IRQSafeNullLock::new(OverloadPolicy::Skip),
+            overload_stats: IRQSafeNullLock::new(OverloadStats::new()),
         }
     }
 
     /// The timer's resolution.
     pub fn resolution(&self) -> Duration {
-        arch_time::resolution()
+        self.timer.resolution()
     }
 
     /// The uptime since power-on of the device.
     ///
     /// This includes time consumed by firmware and bootloaders.
     pub fn uptime(&self) -> Duration {
-        arch_time::uptime()
+        self.timer.uptime()
     }
 
     /// Spin for a given duration.
     pub fn spin_for(&self, duration: Duration) {
-        arch_time::spin_for(duration)
+        self.timer.spin_for(duration)
+    }
+
+    /// The queue of the executing core.
+    fn local_queue(&self) -> &IRQSafeDeviceLock<OrderedTimeoutQueue> {
+        &self.queues[cpu::smp::core_id::<usize>()]
     }
 
-    /// Set a timeout.
-    fn set_timeout(&self, timeout: Timeout) {
-        self.queue.lock(|queue| {
+    /// Arm the executing core's comparator for the earliest timeout of its queue, or disarm it if
+    /// the queue is empty.
+    fn arm(&self) {
+        self.local_queue()
+            .lock(|queue| match queue.peek_next_due_time() {
+                Some(due_time) => self.timer.set_timeout_irq(due_time),
+                None => self.timer.clear_timeout_irq(),
+            });
+    }
+
+    /// Add a timeout to the queue of `core`. Returns its handle, and whether it is the earliest
+    /// one, i.e. whether the comparator must be armed again.
+    fn push(&self, core: usize, timeout: Timeout) -> (TimeoutHandle, bool) {
+        let handle = TimeoutHandle {
+            id: timeout.id,
+            core,
+        };
+
+        let earliest = self.queues[core].lock(|queue| {
+            let due_time = timeout.due_time;
             queue.push(timeout);
 
-            arch_time::set_timeout_irq(queue.peek_next_due_time().unwrap());
+            queue.peek_next_due_time() == Some(due_time)
         });
+
+        (handle, earliest)
+    }
+
+    /// Set a timeout on the executing core.
+    fn set_timeout(&self, timeout: Timeout) -> TimeoutHandle {
+        let (handle, earliest) = self.push(cpu::smp::core_id(), timeout);
+        if earliest {
+            self.arm();
+        }
+
+        handle
+    }
+
+    /// Set a timeout on `core`.
+    fn set_timeout_on(&self, core: usize, timeout: Timeout) -> Result<TimeoutHandle, &'static str> {
+        if core >= bsp::cpu::NUM_CORES {
+            return Err("No such core");
+        }
+        if core == cpu::smp::core_id::<usize>() {
+            return Ok(self.set_timeout(timeout));
+        }
+
+        let (handle, earliest) = self.push(core, timeout);
+        if earliest {
+            if let Err(x) = self.timer.wake(core) {
+                self.cancel(&handle);
+                return Err(x);
+            }
+        }
+
+        Ok(handle)
     }
 
-    /// Set a one-shot timeout.
-    pub fn set_timeout_once(&self, delay: Duration, callback: TimeoutCallback) {
-        let timeout = Timeout {
+    /// Create a timeout that is first due after `delay`, and then every `period` if given.
+    fn new_timeout(
+        &self,
+        delay: Duration,
+        period: Option<Duration>,
+        callback: TimeoutCallback,
+    ) -> Timeout {
+        Timeout {
+            id: NEXT_TIMEOUT_ID.fetch_add(1, Ordering::Relaxed),
             due_time: self.uptime() + delay,
-            period: None,
+            period,
             callback,
+            overruns: 0,
+        }
+    }
+
+    /// Cancel the timeout of `handle`. See [`TimeoutHandle::cancel()`].
+    pub fn cancel(&self, handle: &TimeoutHandle) -> bool {
+        let (cancelled, earliest) = self.queues[handle.core].lock(|queue| {
+            let next = queue.peek_next_due_time();
+            let cancelled = queue.cancel(handle.id);
+
+            (cancelled, queue.peek_next_due_time() != next)
+        });
+
+        // The comparator is still armed for the cancelled timeout, and would fire for nothing.
+        if earliest {
+            if handle.core == cpu::smp::core_id::<usize>() {
+                self.arm();
+            } else {
+                let _ = self.timer.wake(handle.core);
+            }
+        }
+
+        cancelled
+    }
+
+    /// Return if the timeout of `handle` will still fire, or its callback runs right now.
+    pub fn is_active(&self, handle: &TimeoutHandle) -> bool {
+        self.queues[handle.core].lock(|queue| queue.is_active(handle.id))
+    }
+
+    /// Return the number of timeouts queued on each core.
+    pub fn queued(&self) -> [usize; bsp::cpu::NUM_CORES] {
+        let mut queued = [0; bsp::cpu::NUM_CORES];
+        for (n, queue) in queued.iter_mut().zip(self.queues.iter()) {
+            *n = queue.lock(|queue| queue.inner.len());
+        }
+
+        queued
+    }
+
+    /// Run the callbacks of the executing core's due timeouts, at most [`MAX_TIMEOUTS_PER_IRQ`],
+    /// and arm the timer for the next one. Returns the number of callbacks run.
+    ///
+    /// Called by the timeout IRQ. If timeouts are left over, the IRQ fires again right away.
+    pub fn run_due(&self) -> usize {
+        let mut ran = 0;
+        while ran < MAX_TIMEOUTS_PER_IRQ && self.run_next_due() {
+            ran += 1;
+        }
+        self.arm();
+
+        ran
+    }
+
+    /// Set a one-shot timeout on the executing core.
+    pub fn set_timeout_once(&self, delay: Duration, callback: TimeoutCallback) -> TimeoutHandle {
+        self.set_timeout(self.new_timeout(delay, None, callback))
+    }
+
+    /// Set a periodic timeout on the executing core.
+    pub fn set_timeout_periodic(
+        &self,
+        delay: Duration,
+        callback: TimeoutCallback,
+    ) -> TimeoutHandle {
+        self.set_timeout(self.new_timeout(delay, Some(delay), callback))
+    }
+
+    /// Set a one-shot timeout whose callback runs on `core`.
+    pub fn set_timeout_once_on(
+        &self,
+        core: usize,
+        delay: Duration,
+        callback: TimeoutCallback,
+    ) -> Result<TimeoutHandle, &'static str> {
+        self.set_timeout_on(core, self.new_timeout(delay, None, callback))
+    }
+
+    /// Set a periodic timeout whose callback runs on `core`.
+    pub fn set_timeout_periodic_on(
+        &self,
+        core: usize,
+        delay: Duration,
+        callback: TimeoutCallback,
+    ) -> Result<TimeoutHandle, &'static str> {
+        self.set_timeout_on(core, self.new_timeout(delay, Some(delay), callback))
+    }
+
+    /// Return the policy for overloaded periodic timeouts.
+    pub fn overload_policy(&self) -> OverloadPolicy {
+        self.overload_policy.lock(|p| *p)
+    }
+
+    /// Change the policy for overloaded periodic timeouts.
+    pub fn set_overload_policy(&self, policy: OverloadPolicy) {
+        self.overload_policy.lock(|p| *p = policy);
+    }
+
+    /// Apply the overload policy from the config store, if set.
+    pub fn load_overload_policy(&self) -> Result<(), &'static str> {
+        if let Some(policy) = config::store().get(OVERLOAD_POLICY_KEY) {
+            self.set_overload_policy(policy.parse()?);
+        }
+
+        Ok(())
+    }
+
+    /// Write the overload policy to the config store. It is persisted on the next save.
+    pub fn store_overload_policy(&self) -> Result<(), &'static str> {
+        config::store().set(OVERLOAD_POLICY_KEY, &format!("{}", self.overload_policy()))
+    }
+
+    /// Print the overload policy and statistics.
+    pub fn print_overload(&self) {
+        info!("      Policy:    {}", self.overload_policy());
+        self.overload_stats.lock(|stats| {
+            info!("      Overruns:  {}", stats.overruns);
+            info!("      Overloads: {}", stats.episodes);
+            info!("      Skipped:   {}", stats.skipped);
+        });
+    }
+
+    /// Run the callback of the next timeout if it is due. Returns if there was one.
+    fn run_next_due(&self) -> bool {
+        let maybe_timeout: Option<Timeout> = self.local_queue().lock(|queue| {
+            let next_due_time = queue.peek_next_due_time()?;
+            if next_due_time > self.uptime() {
+                return None;
+            }
+
+            let mut timeout = queue.pop().unwrap();
+            queue.running = Some(timeout.id);
+            queue.running_cancelled = false;
+
+            // Refresh as early as possible to prevent drift.
+            if timeout.is_periodic() {
+                timeout.refresh();
+            }
+
+            Some(timeout)
+        });
+
+        let mut timeout = match maybe_timeout {
+            None => return false,
+            Some(t) => t,
         };
 
-        self.set_timeout(timeout);
+        // Important: Call the callback while not holding any lock, because the callback might
+        // attempt to modify data that is protected by a lock (in particular, the timeout queue
+        // itself).
+        (timeout.callback)();
+        if timeout.is_periodic() {
+            self.check_overrun(&mut timeout);
+        }
+
+        self.local_queue().lock(|queue| {
+            let cancelled = queue.running_cancelled;
+            queue.running = None;
+            queue.running_cancelled = false;
+
+            if timeout.is_periodic() && !cancelled {
+                // There might be some overhead involved in the periodic path, because the timeout
+                // item is first popped from the heap and then pushed back again. It could be
+                // faster to keep the item in the queue and find a way to work with a reference to
+                // it.
+                //
+                // We are not going this route on purpose, though. It allows to keep the code simple
+                // and the focus on the high-level concepts.
+                queue.push(timeout);
+            };
+        });
+
+        true
     }
 
-    /// Set a periodic timeout.
-    pub fn set_timeout_periodic(&self, delay: Duration, callback: TimeoutCallback) {
-        let timeout = Timeout {
-            due_time: self.uptime() + delay,
-            period: Some(delay),
-            callback,
+    /// Account for a periodic timeout whose callback just returned, applying the overload policy.
+    fn check_overrun(&self, timeout: &mut Timeout) {
+        let now = self.uptime();
+        let period = match timeout.period {
+            Some(period) if timeout.due_time <= now => period,
+            _ => {
+                timeout.overruns = 0;
+                return;
+            }
         };
 
-        self.set_timeout(timeout);
+        timeout.overruns = timeout.overruns.saturating_add(1);
+        let overloaded = timeout.overruns >= OVERLOAD_THRESHOLD;
+        let policy = self.overload_policy();
+
+        if timeout.overruns == OVERLOAD_THRESHOLD {
+            warn!(
+                "Timer overload: {} period, {} behind, policy {}",
+                Human(period),
+                Human(now - timeout.due_time),
+                policy
+            );
+        }
+
+        let mut skipped = 0;
+        if overloaded {
+            let (due_time, s) = policy.reschedule(timeout.due_time, period, now);
+            timeout.due_time = due_time;
+            skipped = s;
+        }
+
+        self.overload_stats.lock(|stats| {
+            stats.overruns += 1;
+            stats.skipped += skipped;
+            if timeout.overruns == OVERLOAD_THRESHOLD {
+                stats.episodes += 1;
+            }
+        });
     }
 }
 
@@ -174,9 +910,19 @@ pub fn init() -> Result<(), &'static str> {
         return Err("Init already done");
     }
 
-    let timer_descriptor =
-        driver::DeviceDriverDescriptor::new(time_manager(), None, Some(arch_time::timeout_irq()));
-    driver::driver_manager().register_driver(timer_descriptor);
+    let timer_descriptor = driver::DeviceDriverDescriptor::new(
+        time_manager(),
+        None,
+        Some(arch_time::timeout_irq()),
+        &[],
+    );
+    driver::driver_manager().register_driver(timer_descriptor)?;
+
+    // Another core set a timeout on this one and asks it to arm its comparator.
+    exception::asynchronous::register_ipi_handler(
+        exception::asynchronous::IpiMessage::RunTimers,
+        || time_manager().arm(),
+    )?;
 
     INIT_DONE.store(true, Ordering::Relaxed);
     Ok(())
@@ -208,56 +954,214 @@ impl driver::interface::DeviceDriver for TimeManager {
     }
 }
 
+impl interface::Timer for ArchTimer {
+    fn uptime(&self) -> Duration {
+        arch_time::uptime()
+    }
+
+    fn spin_for(&self, duration: Duration) {
+        arch_time::spin_for(duration)
+    }
+
+    fn resolution(&self) -> Duration {
+        arch_time::resolution()
+    }
+
+    fn set_timeout_irq(&self, due_time: Duration) {
+        arch_time::set_timeout_irq(due_time)
+    }
+
+    fn clear_timeout_irq(&self) {
+        arch_time::conclude_timeout_irq()
+    }
+
+    fn wake(&self, core: usize) -> Result<(), &'static str> {
+        exception::asynchronous::send_ipi(core, exception::asynchronous::IpiMessage::RunTimers)
+    }
+}
+
 impl exception::asynchronous::interface::IRQHandler for TimeManager {
     fn handle(&self) -> Result<(), &'static str> {
         arch_time::conclude_timeout_irq();
+        check_monotonic();
 
-        let maybe_timeout: Option<Timeout> = self.queue.lock(|queue| {
-            let next_due_time = queue.peek_next_due_time()?;
-            if next_due_time > self.uptime() {
-                return None;
-            }
+        // Concluding disabled the comparator, so it is armed again even if nothing ran.
+        if self.run_due() == 0 {
+            warn!("Spurious timeout IRQ");
+        }
 
-            let mut timeout = queue.pop().unwrap();
+        Ok(())
+    }
+}
 
-            // Refresh as early as possible to prevent drift.
-            if timeout.is_periodic() {
-                timeout.refresh();
-            }
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
 
-            Some(timeout)
-        });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
 
-        let timeout = match maybe_timeout {
-            None => {
-                warn!("Spurious timeout IRQ");
-                return Ok(());
-            }
-            Some(t) => t,
+    /// Skipping must keep the phase of the schedule, coalescing must restart it from now.
+    #[kernel_test]
+    fn overload_reschedule() {
+        let ms = Duration::from_millis;
+
+        assert_eq!(
+            OverloadPolicy::Log.reschedule(ms(10), ms(10), ms(35)),
+            (ms(10), 0)
+        );
+        assert_eq!(
+            OverloadPolicy::Skip.reschedule(ms(10), ms(10), ms(35)),
+            (ms(40), 3)
+        );
+        assert_eq!(
+            OverloadPolicy::Coalesce.reschedule(ms(10), ms(10), ms(35)),
+            (ms(45), 3)
+        );
+        assert_eq!(
+            OverloadPolicy::Skip.reschedule(ms(40), ms(10), ms(35)),
+            (ms(40), 0)
+        );
+    }
+
+    /// Frequencies must round to whole kHz, and only large deviations exceed the tolerance.
+    #[kernel_test]
+    fn calibration_deviation() {
+        assert_eq!(round_frequency(54_000_499), 54_000_000);
+        assert_eq!(round_frequency(19_199_500), 19_200_000);
+
+        assert_eq!(deviation_ppm(54_000_000, 54_000_000), 0);
+        assert_eq!(deviation_ppm(1_000_000, 1_000_050), 50);
+        assert!(deviation_ppm(62_500_000, 54_000_000) < -CALIBRATION_TOLERANCE_PPM);
+        assert!(deviation_ppm(19_200_000, 19_210_000) <= CALIBRATION_TOLERANCE_PPM);
+    }
+
+    /// Timeouts must come out of the heap by due time, and in setting order if they are equal,
+    /// also after one was taken out of the middle.
+    #[kernel_test]
+    fn queue_order() {
+        let timeout = |id, us| Timeout {
+            id,
+            due_time: Duration::from_micros(us),
+            period: None,
+            callback: Box::new(|| ()),
+            overruns: 0,
         };
+        let mut queue = OrderedTimeoutQueue::new();
+        for (id, us) in [
+            (1, 50),
+            (2, 10),
+            (3, 30),
+            (4, 10),
+            (5, 70),
+            (6, 20),
+            (7, 40),
+        ] {
+            queue.push(timeout(id, us));
+        }
+        assert!(queue.cancel(3));
 
-        // Important: Call the callback while not holding any lock, because the callback might
-        // attempt to modify data that is protected by a lock (in particular, the timeout queue
-        // itself).
-        (timeout.callback)();
+        let mut order = Vec::new();
+        while let Some(t) = queue.pop() {
+            order.push(t.id);
+        }
+        assert_eq!(order, [2, 4, 6, 7, 1, 5]);
+    }
 
-        self.queue.lock(|queue| {
-            if timeout.is_periodic() {
-                // There might be some overhead involved in the periodic path, because the timeout
-                // item is first popped from the underlying Vec and then pushed back again. It could
-                // be faster to keep the item in the queue and find a way to work with a reference
-                // to it.
-                //
-                // We are not going this route on purpose, though. It allows to keep the code simple
-                // and the focus on the high-level concepts.
-                queue.push(timeout);
-            };
+    /// A cancelled timeout must leave the queue, and a running one must be marked instead.
+    #[kernel_test]
+    fn cancel_timeouts() {
+        let timeout = |id| Timeout {
+            id,
+            due_time: Duration::from_millis(id),
+            period: None,
+            callback: Box::new(|| ()),
+            overruns: 0,
+        };
+        let mut queue = OrderedTimeoutQueue::new();
+        queue.push(timeout(1));
+        queue.push(timeout(2));
+        queue.running = Some(3);
 
-            if let Some(due_time) = queue.peek_next_due_time() {
-                arch_time::set_timeout_irq(due_time);
-            }
-        });
+        assert!(queue.cancel(2));
+        assert!(!queue.is_active(2));
+        assert!(!queue.cancel(2));
+        assert!(queue.is_active(1));
 
-        Ok(())
+        assert!(queue.is_active(3));
+        assert!(queue.cancel(3));
+        assert!(!queue.is_active(3));
+        assert!(!queue.cancel(3));
+    }
+
+    /// Timeouts must run once the manual timer passed their due time, and the timer must be armed
+    /// for the next one.
+    #[kernel_test]
+    fn manual_timer() {
+        static TIMER: ManualTimer = ManualTimer::new();
+        static FIRED: AtomicU64 = AtomicU64::new(0);
+
+        let ms = Duration::from_millis;
+        let manager = TimeManager::with_timer(&TIMER);
+        manager.set_timeout_periodic(
+            ms(10),
+            Box::new(|| {
+                FIRED.fetch_add(1, Ordering::Relaxed);
+            }),
+        );
+        let once = manager.set_timeout_once(
+            ms(15),
+            Box::new(|| {
+                FIRED.fetch_add(100, Ordering::Relaxed);
+            }),
+        );
+        assert_eq!(TIMER.armed(), Some(ms(10)));
+
+        TIMER.advance(ms(9));
+        assert_eq!(manager.run_due(), 0);
+
+        TIMER.advance(ms(1));
+        assert_eq!(manager.run_due(), 1);
+        assert_eq!(FIRED.load(Ordering::Relaxed), 1);
+        assert_eq!(TIMER.armed(), Some(ms(15)));
+
+        TIMER.advance(ms(10));
+        assert_eq!(manager.run_due(), 2);
+        assert_eq!(FIRED.load(Ordering::Relaxed), 102);
+        assert!(!manager.is_active(&once));
+        assert_eq!(TIMER.armed(), Some(ms(30)));
+
+        // A timeout on another core stays in that core's queue.
+        let other = (cpu::smp::core_id::<usize>() + 1) % bsp::cpu::NUM_CORES;
+        let remote = manager
+            .set_timeout_once_on(other, ms(1), Box::new(|| ()))
+            .unwrap();
+        TIMER.advance(ms(5));
+        assert_eq!(manager.run_due(), 0);
+        assert!(manager.is_active(&remote));
+        assert_eq!(manager.queued()[other], 1);
+        assert!(manager.cancel(&remote));
+        assert!(manager
+            .set_timeout_once_on(bsp::cpu::NUM_CORES, ms(1), Box::new(|| ()))
+            .is_err());
+    }
+
+    /// Cancelling the earliest timeout must arm the timer for the next one, or disarm it.
+    #[kernel_test]
+    fn cancel_rearms() {
+        static TIMER: ManualTimer = ManualTimer::new();
+
+        let ms = Duration::from_millis;
+        let manager = TimeManager::with_timer(&TIMER);
+        let first = manager.set_timeout_once(ms(5), Box::new(|| ()));
+        let second = manager.set_timeout_once(ms(10), Box::new(|| ()));
+        assert_eq!(TIMER.armed(), Some(ms(5)));
+
+        assert!(manager.cancel(&first));
+        assert_eq!(TIMER.armed(), Some(ms(10)));
+        assert!(manager.cancel(&second));
+        assert_eq!(TIMER.armed(), None);
     }
 }
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Duration formatting.
+//!
+//! Wrappers that print a [`Duration`] in one of the kernel's standard forms:
+//!
+//! - [`Human`]: scaled to ns, us, ms or s, e.g. `1.250 ms`. For measurements and periods.
+//! - [`Clock`]: `HH:MM:SS`, prefixed with days once past a day. For uptimes and times of day.
+//! - [`Seconds`]: seconds with microseconds, e.g. `  3.000125`. For log timestamps.
+//!
+//! [`Human`] and [`Clock`] honor width and alignment, so they line up in tables.
+
+use core::{fmt, time::Duration};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Formats into a fixed buffer, so that the result can be padded without allocating.
+struct Buffer {
+    buf: [u8; 32],
+    len: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A duration in the largest unit that keeps it at or above 1, with three decimals above
+/// nanoseconds, e.g. `850 ns`, `12.500 us`, `1.250 ms` or `3.000 s`.
+pub struct Human(pub Duration);
+
+/// A duration as `HH:MM:SS`, or `Nd HH:MM:SS` from one day on.
+pub struct Clock(pub Duration);
+
+/// A duration as seconds, at least three wide, and microseconds, e.g. `  3.000125`.
+pub struct Seconds(pub Duration);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Buffer {
+    fn new() -> Self {
+        Self {
+            buf: [0; 32],
+            len: 0,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        // Only ever written from `&str`s and cut at their end.
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+}
+
+impl fmt::Write for Buffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+
+        Ok(())
+    }
+}
+
+/// Write `args` into `f`, applying its width and alignment.
+fn pad(f: &mut fmt::Formatter, args: fmt::Arguments) -> fmt::Result {
+    let mut buf = Buffer::new();
+    fmt::Write::write_fmt(&mut buf, args)?;
+
+    f.pad(buf.as_str())
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl fmt::Display for Human {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ns = self.0.as_nanos();
+        let (unit, name) = match ns {
+            0..=999 => return pad(f, format_args!("{} ns", ns)),
+            1_000..=999_999 => (1_000, "us"),
+            1_000_000..=999_999_999 => (1_000_000, "ms"),
+            _ => (1_000_000_000, "s"),
+        };
+
+        pad(
+            f,
+            format_args!("{}.{:03} {}", ns / unit, ns % unit * 1000 / unit, name),
+        )
+    }
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = self.0.as_secs();
+        let (days, hours, minutes, seconds) =
+            (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60, secs % 60);
+
+        if days > 0 {
+            pad(
+                f,
+                format_args!("{}d {:02}:{:02}:{:02}", days, hours, minutes, seconds),
+            )
+        } else {
+            pad(
+                f,
+                format_args!("{:02}:{:02}:{:02}", hours, minutes, seconds),
+            )
+        }
+    }
+}
+
+impl fmt::Display for Seconds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>3}.{:06}", self.0.as_secs(), self.0.subsec_micros())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use test_macros::kernel_test;
+
+    /// Durations must be scaled to the largest unit at or below them, and clocks must roll over to
+    /// days.
+    #[kernel_test]
+    fn scaling_and_clock() {
+        assert_eq!(format!("{}", Human(Duration::from_nanos(850))), "850 ns");
+        assert_eq!(
+            format!("{}", Human(Duration::from_nanos(12_500))),
+            "12.500 us"
+        );
+        assert_eq!(
+            format!("{}", Human(Duration::from_micros(1_250))),
+            "1.250 ms"
+        );
+        assert_eq!(format!("{:>9}", Human(Duration::from_secs(3))), "  3.000 s");
+        assert_eq!(format!("{}", Clock(Duration::from_secs(3_725))), "01:02:05");
+        assert_eq!(
+            format!("{}", Clock(Duration::from_secs(90_000))),
+            "1d 01:00:00"
+        );
+        assert_eq!(
+            format!("{}", Seconds(Duration::from_micros(3_000_125))),
+            "  3.000125"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Manually advanced timer.
+//!
+//! A [`ManualTimer`] stands in for the architectural timer in tests. Its uptime only moves when
+//! the test advances it, and arming it just records the due time, so a [`super::TimeManager`] built
+//! on it runs its timeouts exactly when [`super::TimeManager::run_due()`] is called.
+
+use super::interface;
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Stored in place of a due time while the timer isn't armed.
+const NOT_ARMED: u64 = u64::MAX;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A timer whose uptime is set by hand.
+pub struct ManualTimer {
+    /// Uptime in nanoseconds.
+    now: AtomicU64,
+
+    /// Due time the timer was last armed for, in nanoseconds.
+    armed: AtomicU64,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl ManualTimer {
+    /// Create an instance at uptime zero.
+    pub const fn new() -> Self {
+        Self {
+            now: AtomicU64::new(0),
+            armed: AtomicU64::new(NOT_ARMED),
+        }
+    }
+
+    /// Move the uptime forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.now
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Return the due time the timer was last armed for, if any.
+    pub fn armed(&self) -> Option<Duration> {
+        match self.armed.load(Ordering::Relaxed) {
+            NOT_ARMED => None,
+            ns => Some(Duration::from_nanos(ns)),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+
+impl interface::Timer for ManualTimer {
+    fn uptime(&self) -> Duration {
+        Duration::from_nanos(self.now.load(Ordering::Relaxed))
+    }
+
+    /// Spinning advances the uptime, so that code waiting for it finishes right away.
+    fn spin_for(&self, duration: Duration) {
+        self.advance(duration);
+    }
+
+    fn resolution(&self) -> Duration {
+        Duration::from_nanos(1)
+    }
+
+    fn set_timeout_irq(&self, due_time: Duration) {
+        self.armed
+            .store(due_time.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    fn clear_timeout_irq(&self) {
+        self.armed.store(NOT_ARMED, Ordering::Relaxed);
+    }
+
+    /// All cores share the one timer, and tests run the queues themselves.
+    fn wake(&self, _core: usize) -> Result<(), &'static str> {
+        Ok(())
+    }
+}
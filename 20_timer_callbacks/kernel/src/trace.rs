@@ -0,0 +1,213 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! The trace buffer.
+//!
+//! A ring buffer of timestamped records from all subsystems. When it is full, the oldest records
+//! are overwritten. Recording does not allocate, so it is cheap enough for IRQ handlers and the
+//! packet path.
+//!
+//! The records live on the heap and are released when the heap runs out of memory. Recording stops
+//! until the buffer is cleared. The buffer is allocated within the `trace` memory quota.
+
+use crate::{
+    info,
+    memory::heap_alloc::{self, quota::Quota},
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time::{self, Timestamp},
+};
+use alloc::boxed::Box;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Number of records kept.
+const CAPACITY: usize = 256;
+
+struct TraceBufferInner {
+    /// `None` until the buffer is cleared the first time and after it was released.
+    records: Option<Box<[Option<Record>], &'static Quota>>,
+
+    /// Index of the slot that is written next.
+    next: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A trace record.
+#[derive(Copy, Clone, Debug)]
+pub struct Record {
+    pub stamp: Timestamp,
+
+    /// The subsystem that recorded it, e.g. `net`.
+    pub source: &'static str,
+
+    /// What happened, e.g. `rx`.
+    pub what: &'static str,
+
+    /// A number qualifying the record, e.g. a frame length or pin number.
+    pub arg: u64,
+}
+
+/// The trace buffer.
+pub struct TraceBuffer {
+    inner: IRQSafeNullLock<TraceBufferInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static TRACE_BUFFER: TraceBuffer = TraceBuffer::new();
+
+/// Room for the buffer and a second one while clearing.
+static TRACE_QUOTA: Quota = Quota::new("trace", 32 * 1024);
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return a reference to the global trace buffer.
+pub fn trace_buffer() -> &'static TraceBuffer {
+    &TRACE_BUFFER
+}
+
+/// Allocate the global trace buffer and let it be released when the heap runs out of memory.
+pub fn init() -> Result<(), &'static str> {
+    heap_alloc::quota::register(&TRACE_QUOTA)?;
+    TRACE_BUFFER.clear()?;
+
+    heap_alloc::register_reclaimer("trace", || TRACE_BUFFER.release())
+}
+
+/// Add a record to the global trace buffer.
+pub fn record(source: &'static str, what: &'static str, arg: u64) {
+    TRACE_BUFFER.record(source, what, arg);
+}
+
+impl TraceBuffer {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(TraceBufferInner {
+                records: None,
+                next: 0,
+            }),
+        }
+    }
+
+    /// Add a record, overwriting the oldest one if the buffer is full. Dropped if there is no
+    /// buffer.
+    pub fn record(&self, source: &'static str, what: &'static str, arg: u64) {
+        let stamp = time::timestamp();
+
+        self.inner.lock(|inner| {
+            if let Some(records) = &mut inner.records {
+                records[inner.next] = Some(Record {
+                    stamp,
+                    source,
+                    what,
+                    arg,
+                });
+                inner.next = (inner.next + 1) % CAPACITY;
+            }
+        });
+    }
+
+    /// Remove all records, allocating the buffer if there is none.
+    pub fn clear(&self) -> Result<(), &'static str> {
+        // Allocate outside the lock, so that the buffer can be released while allocating.
+        let mut records = TRACE_QUOTA.try_vec(CAPACITY)?;
+        records.resize(CAPACITY, None);
+        let records = records.into_boxed_slice();
+
+        let old = self.inner.lock(|inner| {
+            inner.next = 0;
+            inner.records.replace(records)
+        });
+        drop(old);
+
+        Ok(())
+    }
+
+    /// Free the buffer. Returns the number of bytes freed.
+    pub fn release(&self) -> usize {
+        let old = self.inner.lock(|inner| inner.records.take());
+
+        old.map_or(0, |records| core::mem::size_of_val(&*records))
+    }
+
+    /// Return if there is a buffer to record to.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.lock(|inner| inner.records.is_some())
+    }
+
+    /// Call `f` for each record, oldest first. `f` must not allocate.
+    pub fn for_each(&self, mut f: impl FnMut(&Record)) {
+        self.inner.lock(|inner| {
+            let records = match &inner.records {
+                Some(r) => r,
+                None => return,
+            };
+            let (newer, older) = records.split_at(inner.next);
+
+            for r in older.iter().chain(newer.iter()).flatten() {
+                f(r);
+            }
+        });
+    }
+
+    /// Print the records, oldest first.
+    pub fn print(&self) {
+        if !self.is_enabled() {
+            info!("      Released to free memory, clear to re-enable");
+            return;
+        }
+
+        self.for_each(|r| {
+            info!(
+                "      {}  {:<8} {:<12} {}",
+                r.stamp, r.source, r.what, r.arg
+            );
+        });
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// After wrapping around, records must come out oldest first with increasing sequence numbers.
+    #[kernel_test]
+    fn wrap_around_keeps_order() {
+        let buffer = TraceBuffer::new();
+        buffer.record("test", "dropped", 0);
+        buffer.clear().unwrap();
+        for i in 0..(CAPACITY as u64 + 10) {
+            buffer.record("test", "step", i);
+        }
+
+        let mut count = 0;
+        let mut last: Option<Record> = None;
+        buffer.for_each(|r| {
+            if let Some(l) = last {
+                assert!(r.stamp.seq > l.stamp.seq);
+                assert_eq!(r.arg, l.arg + 1);
+            } else {
+                assert_eq!(r.arg, 10);
+            }
+            last = Some(*r);
+            count += 1;
+        });
+        assert_eq!(count, CAPACITY);
+    }
+}
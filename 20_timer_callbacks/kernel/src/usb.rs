@@ -0,0 +1,793 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! USB device and host mode.
+//!
+//! The BSP registers the board's USB device controller with [`register_controller()`].
+//! [`export()`] then connects to the host as a mass storage device, so that a PC sees a block
+//! device, e.g. the SD card, as a disk. [`serial()`] connects as a serial port instead, running a
+//! shell console over the cable. [`stop()`] disconnects again.
+//!
+//! [`Gadget`] is everything above the controller: the descriptors, the standard requests on
+//! endpoint 0 and the function on a pair of bulk endpoints. The controller driver moves the bytes
+//! and does what the gadget returns. Requests are handled in the controller's IRQ context, block
+//! reads and writes included.
+//!
+//! In host mode, the board's USB host controller, registered with [`register_host_controller()`],
+//! drives a keyboard instead. See [`host`].
+
+pub mod acm;
+pub mod hid;
+pub mod host;
+pub mod msc;
+
+use crate::{
+    block, console, identity, info,
+    synchronization::{interface::ReadWriteEx, InitStateLock},
+};
+use alloc::{format, string::String, vec, vec::Vec};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// pid.codes test IDs, for development only. Each function has its own product ID, so that hosts
+/// don't mix up what they remembered about the other.
+const VENDOR_ID: u16 = 0x1209;
+const PRODUCT_ID_MASS_STORAGE: u16 = 0x0001;
+const PRODUCT_ID_SERIAL: u16 = 0x0002;
+
+const MANUFACTURER: &str = "KHROS";
+
+const STRING_MANUFACTURER: u8 = 1;
+const STRING_PRODUCT: u8 = 2;
+const STRING_SERIAL: u8 = 3;
+
+// Descriptor types.
+const DESC_DEVICE: u8 = 1;
+const DESC_CONFIGURATION: u8 = 2;
+const DESC_STRING: u8 = 3;
+const DESC_INTERFACE: u8 = 4;
+const DESC_ENDPOINT: u8 = 5;
+const DESC_DEVICE_QUALIFIER: u8 = 6;
+const DESC_OTHER_SPEED_CONFIGURATION: u8 = 7;
+const DESC_CS_INTERFACE: u8 = 0x24;
+
+// Device and interface classes.
+const CLASS_PER_INTERFACE: u8 = 0x00;
+const CLASS_COMMUNICATIONS: u8 = 0x02;
+const CLASS_MASS_STORAGE: u8 = 0x08;
+const CLASS_CDC_DATA: u8 = 0x0A;
+
+// Endpoint types.
+const ENDPOINT_BULK: u8 = 0x02;
+const ENDPOINT_INTERRUPT: u8 = 0x03;
+
+// Standard requests.
+const GET_STATUS: u8 = 0;
+const CLEAR_FEATURE: u8 = 1;
+const SET_FEATURE: u8 = 3;
+const SET_ADDRESS: u8 = 5;
+const GET_DESCRIPTOR: u8 = 6;
+const GET_CONFIGURATION: u8 = 8;
+const SET_CONFIGURATION: u8 = 9;
+const GET_INTERFACE: u8 = 10;
+const SET_INTERFACE: u8 = 11;
+
+// Mass storage class requests.
+const BULK_ONLY_RESET: u8 = 0xFF;
+const GET_MAX_LUN: u8 = 0xFE;
+
+const FEATURE_ENDPOINT_HALT: u16 = 0;
+
+const REQUEST_TYPE_MASK: u8 = 0x60;
+const REQUEST_TYPE_STANDARD: u8 = 0x00;
+const REQUEST_TYPE_CLASS: u8 = 0x20;
+const RECIPIENT_MASK: u8 = 0x1F;
+const RECIPIENT_ENDPOINT: u8 = 2;
+
+const CONFIGURATION_VALUE: u8 = 1;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// USB device and host mode interfaces.
+pub mod interface {
+    use super::{
+        host::{Device, InterruptEndpoint, Speed},
+        Gadget, Setup, Status,
+    };
+    use alloc::vec::Vec;
+
+    /// A USB device controller.
+    pub trait DeviceController {
+        /// Name of the controller.
+        fn name(&self) -> &'static str;
+
+        /// Connect to the host and serve `gadget`.
+        fn start(&self, gadget: Gadget) -> Result<(), &'static str>;
+
+        /// Disconnect from the host.
+        fn stop(&self) -> Result<(), &'static str>;
+
+        /// Return the connection state.
+        fn status(&self) -> Status;
+
+        /// Send what the gadget has queued, if the bulk IN endpoint is idle.
+        fn kick(&self) {}
+    }
+
+    /// A USB host controller with a single root port.
+    pub trait HostController {
+        /// Name of the controller.
+        fn name(&self) -> &'static str;
+
+        /// Power the root port, wait for a device to connect and reset it. Returns its speed.
+        fn start_host(&self) -> Result<Speed, &'static str>;
+
+        /// Power the root port off.
+        fn stop_host(&self) -> Result<(), &'static str>;
+
+        /// Run a control transfer on endpoint 0 of `device`. `data` is sent in the data stage of
+        /// an OUT request. Returns what was received in the data stage of an IN request.
+        fn control(
+            &self,
+            device: &Device,
+            setup: &Setup,
+            data: &[u8],
+        ) -> Result<Vec<u8>, &'static str>;
+
+        /// Ask an interrupt IN endpoint for data, toggling its data PID if some arrived. Returns
+        /// `None` if the device had nothing to send.
+        fn interrupt_in(
+            &self,
+            device: &Device,
+            endpoint: &mut InterruptEndpoint,
+        ) -> Result<Option<Vec<u8>>, &'static str>;
+    }
+}
+
+/// Maximum packet size of endpoint 0.
+pub const CONTROL_PACKET_SIZE: usize = 64;
+
+/// Number of the bulk endpoints, IN and OUT.
+pub const BULK_ENDPOINT: u8 = 1;
+
+/// Maximum packet size of the bulk endpoints at high speed.
+pub const BULK_PACKET_SIZE_HS: usize = 512;
+
+/// Maximum packet size of the bulk endpoints at full speed.
+pub const BULK_PACKET_SIZE_FS: usize = 64;
+
+/// Number of the interrupt IN endpoint of the serial function.
+pub const NOTIFY_ENDPOINT: u8 = 2;
+
+/// Maximum packet size of the interrupt IN endpoint.
+pub const NOTIFY_PACKET_SIZE: usize = 16;
+
+/// A SETUP packet.
+#[derive(Copy, Clone, Debug)]
+pub struct Setup {
+    pub request_type: u8,
+    pub request: u8,
+    pub value: u16,
+    pub index: u16,
+    pub length: u16,
+}
+
+/// What the controller driver does next on the bulk endpoints.
+#[derive(PartialEq, Eq, Debug)]
+pub enum Bulk {
+    /// Receive a transfer of this many bytes on the OUT endpoint.
+    Receive(usize),
+
+    /// Send this on the IN endpoint.
+    Send(Vec<u8>),
+
+    /// Halt the IN endpoint until the host clears it.
+    StallIn,
+
+    /// Nothing until a reset, or until the function has something to send.
+    Idle,
+}
+
+/// What the controller driver does for a SETUP packet.
+#[derive(PartialEq, Eq, Debug)]
+pub enum Control {
+    /// Send this in the data stage.
+    Data(Vec<u8>),
+
+    /// Complete the status stage.
+    Ack,
+
+    /// Stall the request.
+    Stall,
+
+    /// Take the address, then complete the status stage.
+    SetAddress(u8),
+
+    /// Enable the function's endpoints, complete the status stage, then do the first bulk step.
+    Configure(Bulk),
+
+    /// Disable the function's endpoints, then complete the status stage.
+    Deconfigure,
+
+    /// Clear the halt of `endpoint`, an endpoint address, complete the status stage, then do
+    /// the bulk step.
+    ClearHalt { endpoint: u8, then: Bulk },
+
+    /// Cancel the bulk transfers, complete the status stage, then do the bulk step.
+    Reset(Bulk),
+}
+
+/// The function a gadget offers the host.
+pub enum Function {
+    MassStorage(msc::MassStorage),
+    Serial(acm::Serial),
+}
+
+/// Everything above the controller.
+pub struct Gadget {
+    function: Function,
+    serial_number: String,
+    high_speed: bool,
+    configured: bool,
+}
+
+/// Connection state of a controller.
+pub struct Status {
+    pub running: bool,
+    pub configured: bool,
+    pub high_speed: bool,
+    pub function: Option<String>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static CUR_CONTROLLER: InitStateLock<Option<&'static (dyn interface::DeviceController + Sync)>> =
+    InitStateLock::new(None);
+
+static CUR_HOST_CONTROLLER: InitStateLock<Option<&'static (dyn interface::HostController + Sync)>> =
+    InitStateLock::new(None);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn string_descriptor(s: &str) -> Vec<u8> {
+    let mut desc = vec![0, DESC_STRING];
+    for c in s.encode_utf16() {
+        desc.extend_from_slice(&c.to_le_bytes());
+    }
+    desc[0] = desc.len() as u8;
+
+    desc
+}
+
+fn controller() -> Result<&'static (dyn interface::DeviceController + Sync), &'static str> {
+    CUR_CONTROLLER
+        .read(|c| *c)
+        .ok_or("No USB device controller")
+}
+
+fn host_controller() -> Result<&'static (dyn interface::HostController + Sync), &'static str> {
+    CUR_HOST_CONTROLLER
+        .read(|c| *c)
+        .ok_or("No USB host controller")
+}
+
+/// Tell the controller that the gadget has something to send.
+fn kick() {
+    if let Ok(controller) = controller() {
+        controller.kick();
+    }
+}
+
+impl Gadget {
+    fn device_class(&self) -> u8 {
+        match self.function {
+            Function::MassStorage(_) => CLASS_PER_INTERFACE,
+            Function::Serial(_) => CLASS_COMMUNICATIONS,
+        }
+    }
+
+    fn product(&self) -> (u16, &'static str) {
+        match self.function {
+            Function::MassStorage(_) => (PRODUCT_ID_MASS_STORAGE, "KHROS mass storage"),
+            Function::Serial(_) => (PRODUCT_ID_SERIAL, "KHROS serial console"),
+        }
+    }
+
+    fn device_descriptor(&self) -> Vec<u8> {
+        let [vid_lo, vid_hi] = VENDOR_ID.to_le_bytes();
+        let [pid_lo, pid_hi] = self.product().0.to_le_bytes();
+
+        vec![
+            18,
+            DESC_DEVICE,
+            0x00,
+            0x02, // USB 2.0.
+            self.device_class(),
+            0,
+            0,
+            CONTROL_PACKET_SIZE as u8,
+            vid_lo,
+            vid_hi,
+            pid_lo,
+            pid_hi,
+            0x00,
+            0x01, // Device release 1.0.
+            STRING_MANUFACTURER,
+            STRING_PRODUCT,
+            STRING_SERIAL,
+            1,
+        ]
+    }
+
+    fn qualifier_descriptor(&self) -> Vec<u8> {
+        vec![
+            10,
+            DESC_DEVICE_QUALIFIER,
+            0x00,
+            0x02,
+            self.device_class(),
+            0,
+            0,
+            CONTROL_PACKET_SIZE as u8,
+            1,
+            0,
+        ]
+    }
+
+    /// The interfaces of the mass storage function.
+    fn mass_storage_interfaces(mps_lo: u8, mps_hi: u8) -> Vec<u8> {
+        vec![
+            // Interface: mass storage, SCSI transparent command set, Bulk-Only Transport.
+            9,
+            DESC_INTERFACE,
+            0,
+            0,
+            2,
+            CLASS_MASS_STORAGE,
+            0x06,
+            0x50,
+            0,
+            // Bulk IN.
+            7,
+            DESC_ENDPOINT,
+            0x80 | BULK_ENDPOINT,
+            ENDPOINT_BULK,
+            mps_lo,
+            mps_hi,
+            0,
+            // Bulk OUT.
+            7,
+            DESC_ENDPOINT,
+            BULK_ENDPOINT,
+            ENDPOINT_BULK,
+            mps_lo,
+            mps_hi,
+            0,
+        ]
+    }
+
+    /// The interfaces of the serial function: the communications interface with its interrupt
+    /// endpoint, and the data interface with the bulk endpoints.
+    fn serial_interfaces(mps_lo: u8, mps_hi: u8, high_speed: bool) -> Vec<u8> {
+        vec![
+            // Interface: communications, Abstract Control Model, AT commands.
+            9,
+            DESC_INTERFACE,
+            0,
+            0,
+            1,
+            CLASS_COMMUNICATIONS,
+            0x02,
+            0x01,
+            0,
+            // Header, CDC 1.10.
+            5,
+            DESC_CS_INTERFACE,
+            0x00,
+            0x10,
+            0x01,
+            // Call management: none, data interface 1.
+            5,
+            DESC_CS_INTERFACE,
+            0x01,
+            0x00,
+            1,
+            // Abstract Control Management: line coding and control line state.
+            4,
+            DESC_CS_INTERFACE,
+            0x02,
+            0x02,
+            // Union: interface 0 controls interface 1.
+            5,
+            DESC_CS_INTERFACE,
+            0x06,
+            0,
+            1,
+            // Interrupt IN, polled every 32 ms.
+            7,
+            DESC_ENDPOINT,
+            0x80 | NOTIFY_ENDPOINT,
+            ENDPOINT_INTERRUPT,
+            NOTIFY_PACKET_SIZE as u8,
+            0,
+            if high_speed { 9 } else { 32 },
+            // Interface: CDC data.
+            9,
+            DESC_INTERFACE,
+            1,
+            0,
+            2,
+            CLASS_CDC_DATA,
+            0,
+            0,
+            0,
+            // Bulk IN.
+            7,
+            DESC_ENDPOINT,
+            0x80 | BULK_ENDPOINT,
+            ENDPOINT_BULK,
+            mps_lo,
+            mps_hi,
+            0,
+            // Bulk OUT.
+            7,
+            DESC_ENDPOINT,
+            BULK_ENDPOINT,
+            ENDPOINT_BULK,
+            mps_lo,
+            mps_hi,
+            0,
+        ]
+    }
+
+    /// The configuration at high speed if `high_speed`, at full speed otherwise.
+    fn configuration_descriptor(&self, descriptor_type: u8, high_speed: bool) -> Vec<u8> {
+        let packet_size = if high_speed {
+            BULK_PACKET_SIZE_HS
+        } else {
+            BULK_PACKET_SIZE_FS
+        } as u16;
+        let [mps_lo, mps_hi] = packet_size.to_le_bytes();
+
+        let (interfaces, body) = match self.function {
+            Function::MassStorage(_) => (1, Self::mass_storage_interfaces(mps_lo, mps_hi)),
+            Function::Serial(_) => (2, Self::serial_interfaces(mps_lo, mps_hi, high_speed)),
+        };
+        let [len_lo, len_hi] = (9 + body.len() as u16).to_le_bytes();
+
+        let mut desc = vec![
+            9,
+            descriptor_type,
+            len_lo,
+            len_hi,
+            interfaces,
+            CONFIGURATION_VALUE,
+            0,
+            0x80, // Bus powered.
+            50,   // 100 mA.
+        ];
+        desc.extend_from_slice(&body);
+
+        desc
+    }
+
+    fn descriptor(&self, value: u16) -> Option<Vec<u8>> {
+        let [index, descriptor_type] = value.to_le_bytes();
+
+        let desc = match (descriptor_type, index) {
+            (DESC_DEVICE, _) => self.device_descriptor(),
+            (DESC_CONFIGURATION, _) => {
+                self.configuration_descriptor(DESC_CONFIGURATION, self.high_speed)
+            }
+            (DESC_OTHER_SPEED_CONFIGURATION, _) => {
+                self.configuration_descriptor(DESC_OTHER_SPEED_CONFIGURATION, !self.high_speed)
+            }
+            (DESC_DEVICE_QUALIFIER, _) => self.qualifier_descriptor(),
+            // US English only.
+            (DESC_STRING, 0) => vec![4, DESC_STRING, 0x09, 0x04],
+            (DESC_STRING, STRING_MANUFACTURER) => string_descriptor(MANUFACTURER),
+            (DESC_STRING, STRING_PRODUCT) => string_descriptor(self.product().1),
+            (DESC_STRING, STRING_SERIAL) => string_descriptor(&self.serial_number),
+            _ => return None,
+        };
+
+        Some(desc)
+    }
+
+    fn standard_request(&mut self, setup: &Setup) -> Control {
+        let endpoint_request = setup.request_type & RECIPIENT_MASK == RECIPIENT_ENDPOINT;
+
+        match setup.request {
+            GET_STATUS => Control::Data(vec![0, 0]),
+            CLEAR_FEATURE if endpoint_request && setup.value == FEATURE_ENDPOINT_HALT => {
+                let endpoint = setup.index as u8;
+                let then = match &mut self.function {
+                    Function::MassStorage(msc) if endpoint == 0x80 | BULK_ENDPOINT => {
+                        msc.halt_cleared()
+                    }
+                    _ => Bulk::Idle,
+                };
+
+                Control::ClearHalt { endpoint, then }
+            }
+            CLEAR_FEATURE | SET_FEATURE => Control::Ack,
+            SET_ADDRESS if setup.value < 128 => Control::SetAddress(setup.value as u8),
+            GET_DESCRIPTOR => match self.descriptor(setup.value) {
+                Some(desc) => Control::Data(desc),
+                None => Control::Stall,
+            },
+            GET_CONFIGURATION => Control::Data(vec![if self.configured {
+                CONFIGURATION_VALUE
+            } else {
+                0
+            }]),
+            SET_CONFIGURATION => match setup.value as u8 {
+                0 => {
+                    self.configured = false;
+                    Control::Deconfigure
+                }
+                CONFIGURATION_VALUE => {
+                    self.configured = true;
+                    Control::Configure(self.reset_function())
+                }
+                _ => Control::Stall,
+            },
+            GET_INTERFACE => Control::Data(vec![0]),
+            SET_INTERFACE if setup.value == 0 => Control::Ack,
+            _ => Control::Stall,
+        }
+    }
+
+    fn reset_function(&mut self) -> Bulk {
+        match &mut self.function {
+            Function::MassStorage(msc) => msc.reset(),
+            Function::Serial(serial) => serial.reset(),
+        }
+    }
+
+    fn mass_storage_request(msc: &mut msc::MassStorage, setup: &Setup) -> Control {
+        match setup.request {
+            BULK_ONLY_RESET if setup.length == 0 => Control::Reset(msc.reset()),
+            // A single logical unit.
+            GET_MAX_LUN if setup.length > 0 => Control::Data(vec![0]),
+            _ => Control::Stall,
+        }
+    }
+
+    fn class_request(&mut self, setup: &Setup) -> Control {
+        match &mut self.function {
+            Function::MassStorage(msc) => Self::mass_storage_request(msc, setup),
+            Function::Serial(serial) => serial.class_request(setup),
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Setup {
+    /// Decode a SETUP packet.
+    pub fn parse(packet: &[u8; 8]) -> Self {
+        Self {
+            request_type: packet[0],
+            request: packet[1],
+            value: u16::from_le_bytes([packet[2], packet[3]]),
+            index: u16::from_le_bytes([packet[4], packet[5]]),
+            length: u16::from_le_bytes([packet[6], packet[7]]),
+        }
+    }
+
+    /// Encode a SETUP packet.
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let [value_lo, value_hi] = self.value.to_le_bytes();
+        let [index_lo, index_hi] = self.index.to_le_bytes();
+        let [length_lo, length_hi] = self.length.to_le_bytes();
+
+        [
+            self.request_type,
+            self.request,
+            value_lo,
+            value_hi,
+            index_lo,
+            index_hi,
+            length_lo,
+            length_hi,
+        ]
+    }
+}
+
+impl Gadget {
+    /// Create a gadget offering `function`, with the serial number `serial_number`.
+    pub fn new(function: Function, serial_number: u64) -> Self {
+        Self {
+            function,
+            serial_number: format!("{:016X}", serial_number),
+            high_speed: true,
+            configured: false,
+        }
+    }
+
+    /// Describe the function, for status output.
+    pub fn describe(&self) -> String {
+        match &self.function {
+            Function::MassStorage(msc) => format!("mass storage, {}", msc.device_name()),
+            Function::Serial(_) => String::from("serial console"),
+        }
+    }
+
+    /// Return if the function uses the interrupt IN endpoint [`NOTIFY_ENDPOINT`].
+    pub fn has_notify_endpoint(&self) -> bool {
+        matches!(self.function, Function::Serial(_))
+    }
+
+    /// Return if the host selected the configuration.
+    pub fn is_configured(&self) -> bool {
+        self.configured
+    }
+
+    /// Return if the bus runs at high speed.
+    pub fn is_high_speed(&self) -> bool {
+        self.high_speed
+    }
+
+    /// Maximum packet size of the bulk endpoints at the current speed.
+    pub fn bulk_packet_size(&self) -> usize {
+        if self.high_speed {
+            BULK_PACKET_SIZE_HS
+        } else {
+            BULK_PACKET_SIZE_FS
+        }
+    }
+
+    /// The bus was reset. Enumeration ended at high speed if `high_speed`.
+    pub fn bus_reset(&mut self, high_speed: bool) {
+        self.high_speed = high_speed;
+        self.configured = false;
+        let packet_size = self.bulk_packet_size();
+        match &mut self.function {
+            Function::MassStorage(msc) => msc.set_packet_size(packet_size),
+            Function::Serial(serial) => serial.set_packet_size(packet_size),
+        }
+    }
+
+    /// Handle a SETUP packet. Data to send is cut to the requested length.
+    pub fn setup(&mut self, setup: &Setup) -> Control {
+        let control = match setup.request_type & REQUEST_TYPE_MASK {
+            REQUEST_TYPE_STANDARD => self.standard_request(setup),
+            REQUEST_TYPE_CLASS => self.class_request(setup),
+            _ => Control::Stall,
+        };
+
+        match control {
+            Control::Data(mut data) => {
+                data.truncate(setup.length as usize);
+                Control::Data(data)
+            }
+            control => control,
+        }
+    }
+
+    /// The data stage of the last control OUT request arrived.
+    pub fn control_data(&mut self, data: &[u8]) {
+        if let Function::Serial(serial) = &mut self.function {
+            serial.control_data(data);
+        }
+    }
+
+    /// A transfer on the bulk OUT endpoint completed.
+    pub fn received(&mut self, data: &[u8]) -> Bulk {
+        match &mut self.function {
+            Function::MassStorage(msc) => msc.received(data),
+            Function::Serial(serial) => serial.received(data),
+        }
+    }
+
+    /// A transfer on the bulk IN endpoint completed.
+    pub fn sent(&mut self) -> Bulk {
+        match &mut self.function {
+            Function::MassStorage(msc) => msc.sent(),
+            Function::Serial(serial) => serial.sent(),
+        }
+    }
+
+    /// The bulk IN endpoint is idle. Returns what the function has to send meanwhile.
+    pub fn poll(&mut self) -> Bulk {
+        match &mut self.function {
+            Function::Serial(serial) if self.configured => serial.poll(),
+            _ => Bulk::Idle,
+        }
+    }
+}
+
+/// Register the board's USB device controller.
+pub fn register_controller(new_controller: &'static (dyn interface::DeviceController + Sync)) {
+    CUR_CONTROLLER.write(|c| *c = Some(new_controller));
+}
+
+/// Register the board's USB host controller.
+pub fn register_host_controller(new_controller: &'static (dyn interface::HostController + Sync)) {
+    CUR_HOST_CONTROLLER.write(|c| *c = Some(new_controller));
+}
+
+/// Connect to the host as a mass storage device exporting the block device `name`.
+pub fn export(name: &str) -> Result<(), &'static str> {
+    let device = block::lookup(name)?;
+
+    controller()?.start(Gadget::new(
+        Function::MassStorage(msc::MassStorage::new(device)),
+        identity::id().unwrap_or(0),
+    ))
+}
+
+/// Connect to the host as a serial port, and mirror the console to it.
+pub fn serial() -> Result<(), &'static str> {
+    let controller = controller()?;
+
+    match console::register_sink("usb", &acm::USB_CONSOLE) {
+        Ok(()) | Err("Sink already registered") => (),
+        Err(e) => return Err(e),
+    }
+
+    controller.start(Gadget::new(
+        Function::Serial(acm::Serial::new()),
+        identity::id().unwrap_or(0),
+    ))
+}
+
+/// Disconnect from the host, or detach the keyboard in host mode.
+pub fn stop() -> Result<(), &'static str> {
+    if host::is_running() {
+        return host::stop();
+    }
+
+    controller()?.stop()?;
+    acm::USB_CONSOLE.close();
+
+    Ok(())
+}
+
+/// Run what the gadget deferred until the controller is unlocked, i.e. commands typed on the
+/// serial console. Called by the controller driver at the end of its IRQ handler.
+pub fn service() {
+    acm::USB_CONSOLE.run_input();
+}
+
+/// Print the controller's state.
+pub fn print() -> Result<(), &'static str> {
+    if host::is_running() {
+        return host::print();
+    }
+
+    let controller = controller()?;
+    let status = controller.status();
+
+    info!("      Controller: {}", controller.name());
+    if !status.running {
+        info!("      Stopped");
+        return Ok(());
+    }
+    info!(
+        "      Function:   {}",
+        status.function.as_deref().unwrap_or("-")
+    );
+    info!(
+        "      State:      {}",
+        match (status.configured, status.high_speed) {
+            (false, _) => "waiting for host",
+            (true, true) => "configured, high speed",
+            (true, false) => "configured, full speed",
+        }
+    );
+
+    Ok(())
+}
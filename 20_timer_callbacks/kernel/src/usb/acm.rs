@@ -0,0 +1,403 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! USB serial port, CDC Abstract Control Model.
+//!
+//! [`Serial`] is the function: it answers the class requests on endpoint 0 and moves bytes on the
+//! bulk endpoints. The bytes come from and go to [`USB_CONSOLE`], a console like the UARTs. It is
+//! registered as the sink `usb`, so it mirrors all output, and lines typed on the host are run by
+//! the shell with their output going back over the cable.
+//!
+//! Output is only kept while the host has the port open, i.e. set DTR, so that a terminal
+//! started later doesn't get a burst of old messages.
+//!
+//! # Resources
+//!
+//! - Universal Serial Bus Class Definitions for Communications Devices, Revision 1.2
+//! - Universal Serial Bus Communications Class Subclass Specification for PSTN Devices, Revision
+//!   1.2
+
+use super::{Bulk, Control, Setup};
+use crate::{
+    console::{self, line_discipline, line_editor},
+    shell,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+};
+use alloc::vec::Vec;
+use core::fmt;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+// Class requests.
+const SET_LINE_CODING: u8 = 0x20;
+const GET_LINE_CODING: u8 = 0x21;
+const SET_CONTROL_LINE_STATE: u8 = 0x22;
+const SEND_BREAK: u8 = 0x23;
+
+const LINE_CODING_LEN: usize = 7;
+
+/// 115200 baud, 1 stop bit, no parity, 8 data bits.
+const DEFAULT_LINE_CODING: [u8; LINE_CODING_LEN] = [0x00, 0xC2, 0x01, 0x00, 0, 0, 8];
+
+const CONTROL_LINE_DTR: u16 = 1 << 0;
+
+/// Output kept for the host. Older bytes are dropped when it doesn't keep up.
+const TX_CAPACITY: usize = 4096;
+
+/// Received bytes not yet run by the shell. More are dropped.
+const RX_CAPACITY: usize = 256;
+
+struct UsbConsoleInner {
+    open: bool,
+    tx: Vec<u8>,
+    rx: Vec<u8>,
+    editor: line_editor::LineEditor,
+    chars_written: usize,
+    chars_read: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The serial function.
+pub struct Serial {
+    packet_size: usize,
+    line_coding: [u8; LINE_CODING_LEN],
+
+    /// Request whose data stage is awaited.
+    pending: Option<u8>,
+
+    /// A bulk IN transfer is in flight.
+    sending: bool,
+}
+
+/// The console on the other end of the serial function.
+pub struct UsbConsole {
+    inner: IRQSafeNullLock<UsbConsoleInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+pub static USB_CONSOLE: UsbConsole = UsbConsole::new();
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl UsbConsoleInner {
+    const fn new() -> Self {
+        Self {
+            open: false,
+            tx: Vec::new(),
+            rx: Vec::new(),
+            editor: line_editor::LineEditor::new(),
+            chars_written: 0,
+            chars_read: 0,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        if !self.open {
+            return;
+        }
+
+        self.tx.extend_from_slice(bytes);
+        if self.tx.len() > TX_CAPACITY {
+            let excess = self.tx.len() - TX_CAPACITY;
+            self.tx.drain(..excess);
+        }
+    }
+
+    fn write_char(&mut self, c: char) {
+        let mut buf = [0; 4];
+        line_discipline::output(c, |o| self.push(o.encode_utf8(&mut buf).as_bytes()));
+        self.chars_written += 1;
+    }
+
+    /// Take up to `max` bytes to send.
+    fn take(&mut self, max: usize) -> Vec<u8> {
+        let n = self.tx.len().min(max);
+
+        self.tx.drain(..n).collect()
+    }
+
+    /// Feed a received character to the line editor, echoing it.
+    fn edit(&mut self, c: char) -> Option<line_editor::Line> {
+        // The redraw sequences go out raw, only the line end is translated.
+        let mut echo = line_editor::Echo::new();
+        let line = self.editor.input(c, &mut echo);
+        for b in echo.as_bytes() {
+            match *b {
+                b'\n' => self.write_char('\n'),
+                _ => self.push(&[*b]),
+            }
+        }
+
+        line
+    }
+}
+
+impl fmt::Write for UsbConsoleInner {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.write_char(c);
+        }
+
+        Ok(())
+    }
+}
+
+impl UsbConsole {
+    const fn new() -> Self {
+        Self {
+            inner: IRQSafeNullLock::new(UsbConsoleInner::new()),
+        }
+    }
+
+    /// The host opened or closed the port.
+    fn set_open(&self, open: bool) {
+        self.inner.lock(|inner| {
+            if inner.open != open {
+                inner.tx.clear();
+                inner.editor.clear();
+            }
+            inner.open = open;
+        });
+    }
+
+    fn receive(&self, data: &[u8]) {
+        self.inner.lock(|inner| {
+            let n = data.len().min(RX_CAPACITY - inner.rx.len());
+            inner.rx.extend_from_slice(&data[..n]);
+            inner.chars_read += n;
+        });
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Serial {
+    /// Create an instance.
+    pub fn new() -> Self {
+        Self {
+            packet_size: super::BULK_PACKET_SIZE_HS,
+            line_coding: DEFAULT_LINE_CODING,
+            pending: None,
+            sending: false,
+        }
+    }
+
+    /// Set the maximum packet size of the bulk endpoints.
+    pub fn set_packet_size(&mut self, packet_size: usize) {
+        self.packet_size = packet_size;
+    }
+
+    /// Start over, after the host configured the device.
+    pub fn reset(&mut self) -> Bulk {
+        self.sending = false;
+        USB_CONSOLE.set_open(false);
+
+        Bulk::Receive(self.packet_size)
+    }
+
+    /// Handle a class request.
+    pub fn class_request(&mut self, setup: &Setup) -> Control {
+        match setup.request {
+            // The status stage is queued now, the host only asks for it after the data stage.
+            SET_LINE_CODING if setup.length as usize == LINE_CODING_LEN => {
+                self.pending = Some(SET_LINE_CODING);
+                Control::Ack
+            }
+            GET_LINE_CODING => Control::Data(self.line_coding.to_vec()),
+            SET_CONTROL_LINE_STATE => {
+                USB_CONSOLE.set_open(setup.value & CONTROL_LINE_DTR != 0);
+                Control::Ack
+            }
+            // There is no line to break.
+            SEND_BREAK => Control::Ack,
+            _ => Control::Stall,
+        }
+    }
+
+    /// The data stage of the last control OUT request arrived.
+    pub fn control_data(&mut self, data: &[u8]) {
+        if self.pending.take() == Some(SET_LINE_CODING) && data.len() == LINE_CODING_LEN {
+            // Only remembered for GET_LINE_CODING, the bytes move at USB speed anyway.
+            self.line_coding.copy_from_slice(data);
+        }
+    }
+
+    /// A transfer on the bulk OUT endpoint completed.
+    pub fn received(&mut self, data: &[u8]) -> Bulk {
+        USB_CONSOLE.receive(data);
+
+        Bulk::Receive(self.packet_size)
+    }
+
+    /// A transfer on the bulk IN endpoint completed.
+    pub fn sent(&mut self) -> Bulk {
+        self.sending = false;
+
+        self.poll()
+    }
+
+    /// Return the next output to send, if no transfer is in flight.
+    pub fn poll(&mut self) -> Bulk {
+        if self.sending {
+            return Bulk::Idle;
+        }
+
+        // A transfer that is a multiple of the packet size would need a zero length packet to
+        // end it. One byte less ends with a short packet instead.
+        let mut chunk = USB_CONSOLE
+            .inner
+            .lock(|inner| inner.take(4 * self.packet_size - 1));
+        if chunk.is_empty() {
+            return Bulk::Idle;
+        }
+        if chunk.len() % self.packet_size == 0 {
+            let last = chunk.pop();
+            USB_CONSOLE
+                .inner
+                .lock(|inner| last.into_iter().for_each(|b| inner.tx.insert(0, b)));
+        }
+        self.sending = true;
+
+        Bulk::Send(chunk)
+    }
+}
+
+impl Default for Serial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsbConsole {
+    /// Run the commands typed on the host, with their output going back to it.
+    pub fn run_input(&'static self) {
+        let input = self.inner.lock(|inner| core::mem::take(&mut inner.rx));
+
+        for b in input {
+            let line = self
+                .inner
+                .lock(|inner| inner.edit(line_discipline::input(b as char)));
+
+            if let Some(line) = line {
+                super::kick();
+                shell::execute_input(self, line.as_str().trim());
+            }
+        }
+
+        super::kick();
+    }
+
+    /// Drop the output and input, after the gadget stopped.
+    pub fn close(&self) {
+        self.set_open(false);
+        self.inner.lock(|inner| inner.rx.clear());
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+
+impl console::interface::Write for UsbConsole {
+    fn write_char(&self, c: char) {
+        self.inner.lock(|inner| inner.write_char(c));
+        super::kick();
+    }
+
+    fn write_array(&self, a: &[char]) {
+        self.inner
+            .lock(|inner| a.iter().for_each(|c| inner.write_char(*c)));
+        super::kick();
+    }
+
+    fn write_fmt(&self, args: fmt::Arguments) -> fmt::Result {
+        let result = self.inner.lock(|inner| fmt::Write::write_fmt(inner, args));
+        super::kick();
+
+        result
+    }
+
+    /// Output is sent by the controller as the host polls for it, there is nothing to wait for.
+    fn flush(&self) {}
+}
+
+impl console::interface::Read for UsbConsole {
+    fn read_char_nonblocking(&self) -> Option<char> {
+        self.inner.lock(|inner| {
+            if inner.rx.is_empty() {
+                return None;
+            }
+
+            Some(line_discipline::input(inner.rx.remove(0) as char))
+        })
+    }
+
+    fn clear_rx(&self) {
+        self.inner.lock(|inner| {
+            inner.rx.clear();
+            inner.editor.clear();
+        });
+    }
+}
+
+impl console::interface::Configure for UsbConsole {}
+
+impl console::interface::Statistics for UsbConsole {
+    fn chars_written(&self) -> usize {
+        self.inner.lock(|inner| inner.chars_written)
+    }
+
+    fn chars_read(&self) -> usize {
+        self.inner.lock(|inner| inner.chars_read)
+    }
+}
+
+impl console::interface::All for UsbConsole {}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Output must only be kept while the port is open, and must never be sent in a transfer that
+    /// needs a zero length packet.
+    #[kernel_test]
+    fn output_while_open() {
+        let mut serial = Serial::new();
+        serial.set_packet_size(super::super::BULK_PACKET_SIZE_FS);
+        assert_eq!(serial.reset(), Bulk::Receive(64));
+
+        USB_CONSOLE.inner.lock(|inner| inner.push(b"lost"));
+        assert_eq!(serial.poll(), Bulk::Idle);
+
+        let dtr = Setup::parse(&[0x21, SET_CONTROL_LINE_STATE, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(serial.class_request(&dtr), Control::Ack);
+        USB_CONSOLE.inner.lock(|inner| inner.push(&[b'x'; 64]));
+
+        match serial.poll() {
+            Bulk::Send(data) => assert_eq!(data.len(), 63),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(serial.poll(), Bulk::Idle);
+        assert_eq!(serial.sent(), Bulk::Send(alloc::vec![b'x']));
+
+        USB_CONSOLE.close();
+    }
+}
@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! USB keyboard, HID boot protocol.
+//!
+//! In the boot protocol, a keyboard sends an 8 byte report whenever a key goes down or up: the
+//! modifier bits, a reserved byte and the usage IDs of up to six pressed keys. [`Keyboard`] turns
+//! the keys that were newly pressed into the characters a terminal would send for them, on a US
+//! layout: carriage return for Enter, DEL for Backspace and ANSI sequences for the cursor keys.
+//! Keys don't repeat while held.
+//!
+//! # Resources
+//!
+//! - Device Class Definition for Human Interface Devices (HID), Version 1.11, Appendix B
+//! - HID Usage Tables, Keyboard/Keypad Page (0x07)
+
+use alloc::vec::Vec;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+// Modifier bits.
+const LEFT_CTRL: u8 = 1 << 0;
+const LEFT_SHIFT: u8 = 1 << 1;
+const RIGHT_CTRL: u8 = 1 << 4;
+const RIGHT_SHIFT: u8 = 1 << 5;
+
+// Usage IDs.
+const KEY_A: u8 = 0x04;
+const KEY_Z: u8 = 0x1D;
+const KEY_1: u8 = 0x1E;
+const KEY_0: u8 = 0x27;
+const KEY_ENTER: u8 = 0x28;
+const KEY_ESCAPE: u8 = 0x29;
+const KEY_BACKSPACE: u8 = 0x2A;
+const KEY_TAB: u8 = 0x2B;
+const KEY_SPACE: u8 = 0x2C;
+const KEY_MINUS: u8 = 0x2D;
+const KEY_SLASH: u8 = 0x38;
+const KEY_CAPS_LOCK: u8 = 0x39;
+const KEY_HOME: u8 = 0x4A;
+const KEY_END: u8 = 0x4D;
+const KEY_RIGHT: u8 = 0x4F;
+const KEY_LEFT: u8 = 0x50;
+const KEY_DOWN: u8 = 0x51;
+const KEY_UP: u8 = 0x52;
+
+/// Reported in all key slots when more keys are down than the report holds.
+const ERROR_ROLL_OVER: u8 = 0x01;
+
+// Unshifted and shifted characters of the keys from `1` to `0` and from `-` to `/`. 0x32 is the
+// `#` key of non-US layouts.
+const DIGITS: &[u8; 10] = b"1234567890";
+const DIGITS_SHIFTED: &[u8; 10] = b"!@#$%^&*()";
+const PUNCTUATION: &[u8; 12] = b"-=[]\\#;'`,./";
+const PUNCTUATION_SHIFTED: &[u8; 12] = b"_+{}|~:\"~<>?";
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Length of a boot protocol keyboard report.
+pub const REPORT_LEN: usize = 8;
+
+/// A boot protocol keyboard.
+pub struct Keyboard {
+    /// Keys down in the last report.
+    pressed: [u8; 6],
+    caps_lock: bool,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Keyboard {
+    /// Append what `key` types to `out`.
+    fn key_down(&mut self, key: u8, modifiers: u8, out: &mut Vec<char>) {
+        let shift = modifiers & (LEFT_SHIFT | RIGHT_SHIFT) != 0;
+        let ctrl = modifiers & (LEFT_CTRL | RIGHT_CTRL) != 0;
+
+        let c = match key {
+            KEY_A..=KEY_Z => {
+                let letter = b'a' + (key - KEY_A);
+                if ctrl {
+                    // Ctrl-A is 0x01 and so on.
+                    out.push((letter - b'a' + 1) as char);
+                    return;
+                }
+                if shift != self.caps_lock {
+                    letter.to_ascii_uppercase()
+                } else {
+                    letter
+                }
+            }
+            KEY_1..=KEY_0 => {
+                let i = (key - KEY_1) as usize;
+                if shift {
+                    DIGITS_SHIFTED[i]
+                } else {
+                    DIGITS[i]
+                }
+            }
+            KEY_ENTER => b'\r',
+            KEY_ESCAPE => 0x1b,
+            KEY_BACKSPACE => 0x7f,
+            KEY_TAB => b'\t',
+            KEY_SPACE => b' ',
+            KEY_MINUS..=KEY_SLASH => {
+                let i = (key - KEY_MINUS) as usize;
+                if shift {
+                    PUNCTUATION_SHIFTED[i]
+                } else {
+                    PUNCTUATION[i]
+                }
+            }
+            KEY_CAPS_LOCK => {
+                self.caps_lock = !self.caps_lock;
+                return;
+            }
+            KEY_HOME | KEY_END | KEY_RIGHT | KEY_LEFT | KEY_DOWN | KEY_UP => {
+                let code = match key {
+                    KEY_HOME => 'H',
+                    KEY_END => 'F',
+                    KEY_RIGHT => 'C',
+                    KEY_LEFT => 'D',
+                    KEY_DOWN => 'B',
+                    _ => 'A',
+                };
+                out.extend_from_slice(&['\x1b', '[', code]);
+                return;
+            }
+            _ => return,
+        };
+
+        out.push(c as char);
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Keyboard {
+    /// Create an instance, with no keys down and Caps Lock off.
+    pub const fn new() -> Self {
+        Self {
+            pressed: [0; 6],
+            caps_lock: false,
+        }
+    }
+
+    /// Decode a report. Returns the characters typed by the keys that went down since the last
+    /// one.
+    pub fn report(&mut self, report: &[u8]) -> Vec<char> {
+        let mut out = Vec::new();
+        if report.len() < REPORT_LEN {
+            return out;
+        }
+
+        let modifiers = report[0];
+        let keys = &report[2..REPORT_LEN];
+        if keys.contains(&ERROR_ROLL_OVER) {
+            return out;
+        }
+
+        for &key in keys.iter().filter(|k| **k != 0) {
+            if !self.pressed.contains(&key) {
+                self.key_down(key, modifiers, &mut out);
+            }
+        }
+        self.pressed.copy_from_slice(keys);
+
+        out
+    }
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A key must type once when it goes down, not again while held, and shift, caps lock and
+    /// ctrl must change what it types.
+    #[kernel_test]
+    fn typing() {
+        let mut kbd = Keyboard::new();
+
+        assert_eq!(kbd.report(&[0, 0, KEY_A, 0, 0, 0, 0, 0]), ['a']);
+        assert!(kbd.report(&[0, 0, KEY_A, 0, 0, 0, 0, 0]).is_empty());
+        assert_eq!(kbd.report(&[0, 0, KEY_A, KEY_1, 0, 0, 0, 0]), ['1']);
+        assert!(kbd.report(&[0; 8]).is_empty());
+
+        assert_eq!(kbd.report(&[LEFT_SHIFT, 0, KEY_1, 0, 0, 0, 0, 0]), ['!']);
+        assert_eq!(kbd.report(&[RIGHT_CTRL, 0, 0x06, 0, 0, 0, 0, 0]), ['\x03']);
+
+        kbd.report(&[0, 0, KEY_CAPS_LOCK, 0, 0, 0, 0, 0]);
+        assert_eq!(kbd.report(&[0, 0, KEY_Z, 0, 0, 0, 0, 0]), ['Z']);
+        assert!(kbd.report(&[LEFT_SHIFT, 0, 0, 0, 0, 0, 0, 0]).is_empty());
+        assert_eq!(kbd.report(&[LEFT_SHIFT, 0, KEY_Z, 0, 0, 0, 0, 0]), ['z']);
+
+        assert_eq!(
+            kbd.report(&[0, 0, KEY_UP, 0, 0, 0, 0, 0]),
+            ['\x1b', '[', 'A']
+        );
+        assert!(kbd
+            .report(&[0, 0, ERROR_ROLL_OVER, 1, 1, 1, 1, 1])
+            .is_empty());
+        assert_eq!(kbd.report(&[0, 0, KEY_ENTER, 0, 0, 0, 0, 0]), ['\r']);
+    }
+}
@@ -0,0 +1,405 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! USB host mode, for a keyboard.
+//!
+//! [`start()`] powers the host controller's root port and enumerates the device on it, which must
+//! be a keyboard with the HID boot protocol. Hubs aren't supported, the keyboard has to be
+//! attached directly. Its interrupt endpoint is then polled from a periodic timer callback, and
+//! the characters typed go to the registered console as if they were received by it, so they end
+//! up in the same line editor and shell.
+
+use super::{hid, interface, Setup};
+use crate::{
+    console::{self, line_discipline},
+    info,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time, warn,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{fmt, time::Duration};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const CLASS_HID: u8 = 0x03;
+const SUBCLASS_BOOT: u8 = 0x01;
+const PROTOCOL_KEYBOARD: u8 = 0x01;
+
+// HID class requests.
+const SET_IDLE: u8 = 0x0A;
+const SET_PROTOCOL: u8 = 0x0B;
+const BOOT_PROTOCOL: u16 = 0;
+
+const REQUEST_DEVICE_TO_HOST: u8 = 0x80;
+const REQUEST_CLASS_INTERFACE: u8 = 0x21;
+
+const DEVICE_DESCRIPTOR_LEN: u16 = 18;
+const CONFIGURATION_HEADER_LEN: u16 = 9;
+
+/// Address given to the device on the root port.
+const DEVICE_ADDRESS: u8 = 1;
+
+/// Packet size of endpoint 0 until the device descriptor tells. Every device handles 8 bytes.
+const INITIAL_PACKET_SIZE: usize = 8;
+
+/// Shortest interval the keyboard is polled at.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(8);
+
+struct AttachedKeyboard {
+    device: Device,
+    endpoint: InterruptEndpoint,
+    keyboard: hid::Keyboard,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Bus speed of a device.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Speed {
+    Low,
+    Full,
+    High,
+}
+
+/// A device on the root port, as far as transfers need to know it.
+#[derive(Copy, Clone, Debug)]
+pub struct Device {
+    pub address: u8,
+    pub speed: Speed,
+
+    /// Maximum packet size of endpoint 0.
+    pub control_packet_size: usize,
+}
+
+/// An interrupt IN endpoint.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct InterruptEndpoint {
+    pub number: u8,
+    pub packet_size: usize,
+
+    /// Polling interval as given in the endpoint descriptor: in milliseconds at low and full
+    /// speed, as an exponent of microframes at high speed.
+    pub interval: u8,
+
+    /// The next transfer is expected with DATA1.
+    pub toggle: bool,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static KEYBOARD: IRQSafeNullLock<Option<AttachedKeyboard>> = IRQSafeNullLock::new(None);
+
+/// The timeout that polls the keyboard while attached.
+static POLLING: IRQSafeNullLock<Option<time::TimeoutHandle>> = IRQSafeNullLock::new(None);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn get_descriptor(
+    controller: &dyn interface::HostController,
+    device: &Device,
+    descriptor_type: u8,
+    length: u16,
+) -> Result<Vec<u8>, &'static str> {
+    let setup = Setup {
+        request_type: REQUEST_DEVICE_TO_HOST,
+        request: super::GET_DESCRIPTOR,
+        value: (descriptor_type as u16) << 8,
+        index: 0,
+        length,
+    };
+    let desc = controller.control(device, &setup, &[])?;
+
+    if desc.len() < length as usize {
+        return Err("Short descriptor");
+    }
+
+    Ok(desc)
+}
+
+fn class_request(request: u8, value: u16, interface: u8) -> Setup {
+    Setup {
+        request_type: REQUEST_CLASS_INTERFACE,
+        request,
+        value,
+        index: interface as u16,
+        length: 0,
+    }
+}
+
+/// Address and configure the device on the root port, and switch its keyboard interface to the
+/// boot protocol.
+fn enumerate(
+    controller: &dyn interface::HostController,
+    speed: Speed,
+) -> Result<AttachedKeyboard, &'static str> {
+    let mut device = Device {
+        address: 0,
+        speed,
+        control_packet_size: INITIAL_PACKET_SIZE,
+    };
+
+    let desc = get_descriptor(controller, &device, super::DESC_DEVICE, 8)?;
+    device.control_packet_size = desc[7] as usize;
+
+    let set_address = Setup {
+        request_type: 0,
+        request: super::SET_ADDRESS,
+        value: DEVICE_ADDRESS as u16,
+        index: 0,
+        length: 0,
+    };
+    controller.control(&device, &set_address, &[])?;
+    // The device has 2 ms to take the new address.
+    time::time_manager().spin_for(Duration::from_millis(2));
+    device.address = DEVICE_ADDRESS;
+    get_descriptor(
+        controller,
+        &device,
+        super::DESC_DEVICE,
+        DEVICE_DESCRIPTOR_LEN,
+    )?;
+
+    let header = get_descriptor(
+        controller,
+        &device,
+        super::DESC_CONFIGURATION,
+        CONFIGURATION_HEADER_LEN,
+    )?;
+    let total_len = u16::from_le_bytes([header[2], header[3]]);
+    let config = get_descriptor(controller, &device, super::DESC_CONFIGURATION, total_len)?;
+    let (interface, endpoint) = find_boot_keyboard(&config).ok_or("Not a boot keyboard")?;
+
+    let set_configuration = Setup {
+        request_type: 0,
+        request: super::SET_CONFIGURATION,
+        value: config[5] as u16,
+        index: 0,
+        length: 0,
+    };
+    controller.control(&device, &set_configuration, &[])?;
+    controller.control(
+        &device,
+        &class_request(SET_PROTOCOL, BOOT_PROTOCOL, interface),
+        &[],
+    )?;
+    // Report only on changes. It's optional for keyboards, so a stall is fine.
+    let _ = controller.control(&device, &class_request(SET_IDLE, 0, interface), &[]);
+
+    Ok(AttachedKeyboard {
+        device,
+        endpoint,
+        keyboard: hid::Keyboard::new(),
+    })
+}
+
+fn poll_interval(speed: Speed, interval: u8) -> Duration {
+    let interval = match speed {
+        Speed::High => Duration::from_micros(125 << interval.clamp(1, 16).saturating_sub(1)),
+        _ => Duration::from_millis(interval as u64),
+    };
+
+    interval.max(MIN_POLL_INTERVAL)
+}
+
+/// Called periodically while a keyboard is attached.
+fn poll() {
+    let controller = match super::host_controller() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    let report = KEYBOARD.lock(|kbd| {
+        let kbd = kbd.as_mut()?;
+
+        Some(
+            controller
+                .interrupt_in(&kbd.device, &mut kbd.endpoint)
+                .map(|report| report.map(|r| kbd.keyboard.report(&r))),
+        )
+    });
+
+    match report {
+        None | Some(Ok(None)) => (),
+        // Typed outside of the lock, as a line may run a command.
+        Some(Ok(Some(typed))) => {
+            for c in typed {
+                console::console().inject_char(line_discipline::input(c));
+            }
+        }
+        Some(Err(x)) => {
+            warn!("USB keyboard: {}, detached", x);
+            let _ = stop();
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Low => write!(f, "low"),
+            Self::Full => write!(f, "full"),
+            Self::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Find the first interface of a configuration descriptor that is a boot protocol keyboard.
+/// Returns its number and its interrupt IN endpoint.
+pub fn find_boot_keyboard(config: &[u8]) -> Option<(u8, InterruptEndpoint)> {
+    let mut keyboard = None;
+    let mut pos = 0;
+
+    while pos + 2 <= config.len() {
+        let len = config[pos] as usize;
+        if len < 2 || pos + len > config.len() {
+            return None;
+        }
+        let desc = &config[pos..pos + len];
+
+        match desc[1] {
+            super::DESC_INTERFACE if len >= 9 => {
+                keyboard = match (desc[5], desc[6], desc[7]) {
+                    (CLASS_HID, SUBCLASS_BOOT, PROTOCOL_KEYBOARD) => Some(desc[2]),
+                    _ => None,
+                };
+            }
+            super::DESC_ENDPOINT if len >= 7 => {
+                let interrupt_in =
+                    desc[2] & 0x80 != 0 && desc[3] & 0x03 == super::ENDPOINT_INTERRUPT;
+
+                if let (Some(interface), true) = (keyboard, interrupt_in) {
+                    let endpoint = InterruptEndpoint {
+                        number: desc[2] & 0x0F,
+                        packet_size: (u16::from_le_bytes([desc[4], desc[5]]) & 0x7FF) as usize,
+                        interval: desc[6],
+                        toggle: false,
+                    };
+
+                    return Some((interface, endpoint));
+                }
+            }
+            _ => (),
+        }
+
+        pos += len;
+    }
+
+    None
+}
+
+/// Return if a keyboard is attached.
+pub fn is_running() -> bool {
+    KEYBOARD.lock(|kbd| kbd.is_some())
+}
+
+/// Bring up the root port and attach the keyboard on it.
+pub fn start() -> Result<(), &'static str> {
+    if is_running() {
+        return Err("Already running");
+    }
+
+    let controller = super::host_controller()?;
+    let speed = controller.start_host()?;
+    let attached = match enumerate(controller, speed) {
+        Ok(attached) => attached,
+        Err(x) => {
+            let _ = controller.stop_host();
+            return Err(x);
+        }
+    };
+
+    let interval = poll_interval(speed, attached.endpoint.interval);
+    KEYBOARD.lock(|kbd| *kbd = Some(attached));
+
+    let timeout = time::time_manager().set_timeout_periodic(interval, Box::new(poll));
+    POLLING.lock(|polling| *polling = Some(timeout));
+
+    Ok(())
+}
+
+/// Detach the keyboard and power the root port off.
+pub fn stop() -> Result<(), &'static str> {
+    if KEYBOARD.lock(|kbd| kbd.take()).is_none() {
+        return Err("Not running");
+    }
+    if let Some(timeout) = POLLING.lock(|polling| polling.take()) {
+        timeout.cancel();
+    }
+
+    super::host_controller()?.stop_host()
+}
+
+/// Print the attached keyboard.
+pub fn print() -> Result<(), &'static str> {
+    let controller = super::host_controller()?;
+    let attached = KEYBOARD.lock(|kbd| kbd.as_ref().map(|k| (k.device, k.endpoint)));
+
+    info!("      Controller: {}", controller.name());
+    match attached {
+        Some((device, endpoint)) => {
+            info!("      Function:   keyboard, address {}", device.address);
+            info!(
+                "      State:      {} speed, endpoint {}",
+                device.speed, endpoint.number
+            );
+        }
+        None => info!("      Stopped"),
+    }
+
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// The keyboard interface of a combined keyboard and mouse must be found past the other
+    /// interface's endpoint, and nothing must be found in a truncated descriptor.
+    #[kernel_test]
+    fn boot_keyboard_in_configuration() {
+        #[rustfmt::skip]
+        let config = [
+            9, 2, 59, 0, 2, 1, 0, 0xA0, 50,
+            // Interface 0: HID, boot protocol mouse.
+            9, 4, 0, 0, 1, 3, 1, 2, 0,
+            9, 0x21, 0x11, 0x01, 0, 1, 0x22, 52, 0,
+            7, 5, 0x82, 3, 4, 0, 10,
+            // Interface 1: HID, boot protocol keyboard.
+            9, 4, 1, 0, 1, 3, 1, 1, 0,
+            9, 0x21, 0x11, 0x01, 0, 1, 0x22, 63, 0,
+            7, 5, 0x81, 3, 8, 0, 10,
+        ];
+
+        let (interface, endpoint) = find_boot_keyboard(&config).unwrap();
+        assert_eq!(interface, 1);
+        assert_eq!(
+            endpoint,
+            InterruptEndpoint {
+                number: 1,
+                packet_size: 8,
+                interval: 10,
+                toggle: false,
+            }
+        );
+
+        assert!(find_boot_keyboard(&config[..50]).is_none());
+    }
+}
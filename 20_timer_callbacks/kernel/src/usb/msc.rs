@@ -0,0 +1,502 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! USB mass storage, Bulk-Only Transport with the SCSI transparent command set.
+//!
+//! Every command is a Command Block Wrapper (CBW) received on the bulk OUT endpoint, optionally
+//! followed by data in either direction, and answered with a Command Status Wrapper (CSW) on the
+//! bulk IN endpoint. [`MassStorage`] is the state machine: the controller driver hands it
+//! completed transfers and does what the returned [`Bulk`] says.
+//!
+//! Only the commands that hosts send to a plain disk are supported. Data is moved in chunks of
+//! [`CHUNK_BLOCKS`] blocks, so that a large transfer doesn't need a large buffer.
+//!
+//! # Resources
+//!
+//! - Universal Serial Bus Mass Storage Class Bulk-Only Transport, Revision 1.0
+//! - SCSI Block Commands (SBC-2) and SCSI Primary Commands (SPC-2)
+
+use super::Bulk;
+use crate::block::{self, BLOCK_SIZE};
+use alloc::{vec, vec::Vec};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const CBW_LEN: usize = 31;
+const CBW_SIGNATURE: u32 = 0x4342_5355;
+const CSW_SIGNATURE: u32 = 0x5342_5355;
+
+const STATUS_PASSED: u8 = 0;
+const STATUS_FAILED: u8 = 1;
+const STATUS_PHASE_ERROR: u8 = 2;
+
+/// Blocks moved per bulk transfer.
+const CHUNK_BLOCKS: u32 = 8;
+
+// SCSI operation codes.
+const TEST_UNIT_READY: u8 = 0x00;
+const REQUEST_SENSE: u8 = 0x03;
+const INQUIRY: u8 = 0x12;
+const MODE_SENSE_6: u8 = 0x1A;
+const START_STOP_UNIT: u8 = 0x1B;
+const PREVENT_ALLOW_REMOVAL: u8 = 0x1E;
+const READ_FORMAT_CAPACITIES: u8 = 0x23;
+const READ_CAPACITY_10: u8 = 0x25;
+const READ_10: u8 = 0x28;
+const WRITE_10: u8 = 0x2A;
+const VERIFY_10: u8 = 0x2F;
+const SYNCHRONIZE_CACHE_10: u8 = 0x35;
+
+/// Sense key, additional sense code and qualifier.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct Sense(u8, u8, u8);
+
+const SENSE_NONE: Sense = Sense(0x00, 0x00, 0x00);
+const SENSE_INVALID_OPCODE: Sense = Sense(0x05, 0x20, 0x00);
+const SENSE_LBA_OUT_OF_RANGE: Sense = Sense(0x05, 0x21, 0x00);
+const SENSE_INVALID_FIELD: Sense = Sense(0x05, 0x24, 0x00);
+const SENSE_READ_ERROR: Sense = Sense(0x03, 0x11, 0x00);
+const SENSE_WRITE_ERROR: Sense = Sense(0x03, 0x0C, 0x00);
+
+struct Cbw {
+    tag: u32,
+    data_len: u32,
+    data_in: bool,
+    cb: [u8; 16],
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Phase {
+    /// Waiting for a CBW.
+    Command,
+
+    /// Sending blocks from `lba` on.
+    Read { lba: u64, blocks: u32 },
+
+    /// Receiving blocks for `lba` on.
+    Write { lba: u64, blocks: u32 },
+
+    /// Receiving data that is thrown away, `left` bytes of it.
+    Discard { left: u32 },
+
+    /// Sending the CSW once the data phase ended, or once the host cleared the IN halt.
+    Status,
+
+    /// The CSW has been sent.
+    StatusSent,
+
+    /// An invalid CBW was received. Only a reset recovers.
+    Halted,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The mass storage function, exporting one block device as its only logical unit.
+pub struct MassStorage {
+    device: block::Device,
+    packet_size: usize,
+    phase: Phase,
+    sense: Sense,
+    tag: u32,
+    residue: u32,
+    status: u8,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn be_u32(b: &[u8]) -> u32 {
+    u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn le_u32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+impl Cbw {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() != CBW_LEN || le_u32(&data[0..4]) != CBW_SIGNATURE {
+            return None;
+        }
+
+        let cb_len = data[14] as usize;
+        if !(1..=16).contains(&cb_len) {
+            return None;
+        }
+        let mut cb = [0; 16];
+        cb[..cb_len].copy_from_slice(&data[15..15 + cb_len]);
+
+        Some(Self {
+            tag: le_u32(&data[4..8]),
+            data_len: le_u32(&data[8..12]),
+            data_in: data[12] & 0x80 != 0,
+            cb,
+        })
+    }
+}
+
+impl MassStorage {
+    fn csw(&mut self) -> Bulk {
+        let mut csw = Vec::with_capacity(13);
+        csw.extend_from_slice(&CSW_SIGNATURE.to_le_bytes());
+        csw.extend_from_slice(&self.tag.to_le_bytes());
+        csw.extend_from_slice(&self.residue.to_le_bytes());
+        csw.push(self.status);
+
+        self.phase = Phase::StatusSent;
+        Bulk::Send(csw)
+    }
+
+    /// End the command without a data phase of its own. Data the host expects is refused.
+    fn finish(&mut self, cbw: &Cbw, status: u8, sense: Sense) -> Bulk {
+        self.status = status;
+        self.sense = sense;
+        self.residue = cbw.data_len;
+
+        match cbw.data_len {
+            0 => self.csw(),
+            len if !cbw.data_in => {
+                self.phase = Phase::Discard { left: len };
+                self.receive_discard(len)
+            }
+            _ => {
+                self.phase = Phase::Status;
+                Bulk::StallIn
+            }
+        }
+    }
+
+    fn receive_discard(&self, left: u32) -> Bulk {
+        Bulk::Receive((left as usize).min(CHUNK_BLOCKS as usize * BLOCK_SIZE))
+    }
+
+    /// Send `data`, the whole answer of a command, as much of it as the host asked for.
+    fn reply(&mut self, cbw: &Cbw, mut data: Vec<u8>) -> Bulk {
+        if !cbw.data_in || cbw.data_len == 0 {
+            return self.finish(cbw, STATUS_PHASE_ERROR, SENSE_NONE);
+        }
+
+        data.truncate(cbw.data_len as usize);
+        self.status = STATUS_PASSED;
+        self.sense = SENSE_NONE;
+        self.residue = cbw.data_len - data.len() as u32;
+
+        // A short packet ends the transfer. Otherwise, the host would wait for more data, so the
+        // endpoint is halted after the data.
+        self.phase = if self.residue != 0 && data.len() % self.packet_size == 0 {
+            Phase::Status
+        } else {
+            Phase::StatusSent
+        };
+        if data.is_empty() {
+            return Bulk::StallIn;
+        }
+
+        Bulk::Send(data)
+    }
+
+    fn inquiry(&self) -> Vec<u8> {
+        let mut data = vec![0u8; 36];
+        data[1] = 0x80; // Removable.
+        data[2] = 0x04; // SPC-2.
+        data[3] = 0x02; // Response data format.
+        data[4] = 36 - 5;
+        data[8..16].copy_from_slice(b"KHROS   ");
+        data[16..32].copy_from_slice(b"Block device    ");
+        data[32..36].copy_from_slice(b"1.00");
+
+        data
+    }
+
+    fn capacity(&mut self, cbw: &Cbw) -> Bulk {
+        let last = self
+            .device
+            .block_count()
+            .saturating_sub(1)
+            .min(u32::MAX as u64) as u32;
+
+        let mut data = Vec::with_capacity(8);
+        data.extend_from_slice(&last.to_be_bytes());
+        data.extend_from_slice(&(BLOCK_SIZE as u32).to_be_bytes());
+        self.reply(cbw, data)
+    }
+
+    fn format_capacities(&mut self, cbw: &Cbw) -> Bulk {
+        let blocks = self.device.block_count().min(u32::MAX as u64) as u32;
+
+        let mut data = vec![0, 0, 0, 8];
+        data.extend_from_slice(&blocks.to_be_bytes());
+        // Formatted media, then the block length in 3 bytes.
+        data.extend_from_slice(&(0x0200_0000 | BLOCK_SIZE as u32).to_be_bytes());
+        self.reply(cbw, data)
+    }
+
+    fn request_sense(&mut self, cbw: &Cbw) -> Bulk {
+        let Sense(key, asc, ascq) = self.sense;
+
+        let mut data = vec![0u8; 18];
+        data[0] = 0x70; // Current error, fixed format.
+        data[2] = key;
+        data[7] = 18 - 8;
+        data[12] = asc;
+        data[13] = ascq;
+        self.reply(cbw, data)
+    }
+
+    /// Check a READ(10) or WRITE(10) and start its data phase.
+    fn transfer(&mut self, cbw: &Cbw, read: bool) -> Bulk {
+        let lba = be_u32(&cbw.cb[2..6]) as u64;
+        let blocks = u16::from_be_bytes([cbw.cb[7], cbw.cb[8]]) as u32;
+
+        if cbw.data_in != read || cbw.data_len != blocks * BLOCK_SIZE as u32 {
+            return self.finish(cbw, STATUS_PHASE_ERROR, SENSE_NONE);
+        }
+        if lba + blocks as u64 > self.device.block_count() {
+            return self.finish(cbw, STATUS_FAILED, SENSE_LBA_OUT_OF_RANGE);
+        }
+        if blocks == 0 {
+            return self.finish(cbw, STATUS_PASSED, SENSE_NONE);
+        }
+
+        self.status = STATUS_PASSED;
+        self.sense = SENSE_NONE;
+        self.residue = cbw.data_len;
+        if read {
+            self.phase = Phase::Read { lba, blocks };
+            self.next_read()
+        } else {
+            self.phase = Phase::Write { lba, blocks };
+            Bulk::Receive(blocks.min(CHUNK_BLOCKS) as usize * BLOCK_SIZE)
+        }
+    }
+
+    /// Send the next chunk of a READ(10).
+    fn next_read(&mut self) -> Bulk {
+        let (lba, blocks) = match self.phase {
+            Phase::Read { lba, blocks } if blocks > 0 => (lba, blocks),
+            _ => return self.csw(),
+        };
+
+        let count = blocks.min(CHUNK_BLOCKS);
+        let mut data = vec![0u8; count as usize * BLOCK_SIZE];
+        if self.device.read(lba, &mut data).is_err() {
+            // The host still expects the data, so it gets the halt instead.
+            self.status = STATUS_FAILED;
+            self.sense = SENSE_READ_ERROR;
+            self.phase = Phase::Status;
+            return Bulk::StallIn;
+        }
+
+        self.residue -= data.len() as u32;
+        self.phase = Phase::Read {
+            lba: lba + count as u64,
+            blocks: blocks - count,
+        };
+        Bulk::Send(data)
+    }
+
+    fn command(&mut self, cbw: &Cbw) -> Bulk {
+        match cbw.cb[0] {
+            TEST_UNIT_READY
+            | START_STOP_UNIT
+            | PREVENT_ALLOW_REMOVAL
+            | VERIFY_10
+            | SYNCHRONIZE_CACHE_10 => self.finish(cbw, STATUS_PASSED, SENSE_NONE),
+            REQUEST_SENSE => self.request_sense(cbw),
+            INQUIRY if cbw.cb[1] & 0x01 != 0 => {
+                self.finish(cbw, STATUS_FAILED, SENSE_INVALID_FIELD)
+            }
+            INQUIRY => {
+                let data = self.inquiry();
+                self.reply(cbw, data)
+            }
+            // No mode pages, not write protected.
+            MODE_SENSE_6 => self.reply(cbw, vec![3, 0, 0, 0]),
+            READ_FORMAT_CAPACITIES => self.format_capacities(cbw),
+            READ_CAPACITY_10 => self.capacity(cbw),
+            READ_10 => self.transfer(cbw, true),
+            WRITE_10 => self.transfer(cbw, false),
+            _ => self.finish(cbw, STATUS_FAILED, SENSE_INVALID_OPCODE),
+        }
+    }
+
+    fn write_chunk(&mut self, lba: u64, blocks: u32, data: &[u8]) -> Bulk {
+        let count = (data.len() / BLOCK_SIZE) as u32;
+        if count == 0 || count > blocks || data.len() % BLOCK_SIZE != 0 {
+            self.status = STATUS_PHASE_ERROR;
+            return self.csw();
+        }
+
+        self.residue -= data.len() as u32;
+        if self.device.write(lba, data).is_err() {
+            self.status = STATUS_FAILED;
+            self.sense = SENSE_WRITE_ERROR;
+        }
+
+        let blocks = blocks - count;
+        if blocks == 0 {
+            return self.csw();
+        }
+        if self.status != STATUS_PASSED {
+            self.phase = Phase::Discard {
+                left: blocks * BLOCK_SIZE as u32,
+            };
+            return self.receive_discard(blocks * BLOCK_SIZE as u32);
+        }
+
+        self.phase = Phase::Write {
+            lba: lba + count as u64,
+            blocks,
+        };
+        Bulk::Receive(blocks.min(CHUNK_BLOCKS) as usize * BLOCK_SIZE)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl MassStorage {
+    /// Create an instance exporting `device`.
+    pub fn new(device: block::Device) -> Self {
+        Self {
+            device,
+            packet_size: 512,
+            phase: Phase::Command,
+            sense: SENSE_NONE,
+            tag: 0,
+            residue: 0,
+            status: STATUS_PASSED,
+        }
+    }
+
+    /// Name of the exported device.
+    pub fn device_name(&self) -> &str {
+        self.device.name()
+    }
+
+    /// Set the bulk endpoints' maximum packet size, which depends on the bus speed.
+    pub fn set_packet_size(&mut self, packet_size: usize) {
+        self.packet_size = packet_size;
+    }
+
+    /// Start over, after the host configured the device or requested a Bulk-Only reset.
+    pub fn reset(&mut self) -> Bulk {
+        self.phase = Phase::Command;
+        self.sense = SENSE_NONE;
+
+        Bulk::Receive(CBW_LEN)
+    }
+
+    /// A transfer on the OUT endpoint completed.
+    pub fn received(&mut self, data: &[u8]) -> Bulk {
+        match self.phase {
+            Phase::Command => match Cbw::parse(data) {
+                Some(cbw) => {
+                    self.tag = cbw.tag;
+                    self.command(&cbw)
+                }
+                None => {
+                    self.phase = Phase::Halted;
+                    Bulk::StallIn
+                }
+            },
+            Phase::Write { lba, blocks } => self.write_chunk(lba, blocks, data),
+            Phase::Discard { left } => {
+                let left = left.saturating_sub(data.len() as u32);
+                if left == 0 || data.is_empty() {
+                    return self.csw();
+                }
+
+                self.phase = Phase::Discard { left };
+                self.receive_discard(left)
+            }
+            _ => Bulk::Idle,
+        }
+    }
+
+    /// A transfer on the IN endpoint completed.
+    pub fn sent(&mut self) -> Bulk {
+        match self.phase {
+            Phase::Read { .. } => self.next_read(),
+            Phase::Status => Bulk::StallIn,
+            Phase::StatusSent => self.reset(),
+            _ => Bulk::Idle,
+        }
+    }
+
+    /// The host cleared the halt of the IN endpoint.
+    pub fn halt_cleared(&mut self) -> Bulk {
+        match self.phase {
+            Phase::Status => self.csw(),
+            _ => Bulk::Idle,
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::RamDisk;
+    use alloc::{boxed::Box, string::String};
+    use test_macros::kernel_test;
+
+    fn cbw(tag: u32, data_len: u32, data_in: bool, cb: &[u8]) -> Vec<u8> {
+        let mut cbw = vec![0u8; CBW_LEN];
+        cbw[0..4].copy_from_slice(&CBW_SIGNATURE.to_le_bytes());
+        cbw[4..8].copy_from_slice(&tag.to_le_bytes());
+        cbw[8..12].copy_from_slice(&data_len.to_le_bytes());
+        cbw[12] = if data_in { 0x80 } else { 0 };
+        cbw[14] = cb.len() as u8;
+        cbw[15..15 + cb.len()].copy_from_slice(cb);
+
+        cbw
+    }
+
+    /// A block written with WRITE(10) must read back with READ(10), each command ending in a
+    /// passed CSW with its tag, and reads past the end must fail with a halt before the CSW.
+    #[kernel_test]
+    fn write_then_read() {
+        let disk: &'static RamDisk = Box::leak(Box::new(RamDisk::new(String::from("msc"), 16)));
+        let mut msc = MassStorage::new(disk);
+        let passed = |tag: u32, status: u8| {
+            let mut csw = Vec::new();
+            csw.extend_from_slice(&CSW_SIGNATURE.to_le_bytes());
+            csw.extend_from_slice(&tag.to_le_bytes());
+            csw.extend_from_slice(&0u32.to_le_bytes());
+            csw.push(status);
+            Bulk::Send(csw)
+        };
+
+        assert_eq!(msc.reset(), Bulk::Receive(CBW_LEN));
+        let write = [WRITE_10, 0, 0, 0, 0, 3, 0, 0, 1, 0];
+        assert_eq!(
+            msc.received(&cbw(7, 512, false, &write)),
+            Bulk::Receive(BLOCK_SIZE)
+        );
+        assert_eq!(msc.received(&[0x5A; BLOCK_SIZE]), passed(7, STATUS_PASSED));
+        assert_eq!(msc.sent(), Bulk::Receive(CBW_LEN));
+
+        let read = [READ_10, 0, 0, 0, 0, 3, 0, 0, 1, 0];
+        assert_eq!(
+            msc.received(&cbw(8, 512, true, &read)),
+            Bulk::Send(vec![0x5A; BLOCK_SIZE])
+        );
+        assert_eq!(msc.sent(), passed(8, STATUS_PASSED));
+        assert_eq!(msc.sent(), Bulk::Receive(CBW_LEN));
+
+        let past_end = [READ_10, 0, 0, 0, 0, 16, 0, 0, 1, 0];
+        assert_eq!(msc.received(&cbw(9, 512, true, &past_end)), Bulk::StallIn);
+        assert!(matches!(msc.halt_cleared(), Bulk::Send(csw) if csw[12] == STATUS_FAILED));
+    }
+}
@@ -0,0 +1,299 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Software watchdog.
+//!
+//! Subsystems register with a timeout and must [`check_in()`] at least that often. [`check()`]
+//! runs once per second, both from a timer callback and from the idle loop, so that a wedged timer
+//! is noticed as long as the idle loop still runs and vice versa. A subsystem that missed its
+//! check-in is reported once, with diagnostics, and again only after it checked in in between.
+//!
+//! With the `watchdog.hardware` setting on, every check that finds all subsystems on time restarts
+//! the hardware watchdog. A missed check-in, or a wedge that keeps the checks from running at all,
+//! then resets the board after [`HARDWARE_TIMEOUT`].
+
+use crate::{
+    bsp, config, info, jobs, sched, shutdown,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time, warn,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Time between two checks.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The timer callback checks in on every check, so this leaves room for a few late ones.
+const TIMER_TIMEOUT: Duration = Duration::from_secs(5);
+
+const KEY_HARDWARE: &str = "watchdog.hardware";
+
+struct Entry {
+    name: &'static str,
+    timeout: Duration,
+    last_check_in: Duration,
+    missed: usize,
+
+    /// Set while the current miss has been reported.
+    overdue: bool,
+}
+
+struct WatchdogInner {
+    entries: Vec<Entry>,
+    last_check: Duration,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Time from the last successful check until the hardware watchdog resets the board.
+pub const HARDWARE_TIMEOUT: Duration = Duration::from_secs(10);
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static WATCHDOG: IRQSafeNullLock<WatchdogInner> = IRQSafeNullLock::new(WatchdogInner {
+    entries: Vec::new(),
+    last_check: Duration::ZERO,
+});
+
+static HARDWARE: AtomicBool = AtomicBool::new(false);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl Entry {
+    fn is_overdue(&self, now: Duration) -> bool {
+        now.saturating_sub(self.last_check_in) > self.timeout
+    }
+}
+
+/// Report that `name` missed its check-in.
+fn report(name: &str, since: Duration, timeout: Duration) {
+    warn!(
+        "Watchdog: {} missed its check-in, last one {}.{:03} s ago (timeout {} s)",
+        name,
+        since.as_secs(),
+        since.subsec_millis(),
+        timeout.as_secs()
+    );
+    info!("Tasks:");
+    sched::print();
+    info!("Jobs:");
+    jobs::print();
+
+    if HARDWARE.load(Ordering::Relaxed) {
+        warn!(
+            "Watchdog: hardware watchdog resets the board in at most {} s",
+            HARDWARE_TIMEOUT.as_secs()
+        );
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Start the periodic check, watch the timer dispatcher and apply the `watchdog.hardware` setting.
+/// Must be called after the config store was loaded.
+pub fn init() -> Result<(), &'static str> {
+    static INIT_DONE: AtomicBool = AtomicBool::new(false);
+    if INIT_DONE.load(Ordering::Relaxed) {
+        return Err("Init already done");
+    }
+
+    register("timer", TIMER_TIMEOUT)?;
+    time::time_manager().set_timeout_periodic(
+        CHECK_INTERVAL,
+        Box::new(|| {
+            check_in("timer");
+            check();
+        }),
+    );
+
+    set_hardware(config::store().get(KEY_HARDWARE).as_deref() == Some("on"));
+
+    // The next image doesn't know it has to feed the hardware watchdog.
+    shutdown::register("watchdog", shutdown::Stage::Devices, || {
+        set_hardware(false);
+        Ok(())
+    })?;
+
+    INIT_DONE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Watch a subsystem that checks in at least every `timeout`.
+pub fn register(name: &'static str, timeout: Duration) -> Result<(), &'static str> {
+    let now = time::time_manager().uptime();
+
+    WATCHDOG.lock(|inner| {
+        if inner.entries.iter().any(|e| e.name == name) {
+            return Err("Already watched");
+        }
+
+        inner.entries.push(Entry {
+            name,
+            timeout,
+            last_check_in: now,
+            missed: 0,
+            overdue: false,
+        });
+
+        Ok(())
+    })
+}
+
+/// Tell the watchdog that `name` is alive. Can be called from IRQ context.
+pub fn check_in(name: &str) {
+    let now = time::time_manager().uptime();
+
+    let recovered = WATCHDOG.lock(|inner| {
+        let entry = inner.entries.iter_mut().find(|e| e.name == name)?;
+        let late = now.saturating_sub(entry.last_check_in);
+        let was_overdue = entry.overdue;
+        entry.last_check_in = now;
+        entry.overdue = false;
+
+        was_overdue.then_some(late)
+    });
+
+    if let Some(late) = recovered {
+        info!(
+            "Watchdog: {} checked in again after {}.{:03} s",
+            name,
+            late.as_secs(),
+            late.subsec_millis()
+        );
+    }
+}
+
+/// Report the subsystems that missed their check-in, and restart the hardware watchdog if there
+/// are none. Does nothing if the last check was less than a second ago.
+pub fn check() {
+    let now = time::time_manager().uptime();
+
+    let result = WATCHDOG.lock(|inner| {
+        if now.saturating_sub(inner.last_check) < CHECK_INTERVAL {
+            return None;
+        }
+        inner.last_check = now;
+
+        let mut healthy = true;
+        let mut newly_overdue = Vec::new();
+        for e in inner.entries.iter_mut().filter(|e| e.is_overdue(now)) {
+            healthy = false;
+            if !e.overdue {
+                e.overdue = true;
+                e.missed += 1;
+                newly_overdue.push((e.name, now - e.last_check_in, e.timeout));
+            }
+        }
+
+        Some((healthy, newly_overdue))
+    });
+
+    let (healthy, newly_overdue) = match result {
+        Some(r) => r,
+        None => return,
+    };
+
+    // Reported outside of the lock, so that the diagnostics may check in.
+    for (name, since, timeout) in newly_overdue {
+        report(name, since, timeout);
+    }
+
+    if healthy && HARDWARE.load(Ordering::Relaxed) {
+        unsafe { bsp::driver::watchdog_start(HARDWARE_TIMEOUT) };
+    }
+}
+
+/// Enable or disable the hardware watchdog.
+pub fn set_hardware(enable: bool) {
+    HARDWARE.store(enable, Ordering::Relaxed);
+
+    unsafe {
+        if enable {
+            bsp::driver::watchdog_start(HARDWARE_TIMEOUT);
+        } else {
+            bsp::driver::watchdog_stop();
+        }
+    }
+}
+
+/// Enable or disable the hardware watchdog and store the choice in the config store.
+pub fn configure_hardware(enable: bool) -> Result<(), &'static str> {
+    config::store().set(KEY_HARDWARE, if enable { "on" } else { "off" })?;
+    set_hardware(enable);
+
+    Ok(())
+}
+
+/// Print the watched subsystems.
+pub fn print() {
+    let now = time::time_manager().uptime();
+
+    WATCHDOG.lock(|inner| {
+        for e in &inner.entries {
+            let since = now.saturating_sub(e.last_check_in);
+
+            info!(
+                "      {:<8} last check-in {:>4}.{:03} s ago, timeout {:>3} s, {} missed{}",
+                e.name,
+                since.as_secs(),
+                since.subsec_millis(),
+                e.timeout.as_secs(),
+                e.missed,
+                if e.overdue { " (overdue)" } else { "" }
+            );
+        }
+    });
+
+    if HARDWARE.load(Ordering::Relaxed) {
+        let remaining = unsafe { bsp::driver::watchdog_remaining() };
+        info!(
+            "      Hardware watchdog: on, reset in {}.{:03} s",
+            remaining.as_secs(),
+            remaining.subsec_millis()
+        );
+    } else {
+        info!("      Hardware watchdog: off");
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// A subsystem is overdue only once more than its timeout passed since the last check-in.
+    #[kernel_test]
+    fn overdue_after_timeout() {
+        let entry = Entry {
+            name: "test",
+            timeout: Duration::from_secs(3),
+            last_check_in: Duration::from_secs(10),
+            missed: 0,
+            overdue: false,
+        };
+
+        assert!(!entry.is_overdue(Duration::from_secs(5)));
+        assert!(!entry.is_overdue(Duration::from_secs(13)));
+        assert!(entry.is_overdue(Duration::from_millis(13_001)));
+    }
+}
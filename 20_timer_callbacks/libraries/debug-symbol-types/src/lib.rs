@@ -33,6 +33,11 @@ impl Symbol {
         self.addr_range.contains(&addr)
     }
 
+    /// Returns the symbol's start address.
+    pub fn start(&self) -> usize {
+        self.addr_range.start
+    }
+
     /// Returns the symbol's name.
     pub fn name(&self) -> &'static str {
         self.name
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Simulated board.
+
+/// Simulated GPIO.
+///
+/// Pins keep their function and level in memory. There are no protected pins, so `force` has no
+/// effect.
+pub mod driver {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    //----------------------------------------------------------------------------------------------
+    // Private Definitions
+    //----------------------------------------------------------------------------------------------
+
+    /// Number of GPIO pins of the BCM2837.
+    const NUM_PINS: u8 = 54;
+
+    //----------------------------------------------------------------------------------------------
+    // Global instances
+    //----------------------------------------------------------------------------------------------
+
+    /// Pins that are outputs, as a mask.
+    static OUTPUTS: AtomicU64 = AtomicU64::new(0);
+
+    /// Pins that are high, as a mask.
+    static LEVELS: AtomicU64 = AtomicU64::new(0);
+
+    //----------------------------------------------------------------------------------------------
+    // Private Code
+    //----------------------------------------------------------------------------------------------
+
+    fn bit(pin: u8) -> Result<u64, &'static str> {
+        if pin >= NUM_PINS {
+            return Err("Invalid pin");
+        }
+
+        Ok(1 << pin)
+    }
+
+    fn output_bit(pin: u8) -> Result<u64, &'static str> {
+        let bit = bit(pin)?;
+        if OUTPUTS.load(Ordering::Relaxed) & bit == 0 {
+            return Err("Pin is not an output");
+        }
+
+        Ok(bit)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Public Code
+    //----------------------------------------------------------------------------------------------
+
+    /// Make `pin` an output.
+    ///
+    /// # Safety
+    ///
+    /// - None. Unsafe to match the board's driver.
+    pub unsafe fn gpio_as_output(pin: u8, _force: bool) -> Result<(), &'static str> {
+        OUTPUTS.fetch_or(bit(pin)?, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Drive output `pin` high.
+    ///
+    /// # Safety
+    ///
+    /// - None. Unsafe to match the board's driver.
+    pub unsafe fn gpio_high(pin: u8, _force: bool) -> Result<(), &'static str> {
+        LEVELS.fetch_or(output_bit(pin)?, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Drive output `pin` low.
+    ///
+    /// # Safety
+    ///
+    /// - None. Unsafe to match the board's driver.
+    pub unsafe fn gpio_low(pin: u8, _force: bool) -> Result<(), &'static str> {
+        LEVELS.fetch_and(!output_bit(pin)?, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Return the level of `pin`.
+    pub fn gpio_read(pin: u8) -> Result<bool, &'static str> {
+        Ok(LEVELS.load(Ordering::Relaxed) & bit(pin)? != 0)
+    }
+}
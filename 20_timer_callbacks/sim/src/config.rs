@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! In-memory config store. Settings are lost when the simulator exits.
+
+use crate::synchronization::{interface::Mutex, IRQSafeNullLock};
+use std::collections::BTreeMap;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Key-value settings.
+pub struct ConfigStore {
+    entries: IRQSafeNullLock<BTreeMap<String, String>>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static CONFIG_STORE: ConfigStore = ConfigStore {
+    entries: IRQSafeNullLock::new(BTreeMap::new()),
+};
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl ConfigStore {
+    /// Return the value of a setting.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.entries.lock(|entries| entries.get(key).cloned())
+    }
+
+    /// Change a setting. Keys must not contain `=`, values must not contain line breaks.
+    pub fn set(&self, key: &str, value: &str) -> Result<(), &'static str> {
+        if key.is_empty() || key.contains('=') || key.contains('\n') || value.contains('\n') {
+            return Err("Invalid key or value");
+        }
+
+        self.entries
+            .lock(|entries| entries.insert(key.to_string(), value.to_string()));
+
+        Ok(())
+    }
+
+    /// Print all settings.
+    pub fn print(&self) {
+        self.entries.lock(|entries| {
+            for (key, value) in entries.iter() {
+                info!("      {} = {}", key, value);
+            }
+        });
+    }
+}
+
+/// Return a reference to the config store.
+pub fn store() -> &'static ConfigStore {
+    &CONFIG_STORE
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! In-memory console.
+//!
+//! Every printed line is kept in a bounded log and sent to stdout and to all connected shell
+//! clients, so that output from pattern threads and timer callbacks shows up everywhere, like on
+//! the board's UART.
+
+use std::{fmt, io::Write, net::TcpStream, sync::Mutex};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Lines kept in the log.
+const LOG_LINES: usize = 256;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+static CLIENTS: Mutex<Vec<TcpStream>> = Mutex::new(Vec::new());
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Prints an info, with a newline.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => ({
+        $crate::console::print_line(format_args!($($arg)*));
+    })
+}
+
+/// Prints a warning, with a newline.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => ({
+        $crate::console::print_line(format_args!("[W] {}", format_args!($($arg)*)));
+    })
+}
+
+/// Keep a line in the log and send it to stdout and every client. Clients that went away are
+/// dropped.
+pub fn print_line(args: fmt::Arguments) {
+    let line = args.to_string();
+    println!("{}", line);
+
+    CLIENTS
+        .lock()
+        .unwrap()
+        .retain_mut(|client| write!(client, "{}\r\n", line).is_ok());
+
+    let mut log = LOG.lock().unwrap();
+    if log.len() == LOG_LINES {
+        log.remove(0);
+    }
+    log.push(line);
+}
+
+/// Send the console output to `client` from now on.
+pub fn attach(client: TcpStream) {
+    CLIENTS.lock().unwrap().push(client);
+}
+
+/// Return the last `n` lines of the log.
+pub fn tail(n: usize) -> Vec<String> {
+    let log = LOG.lock().unwrap();
+
+    log[log.len().saturating_sub(n)..].to_vec()
+}
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Run the kernel's hardware-independent logic on the host.
+//!
+//! The LED patterns and the timer helpers are compiled from the kernel's own source files. They
+//! reach the rest of the kernel through `crate::` paths, which resolve to the small stand-ins in
+//! this crate instead: simulated GPIO, an in-memory config store and console, threads as tasks,
+//! and timers on the kernel's [`time::ManualTimer`], which follows the wall clock unless stopped.
+//!
+//! The shell listens on a TCP port, `2323` unless given as the first argument:
+//!
+//! ```console
+//! $ make sim
+//! $ nc localhost 2323
+//! ```
+
+extern crate alloc;
+
+#[macro_use]
+mod console;
+
+mod bsp;
+mod config;
+mod shell;
+mod synchronization;
+mod task;
+mod time;
+mod trace;
+
+/// LED patterns.
+#[allow(dead_code)]
+#[path = "../../kernel/src/pattern.rs"]
+mod pattern;
+
+use std::{env, net::TcpListener, process, thread};
+
+const DEFAULT_PORT: u16 = 2323;
+
+fn main() {
+    let port = match env::args().nth(1).map(|arg| arg.parse::<u16>()) {
+        None => DEFAULT_PORT,
+        Some(Ok(port)) => port,
+        Some(Err(_)) => {
+            eprintln!("Usage: sim [<port>]");
+            process::exit(2);
+        }
+    };
+
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Can't listen on port {}: {}", port, e);
+            process::exit(1);
+        }
+    };
+
+    time::init();
+    if let Err(x) = pattern::load_brightness() {
+        warn!("Brightness: {}", x);
+    }
+    info!("Simulator listening on 127.0.0.1:{}", port);
+
+    for stream in listener.incoming().flatten() {
+        thread::spawn(move || shell::serve(stream));
+    }
+}
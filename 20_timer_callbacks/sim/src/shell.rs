@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! The simulator's shell.
+//!
+//! Offers the kernel's pattern commands with the same syntax, plus commands to look at the
+//! simulated LEDs and to control the clock. All clients share one console, like on the board's
+//! UART, so every client sees the output of every command.
+
+use crate::{bsp, config, console, pattern, time, trace};
+use std::{
+    io::{BufRead, BufReader},
+    net::TcpStream,
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A command handler. `args[0]` is the command name. An error is printed after the command name.
+type Handler = fn(args: &[&str]) -> Result<(), &'static str>;
+
+/// Lines `log` prints unless told otherwise.
+const DEFAULT_LOG_LINES: usize = 20;
+
+const COMMANDS: &[(&str, &str, Handler)] = &[
+    (
+        "clock",
+        "Show, stop, run or step the simulated clock",
+        clock,
+    ),
+    ("config", "List the settings", settings),
+    ("help", "List the commands", help),
+    ("hex_counter", "Count to 15 in binary on the LEDs", counter),
+    ("leds", "Show the LEDs", leds),
+    ("left_counter", "Walk an LED to the left", counter),
+    ("log", "Print the last lines of the console", log),
+    (
+        "pattern",
+        "List, stop or dim the running LED patterns",
+        pattern,
+    ),
+    ("right_counter", "Walk an LED to the right", counter),
+    ("trace", "Print the trace records", print_trace),
+    ("uptime", "Print the simulated uptime", uptime),
+];
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn help(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Commands:");
+    for (name, help, _) in COMMANDS {
+        info!("      {:<16} {}", name, help);
+    }
+
+    Ok(())
+}
+
+fn counter(args: &[&str]) -> Result<(), &'static str> {
+    let (title, pattern) = match args[0] {
+        "hex_counter" => ("Hex Counter:", pattern::Pattern::Hex),
+        "left_counter" => ("Left Counter:", pattern::Pattern::Left),
+        _ => ("Right Counter:", pattern::Pattern::Right),
+    };
+    let mut patterns = vec![pattern];
+    let mut pins = vec![];
+
+    // Pins follow `on`, further patterns follow `then`.
+    let mut section = None;
+    for arg in &args[1..] {
+        match (*arg, section) {
+            ("on" | "then", _) => section = Some(*arg),
+            (pin, Some("on")) => pins.push(pin.parse::<u8>().map_err(|_| "Invalid pin")?),
+            (name, Some(_)) => patterns.push(name.parse()?),
+            _ => section = None,
+        }
+        if section.is_none() {
+            info!(
+                "Usage: {} [on <pin>...] [then <hex|left|right>...]",
+                args[0]
+            );
+            return Ok(());
+        }
+    }
+
+    // Without pins, the counter replaces whatever runs on its default pins.
+    let pins = if pins.is_empty() {
+        let pins = pattern::default_pins(&patterns);
+        pattern::stop_on(pins);
+        pins
+    } else {
+        &pins[..]
+    };
+
+    info!("{}", title);
+    let id = pattern::start_chain(&patterns, Some(pins), None)?;
+    info!("Pattern {} on pins {:?}", id, pins);
+
+    Ok(())
+}
+
+fn pattern(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1), args.get(2).map(|a| a.parse::<u32>())) {
+        (None, _) => {
+            info!("Running patterns:");
+            pattern::print();
+            info!("Brightness: {} %", pattern::brightness());
+        }
+        (Some(&"stop"), None) => {
+            pattern::stop();
+            pattern::reset_pins();
+        }
+        (Some(&"stop"), Some(Ok(id))) => pattern::stop_id(id as usize)?,
+        (Some(&"brightness"), None) => info!("Brightness: {} %", pattern::brightness()),
+        (Some(&"brightness"), Some(Ok(percent))) => {
+            pattern::set_brightness(percent)?;
+            pattern::store_brightness()?;
+            info!("Brightness: {} %", percent);
+        }
+        _ => info!("Usage: pattern [stop [<id>] | brightness [<percent>]]"),
+    }
+
+    Ok(())
+}
+
+/// Show the LEDs on the ring pins, `*` for lit. While dimmed, this is the carrier's level.
+fn leds(_args: &[&str]) -> Result<(), &'static str> {
+    let mut pins = String::new();
+    let mut levels = String::new();
+    for pin in pattern::RING_PINS {
+        pins.push_str(&format!(" {}", pin));
+        levels.push_str(if bsp::driver::gpio_read(pin)? {
+            " *"
+        } else {
+            " ."
+        });
+    }
+    info!("      Pin  {}", pins);
+    info!("      LED  {}", levels);
+
+    Ok(())
+}
+
+fn clock(args: &[&str]) -> Result<(), &'static str> {
+    match (args.get(1).copied(), args.get(2).map(|a| a.parse::<u64>())) {
+        (None, _) => (),
+        (Some("run"), None) => time::set_running(true),
+        (Some("stop"), None) => time::set_running(false),
+        (Some("advance"), Some(Ok(ms))) => time::time_manager().advance(Duration::from_millis(ms)),
+        _ => {
+            info!("Usage: clock [run | stop | advance <ms>]");
+            return Ok(());
+        }
+    }
+    info!(
+        "Clock {} at {}, {} timeouts pending",
+        if time::is_running() {
+            "running"
+        } else {
+            "stopped"
+        },
+        time::Seconds(time::time_manager().uptime()),
+        time::time_manager().pending()
+    );
+
+    Ok(())
+}
+
+fn settings(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Settings:");
+    config::store().print();
+
+    Ok(())
+}
+
+fn log(args: &[&str]) -> Result<(), &'static str> {
+    let lines = match args.get(1) {
+        Some(n) => n.parse().map_err(|_| "Invalid number of lines")?,
+        None => DEFAULT_LOG_LINES,
+    };
+
+    // Collected first, as printing adds to the log.
+    for line in console::tail(lines) {
+        info!("{}", line);
+    }
+
+    Ok(())
+}
+
+fn print_trace(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Trace:");
+    trace::print();
+
+    Ok(())
+}
+
+fn uptime(_args: &[&str]) -> Result<(), &'static str> {
+    info!("Uptime: {}", time::Human(time::time_manager().uptime()));
+
+    Ok(())
+}
+
+/// Look up and run a command line.
+fn dispatch(line: &str) {
+    let args: Vec<&str> = line.split_whitespace().collect();
+    let name = match args.first() {
+        Some(name) => *name,
+        None => return,
+    };
+
+    match COMMANDS.iter().find(|(n, _, _)| *n == name) {
+        Some((_, _, handler)) => {
+            if let Err(x) = handler(&args) {
+                info!("{}: {}", name, x);
+            }
+        }
+        None => info!("Command not found: {}", name),
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Attach `stream` to the console and run the lines it sends until it disconnects.
+pub fn serve(stream: TcpStream) {
+    let reader = match stream.try_clone() {
+        Ok(reader) => BufReader::new(reader),
+        Err(_) => return,
+    };
+    console::attach(stream);
+    info!("Client connected. Type 'help' for the commands.");
+
+    for line in reader.lines() {
+        match line {
+            Ok(line) => dispatch(line.trim()),
+            Err(_) => break,
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Synchronization primitives.
+//!
+//! On the board, [`IRQSafeNullLock`] only masks IRQs, which is enough on a single core. Here, tasks
+//! and timer callbacks are threads, so it is a real lock. Kernel code never takes one of these
+//! locks again while holding it, so it needn't be reentrant.
+
+use std::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Synchronization interfaces.
+pub mod interface {
+    /// Any object implementing this trait guarantees exclusive access to the data wrapped within
+    /// the Mutex for the duration of the provided closure.
+    pub trait Mutex {
+        /// The type of the data that is wrapped by this mutex.
+        type Data;
+
+        /// Locks the mutex and grants the closure temporary mutable access to the wrapped data.
+        fn lock<'a, R>(&'a self, f: impl FnOnce(&'a mut Self::Data) -> R) -> R;
+    }
+}
+
+/// A lock with the kernel's interface that excludes other threads.
+pub struct IRQSafeNullLock<T> {
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+unsafe impl<T> Send for IRQSafeNullLock<T> where T: Send {}
+unsafe impl<T> Sync for IRQSafeNullLock<T> where T: Send {}
+
+impl<T> IRQSafeNullLock<T> {
+    /// Create an instance.
+    pub const fn new(data: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+}
+
+impl<T> interface::Mutex for IRQSafeNullLock<T> {
+    type Data = T;
+
+    fn lock<'a, R>(&'a self, f: impl FnOnce(&'a mut Self::Data) -> R) -> R {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            thread::yield_now();
+        }
+
+        // The flag ensures that this is the only reference until the release below.
+        let data = unsafe { &mut *self.data.get() };
+        let result = f(data);
+
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
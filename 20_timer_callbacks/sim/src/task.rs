@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Tasks, run as host threads.
+
+use crate::time;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// How often a sleeping task looks at the simulated clock.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Start a task that runs `f` once. Returns the task id.
+pub fn spawn(name: &str, f: impl FnOnce() + Send + 'static) -> Result<usize, &'static str> {
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(f)
+        .map_err(|_| "Can't start thread")?;
+
+    Ok(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Sleep until the simulated clock advanced by `duration`. Doesn't return while the clock is
+/// stopped.
+pub fn sleep(duration: Duration) {
+    let due = time::time_manager().uptime() + duration;
+
+    while time::time_manager().uptime() < due {
+        thread::sleep(POLL_INTERVAL);
+    }
+}
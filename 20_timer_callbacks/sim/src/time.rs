@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Simulated time.
+//!
+//! The uptime is the kernel's [`ManualTimer`]. A ticker thread advances it with the wall clock and
+//! runs the due timeouts every millisecond, so a timeout fires up to that much late. The clock can
+//! be stopped and stepped by hand instead, which makes the patterns and timeouts deterministic.
+
+#[allow(dead_code)]
+#[path = "../../kernel/src/time/format.rs"]
+mod format;
+
+#[allow(dead_code)]
+#[path = "../../kernel/src/time/manual.rs"]
+mod manual;
+
+use crate::synchronization::{interface::Mutex, IRQSafeNullLock};
+use interface::Timer as _;
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Time between two ticks of the ticker thread.
+const TICK: Duration = Duration::from_millis(1);
+
+struct Timeout {
+    id: u64,
+    due_time: Duration,
+    callback: TimeoutCallback,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+pub use format::{Human, Seconds};
+pub use manual::ManualTimer;
+
+/// Timer interfaces.
+pub mod interface {
+    use core::time::Duration;
+
+    /// The counter and comparator the timeouts run on.
+    pub trait Timer {
+        /// The uptime since the simulator started.
+        fn uptime(&self) -> Duration;
+
+        /// Spin for a given duration.
+        fn spin_for(&self, duration: Duration);
+
+        /// The smallest step of the uptime.
+        fn resolution(&self) -> Duration;
+
+        /// Fire the timeout once the uptime reached `due_time`. Replaces the previous due time.
+        fn set_timeout_irq(&self, due_time: Duration);
+
+        /// Don't fire the timeout until it is armed again.
+        fn clear_timeout_irq(&self);
+
+        /// Have core `core` arm its comparator. The simulator has a single core.
+        fn wake(&self, core: usize) -> Result<(), &'static str>;
+    }
+}
+
+/// The callback type of timeouts.
+pub type TimeoutCallback = Box<dyn Fn() + Send>;
+
+/// Provides time management functions.
+pub struct TimeManager {
+    timer: ManualTimer,
+    queue: IRQSafeNullLock<Vec<Timeout>>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static TIME_MANAGER: TimeManager = TimeManager {
+    timer: ManualTimer::new(),
+    queue: IRQSafeNullLock::new(Vec::new()),
+};
+
+static NEXT_TIMEOUT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Set while the clock follows the wall clock.
+static RUNNING: AtomicBool = AtomicBool::new(true);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Advance the clock with the wall clock while it runs, and run the due timeouts.
+fn ticker() {
+    let mut last = Instant::now();
+
+    loop {
+        thread::sleep(TICK);
+
+        let now = Instant::now();
+        if RUNNING.load(Ordering::Relaxed) {
+            TIME_MANAGER.timer.advance(now - last);
+        }
+        last = now;
+
+        TIME_MANAGER.run_due();
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl TimeManager {
+    /// The uptime since the simulator started.
+    pub fn uptime(&self) -> Duration {
+        self.timer.uptime()
+    }
+
+    /// Set a one-shot timeout.
+    pub fn set_timeout_once(&self, delay: Duration, callback: TimeoutCallback) {
+        let timeout = Timeout {
+            id: NEXT_TIMEOUT_ID.fetch_add(1, Ordering::Relaxed),
+            due_time: self.uptime() + delay,
+            callback,
+        };
+
+        self.queue.lock(|queue| {
+            let pos = queue
+                .iter()
+                .position(|t| (t.due_time, t.id) > (timeout.due_time, timeout.id))
+                .unwrap_or(queue.len());
+            queue.insert(pos, timeout);
+
+            self.timer.set_timeout_irq(queue[0].due_time);
+        });
+    }
+
+    /// Run the callbacks of the due timeouts, in order of their due time. Returns the number of
+    /// callbacks run.
+    pub fn run_due(&self) -> usize {
+        let mut ran = 0;
+
+        loop {
+            let now = self.uptime();
+            let due = self.queue.lock(|queue| match queue.first() {
+                Some(t) if t.due_time <= now => Some(queue.remove(0)),
+                _ => None,
+            });
+
+            // The callback runs without the lock held, as it may set another timeout.
+            match due {
+                Some(timeout) => (timeout.callback)(),
+                None => return ran,
+            }
+            ran += 1;
+        }
+    }
+
+    /// Advance the clock by `duration` and run the timeouts that became due.
+    pub fn advance(&self, duration: Duration) {
+        self.timer.advance(duration);
+        self.run_due();
+    }
+
+    /// Return the number of pending timeouts.
+    pub fn pending(&self) -> usize {
+        self.queue.lock(|queue| queue.len())
+    }
+}
+
+/// Return a reference to the global TimeManager.
+pub fn time_manager() -> &'static TimeManager {
+    &TIME_MANAGER
+}
+
+/// Start the ticker.
+pub fn init() {
+    thread::spawn(ticker);
+}
+
+/// Let the clock follow the wall clock, or stop it.
+pub fn set_running(running: bool) {
+    RUNNING.store(running, Ordering::Relaxed);
+}
+
+/// Return if the clock follows the wall clock.
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::Relaxed)
+}
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2025-2026 The KHROS Contributors
+
+//! Trace records, kept in memory.
+
+use crate::{
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+    time,
+};
+use std::time::Duration;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Records kept.
+const CAPACITY: usize = 64;
+
+struct Record {
+    time: Duration,
+    source: &'static str,
+    what: &'static str,
+    arg: u64,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static RECORDS: IRQSafeNullLock<Vec<Record>> = IRQSafeNullLock::new(Vec::new());
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Record an event. The oldest record is dropped once the buffer is full.
+pub fn record(source: &'static str, what: &'static str, arg: u64) {
+    let time = time::time_manager().uptime();
+
+    RECORDS.lock(|records| {
+        if records.len() == CAPACITY {
+            records.remove(0);
+        }
+        records.push(Record {
+            time,
+            source,
+            what,
+            arg,
+        });
+    });
+}
+
+/// Print the records, oldest first.
+pub fn print() {
+    RECORDS.lock(|records| {
+        for r in records.iter() {
+            info!(
+                "      {} {}.{} {}",
+                time::Seconds(r.time),
+                r.source,
+                r.what,
+                r.arg
+            );
+        }
+    });
+}